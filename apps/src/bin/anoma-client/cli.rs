@@ -21,12 +21,27 @@ pub async fn main() -> Result<()> {
                 Sub::TxUpdateVp(TxUpdateVp(args)) => {
                     tx::submit_update_vp(ctx, args).await;
                 }
+                Sub::TxUpdateKey(TxUpdateKey(args)) => {
+                    tx::submit_update_key(ctx, args).await;
+                }
+                Sub::TxUpdateSpendingPolicy(TxUpdateSpendingPolicy(args)) => {
+                    tx::submit_update_spending_policy(ctx, args).await;
+                }
                 Sub::TxInitAccount(TxInitAccount(args)) => {
                     tx::submit_init_account(ctx, args).await;
                 }
+                Sub::TxInitMultisigAccount(TxInitMultisigAccount(args)) => {
+                    tx::submit_init_multisig_account(ctx, args).await;
+                }
                 Sub::TxInitValidator(TxInitValidator(args)) => {
                     tx::submit_init_validator(ctx, args).await;
                 }
+                Sub::Multisig(Multisig::Sign(MultisigSign(args))) => {
+                    tx::sign_multisig(ctx, args);
+                }
+                Sub::Multisig(Multisig::Submit(MultisigSubmit(args))) => {
+                    tx::submit_multisig(ctx, args).await;
+                }
                 Sub::Bond(Bond(args)) => {
                     tx::submit_bond(ctx, args).await;
                 }
@@ -36,6 +51,41 @@ pub async fn main() -> Result<()> {
                 Sub::Withdraw(Withdraw(args)) => {
                     tx::submit_withdraw(ctx, args).await;
                 }
+                Sub::Redelegate(Redelegate(args)) => {
+                    tx::submit_redelegate(ctx, args).await;
+                }
+                Sub::TxChangeCommission(TxChangeCommission(args)) => {
+                    tx::submit_change_commission(ctx, args).await;
+                }
+                Sub::TxChangeConsensusKey(TxChangeConsensusKey(args)) => {
+                    tx::submit_change_consensus_key(ctx, args).await;
+                }
+                Sub::TxUpdateValidatorMetadata(TxUpdateValidatorMetadata(
+                    args,
+                )) => {
+                    tx::submit_update_validator_metadata(ctx, args).await;
+                }
+                Sub::ClaimRewards(ClaimRewards(args)) => {
+                    tx::submit_claim_rewards(ctx, args).await;
+                }
+                Sub::Unjail(Unjail(args)) => {
+                    tx::submit_unjail(ctx, args).await;
+                }
+                Sub::TxProposeParameters(TxProposeParameters(args)) => {
+                    tx::submit_propose_parameters(ctx, args).await;
+                }
+                Sub::TxVoteParameters(TxVoteParameters(args)) => {
+                    tx::submit_vote_parameters(ctx, args).await;
+                }
+                Sub::TxApplyParameters(TxApplyParameters(args)) => {
+                    tx::submit_apply_parameters(ctx, args).await;
+                }
+                Sub::TxInitProposal(TxInitProposal(args)) => {
+                    tx::submit_init_proposal(ctx, args).await;
+                }
+                Sub::TxVoteProposal(TxVoteProposal(args)) => {
+                    tx::submit_vote_proposal(ctx, args).await;
+                }
                 // Ledger queries
                 Sub::QueryEpoch(QueryEpoch(args)) => {
                     rpc::query_epoch(ctx, args).await;
@@ -49,9 +99,33 @@ pub async fn main() -> Result<()> {
                 Sub::QueryVotingPower(QueryVotingPower(args)) => {
                     rpc::query_voting_power(ctx, args).await;
                 }
+                Sub::QueryValidators(QueryValidators(args)) => {
+                    rpc::query_validators(ctx, args).await;
+                }
                 Sub::QuerySlashes(QuerySlashes(args)) => {
                     rpc::query_slashes(ctx, args).await;
                 }
+                Sub::QueryRewards(QueryRewards(args)) => {
+                    rpc::query_rewards(ctx, args).await;
+                }
+                Sub::QueryStaking(QueryStaking(args)) => {
+                    rpc::query_staking(ctx, args).await;
+                }
+                Sub::QuerySpendingPolicy(QuerySpendingPolicy(args)) => {
+                    rpc::query_spending_policy(ctx, args).await;
+                }
+                Sub::QueryParametersProposal(QueryParametersProposal(args)) => {
+                    rpc::query_parameters_proposal(ctx, args).await;
+                }
+                Sub::QueryProposal(QueryProposal(args)) => {
+                    rpc::query_proposal(ctx, args).await;
+                }
+                Sub::QueryTreasury(QueryTreasury(args)) => {
+                    rpc::query_treasury(ctx, args).await;
+                }
+                Sub::QueryInflation(QueryInflation(args)) => {
+                    rpc::query_inflation(ctx, args).await;
+                }
                 // Gossip cmds
                 Sub::Intent(Intent(args)) => {
                     gossip::gossip_intent(ctx, args).await;
@@ -59,20 +133,31 @@ pub async fn main() -> Result<()> {
                 Sub::SubscribeTopic(SubscribeTopic(args)) => {
                     gossip::subscribe_topic(ctx, args).await;
                 }
+                // Utils
+                Sub::WriteConsensusKey(WriteConsensusKey(args)) => {
+                    utils::write_consensus_key(ctx, args).await;
+                }
             }
         }
-        cli::AnomaClient::WithoutContext(cmd, global_args) => match cmd {
-            // Utils cmds
-            Utils::JoinNetwork(JoinNetwork(args)) => {
-                utils::join_network(global_args, args).await
-            }
-            Utils::InitNetwork(InitNetwork(args)) => {
-                utils::init_network(global_args, args)
-            }
-            Utils::InitGenesisValidator(InitGenesisValidator(args)) => {
-                utils::init_genesis_validator(global_args, args)
+        cli::AnomaClient::WithoutContext(cmd, global_args) => {
+            use AnomaClientWithoutContext as Sub;
+            match cmd {
+                // Utils cmds
+                Sub::Utils(Utils::JoinNetwork(JoinNetwork(args))) => {
+                    utils::join_network(global_args, args).await
+                }
+                Sub::Utils(Utils::InitNetwork(InitNetwork(args))) => {
+                    utils::init_network(global_args, args)
+                }
+                Sub::Utils(Utils::InitGenesisValidator(
+                    InitGenesisValidator(args),
+                )) => utils::init_genesis_validator(global_args, args),
+                // WASM cmds
+                Sub::Wasm(Wasm::Check(WasmCheck(args))) => {
+                    utils::check_wasm(args)
+                }
             }
-        },
+        }
     }
     Ok(())
 }
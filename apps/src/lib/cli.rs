@@ -127,8 +127,9 @@ pub mod cmds {
         /// config. It will generate a new wallet and config, if they
         /// don't exist.
         WithContext(AnomaClientWithContext),
-        /// Utils don't have [`super::Context`], only the global arguments.
-        WithoutContext(Utils),
+        /// Utils and WASM commands don't have [`super::Context`], only the
+        /// global arguments.
+        WithoutContext(AnomaClientWithoutContext),
     }
 
     impl Cmd for AnomaClient {
@@ -138,23 +139,50 @@ pub mod cmds {
                 .subcommand(TxCustom::def().display_order(1))
                 .subcommand(TxTransfer::def().display_order(1))
                 .subcommand(TxUpdateVp::def().display_order(1))
+                .subcommand(TxUpdateKey::def().display_order(1))
+                .subcommand(TxUpdateSpendingPolicy::def().display_order(1))
                 .subcommand(TxInitAccount::def().display_order(1))
+                .subcommand(TxInitMultisigAccount::def().display_order(1))
                 .subcommand(TxInitValidator::def().display_order(1))
+                .subcommand(Multisig::def().display_order(1))
                 // PoS transactions
                 .subcommand(Bond::def().display_order(2))
                 .subcommand(Unbond::def().display_order(2))
                 .subcommand(Withdraw::def().display_order(2))
+                .subcommand(Redelegate::def().display_order(2))
+                .subcommand(TxChangeCommission::def().display_order(2))
+                .subcommand(TxChangeConsensusKey::def().display_order(2))
+                .subcommand(TxUpdateValidatorMetadata::def().display_order(2))
+                .subcommand(ClaimRewards::def().display_order(2))
+                .subcommand(Unjail::def().display_order(2))
+                // Parameters proposals
+                .subcommand(TxProposeParameters::def().display_order(3))
+                .subcommand(TxVoteParameters::def().display_order(3))
+                .subcommand(TxApplyParameters::def().display_order(3))
+                // Governance
+                .subcommand(TxInitProposal::def().display_order(3))
+                .subcommand(TxVoteProposal::def().display_order(3))
                 // Queries
-                .subcommand(QueryEpoch::def().display_order(3))
-                .subcommand(QueryBalance::def().display_order(3))
-                .subcommand(QueryBonds::def().display_order(3))
-                .subcommand(QueryVotingPower::def().display_order(3))
-                .subcommand(QuerySlashes::def().display_order(3))
+                .subcommand(QueryEpoch::def().display_order(4))
+                .subcommand(QueryBalance::def().display_order(4))
+                .subcommand(QueryBonds::def().display_order(4))
+                .subcommand(QueryVotingPower::def().display_order(4))
+                .subcommand(QueryValidators::def().display_order(4))
+                .subcommand(QuerySlashes::def().display_order(4))
+                .subcommand(QueryRewards::def().display_order(4))
+                .subcommand(QueryStaking::def().display_order(4))
+                .subcommand(QuerySpendingPolicy::def().display_order(4))
+                .subcommand(QueryParametersProposal::def().display_order(4))
+                .subcommand(QueryProposal::def().display_order(4))
+                .subcommand(QueryTreasury::def().display_order(4))
+                .subcommand(QueryInflation::def().display_order(4))
                 // Intents
-                .subcommand(Intent::def().display_order(4))
-                .subcommand(SubscribeTopic::def().display_order(4))
+                .subcommand(Intent::def().display_order(5))
+                .subcommand(SubscribeTopic::def().display_order(5))
                 // Utils
-                .subcommand(Utils::def().display_order(5))
+                .subcommand(WriteConsensusKey::def().display_order(6))
+                .subcommand(Utils::def().display_order(6))
+                .subcommand(Wasm::def().display_order(6))
         }
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
@@ -162,37 +190,105 @@ pub mod cmds {
             let tx_custom = Self::parse_with_ctx(matches, TxCustom);
             let tx_transfer = Self::parse_with_ctx(matches, TxTransfer);
             let tx_update_vp = Self::parse_with_ctx(matches, TxUpdateVp);
+            let tx_update_key = Self::parse_with_ctx(matches, TxUpdateKey);
+            let tx_update_spending_policy =
+                Self::parse_with_ctx(matches, TxUpdateSpendingPolicy);
             let tx_init_account = Self::parse_with_ctx(matches, TxInitAccount);
+            let tx_init_multisig_account =
+                Self::parse_with_ctx(matches, TxInitMultisigAccount);
             let tx_init_validator =
                 Self::parse_with_ctx(matches, TxInitValidator);
+            let multisig = Self::parse_with_ctx(matches, Multisig);
             let bond = Self::parse_with_ctx(matches, Bond);
             let unbond = Self::parse_with_ctx(matches, Unbond);
             let withdraw = Self::parse_with_ctx(matches, Withdraw);
+            let redelegate = Self::parse_with_ctx(matches, Redelegate);
+            let tx_change_commission =
+                Self::parse_with_ctx(matches, TxChangeCommission);
+            let tx_change_consensus_key =
+                Self::parse_with_ctx(matches, TxChangeConsensusKey);
+            let tx_update_validator_metadata =
+                Self::parse_with_ctx(matches, TxUpdateValidatorMetadata);
+            let claim_rewards = Self::parse_with_ctx(matches, ClaimRewards);
+            let unjail = Self::parse_with_ctx(matches, Unjail);
+            let tx_propose_parameters =
+                Self::parse_with_ctx(matches, TxProposeParameters);
+            let tx_vote_parameters =
+                Self::parse_with_ctx(matches, TxVoteParameters);
+            let tx_apply_parameters =
+                Self::parse_with_ctx(matches, TxApplyParameters);
+            let tx_init_proposal =
+                Self::parse_with_ctx(matches, TxInitProposal);
+            let tx_vote_proposal =
+                Self::parse_with_ctx(matches, TxVoteProposal);
             let query_epoch = Self::parse_with_ctx(matches, QueryEpoch);
             let query_balance = Self::parse_with_ctx(matches, QueryBalance);
             let query_bonds = Self::parse_with_ctx(matches, QueryBonds);
             let query_voting_power =
                 Self::parse_with_ctx(matches, QueryVotingPower);
+            let query_validators =
+                Self::parse_with_ctx(matches, QueryValidators);
             let query_slashes = Self::parse_with_ctx(matches, QuerySlashes);
+            let query_rewards = Self::parse_with_ctx(matches, QueryRewards);
+            let query_staking = Self::parse_with_ctx(matches, QueryStaking);
+            let query_spending_policy =
+                Self::parse_with_ctx(matches, QuerySpendingPolicy);
+            let query_parameters_proposal =
+                Self::parse_with_ctx(matches, QueryParametersProposal);
+            let query_proposal = Self::parse_with_ctx(matches, QueryProposal);
+            let query_treasury = Self::parse_with_ctx(matches, QueryTreasury);
+            let query_inflation = Self::parse_with_ctx(matches, QueryInflation);
             let intent = Self::parse_with_ctx(matches, Intent);
             let subscribe_topic = Self::parse_with_ctx(matches, SubscribeTopic);
-            let utils = SubCmd::parse(matches).map(Self::WithoutContext);
+            let write_consensus_key =
+                Self::parse_with_ctx(matches, WriteConsensusKey);
+            let utils = SubCmd::parse(matches).map(|sub| {
+                Self::WithoutContext(AnomaClientWithoutContext::Utils(sub))
+            });
+            let wasm = SubCmd::parse(matches).map(|sub| {
+                Self::WithoutContext(AnomaClientWithoutContext::Wasm(sub))
+            });
             tx_custom
                 .or(tx_transfer)
                 .or(tx_update_vp)
+                .or(tx_update_key)
+                .or(tx_update_spending_policy)
                 .or(tx_init_account)
+                .or(tx_init_multisig_account)
                 .or(tx_init_validator)
+                .or(multisig)
                 .or(bond)
                 .or(unbond)
                 .or(withdraw)
+                .or(redelegate)
+                .or(tx_change_commission)
+                .or(tx_change_consensus_key)
+                .or(tx_update_validator_metadata)
+                .or(claim_rewards)
+                .or(unjail)
+                .or(tx_propose_parameters)
+                .or(tx_vote_parameters)
+                .or(tx_apply_parameters)
+                .or(tx_init_proposal)
+                .or(tx_vote_proposal)
                 .or(query_epoch)
                 .or(query_balance)
                 .or(query_bonds)
                 .or(query_voting_power)
+                .or(query_validators)
                 .or(query_slashes)
+                .or(query_rewards)
+                .or(query_staking)
+                .or(query_spending_policy)
+                .or(query_parameters_proposal)
+                .or(query_proposal)
+                .or(query_treasury)
+                .or(query_inflation)
                 .or(intent)
                 .or(subscribe_topic)
+                .or(write_consensus_key)
                 .or(utils)
+                .or(wasm)
         }
     }
 
@@ -231,19 +327,50 @@ pub mod cmds {
         TxCustom(TxCustom),
         TxTransfer(TxTransfer),
         TxUpdateVp(TxUpdateVp),
+        TxUpdateKey(TxUpdateKey),
+        TxUpdateSpendingPolicy(TxUpdateSpendingPolicy),
         TxInitAccount(TxInitAccount),
+        TxInitMultisigAccount(TxInitMultisigAccount),
         TxInitValidator(TxInitValidator),
+        Multisig(Multisig),
         Bond(Bond),
         Unbond(Unbond),
         Withdraw(Withdraw),
+        Redelegate(Redelegate),
+        TxChangeCommission(TxChangeCommission),
+        TxChangeConsensusKey(TxChangeConsensusKey),
+        TxUpdateValidatorMetadata(TxUpdateValidatorMetadata),
+        ClaimRewards(ClaimRewards),
+        Unjail(Unjail),
+        TxProposeParameters(TxProposeParameters),
+        TxVoteParameters(TxVoteParameters),
+        TxApplyParameters(TxApplyParameters),
+        TxInitProposal(TxInitProposal),
+        TxVoteProposal(TxVoteProposal),
         QueryEpoch(QueryEpoch),
         QueryBalance(QueryBalance),
         QueryBonds(QueryBonds),
         QueryVotingPower(QueryVotingPower),
+        QueryValidators(QueryValidators),
         QuerySlashes(QuerySlashes),
+        QueryRewards(QueryRewards),
+        QueryStaking(QueryStaking),
+        QuerySpendingPolicy(QuerySpendingPolicy),
+        QueryParametersProposal(QueryParametersProposal),
+        QueryProposal(QueryProposal),
+        QueryTreasury(QueryTreasury),
+        QueryInflation(QueryInflation),
         // Gossip cmds
         Intent(Intent),
         SubscribeTopic(SubscribeTopic),
+        // Utils
+        WriteConsensusKey(WriteConsensusKey),
+    }
+
+    #[derive(Clone, Debug)]
+    pub enum AnomaClientWithoutContext {
+        Utils(Utils),
+        Wasm(Wasm),
     }
 
     #[derive(Clone, Debug)]
@@ -731,6 +858,52 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateKey(pub args::TxUpdateKey);
+
+    impl SubCmd for TxUpdateKey {
+        const CMD: &'static str = "update-key";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| TxUpdateKey(args::TxUpdateKey::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to update account's public \
+                     key.",
+                )
+                .add_args::<args::TxUpdateKey>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateSpendingPolicy(pub args::TxUpdateSpendingPolicy);
+
+    impl SubCmd for TxUpdateSpendingPolicy {
+        const CMD: &'static str = "update-spending-policy";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxUpdateSpendingPolicy(args::TxUpdateSpendingPolicy::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to set or remove account's \
+                     spending policy.",
+                )
+                .add_args::<args::TxUpdateSpendingPolicy>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxInitAccount(pub args::TxInitAccount);
 
@@ -753,6 +926,108 @@ pub mod cmds {
         }
     }
 
+    #[derive(Clone, Debug)]
+    pub struct TxInitMultisigAccount(pub args::TxInitMultisigAccount);
+
+    impl SubCmd for TxInitMultisigAccount {
+        const CMD: &'static str = "init-multisig-account";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxInitMultisigAccount(args::TxInitMultisigAccount::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Send a signed transaction to create a new established \
+                     account controlled by a threshold of signatures from a \
+                     set of keys.",
+                )
+                .add_args::<args::TxInitMultisigAccount>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub enum Multisig {
+        Sign(MultisigSign),
+        Submit(MultisigSubmit),
+    }
+
+    impl SubCmd for Multisig {
+        const CMD: &'static str = "multisig";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).and_then(|matches| {
+                let sign = SubCmd::parse(matches).map(Self::Sign);
+                let submit = SubCmd::parse(matches).map(Self::Submit);
+                sign.or(submit)
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Collect signatures of a transaction for a \
+                     multi-signature account offline and submit it. An \
+                     unsigned transaction can be obtained from any \
+                     transaction command with the `--unsigned-tx-path` \
+                     argument.",
+                )
+                .subcommand(MultisigSign::def())
+                .subcommand(MultisigSubmit::def())
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct MultisigSign(pub args::MultisigSign);
+
+    impl SubCmd for MultisigSign {
+        const CMD: &'static str = "sign";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::MultisigSign::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Sign an unsigned transaction with a key from your wallet \
+                     and save the signature into a file. This doesn't require \
+                     a connection to a ledger node.",
+                )
+                .add_args::<args::MultisigSign>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct MultisigSubmit(pub args::MultisigSubmit);
+
+    impl SubCmd for MultisigSubmit {
+        const CMD: &'static str = "submit";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::MultisigSubmit::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Combine the signatures of an unsigned transaction and \
+                     submit it.",
+                )
+                .add_args::<args::MultisigSubmit>()
+        }
+    }
+
     #[derive(Clone, Debug)]
     pub struct TxInitValidator(pub args::TxInitValidator);
 
@@ -833,247 +1108,706 @@ pub mod cmds {
     }
 
     #[derive(Clone, Debug)]
-    pub struct QueryEpoch(pub args::Query);
+    pub struct Redelegate(pub args::Redelegate);
 
-    impl SubCmd for QueryEpoch {
-        const CMD: &'static str = "epoch";
+    impl SubCmd for Redelegate {
+        const CMD: &'static str = "redelegate";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches
                 .subcommand_matches(Self::CMD)
-                .map(|matches| QueryEpoch(args::Query::parse(matches)))
+                .map(|matches| Redelegate(args::Redelegate::parse(matches)))
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Query the epoch of the last committed block.")
-                .add_args::<args::Query>()
+                .about(
+                    "Redelegate tokens from one validator to another without \
+                     unbonding them.",
+                )
+                .add_args::<args::Redelegate>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct QueryBalance(pub args::QueryBalance);
+    pub struct TxChangeCommission(pub args::TxChangeCommission);
 
-    impl SubCmd for QueryBalance {
-        const CMD: &'static str = "balance";
+    impl SubCmd for TxChangeCommission {
+        const CMD: &'static str = "change-commission-rate";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
-            matches
-                .subcommand_matches(Self::CMD)
-                .map(|matches| QueryBalance(args::QueryBalance::parse(matches)))
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxChangeCommission(args::TxChangeCommission::parse(matches))
+            })
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Query balance(s) of tokens.")
-                .add_args::<args::QueryBalance>()
+                .about("Change a validator's commission rate.")
+                .add_args::<args::TxChangeCommission>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct QueryBonds(pub args::QueryBonds);
+    pub struct TxChangeConsensusKey(pub args::TxChangeConsensusKey);
 
-    impl SubCmd for QueryBonds {
-        const CMD: &'static str = "bonds";
+    impl SubCmd for TxChangeConsensusKey {
+        const CMD: &'static str = "change-consensus-key";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
-            matches
-                .subcommand_matches(Self::CMD)
-                .map(|matches| QueryBonds(args::QueryBonds::parse(matches)))
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxChangeConsensusKey(args::TxChangeConsensusKey::parse(matches))
+            })
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Query PoS bond(s).")
-                .add_args::<args::QueryBonds>()
+                .about("Change a validator's consensus key.")
+                .add_args::<args::TxChangeConsensusKey>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct QueryVotingPower(pub args::QueryVotingPower);
+    pub struct TxUpdateValidatorMetadata(pub args::TxUpdateValidatorMetadata);
 
-    impl SubCmd for QueryVotingPower {
-        const CMD: &'static str = "voting-power";
+    impl SubCmd for TxUpdateValidatorMetadata {
+        const CMD: &'static str = "update-validator-metadata";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches.subcommand_matches(Self::CMD).map(|matches| {
-                QueryVotingPower(args::QueryVotingPower::parse(matches))
+                TxUpdateValidatorMetadata(
+                    args::TxUpdateValidatorMetadata::parse(matches),
+                )
             })
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Query PoS voting power.")
-                .add_args::<args::QueryVotingPower>()
+                .about("Update a validator's metadata.")
+                .add_args::<args::TxUpdateValidatorMetadata>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct QuerySlashes(pub args::QuerySlashes);
+    pub struct ClaimRewards(pub args::ClaimRewards);
 
-    impl SubCmd for QuerySlashes {
-        const CMD: &'static str = "slashes";
+    impl SubCmd for ClaimRewards {
+        const CMD: &'static str = "claim-rewards";
 
-        fn parse(matches: &ArgMatches) -> Option<Self>
-        where
-            Self: Sized,
-        {
+        fn parse(matches: &ArgMatches) -> Option<Self> {
             matches
                 .subcommand_matches(Self::CMD)
-                .map(|matches| QuerySlashes(args::QuerySlashes::parse(matches)))
+                .map(|matches| ClaimRewards(args::ClaimRewards::parse(matches)))
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Query PoS applied slashes.")
-                .add_args::<args::QuerySlashes>()
+                .about("Claim the rewards accrued by a delegation.")
+                .add_args::<args::ClaimRewards>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct Intent(pub args::Intent);
+    pub struct Unjail(pub args::Unjail);
 
-    impl SubCmd for Intent {
-        const CMD: &'static str = "intent";
+    impl SubCmd for Unjail {
+        const CMD: &'static str = "unjail";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches
                 .subcommand_matches(Self::CMD)
-                .map(|matches| Intent(args::Intent::parse(matches)))
+                .map(|matches| Unjail(args::Unjail::parse(matches)))
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Send an intent.")
-                .add_args::<args::Intent>()
+                .about("Unjail a jailed validator.")
+                .add_args::<args::Unjail>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct SubscribeTopic(pub args::SubscribeTopic);
+    pub struct TxProposeParameters(pub args::ProposeParameters);
 
-    impl SubCmd for SubscribeTopic {
-        const CMD: &'static str = "subscribe-topic";
+    impl SubCmd for TxProposeParameters {
+        const CMD: &'static str = "propose-parameters";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches.subcommand_matches(Self::CMD).map(|matches| {
-                SubscribeTopic(args::SubscribeTopic::parse(matches))
+                TxProposeParameters(args::ProposeParameters::parse(matches))
             })
         }
 
         fn def() -> App {
             App::new(Self::CMD)
                 .about(
-                    "Subscribe intent gossip node with a matchmaker to a \
-                     topic.",
+                    "Submit a proposal to change the protocol parameters. \
+                     The parameters that are not specified are kept at their \
+                     current values.",
                 )
-                .add_args::<args::SubscribeTopic>()
+                .add_args::<args::ProposeParameters>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub enum Utils {
-        JoinNetwork(JoinNetwork),
-        InitNetwork(InitNetwork),
-        InitGenesisValidator(InitGenesisValidator),
+    pub struct TxVoteParameters(pub args::VoteParameters);
+
+    impl SubCmd for TxVoteParameters {
+        const CMD: &'static str = "vote-parameters";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxVoteParameters(args::VoteParameters::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Vote on a protocol parameters proposal.")
+                .add_args::<args::VoteParameters>()
+        }
     }
 
-    impl SubCmd for Utils {
-        const CMD: &'static str = "utils";
+    #[derive(Clone, Debug)]
+    pub struct TxApplyParameters(pub args::ApplyParameters);
+
+    impl SubCmd for TxApplyParameters {
+        const CMD: &'static str = "apply-parameters";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
-            matches.subcommand_matches(Self::CMD).and_then(|matches| {
-                let join_network =
-                    SubCmd::parse(matches).map(Self::JoinNetwork);
-                let init_network =
-                    SubCmd::parse(matches).map(Self::InitNetwork);
-                let init_genesis =
-                    SubCmd::parse(matches).map(Self::InitGenesisValidator);
-                join_network.or(init_network).or(init_genesis)
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxApplyParameters(args::ApplyParameters::parse(matches))
             })
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Utilities.")
-                .subcommand(JoinNetwork::def())
-                .subcommand(InitNetwork::def())
-                .subcommand(InitGenesisValidator::def())
-                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .about(
+                    "Apply a passed protocol parameters proposal in or after \
+                     its activation epoch.",
+                )
+                .add_args::<args::ApplyParameters>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct JoinNetwork(pub args::JoinNetwork);
+    pub struct TxInitProposal(pub args::InitProposal);
 
-    impl SubCmd for JoinNetwork {
-        const CMD: &'static str = "join-network";
+    impl SubCmd for TxInitProposal {
+        const CMD: &'static str = "init-proposal";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
-            matches
-                .subcommand_matches(Self::CMD)
-                .map(|matches| Self(args::JoinNetwork::parse(matches)))
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxInitProposal(args::InitProposal::parse(matches))
+            })
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Configure Anoma to join an existing network.")
-                .add_args::<args::JoinNetwork>()
+                .about(
+                    "Submit a governance proposal with a deposit. The \
+                     proposal may carry WASM code that is executed with \
+                     governance authority if the proposal passes.",
+                )
+                .add_args::<args::InitProposal>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct InitNetwork(pub args::InitNetwork);
+    pub struct TxVoteProposal(pub args::VoteProposal);
 
-    impl SubCmd for InitNetwork {
-        const CMD: &'static str = "init-network";
+    impl SubCmd for TxVoteProposal {
+        const CMD: &'static str = "vote-proposal";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                TxVoteProposal(args::VoteProposal::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Vote on a governance proposal.")
+                .add_args::<args::VoteProposal>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryEpoch(pub args::Query);
+
+    impl SubCmd for QueryEpoch {
+        const CMD: &'static str = "epoch";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches
                 .subcommand_matches(Self::CMD)
-                .map(|matches| Self(args::InitNetwork::parse(matches)))
+                .map(|matches| QueryEpoch(args::Query::parse(matches)))
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about("Initialize a new test network.")
-                .add_args::<args::InitNetwork>()
+                .about("Query the epoch of the last committed block.")
+                .add_args::<args::Query>()
         }
     }
 
     #[derive(Clone, Debug)]
-    pub struct InitGenesisValidator(pub args::InitGenesisValidator);
+    pub struct QueryBalance(pub args::QueryBalance);
 
-    impl SubCmd for InitGenesisValidator {
-        const CMD: &'static str = "init-genesis-validator";
+    impl SubCmd for QueryBalance {
+        const CMD: &'static str = "balance";
 
         fn parse(matches: &ArgMatches) -> Option<Self> {
             matches
                 .subcommand_matches(Self::CMD)
-                .map(|matches| Self(args::InitGenesisValidator::parse(matches)))
+                .map(|matches| QueryBalance(args::QueryBalance::parse(matches)))
         }
 
         fn def() -> App {
             App::new(Self::CMD)
-                .about(
-                    "Initialize genesis validator's address, staking reward \
-                     address, consensus key, validator account key and \
-                     staking rewards key and use it in the ledger's node.",
-                )
-                .add_args::<args::InitGenesisValidator>()
+                .about("Query balance(s) of tokens.")
+                .add_args::<args::QueryBalance>()
         }
     }
-}
 
-pub mod args {
+    #[derive(Clone, Debug)]
+    pub struct QueryBonds(pub args::QueryBonds);
 
-    use std::convert::TryFrom;
-    use std::env;
-    use std::fs::File;
-    use std::net::SocketAddr;
-    use std::path::PathBuf;
-    use std::str::FromStr;
+    impl SubCmd for QueryBonds {
+        const CMD: &'static str = "bonds";
 
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryBonds(args::QueryBonds::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query PoS bond(s).")
+                .add_args::<args::QueryBonds>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryVotingPower(pub args::QueryVotingPower);
+
+    impl SubCmd for QueryVotingPower {
+        const CMD: &'static str = "voting-power";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryVotingPower(args::QueryVotingPower::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query PoS voting power.")
+                .add_args::<args::QueryVotingPower>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryValidators(pub args::QueryValidators);
+
+    impl SubCmd for QueryValidators {
+        const CMD: &'static str = "validators";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryValidators(args::QueryValidators::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the active and inactive PoS validator sets with \
+                     the validators' voting powers, commission rates and \
+                     metadata.",
+                )
+                .add_args::<args::QueryValidators>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QuerySlashes(pub args::QuerySlashes);
+
+    impl SubCmd for QuerySlashes {
+        const CMD: &'static str = "slashes";
+
+        fn parse(matches: &ArgMatches) -> Option<Self>
+        where
+            Self: Sized,
+        {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QuerySlashes(args::QuerySlashes::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query PoS applied slashes.")
+                .add_args::<args::QuerySlashes>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryRewards(pub args::QueryRewards);
+
+    impl SubCmd for QueryRewards {
+        const CMD: &'static str = "rewards";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryRewards(args::QueryRewards::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query PoS block rewards.")
+                .add_args::<args::QueryRewards>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryStaking(pub args::QueryStaking);
+
+    impl SubCmd for QueryStaking {
+        const CMD: &'static str = "staking";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryStaking(args::QueryStaking::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query an overview of an account's PoS bonds, unbonds, \
+                     slashes and rewards.",
+                )
+                .add_args::<args::QueryStaking>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QuerySpendingPolicy(pub args::QuerySpendingPolicy);
+
+    impl SubCmd for QuerySpendingPolicy {
+        const CMD: &'static str = "spending-policy";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QuerySpendingPolicy(args::QuerySpendingPolicy::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query account's spending policy.")
+                .add_args::<args::QuerySpendingPolicy>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryParametersProposal(pub args::QueryParametersProposal);
+
+    impl SubCmd for QueryParametersProposal {
+        const CMD: &'static str = "parameters-proposal";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryParametersProposal(args::QueryParametersProposal::parse(
+                    matches,
+                ))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query a protocol parameters proposal and its votes.")
+                .add_args::<args::QueryParametersProposal>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryProposal(pub args::QueryProposal);
+
+    impl SubCmd for QueryProposal {
+        const CMD: &'static str = "proposal";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                QueryProposal(args::QueryProposal::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query a governance proposal and its votes, or all the \
+                     proposals if no ID is given.",
+                )
+                .add_args::<args::QueryProposal>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryTreasury(pub args::Query);
+
+    impl SubCmd for QueryTreasury {
+        const CMD: &'static str = "treasury";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryTreasury(args::Query::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Query the treasury's parameters and balances.")
+                .add_args::<args::Query>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct QueryInflation(pub args::Query);
+
+    impl SubCmd for QueryInflation {
+        const CMD: &'static str = "inflation";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| QueryInflation(args::Query::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Query the inflation parameters, the current inflation \
+                     rate and the native token supply.",
+                )
+                .add_args::<args::Query>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct Intent(pub args::Intent);
+
+    impl SubCmd for Intent {
+        const CMD: &'static str = "intent";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Intent(args::Intent::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Send an intent.")
+                .add_args::<args::Intent>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct SubscribeTopic(pub args::SubscribeTopic);
+
+    impl SubCmd for SubscribeTopic {
+        const CMD: &'static str = "subscribe-topic";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                SubscribeTopic(args::SubscribeTopic::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Subscribe intent gossip node with a matchmaker to a \
+                     topic.",
+                )
+                .add_args::<args::SubscribeTopic>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct WriteConsensusKey(pub args::WriteConsensusKey);
+
+    impl SubCmd for WriteConsensusKey {
+        const CMD: &'static str = "write-consensus-key";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).map(|matches| {
+                WriteConsensusKey(args::WriteConsensusKey::parse(matches))
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Write a validator's consensus key into the Tendermint \
+                     private validator key file of the local ledger node.",
+                )
+                .add_args::<args::WriteConsensusKey>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub enum Utils {
+        JoinNetwork(JoinNetwork),
+        InitNetwork(InitNetwork),
+        InitGenesisValidator(InitGenesisValidator),
+    }
+
+    impl SubCmd for Utils {
+        const CMD: &'static str = "utils";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches.subcommand_matches(Self::CMD).and_then(|matches| {
+                let join_network =
+                    SubCmd::parse(matches).map(Self::JoinNetwork);
+                let init_network =
+                    SubCmd::parse(matches).map(Self::InitNetwork);
+                let init_genesis =
+                    SubCmd::parse(matches).map(Self::InitGenesisValidator);
+                join_network.or(init_network).or(init_genesis)
+            })
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Utilities.")
+                .subcommand(JoinNetwork::def())
+                .subcommand(InitNetwork::def())
+                .subcommand(InitGenesisValidator::def())
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct JoinNetwork(pub args::JoinNetwork);
+
+    impl SubCmd for JoinNetwork {
+        const CMD: &'static str = "join-network";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::JoinNetwork::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Configure Anoma to join an existing network.")
+                .add_args::<args::JoinNetwork>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct InitNetwork(pub args::InitNetwork);
+
+    impl SubCmd for InitNetwork {
+        const CMD: &'static str = "init-network";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::InitNetwork::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("Initialize a new test network.")
+                .add_args::<args::InitNetwork>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct InitGenesisValidator(pub args::InitGenesisValidator);
+
+    impl SubCmd for InitGenesisValidator {
+        const CMD: &'static str = "init-genesis-validator";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::InitGenesisValidator::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Initialize genesis validator's address, staking reward \
+                     address, consensus key, validator account key and \
+                     staking rewards key and use it in the ledger's node.",
+                )
+                .add_args::<args::InitGenesisValidator>()
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub enum Wasm {
+        Check(WasmCheck),
+    }
+
+    impl SubCmd for Wasm {
+        const CMD: &'static str = "wasm";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .and_then(|matches| SubCmd::parse(matches).map(Self::Check))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about("WASM code utilities.")
+                .subcommand(WasmCheck::def())
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct WasmCheck(pub args::WasmCheck);
+
+    impl SubCmd for WasmCheck {
+        const CMD: &'static str = "check";
+
+        fn parse(matches: &ArgMatches) -> Option<Self> {
+            matches
+                .subcommand_matches(Self::CMD)
+                .map(|matches| Self(args::WasmCheck::parse(matches)))
+        }
+
+        fn def() -> App {
+            App::new(Self::CMD)
+                .about(
+                    "Check that a transaction or a validity predicate WASM \
+                     code would be accepted by the ledger.",
+                )
+                .add_args::<args::WasmCheck>()
+        }
+    }
+}
+
+pub mod args {
+
+    use std::convert::TryFrom;
+    use std::env;
+    use std::fs::File;
+    use std::net::SocketAddr;
+    use std::path::PathBuf;
+    use std::str::FromStr;
+
+    use anoma::ledger::parameters::ProposalVote;
     use anoma::types::address::Address;
     use anoma::types::chain::{ChainId, ChainIdPrefix};
     use anoma::types::intent::{DecimalWrapper, Exchange};
@@ -1083,478 +1817,1367 @@ pub mod args {
     use libp2p::Multiaddr;
     use serde::Deserialize;
 
-    use super::context::{WalletAddress, WalletKeypair, WalletPublicKey};
-    use super::utils::*;
-    use super::ArgMatches;
-    use crate::config;
+    use super::context::{WalletAddress, WalletKeypair, WalletPublicKey};
+    use super::utils::*;
+    use super::ArgMatches;
+    use crate::config;
+
+    const ACTIVATION_EPOCH: Arg<Epoch> = arg("activation-epoch");
+    const ADDRESS: Arg<WalletAddress> = arg("address");
+    const ALIAS_OPT: ArgOpt<String> = ALIAS.opt();
+    const ALIAS: Arg<String> = arg("alias");
+    const ALLOW_DUPLICATE_IP: ArgFlag = flag("allow-duplicate-ip");
+    const ALLOWED_TARGETS: ArgMulti<WalletAddress> =
+        arg_multi("allowed-target");
+    const AMOUNT: Arg<token::Amount> = arg("amount");
+    const AUTHOR: Arg<WalletAddress> = arg("author");
+    const BASE_DIR: ArgDefault<PathBuf> = arg_default(
+        "base-dir",
+        DefaultFn(|| match env::var("ANOMA_BASE_DIR") {
+            Ok(dir) => dir.into(),
+            Err(_) => config::DEFAULT_BASE_DIR.into(),
+        }),
+    );
+    const CHAIN_ID: Arg<ChainId> = arg("chain-id");
+    const CHAIN_ID_OPT: ArgOpt<ChainId> = CHAIN_ID.opt();
+    const CHAIN_ID_PREFIX: Arg<ChainIdPrefix> = arg("chain-prefix");
+    const CODE_PATH: Arg<PathBuf> = arg("code-path");
+    const CODE_PATH_OPT: ArgOpt<PathBuf> = CODE_PATH.opt();
+    const COMMISSION_RATE: ArgDefault<u64> =
+        arg_default("commission-rate", DefaultFn(|| 0));
+    const CONSENSUS_KEY: Arg<WalletKeypair> = arg("consensus-key");
+    const CONSENSUS_PUBLIC_KEY: ArgOpt<WalletPublicKey> =
+        arg_opt("consensus-key");
+    const CONSENSUS_TIMEOUT_COMMIT: ArgDefault<tendermint::Timeout> =
+        arg_default(
+            "consensus-timeout-commit",
+            DefaultFn(|| tendermint::Timeout::from_str("1s").unwrap()),
+        );
+    const CONTACT: ArgOpt<String> = arg_opt("contact");
+    const CONTENT: Arg<String> = arg("content");
+    const DATA_PATH_OPT: ArgOpt<PathBuf> = arg_opt("data-path");
+    const DATA_PATH: Arg<PathBuf> = arg("data-path");
+    const DECRYPT: ArgFlag = flag("decrypt");
+    const DESCRIPTION: ArgOpt<String> = arg_opt("description");
+    const DEST_VALIDATOR: Arg<WalletAddress> = arg("dest-validator");
+    const DRY_RUN_TX: ArgFlag = flag("dry-run");
+    const EPOCH: ArgOpt<Epoch> = arg_opt("epoch");
+    const FILTER_PATH: ArgOpt<PathBuf> = arg_opt("filter-path");
+    const FORCE: ArgFlag = flag("force");
+    const GENESIS_PATH: Arg<PathBuf> = arg("genesis-path");
+    const GRACE_EPOCH: ArgOpt<Epoch> = arg_opt("grace-epoch");
+    const GUARDIAN_KEY: ArgOpt<WalletPublicKey> = arg_opt("guardian-key");
+    const KEY_INDEX: Arg<u8> = arg("key-index");
+    const LEDGER_ADDRESS_ABOUT: &str =
+        "Address of a ledger node as \"{scheme}://{host}:{port}\". If the \
+         scheme is not supplied, it is assumed to be TCP.";
+    const LEDGER_ADDRESS_DEFAULT: ArgDefault<tendermint::net::Address> =
+        LEDGER_ADDRESS.default(DefaultFn(|| {
+            let raw = "127.0.0.1:26657";
+            tendermint::net::Address::from_str(raw).unwrap()
+        }));
+    const LEDGER_ADDRESS_OPT: ArgOpt<tendermint::net::Address> =
+        LEDGER_ADDRESS.opt();
+    const LEDGER_ADDRESS: Arg<tendermint::net::Address> = arg("ledger-address");
+    const LIMITS: ArgMulti<TokenLimit> = arg_multi("limit");
+    const LOCALHOST: ArgFlag = flag("localhost");
+    const MATCHMAKER_PATH: ArgOpt<PathBuf> = arg_opt("matchmaker-path");
+    const MIN_DURATION: ArgOpt<u64> = arg_opt("min-duration");
+    const MIN_NUM_OF_BLOCKS: ArgOpt<u64> = arg_opt("min-num-of-blocks");
+    const MONIKER: ArgOpt<String> = arg_opt("moniker");
+    const MULTIADDR_OPT: ArgOpt<Multiaddr> = arg_opt("address");
+    const NODE_OPT: ArgOpt<String> = arg_opt("node");
+    const NODE: Arg<String> = arg("node");
+    const OUTPUT_PATH: ArgOpt<PathBuf> = arg_opt("output-path");
+    const OUTPUT_JSON: ArgFlag = flag("json");
+    const OWNER: Arg<WalletAddress> = arg("owner");
+    const OWNER_OPT: ArgOpt<WalletAddress> = OWNER.opt();
+    const PEERS: ArgMulti<String> = arg_multi("peers");
+    const PROPOSAL_CODE_PATH: ArgOpt<PathBuf> = arg_opt("proposal-code-path");
+    const PROPOSAL_ID: Arg<u64> = arg("proposal-id");
+    const PROPOSAL_ID_OPT: ArgOpt<u64> = PROPOSAL_ID.opt();
+    const PUBLIC_KEY: Arg<WalletPublicKey> = arg("public-key");
+    const PUBLIC_KEYS: ArgMulti<WalletPublicKey> = PUBLIC_KEY.multi();
+    const RATE: Arg<u64> = arg("rate");
+    const RAW_ADDRESS: Arg<Address> = arg("address");
+    const RAW_PUBLIC_KEY_OPT: ArgOpt<PublicKey> = arg_opt("public-key");
+    const REMOVE: ArgFlag = flag("remove");
+    const REWARDS_CODE_PATH: ArgOpt<PathBuf> = arg_opt("rewards-code-path");
+    const REWARDS_KEY: ArgOpt<WalletPublicKey> = arg_opt("rewards-key");
+    const RPC_SOCKET_ADDR: ArgOpt<SocketAddr> = arg_opt("rpc");
+    const SIGNATURE_PATHS: ArgMulti<PathBuf> = arg_multi("signature-path");
+    const SIGNER: ArgOpt<WalletAddress> = arg_opt("signer");
+    const SIGNING_KEY_OPT: ArgOpt<WalletKeypair> = SIGNING_KEY.opt();
+    const SIGNING_KEY: Arg<WalletKeypair> = arg("signing-key");
+    const SOURCE: Arg<WalletAddress> = arg("source");
+    const SOURCE_OPT: ArgOpt<WalletAddress> = SOURCE.opt();
+    const SRC_VALIDATOR: Arg<WalletAddress> = arg("src-validator");
+    const TARGET: Arg<WalletAddress> = arg("target");
+    const THRESHOLD: Arg<u8> = arg("threshold");
+    const TO_STDOUT: ArgFlag = flag("stdout");
+    const TOKEN_OPT: ArgOpt<WalletAddress> = TOKEN.opt();
+    const TOKEN: Arg<WalletAddress> = arg("token");
+    const TOPIC_OPT: ArgOpt<String> = arg_opt("topic");
+    const TOPIC: Arg<String> = arg("topic");
+    const TOPICS: ArgMulti<String> = TOPIC.multi();
+    const TX_CODE_PATH: ArgOpt<PathBuf> = arg_opt("tx-code-path");
+    const TX_MEMORY_MAX_PAGES: ArgOpt<u32> = arg_opt("tx-memory-max-pages");
+    const TX_PATH: Arg<PathBuf> = arg("tx-path");
+    const UNSAFE_DONT_ENCRYPT: ArgFlag = flag("unsafe-dont-encrypt");
+    const UNSAFE_SHOW_SECRET: ArgFlag = flag("unsafe-show-secret");
+    const UNSIGNED_TX_PATH: ArgOpt<PathBuf> = arg_opt("unsigned-tx-path");
+    const VALIDATOR: Arg<WalletAddress> = arg("validator");
+    const VALIDATOR_OPT: ArgOpt<WalletAddress> = VALIDATOR.opt();
+    const VALIDATOR_ACCOUNT_KEY: ArgOpt<WalletPublicKey> =
+        arg_opt("account-key");
+    const VALIDATOR_CONSENSUS_KEY: ArgOpt<WalletKeypair> =
+        arg_opt("consensus-key");
+    const VALIDATOR_CODE_PATH: ArgOpt<PathBuf> = arg_opt("validator-code-path");
+    const VALUE: ArgOpt<String> = arg_opt("value");
+    const VOTE: Arg<ProposalVote> = arg("vote");
+    const VOTER: Arg<WalletAddress> = arg("voter");
+    const VOTING_END_EPOCH: Arg<Epoch> = arg("voting-end-epoch");
+    const VOTING_START_EPOCH: Arg<Epoch> = arg("voting-start-epoch");
+    const VP: ArgFlag = flag("vp");
+    const VP_MEMORY_MAX_PAGES: ArgOpt<u32> = arg_opt("vp-memory-max-pages");
+    const WASM_DIR: ArgOpt<PathBuf> = arg_opt("wasm-dir");
+    const WASM_STACK_LIMIT: ArgOpt<u32> = arg_opt("wasm-stack-limit");
+    const WEBSITE: ArgOpt<String> = arg_opt("website");
+
+    /// Global command arguments
+    #[derive(Clone, Debug)]
+    pub struct Global {
+        pub chain_id: Option<ChainId>,
+        pub base_dir: PathBuf,
+        pub wasm_dir: Option<PathBuf>,
+    }
+
+    impl Global {
+        /// Parse global arguments
+        pub fn parse(matches: &ArgMatches) -> Self {
+            let chain_id = CHAIN_ID_OPT.parse(matches);
+            let base_dir = BASE_DIR.parse(matches);
+            let wasm_dir = WASM_DIR.parse(matches);
+            Global {
+                chain_id,
+                base_dir,
+                wasm_dir,
+            }
+        }
+
+        /// Add global args definition. Should be added to every top-level
+        /// command.
+        pub fn def(app: App) -> App {
+            app.arg(CHAIN_ID_OPT.def().about("The chain ID."))
+                .arg(BASE_DIR.def().about(
+                    "The base directory is where the nodes, client and wallet \
+                     configuration and state is stored. This value can also \
+                     be set via `ANOMA_BASE_DIR` environment variable, but \
+                     the argument takes precedence, if specified. Defaults to \
+                     `.anoma`.",
+                ))
+                .arg(WASM_DIR.def().about(
+                    "Directory with built WASM validity predicates, \
+                     transactions and matchmaker files. This value can also \
+                     be set via `ANOMA_WASM_DIR` environment variable, but \
+                     the argument takes precedence, if specified. Defaults to \
+                     `wasm` path, relative to current working directory.",
+                ))
+        }
+    }
+
+    /// Custom transaction arguments
+    #[derive(Clone, Debug)]
+    pub struct TxCustom {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Path to the tx WASM code file
+        pub code_path: PathBuf,
+        /// Path to the data file
+        pub data_path: Option<PathBuf>,
+    }
+
+    impl Args for TxCustom {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let code_path = CODE_PATH.parse(matches);
+            let data_path = DATA_PATH_OPT.parse(matches);
+            Self {
+                tx,
+                code_path,
+                data_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(
+                    CODE_PATH
+                        .def()
+                        .about("The path to the transaction's WASM code."),
+                )
+                .arg(DATA_PATH_OPT.def().about(
+                    "The data file at this path containing arbitrary bytes \
+                     will be passed to the transaction code when it's \
+                     executed.",
+                ))
+        }
+    }
+
+    /// Transfer transaction arguments
+    #[derive(Clone, Debug)]
+    pub struct TxTransfer {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Transfer source address
+        pub source: WalletAddress,
+        /// Transfer target address
+        pub target: WalletAddress,
+        /// Transferred token address
+        pub token: WalletAddress,
+        /// Transferred token amount
+        pub amount: token::Amount,
+    }
+
+    impl Args for TxTransfer {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let source = SOURCE.parse(matches);
+            let target = TARGET.parse(matches);
+            let token = TOKEN.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            Self {
+                tx,
+                source,
+                target,
+                token,
+                amount,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(SOURCE.def().about(
+                    "The source account address. The source's key is used to \
+                     produce the signature.",
+                ))
+                .arg(TARGET.def().about("The target account address."))
+                .arg(TOKEN.def().about("The transfer token."))
+                .arg(AMOUNT.def().about("The amount to transfer in decimal."))
+        }
+    }
+
+    /// Transaction to initialize a new account
+    #[derive(Clone, Debug)]
+    pub struct TxInitAccount {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Address of the source account
+        pub source: WalletAddress,
+        /// Path to the VP WASM code file for the new account
+        pub vp_code_path: Option<PathBuf>,
+        /// Public key for the new account
+        pub public_key: WalletPublicKey,
+    }
+
+    impl Args for TxInitAccount {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let source = SOURCE.parse(matches);
+            let vp_code_path = CODE_PATH_OPT.parse(matches);
+            let public_key = PUBLIC_KEY.parse(matches);
+            Self {
+                tx,
+                source,
+                vp_code_path,
+                public_key,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(SOURCE.def().about(
+                    "The source account's address that signs the transaction.",
+                ))
+                .arg(CODE_PATH_OPT.def().about(
+                    "The path to the validity predicate WASM code to be used \
+                     for the new account. Uses the default user VP if none \
+                     specified.",
+                ))
+                .arg(PUBLIC_KEY.def().about(
+                    "A public key to be used for the new account in \
+                     hexadecimal encoding.",
+                ))
+        }
+    }
+
+    /// Transaction to initialize a new multi-signature account
+    #[derive(Clone, Debug)]
+    pub struct TxInitMultisigAccount {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Address of the source account
+        pub source: WalletAddress,
+        /// Path to the VP WASM code file for the new account
+        pub vp_code_path: Option<PathBuf>,
+        /// Public keys for the new account
+        pub public_keys: Vec<WalletPublicKey>,
+        /// The minimum number of signatures required by the new account
+        pub threshold: u8,
+    }
+
+    impl Args for TxInitMultisigAccount {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let source = SOURCE.parse(matches);
+            let vp_code_path = CODE_PATH_OPT.parse(matches);
+            let public_keys = PUBLIC_KEYS.parse(matches);
+            let threshold = THRESHOLD.parse(matches);
+            Self {
+                tx,
+                source,
+                vp_code_path,
+                public_keys,
+                threshold,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(SOURCE.def().about(
+                    "The source account's address that signs the transaction.",
+                ))
+                .arg(CODE_PATH_OPT.def().about(
+                    "The path to the validity predicate WASM code to be used \
+                     for the new account. Uses the default multi-signature \
+                     VP if none specified.",
+                ))
+                .arg(PUBLIC_KEYS.def().required(true).about(
+                    "The public keys to be used for the new account in \
+                     hexadecimal encoding. The order of the keys determines \
+                     their key index used for signing.",
+                ))
+                .arg(THRESHOLD.def().about(
+                    "The minimum number of signatures from the given public \
+                     keys required to authorize a transaction.",
+                ))
+        }
+    }
+
+    /// Sign a transaction offline for a multi-signature account
+    #[derive(Clone, Debug)]
+    pub struct MultisigSign {
+        /// Path to the unsigned transaction file
+        pub tx_path: PathBuf,
+        /// The key to sign with
+        pub signing_key: WalletKeypair,
+        /// The index of the signing key in the account's key set
+        pub key_index: u8,
+        /// Path to the signature file to write
+        pub output_path: Option<PathBuf>,
+    }
+
+    impl Args for MultisigSign {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx_path = TX_PATH.parse(matches);
+            let signing_key = SIGNING_KEY.parse(matches);
+            let key_index = KEY_INDEX.parse(matches);
+            let output_path = OUTPUT_PATH.parse(matches);
+            Self {
+                tx_path,
+                signing_key,
+                key_index,
+                output_path,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(
+                TX_PATH
+                    .def()
+                    .about("The path to the unsigned transaction file."),
+            )
+            .arg(SIGNING_KEY.def().about(
+                "Sign the transaction with the key for the given public key, \
+                 public key hash or alias from your wallet.",
+            ))
+            .arg(KEY_INDEX.def().about(
+                "The index of the signing key in the multi-signature \
+                 account's key set.",
+            ))
+            .arg(OUTPUT_PATH.def().about(
+                "The path of the signature file to write. Defaults to the \
+                 transaction path with a `.{key-index}.sig` suffix.",
+            ))
+        }
+    }
+
+    /// Combine signatures of a transaction for a multi-signature account and
+    /// submit it
+    #[derive(Clone, Debug)]
+    pub struct MultisigSubmit {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Path to the unsigned transaction file
+        pub tx_path: PathBuf,
+        /// Paths to the signature files
+        pub signature_paths: Vec<PathBuf>,
+        /// The multi-signature account whose key set the signatures should
+        /// satisfy
+        pub owner: Option<WalletAddress>,
+    }
+
+    impl Args for MultisigSubmit {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let tx_path = TX_PATH.parse(matches);
+            let signature_paths = SIGNATURE_PATHS.parse(matches);
+            let owner = OWNER_OPT.parse(matches);
+            Self {
+                tx,
+                tx_path,
+                signature_paths,
+                owner,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(
+                    TX_PATH
+                        .def()
+                        .about("The path to the unsigned transaction file."),
+                )
+                .arg(SIGNATURE_PATHS.def().required(true).about(
+                    "The paths to the signature files produced by the \
+                     `multisig sign` command.",
+                ))
+                .arg(OWNER_OPT.def().about(
+                    "The multi-signature account. If given, the signatures \
+                     are checked against the account's key set before \
+                     submitting the transaction.",
+                ))
+        }
+    }
+
+    /// Transaction to initialize a new account
+    #[derive(Clone, Debug)]
+    pub struct TxInitValidator {
+        pub tx: Tx,
+        pub source: WalletAddress,
+        pub account_key: Option<WalletPublicKey>,
+        pub consensus_key: Option<WalletKeypair>,
+        pub rewards_account_key: Option<WalletPublicKey>,
+        pub validator_vp_code_path: Option<PathBuf>,
+        pub rewards_vp_code_path: Option<PathBuf>,
+        pub commission_rate: u64,
+        pub moniker: Option<String>,
+        pub description: Option<String>,
+        pub website: Option<String>,
+        pub contact: Option<String>,
+        pub unsafe_dont_encrypt: bool,
+    }
+
+    impl Args for TxInitValidator {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let source = SOURCE.parse(matches);
+            let account_key = VALIDATOR_ACCOUNT_KEY.parse(matches);
+            let consensus_key = VALIDATOR_CONSENSUS_KEY.parse(matches);
+            let rewards_account_key = REWARDS_KEY.parse(matches);
+            let validator_vp_code_path = VALIDATOR_CODE_PATH.parse(matches);
+            let rewards_vp_code_path = REWARDS_CODE_PATH.parse(matches);
+            let commission_rate = COMMISSION_RATE.parse(matches);
+            let moniker = MONIKER.parse(matches);
+            let description = DESCRIPTION.parse(matches);
+            let website = WEBSITE.parse(matches);
+            let contact = CONTACT.parse(matches);
+            let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
+            Self {
+                tx,
+                source,
+                account_key,
+                consensus_key,
+                rewards_account_key,
+                validator_vp_code_path,
+                rewards_vp_code_path,
+                commission_rate,
+                moniker,
+                description,
+                website,
+                contact,
+                unsafe_dont_encrypt,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(SOURCE.def().about(
+                    "The source account's address that signs the transaction.",
+                ))
+                .arg(VALIDATOR_ACCOUNT_KEY.def().about(
+                    "A public key for the validator account. A new one will \
+                     be generated if none given.",
+                ))
+                .arg(VALIDATOR_CONSENSUS_KEY.def().about(
+                    "A consensus key for the validator account. A new one \
+                     will be generated if none given.",
+                ))
+                .arg(REWARDS_KEY.def().about(
+                    "A public key for the staking reward account. A new one \
+                     will be generated if none given.",
+                ))
+                .arg(VALIDATOR_CODE_PATH.def().about(
+                    "The path to the validity predicate WASM code to be used \
+                     for the validator account. Uses the default validator VP \
+                     if none specified.",
+                ))
+                .arg(REWARDS_CODE_PATH.def().about(
+                    "The path to the validity predicate WASM code to be used \
+                     for the staking reward account. Uses the default staking \
+                     reward VP if none specified.",
+                ))
+                .arg(COMMISSION_RATE.def().about(
+                    "The validator's commission rate on delegators' rewards \
+                     in basis points (1/10000).",
+                ))
+                .arg(MONIKER.def().about("The validator's name."))
+                .arg(DESCRIPTION.def().about("The validator's description."))
+                .arg(WEBSITE.def().about("The validator's website."))
+                .arg(
+                    CONTACT.def().about(
+                        "The validator's contact, e.g. an e-mail address.",
+                    ),
+                )
+                .arg(UNSAFE_DONT_ENCRYPT.def().about(
+                    "UNSAFE: Do not encrypt the generated keypairs. Do not \
+                     use this for keys used in a live network.",
+                ))
+        }
+    }
+
+    /// Transaction to update a VP arguments
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateVp {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Path to the VP WASM code file
+        pub vp_code_path: PathBuf,
+        /// Address of the account whose VP is to be updated
+        pub addr: WalletAddress,
+    }
+
+    impl Args for TxUpdateVp {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let vp_code_path = CODE_PATH.parse(matches);
+            let addr = ADDRESS.parse(matches);
+            Self {
+                tx,
+                vp_code_path,
+                addr,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(
+                    CODE_PATH.def().about(
+                        "The path to the new validity predicate WASM code.",
+                    ),
+                )
+                .arg(ADDRESS.def().about(
+                    "The account's address. It's key is used to produce the \
+                     signature.",
+                ))
+        }
+    }
+
+    /// Transaction to update an account's public key
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateKey {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Address of the account whose public key is to be updated
+        pub addr: WalletAddress,
+        /// The new public key
+        pub public_key: WalletPublicKey,
+    }
 
-    const ADDRESS: Arg<WalletAddress> = arg("address");
-    const ALIAS_OPT: ArgOpt<String> = ALIAS.opt();
-    const ALIAS: Arg<String> = arg("alias");
-    const ALLOW_DUPLICATE_IP: ArgFlag = flag("allow-duplicate-ip");
-    const AMOUNT: Arg<token::Amount> = arg("amount");
-    const BASE_DIR: ArgDefault<PathBuf> = arg_default(
-        "base-dir",
-        DefaultFn(|| match env::var("ANOMA_BASE_DIR") {
-            Ok(dir) => dir.into(),
-            Err(_) => config::DEFAULT_BASE_DIR.into(),
-        }),
-    );
-    const CHAIN_ID: Arg<ChainId> = arg("chain-id");
-    const CHAIN_ID_OPT: ArgOpt<ChainId> = CHAIN_ID.opt();
-    const CHAIN_ID_PREFIX: Arg<ChainIdPrefix> = arg("chain-prefix");
-    const CODE_PATH: Arg<PathBuf> = arg("code-path");
-    const CODE_PATH_OPT: ArgOpt<PathBuf> = CODE_PATH.opt();
-    const CONSENSUS_TIMEOUT_COMMIT: ArgDefault<tendermint::Timeout> =
-        arg_default(
-            "consensus-timeout-commit",
-            DefaultFn(|| tendermint::Timeout::from_str("1s").unwrap()),
-        );
-    const DATA_PATH_OPT: ArgOpt<PathBuf> = arg_opt("data-path");
-    const DATA_PATH: Arg<PathBuf> = arg("data-path");
-    const DECRYPT: ArgFlag = flag("decrypt");
-    const DRY_RUN_TX: ArgFlag = flag("dry-run");
-    const EPOCH: ArgOpt<Epoch> = arg_opt("epoch");
-    const FILTER_PATH: ArgOpt<PathBuf> = arg_opt("filter-path");
-    const FORCE: ArgFlag = flag("force");
-    const GENESIS_PATH: Arg<PathBuf> = arg("genesis-path");
-    const LEDGER_ADDRESS_ABOUT: &str =
-        "Address of a ledger node as \"{scheme}://{host}:{port}\". If the \
-         scheme is not supplied, it is assumed to be TCP.";
-    const LEDGER_ADDRESS_DEFAULT: ArgDefault<tendermint::net::Address> =
-        LEDGER_ADDRESS.default(DefaultFn(|| {
-            let raw = "127.0.0.1:26657";
-            tendermint::net::Address::from_str(raw).unwrap()
-        }));
-    const LEDGER_ADDRESS_OPT: ArgOpt<tendermint::net::Address> =
-        LEDGER_ADDRESS.opt();
-    const LEDGER_ADDRESS: Arg<tendermint::net::Address> = arg("ledger-address");
-    const LOCALHOST: ArgFlag = flag("localhost");
-    const MATCHMAKER_PATH: ArgOpt<PathBuf> = arg_opt("matchmaker-path");
-    const MULTIADDR_OPT: ArgOpt<Multiaddr> = arg_opt("address");
-    const NODE_OPT: ArgOpt<String> = arg_opt("node");
-    const NODE: Arg<String> = arg("node");
-    const OWNER: ArgOpt<WalletAddress> = arg_opt("owner");
-    const PEERS: ArgMulti<String> = arg_multi("peers");
-    const PUBLIC_KEY: Arg<WalletPublicKey> = arg("public-key");
-    const RAW_ADDRESS: Arg<Address> = arg("address");
-    const RAW_PUBLIC_KEY_OPT: ArgOpt<PublicKey> = arg_opt("public-key");
-    const REWARDS_CODE_PATH: ArgOpt<PathBuf> = arg_opt("rewards-code-path");
-    const REWARDS_KEY: ArgOpt<WalletPublicKey> = arg_opt("rewards-key");
-    const RPC_SOCKET_ADDR: ArgOpt<SocketAddr> = arg_opt("rpc");
-    const SIGNER: ArgOpt<WalletAddress> = arg_opt("signer");
-    const SIGNING_KEY_OPT: ArgOpt<WalletKeypair> = SIGNING_KEY.opt();
-    const SIGNING_KEY: Arg<WalletKeypair> = arg("signing-key");
-    const SOURCE: Arg<WalletAddress> = arg("source");
-    const SOURCE_OPT: ArgOpt<WalletAddress> = SOURCE.opt();
-    const TARGET: Arg<WalletAddress> = arg("target");
-    const TO_STDOUT: ArgFlag = flag("stdout");
-    const TOKEN_OPT: ArgOpt<WalletAddress> = TOKEN.opt();
-    const TOKEN: Arg<WalletAddress> = arg("token");
-    const TOPIC_OPT: ArgOpt<String> = arg_opt("topic");
-    const TOPIC: Arg<String> = arg("topic");
-    const TOPICS: ArgMulti<String> = TOPIC.multi();
-    const TX_CODE_PATH: ArgOpt<PathBuf> = arg_opt("tx-code-path");
-    const UNSAFE_DONT_ENCRYPT: ArgFlag = flag("unsafe-dont-encrypt");
-    const UNSAFE_SHOW_SECRET: ArgFlag = flag("unsafe-show-secret");
-    const VALIDATOR: Arg<WalletAddress> = arg("validator");
-    const VALIDATOR_OPT: ArgOpt<WalletAddress> = VALIDATOR.opt();
-    const VALIDATOR_ACCOUNT_KEY: ArgOpt<WalletPublicKey> =
-        arg_opt("account-key");
-    const VALIDATOR_CONSENSUS_KEY: ArgOpt<WalletKeypair> =
-        arg_opt("consensus-key");
-    const VALIDATOR_CODE_PATH: ArgOpt<PathBuf> = arg_opt("validator-code-path");
-    const VALUE: ArgOpt<String> = arg_opt("value");
-    const WASM_DIR: ArgOpt<PathBuf> = arg_opt("wasm-dir");
+    impl Args for TxUpdateKey {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let addr = ADDRESS.parse(matches);
+            let public_key = PUBLIC_KEY.parse(matches);
+            Self {
+                tx,
+                addr,
+                public_key,
+            }
+        }
 
-    /// Global command arguments
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(ADDRESS.def().about(
+                    "The account's address. Its current key is used to \
+                     produce the signature.",
+                ))
+                .arg(PUBLIC_KEY.def().about(
+                    "The new public key of the account. If the keypair is in \
+                     your wallet, the alias of the current key will be \
+                     re-associated with it.",
+                ))
+        }
+    }
+
+    /// Transaction to set or remove an account's spending policy
     #[derive(Clone, Debug)]
-    pub struct Global {
-        pub chain_id: Option<ChainId>,
-        pub base_dir: PathBuf,
-        pub wasm_dir: Option<PathBuf>,
+    pub struct TxUpdateSpendingPolicy {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Address of the account
+        pub addr: WalletAddress,
+        /// Limits of the outflow of tokens per epoch
+        pub limits: Vec<TokenLimit>,
+        /// Addresses that tokens may be sent to
+        pub allowed_targets: Vec<WalletAddress>,
+        /// A key that can bypass the policy
+        pub guardian_key: Option<WalletPublicKey>,
+        /// Remove the policy
+        pub remove: bool,
     }
 
-    impl Global {
-        /// Parse global arguments
-        pub fn parse(matches: &ArgMatches) -> Self {
-            let chain_id = CHAIN_ID_OPT.parse(matches);
-            let base_dir = BASE_DIR.parse(matches);
-            let wasm_dir = WASM_DIR.parse(matches);
-            Global {
-                chain_id,
-                base_dir,
-                wasm_dir,
+    impl Args for TxUpdateSpendingPolicy {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let addr = ADDRESS.parse(matches);
+            let limits = LIMITS.parse(matches);
+            let allowed_targets = ALLOWED_TARGETS.parse(matches);
+            let guardian_key = GUARDIAN_KEY.parse(matches);
+            let remove = REMOVE.parse(matches);
+            Self {
+                tx,
+                addr,
+                limits,
+                allowed_targets,
+                guardian_key,
+                remove,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(ADDRESS.def().about(
+                    "The account's address. Its key is used to produce the \
+                     signature.",
+                ))
+                .arg(
+                    LIMITS
+                        .def()
+                        .about(
+                            "The maximum outflow of a token per epoch, given \
+                             as \"TOKEN:AMOUNT\".",
+                        )
+                        .conflicts_with(REMOVE.name),
+                )
+                .arg(
+                    ALLOWED_TARGETS
+                        .def()
+                        .about(
+                            "The addresses that tokens may be sent to. If \
+                             none are given, tokens may be sent to any \
+                             address.",
+                        )
+                        .conflicts_with(REMOVE.name),
+                )
+                .arg(
+                    GUARDIAN_KEY
+                        .def()
+                        .about(
+                            "A public key whose co-signature bypasses the \
+                             policy. When set, it is also required to change \
+                             the policy, the account's public key or its \
+                             validity predicate.",
+                        )
+                        .conflicts_with(REMOVE.name),
+                )
+                .arg(REMOVE.def().about("Remove the spending policy."))
+        }
+    }
+
+    /// A limit of the outflow of a token, parsed from "TOKEN:AMOUNT"
+    #[derive(Clone, Debug)]
+    pub struct TokenLimit {
+        /// Address of a token
+        pub token: WalletAddress,
+        /// The maximum outflow per epoch
+        pub amount: token::Amount,
+    }
+
+    impl FromStr for TokenLimit {
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let (token, amount) = s.rsplit_once(':').ok_or_else(|| {
+                format!("Expected a \"TOKEN:AMOUNT\" limit, got \"{}\"", s)
+            })?;
+            let amount = token::Amount::from_str(amount)
+                .map_err(|err| format!("Invalid limit amount: {}", err))?;
+            Ok(Self {
+                token: WalletAddress::new(token.to_owned()),
+                amount,
+            })
+        }
+    }
+
+    /// Bond arguments
+    #[derive(Clone, Debug)]
+    pub struct Bond {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// Amount of tokens to stake in a bond
+        pub amount: token::Amount,
+        /// Source address for delegations. For self-bonds, the validator is
+        /// also the source.
+        pub source: Option<WalletAddress>,
+    }
+
+    impl Args for Bond {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let source = SOURCE_OPT.parse(matches);
+            Self {
+                tx,
+                validator,
+                amount,
+                source,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(AMOUNT.def().about("Amount of tokens to stake in a bond."))
+                .arg(SOURCE_OPT.def().about(
+                    "Source address for delegations. For self-bonds, the \
+                     validator is also the source.",
+                ))
+        }
+    }
+
+    /// Unbond arguments
+    #[derive(Clone, Debug)]
+    pub struct Unbond {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// Amount of tokens to unbond from a bond
+        pub amount: token::Amount,
+        /// Source address for unbonding from delegations. For unbonding from
+        /// self-bonds, the validator is also the source
+        pub source: Option<WalletAddress>,
+    }
+
+    impl Args for Unbond {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let source = SOURCE_OPT.parse(matches);
+            Self {
+                tx,
+                validator,
+                amount,
+                source,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(
+                    AMOUNT
+                        .def()
+                        .about("Amount of tokens to unbond from a bond."),
+                )
+                .arg(SOURCE_OPT.def().about(
+                    "Source address for unbonding from delegations. For \
+                     unbonding from self-bonds, the validator is also the \
+                     source.",
+                ))
+        }
+    }
+
+    /// Withdraw arguments
+    #[derive(Clone, Debug)]
+    pub struct Withdraw {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// Source address for withdrawing from delegations. For withdrawing
+        /// from self-bonds, the validator is also the source
+        pub source: Option<WalletAddress>,
+    }
+
+    impl Args for Withdraw {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let source = SOURCE_OPT.parse(matches);
+            Self {
+                tx,
+                validator,
+                source,
             }
         }
 
-        /// Add global args definition. Should be added to every top-level
-        /// command.
-        pub fn def(app: App) -> App {
-            app.arg(CHAIN_ID_OPT.def().about("The chain ID."))
-                .arg(BASE_DIR.def().about(
-                    "The base directory is where the nodes, client and wallet \
-                     configuration and state is stored. This value can also \
-                     be set via `ANOMA_BASE_DIR` environment variable, but \
-                     the argument takes precedence, if specified. Defaults to \
-                     `.anoma`.",
-                ))
-                .arg(WASM_DIR.def().about(
-                    "Directory with built WASM validity predicates, \
-                     transactions and matchmaker files. This value can also \
-                     be set via `ANOMA_WASM_DIR` environment variable, but \
-                     the argument takes precedence, if specified. Defaults to \
-                     `wasm` path, relative to current working directory.",
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(SOURCE_OPT.def().about(
+                    "Source address for withdrawing from delegations. For \
+                     withdrawing from self-bonds, the validator is also the \
+                     source.",
                 ))
         }
     }
 
-    /// Custom transaction arguments
+    /// Redelegation arguments
     #[derive(Clone, Debug)]
-    pub struct TxCustom {
+    pub struct Redelegate {
         /// Common tx arguments
         pub tx: Tx,
-        /// Path to the tx WASM code file
-        pub code_path: PathBuf,
-        /// Path to the data file
-        pub data_path: Option<PathBuf>,
+        /// Source validator address
+        pub src_validator: WalletAddress,
+        /// Destination validator address
+        pub dest_validator: WalletAddress,
+        /// Amount of tokens to redelegate
+        pub amount: token::Amount,
+        /// Source address of the delegation
+        pub source: WalletAddress,
     }
 
-    impl Args for TxCustom {
+    impl Args for Redelegate {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let code_path = CODE_PATH.parse(matches);
-            let data_path = DATA_PATH_OPT.parse(matches);
+            let src_validator = SRC_VALIDATOR.parse(matches);
+            let dest_validator = DEST_VALIDATOR.parse(matches);
+            let amount = AMOUNT.parse(matches);
+            let source = SOURCE.parse(matches);
             Self {
                 tx,
-                code_path,
-                data_path,
+                src_validator,
+                dest_validator,
+                amount,
+                source,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
                 .arg(
-                    CODE_PATH
+                    SRC_VALIDATOR
                         .def()
-                        .about("The path to the transaction's WASM code."),
+                        .about("The validator to redelegate the tokens from."),
                 )
-                .arg(DATA_PATH_OPT.def().about(
-                    "The data file at this path containing arbitrary bytes \
-                     will be passed to the transaction code when it's \
-                     executed.",
+                .arg(
+                    DEST_VALIDATOR
+                        .def()
+                        .about("The validator to redelegate the tokens to."),
+                )
+                .arg(AMOUNT.def().about("Amount of tokens to redelegate."))
+                .arg(SOURCE.def().about("Source address of the delegation."))
+        }
+    }
+
+    /// Change validator commission rate arguments
+    #[derive(Clone, Debug)]
+    pub struct TxChangeCommission {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// The new commission rate in basis points
+        pub rate: u64,
+    }
+
+    impl Args for TxChangeCommission {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let rate = RATE.parse(matches);
+            Self {
+                tx,
+                validator,
+                rate,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(RATE.def().about(
+                    "The new commission rate in basis points (1/10000).",
                 ))
         }
     }
 
-    /// Transfer transaction arguments
+    /// Change validator consensus key arguments
     #[derive(Clone, Debug)]
-    pub struct TxTransfer {
+    pub struct TxChangeConsensusKey {
         /// Common tx arguments
         pub tx: Tx,
-        /// Transfer source address
-        pub source: WalletAddress,
-        /// Transfer target address
-        pub target: WalletAddress,
-        /// Transferred token address
-        pub token: WalletAddress,
-        /// Transferred token amount
-        pub amount: token::Amount,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// The new consensus key
+        pub consensus_key: Option<WalletPublicKey>,
+        /// Don't encrypt the keypair, if a new one is generated
+        pub unsafe_dont_encrypt: bool,
     }
 
-    impl Args for TxTransfer {
+    impl Args for TxChangeConsensusKey {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let source = SOURCE.parse(matches);
-            let target = TARGET.parse(matches);
-            let token = TOKEN.parse(matches);
-            let amount = AMOUNT.parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let consensus_key = CONSENSUS_PUBLIC_KEY.parse(matches);
+            let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
             Self {
                 tx,
-                source,
-                target,
-                token,
-                amount,
+                validator,
+                consensus_key,
+                unsafe_dont_encrypt,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
-                .arg(SOURCE.def().about(
-                    "The source account address. The source's key is used to \
-                     produce the signature.",
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(CONSENSUS_PUBLIC_KEY.def().about(
+                    "The new consensus public key. A new one will be \
+                     generated if none given.",
+                ))
+                .arg(UNSAFE_DONT_ENCRYPT.def().about(
+                    "UNSAFE: Do not encrypt the generated keypair. Do not use \
+                     this for keys used in a live network.",
                 ))
-                .arg(TARGET.def().about("The target account address."))
-                .arg(TOKEN.def().about("The transfer token."))
-                .arg(AMOUNT.def().about("The amount to transfer in decimal."))
         }
     }
 
-    /// Transaction to initialize a new account
+    /// Write validator consensus key arguments
     #[derive(Clone, Debug)]
-    pub struct TxInitAccount {
+    pub struct WriteConsensusKey {
+        /// Common query args
+        pub query: Query,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// The consensus keypair
+        pub consensus_key: WalletKeypair,
+        /// Write the key even if it's not the validator's current key
+        pub force: bool,
+    }
+
+    impl Args for WriteConsensusKey {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let consensus_key = CONSENSUS_KEY.parse(matches);
+            let force = FORCE.parse(matches);
+            Self {
+                query,
+                validator,
+                consensus_key,
+                force,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>()
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(CONSENSUS_KEY.def().about(
+                    "The consensus keypair to be used by the ledger node.",
+                ))
+                .arg(FORCE.def().about(
+                    "Write the key even if it's not the validator's consensus \
+                     key in the current epoch.",
+                ))
+        }
+    }
+
+    /// Update validator metadata arguments
+    #[derive(Clone, Debug)]
+    pub struct TxUpdateValidatorMetadata {
         /// Common tx arguments
         pub tx: Tx,
-        /// Address of the source account
-        pub source: WalletAddress,
-        /// Path to the VP WASM code file for the new account
-        pub vp_code_path: Option<PathBuf>,
-        /// Public key for the new account
-        pub public_key: WalletPublicKey,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// The validator's new name
+        pub moniker: Option<String>,
+        /// The validator's new description
+        pub description: Option<String>,
+        /// The validator's new website
+        pub website: Option<String>,
+        /// The validator's new contact
+        pub contact: Option<String>,
     }
 
-    impl Args for TxInitAccount {
+    impl Args for TxUpdateValidatorMetadata {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let source = SOURCE.parse(matches);
-            let vp_code_path = CODE_PATH_OPT.parse(matches);
-            let public_key = PUBLIC_KEY.parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            let moniker = MONIKER.parse(matches);
+            let description = DESCRIPTION.parse(matches);
+            let website = WEBSITE.parse(matches);
+            let contact = CONTACT.parse(matches);
             Self {
                 tx,
-                source,
-                vp_code_path,
-                public_key,
+                validator,
+                moniker,
+                description,
+                website,
+                contact,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
-                .arg(SOURCE.def().about(
-                    "The source account's address that signs the transaction.",
+                .arg(VALIDATOR.def().about("Validator address."))
+                .arg(MONIKER.def().about(
+                    "The validator's new name. Use an empty string to clear \
+                     it.",
                 ))
-                .arg(CODE_PATH_OPT.def().about(
-                    "The path to the validity predicate WASM code to be used \
-                     for the new account. Uses the default user VP if none \
-                     specified.",
+                .arg(DESCRIPTION.def().about(
+                    "The validator's new description. Use an empty string to \
+                     clear it.",
                 ))
-                .arg(PUBLIC_KEY.def().about(
-                    "A public key to be used for the new account in \
-                     hexadecimal encoding.",
+                .arg(WEBSITE.def().about(
+                    "The validator's new website. Use an empty string to \
+                     clear it.",
+                ))
+                .arg(CONTACT.def().about(
+                    "The validator's new contact, e.g. an e-mail address. Use \
+                     an empty string to clear it.",
                 ))
         }
     }
 
-    /// Transaction to initialize a new account
+    /// Claim delegation rewards arguments
     #[derive(Clone, Debug)]
-    pub struct TxInitValidator {
+    pub struct ClaimRewards {
+        /// Common tx arguments
         pub tx: Tx,
+        /// Validator address
+        pub validator: WalletAddress,
+        /// Source address of the delegation whose rewards are claimed
         pub source: WalletAddress,
-        pub account_key: Option<WalletPublicKey>,
-        pub consensus_key: Option<WalletKeypair>,
-        pub rewards_account_key: Option<WalletPublicKey>,
-        pub validator_vp_code_path: Option<PathBuf>,
-        pub rewards_vp_code_path: Option<PathBuf>,
-        pub unsafe_dont_encrypt: bool,
     }
 
-    impl Args for TxInitValidator {
+    impl Args for ClaimRewards {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
             let source = SOURCE.parse(matches);
-            let account_key = VALIDATOR_ACCOUNT_KEY.parse(matches);
-            let consensus_key = VALIDATOR_CONSENSUS_KEY.parse(matches);
-            let rewards_account_key = REWARDS_KEY.parse(matches);
-            let validator_vp_code_path = VALIDATOR_CODE_PATH.parse(matches);
-            let rewards_vp_code_path = REWARDS_CODE_PATH.parse(matches);
-            let unsafe_dont_encrypt = UNSAFE_DONT_ENCRYPT.parse(matches);
             Self {
                 tx,
+                validator,
                 source,
-                account_key,
-                consensus_key,
-                rewards_account_key,
-                validator_vp_code_path,
-                rewards_vp_code_path,
-                unsafe_dont_encrypt,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
+                .arg(VALIDATOR.def().about("Validator address."))
                 .arg(SOURCE.def().about(
-                    "The source account's address that signs the transaction.",
-                ))
-                .arg(VALIDATOR_ACCOUNT_KEY.def().about(
-                    "A public key for the validator account. A new one will \
-                     be generated if none given.",
-                ))
-                .arg(VALIDATOR_CONSENSUS_KEY.def().about(
-                    "A consensus key for the validator account. A new one \
-                     will be generated if none given.",
+                    "Source address of the delegation whose rewards are \
+                     claimed.",
                 ))
-                .arg(REWARDS_KEY.def().about(
-                    "A public key for the staking reward account. A new one \
-                     will be generated if none given.",
+        }
+    }
+
+    /// Unjail validator arguments
+    #[derive(Clone, Debug)]
+    pub struct Unjail {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// Validator address
+        pub validator: WalletAddress,
+    }
+
+    impl Args for Unjail {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let validator = VALIDATOR.parse(matches);
+            Self { tx, validator }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(VALIDATOR.def().about("Validator address."))
+        }
+    }
+
+    /// Protocol parameters proposal arguments
+    #[derive(Clone, Debug)]
+    pub struct ProposeParameters {
+        /// Common tx arguments
+        pub tx: Tx,
+        /// The author of the proposal
+        pub author: WalletAddress,
+        /// The first epoch of the voting period
+        pub voting_start_epoch: Epoch,
+        /// The epoch at which the voting period ends
+        pub voting_end_epoch: Epoch,
+        /// The epoch from which the proposal can be applied
+        pub activation_epoch: Epoch,
+        /// Minimum number of blocks in an epoch
+        pub min_num_of_blocks: Option<u64>,
+        /// Minimum duration of an epoch in seconds
+        pub min_duration: Option<u64>,
+        /// Maximum number of memory pages of a transaction
+        pub tx_memory_max_pages: Option<u32>,
+        /// Maximum number of memory pages of a validity predicate
+        pub vp_memory_max_pages: Option<u32>,
+        /// Maximum stack height of a transaction or a validity predicate
+        pub wasm_stack_limit: Option<u32>,
+    }
+
+    impl Args for ProposeParameters {
+        fn parse(matches: &ArgMatches) -> Self {
+            let tx = Tx::parse(matches);
+            let author = AUTHOR.parse(matches);
+            let voting_start_epoch = VOTING_START_EPOCH.parse(matches);
+            let voting_end_epoch = VOTING_END_EPOCH.parse(matches);
+            let activation_epoch = ACTIVATION_EPOCH.parse(matches);
+            let min_num_of_blocks = MIN_NUM_OF_BLOCKS.parse(matches);
+            let min_duration = MIN_DURATION.parse(matches);
+            let tx_memory_max_pages = TX_MEMORY_MAX_PAGES.parse(matches);
+            let vp_memory_max_pages = VP_MEMORY_MAX_PAGES.parse(matches);
+            let wasm_stack_limit = WASM_STACK_LIMIT.parse(matches);
+            Self {
+                tx,
+                author,
+                voting_start_epoch,
+                voting_end_epoch,
+                activation_epoch,
+                min_num_of_blocks,
+                min_duration,
+                tx_memory_max_pages,
+                vp_memory_max_pages,
+                wasm_stack_limit,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Tx>()
+                .arg(
+                    AUTHOR.def().about(
+                        "The author of the proposal, who has to sign it.",
+                    ),
+                )
+                .arg(VOTING_START_EPOCH.def().about(
+                    "The first epoch of the voting period. The voting power \
+                     of the voters is taken from this epoch.",
                 ))
-                .arg(VALIDATOR_CODE_PATH.def().about(
-                    "The path to the validity predicate WASM code to be used \
-                     for the validator account. Uses the default validator VP \
-                     if none specified.",
+                .arg(VOTING_END_EPOCH.def().about(
+                    "The epoch at which the voting period ends, exclusive.",
                 ))
-                .arg(REWARDS_CODE_PATH.def().about(
-                    "The path to the validity predicate WASM code to be used \
-                     for the staking reward account. Uses the default staking \
-                     reward VP if none specified.",
+                .arg(ACTIVATION_EPOCH.def().about(
+                    "The epoch from which the proposal can be applied, if it \
+                     passed.",
                 ))
-                .arg(UNSAFE_DONT_ENCRYPT.def().about(
-                    "UNSAFE: Do not encrypt the generated keypairs. Do not \
-                     use this for keys used in a live network.",
+                .arg(
+                    MIN_NUM_OF_BLOCKS
+                        .def()
+                        .about("Minimum number of blocks in an epoch."),
+                )
+                .arg(
+                    MIN_DURATION
+                        .def()
+                        .about("Minimum duration of an epoch in seconds."),
+                )
+                .arg(
+                    TX_MEMORY_MAX_PAGES.def().about(
+                        "Maximum number of memory pages of a transaction.",
+                    ),
+                )
+                .arg(VP_MEMORY_MAX_PAGES.def().about(
+                    "Maximum number of memory pages of a validity predicate.",
+                ))
+                .arg(WASM_STACK_LIMIT.def().about(
+                    "Maximum stack height of a transaction or a validity \
+                     predicate.",
                 ))
         }
     }
 
-    /// Transaction to update a VP arguments
+    /// Vote on a protocol parameters proposal arguments
     #[derive(Clone, Debug)]
-    pub struct TxUpdateVp {
+    pub struct VoteParameters {
         /// Common tx arguments
         pub tx: Tx,
-        /// Path to the VP WASM code file
-        pub vp_code_path: PathBuf,
-        /// Address of the account whose VP is to be updated
-        pub addr: WalletAddress,
+        /// The ID of the proposal
+        pub proposal_id: u64,
+        /// The voter's address
+        pub voter: WalletAddress,
+        /// The vote
+        pub vote: ProposalVote,
     }
 
-    impl Args for TxUpdateVp {
+    impl Args for VoteParameters {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let vp_code_path = CODE_PATH.parse(matches);
-            let addr = ADDRESS.parse(matches);
+            let proposal_id = PROPOSAL_ID.parse(matches);
+            let voter = VOTER.parse(matches);
+            let vote = VOTE.parse(matches);
             Self {
                 tx,
-                vp_code_path,
-                addr,
+                proposal_id,
+                voter,
+                vote,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
+                .arg(PROPOSAL_ID.def().about("The ID of the proposal."))
                 .arg(
-                    CODE_PATH.def().about(
-                        "The path to the new validity predicate WASM code.",
+                    VOTER.def().about(
+                        "The voter's address, a validator or a delegator.",
                     ),
                 )
-                .arg(ADDRESS.def().about(
-                    "The account's address. It's key is used to produce the \
-                     signature.",
-                ))
+                .arg(VOTE.def().about("The vote, \"yay\" or \"nay\"."))
         }
     }
 
-    /// Bond arguments
+    /// Apply a passed protocol parameters proposal arguments
     #[derive(Clone, Debug)]
-    pub struct Bond {
+    pub struct ApplyParameters {
         /// Common tx arguments
         pub tx: Tx,
-        /// Validator address
-        pub validator: WalletAddress,
-        /// Amount of tokens to stake in a bond
-        pub amount: token::Amount,
-        /// Source address for delegations. For self-bonds, the validator is
-        /// also the source.
-        pub source: Option<WalletAddress>,
+        /// The ID of the proposal
+        pub proposal_id: u64,
     }
 
-    impl Args for Bond {
+    impl Args for ApplyParameters {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let validator = VALIDATOR.parse(matches);
-            let amount = AMOUNT.parse(matches);
-            let source = SOURCE_OPT.parse(matches);
-            Self {
-                tx,
-                validator,
-                amount,
-                source,
-            }
+            let proposal_id = PROPOSAL_ID.parse(matches);
+            Self { tx, proposal_id }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
-                .arg(VALIDATOR.def().about("Validator address."))
-                .arg(AMOUNT.def().about("Amount of tokens to stake in a bond."))
-                .arg(SOURCE_OPT.def().about(
-                    "Source address for delegations. For self-bonds, the \
-                     validator is also the source.",
-                ))
+                .arg(PROPOSAL_ID.def().about("The ID of the proposal."))
         }
     }
 
-    /// Unbond arguments
+    /// Submit a governance proposal arguments
     #[derive(Clone, Debug)]
-    pub struct Unbond {
+    pub struct InitProposal {
         /// Common tx arguments
         pub tx: Tx,
-        /// Validator address
-        pub validator: WalletAddress,
-        /// Amount of tokens to unbond from a bond
-        pub amount: token::Amount,
-        /// Source address for unbonding from delegations. For unbonding from
-        /// self-bonds, the validator is also the source
-        pub source: Option<WalletAddress>,
+        /// The author of the proposal
+        pub author: WalletAddress,
+        /// The content of the proposal
+        pub content: String,
+        /// The first epoch of the voting period
+        pub voting_start_epoch: Epoch,
+        /// The epoch at which the voting period ends, exclusive
+        pub voting_end_epoch: Epoch,
+        /// The epoch at which the proposal is finalized, defaults to the end
+        /// of the voting period
+        pub grace_epoch: Option<Epoch>,
+        /// Path to the WASM code to execute if the proposal passes
+        pub proposal_code_path: Option<PathBuf>,
     }
 
-    impl Args for Unbond {
+    impl Args for InitProposal {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let validator = VALIDATOR.parse(matches);
-            let amount = AMOUNT.parse(matches);
-            let source = SOURCE_OPT.parse(matches);
+            let author = AUTHOR.parse(matches);
+            let content = CONTENT.parse(matches);
+            let voting_start_epoch = VOTING_START_EPOCH.parse(matches);
+            let voting_end_epoch = VOTING_END_EPOCH.parse(matches);
+            let grace_epoch = GRACE_EPOCH.parse(matches);
+            let proposal_code_path = PROPOSAL_CODE_PATH.parse(matches);
             Self {
                 tx,
-                validator,
-                amount,
-                source,
+                author,
+                content,
+                voting_start_epoch,
+                voting_end_epoch,
+                grace_epoch,
+                proposal_code_path,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
-                .arg(VALIDATOR.def().about("Validator address."))
-                .arg(
-                    AMOUNT
-                        .def()
-                        .about("Amount of tokens to unbond from a bond."),
-                )
-                .arg(SOURCE_OPT.def().about(
-                    "Source address for unbonding from delegations. For \
-                     unbonding from self-bonds, the validator is also the \
-                     source.",
+                .arg(AUTHOR.def().about(
+                    "The author of the proposal, who has to sign it and \
+                         pay the deposit.",
+                ))
+                .arg(CONTENT.def().about("The content of the proposal."))
+                .arg(VOTING_START_EPOCH.def().about(
+                    "The first epoch of the voting period. The voting power \
+                     of the voters is taken from this epoch.",
+                ))
+                .arg(VOTING_END_EPOCH.def().about(
+                    "The epoch at which the voting period ends, exclusive.",
+                ))
+                .arg(GRACE_EPOCH.def().about(
+                    "The epoch at which the proposal is tallied and its code \
+                     executed, if it passed. Defaults to the end of the \
+                     voting period.",
+                ))
+                .arg(PROPOSAL_CODE_PATH.def().about(
+                    "The path to the WASM code to execute with governance \
+                     authority if the proposal passes.",
                 ))
         }
     }
 
-    /// Withdraw arguments
+    /// Vote on a governance proposal arguments
     #[derive(Clone, Debug)]
-    pub struct Withdraw {
+    pub struct VoteProposal {
         /// Common tx arguments
         pub tx: Tx,
-        /// Validator address
-        pub validator: WalletAddress,
-        /// Source address for withdrawing from delegations. For withdrawing
-        /// from self-bonds, the validator is also the source
-        pub source: Option<WalletAddress>,
+        /// The ID of the proposal
+        pub proposal_id: u64,
+        /// The voter's address
+        pub voter: WalletAddress,
+        /// The vote
+        pub vote: ProposalVote,
     }
 
-    impl Args for Withdraw {
+    impl Args for VoteProposal {
         fn parse(matches: &ArgMatches) -> Self {
             let tx = Tx::parse(matches);
-            let validator = VALIDATOR.parse(matches);
-            let source = SOURCE_OPT.parse(matches);
+            let proposal_id = PROPOSAL_ID.parse(matches);
+            let voter = VOTER.parse(matches);
+            let vote = VOTE.parse(matches);
             Self {
                 tx,
-                validator,
-                source,
+                proposal_id,
+                voter,
+                vote,
             }
         }
 
         fn def(app: App) -> App {
             app.add_args::<Tx>()
-                .arg(VALIDATOR.def().about("Validator address."))
-                .arg(SOURCE_OPT.def().about(
-                    "Source address for withdrawing from delegations. For \
-                     withdrawing from self-bonds, the validator is also the \
-                     source.",
-                ))
+                .arg(PROPOSAL_ID.def().about("The ID of the proposal."))
+                .arg(
+                    VOTER.def().about(
+                        "The voter's address, a validator or a delegator.",
+                    ),
+                )
+                .arg(VOTE.def().about("The vote, \"yay\" or \"nay\"."))
         }
     }
 
@@ -1572,7 +3195,7 @@ pub mod args {
     impl Args for QueryBalance {
         fn parse(matches: &ArgMatches) -> Self {
             let query = Query::parse(matches);
-            let owner = OWNER.parse(matches);
+            let owner = OWNER_OPT.parse(matches);
             let token = TOKEN_OPT.parse(matches);
             Self {
                 query,
@@ -1584,7 +3207,7 @@ pub mod args {
         fn def(app: App) -> App {
             app.add_args::<Query>()
                 .arg(
-                    OWNER
+                    OWNER_OPT
                         .def()
                         .about("The account address whose balance to query."),
                 )
@@ -1596,6 +3219,78 @@ pub mod args {
         }
     }
 
+    /// Query an account's spending policy
+    #[derive(Clone, Debug)]
+    pub struct QuerySpendingPolicy {
+        /// Common query args
+        pub query: Query,
+        /// Address of the account
+        pub owner: WalletAddress,
+    }
+
+    impl Args for QuerySpendingPolicy {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let owner = ADDRESS.parse(matches);
+            Self { query, owner }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>().arg(
+                ADDRESS
+                    .def()
+                    .about("The account address whose policy to query."),
+            )
+        }
+    }
+
+    /// Query a protocol parameters proposal arguments
+    #[derive(Clone, Debug)]
+    pub struct QueryParametersProposal {
+        /// Common query args
+        pub query: Query,
+        /// The ID of the proposal
+        pub proposal_id: u64,
+    }
+
+    impl Args for QueryParametersProposal {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let proposal_id = PROPOSAL_ID.parse(matches);
+            Self { query, proposal_id }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>()
+                .arg(PROPOSAL_ID.def().about("The ID of the proposal."))
+        }
+    }
+
+    /// Query governance proposals arguments
+    #[derive(Clone, Debug)]
+    pub struct QueryProposal {
+        /// Common query args
+        pub query: Query,
+        /// The ID of the proposal, all the proposals if not given
+        pub proposal_id: Option<u64>,
+    }
+
+    impl Args for QueryProposal {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let proposal_id = PROPOSAL_ID_OPT.parse(matches);
+            Self { query, proposal_id }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>().arg(
+                PROPOSAL_ID_OPT
+                    .def()
+                    .about("The ID of the proposal. Defaults to all."),
+            )
+        }
+    }
+
     /// Helper struct for generating intents
     #[derive(Debug, Clone, Deserialize)]
     pub struct ExchangeDefinition {
@@ -1671,7 +3366,7 @@ pub mod args {
     impl Args for QueryBonds {
         fn parse(matches: &ArgMatches) -> Self {
             let query = Query::parse(matches);
-            let owner = OWNER.parse(matches);
+            let owner = OWNER_OPT.parse(matches);
             let validator = VALIDATOR_OPT.parse(matches);
             Self {
                 query,
@@ -1683,7 +3378,7 @@ pub mod args {
         fn def(app: App) -> App {
             app.add_args::<Query>()
                 .arg(
-                    OWNER.def().about(
+                    OWNER_OPT.def().about(
                         "The owner account address whose bonds to query.",
                     ),
                 )
@@ -1730,6 +3425,30 @@ pub mod args {
         }
     }
 
+    /// Query PoS validators
+    #[derive(Clone, Debug)]
+    pub struct QueryValidators {
+        /// Common query args
+        pub query: Query,
+        /// Epoch in which to find the validator sets
+        pub epoch: Option<Epoch>,
+    }
+
+    impl Args for QueryValidators {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let epoch = EPOCH.parse(matches);
+            Self { query, epoch }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>().arg(EPOCH.def().about(
+                "The epoch at which to query (last committed, if not \
+                 specified).",
+            ))
+        }
+    }
+
     /// Query PoS slashes
     #[derive(Clone, Debug)]
     pub struct QuerySlashes {
@@ -1755,6 +3474,90 @@ pub mod args {
         }
     }
 
+    /// Query PoS block rewards
+    #[derive(Clone, Debug)]
+    pub struct QueryRewards {
+        /// Common query args
+        pub query: Query,
+        /// Address of an owner
+        pub owner: Option<WalletAddress>,
+        /// Address of a validator
+        pub validator: Option<WalletAddress>,
+    }
+
+    impl Args for QueryRewards {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let owner = OWNER_OPT.parse(matches);
+            let validator = VALIDATOR_OPT.parse(matches);
+            Self {
+                query,
+                owner,
+                validator,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>()
+                .arg(OWNER_OPT.def().about(
+                    "The owner account address whose rewards to query. The \
+                     rewards of a validator's self-bond are found under the \
+                     validator's address.",
+                ))
+                .arg(
+                    VALIDATOR_OPT.def().about(
+                        "The validator's address whose rewards to query.",
+                    ),
+                )
+        }
+    }
+
+    /// Query PoS staking overview
+    #[derive(Clone, Debug)]
+    pub struct QueryStaking {
+        /// Common query args
+        pub query: Query,
+        /// Address of an owner
+        pub owner: WalletAddress,
+        /// Address of a validator
+        pub validator: Option<WalletAddress>,
+        /// Print the overview as JSON
+        pub json: bool,
+    }
+
+    impl Args for QueryStaking {
+        fn parse(matches: &ArgMatches) -> Self {
+            let query = Query::parse(matches);
+            let owner = OWNER.parse(matches);
+            let validator = VALIDATOR_OPT.parse(matches);
+            let json = OUTPUT_JSON.parse(matches);
+            Self {
+                query,
+                owner,
+                validator,
+                json,
+            }
+        }
+
+        fn def(app: App) -> App {
+            app.add_args::<Query>()
+                .arg(OWNER.def().about(
+                    "The owner account address whose staking to query. For a \
+                     validator's self-bonds, use the validator's address.",
+                ))
+                .arg(
+                    VALIDATOR_OPT.def().about(
+                        "Only show the staking with the given validator.",
+                    ),
+                )
+                .arg(
+                    OUTPUT_JSON
+                        .def()
+                        .about("Print the staking overview as JSON."),
+                )
+        }
+    }
+
     /// Intent arguments
     #[derive(Clone, Debug)]
     pub struct Intent {
@@ -1978,6 +3781,9 @@ pub mod args {
         pub signing_key: Option<WalletKeypair>,
         /// Sign the tx with the keypair of the public key of the given address
         pub signer: Option<WalletAddress>,
+        /// Instead of signing and submitting the tx, write it into the given
+        /// file, e.g. to collect signatures for a multi-signature account
+        pub unsigned_tx_path: Option<PathBuf>,
     }
 
     impl Args for Tx {
@@ -2016,6 +3822,17 @@ pub mod args {
                     )
                     .conflicts_with(SIGNING_KEY_OPT.name),
             )
+            .arg(
+                UNSIGNED_TX_PATH
+                    .def()
+                    .about(
+                        "Instead of signing and submitting the transaction, \
+                         write the unsigned transaction into a file at the \
+                         given path. It can be signed offline with `multisig \
+                         sign`.",
+                    )
+                    .conflicts_with_all(&[SIGNING_KEY_OPT.name, SIGNER.name]),
+            )
         }
 
         fn parse(matches: &ArgMatches) -> Self {
@@ -2025,6 +3842,7 @@ pub mod args {
             let initialized_account_alias = ALIAS_OPT.parse(matches);
             let signing_key = SIGNING_KEY_OPT.parse(matches);
             let signer = SIGNER.parse(matches);
+            let unsigned_tx_path = UNSIGNED_TX_PATH.parse(matches);
             Self {
                 dry_run,
                 force,
@@ -2032,6 +3850,7 @@ pub mod args {
                 initialized_account_alias,
                 signing_key,
                 signer,
+                unsigned_tx_path,
             }
         }
     }
@@ -2335,6 +4154,31 @@ pub mod args {
                 ))
         }
     }
+
+    /// WASM code check arguments
+    #[derive(Clone, Debug)]
+    pub struct WasmCheck {
+        /// Path to the WASM code file
+        pub code_path: PathBuf,
+        /// Check the code as a validity predicate instead of a transaction
+        pub vp: bool,
+    }
+
+    impl Args for WasmCheck {
+        fn parse(matches: &ArgMatches) -> Self {
+            let code_path = CODE_PATH.parse(matches);
+            let vp = VP.parse(matches);
+            Self { code_path, vp }
+        }
+
+        fn def(app: App) -> App {
+            app.arg(CODE_PATH.def().about("The path to the WASM code file."))
+                .arg(VP.def().about(
+                    "Check the code as a validity predicate. By default, it's \
+                     checked as a transaction.",
+                ))
+        }
+    }
 }
 
 pub fn anoma_cli() -> (cmds::Anoma, String) {
@@ -2358,7 +4202,7 @@ pub fn anoma_node_cli() -> (cmds::AnomaNode, Context) {
 }
 
 pub enum AnomaClient {
-    WithoutContext(cmds::Utils, args::Global),
+    WithoutContext(cmds::AnomaClientWithoutContext, args::Global),
     WithContext(Box<(cmds::AnomaClientWithContext, Context)>),
 }
 
@@ -238,6 +238,20 @@ where
     }
 }
 
+impl<T> ArgMulti<FromContext<T>> {
+    pub fn def(&self) -> ClapArg {
+        ClapArg::new(self.name).long(self.name).multiple(true)
+    }
+
+    pub fn parse(&self, matches: &ArgMatches) -> Vec<FromContext<T>> {
+        matches
+            .values_of(self.name)
+            .unwrap_or_default()
+            .map(|raw| FromContext::new(raw.to_string()))
+            .collect()
+    }
+}
+
 /// Extensions for defining commands and arguments.
 /// Every function here should have a matcher in [`ArgMatchesExt`].
 pub trait AppExt {
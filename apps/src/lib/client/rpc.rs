@@ -1,21 +1,25 @@
 //! Client RPC queries
 
 use std::borrow::Cow;
-use std::convert::TryInto;
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::{TryFrom, TryInto};
 use std::io::{self, Write};
 
 use anoma::ledger::pos::types::{
-    Epoch as PosEpoch, VotingPower, WeightedValidator,
+    BasisPoints, Epoch as PosEpoch, VotingPower, WeightedValidator,
 };
 use anoma::ledger::pos::{
     self, is_validator_slashes_key, Bonds, Slash, Unbonds,
 };
+use anoma::ledger::{governance, inflation, parameters, treasury};
 use anoma::types::address::Address;
-use anoma::types::key::ed25519;
+use anoma::types::key::{ed25519, multisig};
+use anoma::types::spending_policy::{self, SpendingPolicy};
 use anoma::types::storage::Epoch;
 use anoma::types::{address, storage, token};
 use borsh::BorshDeserialize;
 use itertools::Itertools;
+use serde::Serialize;
 use tendermint_rpc::{Client, HttpClient};
 
 use crate::cli::{self, args, Context};
@@ -24,6 +28,13 @@ use crate::node::ledger::rpc::{Path, PrefixValue};
 /// Query the epoch of the last committed block
 pub async fn query_epoch(ctx: Context, args: args::Query) -> (Context, Epoch) {
     let client = HttpClient::new(args.ledger_address).unwrap();
+    let epoch = get_epoch(client).await;
+    println!("Last committed epoch: {}", epoch);
+    (ctx, epoch)
+}
+
+/// Get the epoch of the last committed block
+pub async fn get_epoch(client: HttpClient) -> Epoch {
     let path = Path::Epoch;
     let data = vec![];
     let response = client
@@ -33,10 +44,7 @@ pub async fn query_epoch(ctx: Context, args: args::Query) -> (Context, Epoch) {
     match response.code {
         tendermint::abci::Code::Ok => {
             match Epoch::try_from_slice(&response.value[..]) {
-                Ok(epoch) => {
-                    println!("Last committed epoch: {}", epoch);
-                    return (ctx, epoch);
-                }
+                Ok(epoch) => return epoch,
 
                 Err(err) => {
                     eprintln!("Error decoding the epoch value: {}", err)
@@ -598,6 +606,76 @@ pub async fn query_voting_power(ctx: Context, args: args::QueryVotingPower) {
     println!("Total voting power: {}", total_voting_power);
 }
 
+/// Query PoS validator sets with the validators' voting powers, commission
+/// rates and metadata
+pub async fn query_validators(ctx: Context, args: args::QueryValidators) {
+    let (_ctx, epoch) = match args.epoch {
+        Some(epoch) => (ctx, epoch),
+        None => query_epoch(ctx, args.query.clone()).await,
+    };
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+
+    let validator_sets = query_storage_value::<pos::ValidatorSets>(
+        client.clone(),
+        pos::validator_set_key(),
+    )
+    .await
+    .expect("Validator set should always be set");
+    let validator_set = validator_sets
+        .get(epoch)
+        .expect("Validator set should be always set in the current epoch");
+
+    let stdout = io::stdout();
+    let mut w = stdout.lock();
+    writeln!(w, "Validators in epoch {}:", epoch).unwrap();
+    for (heading, validators) in [
+        ("Active validators:", &validator_set.active),
+        ("Inactive validators:", &validator_set.inactive),
+    ] {
+        if validators.is_empty() {
+            continue;
+        }
+        writeln!(w, "{}", heading).unwrap();
+        // Validators with the highest voting power first
+        for validator in validators.iter().rev() {
+            let address = &validator.address;
+            let commission_rate =
+                query_storage_value::<pos::ValidatorCommissionRates>(
+                    client.clone(),
+                    pos::validator_commission_rate_key(address),
+                )
+                .await
+                .and_then(|rates| rates.get(epoch).copied());
+            let metadata = query_storage_value::<pos::ValidatorMetadata>(
+                client.clone(),
+                pos::validator_metadata_key(address),
+            )
+            .await
+            .unwrap_or_default();
+            writeln!(
+                w,
+                "  {}: voting power {}",
+                address.encode(),
+                validator.voting_power
+            )
+            .unwrap();
+            if let Some(rate) = commission_rate {
+                writeln!(w, "    Commission rate: {}", rate).unwrap();
+            }
+            for (name, field) in [
+                ("Moniker", &metadata.moniker),
+                ("Description", &metadata.description),
+                ("Website", &metadata.website),
+                ("Contact", &metadata.contact),
+            ] {
+                if let Some(field) = field {
+                    writeln!(w, "    {}: {}", name, field).unwrap();
+                }
+            }
+        }
+    }
+}
+
 /// Query PoS slashes
 pub async fn query_slashes(ctx: Context, args: args::QuerySlashes) {
     let client = HttpClient::new(args.query.ledger_address).unwrap();
@@ -672,6 +750,906 @@ pub async fn query_slashes(ctx: Context, args: args::QuerySlashes) {
     }
 }
 
+/// Query PoS block rewards
+pub async fn query_rewards(ctx: Context, args: args::QueryRewards) {
+    let client = HttpClient::new(args.query.ledger_address.clone()).unwrap();
+    let owner = args.owner.map(|owner| ctx.get(&owner));
+    let validator = args.validator.map(|validator| ctx.get(&validator));
+
+    let stdout = io::stdout();
+    let mut w = stdout.lock();
+
+    // Rewards accumulated by the validator, which are not yet paid out
+    if let Some(validator) = &validator {
+        let accumulator = query_storage_value::<pos::RewardsAccumulator>(
+            client.clone(),
+            pos::rewards_accumulator_key(),
+        )
+        .await;
+        if let Some(accumulator) = accumulator {
+            let pending = accumulator
+                .rewards
+                .get(validator)
+                .copied()
+                .unwrap_or_default();
+            writeln!(
+                w,
+                "Validator {} rewards accumulated in epoch {}: {}",
+                validator.encode(),
+                accumulator.epoch,
+                pending
+            )
+            .unwrap();
+        }
+
+        let (_ctx, epoch) = query_epoch(ctx, args.query.clone()).await;
+        let commission_rates =
+            query_storage_value::<pos::ValidatorCommissionRates>(
+                client.clone(),
+                pos::validator_commission_rate_key(validator),
+            )
+            .await;
+        if let Some(rate) =
+            commission_rates.and_then(|rates| rates.get(epoch).copied())
+        {
+            writeln!(
+                w,
+                "Validator {} commission rate in epoch {}: {}",
+                validator.encode(),
+                epoch,
+                rate
+            )
+            .unwrap();
+        }
+    }
+
+    // Rewards paid out to bonds
+    let bond_rewards: Vec<(pos::BondId, pos::BondRewards)> =
+        match (owner, validator) {
+            (Some(source), Some(validator)) => {
+                let bond_id = pos::BondId { source, validator };
+                let key = pos::bond_rewards_key(&bond_id);
+                query_storage_value::<pos::BondRewards>(client, key)
+                    .await
+                    .map(|rewards| vec![(bond_id, rewards)])
+                    .unwrap_or_default()
+            }
+            (owner, validator) => {
+                let prefix = match &owner {
+                    Some(owner) => pos::bond_rewards_for_source_prefix(owner),
+                    None => pos::bond_rewards_prefix(),
+                };
+                query_storage_prefix::<pos::BondRewards>(client, prefix)
+                    .await
+                    .map(|rewards| {
+                        rewards
+                            .filter_map(|(key, rewards)| {
+                                let bond_id = pos::is_bond_rewards_key(&key)?;
+                                match &validator {
+                                    Some(validator)
+                                        if validator != &bond_id.validator =>
+                                    {
+                                        None
+                                    }
+                                    _ => Some((bond_id, rewards)),
+                                }
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+        };
+    if bond_rewards.is_empty() {
+        writeln!(w, "No bond rewards found").unwrap();
+    }
+    for (bond_id, rewards) in bond_rewards {
+        let bond_type = if bond_id.source == bond_id.validator {
+            "Self-bond"
+        } else {
+            "Delegation"
+        };
+        writeln!(
+            w,
+            "{} of {} to validator {}: total {}, last {} in epoch {}, \
+             unclaimed {}",
+            bond_type,
+            bond_id.source.encode(),
+            bond_id.validator.encode(),
+            rewards.total,
+            rewards.last_amount,
+            rewards.last_epoch,
+            rewards.unclaimed
+        )
+        .unwrap();
+    }
+}
+
+/// An overview of an account's PoS bonds, unbonds, slashes and rewards
+#[derive(Debug, Serialize)]
+pub struct StakingOverview {
+    /// The owner of the bonds
+    pub owner: Address,
+    /// The epoch of the last committed block
+    pub epoch: Epoch,
+    /// The staking with each validator
+    pub validators: Vec<ValidatorStaking>,
+    /// The total bonded amount, with slashes applied
+    pub total_bonded: token::Amount,
+    /// The total bonded amount that is active in the current epoch
+    pub total_active: token::Amount,
+    /// The total unbonded amount, with slashes applied
+    pub total_unbonded: token::Amount,
+    /// The total unbonded amount that may be withdrawn in the current epoch
+    pub total_withdrawable: token::Amount,
+    /// The total rewards that have accrued, but have not yet been claimed
+    pub total_unclaimed_rewards: token::Amount,
+    /// The total estimate of the rewards accumulated in `rewards_epoch`,
+    /// which are not yet paid out
+    pub total_estimated_rewards: token::Amount,
+    /// The epoch in which the estimated rewards have been accumulated
+    pub rewards_epoch: Option<Epoch>,
+}
+
+/// The staking of an account with a validator
+#[derive(Debug, Serialize)]
+pub struct ValidatorStaking {
+    /// The validator address
+    pub validator: Address,
+    /// The bonds to the validator
+    pub bonds: Vec<BondOverview>,
+    /// The unbonds from the validator
+    pub unbonds: Vec<UnbondOverview>,
+    /// The bonded amount, with slashes applied
+    pub bonded: token::Amount,
+    /// The bonded amount that is active in the current epoch
+    pub active: token::Amount,
+    /// The unbonded amount, with slashes applied
+    pub unbonded: token::Amount,
+    /// The unbonded amount that may be withdrawn in the current epoch
+    pub withdrawable: token::Amount,
+    /// The rewards that have accrued, but have not yet been claimed
+    pub unclaimed_rewards: token::Amount,
+    /// An estimate of the rewards accumulated in the rewards epoch, which are
+    /// not yet paid out
+    pub estimated_rewards: token::Amount,
+}
+
+/// A bond delta
+#[derive(Debug, Serialize)]
+pub struct BondOverview {
+    /// The epoch from which the bond is active
+    pub start_epoch: Epoch,
+    /// The bonded amount
+    pub amount: token::Amount,
+    /// The slashes applied to the bond
+    pub slashes: Vec<SlashOverview>,
+    /// The bonded amount with slashes applied
+    pub amount_after_slashes: token::Amount,
+    /// Is the bond active in the current epoch?
+    pub active: bool,
+}
+
+/// An unbond delta
+#[derive(Debug, Serialize)]
+pub struct UnbondOverview {
+    /// The epoch from which the unbonded bond was active
+    pub start_epoch: Epoch,
+    /// The epoch from which the unbond may be withdrawn
+    pub withdrawable_epoch: Epoch,
+    /// The unbonded amount
+    pub amount: token::Amount,
+    /// The slashes applied to the unbond
+    pub slashes: Vec<SlashOverview>,
+    /// The unbonded amount with slashes applied
+    pub amount_after_slashes: token::Amount,
+    /// May the unbond be withdrawn in the current epoch?
+    pub withdrawable: bool,
+}
+
+/// A slash applied to a bond or an unbond
+#[derive(Debug, Serialize)]
+pub struct SlashOverview {
+    /// The epoch in which the fault occurred
+    pub epoch: Epoch,
+    /// The type of the fault
+    pub r#type: String,
+    /// The slash rate in basis points
+    pub rate: u64,
+    /// The slashed amount
+    pub amount: token::Amount,
+}
+
+/// Query an overview of an account's PoS bonds, unbonds, slashes and rewards
+pub async fn query_staking(ctx: Context, args: args::QueryStaking) {
+    let client = HttpClient::new(args.query.ledger_address).unwrap();
+    let owner = ctx.get(&args.owner);
+    let validator = args.validator.map(|validator| ctx.get(&validator));
+    let overview = get_staking_overview(client, owner, validator).await;
+    if args.json {
+        let json = serde_json::to_string_pretty(&overview)
+            .expect("Serializing the staking overview shouldn't fail");
+        println!("{}", json);
+    } else {
+        let stdout = io::stdout();
+        let mut w = stdout.lock();
+        write_staking_overview(&overview, &mut w);
+    }
+}
+
+/// Get an overview of the `owner`'s PoS bonds, unbonds, slashes and rewards,
+/// optionally only with the given `validator`.
+pub async fn get_staking_overview(
+    client: HttpClient,
+    owner: Address,
+    validator: Option<Address>,
+) -> StakingOverview {
+    let epoch = get_epoch(client.clone()).await;
+    let is_included = |bond_id: &pos::BondId| match &validator {
+        Some(validator) => &bond_id.validator == validator,
+        None => true,
+    };
+    let mut validators: BTreeMap<Address, ValidatorStaking> = BTreeMap::new();
+
+    let bonds = query_storage_prefix::<pos::Bonds>(
+        client.clone(),
+        pos::bonds_for_source_prefix(&owner),
+    )
+    .await;
+    let bonds: Vec<(pos::BondId, pos::Bonds)> = bonds
+        .map(|bonds| {
+            bonds
+                .filter_map(|(key, bonds)| {
+                    let bond_id = pos::is_bond_key(&key)?;
+                    is_included(&bond_id).then(|| (bond_id, bonds))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let unbonds = query_storage_prefix::<pos::Unbonds>(
+        client.clone(),
+        pos::unbonds_for_source_prefix(&owner),
+    )
+    .await;
+    let unbonds: Vec<(pos::BondId, pos::Unbonds)> = unbonds
+        .map(|unbonds| {
+            unbonds
+                .filter_map(|(key, unbonds)| {
+                    let bond_id = pos::is_unbond_key(&key)?;
+                    is_included(&bond_id).then(|| (bond_id, unbonds))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let rewards = query_storage_prefix::<pos::BondRewards>(
+        client.clone(),
+        pos::bond_rewards_for_source_prefix(&owner),
+    )
+    .await;
+    let rewards: Vec<(pos::BondId, pos::BondRewards)> = rewards
+        .map(|rewards| {
+            rewards
+                .filter_map(|(key, rewards)| {
+                    let bond_id = pos::is_bond_rewards_key(&key)?;
+                    is_included(&bond_id).then(|| (bond_id, rewards))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    // Rewards accumulated in the current epoch, which are not yet paid out
+    let accumulator = query_storage_value::<pos::RewardsAccumulator>(
+        client.clone(),
+        pos::rewards_accumulator_key(),
+    )
+    .await;
+
+    let validator_addresses: BTreeSet<Address> = bonds
+        .iter()
+        .map(|(bond_id, _)| bond_id)
+        .chain(unbonds.iter().map(|(bond_id, _)| bond_id))
+        .chain(rewards.iter().map(|(bond_id, _)| bond_id))
+        .map(|bond_id| bond_id.validator.clone())
+        .collect();
+    let mut slashes: BTreeMap<Address, pos::Slashes> = BTreeMap::new();
+    for validator in validator_addresses {
+        let validator_slashes = query_storage_value::<pos::Slashes>(
+            client.clone(),
+            pos::validator_slashes_key(&validator),
+        )
+        .await
+        .unwrap_or_default();
+        validators.insert(
+            validator.clone(),
+            ValidatorStaking::new(validator.clone()),
+        );
+        slashes.insert(validator, validator_slashes);
+    }
+
+    for (bond_id, bonds) in &bonds {
+        let validator_slashes = &slashes[&bond_id.validator];
+        let staking = validators.get_mut(&bond_id.validator).unwrap();
+        for bond in bonds.iter() {
+            for (epoch_start, delta) in bond.deltas.iter().sorted() {
+                let (slashes, amount_after_slashes) = slashes_overview(
+                    validator_slashes,
+                    *delta,
+                    *epoch_start,
+                    None,
+                );
+                let start_epoch: Epoch = (*epoch_start).into();
+                let active = start_epoch <= epoch;
+                staking.bonded += amount_after_slashes;
+                if active {
+                    staking.active += amount_after_slashes;
+                }
+                staking.bonds.push(BondOverview {
+                    start_epoch,
+                    amount: *delta,
+                    slashes,
+                    amount_after_slashes,
+                    active,
+                });
+            }
+        }
+        if let Some(accumulator) = &accumulator {
+            let commission_rate =
+                query_storage_value::<pos::ValidatorCommissionRates>(
+                    client.clone(),
+                    pos::validator_commission_rate_key(&bond_id.validator),
+                )
+                .await
+                .and_then(|rates| rates.get(accumulator.epoch).copied())
+                .unwrap_or_default();
+            let total_deltas =
+                query_storage_value::<pos::ValidatorTotalDeltas>(
+                    client.clone(),
+                    pos::validator_total_deltas_key(&bond_id.validator),
+                )
+                .await;
+            let total_stake = total_deltas
+                .and_then(|deltas| deltas.get(accumulator.epoch))
+                .and_then(|stake| u64::try_from(stake).ok())
+                .unwrap_or_default();
+            staking.estimated_rewards += estimate_bond_rewards(
+                bond_id,
+                bonds,
+                validator_slashes,
+                accumulator,
+                commission_rate,
+                total_stake,
+            );
+        }
+    }
+
+    for (bond_id, unbonds) in &unbonds {
+        let validator_slashes = &slashes[&bond_id.validator];
+        let staking = validators.get_mut(&bond_id.validator).unwrap();
+        for unbond in unbonds.iter() {
+            for ((epoch_start, epoch_end), delta) in
+                unbond.deltas.iter().sorted()
+            {
+                let withdraw_epoch = *epoch_end + 1_u64;
+                let (slashes, amount_after_slashes) = slashes_overview(
+                    validator_slashes,
+                    *delta,
+                    *epoch_start,
+                    Some(withdraw_epoch),
+                );
+                let withdrawable_epoch: Epoch = withdraw_epoch.into();
+                let withdrawable = withdrawable_epoch <= epoch;
+                staking.unbonded += amount_after_slashes;
+                if withdrawable {
+                    staking.withdrawable += amount_after_slashes;
+                }
+                staking.unbonds.push(UnbondOverview {
+                    start_epoch: (*epoch_start).into(),
+                    withdrawable_epoch,
+                    amount: *delta,
+                    slashes,
+                    amount_after_slashes,
+                    withdrawable,
+                });
+            }
+        }
+    }
+
+    for (bond_id, rewards) in &rewards {
+        let staking = validators.get_mut(&bond_id.validator).unwrap();
+        staking.unclaimed_rewards += rewards.unclaimed;
+    }
+
+    let validators: Vec<ValidatorStaking> = validators.into_values().collect();
+    let sum = |amount: fn(&ValidatorStaking) -> token::Amount| {
+        validators
+            .iter()
+            .fold(token::Amount::default(), |sum, staking| {
+                sum + amount(staking)
+            })
+    };
+    StakingOverview {
+        owner,
+        epoch,
+        total_bonded: sum(|staking| staking.bonded),
+        total_active: sum(|staking| staking.active),
+        total_unbonded: sum(|staking| staking.unbonded),
+        total_withdrawable: sum(|staking| staking.withdrawable),
+        total_unclaimed_rewards: sum(|staking| staking.unclaimed_rewards),
+        total_estimated_rewards: sum(|staking| staking.estimated_rewards),
+        rewards_epoch: accumulator.map(|accumulator| accumulator.epoch.into()),
+        validators,
+    }
+}
+
+/// Write the slashes applied to a bond or an unbond.
+fn write_slashes(slashes: &[SlashOverview], w: &mut std::io::StdoutLock) {
+    for slash in slashes {
+        writeln!(
+            w,
+            "      ⚠ Slash: {}‱ for {} in epoch {}: {}",
+            slash.rate, slash.r#type, slash.epoch, slash.amount
+        )
+        .unwrap();
+    }
+}
+
+/// Write a human-readable staking overview.
+fn write_staking_overview(
+    overview: &StakingOverview,
+    w: &mut std::io::StdoutLock,
+) {
+    writeln!(
+        w,
+        "Staking of {} in epoch {}:",
+        overview.owner.encode(),
+        overview.epoch
+    )
+    .unwrap();
+    if overview.validators.is_empty() {
+        writeln!(w, "No bonds or unbonds found").unwrap();
+        return;
+    }
+    for staking in &overview.validators {
+        writeln!(w, "Validator {}:", staking.validator.encode()).unwrap();
+        if !staking.bonds.is_empty() {
+            writeln!(w, "  Bonds:").unwrap();
+        }
+        for bond in &staking.bonds {
+            let status = if bond.active { "Active" } else { "Pending" };
+            writeln!(
+                w,
+                "    {} from epoch {}: {}",
+                status, bond.start_epoch, bond.amount
+            )
+            .unwrap();
+            write_slashes(&bond.slashes, w);
+            if !bond.slashes.is_empty() {
+                writeln!(
+                    w,
+                    "      ⚠ After slashing: {}",
+                    bond.amount_after_slashes
+                )
+                .unwrap();
+            }
+        }
+        if !staking.unbonds.is_empty() {
+            writeln!(w, "  Unbonds:").unwrap();
+        }
+        for unbond in &staking.unbonds {
+            let status = if unbond.withdrawable {
+                "Withdrawable"
+            } else {
+                "Pending"
+            };
+            writeln!(
+                w,
+                "    {} from epoch {} (active from {}): {}",
+                status,
+                unbond.withdrawable_epoch,
+                unbond.start_epoch,
+                unbond.amount
+            )
+            .unwrap();
+            write_slashes(&unbond.slashes, w);
+            if !unbond.slashes.is_empty() {
+                writeln!(
+                    w,
+                    "      ⚠ After slashing: {}",
+                    unbond.amount_after_slashes
+                )
+                .unwrap();
+            }
+        }
+        writeln!(
+            w,
+            "  Bonded: {}, active: {}",
+            staking.bonded, staking.active
+        )
+        .unwrap();
+        writeln!(
+            w,
+            "  Unbonded: {}, withdrawable: {}",
+            staking.unbonded, staking.withdrawable
+        )
+        .unwrap();
+        writeln!(
+            w,
+            "  Unclaimed rewards: {}, estimated rewards: {}",
+            staking.unclaimed_rewards, staking.estimated_rewards
+        )
+        .unwrap();
+    }
+    writeln!(
+        w,
+        "Bonded total: {}, active: {}",
+        overview.total_bonded, overview.total_active
+    )
+    .unwrap();
+    writeln!(
+        w,
+        "Unbonded total: {}, withdrawable: {}",
+        overview.total_unbonded, overview.total_withdrawable
+    )
+    .unwrap();
+    writeln!(
+        w,
+        "Unclaimed rewards total: {}",
+        overview.total_unclaimed_rewards
+    )
+    .unwrap();
+    if let Some(rewards_epoch) = overview.rewards_epoch {
+        writeln!(
+            w,
+            "Estimated rewards total for epoch {}: {}",
+            rewards_epoch, overview.total_estimated_rewards
+        )
+        .unwrap();
+    }
+}
+pub async fn query_spending_policy(
+    ctx: Context,
+    args: args::QuerySpendingPolicy,
+) {
+    let owner = ctx.get(&args.owner);
+    let policy =
+        get_spending_policy(&owner, args.query.ledger_address.clone()).await;
+    let policy = match policy {
+        Some(policy) => policy,
+        None => {
+            println!("No spending policy found for {}", owner.encode());
+            return;
+        }
+    };
+    let client = HttpClient::new(args.query.ledger_address.clone()).unwrap();
+    let (_ctx, epoch) = query_epoch(ctx, args.query).await;
+
+    println!("Spending policy of {}:", owner.encode());
+    if policy.limits.is_empty() {
+        println!("  Limits: none");
+    } else {
+        println!("  Limits per epoch:");
+    }
+    for (token, limit) in &policy.limits {
+        let spent_key = spending_policy::spent_key(&owner, token);
+        let spent = query_storage_value::<spending_policy::Spent>(
+            client.clone(),
+            spent_key,
+        )
+        .await
+        .map(|spent| spent.in_epoch(epoch))
+        .unwrap_or_default();
+        println!(
+            "    {}: limit {}, spent {} in epoch {}",
+            token.encode(),
+            limit,
+            spent,
+            epoch
+        );
+    }
+    if policy.allowed_targets.is_empty() {
+        println!("  Allowed targets: any");
+    } else {
+        println!("  Allowed targets:");
+        for target in &policy.allowed_targets {
+            println!("    {}", target.encode());
+        }
+    }
+    match &policy.guardian {
+        Some(guardian) => println!("  Guardian key: {}", guardian),
+        None => println!("  Guardian key: none"),
+    }
+}
+
+/// Query a protocol parameters proposal and its votes
+pub async fn query_parameters_proposal(
+    ctx: Context,
+    args: args::QueryParametersProposal,
+) {
+    let client = HttpClient::new(args.query.ledger_address.clone()).unwrap();
+    let (_ctx, epoch) = query_epoch(ctx, args.query).await;
+    let id = args.proposal_id;
+    let proposal = query_storage_value::<parameters::ParametersProposal>(
+        client.clone(),
+        parameters::proposal_key(id),
+    )
+    .await;
+    let proposal = match proposal {
+        Some(proposal) => proposal,
+        None => {
+            println!("No parameters proposal found with ID {}", id);
+            return;
+        }
+    };
+    let params = &proposal.parameters;
+    println!("Parameters proposal {}:", id);
+    println!("  Author: {}", proposal.author.encode());
+    println!(
+        "  Voting period: epochs {} to {} (exclusive)",
+        proposal.voting_start_epoch, proposal.voting_end_epoch
+    );
+    println!("  Activation epoch: {}", proposal.activation_epoch);
+    println!("  Proposed parameters:");
+    println!(
+        "    Minimum number of blocks in an epoch: {}",
+        params.epoch_duration.min_num_of_blocks
+    );
+    println!(
+        "    Minimum duration of an epoch: {}s",
+        params.epoch_duration.min_duration.0
+    );
+    println!(
+        "    Transaction memory max pages: {}",
+        params.wasm_limits.tx_memory_max_pages
+    );
+    println!(
+        "    Validity predicate memory max pages: {}",
+        params.wasm_limits.vp_memory_max_pages
+    );
+    println!("    WASM stack limit: {}", params.wasm_limits.stack_limit);
+
+    let votes = query_storage_prefix::<parameters::ProposalVote>(
+        client.clone(),
+        parameters::proposal_votes_prefix(id),
+    )
+    .await;
+    let mut has_votes = false;
+    if let Some(votes) = votes {
+        for (key, vote) in votes {
+            if let Some((_id, voter)) = parameters::is_proposal_vote_key(&key) {
+                if !has_votes {
+                    println!("  Votes:");
+                    has_votes = true;
+                }
+                println!("    {}: {}", voter.encode(), vote);
+            }
+        }
+    }
+    if !has_votes {
+        println!("  Votes: none");
+    }
+
+    let applied = query_storage_value::<Epoch>(
+        client,
+        parameters::proposal_applied_key(id),
+    )
+    .await;
+    match applied {
+        Some(applied) => println!("  Applied in epoch {}", applied),
+        None if epoch < proposal.voting_end_epoch => {
+            println!("  Status: voting (current epoch {})", epoch)
+        }
+        None => println!("  Status: not applied (current epoch {})", epoch),
+    }
+}
+
+/// Query a governance proposal and its votes, or all the proposals if no ID
+/// is given
+pub async fn query_proposal(ctx: Context, args: args::QueryProposal) {
+    let client = HttpClient::new(args.query.ledger_address.clone()).unwrap();
+    let (_ctx, epoch) = query_epoch(ctx, args.query).await;
+    match args.proposal_id {
+        Some(id) => {
+            if !print_proposal(client, id, epoch).await {
+                println!("No governance proposal found with ID {}", id);
+            }
+        }
+        None => {
+            let params = query_storage_value::<governance::GovParams>(
+                client.clone(),
+                governance::params_key(),
+            )
+            .await
+            .expect("Governance parameters should always exist in storage");
+            println!("Governance parameters:");
+            println!(
+                "  Minimum proposal deposit: {}",
+                params.min_proposal_deposit
+            );
+            println!(
+                "  Voting period: {} to {} epochs",
+                params.min_voting_period, params.max_voting_period
+            );
+            println!(
+                "  Maximum proposal code size: {} bytes",
+                params.max_proposal_code_size
+            );
+            println!(
+                "  Maximum proposal content size: {} bytes",
+                params.max_proposal_content_size
+            );
+            println!("  Quorum: {}", params.quorum);
+            println!("  Threshold: {}", params.threshold);
+
+            let counter = query_storage_value::<u64>(
+                client.clone(),
+                governance::counter_key(),
+            )
+            .await
+            .unwrap_or_default();
+            if counter == 0 {
+                println!("No governance proposals found");
+            }
+            for id in 0..counter {
+                print_proposal(client.clone(), id, epoch).await;
+            }
+        }
+    }
+}
+
+/// Query the treasury's parameters and balances
+pub async fn query_treasury(_ctx: Context, args: args::Query) {
+    let client = HttpClient::new(args.ledger_address).unwrap();
+    let params = query_storage_value::<treasury::TreasuryParams>(
+        client.clone(),
+        treasury::params_key(),
+    )
+    .await
+    .expect("Treasury parameters should always exist in storage");
+    println!("Treasury {}:", treasury::ADDRESS.encode());
+    println!("  Fee share: {}", params.fee_share);
+    let mut found_any = false;
+    for (token, currency_code) in address::tokens() {
+        let key = token::balance_key(&token, &treasury::ADDRESS);
+        if let Some(balance) =
+            query_storage_value::<token::Amount>(client.clone(), key).await
+        {
+            if !found_any {
+                println!("  Balances:");
+                found_any = true;
+            }
+            println!("    {}: {}", currency_code, balance);
+        }
+    }
+    if !found_any {
+        println!("  Balances: none");
+    }
+}
+
+/// Query the inflation parameters, its state and the native token supply
+pub async fn query_inflation(_ctx: Context, args: args::Query) {
+    let client = HttpClient::new(args.ledger_address).unwrap();
+    let params = query_storage_value::<inflation::InflationParams>(
+        client.clone(),
+        inflation::params_key(),
+    )
+    .await
+    .expect("Inflation parameters should always exist in storage");
+    let state = query_storage_value::<inflation::InflationState>(
+        client.clone(),
+        inflation::state_key(),
+    )
+    .await
+    .expect("Inflation state should always exist in storage");
+    let supply = query_storage_value::<token::Amount>(
+        client.clone(),
+        inflation::supply_key(),
+    )
+    .await
+    .expect("Token supply should always exist in storage");
+    println!("Inflation parameters:");
+    println!("  Target staking ratio: {}", params.target_staking_ratio);
+    println!("  Minimum rate: {}", params.min_rate);
+    println!("  Maximum rate: {}", params.max_rate);
+    println!("  Proportional gain: {}", params.p_gain);
+    println!("  Derivative gain: {}", params.d_gain);
+    println!("  Epochs per year: {}", params.epochs_per_year);
+    println!("  Treasury share: {}", params.treasury_share);
+    println!("Inflation as of epoch {}:", state.epoch);
+    println!("  Annual rate: {}", state.rate);
+    println!("  Staking ratio: {}", state.staking_ratio);
+    println!("  Minted into the treasury: {}", state.treasury_minted);
+    println!("  Minted for PoS rewards: {}", state.pos_minted);
+    println!("Native token supply: {}", supply);
+}
+
+/// Print a governance proposal with its votes and status. Returns `false` if
+/// the proposal doesn't exist.
+async fn print_proposal(client: HttpClient, id: u64, epoch: Epoch) -> bool {
+    let proposal = query_storage_value::<governance::Proposal>(
+        client.clone(),
+        governance::proposal_key(id),
+    )
+    .await;
+    let proposal = match proposal {
+        Some(proposal) => proposal,
+        None => return false,
+    };
+    println!("Governance proposal {}:", id);
+    println!("  Author: {}", proposal.author.encode());
+    println!("  Content: {}", proposal.content);
+    println!(
+        "  Voting period: epochs {} to {} (exclusive)",
+        proposal.voting_start_epoch, proposal.voting_end_epoch
+    );
+    println!("  Grace epoch: {}", proposal.grace_epoch);
+    let funds = query_storage_value::<token::Amount>(
+        client.clone(),
+        governance::proposal_funds_key(id),
+    )
+    .await
+    .unwrap_or_default();
+    println!("  Deposit: {}", funds);
+    let code = query_storage_value::<Vec<u8>>(
+        client.clone(),
+        governance::proposal_code_key(id),
+    )
+    .await;
+    match code {
+        Some(code) => println!("  Code: {} bytes", code.len()),
+        None => println!("  Code: none"),
+    }
+
+    let votes = query_storage_prefix::<parameters::ProposalVote>(
+        client.clone(),
+        governance::proposal_votes_prefix(id),
+    )
+    .await;
+    let mut has_votes = false;
+    if let Some(votes) = votes {
+        for (key, vote) in votes {
+            if let Some((_id, voter)) = governance::is_proposal_vote_key(&key) {
+                if !has_votes {
+                    println!("  Votes:");
+                    has_votes = true;
+                }
+                println!("    {}: {}", voter.encode(), vote);
+            }
+        }
+    }
+    if !has_votes {
+        println!("  Votes: none");
+    }
+
+    let result = query_storage_value::<governance::ProposalResult>(
+        client,
+        governance::proposal_result_key(id),
+    )
+    .await;
+    match result {
+        Some(result) => {
+            let tally = &result.tally;
+            println!(
+                "  Tally: yay {}, nay {}, total voting power {}",
+                tally.yay, tally.nay, tally.total
+            );
+            let status = if result.passed { "passed" } else { "rejected" };
+            println!("  Status: {}", status);
+            match result.code_accepted {
+                Some(true) => println!("  Code: executed"),
+                Some(false) => println!("  Code: rejected"),
+                None => {}
+            }
+        }
+        None if epoch < proposal.voting_start_epoch => {
+            println!("  Status: pending (current epoch {})", epoch)
+        }
+        None if epoch < proposal.voting_end_epoch => {
+            println!("  Status: voting (current epoch {})", epoch)
+        }
+        None => println!("  Status: ended (current epoch {})", epoch),
+    }
+    true
+}
+
 /// Dry run a transaction
 pub async fn dry_run_tx(
     ledger_address: &tendermint::net::Address,
@@ -696,6 +1674,26 @@ pub async fn get_public_key(
     query_storage_value(client, key).await
 }
 
+/// Get the multi-signature key set of the given address, if any.
+pub async fn get_multisig_keys(
+    address: &Address,
+    ledger_address: tendermint::net::Address,
+) -> Option<multisig::ThresholdKeys> {
+    let client = HttpClient::new(ledger_address).unwrap();
+    let key = multisig::keys_key(address);
+    query_storage_value(client, key).await
+}
+
+/// Get the spending policy of the given address, if any.
+pub async fn get_spending_policy(
+    address: &Address,
+    ledger_address: tendermint::net::Address,
+) -> Option<SpendingPolicy> {
+    let client = HttpClient::new(ledger_address).unwrap();
+    let key = spending_policy::policy_key(address);
+    query_storage_value(client, key).await
+}
+
 /// Check if the given address is a known validator.
 pub async fn is_validator(
     address: &Address,
@@ -750,7 +1748,9 @@ fn apply_slashes(
             )
             .unwrap();
             let raw_delta: u64 = delta.into();
-            let current_slashed = token::Amount::from(slash.rate * raw_delta);
+            let current_slashed = token::Amount::from(
+                slash.rate.portion_of(raw_delta).unwrap_or(raw_delta),
+            );
             slashed += current_slashed;
             delta -= current_slashed;
         }
@@ -762,6 +1762,82 @@ fn apply_slashes(
     delta
 }
 
+/// Find the slashes starting from `epoch_start` until (optionally)
+/// `withdraw_epoch` and apply them to the token amount `delta`.
+fn slashes_overview(
+    slashes: &[Slash],
+    mut delta: token::Amount,
+    epoch_start: PosEpoch,
+    withdraw_epoch: Option<PosEpoch>,
+) -> (Vec<SlashOverview>, token::Amount) {
+    let mut applied = Vec::new();
+    for slash in slashes {
+        if slash.epoch >= epoch_start
+            && slash.epoch < withdraw_epoch.unwrap_or_else(|| u64::MAX.into())
+        {
+            let raw_delta: u64 = delta.into();
+            let amount = token::Amount::from(
+                slash.rate.portion_of(raw_delta).unwrap_or(raw_delta),
+            );
+            delta -= amount;
+            applied.push(SlashOverview {
+                epoch: slash.epoch.into(),
+                r#type: slash.r#type.to_string(),
+                rate: slash.rate.into(),
+                amount,
+            });
+        }
+    }
+    (applied, delta)
+}
+
+/// Estimate a bond's share of the rewards that have been accumulated by its
+/// validator, but that are not yet paid out. The validator's commission is
+/// included in the estimate for a self-bond.
+fn estimate_bond_rewards(
+    bond_id: &pos::BondId,
+    bonds: &Bonds,
+    slashes: &[Slash],
+    accumulator: &pos::RewardsAccumulator,
+    commission_rate: BasisPoints,
+    total_stake: u64,
+) -> token::Amount {
+    // A validator slashed for a fault in the epoch forfeits its rewards
+    if slashes.iter().any(|slash| slash.epoch == accumulator.epoch) {
+        return 0.into();
+    }
+    let reward: u64 = accumulator
+        .rewards
+        .get(&bond_id.validator)
+        .copied()
+        .unwrap_or_default()
+        .into();
+    if reward == 0 || total_stake == 0 {
+        return 0.into();
+    }
+    let stake: u64 = match bonds.get(accumulator.epoch) {
+        Some(bond) => bond
+            .deltas
+            .iter()
+            .filter(|(epoch_start, _)| **epoch_start <= accumulator.epoch)
+            .map(|(epoch_start, delta)| {
+                let (_, delta) =
+                    slashes_overview(slashes, *delta, *epoch_start, None);
+                u64::from(delta)
+            })
+            .sum(),
+        None => 0,
+    };
+    let commission = commission_rate.portion_of(reward).unwrap_or(reward);
+    let share = ((reward - commission) as u128 * stake as u128
+        / total_stake as u128) as u64;
+    if bond_id.source == bond_id.validator {
+        token::Amount::from(share + commission)
+    } else {
+        token::Amount::from(share)
+    }
+}
+
 /// Process the result of a blonds query to determine total bonds
 /// and total active bonds. This includes taking into account
 /// an aggregation of slashes since the start of the given epoch.
@@ -4,12 +4,24 @@
 use std::rc::Rc;
 
 use anoma::types::address::{Address, ImplicitAddress};
-use anoma::types::key::ed25519::Keypair;
+use anoma::types::key::ed25519::{Keypair, Signature};
+use serde::{Deserialize, Serialize};
 
 use super::rpc;
 use crate::cli;
 use crate::wallet::Wallet;
 
+/// A signature of a transaction made with one of the keys of a
+/// multi-signature account, collected offline. The signatures of a
+/// transaction are combined before it's submitted.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartialSignature {
+    /// The index of the signing key in the account's key set
+    pub index: u8,
+    /// The signature of the unsigned transaction
+    pub signature: Signature,
+}
+
 /// Find the public key for the given address and try to load the keypair
 /// for it from the wallet. Panics if the key cannot be found or loaded.
 pub async fn find_keypair(
@@ -1,11 +1,23 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::convert::TryFrom;
 
-use anoma::ledger::pos::{BondId, Bonds, Unbonds};
+use anoma::ledger::pos::{
+    BondId, BondRewards, Bonds, Unbonds, ValidatorMetadata,
+};
 use anoma::proto::Tx;
 use anoma::types::address::{self, Address};
+use anoma::types::key::ed25519::{self, Signature};
+use anoma::types::key::multisig::ThresholdKeys;
+use anoma::types::spending_policy::{SpendingPolicy, GUARDIAN_SIG_INDEX};
+use anoma::types::storage::Epoch;
+use anoma::types::time::DurationSecs;
 use anoma::types::token;
-use anoma::types::transaction::{pos, InitAccount, InitValidator, UpdateVp};
+use anoma::types::transaction::{
+    governance, parameters, pos, InitAccount, InitMultisigAccount,
+    InitValidator, UpdateKey, UpdateSpendingPolicy, UpdateVp,
+};
+use anoma::vm::WasmCodeKind;
 use anoma::{ledger, vm};
 use async_std::io::{self, WriteExt};
 use borsh::BorshSerialize;
@@ -23,16 +35,42 @@ use crate::client::tendermint_websocket_client::{
 use crate::node::ledger::tendermint_node;
 
 const TX_INIT_ACCOUNT_WASM: &str = "tx_init_account.wasm";
+const TX_INIT_MULTISIG_ACCOUNT_WASM: &str = "tx_init_multisig_account.wasm";
 const TX_INIT_VALIDATOR_WASM: &str = "tx_init_validator.wasm";
 const TX_UPDATE_VP_WASM: &str = "tx_update_vp.wasm";
+const TX_UPDATE_KEY_WASM: &str = "tx_update_key.wasm";
+const TX_UPDATE_SPENDING_POLICY_WASM: &str = "tx_update_spending_policy.wasm";
 const TX_TRANSFER_WASM: &str = "tx_transfer.wasm";
 const VP_USER_WASM: &str = "vp_user.wasm";
+const VP_MULTISIG_WASM: &str = "vp_multisig.wasm";
 const TX_BOND_WASM: &str = "tx_bond.wasm";
 const TX_UNBOND_WASM: &str = "tx_unbond.wasm";
 const TX_WITHDRAW_WASM: &str = "tx_withdraw.wasm";
+const TX_REDELEGATE_WASM: &str = "tx_redelegate.wasm";
+const TX_CHANGE_COMMISSION_WASM: &str = "tx_change_commission.wasm";
+const TX_CHANGE_CONSENSUS_KEY_WASM: &str = "tx_change_consensus_key.wasm";
+const TX_CLAIM_REWARDS_WASM: &str = "tx_claim_rewards.wasm";
+const TX_UNJAIL_WASM: &str = "tx_unjail.wasm";
+const TX_UPDATE_VALIDATOR_METADATA_WASM: &str =
+    "tx_update_validator_metadata.wasm";
+const TX_PROPOSE_PARAMETERS_WASM: &str = "tx_propose_parameters.wasm";
+const TX_VOTE_PARAMETERS_WASM: &str = "tx_vote_parameters.wasm";
+const TX_APPLY_PARAMETERS_WASM: &str = "tx_apply_parameters.wasm";
+const TX_INIT_PROPOSAL_WASM: &str = "tx_init_proposal.wasm";
+const TX_VOTE_PROPOSAL_WASM: &str = "tx_vote_proposal.wasm";
+
+/// The maximum validator commission rate in basis points
+const MAX_COMMISSION_RATE: u64 = 10_000;
 
 pub async fn submit_custom(ctx: Context, args: args::TxCustom) {
     let tx_code = ctx.read_wasm(args.code_path);
+    // Validate the tx code
+    if let Err(err) = vm::validate_untrusted_code(&tx_code, WasmCodeKind::Tx) {
+        eprintln!("Transaction code validation failed with {}", err);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
     let data = args.data_path.map(|data_path| {
         std::fs::read(data_path).expect("Expected a file at given data path")
     });
@@ -81,7 +119,7 @@ pub async fn submit_update_vp(ctx: Context, args: args::TxUpdateVp) {
 
     let vp_code = ctx.read_wasm(args.vp_code_path);
     // Validate the VP code
-    if let Err(err) = vm::validate_untrusted_wasm(&vp_code) {
+    if let Err(err) = vm::validate_untrusted_code(&vp_code, WasmCodeKind::Vp) {
         eprintln!("Validity predicate code validation failed with {}", err);
         if !args.tx.force {
             safe_exit(1)
@@ -98,6 +136,178 @@ pub async fn submit_update_vp(ctx: Context, args: args::TxUpdateVp) {
     submit_tx(ctx, &args.tx, tx).await;
 }
 
+pub async fn submit_update_key(mut ctx: Context, args: args::TxUpdateKey) {
+    let addr = ctx.get(&args.addr);
+
+    // Check that the address is established and exists on chain
+    match &addr {
+        Address::Established(_) => {
+            let exists =
+                rpc::known_address(&addr, args.tx.ledger_address.clone()).await;
+            if !exists {
+                eprintln!("The address {} doesn't exist on chain.", addr);
+                if !args.tx.force {
+                    safe_exit(1)
+                }
+            }
+        }
+        Address::Implicit(_) => {
+            eprintln!(
+                "A public key of an implicit address cannot be updated. You \
+                 can use an established address for this purpose."
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+        Address::Internal(_) => {
+            eprintln!("An internal address doesn't have a public key.");
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let new_pk = ctx.get_cached(&args.public_key);
+    let old_pk =
+        rpc::get_public_key(&addr, args.tx.ledger_address.clone()).await;
+    if old_pk.as_ref() == Some(&new_pk) {
+        eprintln!("The account {} already uses the given public key.", addr);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_UPDATE_KEY_WASM);
+
+    let data = UpdateKey {
+        addr: addr.clone(),
+        public_key: new_pk.clone(),
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    // The tx is signed with the current key of the account
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.addr)).await;
+    let (mut ctx, _) = submit_tx(ctx, &args.tx, tx).await;
+
+    if args.tx.dry_run || args.tx.unsigned_tx_path.is_some() {
+        return;
+    }
+    // Only re-associate the wallet keys once the update has been applied
+    let applied_pk =
+        rpc::get_public_key(&addr, args.tx.ledger_address.clone()).await;
+    match (old_pk, applied_pk) {
+        (Some(old_pk), Some(applied_pk)) if applied_pk == new_pk => {
+            match ctx.wallet.rotate_key(&old_pk, &new_pk) {
+                Some(alias) => {
+                    println!(
+                        "The key alias \"{}\" is now associated with the new \
+                         public key.",
+                        alias
+                    );
+                    ctx.wallet
+                        .save()
+                        .unwrap_or_else(|err| eprintln!("{}", err));
+                }
+                None => {
+                    eprintln!(
+                        "Warning: The old and the new keypairs are not both \
+                         in the wallet, no key alias has been updated."
+                    );
+                }
+            }
+        }
+        _ => {
+            eprintln!(
+                "The public key of the account {} has not been updated.",
+                addr
+            );
+        }
+    }
+}
+
+pub async fn submit_update_spending_policy(
+    mut ctx: Context,
+    args: args::TxUpdateSpendingPolicy,
+) {
+    let addr = ctx.get(&args.addr);
+
+    // Check that the address is established and exists on chain
+    if let Address::Established(_) = &addr {
+        let exists =
+            rpc::known_address(&addr, args.tx.ledger_address.clone()).await;
+        if !exists {
+            eprintln!("The address {} doesn't exist on chain.", addr);
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    } else {
+        eprintln!(
+            "A spending policy can only be set for an established address."
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let policy = if args.remove {
+        None
+    } else {
+        let policy = SpendingPolicy {
+            limits: args
+                .limits
+                .iter()
+                .map(|limit| (ctx.get(&limit.token), limit.amount))
+                .collect(),
+            allowed_targets: args
+                .allowed_targets
+                .iter()
+                .map(|target| ctx.get(target))
+                .collect(),
+            guardian: ctx.get_opt_cached(&args.guardian_key),
+        };
+        if policy.is_empty() {
+            eprintln!(
+                "The spending policy is empty. Use the --remove flag to \
+                 remove the policy."
+            );
+            safe_exit(1)
+        }
+        Some(policy)
+    };
+
+    // If the current policy has a guardian, the tx must be co-signed
+    let current_policy =
+        rpc::get_spending_policy(&addr, args.tx.ledger_address.clone()).await;
+    let has_guardian = current_policy
+        .map(|policy| policy.guardian.is_some())
+        .unwrap_or_default();
+    if has_guardian && args.tx.unsigned_tx_path.is_none() {
+        eprintln!(
+            "The current spending policy has a guardian key, which has to \
+             co-sign this transaction. Save the transaction with \
+             --unsigned-tx-path, sign it with `multisig sign` with the \
+             account's key at key index 0 and with the guardian key at key \
+             index {} and submit it with `multisig submit`.",
+            GUARDIAN_SIG_INDEX
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_UPDATE_SPENDING_POLICY_WASM);
+
+    let data = UpdateSpendingPolicy { addr, policy };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.addr)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
 pub async fn submit_init_account(mut ctx: Context, args: args::TxInitAccount) {
     let public_key = ctx.get_cached(&args.public_key);
     let vp_code = args
@@ -105,7 +315,7 @@ pub async fn submit_init_account(mut ctx: Context, args: args::TxInitAccount) {
         .map(|path| ctx.read_wasm(path))
         .unwrap_or_else(|| ctx.read_wasm(VP_USER_WASM));
     // Validate the VP code
-    if let Err(err) = vm::validate_untrusted_wasm(&vp_code) {
+    if let Err(err) = vm::validate_untrusted_code(&vp_code, WasmCodeKind::Vp) {
         eprintln!("Validity predicate code validation failed with {}", err);
         if !args.tx.force {
             safe_exit(1)
@@ -125,6 +335,161 @@ pub async fn submit_init_account(mut ctx: Context, args: args::TxInitAccount) {
     save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
 }
 
+pub async fn submit_init_multisig_account(
+    mut ctx: Context,
+    args: args::TxInitMultisigAccount,
+) {
+    let public_keys = args
+        .public_keys
+        .iter()
+        .map(|public_key| ctx.get_cached(public_key))
+        .collect();
+    let keys =
+        ThresholdKeys::new(args.threshold, public_keys).unwrap_or_else(|err| {
+            eprintln!("Invalid multi-signature key set: {}", err);
+            safe_exit(1)
+        });
+    let vp_code = args
+        .vp_code_path
+        .map(|path| ctx.read_wasm(path))
+        .unwrap_or_else(|| ctx.read_wasm(VP_MULTISIG_WASM));
+    // Validate the VP code
+    if let Err(err) = vm::validate_untrusted_code(&vp_code, WasmCodeKind::Vp) {
+        eprintln!("Validity predicate code validation failed with {}", err);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_INIT_MULTISIG_ACCOUNT_WASM);
+    let data = InitMultisigAccount { keys, vp_code };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.source)).await;
+    let (ctx, initialized_accounts) = submit_tx(ctx, &args.tx, tx).await;
+    save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
+}
+
+/// Sign an unsigned transaction with one of the keys of a multi-signature
+/// account and write the signature into a file.
+pub fn sign_multisig(mut ctx: Context, args: args::MultisigSign) {
+    let tx = read_unsigned_tx(&args.tx_path);
+    let keypair = ctx.get_cached(&args.signing_key);
+    let signature = ed25519::sign_tx_partial(&keypair, &tx);
+    let partial_sig = signing::PartialSignature {
+        index: args.key_index,
+        signature,
+    };
+    let output_path = args.output_path.unwrap_or_else(|| {
+        let mut path = args.tx_path.clone().into_os_string();
+        path.push(format!(".{}.sig", args.key_index));
+        path.into()
+    });
+    let json = serde_json::to_string_pretty(&partial_sig)
+        .expect("Encoding a signature shouldn't fail");
+    std::fs::write(&output_path, json).unwrap_or_else(|err| {
+        eprintln!(
+            "Failed to write the signature to {}: {}",
+            output_path.to_string_lossy(),
+            err
+        );
+        safe_exit(1)
+    });
+    println!(
+        "Signature with key index {} saved to {}.",
+        args.key_index,
+        output_path.to_string_lossy()
+    );
+}
+
+/// Combine the signatures of an unsigned transaction for a multi-signature
+/// account and submit it.
+pub async fn submit_multisig(ctx: Context, args: args::MultisigSubmit) {
+    let tx = read_unsigned_tx(&args.tx_path);
+    let mut sigs: BTreeMap<u8, Signature> = BTreeMap::new();
+    for path in &args.signature_paths {
+        let partial_sig: signing::PartialSignature = std::fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|json| {
+                serde_json::from_slice(&json).map_err(|err| err.to_string())
+            })
+            .unwrap_or_else(|err| {
+                eprintln!(
+                    "Failed to read a signature from {}: {}",
+                    path.to_string_lossy(),
+                    err
+                );
+                safe_exit(1)
+            });
+        if sigs
+            .insert(partial_sig.index, partial_sig.signature)
+            .is_some()
+        {
+            eprintln!(
+                "Found more than one signature with key index {}.",
+                partial_sig.index
+            );
+            safe_exit(1)
+        }
+    }
+    if sigs.is_empty() {
+        eprintln!("At least one signature is required.");
+        safe_exit(1)
+    }
+    let signed_tx = ed25519::attach_tx_sigs(tx, sigs.clone());
+
+    // Check the signatures against the account's key set
+    if let Some(owner) = ctx.get_opt(&args.owner) {
+        let keys =
+            rpc::get_multisig_keys(&owner, args.tx.ledger_address.clone())
+                .await
+                .unwrap_or_else(|| {
+                    eprintln!(
+                        "No multi-signature key set found for the address {}",
+                        owner.encode()
+                    );
+                    safe_exit(1)
+                });
+        let valid = keys.count_valid_sigs(&sigs, |pk, sig| {
+            ed25519::verify_tx_sig(pk, &signed_tx, sig).is_ok()
+        });
+        if valid < keys.threshold as usize {
+            eprintln!(
+                "The transaction has {} valid signatures, but the account {} \
+                 requires {}.",
+                valid,
+                owner.encode(),
+                keys.threshold
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let (ctx, initialized_accounts) = submit_tx(ctx, &args.tx, signed_tx).await;
+    save_initialized_accounts(ctx, &args.tx, initialized_accounts).await;
+}
+
+/// Read an unsigned transaction written with the `--unsigned-tx-path`
+/// argument.
+fn read_unsigned_tx(path: &std::path::Path) -> Tx {
+    std::fs::read(path)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| {
+            Tx::try_from(&bytes[..]).map_err(|err| err.to_string())
+        })
+        .unwrap_or_else(|err| {
+            eprintln!(
+                "Failed to read a transaction from {}: {}",
+                path.to_string_lossy(),
+                err
+            );
+            safe_exit(1)
+        })
+}
+
 pub async fn submit_init_validator(
     mut ctx: Context,
     args::TxInitValidator {
@@ -135,6 +500,11 @@ pub async fn submit_init_validator(
         rewards_account_key,
         validator_vp_code_path,
         rewards_vp_code_path,
+        commission_rate,
+        moniker,
+        description,
+        website,
+        contact,
         unsafe_dont_encrypt,
     }: args::TxInitValidator,
 ) {
@@ -180,7 +550,9 @@ pub async fn submit_init_validator(
         .map(|path| ctx.read_wasm(path))
         .unwrap_or_else(|| ctx.read_wasm(VP_USER_WASM));
     // Validate the validator VP code
-    if let Err(err) = vm::validate_untrusted_wasm(&validator_vp_code) {
+    if let Err(err) =
+        vm::validate_untrusted_code(&validator_vp_code, WasmCodeKind::Vp)
+    {
         eprintln!(
             "Validator validity predicate code validation failed with {}",
             err
@@ -193,7 +565,9 @@ pub async fn submit_init_validator(
         .map(|path| ctx.read_wasm(path))
         .unwrap_or_else(|| ctx.read_wasm(VP_USER_WASM));
     // Validate the rewards VP code
-    if let Err(err) = vm::validate_untrusted_wasm(&rewards_vp_code) {
+    if let Err(err) =
+        vm::validate_untrusted_code(&rewards_vp_code, WasmCodeKind::Vp)
+    {
         eprintln!(
             "Staking reward account validity predicate code validation failed \
              with {}",
@@ -203,6 +577,31 @@ pub async fn submit_init_validator(
             safe_exit(1)
         }
     }
+    if commission_rate > MAX_COMMISSION_RATE {
+        eprintln!(
+            "The commission rate {} must not exceed {} basis points.",
+            commission_rate, MAX_COMMISSION_RATE
+        );
+        if !tx_args.force {
+            safe_exit(1)
+        }
+    }
+    let metadata = ValidatorMetadata {
+        moniker,
+        description,
+        website,
+        contact,
+    };
+    if let Some(field) = metadata.find_too_long_field() {
+        eprintln!(
+            "The validator's {} must not be longer than {} bytes.",
+            field,
+            ValidatorMetadata::MAX_FIELD_LEN
+        );
+        if !tx_args.force {
+            safe_exit(1)
+        }
+    }
     let tx_code = ctx.read_wasm(TX_INIT_VALIDATOR_WASM);
 
     let data = InitValidator {
@@ -211,6 +610,11 @@ pub async fn submit_init_validator(
         rewards_account_key,
         validator_vp_code,
         rewards_vp_code,
+        commission_rate,
+        moniker: metadata.moniker,
+        description: metadata.description,
+        website: metadata.website,
+        contact: metadata.contact,
     };
     let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
     let tx = Tx::new(tx_code, Some(data));
@@ -529,6 +933,83 @@ pub async fn submit_unbond(ctx: Context, args: args::Unbond) {
     submit_tx(ctx, &args.tx, tx).await;
 }
 
+pub async fn submit_redelegate(ctx: Context, args: args::Redelegate) {
+    let src_validator = ctx.get(&args.src_validator);
+    let dest_validator = ctx.get(&args.dest_validator);
+    // Check that both validator addresses exist on chain
+    for validator in [&src_validator, &dest_validator] {
+        let is_validator =
+            rpc::is_validator(validator, args.tx.ledger_address.clone()).await;
+        if !is_validator {
+            eprintln!(
+                "The address {} doesn't belong to any known validator \
+                 account.",
+                validator
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+    if src_validator == dest_validator {
+        eprintln!("The source and destination validators must be different.");
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let source = ctx.get(&args.source);
+    let tx_code = ctx.read_wasm(TX_REDELEGATE_WASM);
+
+    // Check the source's current bond amount
+    let bond_id = BondId {
+        source: source.clone(),
+        validator: src_validator.clone(),
+    };
+    let bond_key = ledger::pos::bond_key(&bond_id);
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let bonds = rpc::query_storage_value::<Bonds>(client, bond_key).await;
+    match bonds {
+        Some(bonds) => {
+            let mut bond_amount: token::Amount = 0.into();
+            for bond in bonds.iter() {
+                for delta in bond.deltas.values() {
+                    bond_amount += *delta;
+                }
+            }
+            if args.amount > bond_amount {
+                eprintln!(
+                    "The total bonds of the source {} is lower than the \
+                     amount to be redelegated. Amount to redelegate is {} and \
+                     the total bonds is {}.",
+                    source, args.amount, bond_amount
+                );
+                if !args.tx.force {
+                    safe_exit(1)
+                }
+            }
+        }
+        None => {
+            eprintln!("No bonds found");
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let data = pos::Redelegation {
+        src_validator,
+        dest_validator,
+        amount: args.amount,
+        source,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.source)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
 pub async fn submit_withdraw(ctx: Context, args: args::Withdraw) {
     let (ctx, epoch) = rpc::query_epoch(
         ctx,
@@ -601,43 +1082,595 @@ pub async fn submit_withdraw(ctx: Context, args: args::Withdraw) {
     submit_tx(ctx, &args.tx, tx).await;
 }
 
-/// Sign a transaction with a given signing key or public key of a given signer.
-/// If no explicit signer given, use the `default`. If no `default` is given,
-/// returns unsigned transaction.
-async fn sign_tx(
-    mut ctx: Context,
-    tx: Tx,
-    args: &args::Tx,
-    default: Option<&WalletAddress>,
-) -> (Context, Tx) {
-    let tx = if let Some(signing_key) = &args.signing_key {
-        let signing_key = ctx.get_cached(signing_key);
-        tx.sign(&signing_key)
-    } else if let Some(signer) = args.signer.as_ref().or(default) {
-        let signer = ctx.get(signer);
-        let signing_key = signing::find_keypair(
-            &mut ctx.wallet,
-            &signer,
-            args.ledger_address.clone(),
-        )
-        .await;
-        tx.sign(&signing_key)
-    } else {
-        // Unsigned tx
-        tx
-    };
-    (ctx, tx)
-}
-
-/// Submit transaction and wait for result. Returns a list of addresses
-/// initialized in the transaction if any. In dry run, this is always empty.
-async fn submit_tx(
+pub async fn submit_change_commission(
     ctx: Context,
-    args: &args::Tx,
+    args: args::TxChangeCommission,
+) {
+    let validator = ctx.get(&args.validator);
+    // Check that the validator address exists on chain
+    let is_validator =
+        rpc::is_validator(&validator, args.tx.ledger_address.clone()).await;
+    if !is_validator {
+        eprintln!(
+            "The address {} doesn't belong to any known validator account.",
+            validator
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+    if args.rate > MAX_COMMISSION_RATE {
+        eprintln!(
+            "The commission rate {} must not exceed {} basis points.",
+            args.rate, MAX_COMMISSION_RATE
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_CHANGE_COMMISSION_WASM);
+    let data = pos::CommissionChange {
+        validator,
+        rate: args.rate,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.validator)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+pub async fn submit_change_consensus_key(
+    mut ctx: Context,
+    args: args::TxChangeConsensusKey,
+) {
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let epoch = rpc::get_epoch(client.clone()).await;
+
+    let validator = ctx.get(&args.validator);
+    // Check that the validator address exists on chain
+    let consensus_keys =
+        rpc::query_storage_value::<ledger::pos::ValidatorConsensusKeys>(
+            client.clone(),
+            ledger::pos::validator_consensus_key_key(&validator),
+        )
+        .await;
+    if consensus_keys.is_none() {
+        eprintln!(
+            "The address {} doesn't belong to any known validator account.",
+            validator
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let consensus_key =
+        ctx.get_opt_cached(&args.consensus_key).unwrap_or_else(|| {
+            println!("Generating new consensus key...");
+            let (alias, keypair) =
+                ctx.wallet.gen_key(None, args.unsafe_dont_encrypt);
+            ctx.wallet.save().unwrap_or_else(|err| eprintln!("{}", err));
+            println!(
+                "The new consensus key was stored in the wallet as {}.",
+                alias
+            );
+            keypair.public.clone()
+        });
+
+    let params = rpc::query_storage_value::<ledger::pos::PosParams>(
+        client,
+        ledger::pos::params_key(),
+    )
+    .await
+    .expect("PoS parameters should always exist in storage");
+    let pipeline_epoch = epoch + params.pipeline_len;
+    if let Some(keys) = consensus_keys {
+        if keys.get(pipeline_epoch) == Some(&consensus_key) {
+            eprintln!(
+                "The validator {} already uses the given consensus key.",
+                validator
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_CHANGE_CONSENSUS_KEY_WASM);
+    let data = pos::ConsensusKeyChange {
+        validator,
+        consensus_key,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.validator)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+    if !args.tx.dry_run {
+        println!(
+            "The new consensus key will take effect from the beginning of \
+             epoch {}. Until then, the ledger node must keep using the \
+             current key. Once the epoch is reached, use the \
+             `write-consensus-key` command to set up the ledger node with \
+             the new key and restart it.",
+            pipeline_epoch
+        );
+    }
+}
+
+pub async fn submit_update_validator_metadata(
+    ctx: Context,
+    args: args::TxUpdateValidatorMetadata,
+) {
+    let validator = ctx.get(&args.validator);
+    // Check that the validator address exists on chain
+    let is_validator =
+        rpc::is_validator(&validator, args.tx.ledger_address.clone()).await;
+    if !is_validator {
+        eprintln!(
+            "The address {} doesn't belong to any known validator account.",
+            validator
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+    let change = ValidatorMetadata {
+        moniker: args.moniker,
+        description: args.description,
+        website: args.website,
+        contact: args.contact,
+    };
+    if change == ValidatorMetadata::default() {
+        eprintln!("No metadata to update given.");
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+    if let Some(field) = change.find_too_long_field() {
+        eprintln!(
+            "The validator's {} must not be longer than {} bytes.",
+            field,
+            ValidatorMetadata::MAX_FIELD_LEN
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_UPDATE_VALIDATOR_METADATA_WASM);
+    let data = pos::MetadataChange {
+        validator,
+        moniker: change.moniker,
+        description: change.description,
+        website: change.website,
+        contact: change.contact,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.validator)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+pub async fn submit_claim_rewards(ctx: Context, args: args::ClaimRewards) {
+    let validator = ctx.get(&args.validator);
+    let source = ctx.get(&args.source);
+
+    // Check that there are some unclaimed rewards
+    let bond_id = BondId {
+        source: source.clone(),
+        validator: validator.clone(),
+    };
+    let rewards_key = ledger::pos::bond_rewards_key(&bond_id);
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let rewards =
+        rpc::query_storage_value::<BondRewards>(client, rewards_key).await;
+    match rewards {
+        Some(rewards) => {
+            if rewards.unclaimed == 0.into() {
+                eprintln!("There are no unclaimed rewards for this bond.");
+                if !args.tx.force {
+                    safe_exit(1)
+                }
+            }
+        }
+        None => {
+            eprintln!("No bond rewards found");
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_CLAIM_REWARDS_WASM);
+    let data = pos::ClaimRewards { validator, source };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.source)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+pub async fn submit_unjail(ctx: Context, args: args::Unjail) {
+    let (ctx, epoch) = rpc::query_epoch(
+        ctx,
+        args::Query {
+            ledger_address: args.tx.ledger_address.clone(),
+        },
+    )
+    .await;
+
+    let validator = ctx.get(&args.validator);
+    // Check that the validator is jailed and that it can be unjailed
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let jailed_epoch = rpc::query_storage_value::<ledger::pos::types::Epoch>(
+        client.clone(),
+        ledger::pos::validator_jailed_epoch_key(&validator),
+    )
+    .await;
+    match jailed_epoch {
+        Some(jailed_epoch) => {
+            let params = rpc::query_storage_value::<ledger::pos::PosParams>(
+                client,
+                ledger::pos::params_key(),
+            )
+            .await
+            .expect("PoS parameters should always exist in storage");
+            let unjail_epoch: Epoch =
+                (jailed_epoch + params.min_jail_duration).into();
+            if epoch < unjail_epoch {
+                eprintln!(
+                    "The validator {} cannot be unjailed before epoch {}.",
+                    validator, unjail_epoch
+                );
+                if !args.tx.force {
+                    safe_exit(1)
+                }
+            }
+        }
+        None => {
+            eprintln!("The validator {} is not jailed.", validator);
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_UNJAIL_WASM);
+    let data = pos::Unjail { validator };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.validator)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+pub async fn submit_propose_parameters(
+    ctx: Context,
+    args: args::ProposeParameters,
+) {
+    let (ctx, epoch) = rpc::query_epoch(
+        ctx,
+        args::Query {
+            ledger_address: args.tx.ledger_address.clone(),
+        },
+    )
+    .await;
+
+    // Check that the proposal's epochs are in order
+    if !(epoch < args.voting_start_epoch
+        && args.voting_start_epoch < args.voting_end_epoch
+        && args.voting_end_epoch <= args.activation_epoch)
+    {
+        eprintln!(
+            "The proposal's epochs must satisfy: current epoch ({}) < voting \
+             start epoch < voting end epoch <= activation epoch.",
+            epoch
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    // The parameters that are not specified are kept at their current values
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let mut parameters: ledger::parameters::Parameters =
+        rpc::query_storage_value(client, ledger::parameters::storage_key())
+            .await
+            .expect("Protocol parameters should always exist in storage");
+    if let Some(min_num_of_blocks) = args.min_num_of_blocks {
+        parameters.epoch_duration.min_num_of_blocks = min_num_of_blocks;
+    }
+    if let Some(min_duration) = args.min_duration {
+        parameters.epoch_duration.min_duration = DurationSecs(min_duration);
+    }
+    if let Some(tx_memory_max_pages) = args.tx_memory_max_pages {
+        parameters.wasm_limits.tx_memory_max_pages = tx_memory_max_pages;
+    }
+    if let Some(vp_memory_max_pages) = args.vp_memory_max_pages {
+        parameters.wasm_limits.vp_memory_max_pages = vp_memory_max_pages;
+    }
+    if let Some(wasm_stack_limit) = args.wasm_stack_limit {
+        parameters.wasm_limits.stack_limit = wasm_stack_limit;
+    }
+    if parameters.epoch_duration.min_num_of_blocks == 0 {
+        eprintln!("The minimum number of blocks in an epoch must be positive.");
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_PROPOSE_PARAMETERS_WASM);
+    let data = ledger::parameters::ParametersProposal {
+        author: ctx.get(&args.author),
+        parameters,
+        voting_start_epoch: args.voting_start_epoch,
+        voting_end_epoch: args.voting_end_epoch,
+        activation_epoch: args.activation_epoch,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.author)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+pub async fn submit_vote_parameters(ctx: Context, args: args::VoteParameters) {
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let proposal = rpc::query_storage_value::<
+        ledger::parameters::ParametersProposal,
+    >(
+        client, ledger::parameters::proposal_key(args.proposal_id)
+    )
+    .await;
+    if proposal.is_none() {
+        eprintln!("The proposal {} doesn't exist.", args.proposal_id);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_VOTE_PARAMETERS_WASM);
+    let data = parameters::VoteProposal {
+        id: args.proposal_id,
+        voter: ctx.get(&args.voter),
+        vote: args.vote,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.voter)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+pub async fn submit_apply_parameters(
+    ctx: Context,
+    args: args::ApplyParameters,
+) {
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let proposal = rpc::query_storage_value::<
+        ledger::parameters::ParametersProposal,
+    >(
+        client, ledger::parameters::proposal_key(args.proposal_id)
+    )
+    .await;
+    if proposal.is_none() {
+        eprintln!("The proposal {} doesn't exist.", args.proposal_id);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_APPLY_PARAMETERS_WASM);
+    let data = parameters::ApplyProposal {
+        id: args.proposal_id,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    // Anyone can apply a passed proposal, so the tx doesn't have to be signed
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, None).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+pub async fn submit_init_proposal(ctx: Context, args: args::InitProposal) {
+    let (ctx, epoch) = rpc::query_epoch(
+        ctx,
+        args::Query {
+            ledger_address: args.tx.ledger_address.clone(),
+        },
+    )
+    .await;
+    let grace_epoch = args.grace_epoch.unwrap_or(args.voting_end_epoch);
+
+    // Check that the proposal's epochs are in order
+    if !(epoch < args.voting_start_epoch
+        && args.voting_start_epoch < args.voting_end_epoch
+        && args.voting_end_epoch <= grace_epoch)
+    {
+        eprintln!(
+            "The proposal's epochs must satisfy: current epoch ({}) < voting \
+             start epoch < voting end epoch <= grace epoch.",
+            epoch
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let params: ledger::governance::GovParams = rpc::query_storage_value(
+        client.clone(),
+        ledger::governance::params_key(),
+    )
+    .await
+    .expect("Governance parameters should always exist in storage");
+
+    // Check the voting period and the content against the parameters
+    let voting_period = args
+        .voting_end_epoch
+        .0
+        .saturating_sub(args.voting_start_epoch.0);
+    if voting_period < params.min_voting_period
+        || voting_period > params.max_voting_period
+    {
+        eprintln!(
+            "The voting period must be between {} and {} epochs, but it is {}.",
+            params.min_voting_period, params.max_voting_period, voting_period
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+    if args.content.len() as u64 > params.max_proposal_content_size {
+        eprintln!(
+            "The proposal's content must not exceed {} bytes.",
+            params.max_proposal_content_size
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+    let proposal_code = args.proposal_code_path.as_ref().map(|path| {
+        std::fs::read(path).expect("Expected a file at given code path")
+    });
+    if let Some(code) = &proposal_code {
+        if code.len() as u64 > params.max_proposal_code_size {
+            eprintln!(
+                "The proposal's code must not exceed {} bytes.",
+                params.max_proposal_code_size
+            );
+            if !args.tx.force {
+                safe_exit(1)
+            }
+        }
+    }
+
+    // Check the author's balance for the deposit
+    let author = ctx.get(&args.author);
+    let balance_key = token::balance_key(
+        &ledger::governance::deposit_token_address(),
+        &author,
+    );
+    let balance: token::Amount = rpc::query_storage_value(client, balance_key)
+        .await
+        .unwrap_or_default();
+    if balance < params.min_proposal_deposit {
+        eprintln!(
+            "The balance of the author {} is lower than the minimum proposal \
+             deposit. The deposit is {} and the balance is {}.",
+            author, params.min_proposal_deposit, balance
+        );
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_INIT_PROPOSAL_WASM);
+    let data = governance::InitProposal {
+        author,
+        content: args.content,
+        voting_start_epoch: args.voting_start_epoch,
+        voting_end_epoch: args.voting_end_epoch,
+        grace_epoch,
+        proposal_code,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.author)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+pub async fn submit_vote_proposal(ctx: Context, args: args::VoteProposal) {
+    let client = HttpClient::new(args.tx.ledger_address.clone()).unwrap();
+    let proposal: Option<ledger::governance::Proposal> =
+        rpc::query_storage_value(
+            client,
+            ledger::governance::proposal_key(args.proposal_id),
+        )
+        .await;
+    if proposal.is_none() {
+        eprintln!("The proposal {} doesn't exist.", args.proposal_id);
+        if !args.tx.force {
+            safe_exit(1)
+        }
+    }
+
+    let tx_code = ctx.read_wasm(TX_VOTE_PROPOSAL_WASM);
+    let data = governance::VoteProposal {
+        id: args.proposal_id,
+        voter: ctx.get(&args.voter),
+        vote: args.vote,
+    };
+    let data = data.try_to_vec().expect("Encoding tx data shouldn't fail");
+
+    let tx = Tx::new(tx_code, Some(data));
+    let (ctx, tx) = sign_tx(ctx, tx, &args.tx, Some(&args.voter)).await;
+    submit_tx(ctx, &args.tx, tx).await;
+}
+
+/// Sign a transaction with a given signing key or public key of a given signer.
+/// If no explicit signer given, use the `default`. If no `default` is given or
+/// the unsigned tx is requested, returns unsigned transaction.
+async fn sign_tx(
+    mut ctx: Context,
+    tx: Tx,
+    args: &args::Tx,
+    default: Option<&WalletAddress>,
+) -> (Context, Tx) {
+    let tx = if args.unsigned_tx_path.is_some() {
+        // Unsigned tx to be signed offline
+        tx
+    } else if let Some(signing_key) = &args.signing_key {
+        let signing_key = ctx.get_cached(signing_key);
+        tx.sign(&signing_key)
+    } else if let Some(signer) = args.signer.as_ref().or(default) {
+        let signer = ctx.get(signer);
+        let signing_key = signing::find_keypair(
+            &mut ctx.wallet,
+            &signer,
+            args.ledger_address.clone(),
+        )
+        .await;
+        tx.sign(&signing_key)
+    } else {
+        // Unsigned tx
+        tx
+    };
+    (ctx, tx)
+}
+
+/// Submit transaction and wait for result. Returns a list of addresses
+/// initialized in the transaction if any. In dry run or when the unsigned tx
+/// is requested, this is always empty.
+async fn submit_tx(
+    ctx: Context,
+    args: &args::Tx,
     tx: Tx,
 ) -> (Context, Vec<Address>) {
     let tx_bytes = tx.to_bytes();
 
+    if let Some(path) = &args.unsigned_tx_path {
+        std::fs::write(path, tx_bytes).unwrap_or_else(|err| {
+            eprintln!(
+                "Failed to write the transaction to {}: {}",
+                path.to_string_lossy(),
+                err
+            );
+            safe_exit(1)
+        });
+        println!(
+            "Unsigned transaction saved to {}. It can be signed with \
+             `multisig sign`.",
+            path.to_string_lossy()
+        );
+        return (ctx, vec![]);
+    }
+
     // NOTE: use this to print the request JSON body:
 
     // let request =
@@ -4,15 +4,21 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use anoma::ledger::pos;
+use anoma::ledger::pos::types::BasisPoints;
 use anoma::types::chain::ChainId;
 use anoma::types::key::ed25519::Keypair;
 use anoma::types::{address, token};
+use anoma::vm::wasm::memory::MemoryLimits;
+use anoma::vm::{self, WasmCodeKind};
 use borsh::BorshSerialize;
 use rand::prelude::ThreadRng;
 use rand::thread_rng;
 use serde_json::json;
+use tendermint_rpc::HttpClient;
 
-use crate::cli::{self, args};
+use super::rpc;
+use crate::cli::{self, args, Context};
 use crate::config::genesis::genesis_config;
 use crate::config::global::GlobalConfig;
 use crate::config::{self, genesis, Config, IntentGossiper, PeerAddress};
@@ -245,6 +251,8 @@ pub fn init_network(
                             tx_code: tx_code.clone().into(),
                             ledger_address,
                             filter: None,
+                            memory: MemoryLimits::matchmaker(),
+                            filter_memory: MemoryLimits::filter(),
                         });
                     }
                     None => {
@@ -526,6 +534,103 @@ pub fn init_genesis_validator(
     );
 }
 
+/// Write a validator's consensus key into the Tendermint private validator key
+/// file of the local ledger node, e.g. after the validator's consensus key has
+/// been changed. The ledger node has to be restarted to use the new key.
+pub async fn write_consensus_key(
+    mut ctx: Context,
+    args::WriteConsensusKey {
+        query,
+        validator,
+        consensus_key,
+        force,
+    }: args::WriteConsensusKey,
+) {
+    let validator = ctx.get(&validator);
+    let consensus_key = ctx.get_cached(&consensus_key);
+
+    // Check that the key is the validator's consensus key in the current epoch
+    let client = HttpClient::new(query.ledger_address).unwrap();
+    let epoch = rpc::get_epoch(client.clone()).await;
+    let consensus_keys =
+        rpc::query_storage_value::<pos::ValidatorConsensusKeys>(
+            client,
+            pos::validator_consensus_key_key(&validator),
+        )
+        .await;
+    match consensus_keys {
+        Some(keys) => {
+            if keys.get(epoch) != Some(&consensus_key.public) {
+                eprintln!(
+                    "The given key is not the consensus key of validator {} \
+                     in the current epoch {}.",
+                    validator, epoch
+                );
+                if !force {
+                    cli::safe_exit(1)
+                }
+            }
+        }
+        None => {
+            eprintln!(
+                "The address {} doesn't belong to any known validator account.",
+                validator
+            );
+            if !force {
+                cli::safe_exit(1)
+            }
+        }
+    }
+
+    let tendermint_home = ctx.config.ledger.tendermint_dir();
+    tendermint_node::write_validator_key(
+        &tendermint_home,
+        &validator,
+        &consensus_key,
+    );
+    println!(
+        "The consensus key has been written into the private validator key \
+         file in {}. Restart the ledger node to start using it.",
+        tendermint_home.to_string_lossy()
+    );
+}
+
+/// Check that a transaction or a validity predicate WASM code passes the
+/// validation that is applied by the ledger when the code is submitted.
+pub fn check_wasm(args::WasmCheck { code_path, vp }: args::WasmCheck) {
+    let code = fs::read(&code_path).unwrap_or_else(|err| {
+        eprintln!(
+            "Failed to read the WASM code file {}: {}",
+            code_path.to_string_lossy(),
+            err
+        );
+        cli::safe_exit(1)
+    });
+    let kind = if vp {
+        WasmCodeKind::Vp
+    } else {
+        WasmCodeKind::Tx
+    };
+    match vm::validate_untrusted_code(&code, kind) {
+        Ok(()) => {
+            println!(
+                "The {} code {} is valid.",
+                kind,
+                code_path.to_string_lossy()
+            );
+        }
+        Err(err) => {
+            eprintln!(
+                "The {} code {} is invalid: {}",
+                kind,
+                code_path.to_string_lossy(),
+                err
+            );
+            cli::safe_exit(1)
+        }
+    }
+}
+
 /// Initialize genesis validator's address, staking reward address,
 /// consensus key, validator account key and staking rewards key and use
 /// it in the ledger's node.
@@ -597,6 +702,7 @@ fn init_genesis_validator_aux(
             tokens: token::Amount::whole(200_000),
             consensus_key: consensus_key.public.clone(),
             staking_reward_key: rewards_key.public.clone(),
+            commission_rate: BasisPoints::new(0),
         },
         account_key: validator_key.public.clone(),
         non_staked_balance: token::Amount::whole(100_000),
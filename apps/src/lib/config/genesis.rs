@@ -4,9 +4,12 @@ use std::collections::HashMap;
 #[cfg(not(feature = "dev"))]
 use std::path::Path;
 
+use anoma::ledger::governance::GovParams;
+use anoma::ledger::inflation::InflationParams;
 use anoma::ledger::parameters::Parameters;
 use anoma::ledger::pos::{GenesisValidator, PosParams};
-use anoma::types::address::Address;
+use anoma::ledger::treasury::TreasuryParams;
+use anoma::types::address::{self, Address};
 #[cfg(not(feature = "dev"))]
 use anoma::types::chain::ChainId;
 #[cfg(feature = "dev")]
@@ -25,9 +28,12 @@ pub mod genesis_config {
     use std::path::Path;
     use std::str::FromStr;
 
-    use anoma::ledger::parameters::{EpochDuration, Parameters};
+    use anoma::ledger::governance::GovParams;
+    use anoma::ledger::inflation::InflationParams;
+    use anoma::ledger::parameters::{EpochDuration, Parameters, WasmLimits};
     use anoma::ledger::pos::types::BasisPoints;
     use anoma::ledger::pos::{GenesisValidator, PosParams};
+    use anoma::ledger::treasury::TreasuryParams;
     use anoma::types::address::Address;
     use anoma::types::key::ed25519::{ParsePublicKeyError, PublicKey};
     use anoma::types::time::Rfc3339String;
@@ -102,6 +108,12 @@ pub mod genesis_config {
         pub parameters: ParametersConfig,
         // PoS parameters
         pub pos_params: PosParamsConfig,
+        // Governance parameters
+        pub gov_params: GovParamsConfig,
+        // Treasury parameters and balance
+        pub treasury: TreasuryConfig,
+        // Inflation parameters
+        pub inflation: InflationConfig,
         // Wasm definitions
         pub wasm: HashMap<String, WasmConfig>,
     }
@@ -124,6 +136,8 @@ pub mod genesis_config {
         // Unstaked balance at genesis.
         // XXX: u64 doesn't work with toml-rs!
         pub non_staked_balance: u64,
+        // Commission rate (in basis points). (default: 0)
+        pub commission_rate: Option<u64>,
         // Filename of validator VP. (default: default validator VP)
         pub validator_vp: Option<String>,
         // Filename of staking reward account VP. (default: user VP)
@@ -175,6 +189,12 @@ pub mod genesis_config {
         // Minimum duration of an epoch (in seconds).
         // TODO: this is i64 because datetime wants it
         min_duration: i64,
+        // Maximum number of WASM memory pages for transactions.
+        tx_memory_max_pages: u32,
+        // Maximum number of WASM memory pages for validity predicates.
+        vp_memory_max_pages: u32,
+        // Maximum stack-height of WASM transactions and validity predicates.
+        wasm_stack_limit: u32,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -191,10 +211,10 @@ pub mod genesis_config {
         // Votes per token (in basis points).
         // XXX: u64 doesn't work with toml-rs!
         votes_per_token: u64,
-        // Reward for proposing a block.
+        // Weight of the reward for proposing a block.
         // XXX: u64 doesn't work with toml-rs!
         block_proposer_reward: u64,
-        // Reward for voting on a block.
+        // Weight of the reward for voting on a block.
         // XXX: u64 doesn't work with toml-rs!
         block_vote_reward: u64,
         // Portion of a validator's stake that should be slashed on a
@@ -205,6 +225,94 @@ pub mod genesis_config {
         // light client attack (in basis points).
         // XXX: u64 doesn't work with toml-rs!
         light_client_attack_slash_rate: u64,
+        // Maximum change of a validator's commission rate per epoch (in
+        // basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        max_commission_rate_change: u64,
+        // Portion of a validator's stake that should be slashed for
+        // downtime (in basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        downtime_slash_rate: u64,
+        // Number of the most recent blocks in which validators' liveness is
+        // tracked.
+        // XXX: u64 doesn't work with toml-rs!
+        signed_blocks_window: u64,
+        // Maximum portion of the blocks in the signing window that a
+        // validator may miss signing before it gets slashed for downtime and
+        // jailed (in basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        max_missed_blocks_rate: u64,
+        // Minimum number of epochs that a validator must stay jailed for
+        // before it can be unjailed.
+        // XXX: u64 doesn't work with toml-rs!
+        min_jail_duration: u64,
+    }
+
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    pub struct GovParamsConfig {
+        // Minimum number of tokens to be deposited with a proposal.
+        // XXX: u64 doesn't work with toml-rs!
+        min_proposal_deposit: u64,
+        // Minimum number of epochs of a voting period.
+        // XXX: u64 doesn't work with toml-rs!
+        min_voting_period: u64,
+        // Maximum number of epochs of a voting period.
+        // XXX: u64 doesn't work with toml-rs!
+        max_voting_period: u64,
+        // Maximum size of a proposal's WASM code (in bytes).
+        // XXX: u64 doesn't work with toml-rs!
+        max_proposal_code_size: u64,
+        // Maximum size of a proposal's content (in bytes).
+        // XXX: u64 doesn't work with toml-rs!
+        max_proposal_content_size: u64,
+        // Minimum portion of the total voting power that has to vote on a
+        // proposal for it to pass (in basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        quorum: u64,
+        // Portion of the voting power that voted on a proposal that has to be
+        // exceeded by the voting power that voted for it for it to pass (in
+        // basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        threshold: u64,
+    }
+
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    pub struct TreasuryConfig {
+        // Initial balance of the native token (in whole tokens).
+        // XXX: u64 doesn't work with toml-rs!
+        balance: u64,
+        // Portion of every transaction fee credited to the treasury (in basis
+        // points).
+        // XXX: u64 doesn't work with toml-rs!
+        fee_share: u64,
+    }
+
+    #[derive(Clone, Debug, Deserialize, Serialize)]
+    pub struct InflationConfig {
+        // Targeted portion of the native token supply that is staked (in
+        // basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        target_staking_ratio: u64,
+        // Minimum annual inflation rate (in basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        min_rate: u64,
+        // Maximum annual inflation rate (in basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        max_rate: u64,
+        // Proportional gain of the inflation rate controller (in basis
+        // points).
+        // XXX: u64 doesn't work with toml-rs!
+        p_gain: u64,
+        // Derivative gain of the inflation rate controller (in basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        d_gain: u64,
+        // Expected number of epochs in a year.
+        // XXX: u64 doesn't work with toml-rs!
+        epochs_per_year: u64,
+        // Portion of the inflation minted into the treasury, the rest is paid
+        // out as PoS rewards (in basis points).
+        // XXX: u64 doesn't work with toml-rs!
+        treasury_share: u64,
     }
 
     #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -243,6 +351,9 @@ pub mod genesis_config {
                     .unwrap()
                     .to_public_key()
                     .unwrap(),
+                commission_rate: BasisPoints::new(
+                    config.commission_rate.unwrap_or_default(),
+                ),
             },
             account_key: config
                 .account_public_key
@@ -367,6 +478,11 @@ pub mod genesis_config {
                 )
                 .into(),
             },
+            wasm_limits: WasmLimits {
+                tx_memory_max_pages: config.parameters.tx_memory_max_pages,
+                vp_memory_max_pages: config.parameters.vp_memory_max_pages,
+                stack_limit: config.parameters.wasm_stack_limit,
+            },
         };
 
         let pos_params = PosParams {
@@ -384,6 +500,48 @@ pub mod genesis_config {
             light_client_attack_slash_rate: BasisPoints::new(
                 config.pos_params.light_client_attack_slash_rate,
             ),
+            max_commission_rate_change: BasisPoints::new(
+                config.pos_params.max_commission_rate_change,
+            ),
+            downtime_slash_rate: BasisPoints::new(
+                config.pos_params.downtime_slash_rate,
+            ),
+            signed_blocks_window: config.pos_params.signed_blocks_window,
+            max_missed_blocks_rate: BasisPoints::new(
+                config.pos_params.max_missed_blocks_rate,
+            ),
+            min_jail_duration: config.pos_params.min_jail_duration,
+        };
+
+        let gov_params = GovParams {
+            min_proposal_deposit: token::Amount::whole(
+                config.gov_params.min_proposal_deposit,
+            ),
+            min_voting_period: config.gov_params.min_voting_period,
+            max_voting_period: config.gov_params.max_voting_period,
+            max_proposal_code_size: config.gov_params.max_proposal_code_size,
+            max_proposal_content_size: config
+                .gov_params
+                .max_proposal_content_size,
+            quorum: BasisPoints::new(config.gov_params.quorum),
+            threshold: BasisPoints::new(config.gov_params.threshold),
+        };
+
+        let treasury_params = TreasuryParams {
+            fee_share: BasisPoints::new(config.treasury.fee_share),
+        };
+        let treasury_balance = token::Amount::whole(config.treasury.balance);
+
+        let inflation_params = InflationParams {
+            target_staking_ratio: BasisPoints::new(
+                config.inflation.target_staking_ratio,
+            ),
+            min_rate: BasisPoints::new(config.inflation.min_rate),
+            max_rate: BasisPoints::new(config.inflation.max_rate),
+            p_gain: BasisPoints::new(config.inflation.p_gain),
+            d_gain: BasisPoints::new(config.inflation.d_gain),
+            epochs_per_year: config.inflation.epochs_per_year,
+            treasury_share: BasisPoints::new(config.inflation.treasury_share),
         };
 
         let mut genesis = Genesis {
@@ -394,6 +552,10 @@ pub mod genesis_config {
             implicit_accounts,
             parameters,
             pos_params,
+            gov_params,
+            treasury_params,
+            treasury_balance,
+            inflation_params,
         };
         genesis.init();
         genesis
@@ -427,6 +589,10 @@ pub struct Genesis {
     pub implicit_accounts: Vec<ImplicitAccount>,
     pub parameters: Parameters,
     pub pos_params: PosParams,
+    pub gov_params: GovParams,
+    pub treasury_params: TreasuryParams,
+    pub treasury_balance: token::Amount,
+    pub inflation_params: InflationParams,
 }
 
 impl Genesis {
@@ -437,6 +603,27 @@ impl Genesis {
         self.established_accounts.sort();
         self.implicit_accounts.sort();
     }
+
+    /// The total supply of the native token at genesis, including the tokens
+    /// bonded by and the balances of the validators and the treasury balance
+    pub fn native_token_supply(&self) -> token::Amount {
+        let xan = address::xan();
+        let mut supply = self.treasury_balance;
+        let balances = self
+            .token_accounts
+            .iter()
+            .filter(|account| account.address == xan)
+            .flat_map(|account| account.balances.values());
+        let validators_tokens = self.validators.iter().flat_map(|validator| {
+            vec![&validator.pos_data.tokens, &validator.non_staked_balance]
+        });
+        for amount in balances.chain(validators_tokens) {
+            supply
+                .receive(amount)
+                .expect("The genesis token supply must not overflow");
+        }
+        supply
+    }
 }
 
 #[derive(
@@ -532,8 +719,8 @@ pub fn genesis(base_dir: impl AsRef<Path>, chain_id: &ChainId) -> Genesis {
 pub fn genesis() -> Genesis {
     use std::iter::FromIterator;
 
-    use anoma::ledger::parameters::EpochDuration;
-    use anoma::types::address;
+    use anoma::ledger::parameters::{EpochDuration, WasmLimits};
+    use anoma::ledger::pos::types::BasisPoints;
 
     use crate::wallet;
 
@@ -561,6 +748,7 @@ pub fn genesis() -> Genesis {
             tokens: token::Amount::whole(200_000),
             consensus_key: consensus_keypair.public,
             staking_reward_key: staking_reward_keypair.public,
+            commission_rate: BasisPoints::new(0),
         },
         account_key: account_keypair.public,
         non_staked_balance: token::Amount::whole(100_000),
@@ -575,6 +763,7 @@ pub fn genesis() -> Genesis {
             min_num_of_blocks: 10,
             min_duration: anoma::types::time::Duration::minutes(1).into(),
         },
+        wasm_limits: WasmLimits::default(),
     };
     let albert = EstablishedAccount {
         address: wallet::defaults::albert_address(),
@@ -631,6 +820,10 @@ pub fn genesis() -> Genesis {
         token_accounts,
         parameters,
         pos_params: PosParams::default(),
+        gov_params: GovParams::default(),
+        treasury_params: TreasuryParams::default(),
+        treasury_balance: token::Amount::whole(1_000_000),
+        inflation_params: InflationParams::default(),
     }
 }
 
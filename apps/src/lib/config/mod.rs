@@ -14,6 +14,7 @@ use std::str::FromStr;
 
 use anoma::types::chain::ChainId;
 use anoma::types::time::Rfc3339String;
+use anoma::vm::wasm::memory::MemoryLimits;
 use gossiper::Gossiper;
 use libp2p::multiaddr::{Multiaddr, Protocol};
 use libp2p::multihash::Multihash;
@@ -150,6 +151,12 @@ pub struct Matchmaker {
     pub tx_code: PathBuf,
     pub ledger_address: net::Address,
     pub filter: Option<PathBuf>,
+    /// The limits of the WASM memory for the matchmaker code
+    #[serde(default = "MemoryLimits::matchmaker")]
+    pub memory: MemoryLimits,
+    /// The limits of the WASM memory for the filter code
+    #[serde(default = "MemoryLimits::filter")]
+    pub filter_memory: MemoryLimits,
 }
 
 // TODO maybe add also maxCount for a maximum number of subscription for a
@@ -361,6 +368,8 @@ impl IntentGossiper {
                 tx_code: tx_code.clone(),
                 ledger_address: ledger_address.clone(),
                 filter: filter_arg,
+                memory: MemoryLimits::matchmaker(),
+                filter_memory: MemoryLimits::filter(),
             });
         } else if matchmaker_arg.is_some()
             || tx_code_arg.is_some()
@@ -402,6 +411,8 @@ impl IntentGossiper {
                 tx_code: "../wasm/tx_from_intent.wasm".parse().unwrap(),
                 ledger_address: "0.0.0.0:26657".parse().unwrap(),
                 filter: None,
+                memory: MemoryLimits::matchmaker(),
+                filter_memory: MemoryLimits::filter(),
             })
         }
 
@@ -2,6 +2,7 @@ use std::path::Path;
 
 use anoma::proto::Intent;
 use anoma::vm::wasm;
+use anoma::vm::wasm::memory::MemoryLimits;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -17,17 +18,26 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug)]
 pub struct Filter {
     code: Vec<u8>,
+    memory_limits: MemoryLimits,
 }
 
 impl Filter {
-    pub fn from_file(path: impl AsRef<Path>) -> Result<Filter> {
+    pub fn from_file(
+        path: impl AsRef<Path>,
+        memory_limits: MemoryLimits,
+    ) -> Result<Filter> {
         Ok(Filter {
             code: std::fs::read(path).map_err(Error::FileFailed)?,
+            memory_limits,
         })
     }
 
     pub fn validate(&self, intent: &Intent) -> Result<bool> {
-        wasm::run::matchmaker_filter(&self.code, &intent.data)
-            .map_err(Error::RunnerError)
+        wasm::run::matchmaker_filter(
+            &self.code,
+            &intent.data,
+            &self.memory_limits,
+        )
+        .map_err(Error::RunnerError)
     }
 }
@@ -7,6 +7,7 @@ use anoma::types::address::Address;
 use anoma::types::intent::{IntentTransfers, MatchedExchanges};
 use anoma::types::key::ed25519::Keypair;
 use anoma::vm::wasm;
+use anoma::vm::wasm::memory::MemoryLimits;
 use borsh::{BorshDeserialize, BorshSerialize};
 use tendermint::net;
 use thiserror::Error;
@@ -27,6 +28,8 @@ pub struct Matchmaker {
     /// Possible filter that filter any received intent.
     filter: Option<Filter>,
     matchmaker_code: Vec<u8>,
+    /// The limits of the WASM memory for the matchmaker code
+    memory_limits: MemoryLimits,
     /// The code of the transaction that is going to be send to a ledger.
     tx_code: Vec<u8>,
     /// the matchmaker's state as arbitrary bytes
@@ -102,7 +105,7 @@ impl Matchmaker {
         let filter = config
             .filter
             .as_ref()
-            .map(Filter::from_file)
+            .map(|path| Filter::from_file(path, config.filter_memory))
             .transpose()
             .map_err(Error::FilterInit)?;
 
@@ -111,6 +114,7 @@ impl Matchmaker {
                 mempool: IntentMempool::new(),
                 filter,
                 matchmaker_code,
+                memory_limits: config.memory,
                 tx_code,
                 state: Vec::new(),
                 ledger_address: config.ledger_address.clone(),
@@ -146,6 +150,7 @@ impl Matchmaker {
                 &intent.id().0,
                 &intent.data,
                 self.wasm_host.clone(),
+                &self.memory_limits,
             )
             .map_err(Error::RunnerFailed)
         } else {
@@ -8,8 +8,9 @@ pub mod tendermint_node;
 
 use std::convert::{TryFrom, TryInto};
 use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver};
+use std::sync::mpsc::{channel, Receiver, Sender};
 
+use anoma::ledger::upgrade::UpgradePlan;
 use anoma::types::chain::ChainId;
 use anoma::types::storage::BlockHash;
 use futures::future::{AbortHandle, AbortRegistration, Abortable};
@@ -22,6 +23,10 @@ use crate::node::ledger::shims::abcipp_shim::AbcippShim;
 use crate::node::ledger::shims::abcipp_shim_types::shim::{Request, Response};
 use crate::{config, wasm_loader};
 
+/// The exit code of the ledger when it halts for a scheduled protocol upgrade
+/// that is not implemented by this version
+pub const UPGRADE_EXIT_CODE: i32 = 3;
+
 /// A panic-proof handle for aborting a future. Will abort during
 /// stack unwinding as its drop method calls abort.
 struct Aborter {
@@ -65,18 +70,22 @@ impl Shell {
                     block.header.expect("missing block's header").try_into(),
                 ) {
                     (Ok(hash), Ok(header)) => {
-                        let _ = self.prepare_proposal(
+                        self.prepare_proposal(
                             hash,
                             header,
                             block.byzantine_validators,
-                        );
+                        )?;
                     }
                     (Ok(_), Err(msg)) => {
                         tracing::error!("Unexpected block header {}", msg);
                     }
                     (err @ Err(_), _) => tracing::error!("{:#?}", err),
                 };
-                Ok(Response::PrepareProposal(Default::default()))
+                let response = Response::PrepareProposal(Default::default());
+                // When halted for a protocol upgrade, the request is still
+                // responded to normally before the ledger shuts down
+                self.shut_down_on_upgrade_halt();
+                Ok(response)
             }
             Request::VerifyHeader(_req) => {
                 Ok(Response::VerifyHeader(self.verify_header(_req)))
@@ -145,10 +154,19 @@ async fn run_shell(
     wasm_dir: PathBuf,
     abort_registration: AbortRegistration,
     failure_receiver: Receiver<()>,
+    abort_sender: Sender<bool>,
+    upgrade_sender: Sender<UpgradePlan>,
 ) {
     // Construct our ABCI application.
     let db_dir = config.db_dir(&chain_id);
-    let service = AbcippShim::new(config.base_dir, db_dir, chain_id, wasm_dir);
+    let service = AbcippShim::new(
+        config.base_dir,
+        db_dir,
+        chain_id,
+        wasm_dir,
+        abort_sender,
+        upgrade_sender,
+    );
 
     // Split it into components.
     let (consensus, mempool, snapshot, info) = split::service(service, 5);
@@ -217,6 +235,11 @@ pub fn run(config: config::Ledger, wasm_dir: PathBuf) {
     // Send `true` for a graceful shutdown or `false` on a critical error.
     let (abort_sender, abort_receiver) = channel();
     let shell_abort_sender = abort_sender.clone();
+    let shell_halt_sender = abort_sender.clone();
+
+    // For signalling from the shell that the ledger halted for a protocol
+    // upgrade
+    let (upgrade_sender, upgrade_receiver) = channel();
 
     // For signalling shut down to the shell from Tendermint, which ensures that
     // drop is called on the database
@@ -257,6 +280,8 @@ pub fn run(config: config::Ledger, wasm_dir: PathBuf) {
             wasm_dir,
             abort_registration,
             failure_receiver,
+            shell_halt_sender,
+            upgrade_sender,
         );
     });
 
@@ -273,4 +298,18 @@ pub fn run(config: config::Ledger, wasm_dir: PathBuf) {
     tendermint_handle
         .join()
         .expect("Tendermint node did not shut down properly");
+
+    if let Ok(plan) = upgrade_receiver.try_recv() {
+        tracing::info!(
+            "Anoma ledger halted for the protocol upgrade \"{}\" at height \
+             {}. Restart the node with a version that implements it.",
+            plan.name,
+            plan.height
+        );
+        // Exit with the upgrade status code
+        use std::io::Write;
+        let _ = std::io::stdout().lock().flush();
+        let _ = std::io::stderr().lock().flush();
+        std::process::exit(UPGRADE_EXIT_CODE)
+    }
 }
@@ -4,14 +4,18 @@ use std::convert::TryFrom;
 use std::{fmt, panic};
 
 use anoma::ledger::gas::{self, BlockGasMeter, VpGasMeter, VpsGas};
+use anoma::ledger::governance::{self, GovernanceVp};
+use anoma::ledger::ibc::token::IbcToken;
 use anoma::ledger::ibc::{self, Ibc};
 use anoma::ledger::native_vp::{self, NativeVp};
-use anoma::ledger::parameters::{self, ParametersVp};
+use anoma::ledger::parameters::{self, ParametersVp, WasmLimits};
 use anoma::ledger::pos::{self, PosVP};
 use anoma::ledger::storage::write_log::WriteLog;
+use anoma::ledger::treasury::{self, TreasuryVp};
 use anoma::proto::{self, Tx};
 use anoma::types::address::{Address, InternalAddress};
 use anoma::types::storage::Key;
+use anoma::types::token;
 use anoma::types::transaction::{process_tx, TxType};
 use anoma::vm::{self, wasm};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
@@ -35,14 +39,22 @@ pub enum Error {
     MissingAddress(Address),
     #[error("IBC native VP: {0}")]
     IbcNativeVpError(ibc::Error),
+    #[error("IBC token native VP: {0}")]
+    IbcTokenNativeVpError(ibc::token::Error),
     #[error("PoS native VP: {0}")]
     PosNativeVpError(pos::vp::Error),
     #[error("PoS native VP panicked")]
     PosNativeVpRuntime,
     #[error("Parameters native VP: {0}")]
     ParametersNativeVpError(parameters::Error),
+    #[error("Governance native VP: {0}")]
+    GovernanceNativeVpError(governance::vp::Error),
+    #[error("Treasury native VP: {0}")]
+    TreasuryNativeVpError(treasury::Error),
     #[error("Access to an internal address {0} is forbidden")]
     AccessForbidden(InternalAddress),
+    #[error("Error reading the protocol parameters: {0}")]
+    ReadParametersError(parameters::ReadError),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -97,8 +109,18 @@ pub fn apply_tx(
 
     match process_tx(tx).unwrap() {
         TxType::Raw(tx) => {
-            let verifiers =
-                execute_tx(&tx, storage, block_gas_meter, write_log)?;
+            let (parameters, gas) = parameters::read(storage)
+                .map_err(Error::ReadParametersError)?;
+            block_gas_meter.add(gas).map_err(Error::GasError)?;
+            let wasm_limits = &parameters.wasm_limits;
+
+            let verifiers = execute_tx(
+                &tx,
+                storage,
+                block_gas_meter,
+                write_log,
+                wasm_limits,
+            )?;
 
             let vps_result = check_vps(
                 &tx,
@@ -106,6 +128,7 @@ pub fn apply_tx(
                 block_gas_meter,
                 write_log,
                 &verifiers,
+                wasm_limits,
             )?;
 
             let gas_used = block_gas_meter
@@ -121,6 +144,9 @@ pub fn apply_tx(
                 initialized_accounts,
             })
         }
+        // The inner transaction is not decrypted and applied yet. When it is,
+        // its code must first be checked with `vm::validate_untrusted_code`,
+        // because it skips the check in the mempool validation
         TxType::Wrapper(_) => {
             let gas_used = block_gas_meter
                 .finalize_transaction()
@@ -139,14 +165,22 @@ fn execute_tx(
     storage: &PersistentStorage,
     gas_meter: &mut BlockGasMeter,
     write_log: &mut WriteLog,
+    wasm_limits: &WasmLimits,
 ) -> Result<HashSet<Address>> {
     gas_meter
         .add_compiling_fee(tx.code.len())
         .map_err(Error::GasError)?;
     let empty = vec![];
     let tx_data = tx.data.as_ref().unwrap_or(&empty);
-    wasm::run::tx(storage, write_log, gas_meter, &tx.code, tx_data)
-        .map_err(Error::TxRunnerError)
+    wasm::run::tx(
+        storage,
+        write_log,
+        gas_meter,
+        &tx.code,
+        tx_data,
+        wasm_limits,
+    )
+    .map_err(Error::TxRunnerError)
 }
 
 /// A validity predicate
@@ -162,6 +196,7 @@ fn check_vps(
     gas_meter: &mut BlockGasMeter,
     write_log: &WriteLog,
     verifiers_from_tx: &HashSet<Address>,
+    wasm_limits: &WasmLimits,
 ) -> Result<VpsResult> {
     let verifiers = write_log.verifiers_changed_keys(verifiers_from_tx);
 
@@ -192,8 +227,14 @@ fn check_vps(
 
     let initial_gas = gas_meter.get_current_transaction_gas();
 
-    let vps_result =
-        execute_vps(verifiers, tx, storage, write_log, initial_gas)?;
+    let vps_result = execute_vps(
+        verifiers,
+        tx,
+        storage,
+        write_log,
+        initial_gas,
+        wasm_limits,
+    )?;
     tracing::debug!("Total VPs gas cost {:?}", vps_result.gas_used);
 
     gas_meter
@@ -210,6 +251,7 @@ fn execute_vps(
     storage: &PersistentStorage,
     write_log: &WriteLog,
     initial_gas: u64,
+    wasm_limits: &WasmLimits,
 ) -> Result<VpsResult> {
     let verifiers_addr = verifiers
         .iter()
@@ -231,6 +273,7 @@ fn execute_vps(
                     &mut gas_meter,
                     keys,
                     &verifiers_addr,
+                    wasm_limits,
                 )
                 .map_err(Error::VpRunnerError),
                 Vp::Native(internal_addr) => {
@@ -246,9 +289,10 @@ fn execute_vps(
                             let pos = PosVP { ctx };
                             let verifiers_addr_ref = &verifiers_addr;
                             let pos_ref = &pos;
-                            // TODO this is temporarily ran in a new thread to
-                            // avoid crashing the ledger (required `UnwindSafe`
-                            // and `RefUnwindSafe` in
+                            // TODO the PoS VP's storage reads still panic on
+                            // storage and decoding errors, so it's caught here
+                            // to avoid crashing the ledger (required
+                            // `UnwindSafe` and `RefUnwindSafe` in
                             // shared/src/ledger/pos/vp.rs)
                             let result = match panic::catch_unwind(move || {
                                 pos_ref
@@ -281,6 +325,16 @@ fn execute_vps(
                             gas_meter = ibc.ctx.gas_meter.into_inner();
                             result
                         }
+                        InternalAddress::IbcEscrow
+                        | InternalAddress::IbcToken(_) => {
+                            let ibc_token = IbcToken { ctx };
+                            let result = ibc_token
+                                .validate_tx(tx_data, keys, &verifiers_addr)
+                                .map_err(Error::IbcTokenNativeVpError);
+                            // Take the gas meter back out of the context
+                            gas_meter = ibc_token.ctx.gas_meter.into_inner();
+                            result
+                        }
                         InternalAddress::Parameters => {
                             let parameters = ParametersVp { ctx };
                             let result = parameters
@@ -290,12 +344,44 @@ fn execute_vps(
                             gas_meter = parameters.ctx.gas_meter.into_inner();
                             result
                         }
+                        InternalAddress::Governance => {
+                            let governance = GovernanceVp { ctx };
+                            let result = governance
+                                .validate_tx(tx_data, keys, &verifiers_addr)
+                                .map_err(Error::GovernanceNativeVpError);
+                            // Take the gas meter back out of the context
+                            gas_meter = governance.ctx.gas_meter.into_inner();
+                            result
+                        }
+                        InternalAddress::Treasury => {
+                            let treasury = TreasuryVp { ctx };
+                            let result = treasury
+                                .validate_tx(tx_data, keys, &verifiers_addr)
+                                .map_err(Error::TreasuryNativeVpError);
+                            // Take the gas meter back out of the context
+                            gas_meter = treasury.ctx.gas_meter.into_inner();
+                            result
+                        }
                         InternalAddress::PosSlashPool => {
                             // Take the gas meter back out of the context
                             gas_meter = ctx.gas_meter.into_inner();
-                            Err(Error::AccessForbidden(
-                                (*internal_addr).clone(),
-                            ))
+                            // The slash pool may only be credited with the
+                            // clawbacks repaid from withdrawals, which are
+                            // checked by the PoS VP
+                            let balance_key = token::balance_key(
+                                &pos::staking_token_address(),
+                                &pos::SLASH_POOL_ADDRESS,
+                            );
+                            let is_repaid_clawback = verifiers_addr
+                                .contains(&pos::ADDRESS)
+                                && keys.iter().all(|key| key == &balance_key);
+                            if is_repaid_clawback {
+                                Ok(true)
+                            } else {
+                                Err(Error::AccessForbidden(
+                                    (*internal_addr).clone(),
+                                ))
+                            }
                         }
                     };
 
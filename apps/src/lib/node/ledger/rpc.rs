@@ -6,6 +6,11 @@ use std::str::FromStr;
 use anoma::types::address::Address;
 use anoma::types::storage;
 use borsh::{BorshDeserialize, BorshSerialize};
+use ibc::ics02_client::height::Height;
+use ibc::ics04_channel::packet::Sequence;
+use ibc::ics24_host::identifier::{
+    ChannelId, ClientId, ConnectionId, PortChannelId, PortId,
+};
 use thiserror::Error;
 
 /// RPC query path
@@ -21,6 +26,28 @@ pub enum Path {
     Prefix(storage::Key),
     /// Check if the given storage key exists
     HasKey(storage::Key),
+    /// Read an IBC client state
+    IbcClientState(ClientId),
+    /// Read an IBC consensus state of the client at the height
+    IbcConsensusState(ClientId, Height),
+    /// Read an IBC connection end
+    IbcConnection(ConnectionId),
+    /// Read an IBC channel end
+    IbcChannel(PortChannelId),
+    /// Read an IBC packet commitment
+    IbcPacketCommitment(PortChannelId, Sequence),
+    /// Read an IBC packet receipt
+    IbcPacketReceipt(PortChannelId, Sequence),
+    /// Read an IBC packet acknowledgement
+    IbcPacketAck(PortChannelId, Sequence),
+    /// Filter the given packet sequences to the ones which haven't been
+    /// received on the channel
+    IbcUnreceivedPackets(PortChannelId),
+    /// Filter the given packet sequences to the ones whose acknowledgement
+    /// hasn't been received on the channel
+    IbcUnreceivedAcks(PortChannelId),
+    /// ICS-23 proof specs of the storage proofs
+    IbcProofSpecs,
 }
 
 /// RPC query path
@@ -41,6 +68,17 @@ const EPOCH_PATH: &str = "epoch";
 const VALUE_PREFIX: &str = "value";
 const PREFIX_PREFIX: &str = "prefix";
 const HAS_KEY_PREFIX: &str = "has_key";
+const IBC_PREFIX: &str = "ibc";
+const IBC_CLIENT_STATE_PREFIX: &str = "client_state";
+const IBC_CONSENSUS_STATE_PREFIX: &str = "consensus_state";
+const IBC_CONNECTION_PREFIX: &str = "connection";
+const IBC_CHANNEL_PREFIX: &str = "channel";
+const IBC_COMMITMENT_PREFIX: &str = "commitment";
+const IBC_RECEIPT_PREFIX: &str = "receipt";
+const IBC_ACK_PREFIX: &str = "ack";
+const IBC_UNRECEIVED_PACKETS_PREFIX: &str = "unreceived_packets";
+const IBC_UNRECEIVED_ACKS_PREFIX: &str = "unreceived_acks";
+const IBC_PROOF_SPECS_PATH: &str = "proof_specs";
 
 impl Display for Path {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -56,6 +94,81 @@ impl Display for Path {
             Path::HasKey(storage_key) => {
                 write!(f, "{}/{}", HAS_KEY_PREFIX, storage_key)
             }
+            Path::IbcClientState(client_id) => write!(
+                f,
+                "{}/{}/{}",
+                IBC_PREFIX, IBC_CLIENT_STATE_PREFIX, client_id
+            ),
+            Path::IbcConsensusState(client_id, height) => write!(
+                f,
+                "{}/{}/{}/{}-{}",
+                IBC_PREFIX,
+                IBC_CONSENSUS_STATE_PREFIX,
+                client_id,
+                height.revision_number,
+                height.revision_height
+            ),
+            Path::IbcConnection(conn_id) => {
+                write!(
+                    f,
+                    "{}/{}/{}",
+                    IBC_PREFIX, IBC_CONNECTION_PREFIX, conn_id
+                )
+            }
+            Path::IbcChannel(port_channel_id) => write!(
+                f,
+                "{}/{}/{}/{}",
+                IBC_PREFIX,
+                IBC_CHANNEL_PREFIX,
+                port_channel_id.port_id,
+                port_channel_id.channel_id
+            ),
+            Path::IbcPacketCommitment(port_channel_id, sequence) => write!(
+                f,
+                "{}/{}/{}/{}/{}",
+                IBC_PREFIX,
+                IBC_COMMITMENT_PREFIX,
+                port_channel_id.port_id,
+                port_channel_id.channel_id,
+                sequence
+            ),
+            Path::IbcPacketReceipt(port_channel_id, sequence) => write!(
+                f,
+                "{}/{}/{}/{}/{}",
+                IBC_PREFIX,
+                IBC_RECEIPT_PREFIX,
+                port_channel_id.port_id,
+                port_channel_id.channel_id,
+                sequence
+            ),
+            Path::IbcPacketAck(port_channel_id, sequence) => write!(
+                f,
+                "{}/{}/{}/{}/{}",
+                IBC_PREFIX,
+                IBC_ACK_PREFIX,
+                port_channel_id.port_id,
+                port_channel_id.channel_id,
+                sequence
+            ),
+            Path::IbcUnreceivedPackets(port_channel_id) => write!(
+                f,
+                "{}/{}/{}/{}",
+                IBC_PREFIX,
+                IBC_UNRECEIVED_PACKETS_PREFIX,
+                port_channel_id.port_id,
+                port_channel_id.channel_id
+            ),
+            Path::IbcUnreceivedAcks(port_channel_id) => write!(
+                f,
+                "{}/{}/{}/{}",
+                IBC_PREFIX,
+                IBC_UNRECEIVED_ACKS_PREFIX,
+                port_channel_id.port_id,
+                port_channel_id.channel_id
+            ),
+            Path::IbcProofSpecs => {
+                write!(f, "{}/{}", IBC_PREFIX, IBC_PROOF_SPECS_PATH)
+            }
         }
     }
 }
@@ -64,6 +177,10 @@ impl FromStr for Path {
     type Err = PathParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // IBC identifiers are case-sensitive
+        if let Some((IBC_PREFIX, ibc_path)) = s.split_once("/") {
+            return parse_ibc_path(ibc_path);
+        }
         let path = s.to_lowercase();
         match path.as_str() {
             DRY_RUN_TX_PATH => Ok(Self::DryRunTx),
@@ -90,6 +207,89 @@ impl FromStr for Path {
     }
 }
 
+/// Parse an IBC query path without the IBC prefix
+fn parse_ibc_path(path: &str) -> Result<Path, PathParseError> {
+    let invalid = || PathParseError::InvalidIbcPath(path.to_string());
+    if path == IBC_PROOF_SPECS_PATH {
+        return Ok(Path::IbcProofSpecs);
+    }
+    let (prefix, ids) = path.split_once("/").ok_or_else(invalid)?;
+    let ids: Vec<&str> = ids.split('/').collect();
+    match (prefix, &ids[..]) {
+        (IBC_CLIENT_STATE_PREFIX, [client_id]) => {
+            let client_id = ClientId::from_str(client_id)
+                .map_err(|e| PathParseError::InvalidIbcId(e.to_string()))?;
+            Ok(Path::IbcClientState(client_id))
+        }
+        (IBC_CONSENSUS_STATE_PREFIX, [client_id, height]) => {
+            let client_id = ClientId::from_str(client_id)
+                .map_err(|e| PathParseError::InvalidIbcId(e.to_string()))?;
+            let height = parse_height(height).ok_or_else(invalid)?;
+            Ok(Path::IbcConsensusState(client_id, height))
+        }
+        (IBC_CONNECTION_PREFIX, [conn_id]) => {
+            let conn_id = ConnectionId::from_str(conn_id)
+                .map_err(|e| PathParseError::InvalidIbcId(e.to_string()))?;
+            Ok(Path::IbcConnection(conn_id))
+        }
+        (IBC_CHANNEL_PREFIX, [port_id, channel_id]) => {
+            let port_channel_id = parse_port_channel_id(port_id, channel_id)?;
+            Ok(Path::IbcChannel(port_channel_id))
+        }
+        (IBC_COMMITMENT_PREFIX, [port_id, channel_id, sequence]) => {
+            let port_channel_id = parse_port_channel_id(port_id, channel_id)?;
+            let sequence = parse_sequence(sequence).ok_or_else(invalid)?;
+            Ok(Path::IbcPacketCommitment(port_channel_id, sequence))
+        }
+        (IBC_RECEIPT_PREFIX, [port_id, channel_id, sequence]) => {
+            let port_channel_id = parse_port_channel_id(port_id, channel_id)?;
+            let sequence = parse_sequence(sequence).ok_or_else(invalid)?;
+            Ok(Path::IbcPacketReceipt(port_channel_id, sequence))
+        }
+        (IBC_ACK_PREFIX, [port_id, channel_id, sequence]) => {
+            let port_channel_id = parse_port_channel_id(port_id, channel_id)?;
+            let sequence = parse_sequence(sequence).ok_or_else(invalid)?;
+            Ok(Path::IbcPacketAck(port_channel_id, sequence))
+        }
+        (IBC_UNRECEIVED_PACKETS_PREFIX, [port_id, channel_id]) => {
+            let port_channel_id = parse_port_channel_id(port_id, channel_id)?;
+            Ok(Path::IbcUnreceivedPackets(port_channel_id))
+        }
+        (IBC_UNRECEIVED_ACKS_PREFIX, [port_id, channel_id]) => {
+            let port_channel_id = parse_port_channel_id(port_id, channel_id)?;
+            Ok(Path::IbcUnreceivedAcks(port_channel_id))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn parse_port_channel_id(
+    port_id: &str,
+    channel_id: &str,
+) -> Result<PortChannelId, PathParseError> {
+    let port_id = PortId::from_str(port_id)
+        .map_err(|e| PathParseError::InvalidIbcId(e.to_string()))?;
+    let channel_id = ChannelId::from_str(channel_id)
+        .map_err(|e| PathParseError::InvalidIbcId(e.to_string()))?;
+    Ok(PortChannelId {
+        port_id,
+        channel_id,
+    })
+}
+
+/// Parse a height `<revision_number>-<revision_height>`
+fn parse_height(height: &str) -> Option<Height> {
+    let (revision_number, revision_height) = height.split_once("-")?;
+    Some(Height::new(
+        revision_number.parse().ok()?,
+        revision_height.parse().ok()?,
+    ))
+}
+
+fn parse_sequence(sequence: &str) -> Option<Sequence> {
+    sequence.parse::<u64>().ok().map(Sequence::from)
+}
+
 impl From<Path> for tendermint::abci::Path {
     fn from(path: Path) -> Self {
         let path = path.to_string();
@@ -106,4 +306,51 @@ pub enum PathParseError {
     InvalidPath(String),
     #[error("Invalid storage key: {0}")]
     InvalidStorageKey(storage::Error),
+    #[error("Unrecognized IBC query path: {0}")]
+    InvalidIbcPath(String),
+    #[error("Invalid IBC identifier: {0}")]
+    InvalidIbcId(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ibc_path_round_trip() {
+        let client_id = ClientId::from_str("07-tendermint-0").unwrap();
+        let port_channel_id = PortChannelId {
+            port_id: PortId::from_str("transfer").unwrap(),
+            channel_id: ChannelId::from_str("channel-0").unwrap(),
+        };
+        let sequence = Sequence::from(1);
+        let paths = vec![
+            Path::IbcClientState(client_id.clone()),
+            Path::IbcConsensusState(client_id, Height::new(0, 10)),
+            Path::IbcConnection(
+                ConnectionId::from_str("connection-0").unwrap(),
+            ),
+            Path::IbcChannel(port_channel_id.clone()),
+            Path::IbcPacketCommitment(port_channel_id.clone(), sequence),
+            Path::IbcPacketReceipt(port_channel_id.clone(), sequence),
+            Path::IbcPacketAck(port_channel_id.clone(), sequence),
+            Path::IbcUnreceivedPackets(port_channel_id.clone()),
+            Path::IbcUnreceivedAcks(port_channel_id),
+            Path::IbcProofSpecs,
+        ];
+        for path in paths {
+            let parsed = Path::from_str(&path.to_string())
+                .expect("parsing the IBC path shouldn't fail");
+            assert_eq!(parsed.to_string(), path.to_string());
+        }
+    }
+
+    #[test]
+    fn test_invalid_ibc_path() {
+        assert!(Path::from_str("ibc/client_state").is_err());
+        assert!(Path::from_str("ibc/commitment/transfer/channel-0").is_err());
+        assert!(
+            Path::from_str("ibc/consensus_state/07-tendermint-0/10").is_err()
+        );
+    }
 }
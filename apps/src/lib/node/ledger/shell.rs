@@ -7,12 +7,13 @@
 //! More info in <https://github.com/anoma/anoma/issues/362>.
 
 use std::cmp::max;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::hash::Hash;
 use std::mem;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::mpsc::Sender;
 
 use anoma::ledger::gas::BlockGasMeter;
 use anoma::ledger::parameters::Parameters;
@@ -22,7 +23,8 @@ use anoma::ledger::pos::anoma_proof_of_stake::types::{
 use anoma::ledger::pos::anoma_proof_of_stake::PosBase;
 use anoma::ledger::pos::PosParams;
 use anoma::ledger::storage::write_log::WriteLog;
-use anoma::ledger::{ibc, parameters, pos};
+use anoma::ledger::upgrade::{self, UpgradeCheck, UpgradePlan};
+use anoma::ledger::{governance, ibc, inflation, parameters, pos, treasury};
 use anoma::proto::{self, Tx};
 use anoma::types::address::Address;
 use anoma::types::chain::ChainId;
@@ -30,12 +32,13 @@ use anoma::types::storage::{BlockHash, BlockHeight, Key};
 use anoma::types::time::{DateTime, DateTimeUtc, TimeZone, Utc};
 use anoma::types::transaction::{process_tx, TxType, WrapperTx};
 use anoma::types::{address, key, token};
+use anoma::vm::{self, WasmCodeKind};
 use borsh::{BorshDeserialize, BorshSerialize};
 #[cfg(not(feature = "dev"))]
 use sha2::{Digest, Sha256};
 use tendermint::block::Header;
 use tendermint_proto::abci::{
-    self, ConsensusParams, Evidence, ValidatorUpdate,
+    self, ConsensusParams, Evidence, ValidatorUpdate, VoteInfo,
 };
 use tendermint_proto::crypto::ProofOps;
 use tendermint_proto::types::EvidenceParams;
@@ -59,16 +62,27 @@ pub enum Error {
     ChainId(String),
     #[error("Error decoding a transaction from bytes: {0}")]
     TxDecoding(proto::Error),
+    #[error("Invalid transaction code: {0}")]
+    TxCodeValidation(vm::WasmValidationError),
     #[error("Error trying to apply a transaction: {0}")]
     TxApply(protocol::Error),
     #[error("Gas limit exceeding while applying transactions in block")]
     GasOverflow,
     #[error("{0}")]
     Tendermint(tendermint_node::Error),
+    #[error("Started before the height of the protocol upgrade {0}")]
+    UpgradeTooEarly(String),
+    #[error("Error in the protocol upgrade plan: {0}")]
+    UpgradePlan(upgrade::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The names of the protocol upgrades implemented by this version of the
+/// ledger. When a scheduled upgrade is not in this list, the ledger halts
+/// before the block at the upgrade height.
+const UPGRADES: &[&str] = &[];
+
 pub fn reset(config: config::Ledger) -> Result<()> {
     // simply nuke the DB files
     let db_path = config.db_dir();
@@ -106,6 +120,14 @@ pub struct Shell {
     base_dir: PathBuf,
     /// Path to the WASM directory for files used in the genesis block.
     wasm_dir: PathBuf,
+    /// For signalling shut down to the Tendermint node when the ledger halts.
+    /// Send `true` for a graceful shutdown or `false` on a critical error.
+    abort_sender: Sender<bool>,
+    /// For signalling that the ledger halted for a protocol upgrade
+    upgrade_sender: Sender<UpgradePlan>,
+    /// The scheduled protocol upgrade for which the ledger halted. Once set,
+    /// no more blocks are applied or committed.
+    upgrade_halt: Option<UpgradePlan>,
 }
 
 impl Shell {
@@ -116,6 +138,8 @@ impl Shell {
         db_path: impl AsRef<Path>,
         chain_id: ChainId,
         wasm_dir: PathBuf,
+        abort_sender: Sender<bool>,
+        upgrade_sender: Sender<UpgradePlan>,
     ) -> Self {
         let mut storage = storage::open(db_path, chain_id);
         storage
@@ -132,6 +156,9 @@ impl Shell {
             byzantine_validators: vec![],
             base_dir,
             wasm_dir,
+            abort_sender,
+            upgrade_sender,
+            upgrade_halt: None,
         }
     }
 
@@ -166,6 +193,7 @@ impl Shell {
         }
         #[cfg(feature = "dev")]
         let genesis = genesis::genesis();
+        let native_token_supply = genesis.native_token_supply();
 
         let ts: tendermint_proto::google::protobuf::Timestamp =
             init.time.expect("Missing genesis time");
@@ -361,6 +389,21 @@ impl Shell {
             current_epoch,
         );
         ibc::init_genesis_storage(&mut self.storage);
+        governance::init_genesis_storage(
+            &mut self.storage,
+            &genesis.gov_params,
+        );
+        treasury::init_genesis_storage(
+            &mut self.storage,
+            &genesis.treasury_params,
+            genesis.treasury_balance,
+        );
+        inflation::init_genesis_storage(
+            &mut self.storage,
+            &genesis.inflation_params,
+            native_token_supply,
+            current_epoch,
+        );
 
         let evidence_params =
             self.get_evidence_params(&genesis.parameters, &genesis.pos_params);
@@ -381,8 +424,11 @@ impl Shell {
                 )),
             };
             abci_validator.pub_key = Some(pub_key);
-            let power: u64 =
-                validator.pos_data.voting_power(&genesis.pos_params).into();
+            let power: u64 = validator
+                .pos_data
+                .voting_power(&genesis.pos_params)
+                .expect("unexpected validator's voting power")
+                .into();
             abci_validator.power = power
                 .try_into()
                 .expect("unexpected validator's voting power");
@@ -442,6 +488,82 @@ impl Shell {
                     self.read_storage_prefix(&storage_key, query.prove)
                 }
                 Path::HasKey(storage_key) => self.has_storage_key(&storage_key),
+                Path::IbcClientState(client_id) => {
+                    let key = ibc::storage::client_state_key(&client_id);
+                    self.read_ibc_value(&key, query.prove)
+                }
+                Path::IbcConsensusState(client_id, height) => {
+                    let key =
+                        ibc::storage::consensus_state_key(&client_id, height);
+                    self.read_ibc_value(&key, query.prove)
+                }
+                Path::IbcConnection(conn_id) => {
+                    let key = ibc::storage::connection_key(&conn_id);
+                    self.read_ibc_value(&key, query.prove)
+                }
+                Path::IbcChannel(port_channel_id) => {
+                    let key = ibc::storage::channel_key(&port_channel_id);
+                    self.read_ibc_value(&key, query.prove)
+                }
+                Path::IbcPacketCommitment(port_channel_id, sequence) => {
+                    let key = ibc::storage::commitment_key(
+                        &port_channel_id.port_id,
+                        &port_channel_id.channel_id,
+                        sequence,
+                    );
+                    self.read_ibc_value(&key, query.prove)
+                }
+                Path::IbcPacketReceipt(port_channel_id, sequence) => {
+                    let key = ibc::storage::receipt_key(
+                        &port_channel_id.port_id,
+                        &port_channel_id.channel_id,
+                        sequence,
+                    );
+                    self.read_ibc_value(&key, query.prove)
+                }
+                Path::IbcPacketAck(port_channel_id, sequence) => {
+                    let key = ibc::storage::ack_key(
+                        &port_channel_id.port_id,
+                        &port_channel_id.channel_id,
+                        sequence,
+                    );
+                    self.read_ibc_value(&key, query.prove)
+                }
+                Path::IbcUnreceivedPackets(port_channel_id) => self
+                    .filter_ibc_sequences(&query.data, false, |sequence| {
+                        // a packet has been received if its receipt exists
+                        ibc::storage::receipt_key(
+                            &port_channel_id.port_id,
+                            &port_channel_id.channel_id,
+                            sequence.into(),
+                        )
+                    }),
+                Path::IbcUnreceivedAcks(port_channel_id) => self
+                    .filter_ibc_sequences(&query.data, true, |sequence| {
+                        // the commitment is deleted when the ack is received
+                        ibc::storage::commitment_key(
+                            &port_channel_id.port_id,
+                            &port_channel_id.channel_id,
+                            sequence.into(),
+                        )
+                    }),
+                Path::IbcProofSpecs => match self.storage.get_proof_specs() {
+                    Ok(specs) => {
+                        let specs: Vec<Vec<u8>> = specs
+                            .iter()
+                            .map(prost::Message::encode_to_vec)
+                            .collect();
+                        response::Query {
+                            value: specs.try_to_vec().unwrap(),
+                            ..Default::default()
+                        }
+                    }
+                    Err(err) => response::Query {
+                        code: 1,
+                        info: format!("RPC error: {}", err),
+                        ..Default::default()
+                    },
+                },
             },
             Err(err) => response::Query {
                 code: 1,
@@ -461,7 +583,7 @@ impl Shell {
         hash: BlockHash,
         header: Header,
         byzantine_validators: Vec<Evidence>,
-    ) {
+    ) -> Result<()> {
         let height = BlockHeight(header.height.into());
 
         // We can safely reset meter, because if the block is rejected, we'll
@@ -480,6 +602,87 @@ impl Shell {
 
         // The value set will be overwritten if this proposal is rejected.
         self.byzantine_validators = byzantine_validators;
+
+        self.check_upgrade(height)
+    }
+
+    /// Check if a protocol upgrade is scheduled before the block at the
+    /// given height. If this version of the ledger doesn't implement a due
+    /// upgrade, it halts, so that it can be replaced by a version that does.
+    /// The halted ledger still responds to the requests for the block, but it
+    /// doesn't apply or commit it and it shuts down with
+    /// [`Shell::shut_down_on_upgrade_halt`]. A version that implements the
+    /// upgrade applies it at the upgrade height and it refuses to apply any
+    /// blocks before it.
+    fn check_upgrade(&mut self, height: BlockHeight) -> Result<()> {
+        let plan = match upgrade::read_plan(&self.storage) {
+            Ok(plan) => plan,
+            Err(err) => {
+                // The plan may be for an upgrade that this version doesn't
+                // implement, so the ledger must not continue without it
+                tracing::error!(
+                    "The protocol upgrade plan cannot be read: {}. Halting \
+                     at height {}.",
+                    err,
+                    self.storage.last_height
+                );
+                let _ = self.abort_sender.send(false);
+                return Err(Error::UpgradePlan(err));
+            }
+        };
+        match upgrade::check(plan, height, UPGRADES) {
+            UpgradeCheck::Continue => Ok(()),
+            UpgradeCheck::Apply(plan) => {
+                tracing::info!(
+                    "Applying the protocol upgrade \"{}\" at height {}",
+                    plan.name,
+                    height
+                );
+                upgrade::apply(&mut self.storage, &plan).map_err(|err| {
+                    tracing::error!(
+                        "The protocol upgrade \"{}\" cannot be applied: {}",
+                        plan.name,
+                        err
+                    );
+                    let _ = self.abort_sender.send(false);
+                    Error::UpgradePlan(err)
+                })
+            }
+            UpgradeCheck::Halt(plan) => {
+                tracing::info!(
+                    "UPGRADE NEEDED: The protocol upgrade \"{}\" is \
+                     scheduled at height {}, which is not implemented by \
+                     this version of the ledger. Halting at height {}.",
+                    plan.name,
+                    plan.height,
+                    self.storage.last_height
+                );
+                self.upgrade_halt = Some(plan);
+                Ok(())
+            }
+            UpgradeCheck::TooEarly(plan) => {
+                tracing::error!(
+                    "This version of the ledger implements the protocol \
+                     upgrade \"{}\", which is scheduled at height {}, but \
+                     the next block is at height {}. Use the previous \
+                     version until the upgrade height.",
+                    plan.name,
+                    plan.height,
+                    height
+                );
+                let _ = self.abort_sender.send(false);
+                Err(Error::UpgradeTooEarly(plan.name))
+            }
+        }
+    }
+
+    /// If the ledger halted for a protocol upgrade, signal the upgrade and
+    /// shut down the Tendermint node, which in turn shuts down the shell.
+    pub fn shut_down_on_upgrade_halt(&self) {
+        if let Some(plan) = &self.upgrade_halt {
+            let _ = self.upgrade_sender.send(plan.clone());
+            let _ = self.abort_sender.send(true);
+        }
     }
 
     /// Apply PoS slashes from the evidence
@@ -540,41 +743,19 @@ impl Shell {
                             continue;
                         }
                     };
-                let validator_raw_hash = match evidence.validator {
-                    Some(validator) => {
-                        match String::from_utf8(validator.address) {
-                            Ok(raw_hash) => raw_hash,
-                            Err(err) => {
-                                tracing::error!(
-                                    "Evidence failed to decode validator \
-                                     address from utf-8 with {}",
-                                    err
-                                );
-                                continue;
-                            }
+                let validator =
+                    match evidence.validator.as_ref().and_then(|validator| {
+                        self.find_validator(&validator.address)
+                    }) {
+                        Some(validator) => validator,
+                        None => {
+                            tracing::error!(
+                                "Evidence without a known validator {:#?}",
+                                evidence
+                            );
+                            continue;
                         }
-                    }
-                    None => {
-                        tracing::error!(
-                            "Evidence without a validator {:#?}",
-                            evidence
-                        );
-                        continue;
-                    }
-                };
-                let validator = match self
-                    .storage
-                    .read_validator_address_raw_hash(&validator_raw_hash)
-                {
-                    Some(validator) => validator,
-                    None => {
-                        tracing::error!(
-                            "Cannot find validator's address from raw hash {}",
-                            validator_raw_hash
-                        );
-                        continue;
-                    }
-                };
+                    };
                 tracing::info!(
                     "Slashing {} for {} in epoch {}, block height {}",
                     evidence_epoch,
@@ -596,6 +777,340 @@ impl Shell {
         }
     }
 
+    /// Accumulate PoS block rewards for the proposer of the current block and
+    /// for the validators that signed the last block. The rewards accumulated
+    /// in a previous epoch are paid out first.
+    fn reward_validators(&mut self, votes: &[VoteInfo]) {
+        let current_epoch = self.storage.block.epoch;
+        let proposer = self.block_proposer();
+        let signers: Vec<Address> = votes
+            .iter()
+            .filter(|vote| vote.signed_last_block)
+            .filter_map(|vote| vote.validator.as_ref())
+            .filter_map(|validator| self.find_validator(&validator.address))
+            .collect();
+        self.storage.accumulate_block_rewards(
+            current_epoch,
+            proposer.as_ref(),
+            &signers,
+        );
+    }
+
+    /// Record the validators' votes on the last block, and slash and jail the
+    /// validators that have missed signing too many blocks in the signing
+    /// window.
+    fn slash_offline_validators(
+        &mut self,
+        height: BlockHeight,
+        votes: &[VoteInfo],
+    ) {
+        let pos_params = self.storage.read_pos_params();
+        let current_epoch = self.storage.block.epoch;
+        let votes: Vec<(Address, bool)> = votes
+            .iter()
+            .filter_map(|vote| {
+                let validator = vote.validator.as_ref()?;
+                let address = self.find_validator(&validator.address)?;
+                Some((address, vote.signed_last_block))
+            })
+            .collect();
+        match self.storage.record_block_votes(
+            &pos_params,
+            current_epoch,
+            height,
+            votes,
+        ) {
+            Ok(slashed) => {
+                for validator in slashed {
+                    tracing::info!(
+                        "Slashed and jailed validator {} for downtime",
+                        validator
+                    );
+                }
+            }
+            Err(err) => {
+                tracing::error!("Error in recording block votes: {}", err)
+            }
+        }
+    }
+
+    /// Update the inflation rate and mint the inflation for the new epoch.
+    /// The PoS share of it is paid out with the block rewards of the last
+    /// epoch, so this must be called before the rewards are distributed.
+    fn apply_inflation(&mut self) {
+        let (current_epoch, _gas) = self.storage.get_current_epoch();
+        match inflation::update(&mut self.storage, current_epoch) {
+            Ok(Some(state)) => tracing::info!(
+                "Inflation rate {} at staking ratio {}, minted {} into the \
+                 treasury and {} for PoS rewards",
+                state.rate,
+                state.staking_ratio,
+                state.treasury_minted,
+                state.pos_minted,
+            ),
+            Ok(None) => {
+                inflation::discard_pos_rewards(&mut self.storage, current_epoch)
+            }
+            Err(err) => {
+                tracing::error!("Error in applying inflation: {}", err);
+                // The block rewards haven't been scaled to the inflation, so
+                // they must not be distributed
+                inflation::discard_pos_rewards(&mut self.storage, current_epoch)
+            }
+        }
+    }
+
+    /// Charge the fees of the wrapper transactions in the block. The
+    /// treasury's share of each fee is credited to the treasury and the rest
+    /// to the block proposer. Returns the indices of the wrapper transactions
+    /// whose fee could not be paid, which must not be applied.
+    fn charge_fees(&mut self, txs: &[shim::TxBytes]) -> HashSet<usize> {
+        let wrappers: Vec<(usize, WrapperTx)> = txs
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, tx)| {
+                match process_tx(Tx::try_from(&tx[..]).ok()?) {
+                    Ok(TxType::Wrapper(tx)) => {
+                        Some((ix, WrapperTx::try_from(&tx).ok()?))
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        if wrappers.is_empty() {
+            return HashSet::new();
+        }
+        let params = match treasury::read_params(&self.storage) {
+            Ok(Some(params)) => params,
+            Ok(None) => {
+                tracing::error!("Treasury parameters are missing");
+                return wrappers.into_iter().map(|(ix, _)| ix).collect();
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Error in reading treasury parameters: {}",
+                    err
+                );
+                return wrappers.into_iter().map(|(ix, _)| ix).collect();
+            }
+        };
+        let proposer = self.block_proposer();
+        let mut unpaid = HashSet::new();
+        for (ix, wrapper) in wrappers {
+            let payer = wrapper.fee_payer();
+            match treasury::pay_fee(
+                &mut self.storage,
+                &params,
+                &wrapper.fee.token,
+                wrapper.fee.amount,
+                &payer,
+                proposer.as_ref(),
+            ) {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::info!(
+                        "The fee payer {} has insufficient balance to pay \
+                         fee {}",
+                        payer,
+                        wrapper.fee.amount
+                    );
+                    unpaid.insert(ix);
+                }
+                Err(err) => {
+                    tracing::error!("Error in charging a fee: {}", err);
+                    unpaid.insert(ix);
+                }
+            }
+        }
+        unpaid
+    }
+
+    /// Tally the governance proposals whose grace epoch has been reached. The
+    /// code of a passed proposal is executed with governance authority.
+    fn finalize_governance_proposals(&mut self) {
+        let current_epoch = self.storage.block.epoch;
+        let proposals = match governance::proposals_to_finalize(
+            &self.storage,
+            current_epoch,
+        ) {
+            Ok(proposals) => proposals,
+            Err(err) => {
+                tracing::error!(
+                    "Error in reading governance proposals: {}",
+                    err
+                );
+                return;
+            }
+        };
+        if proposals.is_empty() {
+            return;
+        }
+        let params = match governance::read_params(&self.storage) {
+            Ok(Some(params)) => params,
+            Ok(None) => {
+                tracing::error!("Governance parameters are missing");
+                return;
+            }
+            Err(err) => {
+                tracing::error!(
+                    "Error in reading governance parameters: {}",
+                    err
+                );
+                return;
+            }
+        };
+        for (id, proposal) in proposals {
+            let tally = match governance::tally(&self.storage, id, &proposal) {
+                Ok(tally) => tally,
+                Err(err) => {
+                    tracing::error!(
+                        "Error in tallying governance proposal {}: {}",
+                        id,
+                        err
+                    );
+                    continue;
+                }
+            };
+            let passed = params.is_passed(&tally);
+            tracing::info!(
+                "Governance proposal {} {} with tally {:?}",
+                id,
+                if passed { "passed" } else { "was rejected" },
+                tally
+            );
+            let code_accepted = if passed {
+                // The deposit is returned before the code is executed,
+                // because the code's changes are applied from the write log
+                // on top of the storage
+                if let Err(err) =
+                    governance::refund_deposit(&mut self.storage, id, &proposal)
+                {
+                    tracing::error!(
+                        "Error in refunding governance proposal {} deposit: {}",
+                        id,
+                        err
+                    );
+                }
+                self.execute_governance_proposal(id)
+            } else {
+                if let Err(err) = governance::transfer_deposit_to_treasury(
+                    &mut self.storage,
+                    id,
+                ) {
+                    tracing::error!(
+                        "Error in transferring governance proposal {} deposit \
+                         to the treasury: {}",
+                        id,
+                        err
+                    );
+                }
+                None
+            };
+            let result = governance::ProposalResult {
+                tally,
+                passed,
+                code_accepted,
+            };
+            if let Err(err) = governance::write_result(
+                &mut self.storage,
+                id,
+                &proposal,
+                &result,
+            ) {
+                tracing::error!(
+                    "Error in writing governance proposal {} result: {}",
+                    id,
+                    err
+                );
+            }
+        }
+    }
+
+    /// Execute the code of a passed governance proposal, if any, with the
+    /// proposal ID as its data. While the code is being executed, the
+    /// proposal's pending key is present in storage, which gives the
+    /// transaction governance authority. Returns whether the transaction has
+    /// been accepted, or `None` if the proposal has no code.
+    fn execute_governance_proposal(&mut self, id: u64) -> Option<bool> {
+        let code = match governance::read_proposal_code(&self.storage, id) {
+            Ok(Some(code)) => code,
+            Ok(None) => return None,
+            Err(err) => {
+                tracing::error!(
+                    "Error in reading governance proposal {} code: {}",
+                    id,
+                    err
+                );
+                return Some(false);
+            }
+        };
+        let pending_key = governance::proposal_pending_key(id);
+        self.storage
+            .write(&pending_key, vec![])
+            .expect("Must be able to write a governance proposal pending key");
+        let tx = Tx::new(code, Some(id.try_to_vec().unwrap()));
+        let accepted = match protocol::apply_tx(
+            &tx.to_bytes(),
+            &mut self.gas_meter,
+            &mut self.write_log,
+            &self.storage,
+        ) {
+            Ok(result) if result.is_accepted() => {
+                tracing::info!(
+                    "Governance proposal {} code has been applied {}",
+                    id,
+                    result
+                );
+                self.write_log.commit_tx();
+                true
+            }
+            Ok(result) => {
+                tracing::info!(
+                    "Governance proposal {} code has been rejected {}",
+                    id,
+                    result
+                );
+                self.write_log.drop_tx();
+                false
+            }
+            Err(err) => {
+                tracing::info!(
+                    "Governance proposal {} code failed with: {}",
+                    id,
+                    err
+                );
+                self.write_log.drop_tx();
+                false
+            }
+        };
+        self.storage
+            .delete(&pending_key)
+            .expect("Must be able to delete a governance proposal pending key");
+        Some(accepted)
+    }
+
+    /// Find the address of the proposer of the current block.
+    fn block_proposer(&self) -> Option<Address> {
+        self.storage.header.as_ref().and_then(|header| {
+            self.find_validator(header.proposer_address.as_bytes())
+        })
+    }
+
+    /// Find a validator's address from its Tendermint address, which is the
+    /// raw hash of the validator's consensus key.
+    fn find_validator(&self, tm_address: &[u8]) -> Option<Address> {
+        let raw_hash = hex::encode_upper(tm_address);
+        let owner = self.storage.read_consensus_key_owner(&raw_hash);
+        if owner.is_none() {
+            tracing::error!(
+                "Cannot find validator's address from consensus key raw hash \
+                 {}",
+                raw_hash
+            );
+        }
+        owner.map(|owner| owner.validator)
+    }
+
     /// INVARIANT: This method must be stateless.
     pub fn verify_header(
         &self,
@@ -733,6 +1248,11 @@ impl Shell {
         &mut self,
         req: shim::request::FinalizeBlock,
     ) -> Result<shim::response::FinalizeBlock> {
+        // The block at the upgrade height must only be applied by a version
+        // that implements the upgrade
+        if self.upgrade_halt.is_some() {
+            return Ok(shim::response::FinalizeBlock::default());
+        }
         let header = self
             .storage
             .header
@@ -747,11 +1267,31 @@ impl Shell {
             .expect("Must be able to update epoch");
 
         self.slash();
+        // The inflation is minted directly in storage, so it must happen
+        // before any changes are written to the write log in this block
+        if new_epoch {
+            self.apply_inflation();
+        }
+        self.reward_validators(&req.votes);
+        self.slash_offline_validators(height, &req.votes);
+        let unpaid_fees = self.charge_fees(&req.txs);
+        if new_epoch {
+            self.finalize_governance_proposals();
+        }
 
         let mut response = shim::response::FinalizeBlock::default();
-        for tx in &req.txs {
+        for (ix, tx) in req.txs.iter().enumerate() {
             let mut tx_result =
                 Event::new_tx_event(EventType::Applied, tx, req.height);
+            if unpaid_fees.contains(&ix) {
+                tx_result["info"] =
+                    "The fee of the wrapper transaction could not be paid"
+                        .into();
+                tx_result["gas_used"] = "0".into();
+                tx_result["code"] = "2".into();
+                response.events.push(tx_result.into());
+                continue;
+            }
             match protocol::apply_tx(
                 tx,
                 &mut self.gas_meter,
@@ -872,6 +1412,11 @@ impl Shell {
     /// hash.
     pub fn commit(&mut self) -> response::Commit {
         let mut response = response::Commit::default();
+        // Keep the last committed block when halted for an upgrade
+        if self.upgrade_halt.is_some() {
+            response.data = self.storage.merkle_root().0;
+            return response;
+        }
         // commit changes from the write-log to storage
         self.write_log
             .commit_block(&mut self.storage)
@@ -903,8 +1448,23 @@ impl Shell {
         r#_type: MempoolTxType,
     ) -> response::CheckTx {
         let mut response = response::CheckTx::default();
-        match Tx::try_from(tx_bytes).map_err(Error::TxDecoding) {
-            Ok(_) => response.log = String::from("Mempool validation passed"),
+        match Tx::try_from(tx_bytes)
+            .map_err(Error::TxDecoding)
+            .and_then(|tx| match process_tx(tx) {
+                // The code of a raw transaction is checked on submission.
+                // The inner transaction of a wrapper is encrypted, so its
+                // code cannot be checked here. The ledger doesn't decrypt
+                // and apply inner transactions yet (see
+                // `protocol::apply_tx`), so they cannot bypass the check.
+                // Once it does, the decrypted code must be checked with
+                // `vm::validate_untrusted_code` before it's applied.
+                Ok(TxType::Raw(tx)) => {
+                    vm::validate_untrusted_code(&tx.code, WasmCodeKind::Tx)
+                        .map_err(Error::TxCodeValidation)
+                }
+                _ => Ok(()),
+            }) {
+            Ok(()) => response.log = String::from("Mempool validation passed"),
             Err(msg) => {
                 response.code = 1;
                 response.log = msg.to_string();
@@ -1043,6 +1603,61 @@ impl Shell {
         }
     }
 
+    /// Query to read an IBC state. The response includes the height of the
+    /// last committed block at which the value and its proof are read.
+    fn read_ibc_value(&self, key: &Key, is_proven: bool) -> response::Query {
+        let response = self.read_storage_value(key, is_proven);
+        response::Query {
+            height: self.storage.last_height.0 as _,
+            ..response
+        }
+    }
+
+    /// Query to filter the IBC packet sequences [`Vec<u64>`] encoded with
+    /// [`BorshSerialize`] in the request data to the ones for which the
+    /// existence of the key built by `key_fn` matches `exists`. The value in
+    /// successful response is the filtered sequences encoded in the same way.
+    fn filter_ibc_sequences(
+        &self,
+        data: &[u8],
+        exists: bool,
+        key_fn: impl Fn(u64) -> Key,
+    ) -> response::Query {
+        let sequences = match Vec::<u64>::try_from_slice(data) {
+            Ok(sequences) => sequences,
+            Err(err) => {
+                return response::Query {
+                    code: 1,
+                    info: format!("Invalid packet sequences: {}", err),
+                    ..Default::default()
+                };
+            }
+        };
+        let mut filtered = vec![];
+        for sequence in sequences {
+            let key = key_fn(sequence);
+            match self.storage.has_key(&key) {
+                Ok((has_key, _gas)) => {
+                    if has_key == exists {
+                        filtered.push(sequence)
+                    }
+                }
+                Err(err) => {
+                    return response::Query {
+                        code: 2,
+                        info: format!("Storage error: {}", err),
+                        ..Default::default()
+                    };
+                }
+            }
+        }
+        response::Query {
+            value: filtered.try_to_vec().unwrap(),
+            height: self.storage.last_height.0 as _,
+            ..Default::default()
+        }
+    }
+
     /// Query to check if a storage key exists.
     fn has_storage_key(&self, key: &Key) -> response::Query {
         match self.storage.has_key(key) {
@@ -1108,3 +1723,190 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+
+    use anoma::ledger::pos::anoma_proof_of_stake::types::BasisPoints;
+    use anoma::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use anoma::types::key::ed25519::testing::{keypair_1, keypair_2};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// Initialize a shell with a single genesis validator with the given PoS
+    /// parameters and return it together with the validator's address and the
+    /// Tendermint address of its consensus key.
+    fn setup(params: &PosParams) -> (Shell, TempDir, Address, Vec<u8>) {
+        let base_dir =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let (abort_sender, _abort_receiver) = mpsc::channel();
+        let (upgrade_sender, _upgrade_receiver) = mpsc::channel();
+        let mut shell = Shell::new(
+            base_dir.path().to_owned(),
+            base_dir.path().join("db"),
+            ChainId::default(),
+            base_dir.path().join("wasm"),
+            abort_sender,
+            upgrade_sender,
+        );
+
+        let address = established_address_1();
+        let consensus_key = keypair_1().public;
+        let validator = pos::GenesisValidator {
+            address: address.clone(),
+            staking_reward_address: established_address_2(),
+            tokens: token::Amount::whole(100_000),
+            consensus_key: consensus_key.clone(),
+            staking_reward_key: keypair_2().public,
+            commission_rate: BasisPoints::new(500),
+        };
+        let current_epoch = shell.storage.block.epoch;
+        pos::init_genesis_storage(
+            &mut shell.storage,
+            params,
+            [validator].iter(),
+            current_epoch,
+        );
+
+        // The Tendermint address is derived independently of the ledger's
+        // raw hash of the key
+        let consensus_key: ed25519_dalek::PublicKey = consensus_key.into();
+        let tm_key =
+            tendermint::PublicKey::from_raw_ed25519(consensus_key.as_bytes())
+                .expect("Consensus key must be a valid Tendermint key");
+        let tm_address = tendermint::account::Id::from(tm_key);
+
+        (shell, base_dir, address, tm_address.as_bytes().to_vec())
+    }
+
+    /// Test that a validator can be found from the Tendermint address of its
+    /// consensus key.
+    #[test]
+    fn test_find_validator() {
+        let (shell, _base_dir, address, tm_address) =
+            setup(&PosParams::default());
+
+        assert_eq!(shell.find_validator(&tm_address), Some(address));
+        assert_eq!(shell.find_validator(&[0; 20]), None);
+    }
+
+    /// Test that the validator from the evidence is slashed.
+    #[test]
+    fn test_slash_evidence() {
+        let (mut shell, _base_dir, address, tm_address) =
+            setup(&PosParams::default());
+        assert!(shell.storage.read_validator_slashes(&address).is_empty());
+
+        shell.byzantine_validators.push(Evidence {
+            r#type: abci::EvidenceType::DuplicateVote as i32,
+            validator: Some(abci::Validator {
+                address: tm_address,
+                power: 100_000,
+            }),
+            height: 1,
+            ..Evidence::default()
+        });
+        shell.slash();
+
+        let slashes = shell.storage.read_validator_slashes(&address);
+        assert_eq!(slashes.len(), 1);
+        assert!(matches!(
+            slashes[0].r#type,
+            pos::types::SlashType::DuplicateVote
+        ));
+        assert_eq!(slashes[0].block_height, 1);
+    }
+
+    /// Test that a validator who missed signing too many blocks, given by the
+    /// votes from Tendermint, is slashed and jailed.
+    #[test]
+    fn test_slash_offline_validator() {
+        let params = PosParams {
+            signed_blocks_window: 4,
+            max_missed_blocks_rate: BasisPoints::new(5000),
+            ..PosParams::default()
+        };
+        let (mut shell, _base_dir, address, tm_address) = setup(&params);
+        let vote = |signed_last_block| VoteInfo {
+            validator: Some(abci::Validator {
+                address: tm_address.clone(),
+                power: 100_000,
+            }),
+            signed_last_block,
+        };
+
+        // Missing half of the blocks in the window is tolerated
+        for (height, signed) in [true, false, true, false].iter().enumerate() {
+            shell.slash_offline_validators(
+                BlockHeight(height as u64 + 1),
+                &[vote(*signed)],
+            );
+        }
+        assert!(shell.storage.read_validator_slashes(&address).is_empty());
+        assert_eq!(shell.storage.read_validator_jailed_epoch(&address), None);
+
+        // Missing one more block in the window is not
+        shell.slash_offline_validators(BlockHeight(5), &[vote(false)]);
+        let slashes = shell.storage.read_validator_slashes(&address);
+        assert_eq!(slashes.len(), 1);
+        assert!(matches!(slashes[0].r#type, pos::types::SlashType::Downtime));
+        assert_eq!(slashes[0].block_height, 5);
+        assert_eq!(
+            shell.storage.read_validator_jailed_epoch(&address),
+            Some(shell.storage.block.epoch)
+        );
+    }
+
+    /// Test that the ledger halts at the height of a scheduled protocol
+    /// upgrade that it doesn't implement and that once halted, it doesn't
+    /// apply or commit the block.
+    #[test]
+    fn test_check_upgrade() {
+        let (mut shell, _base_dir, _address, _tm_address) =
+            setup(&PosParams::default());
+        let (abort_sender, abort_receiver) = mpsc::channel();
+        let (upgrade_sender, upgrade_receiver) = mpsc::channel();
+        shell.abort_sender = abort_sender;
+        shell.upgrade_sender = upgrade_sender;
+        let plan = UpgradePlan {
+            name: "upgrade-1".to_owned(),
+            height: BlockHeight(10),
+        };
+        shell
+            .storage
+            .write(&upgrade::plan_key(), plan.try_to_vec().unwrap())
+            .expect("write failed");
+
+        // Before the upgrade height, the ledger continues
+        shell.check_upgrade(BlockHeight(9)).unwrap();
+        assert_eq!(shell.upgrade_halt, None);
+        shell.shut_down_on_upgrade_halt();
+        assert!(abort_receiver.try_recv().is_err());
+        assert!(upgrade_receiver.try_recv().is_err());
+
+        // At the upgrade height, it halts and shuts down
+        shell
+            .storage
+            .begin_block(BlockHash::default(), BlockHeight(10))
+            .unwrap();
+        shell.check_upgrade(BlockHeight(10)).unwrap();
+        assert_eq!(shell.upgrade_halt, Some(plan.clone()));
+        shell.shut_down_on_upgrade_halt();
+        assert_eq!(upgrade_receiver.try_recv(), Ok(plan.clone()));
+        assert_eq!(abort_receiver.try_recv(), Ok(true));
+        let last_height = shell.storage.last_height;
+        let root = shell.storage.merkle_root();
+        let response = shell.commit();
+        assert_eq!(shell.storage.last_height, last_height);
+        assert_eq!(response.data, root.0);
+
+        // After the upgrade height, e.g. when it's restarted, it halts again
+        shell.upgrade_halt = None;
+        shell.check_upgrade(BlockHeight(11)).unwrap();
+        assert_eq!(shell.upgrade_halt, Some(plan));
+    }
+}
@@ -2,11 +2,14 @@ use std::convert::TryFrom;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::mpsc::Sender;
 use std::task::{Context, Poll};
 
+use anoma::ledger::upgrade::UpgradePlan;
 use anoma::types::chain::ChainId;
 use anoma::types::storage::BlockHeight;
 use futures::future::FutureExt;
+use tendermint_proto::abci::VoteInfo;
 use tower::Service;
 use tower_abci::{BoxError, Request as Req, Response as Resp};
 
@@ -22,6 +25,7 @@ use super::abcipp_shim_types::shim::{
 pub struct AbcippShim {
     service: Shell,
     block_txs: Vec<TxBytes>,
+    last_commit_votes: Vec<VoteInfo>,
 }
 
 impl AbcippShim {
@@ -30,10 +34,20 @@ impl AbcippShim {
         db_path: impl AsRef<Path>,
         chain_id: ChainId,
         wasm_dir: PathBuf,
+        abort_sender: Sender<bool>,
+        upgrade_sender: Sender<UpgradePlan>,
     ) -> Self {
         Self {
-            service: Shell::new(base_dir, db_path, chain_id, wasm_dir),
+            service: Shell::new(
+                base_dir,
+                db_path,
+                chain_id,
+                wasm_dir,
+                abort_sender,
+                upgrade_sender,
+            ),
             block_txs: vec![],
+            last_commit_votes: vec![],
         }
     }
 }
@@ -67,6 +81,13 @@ impl Service<Req> for AbcippShim {
                     _ => Err(Error::ConvertResp(res)),
                 }),
             Req::BeginBlock(block) => {
+                // The votes on the last block are needed for the block
+                // rewards when the block is finalized
+                self.last_commit_votes = block
+                    .last_commit_info
+                    .as_ref()
+                    .map(|info| info.votes.clone())
+                    .unwrap_or_default();
                 // we simply forward BeginBlock request to the PrepareProposal
                 // request
                 self.service
@@ -91,11 +112,13 @@ impl Service<Req> for AbcippShim {
                 });
                 let mut txs = vec![];
                 std::mem::swap(&mut txs, &mut self.block_txs);
+                let votes = std::mem::take(&mut self.last_commit_votes);
 
                 self.service
                     .call(Request::FinalizeBlock(request::FinalizeBlock {
                         height: end.height,
                         txs,
+                        votes,
                     }))
                     .map_err(Error::from)
                     .and_then(|res| match res {
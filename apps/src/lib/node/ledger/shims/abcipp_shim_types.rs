@@ -154,7 +154,7 @@ pub mod shim {
 
     /// Custom types for request payloads
     pub mod request {
-        use tendermint_proto::abci::{Evidence, RequestBeginBlock};
+        use tendermint_proto::abci::{Evidence, RequestBeginBlock, VoteInfo};
         use tendermint_proto::types::Header;
 
         pub struct PrepareProposal {
@@ -192,6 +192,8 @@ pub mod shim {
         pub struct FinalizeBlock {
             pub height: i64,
             pub txs: Vec<super::TxBytes>,
+            /// Votes on the last block from its commit info
+            pub votes: Vec<VoteInfo>,
         }
     }
 
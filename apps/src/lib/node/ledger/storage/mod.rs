@@ -4,6 +4,7 @@
 mod rocksdb;
 
 use std::collections::HashMap;
+use std::convert::TryInto;
 use std::fmt;
 use std::path::Path;
 
@@ -13,12 +14,17 @@ use anoma::types::address::EstablishedAddressGen;
 use anoma::types::chain::ChainId;
 use anoma::types::storage::{BlockHash, BlockHeight, Epoch, Epochs, Key};
 use anoma::types::time::DateTimeUtc;
-use blake2b_rs::{Blake2b, Blake2bBuilder};
-use sparse_merkle_tree::blake2b::Blake2bHasher;
+use ics23::HashOp;
+use sha2::{Digest, Sha256};
 use sparse_merkle_tree::traits::Hasher;
 use sparse_merkle_tree::H256;
 
-pub struct PersistentStorageHasher(Blake2bHasher);
+/// The storage hasher used for the merkle tree. It uses SHA-256 so that the
+/// ICS-23 proofs of the storage can be verified by IBC relayers. Note that
+/// this hasher replaced a Blake2b hasher, which changed the merkle root of
+/// the same state, so a chain started with the Blake2b hasher cannot be
+/// resumed with it and has to be restarted from the genesis.
+pub struct PersistentStorageHasher(Sha256);
 
 pub type PersistentDB = rocksdb::RocksDB;
 
@@ -50,35 +56,36 @@ pub fn open(db_path: impl AsRef<Path>, chain_id: ChainId) -> PersistentStorage {
 
 impl Default for PersistentStorageHasher {
     fn default() -> Self {
-        Self(Blake2bHasher::default())
+        Self(Sha256::default())
     }
 }
 
 impl Hasher for PersistentStorageHasher {
     fn write_h256(&mut self, h: &H256) {
-        self.0.write_h256(h)
+        self.0.update(h.as_slice())
     }
 
     fn finish(self) -> H256 {
-        self.0.finish()
+        let hash = self.0.finalize();
+        let bytes: [u8; 32] = hash
+            .as_slice()
+            .try_into()
+            .expect("Sha256 output conversion to fixed array shouldn't fail");
+        bytes.into()
     }
 }
 
 impl StorageHasher for PersistentStorageHasher {
     fn hash_key(key: &Key) -> H256 {
-        let mut buf = [0u8; 32];
-        let mut hasher = new_blake2b();
-        hasher.update(&types::encode(key));
-        hasher.finalize(&mut buf);
-        buf.into()
+        sha256(&types::encode(key))
     }
 
     fn hash_value(value: impl AsRef<[u8]>) -> H256 {
-        let mut buf = [0u8; 32];
-        let mut hasher = new_blake2b();
-        hasher.update(value.as_ref());
-        hasher.finalize(&mut buf);
-        buf.into()
+        sha256(value.as_ref())
+    }
+
+    fn hash_op() -> Option<HashOp> {
+        Some(HashOp::Sha256)
     }
 }
 
@@ -88,13 +95,21 @@ impl fmt::Debug for PersistentStorageHasher {
     }
 }
 
-fn new_blake2b() -> Blake2b {
-    Blake2bBuilder::new(32).personal(b"anoma storage").build()
+fn sha256(bytes: &[u8]) -> H256 {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    let hash = hasher.finalize();
+    let bytes: [u8; 32] = hash
+        .as_slice()
+        .try_into()
+        .expect("Sha256 output conversion to fixed array shouldn't fail");
+    bytes.into()
 }
 
 #[cfg(test)]
 mod tests {
     use anoma::ledger::storage::types;
+    use prost::Message;
     use tempfile::TempDir;
 
     use super::*;
@@ -250,4 +265,39 @@ mod tests {
         assert_eq!(vp.expect("no VP"), vp1);
         assert_eq!(gas, (key.len() + vp1.len()) as u64);
     }
+
+    #[test]
+    fn test_proof_with_specs() {
+        let db_path =
+            TempDir::new().expect("Unable to create a temporary DB directory");
+        let mut storage = open(db_path.path(), ChainId::default());
+        let key =
+            Key::parse("key".to_owned()).expect("cannot parse the key string");
+        let value_bytes = types::encode(&1_u64);
+        storage
+            .write(&key, value_bytes.clone())
+            .expect("write failed");
+        let root = storage.merkle_root();
+
+        let proof_op = storage.get_proof(&key).expect("get_proof failed");
+        let proof = ics23::CommitmentProof::decode(&proof_op.data[..])
+            .expect("decoding the proof failed");
+        let specs = storage.get_proof_specs().expect("no proof specs");
+        assert_eq!(specs.len(), 1);
+        assert!(ics23::verify_membership(
+            &proof,
+            &specs[0],
+            &root.0,
+            &proof_op.key,
+            &value_bytes,
+        ));
+        // A different value must not be verified by the proof
+        assert!(!ics23::verify_membership(
+            &proof,
+            &specs[0],
+            &root.0,
+            &proof_op.key,
+            &types::encode(&2_u64),
+        ));
+    }
 }
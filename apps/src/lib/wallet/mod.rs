@@ -216,6 +216,28 @@ impl Wallet {
         self.store.get_keys()
     }
 
+    /// Re-associate the alias of the old key with the new key, after the
+    /// public key of an account has been updated. The old key is kept under
+    /// the alias of the new key. Returns the re-associated alias, or `None` if
+    /// any of the keys is not found in the wallet.
+    pub fn rotate_key(
+        &mut self,
+        old_pk: &PublicKey,
+        new_pk: &PublicKey,
+    ) -> Option<String> {
+        let old_alias = self
+            .store
+            .find_alias_by_pkh(&PublicKeyHash::from(old_pk))?;
+        let new_alias = self
+            .store
+            .find_alias_by_pkh(&PublicKeyHash::from(new_pk))?;
+        let alias = self.store.rotate_key(old_pk, new_pk)?;
+        // The cached keys by the swapped aliases are no longer valid
+        self.decrypted_key_cache.remove(&old_alias);
+        self.decrypted_key_cache.remove(&new_alias);
+        Some(alias)
+    }
+
     /// Find the stored address by an alias.
     pub fn find_address(&self, alias: impl AsRef<str>) -> Option<&Address> {
         self.store.find_address(alias)
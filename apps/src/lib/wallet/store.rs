@@ -284,6 +284,47 @@ impl Store {
         true
     }
 
+    /// Re-associate the alias of the old key with the new key, after the
+    /// public key of an account has been updated. The old key is kept under
+    /// the alias of the new key. Returns the re-associated alias, or `None` if
+    /// any of the keys is not found in the store.
+    pub fn rotate_key(
+        &mut self,
+        old_pk: &PublicKey,
+        new_pk: &PublicKey,
+    ) -> Option<Alias> {
+        let old_pkh = PublicKeyHash::from(old_pk);
+        let new_pkh = PublicKeyHash::from(new_pk);
+        let old_alias = self.pkhs.get(&old_pkh)?.clone();
+        let new_alias = self.pkhs.get(&new_pkh)?.clone();
+        if old_alias == new_alias {
+            return Some(old_alias);
+        }
+        if !self.keys.contains_key(&old_alias)
+            || !self.keys.contains_key(&new_alias)
+        {
+            return None;
+        }
+        // Swap the keys
+        let old_key = self.keys.remove(&old_alias).unwrap();
+        let new_key = self.keys.remove(&new_alias).unwrap();
+        self.keys.insert(old_alias.clone(), new_key);
+        self.keys.insert(new_alias.clone(), old_key);
+        self.pkhs.insert(new_pkh.clone(), old_alias.clone());
+        self.pkhs.insert(old_pkh.clone(), new_alias.clone());
+        // Swap the implicit addresses of the keys, if they're under the same
+        // aliases
+        let old_implicit = Address::Implicit(ImplicitAddress::Ed25519(old_pkh));
+        let new_implicit = Address::Implicit(ImplicitAddress::Ed25519(new_pkh));
+        if self.addresses.get(&old_alias) == Some(&old_implicit)
+            && self.addresses.get(&new_alias) == Some(&new_implicit)
+        {
+            self.addresses.insert(old_alias.clone(), new_implicit);
+            self.addresses.insert(new_alias, old_implicit);
+        }
+        Some(old_alias)
+    }
+
     fn decode(data: Vec<u8>) -> Result<Self, toml::de::Error> {
         toml::from_slice(&data)
     }
@@ -335,3 +376,54 @@ const FILE_NAME: &str = "wallet.toml";
 pub fn wallet_file(store_dir: impl AsRef<Path>) -> PathBuf {
     store_dir.as_ref().join(FILE_NAME)
 }
+
+#[cfg(test)]
+mod tests {
+    use anoma::types::key::ed25519::testing;
+
+    use super::*;
+
+    #[test]
+    fn test_rotate_key() {
+        let mut store = Store::default();
+        let old_keypair = testing::keypair_1();
+        let new_keypair = testing::keypair_2();
+        let old_pk = old_keypair.public.clone();
+        let new_pk = new_keypair.public.clone();
+        let old_pkh = PublicKeyHash::from(&old_pk);
+        let new_pkh = PublicKeyHash::from(&new_pk);
+        for (alias, keypair, pkh) in [
+            ("my-key", old_keypair, &old_pkh),
+            ("my-new-key", new_keypair, &new_pkh),
+        ] {
+            let address =
+                Address::Implicit(ImplicitAddress::Ed25519(pkh.clone()));
+            let (keypair, _) = StoredKeypair::new(keypair, None);
+            assert!(store.insert_keypair(alias.into(), keypair, pkh.clone()));
+            assert!(store.insert_address(alias.into(), address));
+        }
+
+        assert_eq!(store.rotate_key(&old_pk, &new_pk), Some("my-key".into()));
+
+        // The alias now points to the new key and the old key is kept
+        let find_pk = |store: &Store, alias: &str| match store.find_key(alias) {
+            Some(StoredKeypair::Raw(keypair)) => keypair.public.clone(),
+            _ => panic!("expected a raw keypair"),
+        };
+        assert_eq!(find_pk(&store, "my-key"), new_pk);
+        assert_eq!(find_pk(&store, "my-new-key"), old_pk);
+        assert_eq!(store.find_alias_by_pkh(&new_pkh), Some("my-key".into()));
+        assert_eq!(
+            store.find_alias_by_pkh(&old_pkh),
+            Some("my-new-key".into())
+        );
+        assert_eq!(
+            store.find_address("my-key"),
+            Some(&Address::Implicit(ImplicitAddress::Ed25519(new_pkh)))
+        );
+
+        // Unknown keys cannot be rotated
+        let unknown_pk = testing::gen_keypair().public;
+        assert_eq!(store.rotate_key(&unknown_pk, &old_pk), None);
+    }
+}
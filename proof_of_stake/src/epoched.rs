@@ -561,7 +561,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap};
     use std::rc::Rc;
 
     use proptest::prelude::*;
@@ -570,7 +570,18 @@ mod tests {
 
     use super::*;
     use crate::types::tests::arb_epoch;
-    use crate::types::BasisPoints;
+    use crate::types::{
+        BasisPoints, BondId, Bonds, Slash, SlashType, Slashes,
+        TotalVotingPowers, Unbonds, ValidatorSet, ValidatorSets,
+        ValidatorStates, ValidatorTotalDeltas, ValidatorVotingPowers,
+        VotingPowerDelta, Withdrawals,
+    };
+    use crate::{
+        become_validator, bond_tokens, record_withdrawal, slash,
+        slashed_amounts, slashed_bond_stake, unbond_tokens, withdraw_unbonds,
+        BecomeValidatorData, BondData, SlashedAmounts, UnbondData,
+        WithdrawData,
+    };
 
     prop_state_machine! {
         #[test]
@@ -588,6 +599,10 @@ mod tests {
         #[test]
         fn epoched_delta_state_machine_with_unbounding_offset(
             sequential 1..20 => EpochedDeltaAbstractStateMachine<OffsetUnboundingLen>);
+
+        #[test]
+        fn pos_tokens_conservation_state_machine(
+            sequential 1..50 => PosAbstractStateMachine);
     }
 
     /// Abstract representation of [`Epoched`].
@@ -1198,6 +1213,11 @@ mod tests {
             1..1_000_u64,
             1..10_000_u64,
             1..10_000_u64,
+            1..10_000_u64,
+            1..10_000_u64,
+            1..1_000_u64,
+            0..10_000_u64,
+            0..10_u64,
         )
             .prop_flat_map(
                 |(
@@ -1208,6 +1228,11 @@ mod tests {
                     block_vote_reward,
                     duplicate_vote_slash_rate,
                     light_client_attack_slash_rate,
+                    max_commission_rate_change,
+                    downtime_slash_rate,
+                    signed_blocks_window,
+                    max_missed_blocks_rate,
+                    min_jail_duration,
                 )| {
                     (pipeline_len + 1..pipeline_len + 10).prop_map(
                         move |unbonding_len| PosParams {
@@ -1223,6 +1248,17 @@ mod tests {
                             light_client_attack_slash_rate: BasisPoints::new(
                                 light_client_attack_slash_rate,
                             ),
+                            max_commission_rate_change: BasisPoints::new(
+                                max_commission_rate_change,
+                            ),
+                            downtime_slash_rate: BasisPoints::new(
+                                downtime_slash_rate,
+                            ),
+                            signed_blocks_window,
+                            max_missed_blocks_rate: BasisPoints::new(
+                                max_missed_blocks_rate,
+                            ),
+                            min_jail_duration,
                         },
                     )
                 },
@@ -1243,4 +1279,508 @@ mod tests {
             .boxed(),
         }
     }
+
+    /// The address of the validator in the state machine test
+    const VALIDATOR: u64 = 1;
+    /// The addresses of the delegators in the state machine test
+    const DELEGATORS: [u64; 2] = [2, 3];
+    /// The address of the PoS account in the state machine test
+    const POS: u64 = 100;
+    /// The address of the PoS slash pool in the state machine test
+    const SLASH_POOL: u64 = 101;
+    /// The most tokens by which the slashes of the tokens unbonded in one
+    /// unbond may be rounded down, per slash. Only the bond delta that is
+    /// split between the bond and the unbond may lose a token per slash, the
+    /// other decremented deltas are slashed whole in both.
+    const SLASH_ROUNDING_TOLERANCE: u64 = 1;
+
+    /// Abstract representation of the PoS system with a single validator,
+    /// used to generate valid transitions.
+    #[derive(Clone, Debug)]
+    struct PosState {
+        params: PosParams,
+        epoch: Epoch,
+        /// The sources' tokens that haven't been bonded. The withdrawn tokens
+        /// are not added back, as the slashed amount isn't tracked here.
+        balances: HashMap<u64, u64>,
+        /// The sources' bonded tokens, not reduced by slashes
+        bonded: HashMap<u64, u64>,
+        /// The sources' unbonded tokens with the epoch from which they can be
+        /// withdrawn
+        unbonded: HashMap<u64, Vec<(Epoch, u64)>>,
+    }
+
+    #[derive(Clone, Debug)]
+    enum PosTransition {
+        NextEpoch,
+        Bond {
+            source: u64,
+            amount: u64,
+        },
+        Unbond {
+            source: u64,
+            amount: u64,
+        },
+        Withdraw {
+            source: u64,
+        },
+        Slash {
+            evidence_epoch: Epoch,
+            rate: BasisPoints,
+        },
+    }
+
+    /// The PoS data of the validator and the token balances, updated by the
+    /// PoS functions.
+    struct ConcretePosState {
+        params: PosParams,
+        epoch: Epoch,
+        total_supply: u64,
+        /// The upper bound of the tokens by which the claims may exceed the
+        /// PoS account due to the slashes rounding, accumulated per unbond
+        rounding_tolerance: u64,
+        /// The sum of the tokens slashed from the bonds and unbonds
+        slashed: u64,
+        /// The sum of the tokens slashed from the withdrawals
+        clawbacks: u64,
+        /// The sum of the clawbacks repaid from the withdrawals
+        repaid: u64,
+        balances: HashMap<u64, u64>,
+        validator_state: ValidatorStates,
+        total_deltas: ValidatorTotalDeltas<i128>,
+        voting_power: ValidatorVotingPowers,
+        total_voting_power: TotalVotingPowers,
+        validator_set: ValidatorSets<u64>,
+        bonds: HashMap<BondId<u64>, Bonds<u64>>,
+        unbonds: HashMap<BondId<u64>, Unbonds<u64>>,
+        withdrawals: HashMap<BondId<u64>, Withdrawals<u64>>,
+        slashes: Slashes,
+    }
+
+    impl ConcretePosState {
+        fn transfer(&mut self, amount: u64, src: u64, dest: u64) {
+            let src_balance = self.balances.entry(src).or_default();
+            *src_balance =
+                src_balance.checked_sub(amount).unwrap_or_else(|| {
+                    panic!(
+                        "Insufficient balance of {} to transfer {}",
+                        src, amount
+                    )
+                });
+            *self.balances.entry(dest).or_default() += amount;
+        }
+
+        /// The tokens that can still be paid out from the PoS account, i.e.
+        /// the bonded and unbonded tokens reduced by the slashes.
+        fn claims(&self) -> u64 {
+            let bonded: u64 = self
+                .bonds
+                .values()
+                .map(|bonds| {
+                    slashed_bond_stake(
+                        bonds,
+                        &self.slashes,
+                        self.epoch + self.params.pipeline_len,
+                    )
+                    .unwrap()
+                })
+                .sum();
+            let unbonded: u64 = self
+                .unbonds
+                .values()
+                .filter_map(|unbonds| {
+                    unbonds.get_at_offset(
+                        self.epoch,
+                        DynEpochOffset::UnbondingLen,
+                        &self.params,
+                    )
+                })
+                .flat_map(|unbond| unbond.deltas.into_iter())
+                .map(|((epoch_start, epoch_end), mut delta)| {
+                    for slash in &self.slashes {
+                        if slash.epoch >= epoch_start
+                            && slash.epoch <= epoch_end
+                        {
+                            delta -= slash.rate.portion_of(delta).unwrap();
+                        }
+                    }
+                    delta
+                })
+                .sum();
+            bonded + unbonded
+        }
+    }
+
+    /// Abstract state machine implementation for the PoS bonds, unbonds,
+    /// withdrawals and slashes.
+    struct PosAbstractStateMachine;
+    impl AbstractStateMachine for PosAbstractStateMachine {
+        type State = PosState;
+        type Transition = PosTransition;
+
+        fn init_state() -> BoxedStrategy<Self::State> {
+            (
+                1..4_u64,
+                1..6_u64,
+                arb_epoch(0..1_000),
+                prop::collection::vec(1..1_000_000_u64, 3),
+            )
+                .prop_map(|(pipeline_len, unbonding_extra, epoch, balances)| {
+                    let params = PosParams {
+                        pipeline_len,
+                        unbonding_len: pipeline_len + unbonding_extra,
+                        ..PosParams::default()
+                    };
+                    let sources = std::iter::once(VALIDATOR)
+                        .chain(DELEGATORS.iter().copied());
+                    PosState {
+                        params,
+                        epoch,
+                        balances: sources.zip(balances).collect(),
+                        bonded: HashMap::default(),
+                        unbonded: HashMap::default(),
+                    }
+                })
+                .boxed()
+        }
+
+        fn transitions(state: &Self::State) -> BoxedStrategy<Self::Transition> {
+            let bond = state.balances.iter().filter(|(_, b)| **b > 0).map(
+                |(source, balance)| {
+                    let source = *source;
+                    (1..=*balance)
+                        .prop_map(move |amount| PosTransition::Bond {
+                            source,
+                            amount,
+                        })
+                        .boxed()
+                },
+            );
+            let unbond = state.bonded.iter().filter(|(_, b)| **b > 0).map(
+                |(source, bonded)| {
+                    let source = *source;
+                    (1..=*bonded)
+                        .prop_map(move |amount| PosTransition::Unbond {
+                            source,
+                            amount,
+                        })
+                        .boxed()
+                },
+            );
+            let withdraw = state
+                .unbonded
+                .iter()
+                .filter(|(_, unbonded)| {
+                    unbonded.iter().any(|(epoch, _)| *epoch <= state.epoch)
+                })
+                .map(|(source, _)| {
+                    Just(PosTransition::Withdraw { source: *source }).boxed()
+                });
+            let epoch: u64 = state.epoch.into();
+            let min_evidence_epoch =
+                epoch.saturating_sub(state.params.unbonding_len);
+            let slash =
+                (arb_epoch(min_evidence_epoch..epoch + 1), 1..=10_000_u64)
+                    .prop_map(|(evidence_epoch, rate)| PosTransition::Slash {
+                        evidence_epoch,
+                        rate: BasisPoints::new(rate),
+                    })
+                    .boxed();
+            let transitions: Vec<BoxedStrategy<PosTransition>> =
+                std::iter::once(Just(PosTransition::NextEpoch).boxed())
+                    .chain(bond)
+                    .chain(unbond)
+                    .chain(withdraw)
+                    .chain(std::iter::once(slash))
+                    .collect();
+            proptest::strategy::Union::new(transitions).boxed()
+        }
+
+        fn apply_abstract(
+            mut state: Self::State,
+            transition: &Self::Transition,
+        ) -> Self::State {
+            match transition {
+                PosTransition::NextEpoch => {
+                    state.epoch = state.epoch + 1_u64;
+                }
+                PosTransition::Bond { source, amount } => {
+                    *state.balances.get_mut(source).unwrap() -= amount;
+                    *state.bonded.entry(*source).or_default() += amount;
+                }
+                PosTransition::Unbond { source, amount } => {
+                    *state.bonded.get_mut(source).unwrap() -= amount;
+                    let withdrawable_epoch =
+                        state.epoch + state.params.unbonding_len;
+                    state
+                        .unbonded
+                        .entry(*source)
+                        .or_default()
+                        .push((withdrawable_epoch, *amount));
+                }
+                PosTransition::Withdraw { source } => {
+                    let epoch = state.epoch;
+                    state.unbonded.get_mut(source).unwrap().retain(
+                        |(withdrawable_epoch, _)| *withdrawable_epoch > epoch,
+                    );
+                }
+                PosTransition::Slash { .. } => {
+                    // The slashed amount is not tracked in the abstract state
+                }
+            }
+            state
+        }
+    }
+
+    impl StateMachineTest for PosAbstractStateMachine {
+        type Abstract = Self;
+        type ConcreteState = ConcretePosState;
+
+        fn init_test(
+            initial_state: <Self::Abstract as AbstractStateMachine>::State,
+        ) -> Self::ConcreteState {
+            let PosState {
+                params,
+                epoch,
+                balances,
+                ..
+            } = initial_state;
+            let mut validator_set: ValidatorSets<u64> =
+                Epoched::init_at_genesis(
+                    ValidatorSet {
+                        active: BTreeSet::default(),
+                        inactive: BTreeSet::default(),
+                    },
+                    epoch,
+                );
+            let BecomeValidatorData {
+                state: validator_state,
+                total_deltas,
+                voting_power,
+                ..
+            } = become_validator(
+                &params,
+                &VALIDATOR,
+                &VALIDATOR,
+                BasisPoints::new(0),
+                &mut validator_set,
+                epoch,
+            );
+            ConcretePosState {
+                total_supply: balances.values().sum(),
+                rounding_tolerance: 0,
+                slashed: 0,
+                clawbacks: 0,
+                repaid: 0,
+                balances,
+                validator_state,
+                total_deltas,
+                voting_power,
+                total_voting_power: EpochedDelta::init_at_genesis(
+                    VotingPowerDelta::default(),
+                    epoch,
+                ),
+                validator_set,
+                bonds: HashMap::default(),
+                unbonds: HashMap::default(),
+                withdrawals: HashMap::default(),
+                slashes: Slashes::default(),
+                params,
+                epoch,
+            }
+        }
+
+        fn apply_concrete(
+            mut state: Self::ConcreteState,
+            transition: &<Self::Abstract as AbstractStateMachine>::Transition,
+        ) -> Self::ConcreteState {
+            let params = state.params.clone();
+            let epoch = state.epoch;
+            match transition {
+                PosTransition::NextEpoch => {
+                    state.epoch = epoch + 1_u64;
+                }
+                PosTransition::Bond { source, amount } => {
+                    let bond_id = BondId {
+                        source: *source,
+                        validator: VALIDATOR,
+                    };
+                    let BondData {
+                        bond,
+                        validator_total_deltas,
+                        validator_voting_power,
+                    } = bond_tokens(
+                        &params,
+                        Some(state.validator_state.clone()),
+                        &bond_id,
+                        state.bonds.remove(&bond_id),
+                        *amount,
+                        Some(state.total_deltas.clone()),
+                        Some(state.voting_power.clone()),
+                        &mut state.total_voting_power,
+                        &mut state.validator_set,
+                        epoch,
+                    )
+                    .unwrap();
+                    state.bonds.insert(bond_id, bond);
+                    state.total_deltas = validator_total_deltas;
+                    state.voting_power = validator_voting_power;
+                    state.transfer(*amount, *source, POS);
+                }
+                PosTransition::Unbond { source, amount } => {
+                    let bond_id = BondId {
+                        source: *source,
+                        validator: VALIDATOR,
+                    };
+                    let bond = state.bonds.get_mut(&bond_id).unwrap();
+                    state.rounding_tolerance +=
+                        SLASH_ROUNDING_TOLERANCE * state.slashes.len() as u64;
+                    let UnbondData { unbond } = unbond_tokens(
+                        &params,
+                        &bond_id,
+                        bond,
+                        state.unbonds.remove(&bond_id),
+                        *amount,
+                        state.slashes.clone(),
+                        &mut state.total_deltas,
+                        &mut state.voting_power,
+                        &mut state.total_voting_power,
+                        &mut state.validator_set,
+                        epoch,
+                    )
+                    .unwrap();
+                    state.unbonds.insert(bond_id, unbond);
+                }
+                PosTransition::Withdraw { source } => {
+                    let bond_id = BondId {
+                        source: *source,
+                        validator: VALIDATOR,
+                    };
+                    let WithdrawData {
+                        unbond,
+                        withdrawn_deltas,
+                        withdrawn,
+                        ..
+                    } = withdraw_unbonds(
+                        &params,
+                        &bond_id,
+                        state.unbonds.remove(&bond_id),
+                        state.slashes.clone(),
+                        None,
+                        &HashMap::default(),
+                        epoch,
+                    )
+                    .unwrap();
+                    state.unbonds.insert(bond_id.clone(), unbond);
+                    let withdrawals =
+                        state.withdrawals.entry(bond_id).or_default();
+                    let repaid = record_withdrawal(
+                        &params,
+                        withdrawals,
+                        withdrawn_deltas,
+                        withdrawn,
+                        epoch,
+                    );
+                    state.repaid += repaid;
+                    state.transfer(withdrawn - repaid, POS, *source);
+                    state.transfer(repaid, POS, SLASH_POOL);
+                }
+                PosTransition::Slash {
+                    evidence_epoch,
+                    rate,
+                } => {
+                    let validator_slash = Slash {
+                        epoch: *evidence_epoch,
+                        block_height: 0,
+                        r#type: SlashType::DuplicateVote,
+                        rate: *rate,
+                    };
+                    let bonds: Vec<_> = state
+                        .bonds
+                        .iter()
+                        .map(|(id, bonds)| (id.clone(), bonds.clone()))
+                        .collect();
+                    let unbonds: Vec<_> = state
+                        .unbonds
+                        .iter()
+                        .map(|(id, unbonds)| (id.clone(), unbonds.clone()))
+                        .collect();
+                    let withdrawals: Vec<_> = state
+                        .withdrawals
+                        .iter()
+                        .map(|(id, withdrawals)| {
+                            (id.clone(), withdrawals.clone())
+                        })
+                        .collect();
+                    let SlashedAmounts {
+                        bonded,
+                        unbonded,
+                        withdrawn,
+                    } = slashed_amounts(
+                        &params,
+                        &validator_slash,
+                        &state.slashes,
+                        &bonds,
+                        &unbonds,
+                        &withdrawals,
+                        epoch,
+                    )
+                    .unwrap();
+                    slash(
+                        &params,
+                        epoch,
+                        &VALIDATOR,
+                        i128::from(bonded),
+                        &mut state.total_deltas,
+                        &mut state.voting_power,
+                        &mut state.validator_set,
+                        &mut state.total_voting_power,
+                    )
+                    .unwrap();
+                    state.slashes.push(validator_slash);
+                    state.slashed += bonded + unbonded;
+                    state.transfer(bonded + unbonded, POS, SLASH_POOL);
+                    for (bond_id, clawback) in withdrawn {
+                        state.clawbacks += clawback;
+                        state
+                            .withdrawals
+                            .get_mut(&bond_id)
+                            .unwrap()
+                            .clawback += clawback;
+                    }
+                }
+            }
+            state
+        }
+
+        fn invariants(state: &Self::ConcreteState) {
+            // No tokens are created or destroyed
+            let total: u64 = state.balances.values().sum();
+            assert_eq!(total, state.total_supply);
+            // The PoS account holds the tokens that can be paid out, up to
+            // the slashes rounding
+            let pos_balance =
+                state.balances.get(&POS).copied().unwrap_or_default();
+            let claims = state.claims();
+            assert!(
+                claims <= pos_balance + state.rounding_tolerance,
+                "The claims {} exceed the PoS balance {} by more than {}",
+                claims,
+                pos_balance,
+                state.rounding_tolerance
+            );
+            // The slash pool holds the slashed tokens and the repaid
+            // clawbacks
+            let slash_pool_balance =
+                state.balances.get(&SLASH_POOL).copied().unwrap_or_default();
+            assert_eq!(slash_pool_balance, state.slashed + state.repaid);
+            // The clawbacks are either repaid or still owed
+            let owed: u64 = state
+                .withdrawals
+                .values()
+                .map(|withdrawals| withdrawals.clawback)
+                .sum();
+            assert_eq!(owed + state.repaid, state.clawbacks);
+        }
+    }
 }
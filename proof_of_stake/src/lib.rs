@@ -19,11 +19,11 @@ pub mod types;
 pub mod validation;
 
 use core::fmt::Debug;
-use std::collections::{BTreeSet, HashMap};
+use std::cmp;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::TryFrom;
 use std::fmt::Display;
 use std::hash::Hash;
-use std::num::TryFromIntError;
 use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -33,10 +33,13 @@ use epoched::{
 use parameters::PosParams;
 use thiserror::Error;
 use types::{
-    ActiveValidator, Bonds, Epoch, GenesisValidator, Slash, SlashType, Slashes,
-    TotalVotingPowers, Unbond, Unbonds, ValidatorConsensusKeys, ValidatorSet,
-    ValidatorSetUpdate, ValidatorSets, ValidatorState, ValidatorStates,
-    ValidatorTotalDeltas, ValidatorVotingPowers, VotingPower, VotingPowerDelta,
+    ActiveValidator, ArithmeticError, BasisPoints, BondRewards, Bonds,
+    ConsensusKeyOwner, Epoch, GenesisValidator, Redelegations,
+    RewardsAccumulator, Slash, SlashType, Slashes, TotalVotingPowers, Unbond,
+    Unbonds, ValidatorCommissionRates, ValidatorConsensusKeys,
+    ValidatorLiveness, ValidatorMetadata, ValidatorSet, ValidatorSetUpdate,
+    ValidatorSets, ValidatorState, ValidatorStates, ValidatorTotalDeltas,
+    ValidatorVotingPowers, VotingPower, VotingPowerDelta, Withdrawals,
 };
 
 use crate::btree_set::BTreeSetShims;
@@ -85,10 +88,16 @@ pub trait PosReadOnly {
         + BorshDeserialize
         + BorshSerialize;
     /// Cryptographic public key type
-    type PublicKey: Debug + Clone + BorshDeserialize + BorshSerialize;
+    type PublicKey: Debug
+        + Clone
+        + PartialEq
+        + BorshDeserialize
+        + BorshSerialize;
 
     /// Address of the PoS account
     const POS_ADDRESS: Self::Address;
+    /// Address of the slash pool, into which slashed tokens are transferred.
+    const POS_SLASH_POOL_ADDRESS: Self::Address;
     /// Address of the staking token
     /// TODO: this should be `const`, but in the ledger `address::xan` is not a
     /// `const fn`
@@ -106,6 +115,11 @@ pub trait PosReadOnly {
         &self,
         key: &Self::Address,
     ) -> Option<ValidatorConsensusKeys<Self::PublicKey>>;
+    /// Read the owner of a consensus key from the index of consensus keys.
+    fn read_consensus_key_owner(
+        &self,
+        key: &Self::PublicKey,
+    ) -> Option<ConsensusKeyOwner<Self::Address>>;
     /// Read PoS validator's state.
     fn read_validator_state(
         &self,
@@ -122,6 +136,20 @@ pub trait PosReadOnly {
         &self,
         key: &Self::Address,
     ) -> Option<ValidatorVotingPowers>;
+    /// Read PoS validator's commission rate.
+    fn read_validator_commission_rate(
+        &self,
+        key: &Self::Address,
+    ) -> Option<ValidatorCommissionRates>;
+    /// Read the epoch in which a PoS validator has been jailed, if it's
+    /// jailed.
+    fn read_validator_jailed_epoch(&self, key: &Self::Address)
+        -> Option<Epoch>;
+    /// Read PoS validator's metadata.
+    fn read_validator_metadata(
+        &self,
+        key: &Self::Address,
+    ) -> Option<ValidatorMetadata>;
     /// Read PoS slashes applied to a validator.
     fn read_validator_slashes(&self, key: &Self::Address) -> Vec<Slash>;
     /// Read PoS bond (validator self-bond or a delegation).
@@ -135,6 +163,23 @@ pub trait PosReadOnly {
         &self,
         key: &BondId<Self::Address>,
     ) -> Option<Unbonds<Self::TokenAmount>>;
+    /// Read PoS redelegations of a delegation.
+    fn read_redelegations(
+        &self,
+        key: &BondId<Self::Address>,
+    ) -> Option<Redelegations<Self::Address, Self::TokenAmount>>;
+    /// Read PoS withdrawals (withdrawn tokens from validator self-bond or a
+    /// delegation that can still be slashed).
+    fn read_withdrawals(
+        &self,
+        key: &BondId<Self::Address>,
+    ) -> Option<Withdrawals<Self::TokenAmount>>;
+    /// Read PoS block rewards of a bond (validator self-bond or a
+    /// delegation).
+    fn read_bond_rewards(
+        &self,
+        key: &BondId<Self::Address>,
+    ) -> Option<BondRewards<Self::TokenAmount>>;
     /// Read PoS validator set (active and inactive).
     fn read_validator_set(&self) -> ValidatorSets<Self::Address>;
     /// Read PoS total voting power of all validators (active and inactive).
@@ -161,6 +206,12 @@ pub trait PosActions: PosReadOnly {
         key: &Self::Address,
         value: ValidatorConsensusKeys<Self::PublicKey>,
     );
+    /// Write the owner of a consensus key into the index of consensus keys.
+    fn write_consensus_key_owner(
+        &mut self,
+        key: &Self::PublicKey,
+        value: ConsensusKeyOwner<Self::Address>,
+    );
     /// Write PoS validator's state.
     fn write_validator_state(
         &mut self,
@@ -180,6 +231,18 @@ pub trait PosActions: PosReadOnly {
         key: &Self::Address,
         value: ValidatorVotingPowers,
     );
+    /// Write PoS validator's commission rate.
+    fn write_validator_commission_rate(
+        &mut self,
+        key: &Self::Address,
+        value: ValidatorCommissionRates,
+    );
+    /// Write PoS validator's metadata.
+    fn write_validator_metadata(
+        &mut self,
+        key: &Self::Address,
+        value: ValidatorMetadata,
+    );
     /// Write PoS bond (validator self-bond or a delegation).
     fn write_bond(
         &mut self,
@@ -193,6 +256,26 @@ pub trait PosActions: PosReadOnly {
         key: &BondId<Self::Address>,
         value: Unbonds<Self::TokenAmount>,
     );
+    /// Write PoS redelegations of a delegation.
+    fn write_redelegations(
+        &mut self,
+        key: &BondId<Self::Address>,
+        value: Redelegations<Self::Address, Self::TokenAmount>,
+    );
+    /// Write PoS withdrawals (withdrawn tokens from validator self-bond or a
+    /// delegation that can still be slashed).
+    fn write_withdrawals(
+        &mut self,
+        key: &BondId<Self::Address>,
+        value: Withdrawals<Self::TokenAmount>,
+    );
+    /// Write PoS block rewards of a bond (validator self-bond or a
+    /// delegation).
+    fn write_bond_rewards(
+        &mut self,
+        key: &BondId<Self::Address>,
+        value: BondRewards<Self::TokenAmount>,
+    );
     /// Write PoS validator set (active and inactive).
     fn write_validator_set(&mut self, value: ValidatorSets<Self::Address>);
     /// Write PoS total voting power of all validators (active and inactive).
@@ -203,6 +286,16 @@ pub trait PosActions: PosReadOnly {
     /// Delete an emptied PoS unbond (unbonded tokens from validator self-bond
     /// or a delegation).
     fn delete_unbond(&mut self, key: &BondId<Self::Address>);
+    /// Delete emptied PoS redelegations of a delegation.
+    fn delete_redelegations(&mut self, key: &BondId<Self::Address>);
+    /// Delete PoS withdrawals that can no longer be slashed and that have no
+    /// clawback owed.
+    fn delete_withdrawals(&mut self, key: &BondId<Self::Address>);
+    /// Delete the jailed epoch of an unjailed PoS validator.
+    fn delete_validator_jailed_epoch(&mut self, key: &Self::Address);
+    /// Delete the owner of a consensus key that has been replaced before it
+    /// took effect from the index of consensus keys.
+    fn delete_consensus_key_owner(&mut self, key: &Self::PublicKey);
 
     /// Transfer tokens from the `src` to the `dest`.
     fn transfer(
@@ -219,6 +312,8 @@ pub trait PosActions: PosReadOnly {
         address: &Self::Address,
         staking_reward_address: &Self::Address,
         consensus_key: &Self::PublicKey,
+        commission_rate: BasisPoints,
+        metadata: ValidatorMetadata,
         current_epoch: impl Into<Epoch>,
     ) -> Result<(), BecomeValidatorError<Self::Address>> {
         let current_epoch = current_epoch.into();
@@ -236,15 +331,40 @@ pub trait PosActions: PosReadOnly {
                 ),
             );
         }
+        if commission_rate > BasisPoints::MAX {
+            return Err(BecomeValidatorError::InvalidCommissionRate(
+                commission_rate,
+            ));
+        }
+        if let Some(field) = metadata.find_too_long_field() {
+            return Err(BecomeValidatorError::MetadataFieldTooLong(field));
+        }
+        if let Some(owner) = self.read_consensus_key_owner(consensus_key) {
+            if !owner.is_expired(current_epoch, &params) {
+                return Err(BecomeValidatorError::ConsensusKeyAlreadyUsed(
+                    owner.validator,
+                ));
+            }
+        }
+        // The key is used from the epoch in which the validator is added to
+        // the validator set
+        let consensus_key_owner = ConsensusKeyOwner {
+            validator: address.clone(),
+            start: current_epoch + params.pipeline_len,
+            end: None,
+        };
+        self.write_consensus_key_owner(consensus_key, consensus_key_owner);
         let BecomeValidatorData {
             consensus_key,
             state,
             total_deltas,
             voting_power,
+            commission_rate,
         } = become_validator(
             &params,
             address,
             consensus_key,
+            commission_rate,
             &mut validator_set,
             current_epoch,
         );
@@ -258,9 +378,28 @@ pub trait PosActions: PosReadOnly {
         self.write_validator_address_raw_hash(address);
         self.write_validator_total_deltas(address, total_deltas);
         self.write_validator_voting_power(address, voting_power);
+        self.write_validator_commission_rate(address, commission_rate);
+        self.write_validator_metadata(address, metadata);
         Ok(())
     }
 
+    /// Read the slashes of the source validators of the given redelegations.
+    fn read_redelegations_slashes(
+        &self,
+        redelegations: Option<&Redelegations<Self::Address, Self::TokenAmount>>,
+    ) -> HashMap<Self::Address, Slashes> {
+        redelegations
+            .iter()
+            .flat_map(|redelegations| redelegations.deltas.keys())
+            .map(|(_epoch, src_validator, _bond_start)| {
+                (
+                    src_validator.clone(),
+                    self.read_validator_slashes(src_validator),
+                )
+            })
+            .collect()
+    }
+
     /// Check if the given address is a validator by checking that it has some
     /// state.
     fn is_validator(&mut self, address: &Self::Address) -> bool {
@@ -403,9 +542,144 @@ pub trait PosActions: PosReadOnly {
         Ok(())
     }
 
+    /// Redelegate tokens delegated from the `source` to the `src_validator`
+    /// to the `dest_validator`. The tokens are moved at the pipeline offset
+    /// from the current epoch and they remain slashable for the faults of the
+    /// `src_validator` committed before they were moved.
+    fn redelegate_tokens(
+        &mut self,
+        source: &Self::Address,
+        src_validator: &Self::Address,
+        dest_validator: &Self::Address,
+        amount: Self::TokenAmount,
+        current_epoch: impl Into<Epoch>,
+    ) -> Result<(), RedelegationError<Self::Address, Self::TokenAmount>> {
+        let current_epoch = current_epoch.into();
+        if self.is_validator(source) {
+            return Err(RedelegationError::SourceMustNotBeAValidator(
+                source.clone(),
+            ));
+        }
+        let params = self.read_pos_params();
+        let src_bond_id = BondId {
+            source: source.clone(),
+            validator: src_validator.clone(),
+        };
+        let dest_bond_id = BondId {
+            source: source.clone(),
+            validator: dest_validator.clone(),
+        };
+        let mut src_bond = self
+            .read_bond(&src_bond_id)
+            .ok_or(RedelegationError::NoBondFound)?;
+        let mut src_redelegations = self.read_redelegations(&src_bond_id);
+        let redelegations_slashes =
+            self.read_redelegations_slashes(src_redelegations.as_ref());
+        let mut src_total_deltas = self
+            .read_validator_total_deltas(src_validator)
+            .ok_or_else(|| {
+                RedelegationError::ValidatorHasNoBonds(src_validator.clone())
+            })?;
+        let mut src_voting_power = self
+            .read_validator_voting_power(src_validator)
+            .ok_or_else(|| {
+                RedelegationError::ValidatorHasNoVotingPower(
+                    src_validator.clone(),
+                )
+            })?;
+        let src_slashes = self.read_validator_slashes(src_validator);
+        let dest_state = self.read_validator_state(dest_validator);
+        let dest_bond = self.read_bond(&dest_bond_id);
+        let dest_redelegations = self.read_redelegations(&dest_bond_id);
+        let dest_total_deltas =
+            self.read_validator_total_deltas(dest_validator);
+        let dest_voting_power =
+            self.read_validator_voting_power(dest_validator);
+        let mut total_voting_power = self.read_total_voting_power();
+        let mut validator_set = self.read_validator_set();
+
+        let RedelegationData { deltas } = redelegate_tokens(
+            &params,
+            &src_bond_id,
+            dest_validator,
+            &mut src_bond,
+            src_redelegations.as_mut(),
+            &redelegations_slashes,
+            amount,
+            src_slashes,
+            &mut src_total_deltas,
+            &mut src_voting_power,
+            &mut total_voting_power,
+            &mut validator_set,
+            current_epoch,
+        )?;
+        let BondData {
+            bond: dest_bond,
+            validator_total_deltas: dest_total_deltas,
+            validator_voting_power: dest_voting_power,
+        } = bond_tokens(
+            &params,
+            dest_state,
+            &dest_bond_id,
+            dest_bond,
+            amount,
+            dest_total_deltas,
+            dest_voting_power,
+            &mut total_voting_power,
+            &mut validator_set,
+            current_epoch,
+        )?;
+
+        // Record the redelegated tokens with the source validator
+        let redelegation_epoch =
+            current_epoch + DynEpochOffset::PipelineLen.value(&params);
+        let mut dest_redelegations =
+            dest_redelegations.unwrap_or_else(|| Redelegations {
+                deltas: BTreeMap::default(),
+            });
+        for (bond_start, delta) in deltas {
+            *dest_redelegations
+                .deltas
+                .entry((redelegation_epoch, src_validator.clone(), bond_start))
+                .or_default() += delta;
+        }
+
+        let total_bonds = src_bond.get_at_offset(
+            current_epoch,
+            DynEpochOffset::PipelineLen,
+            &params,
+        );
+        match total_bonds {
+            Some(total_bonds) if total_bonds.sum() != 0.into() => {
+                self.write_bond(&src_bond_id, src_bond);
+            }
+            _ => {
+                // If the bond is left empty, delete it
+                self.delete_bond(&src_bond_id)
+            }
+        }
+        if src_redelegations.is_some() {
+            // The source bond's redelegations that could no longer be
+            // slashed have been forgotten
+            self.delete_redelegations(&src_bond_id);
+        }
+        self.write_bond(&dest_bond_id, dest_bond);
+        self.write_redelegations(&dest_bond_id, dest_redelegations);
+        self.write_validator_total_deltas(src_validator, src_total_deltas);
+        self.write_validator_voting_power(src_validator, src_voting_power);
+        self.write_validator_total_deltas(dest_validator, dest_total_deltas);
+        self.write_validator_voting_power(dest_validator, dest_voting_power);
+        self.write_total_voting_power(total_voting_power);
+        self.write_validator_set(validator_set);
+
+        Ok(())
+    }
+
     /// Withdraw unbonded tokens from a self-bond to a validator when `source`
     /// is `None` or equal to the `validator` address, or withdraw unbonded
-    /// tokens delegated to the `validator` to the `source`.
+    /// tokens delegated to the `validator` to the `source`. The withdrawn
+    /// tokens first repay the bond's clawback owed to the slash pool, if any.
+    /// Returns the slashed amount, including the repaid clawback.
     fn withdraw_tokens(
         &mut self,
         source: Option<&Self::Address>,
@@ -422,9 +696,13 @@ pub trait PosActions: PosReadOnly {
 
         let unbond = self.read_unbond(&bond_id);
         let slashes = self.read_validator_slashes(&bond_id.validator);
+        let mut redelegations = self.read_redelegations(&bond_id);
+        let redelegations_slashes =
+            self.read_redelegations_slashes(redelegations.as_ref());
 
         let WithdrawData {
             unbond,
+            withdrawn_deltas,
             withdrawn,
             slashed,
         } = withdraw_unbonds(
@@ -432,6 +710,8 @@ pub trait PosActions: PosReadOnly {
             &bond_id,
             unbond,
             slashes,
+            redelegations.as_mut(),
+            &redelegations_slashes,
             current_epoch,
         )?;
 
@@ -449,16 +729,247 @@ pub trait PosActions: PosReadOnly {
                 self.delete_unbond(&bond_id)
             }
         }
+        match redelegations {
+            Some(redelegations) if !redelegations.deltas.is_empty() => {
+                self.write_redelegations(&bond_id, redelegations);
+            }
+            Some(_) => {
+                // If all the redelegated tokens are withdrawn, delete them
+                self.delete_redelegations(&bond_id)
+            }
+            None => {}
+        }
+
+        let prev_withdrawals = self.read_withdrawals(&bond_id);
+        let has_withdrawals = prev_withdrawals.is_some();
+        let mut withdrawals = prev_withdrawals.unwrap_or_default();
+        let repaid = record_withdrawal(
+            &params,
+            &mut withdrawals,
+            withdrawn_deltas,
+            withdrawn,
+            current_epoch,
+        );
+        if !withdrawals.deltas.is_empty()
+            || withdrawals.clawback != 0.into()
+        {
+            self.write_withdrawals(&bond_id, withdrawals);
+        } else if has_withdrawals {
+            // If the withdrawals can no longer be slashed, delete them
+            self.delete_withdrawals(&bond_id)
+        }
 
-        // Transfer the tokens from PoS back to the source
+        // Transfer the tokens from PoS back to the source, less the repaid
+        // clawback that goes to the slash pool
+        let mut paid_out = withdrawn;
+        paid_out -= repaid;
         self.transfer(
             &Self::staking_token_address(),
-            withdrawn,
+            paid_out,
             &Self::POS_ADDRESS,
             source,
         );
+        if repaid != 0.into() {
+            self.transfer(
+                &Self::staking_token_address(),
+                repaid,
+                &Self::POS_ADDRESS,
+                &Self::POS_SLASH_POOL_ADDRESS,
+            );
+        }
 
-        Ok(slashed)
+        Ok(slashed + repaid)
+    }
+
+    /// Change a validator's commission rate. The new rate takes effect at the
+    /// pipeline offset from the current epoch.
+    fn change_commission_rate(
+        &mut self,
+        validator: &Self::Address,
+        rate: BasisPoints,
+        current_epoch: impl Into<Epoch>,
+    ) -> Result<(), CommissionRateChangeError<Self::Address>> {
+        let current_epoch = current_epoch.into();
+        let params = self.read_pos_params();
+        let mut commission_rates =
+            self.read_validator_commission_rate(validator).ok_or_else(
+                || CommissionRateChangeError::NotAValidator(validator.clone()),
+            )?;
+
+        change_commission_rate(
+            &params,
+            &mut commission_rates,
+            rate,
+            current_epoch,
+        )?;
+
+        self.write_validator_commission_rate(validator, commission_rates);
+        Ok(())
+    }
+
+    /// Change a validator's consensus key. The new key takes effect at the
+    /// pipeline offset from the current epoch.
+    fn change_consensus_key(
+        &mut self,
+        validator: &Self::Address,
+        consensus_key: &Self::PublicKey,
+        current_epoch: impl Into<Epoch>,
+    ) -> Result<(), ConsensusKeyChangeError<Self::Address>> {
+        let current_epoch = current_epoch.into();
+        let params = self.read_pos_params();
+        let mut consensus_keys =
+            match self.read_validator_consensus_key(validator) {
+                Some(consensus_keys) => consensus_keys,
+                None => {
+                    return Err(ConsensusKeyChangeError::NotAValidator(
+                        validator.clone(),
+                    ));
+                }
+            };
+
+        let pipeline_epoch = current_epoch + params.pipeline_len;
+        // The key may be used again by the same validator, but not by another
+        // validator until the evidence for it has expired
+        let owner = self
+            .read_consensus_key_owner(consensus_key)
+            .filter(|owner| !owner.is_expired(current_epoch, &params));
+        if let Some(owner) = &owner {
+            if &owner.validator != validator {
+                return Err(ConsensusKeyChangeError::KeyAlreadyUsed(
+                    owner.validator.clone(),
+                ));
+            }
+        }
+        // The key that would otherwise be in effect in the pipeline epoch
+        let pipeline_key = consensus_keys
+            .get_at_offset(current_epoch, DynEpochOffset::PipelineLen, &params)
+            .cloned();
+
+        change_consensus_key(
+            &params,
+            &mut consensus_keys,
+            validator,
+            consensus_key,
+            current_epoch,
+        )?;
+
+        // The replaced key stays in the index until the evidence for it
+        // expires, unless it's been replaced before it took effect
+        if let Some(pipeline_key) = pipeline_key {
+            if let Some(mut replaced_owner) =
+                self.read_consensus_key_owner(&pipeline_key)
+            {
+                if replaced_owner.start >= pipeline_epoch {
+                    self.delete_consensus_key_owner(&pipeline_key);
+                } else {
+                    replaced_owner.end = Some(pipeline_epoch);
+                    self.write_consensus_key_owner(
+                        &pipeline_key,
+                        replaced_owner,
+                    );
+                }
+            }
+        }
+        let start = match owner {
+            Some(owner) => owner.start,
+            None => pipeline_epoch,
+        };
+        self.write_consensus_key_owner(
+            consensus_key,
+            ConsensusKeyOwner {
+                validator: validator.clone(),
+                start,
+                end: None,
+            },
+        );
+        self.write_validator_consensus_key(validator, consensus_keys);
+        Ok(())
+    }
+
+    /// Update a validator's metadata. Unlike most of the validator's data, the
+    /// metadata are not epoched and the update takes effect immediately.
+    fn update_validator_metadata(
+        &mut self,
+        validator: &Self::Address,
+        metadata: ValidatorMetadata,
+    ) -> Result<(), MetadataUpdateError<Self::Address>> {
+        if !self.is_validator(validator) {
+            return Err(MetadataUpdateError::NotAValidator(validator.clone()));
+        }
+        if let Some(field) = metadata.find_too_long_field() {
+            return Err(MetadataUpdateError::FieldTooLong(field));
+        }
+        self.write_validator_metadata(validator, metadata);
+        Ok(())
+    }
+
+    /// Claim the rewards that have accrued to a delegation from the `source`
+    /// to the `validator`. On success, returns the claimed amount.
+    fn claim_rewards(
+        &mut self,
+        source: &Self::Address,
+        validator: &Self::Address,
+    ) -> Result<Self::TokenAmount, ClaimRewardsError<Self::Address>> {
+        let bond_id = BondId {
+            source: source.clone(),
+            validator: validator.clone(),
+        };
+        let mut bond_rewards =
+            self.read_bond_rewards(&bond_id).ok_or_else(|| {
+                ClaimRewardsError::NoRewardsFound(bond_id.clone())
+            })?;
+        let claimed = bond_rewards.claim();
+        if claimed == Self::TokenAmount::default() {
+            return Err(ClaimRewardsError::NoRewardsToClaim(bond_id));
+        }
+        self.write_bond_rewards(&bond_id, bond_rewards);
+
+        // Transfer the claimed rewards from PoS to the source
+        self.transfer(
+            &Self::staking_token_address(),
+            claimed,
+            &Self::POS_ADDRESS,
+            source,
+        );
+
+        Ok(claimed)
+    }
+
+    /// Unjail a jailed validator, once it has been jailed for at least
+    /// `min_jail_duration` epochs. The validator will be added back to the
+    /// validator set at the pipeline offset from the current epoch.
+    fn unjail_validator(
+        &mut self,
+        validator: &Self::Address,
+        current_epoch: impl Into<Epoch>,
+    ) -> Result<(), UnjailError<Self::Address>> {
+        let current_epoch = current_epoch.into();
+        let params = self.read_pos_params();
+        let mut state = self
+            .read_validator_state(validator)
+            .ok_or_else(|| UnjailError::NotAValidator(validator.clone()))?;
+        let jailed_epoch = self
+            .read_validator_jailed_epoch(validator)
+            .ok_or_else(|| UnjailError::NotJailed(validator.clone()))?;
+        let voting_power = self
+            .read_validator_voting_power(validator)
+            .ok_or_else(|| UnjailError::NotAValidator(validator.clone()))?;
+        let mut validator_set = self.read_validator_set();
+
+        unjail_validator(
+            &params,
+            validator,
+            &mut state,
+            jailed_epoch,
+            &voting_power,
+            &mut validator_set,
+            current_epoch,
+        )?;
+
+        self.write_validator_state(validator, state);
+        self.write_validator_set(validator_set);
+        self.delete_validator_jailed_epoch(validator);
+        Ok(())
     }
 }
 
@@ -511,7 +1022,12 @@ pub trait PosBase {
         + BorshDeserialize
         + BorshSerialize;
     /// Cryptographic public key type
-    type PublicKey: 'static + Debug + Clone + BorshDeserialize + BorshSerialize;
+    type PublicKey: 'static
+        + Debug
+        + Clone
+        + PartialEq
+        + BorshDeserialize
+        + BorshSerialize;
 
     /// Address of the PoS account
     const POS_ADDRESS: Self::Address;
@@ -529,6 +1045,12 @@ pub trait PosBase {
         &self,
         raw_hash: impl AsRef<str>,
     ) -> Option<Self::Address>;
+    /// Read the owner of a consensus key from the index of consensus keys by
+    /// the key's raw hash, which is the address of the key in the consensus.
+    fn read_consensus_key_owner(
+        &self,
+        raw_hash: impl AsRef<str>,
+    ) -> Option<ConsensusKeyOwner<Self::Address>>;
     /// Read PoS validator's consensus key (used for signing block votes).
     fn read_validator_consensus_key(
         &self,
@@ -556,6 +1078,51 @@ pub trait PosBase {
     fn read_validator_set(&self) -> ValidatorSets<Self::Address>;
     /// Read PoS total voting power of all validators (active and inactive).
     fn read_total_voting_power(&self) -> TotalVotingPowers;
+    /// Read PoS validator's staking reward address.
+    fn read_validator_staking_reward_address(
+        &self,
+        key: &Self::Address,
+    ) -> Option<Self::Address>;
+    /// Read PoS validator's commission rate.
+    fn read_validator_commission_rate(
+        &self,
+        key: &Self::Address,
+    ) -> Option<ValidatorCommissionRates>;
+    /// Read the epoch in which a PoS validator has been jailed, if it's
+    /// jailed.
+    fn read_validator_jailed_epoch(&self, key: &Self::Address)
+        -> Option<Epoch>;
+    /// Read the liveness of a PoS validator in the signing window.
+    fn read_validator_liveness(&self, key: &Self::Address)
+        -> ValidatorLiveness;
+    /// Read all the PoS bonds (validator self-bond and delegations) of a
+    /// validator.
+    fn read_validator_bonds(
+        &self,
+        validator: &Self::Address,
+    ) -> Vec<(BondId<Self::Address>, Bonds<Self::TokenAmount>)>;
+    /// Read all the PoS unbonds (unbonded tokens from validator self-bond and
+    /// delegations) of a validator that haven't been withdrawn yet.
+    fn read_validator_unbonds(
+        &self,
+        validator: &Self::Address,
+    ) -> Vec<(BondId<Self::Address>, Unbonds<Self::TokenAmount>)>;
+    /// Read all the PoS withdrawals (withdrawn tokens from validator self-bond
+    /// and delegations that can still be slashed) of a validator.
+    fn read_validator_withdrawals(
+        &self,
+        validator: &Self::Address,
+    ) -> Vec<(BondId<Self::Address>, Withdrawals<Self::TokenAmount>)>;
+    /// Read PoS block rewards accumulated by validators in an epoch.
+    fn read_rewards_accumulator(
+        &self,
+    ) -> Option<RewardsAccumulator<Self::Address, Self::TokenAmount>>;
+    /// Read PoS block rewards of a bond (validator self-bond or a
+    /// delegation).
+    fn read_bond_rewards(
+        &self,
+        key: &BondId<Self::Address>,
+    ) -> Option<BondRewards<Self::TokenAmount>>;
 
     /// Write PoS parameters.
     fn write_pos_params(&mut self, params: &PosParams);
@@ -574,6 +1141,12 @@ pub trait PosBase {
         key: &Self::Address,
         value: &ValidatorConsensusKeys<Self::PublicKey>,
     );
+    /// Write the owner of a consensus key into the index of consensus keys.
+    fn write_consensus_key_owner(
+        &mut self,
+        key: &Self::PublicKey,
+        value: &ConsensusKeyOwner<Self::Address>,
+    );
     /// Write PoS validator's state.
     fn write_validator_state(
         &mut self,
@@ -593,6 +1166,24 @@ pub trait PosBase {
         key: &Self::Address,
         value: &ValidatorVotingPowers,
     );
+    /// Write PoS validator's commission rate.
+    fn write_validator_commission_rate(
+        &mut self,
+        key: &Self::Address,
+        value: &ValidatorCommissionRates,
+    );
+    /// Write the epoch in which a PoS validator has been jailed.
+    fn write_validator_jailed_epoch(
+        &mut self,
+        key: &Self::Address,
+        value: Epoch,
+    );
+    /// Write the liveness of a PoS validator in the signing window.
+    fn write_validator_liveness(
+        &mut self,
+        key: &Self::Address,
+        value: &ValidatorLiveness,
+    );
     /// Write (append) PoS slash applied to a validator.
     fn write_validator_slash(
         &mut self,
@@ -605,10 +1196,29 @@ pub trait PosBase {
         key: &BondId<Self::Address>,
         value: &Bonds<Self::TokenAmount>,
     );
+    /// Write PoS withdrawals (withdrawn tokens from validator self-bond or a
+    /// delegation that can still be slashed).
+    fn write_withdrawals(
+        &mut self,
+        key: &BondId<Self::Address>,
+        value: &Withdrawals<Self::TokenAmount>,
+    );
     /// Write PoS validator set (active and inactive).
     fn write_validator_set(&mut self, value: &ValidatorSets<Self::Address>);
     /// Read PoS total voting power of all validators (active and inactive).
     fn write_total_voting_power(&mut self, value: &TotalVotingPowers);
+    /// Write PoS block rewards accumulated by validators in an epoch.
+    fn write_rewards_accumulator(
+        &mut self,
+        value: &RewardsAccumulator<Self::Address, Self::TokenAmount>,
+    );
+    /// Write PoS block rewards of a bond (validator self-bond or a
+    /// delegation).
+    fn write_bond_rewards(
+        &mut self,
+        key: &BondId<Self::Address>,
+        value: &BondRewards<Self::TokenAmount>,
+    );
     /// Initialize staking reward account with the given public key.
     fn init_staking_reward_account(
         &mut self,
@@ -616,7 +1226,7 @@ pub trait PosBase {
         pk: &Self::PublicKey,
     );
     /// Credit tokens to the `target` account. This should only be used at
-    /// genesis.
+    /// genesis and to pay out block rewards.
     fn credit_tokens(
         &mut self,
         token: &Self::Address,
@@ -664,10 +1274,12 @@ pub trait PosBase {
                 ref address,
                 staking_reward_address,
                 consensus_key,
+                consensus_key_owner: (key, key_owner),
                 staking_reward_key,
                 state,
                 total_deltas,
                 voting_power,
+                commission_rate,
                 bond: (bond_id, bond),
             } = res?;
             self.write_validator_address_raw_hash(address);
@@ -676,9 +1288,11 @@ pub trait PosBase {
                 &staking_reward_address,
             );
             self.write_validator_consensus_key(address, &consensus_key);
+            self.write_consensus_key_owner(&key, &key_owner);
             self.write_validator_state(address, &state);
             self.write_validator_total_deltas(address, &total_deltas);
             self.write_validator_voting_power(address, &voting_power);
+            self.write_validator_commission_rate(address, &commission_rate);
             self.write_bond(&bond_id, &bond);
             self.init_staking_reward_account(
                 &staking_reward_address,
@@ -717,6 +1331,19 @@ pub trait PosBase {
         let cur_validators = validators.get(current_epoch).unwrap();
         let prev_validators =
             previous_epoch.and_then(|epoch| validators.get(epoch));
+        // Find the validator's consensus keys in the previous and the current
+        // epoch. The key from the previous epoch is the one known to
+        // Tendermint, which differs from the current key if the validator has
+        // changed its consensus key.
+        let consensus_keys = |address: &Self::Address| {
+            let keys = self.read_validator_consensus_key(address).unwrap();
+            let current_key = keys.get(current_epoch).unwrap().clone();
+            let prev_key = previous_epoch
+                .and_then(|epoch| keys.get(epoch))
+                .cloned()
+                .unwrap_or_else(|| current_key.clone());
+            (prev_key, current_key)
+        };
 
         // If the validator never been active before and it doesn't have more
         // than 0 voting power, we should not tell Tendermint to update it until
@@ -729,13 +1356,18 @@ pub trait PosBase {
         // still need to guard against it.
         let active_validators = cur_validators.active.iter().filter_map(
             |validator: &WeightedValidator<_>| {
+                let (prev_key, consensus_key) =
+                    consensus_keys(&validator.address);
                 // If the validators set from previous epoch contains the same
                 // validator, it means its voting power hasn't changed and hence
-                // doesn't need to updated.
+                // doesn't need to updated, unless its consensus key has
+                // changed.
                 if let (Some(prev_epoch), Some(prev_validators)) =
                     (previous_epoch, prev_validators)
                 {
-                    if prev_validators.active.contains(validator) {
+                    if prev_validators.active.contains(validator)
+                        && prev_key == consensus_key
+                    {
                         println!(
                             "skipping validator update, still the same {}",
                             validator.address
@@ -761,12 +1393,6 @@ pub trait PosBase {
                         }
                     }
                 }
-                let consensus_key = self
-                    .read_validator_consensus_key(&validator.address)
-                    .unwrap()
-                    .get(current_epoch)
-                    .unwrap()
-                    .clone();
                 Some(ValidatorSetUpdate::Active(ActiveValidator {
                     consensus_key,
                     voting_power: validator.voting_power,
@@ -799,19 +1425,65 @@ pub trait PosBase {
                         }
                     }
                 }
-                let consensus_key = self
-                    .read_validator_consensus_key(&validator.address)
-                    .unwrap()
-                    .get(current_epoch)
-                    .unwrap()
-                    .clone();
-                Some(ValidatorSetUpdate::Deactivated(consensus_key))
+                let (prev_key, _) = consensus_keys(&validator.address);
+                Some(ValidatorSetUpdate::Deactivated(prev_key))
             },
         );
-        active_validators.chain(inactive_validators).for_each(f)
+        // Validators that were active in the previous epoch, but have been
+        // removed from the validator set since (i.e. they are jailed), have to
+        // be deactivated too.
+        let removed_validators = prev_validators
+            .into_iter()
+            .flat_map(|prev_validators| prev_validators.active.iter())
+            .filter_map(|validator: &WeightedValidator<Self::Address>| {
+                let is_removed = !cur_validators
+                    .active
+                    .iter()
+                    .chain(cur_validators.inactive.iter())
+                    .any(|cur_validator| {
+                        cur_validator.address == validator.address
+                    });
+                if !is_removed {
+                    return None;
+                }
+                let (prev_key, _) = consensus_keys(&validator.address);
+                Some(ValidatorSetUpdate::Deactivated(prev_key))
+            });
+        // Validators that were active in the previous epoch and are still
+        // active, but have changed their consensus key, have to have their
+        // old key removed from Tendermint.
+        let rotated_validators = prev_validators
+            .into_iter()
+            .flat_map(|prev_validators| prev_validators.active.iter())
+            .filter_map(|validator: &WeightedValidator<Self::Address>| {
+                let is_active =
+                    cur_validators.active.iter().any(|cur_validator| {
+                        cur_validator.address == validator.address
+                    });
+                if !is_active {
+                    return None;
+                }
+                let (prev_key, consensus_key) =
+                    consensus_keys(&validator.address);
+                if prev_key == consensus_key {
+                    return None;
+                }
+                Some(ValidatorSetUpdate::Deactivated(prev_key))
+            });
+        active_validators
+            .chain(inactive_validators)
+            .chain(removed_validators)
+            .chain(rotated_validators)
+            .for_each(f)
     }
 
-    /// Apply a slash to a byzantine validator for the given evidence.
+    /// Apply a slash to a byzantine validator for the given evidence and jail
+    /// the validator. Only the validator's bonds and unbonds that were
+    /// contributing to its stake in the epoch of the evidence are slashed and
+    /// the slashed tokens are transferred to the slash pool. The tokens
+    /// slashed from the unbonds that have already been withdrawn are no
+    /// longer in the PoS account, so they are added to the clawback owed by
+    /// their bonds instead.
     fn slash(
         &mut self,
         params: &PosParams,
@@ -841,21 +1513,49 @@ pub trait PosBase {
             })?;
         let mut validator_set = self.read_validator_set();
         let mut total_voting_power = self.read_total_voting_power();
+        let slashes = self.read_validator_slashes(validator);
+        let bonds = self.read_validator_bonds(validator);
+        let unbonds = self.read_validator_unbonds(validator);
+        let mut withdrawals = self.read_validator_withdrawals(validator);
 
-        let slashed_change = slash(
+        let SlashedAmounts {
+            bonded,
+            unbonded,
+            withdrawn,
+        } = slashed_amounts(
             params,
-            current_epoch,
-            validator,
             &validator_slash,
-            &mut total_deltas,
+            &slashes,
+            &bonds,
+            &unbonds,
+            &withdrawals,
+            current_epoch,
+        )?;
+        slash(
+            params,
+            current_epoch,
+            validator,
+            bonded.into(),
+            &mut total_deltas,
             &mut voting_power,
             &mut validator_set,
             &mut total_voting_power,
         )?;
-        let slashed_change: i128 = slashed_change.into();
-        let slashed_amount = u64::try_from(slashed_change)
-            .map_err(|_err| SlashError::InvalidSlashChange(slashed_change))?;
-        let slashed_amount = Self::TokenAmount::from(slashed_amount);
+        // Add the tokens slashed from the withdrawals to their clawbacks
+        let mut clawed_back = Vec::new();
+        for (bond_id, clawback) in withdrawn {
+            if let Some((_, withdrawals)) =
+                withdrawals.iter_mut().find(|(id, _)| id == &bond_id)
+            {
+                let owed: u64 = withdrawals.clawback.into();
+                let clawback: u64 = clawback.into();
+                let owed = owed
+                    .checked_add(clawback)
+                    .ok_or(ArithmeticError::TokenAmountOverflow)?;
+                withdrawals.clawback = owed.into();
+                clawed_back.push(bond_id);
+            }
+        }
 
         self.write_validator_total_deltas(validator, &total_deltas);
         self.write_validator_voting_power(validator, &voting_power);
@@ -865,19 +1565,211 @@ pub trait PosBase {
         // Transfer the slashed tokens to the PoS slash pool
         self.transfer(
             &Self::staking_token_address(),
-            slashed_amount,
+            bonded + unbonded,
             &Self::POS_ADDRESS,
             &Self::POS_SLASH_POOL_ADDRESS,
         );
+        for (bond_id, withdrawals) in &withdrawals {
+            if clawed_back.contains(bond_id) {
+                self.write_withdrawals(bond_id, withdrawals);
+            }
+        }
+
+        self.jail_validator(params, current_epoch, validator)?;
+        Ok(())
+    }
+
+    /// Jail a validator. The validator is removed from the validator set at
+    /// the pipeline offset from the current epoch and it stays jailed until
+    /// it's unjailed with a transaction.
+    fn jail_validator(
+        &mut self,
+        params: &PosParams,
+        current_epoch: impl Into<Epoch>,
+        validator: &Self::Address,
+    ) -> Result<(), JailError<Self::Address>> {
+        let current_epoch = current_epoch.into();
+        let mut state = self
+            .read_validator_state(validator)
+            .ok_or_else(|| JailError::ValidatorHasNoState(validator.clone()))?;
+        let mut validator_set = self.read_validator_set();
+
+        jail_validator(
+            params,
+            validator,
+            &mut state,
+            &mut validator_set,
+            current_epoch,
+        );
+
+        self.write_validator_state(validator, &state);
+        self.write_validator_set(&validator_set);
+        self.write_validator_jailed_epoch(validator, current_epoch);
         Ok(())
     }
+
+    /// Record the validators' votes on the last block, given as pairs of the
+    /// validator's address and whether it signed the block. A validator that
+    /// misses signing more than `max_missed_blocks_rate` of the blocks in the
+    /// `signed_blocks_window` is slashed for downtime and jailed. Returns the
+    /// addresses of the slashed validators.
+    fn record_block_votes(
+        &mut self,
+        params: &PosParams,
+        current_epoch: impl Into<Epoch>,
+        block_height: impl Into<u64>,
+        votes: impl IntoIterator<Item = (Self::Address, bool)>,
+    ) -> Result<Vec<Self::Address>, SlashError<Self::Address>> {
+        let current_epoch = current_epoch.into();
+        let block_height = block_height.into();
+        let mut slashed = vec![];
+        for (validator, signed) in votes {
+            // A jailed validator may still be voting until it's removed from
+            // the validator set at the pipeline offset
+            if self.read_validator_jailed_epoch(&validator).is_some() {
+                continue;
+            }
+            let mut liveness = self.read_validator_liveness(&validator);
+            liveness.record(signed, params.signed_blocks_window);
+            if liveness.is_down(params.max_missed_blocks_rate) {
+                self.slash(
+                    params,
+                    current_epoch,
+                    current_epoch,
+                    block_height,
+                    SlashType::Downtime,
+                    &validator,
+                )?;
+                // Start tracking afresh once the validator is unjailed
+                self.write_validator_liveness(
+                    &validator,
+                    &ValidatorLiveness::default(),
+                );
+                slashed.push(validator);
+            } else {
+                self.write_validator_liveness(&validator, &liveness);
+            }
+        }
+        Ok(slashed)
+    }
+
+    /// Accumulate the block rewards of the block proposer and of the
+    /// validators that signed the last block. The rewards are paid out at the
+    /// beginning of the next epoch with [`PosBase::distribute_rewards`].
+    fn accumulate_block_rewards<'a>(
+        &mut self,
+        current_epoch: impl Into<Epoch>,
+        proposer: Option<&'a Self::Address>,
+        signers: impl IntoIterator<Item = &'a Self::Address>,
+    ) {
+        let current_epoch = current_epoch.into();
+        // Rewards from a previous epoch must be paid out first
+        self.distribute_rewards(current_epoch);
+        let params = self.read_pos_params();
+        let mut accumulator = match self.read_rewards_accumulator() {
+            Some(accumulator) if accumulator.epoch == current_epoch => {
+                accumulator
+            }
+            _ => RewardsAccumulator::new(current_epoch),
+        };
+        if let Some(proposer) = proposer {
+            accumulator.add(
+                proposer,
+                Self::TokenAmount::from(params.block_proposer_reward),
+            );
+        }
+        for signer in signers {
+            accumulator
+                .add(signer, Self::TokenAmount::from(params.block_vote_reward));
+        }
+        self.write_rewards_accumulator(&accumulator);
+    }
+
+    /// Pay out the block rewards accumulated in a previous epoch. The
+    /// validator's commission is taken from the reward of each validator and
+    /// the rest is split between its bonds pro rata by their stake in the
+    /// epoch, reduced by any slashes. The commission and the share of the
+    /// validator's self-bond are credited to its staking reward address.
+    /// Delegators' shares are credited to the PoS account, from which the
+    /// delegators can claim them. A validator that has been slashed for a
+    /// fault committed in the epoch forfeits its rewards.
+    fn distribute_rewards(&mut self, current_epoch: impl Into<Epoch>) {
+        let current_epoch = current_epoch.into();
+        let RewardsAccumulator { epoch, rewards } =
+            match self.read_rewards_accumulator() {
+                Some(accumulator) if accumulator.epoch < current_epoch => {
+                    accumulator
+                }
+                _ => return,
+            };
+        for (validator, reward) in rewards {
+            let slashes = self.read_validator_slashes(&validator);
+            if slashes.iter().any(|slash| slash.epoch == epoch) {
+                continue;
+            }
+            let staking_reward_address =
+                match self.read_validator_staking_reward_address(&validator) {
+                    Some(address) => address,
+                    None => continue,
+                };
+            let commission_rate = self
+                .read_validator_commission_rate(&validator)
+                .and_then(|rates| rates.get(epoch).copied())
+                .unwrap_or_default();
+            let stakes: Result<Vec<_>, _> = self
+                .read_validator_bonds(&validator)
+                .into_iter()
+                .map(|(bond_id, bonds)| {
+                    slashed_bond_stake(&bonds, &slashes, epoch)
+                        .map(|stake| (bond_id, stake))
+                })
+                .collect();
+            // The reward cannot be split between the bonds when their stake
+            // cannot be calculated, so it's forfeited
+            let stakes = match stakes {
+                Ok(stakes) => stakes,
+                Err(_) => continue,
+            };
+            for (bond_id, amount) in
+                bonds_rewards(&validator, &stakes, reward, commission_rate)
+            {
+                if amount == Self::TokenAmount::default() {
+                    continue;
+                }
+                let mut bond_rewards =
+                    self.read_bond_rewards(&bond_id).unwrap_or_default();
+                if bond_id.source == bond_id.validator {
+                    self.credit_tokens(
+                        &Self::staking_token_address(),
+                        &staking_reward_address,
+                        amount,
+                    );
+                    bond_rewards.record(epoch, amount);
+                } else {
+                    self.credit_tokens(
+                        &Self::staking_token_address(),
+                        &Self::POS_ADDRESS,
+                        amount,
+                    );
+                    bond_rewards.accrue(epoch, amount);
+                }
+                self.write_bond_rewards(&bond_id, &bond_rewards);
+            }
+        }
+        self.write_rewards_accumulator(&RewardsAccumulator::new(current_epoch));
+    }
 }
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum GenesisError {
-    #[error("Voting power overflow: {0}")]
-    VotingPowerOverflow(TryFromIntError),
+    #[error("Arithmetic error: {0}")]
+    Arithmetic(#[from] ArithmeticError),
+    #[error(
+        "The consensus key of the genesis validator {0} is used by another \
+         validator"
+    )]
+    DuplicateConsensusKey(String),
 }
 
 #[allow(missing_docs)]
@@ -890,6 +1782,16 @@ pub enum BecomeValidatorError<Address: Display + Debug> {
          address {0}"
     )]
     StakingRewardAddressEqValidatorAddress(Address),
+    #[error("Invalid commission rate {0}, it must not be greater than 100%")]
+    InvalidCommissionRate(BasisPoints),
+    #[error(
+        "The validator metadata field {0} is longer than the maximum length \
+         of {} bytes",
+        ValidatorMetadata::MAX_FIELD_LEN
+    )]
+    MetadataFieldTooLong(&'static str),
+    #[error("The consensus key is already used by the validator {0}")]
+    ConsensusKeyAlreadyUsed(Address),
 }
 
 #[allow(missing_docs)]
@@ -904,8 +1806,8 @@ pub enum BondError<Address: Display + Debug> {
     SourceMustNotBeAValidator(Address),
     #[error("The given validator address {0} is inactive")]
     InactiveValidator(Address),
-    #[error("Voting power overflow: {0}")]
-    VotingPowerOverflow(TryFromIntError),
+    #[error("Arithmetic error: {0}")]
+    Arithmetic(#[from] ArithmeticError),
     #[error("Given zero amount to unbond")]
     ZeroAmount,
 }
@@ -923,12 +1825,49 @@ pub enum UnbondError<Address: Display + Debug, TokenAmount: Display + Debug> {
     ValidatorHasNoBonds(Address),
     #[error("Voting power not found for the validator {0}")]
     ValidatorHasNoVotingPower(Address),
-    #[error("Voting power overflow: {0}")]
-    VotingPowerOverflow(TryFromIntError),
+    #[error("Arithmetic error: {0}")]
+    Arithmetic(#[from] ArithmeticError),
     #[error("Given zero amount to unbond")]
     ZeroAmount,
 }
 
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum RedelegationError<Address, TokenAmount>
+where
+    Address: Display + Debug + Clone + PartialOrd + Ord + Hash,
+    TokenAmount: Display + Debug,
+{
+    #[error("No bond could be found")]
+    NoBondFound,
+    #[error(
+        "The given source address {0} is a validator address. Validators may \
+         not redelegate."
+    )]
+    SourceMustNotBeAValidator(Address),
+    #[error("Cannot redelegate tokens to the same validator {0}")]
+    SameValidator(Address),
+    #[error(
+        "The bond {0} contains redelegated tokens that may still be slashed \
+         for the faults of their source validator"
+    )]
+    IncomingRedelegations(BondId<Address>),
+    #[error(
+        "Trying to redelegate more tokens ({0}) than the amount bonded ({1})"
+    )]
+    RedelegationAmountGreaterThanBond(TokenAmount, TokenAmount),
+    #[error("No bonds found for the validator {0}")]
+    ValidatorHasNoBonds(Address),
+    #[error("Voting power not found for the validator {0}")]
+    ValidatorHasNoVotingPower(Address),
+    #[error("Arithmetic error: {0}")]
+    Arithmetic(#[from] ArithmeticError),
+    #[error("Given zero amount to redelegate")]
+    ZeroAmount,
+    #[error("Bonding error: {0}")]
+    Bond(#[from] BondError<Address>),
+}
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum WithdrawError<Address>
@@ -939,6 +1878,60 @@ where
     NoUnbondFound(BondId<Address>),
     #[error("No unbond may be withdrawn yet for {0}")]
     NoWithdrawableUnbond(BondId<Address>),
+    #[error("Arithmetic error: {0}")]
+    Arithmetic(#[from] ArithmeticError),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum CommissionRateChangeError<Address: Display + Debug> {
+    #[error("The given address {0} is not a validator address")]
+    NotAValidator(Address),
+    #[error("Invalid commission rate {0}, it must not be greater than 100%")]
+    InvalidCommissionRate(BasisPoints),
+    #[error(
+        "The commission rate change {0} is greater than the maximum change \
+         {1} per epoch"
+    )]
+    RateChangeTooLarge(BasisPoints, BasisPoints),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ConsensusKeyChangeError<Address: Display + Debug> {
+    #[error("The given address {0} is not a validator address")]
+    NotAValidator(Address),
+    #[error(
+        "The new consensus key of validator {0} is the same as its current key"
+    )]
+    KeyUnchanged(Address),
+    #[error("The consensus key is already used by validator {0}")]
+    KeyAlreadyUsed(Address),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum MetadataUpdateError<Address: Display + Debug> {
+    #[error("The given address {0} is not a validator address")]
+    NotAValidator(Address),
+    #[error(
+        "The validator metadata field {0} is longer than the maximum length \
+         of {} bytes",
+        ValidatorMetadata::MAX_FIELD_LEN
+    )]
+    FieldTooLong(&'static str),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ClaimRewardsError<Address>
+where
+    Address: Display + Debug + Clone + PartialOrd + Ord + Hash,
+{
+    #[error("No rewards could be found for {0}")]
+    NoRewardsFound(BondId<Address>),
+    #[error("No rewards may be claimed yet for {0}")]
+    NoRewardsToClaim(BondId<Address>),
 }
 
 #[allow(missing_docs)]
@@ -951,12 +1944,33 @@ where
     ValidatorHasNoTotalDeltas(Address),
     #[error("The validator {0} has no voting power")]
     ValidatorHasNoVotingPower(Address),
-    #[error("Unexpected slash token change")]
-    InvalidSlashChange(i128),
-    #[error("Voting power overflow: {0}")]
-    VotingPowerOverflow(TryFromIntError),
+    #[error("Arithmetic error: {0}")]
+    Arithmetic(#[from] ArithmeticError),
     #[error("Unexpected negative stake {0} for validator {1}")]
     NegativeStake(i128, Address),
+    #[error("Jailing error: {0}")]
+    Jail(#[from] JailError<Address>),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum JailError<Address>
+where
+    Address: Display + Debug + Clone + PartialOrd + Ord + Hash,
+{
+    #[error("The validator {0} has no state")]
+    ValidatorHasNoState(Address),
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum UnjailError<Address: Display + Debug> {
+    #[error("The given address {0} is not a validator address")]
+    NotAValidator(Address),
+    #[error("The validator {0} is not jailed")]
+    NotJailed(Address),
+    #[error("The validator {0} cannot be unjailed before epoch {1}")]
+    JailDurationNotElapsed(Address, Epoch),
 }
 
 struct GenesisData<Validators, Address, TokenAmount, TokenChange, PK>
@@ -1022,10 +2036,12 @@ where
     address: Address,
     staking_reward_address: Address,
     consensus_key: ValidatorConsensusKeys<PK>,
+    consensus_key_owner: (PK, ConsensusKeyOwner<Address>),
     staking_reward_key: PK,
     state: ValidatorStates,
     total_deltas: ValidatorTotalDeltas<TokenChange>,
     voting_power: ValidatorVotingPowers,
+    commission_rate: ValidatorCommissionRates,
     bond: (BondId<Address>, Bonds<TokenAmount>),
 }
 
@@ -1077,21 +2093,32 @@ where
         + From<TokenAmount>
         + BorshDeserialize
         + BorshSerialize,
-    PK: 'a + Debug + Clone + BorshDeserialize + BorshSerialize,
+    PK: 'a + Debug + Clone + PartialEq + BorshDeserialize + BorshSerialize,
 {
     // Accumulate the validator set and total voting power
     let mut active: BTreeSet<WeightedValidator<Address>> = BTreeSet::default();
     let mut total_voting_power = VotingPowerDelta::default();
     let mut total_bonded_balance = TokenAmount::default();
+    let mut consensus_keys: Vec<&PK> = vec![];
     for GenesisValidator {
-        address, tokens, ..
+        address,
+        tokens,
+        consensus_key,
+        ..
     } in validators.clone()
     {
+        if consensus_keys.contains(&consensus_key) {
+            return Err(GenesisError::DuplicateConsensusKey(
+                address.to_string(),
+            ));
+        }
+        consensus_keys.push(consensus_key);
         total_bonded_balance += *tokens;
-        let delta = VotingPowerDelta::try_from_tokens(*tokens, params)
-            .map_err(GenesisError::VotingPowerOverflow)?;
-        total_voting_power += delta;
-        let voting_power = VotingPower::from_tokens(*tokens, params);
+        let delta = VotingPowerDelta::try_from_tokens(*tokens, params)?;
+        total_voting_power = total_voting_power
+            .checked_add(delta)
+            .ok_or(ArithmeticError::VotingPowerOverflow)?;
+        let voting_power = VotingPower::try_from_tokens(*tokens, params)?;
         active.insert(WeightedValidator {
             voting_power,
             address: address.clone(),
@@ -1123,7 +2150,16 @@ where
                   tokens,
                   consensus_key,
                   staking_reward_key,
+                  commission_rate,
               }| {
+            let consensus_key_owner = (
+                consensus_key.clone(),
+                ConsensusKeyOwner {
+                    validator: address.clone(),
+                    start: current_epoch,
+                    end: None,
+                },
+            );
             let consensus_key =
                 Epoched::init_at_genesis(consensus_key.clone(), current_epoch);
             let state = Epoched::init_at_genesis(
@@ -1134,10 +2170,11 @@ where
             let total_deltas =
                 EpochedDelta::init_at_genesis(token_delta, current_epoch);
             let voting_power =
-                VotingPowerDelta::try_from_tokens(*tokens, params)
-                    .map_err(GenesisError::VotingPowerOverflow)?;
+                VotingPowerDelta::try_from_tokens(*tokens, params)?;
             let voting_power =
                 EpochedDelta::init_at_genesis(voting_power, current_epoch);
+            let commission_rate =
+                Epoched::init_at_genesis(*commission_rate, current_epoch);
             let bond_id = BondId {
                 source: address.clone(),
                 validator: address.clone(),
@@ -1150,10 +2187,12 @@ where
                 address: address.clone(),
                 staking_reward_address: staking_reward_address.clone(),
                 consensus_key,
+                consensus_key_owner,
                 staking_reward_key: staking_reward_key.clone(),
                 state,
                 total_deltas,
                 voting_power,
+                commission_rate,
                 bond: (bond_id, bond),
             })
         },
@@ -1167,18 +2206,19 @@ where
     })
 }
 
-/// A function to apply a slash to byzantine validator.
+/// A function to apply a slash to byzantine validator. The `slashed_bonds`
+/// are subtracted from the validator's stake at the pipeline offset.
 #[allow(clippy::too_many_arguments)]
 fn slash<Address, TokenChange>(
     params: &PosParams,
     current_epoch: Epoch,
     validator: &Address,
-    slash: &Slash,
+    slashed_bonds: TokenChange,
     total_deltas: &mut ValidatorTotalDeltas<TokenChange>,
     voting_power: &mut ValidatorVotingPowers,
     validator_set: &mut ValidatorSets<Address>,
     total_voting_power: &mut TotalVotingPowers,
-) -> Result<TokenChange, SlashError<Address>>
+) -> Result<(), SlashError<Address>>
 where
     Address: Display
         + Debug
@@ -1208,9 +2248,23 @@ where
             validator.clone(),
         ));
     }
-    let raw_current_stake: i128 = current_stake.into();
-    let slashed_amount: TokenChange = (slash.rate * raw_current_stake).into();
-    let token_change = -slashed_amount;
+    // The slashed bonds may add up to slightly more than the validator's
+    // stake, because of the rounding of the slashes applied to bond deltas
+    // when they are unbonded. The stake mustn't become negative in any epoch
+    // from the pipeline offset, at which the slash is applied.
+    let lowest_stake = (params.pipeline_len..=params.unbonding_len)
+        .map(|offset| {
+            let stake: i128 = total_deltas
+                .get(current_epoch + offset)
+                .unwrap_or_default()
+                .into();
+            stake
+        })
+        .min()
+        .unwrap_or_default();
+    let slashed_bonds: i128 = slashed_bonds.into();
+    let token_change =
+        TokenChange::from(-cmp::min(slashed_bonds, cmp::max(lowest_stake, 0)));
 
     // Apply slash at pipeline offset
     let update_offset = DynEpochOffset::PipelineLen;
@@ -1226,7 +2280,7 @@ where
         validator_set,
         Some(total_deltas),
         current_epoch,
-    );
+    )?;
 
     // Update validator's total deltas
     total_deltas.add_at_offset(
@@ -1244,10 +2298,134 @@ where
         voting_power,
         total_voting_power,
         current_epoch,
-    )
-    .map_err(SlashError::VotingPowerOverflow)?;
+    )?;
+
+    Ok(())
+}
+
+/// The token amounts slashed from a validator's bonds, unbonds and
+/// withdrawals.
+#[derive(Debug, Clone, PartialEq)]
+struct SlashedAmounts<Address, TokenAmount>
+where
+    Address: Display + Debug + Clone + PartialEq + Eq + PartialOrd + Ord + Hash,
+{
+    /// Slashed from the tokens that are still bonded
+    pub bonded: TokenAmount,
+    /// Slashed from the unbonded tokens that haven't been withdrawn yet
+    pub unbonded: TokenAmount,
+    /// Slashed from the tokens that have already been withdrawn, by bond.
+    /// These are owed to the slash pool as clawbacks.
+    pub withdrawn: Vec<(BondId<Address>, TokenAmount)>,
+}
+
+/// Find the token amounts slashed by a new `slash` from a validator's bonds,
+/// unbonds and withdrawals. A bond is slashable if it started on or before
+/// the slash epoch and an unbond or a withdrawal if the slash epoch is within
+/// its start and end epochs. The slash is calculated from the delta reduced
+/// by the validator's `previous_slashes` in the same way as when the tokens
+/// are unbonded or withdrawn, so that the slashed amounts match the tokens
+/// that will never be paid out from the PoS account.
+fn slashed_amounts<Address, TokenAmount>(
+    params: &PosParams,
+    slash: &Slash,
+    previous_slashes: &[Slash],
+    bonds: &[(BondId<Address>, Bonds<TokenAmount>)],
+    unbonds: &[(BondId<Address>, Unbonds<TokenAmount>)],
+    withdrawals: &[(BondId<Address>, Withdrawals<TokenAmount>)],
+    current_epoch: Epoch,
+) -> Result<SlashedAmounts<Address, TokenAmount>, ArithmeticError>
+where
+    Address: Display + Debug + Clone + PartialEq + Eq + PartialOrd + Ord + Hash,
+    TokenAmount: Debug
+        + Default
+        + Clone
+        + Copy
+        + Add<Output = TokenAmount>
+        + AddAssign
+        + Into<u64>
+        + From<u64>
+        + SubAssign
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    // Each slash is calculated from the delta reduced by the previous slashes
+    let slash_delta = |delta: TokenAmount, previous_slashes: &[&Slash]| {
+        let mut delta = delta;
+        for previous_slash in previous_slashes {
+            let raw_delta: u64 = delta.into();
+            delta -=
+                TokenAmount::from(previous_slash.rate.portion_of(raw_delta)?);
+        }
+        let raw_delta: u64 = delta.into();
+        Ok(TokenAmount::from(slash.rate.portion_of(raw_delta)?))
+    };
 
-    Ok(slashed_amount)
+    let mut bonded = TokenAmount::default();
+    for (_bond_id, bonds) in bonds {
+        let bond = bonds.get_at_offset(
+            current_epoch,
+            DynEpochOffset::PipelineLen,
+            params,
+        );
+        for (epoch_start, delta) in bond.iter().flat_map(|bond| &bond.deltas) {
+            if *epoch_start > slash.epoch {
+                continue;
+            }
+            let applicable_slashes: Vec<&Slash> = previous_slashes
+                .iter()
+                .filter(|previous_slash| previous_slash.epoch >= *epoch_start)
+                .collect();
+            bonded += slash_delta(*delta, &applicable_slashes)?;
+        }
+    }
+    let mut unbonded = TokenAmount::default();
+    for (_bond_id, unbonds) in unbonds {
+        let unbond = unbonds.get_at_offset(
+            current_epoch,
+            DynEpochOffset::UnbondingLen,
+            params,
+        );
+        for ((epoch_start, epoch_end), delta) in
+            unbond.iter().flat_map(|unbond| &unbond.deltas)
+        {
+            let is_slashable =
+                |epoch: Epoch| epoch >= *epoch_start && epoch <= *epoch_end;
+            if !is_slashable(slash.epoch) {
+                continue;
+            }
+            let applicable_slashes: Vec<&Slash> = previous_slashes
+                .iter()
+                .filter(|previous_slash| is_slashable(previous_slash.epoch))
+                .collect();
+            unbonded += slash_delta(*delta, &applicable_slashes)?;
+        }
+    }
+    let mut withdrawn = Vec::new();
+    for (bond_id, withdrawals) in withdrawals {
+        let mut clawback = TokenAmount::default();
+        for ((epoch_start, epoch_end), delta) in &withdrawals.deltas {
+            let is_slashable =
+                |epoch: Epoch| epoch >= *epoch_start && epoch <= *epoch_end;
+            if !is_slashable(slash.epoch) {
+                continue;
+            }
+            let applicable_slashes: Vec<&Slash> = previous_slashes
+                .iter()
+                .filter(|previous_slash| is_slashable(previous_slash.epoch))
+                .collect();
+            clawback += slash_delta(*delta, &applicable_slashes)?;
+        }
+        let raw_clawback: u64 = clawback.into();
+        if raw_clawback != 0 {
+            withdrawn.push((bond_id.clone(), clawback));
+        }
+    }
+    Ok(SlashedAmounts {
+        bonded,
+        unbonded,
+        withdrawn,
+    })
 }
 
 struct BecomeValidatorData<PK, TokenChange>
@@ -1265,6 +2443,7 @@ where
     state: ValidatorStates,
     total_deltas: ValidatorTotalDeltas<TokenChange>,
     voting_power: ValidatorVotingPowers,
+    commission_rate: ValidatorCommissionRates,
 }
 
 /// A function that initialized data for a new validator.
@@ -1272,6 +2451,7 @@ fn become_validator<Address, PK, TokenChange>(
     params: &PosParams,
     address: &Address,
     consensus_key: &PK,
+    commission_rate: BasisPoints,
     validator_set: &mut ValidatorSets<Address>,
     current_epoch: Epoch,
 ) -> BecomeValidatorData<PK, TokenChange>
@@ -1288,6 +2468,7 @@ where
 {
     let consensus_key =
         Epoched::init(consensus_key.clone(), current_epoch, params);
+    let commission_rate = Epoched::init(commission_rate, current_epoch, params);
 
     let mut state =
         Epoched::init_at_genesis(ValidatorState::Pending, current_epoch);
@@ -1329,46 +2510,78 @@ where
         state,
         total_deltas,
         voting_power,
+        commission_rate,
     }
 }
 
-struct BondData<TokenAmount, TokenChange>
+/// Change a validator's commission rate at the pipeline offset. The change
+/// from the rate in the epoch before it must not be greater than the maximum
+/// change allowed by the PoS parameters.
+fn change_commission_rate<Address>(
+    params: &PosParams,
+    commission_rates: &mut ValidatorCommissionRates,
+    rate: BasisPoints,
+    current_epoch: Epoch,
+) -> Result<(), CommissionRateChangeError<Address>>
 where
-    TokenAmount: Debug
-        + Default
-        + Clone
-        + Copy
-        + Add<Output = TokenAmount>
-        + AddAssign
-        + BorshDeserialize
-        + BorshSerialize,
-    TokenChange: Debug
-        + Clone
-        + Copy
-        + Add<Output = TokenChange>
-        + BorshDeserialize
-        + BorshSerialize,
+    Address: Display + Debug,
 {
-    pub bond: Bonds<TokenAmount>,
-    pub validator_total_deltas: ValidatorTotalDeltas<TokenChange>,
-    pub validator_voting_power: ValidatorVotingPowers,
+    if rate > BasisPoints::MAX {
+        return Err(CommissionRateChangeError::InvalidCommissionRate(rate));
+    }
+    // The rate in effect in the epoch before the pipeline epoch
+    let pipeline_len = DynEpochOffset::PipelineLen.value(params);
+    let prev_rate = commission_rates
+        .get(current_epoch + pipeline_len.saturating_sub(1))
+        .copied()
+        .unwrap_or_default();
+    let change = rate.abs_diff(prev_rate);
+    if change > params.max_commission_rate_change {
+        return Err(CommissionRateChangeError::RateChangeTooLarge(
+            change,
+            params.max_commission_rate_change,
+        ));
+    }
+    commission_rates.set(rate, current_epoch, params);
+    Ok(())
 }
 
-/// Bond tokens to a validator (self-bond or delegation).
-#[allow(clippy::too_many_arguments)]
-fn bond_tokens<Address, TokenAmount, TokenChange>(
+/// Change a validator's consensus key at the pipeline offset from the current
+/// epoch.
+fn change_consensus_key<Address, PK>(
     params: &PosParams,
-    validator_state: Option<ValidatorStates>,
-    bond_id: &BondId<Address>,
-    current_bond: Option<Bonds<TokenAmount>>,
-    amount: TokenAmount,
-    validator_total_deltas: Option<ValidatorTotalDeltas<TokenChange>>,
-    validator_voting_power: Option<ValidatorVotingPowers>,
-    total_voting_power: &mut TotalVotingPowers,
-    validator_set: &mut ValidatorSets<Address>,
+    consensus_keys: &mut ValidatorConsensusKeys<PK>,
+    validator: &Address,
+    consensus_key: &PK,
     current_epoch: Epoch,
-) -> Result<BondData<TokenAmount, TokenChange>, BondError<Address>>
+) -> Result<(), ConsensusKeyChangeError<Address>>
 where
+    Address: Display + Debug + Clone,
+    PK: Debug + Clone + PartialEq + BorshDeserialize + BorshSerialize,
+{
+    // The key that would otherwise be in effect in the pipeline epoch
+    let pipeline_key = consensus_keys.get_at_offset(
+        current_epoch,
+        DynEpochOffset::PipelineLen,
+        params,
+    );
+    if pipeline_key == Some(consensus_key) {
+        return Err(ConsensusKeyChangeError::KeyUnchanged(validator.clone()));
+    }
+    consensus_keys.set(consensus_key.clone(), current_epoch, params);
+    Ok(())
+}
+
+/// Jail a validator at the pipeline offset from the current epoch. The
+/// validator is removed from the validator set and if it was active, the
+/// inactive validator with the most voting power takes its slot.
+fn jail_validator<Address>(
+    params: &PosParams,
+    validator: &Address,
+    state: &mut ValidatorStates,
+    validator_set: &mut ValidatorSets<Address>,
+    current_epoch: Epoch,
+) where
     Address: Display
         + Debug
         + Clone
@@ -1379,20 +2592,173 @@ where
         + Hash
         + BorshDeserialize
         + BorshSerialize,
-    TokenAmount: Display
-        + Debug
-        + Default
-        + Clone
-        + Copy
-        + PartialEq
-        + Add<Output = TokenAmount>
-        + AddAssign
-        + Into<u64>
-        + BorshDeserialize
-        + BorshSerialize,
-    TokenChange: Display
-        + Debug
-        + Default
+{
+    state.set(ValidatorState::Jailed, current_epoch, params);
+
+    validator_set.update_from_offset(
+        |validator_set, _epoch| {
+            let active = validator_set
+                .active
+                .iter()
+                .find(|active| &active.address == validator)
+                .cloned();
+            match active {
+                Some(active) => {
+                    validator_set.active.remove(&active);
+                    if let Some(activate_max) =
+                        validator_set.inactive.pop_last_shim()
+                    {
+                        validator_set.active.insert(activate_max);
+                    }
+                }
+                None => {
+                    let inactive = validator_set
+                        .inactive
+                        .iter()
+                        .find(|inactive| &inactive.address == validator)
+                        .cloned();
+                    if let Some(inactive) = inactive {
+                        validator_set.inactive.remove(&inactive);
+                    }
+                }
+            }
+        },
+        current_epoch,
+        DynEpochOffset::PipelineLen,
+        params,
+    );
+}
+
+/// Unjail a validator at the pipeline offset from the current epoch. The
+/// validator is added back to the validator set with its voting power.
+fn unjail_validator<Address>(
+    params: &PosParams,
+    validator: &Address,
+    state: &mut ValidatorStates,
+    jailed_epoch: Epoch,
+    voting_power: &ValidatorVotingPowers,
+    validator_set: &mut ValidatorSets<Address>,
+    current_epoch: Epoch,
+) -> Result<(), UnjailError<Address>>
+where
+    Address: Display
+        + Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    let unjail_epoch = jailed_epoch + params.min_jail_duration;
+    if current_epoch < unjail_epoch {
+        return Err(UnjailError::JailDurationNotElapsed(
+            validator.clone(),
+            unjail_epoch,
+        ));
+    }
+
+    state.set(ValidatorState::Candidate, current_epoch, params);
+
+    validator_set.update_from_offset(
+        |validator_set, epoch| {
+            let voting_power = voting_power
+                .get(epoch)
+                .and_then(|delta| VotingPower::try_from(delta).ok())
+                .unwrap_or_default();
+            let validator = WeightedValidator {
+                voting_power,
+                address: validator.clone(),
+            };
+            if validator_set.active.len() < params.max_validator_slots as usize
+            {
+                validator_set.active.insert(validator);
+                return;
+            }
+            let min_active_validator = validator_set.active.first_shim();
+            let min_voting_power = min_active_validator
+                .map(|v| v.voting_power)
+                .unwrap_or_default();
+            if voting_power > min_voting_power {
+                let deactivate_min = validator_set.active.pop_first_shim();
+                validator_set.active.insert(validator);
+                if let Some(deactivate_min) = deactivate_min {
+                    validator_set.inactive.insert(deactivate_min);
+                }
+            } else {
+                validator_set.inactive.insert(validator);
+            }
+        },
+        current_epoch,
+        DynEpochOffset::PipelineLen,
+        params,
+    );
+    Ok(())
+}
+
+struct BondData<TokenAmount, TokenChange>
+where
+    TokenAmount: Debug
+        + Default
+        + Clone
+        + Copy
+        + Add<Output = TokenAmount>
+        + AddAssign
+        + BorshDeserialize
+        + BorshSerialize,
+    TokenChange: Debug
+        + Clone
+        + Copy
+        + Add<Output = TokenChange>
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    pub bond: Bonds<TokenAmount>,
+    pub validator_total_deltas: ValidatorTotalDeltas<TokenChange>,
+    pub validator_voting_power: ValidatorVotingPowers,
+}
+
+/// Bond tokens to a validator (self-bond or delegation).
+#[allow(clippy::too_many_arguments)]
+fn bond_tokens<Address, TokenAmount, TokenChange>(
+    params: &PosParams,
+    validator_state: Option<ValidatorStates>,
+    bond_id: &BondId<Address>,
+    current_bond: Option<Bonds<TokenAmount>>,
+    amount: TokenAmount,
+    validator_total_deltas: Option<ValidatorTotalDeltas<TokenChange>>,
+    validator_voting_power: Option<ValidatorVotingPowers>,
+    total_voting_power: &mut TotalVotingPowers,
+    validator_set: &mut ValidatorSets<Address>,
+    current_epoch: Epoch,
+) -> Result<BondData<TokenAmount, TokenChange>, BondError<Address>>
+where
+    Address: Display
+        + Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize,
+    TokenAmount: Display
+        + Debug
+        + Default
+        + Clone
+        + Copy
+        + PartialEq
+        + Add<Output = TokenAmount>
+        + AddAssign
+        + Into<u64>
+        + BorshDeserialize
+        + BorshSerialize,
+    TokenChange: Display
+        + Debug
+        + Default
         + Clone
         + Copy
         + Neg
@@ -1457,7 +2823,7 @@ where
         validator_set,
         validator_total_deltas.as_ref(),
         current_epoch,
-    );
+    )?;
 
     // Update validator's total deltas
     let delta = TokenChange::from(amount);
@@ -1496,8 +2862,7 @@ where
         &mut validator_voting_power,
         total_voting_power,
         current_epoch,
-    )
-    .map_err(BondError::VotingPowerOverflow)?;
+    )?;
 
     Ok(BondData {
         bond,
@@ -1594,103 +2959,167 @@ where
     };
 
     let update_offset = DynEpochOffset::UnbondingLen;
-    let mut to_unbond = amount;
-    let to_unbond = &mut to_unbond;
+    let (decremented, slashed_amount) =
+        decrement_bond(params, bond, amount, &slashes, current_epoch)?;
+    let unbond_end = current_epoch + update_offset.value(params) - 1;
+    // For each decremented bond value write a new unbond
+    for (epoch_start, delta) in decremented {
+        let mut unbonded = HashMap::default();
+        unbonded.insert((epoch_start, unbond_end), delta);
+        unbond.add(Unbond { deltas: unbonded }, current_epoch, params);
+    }
+
+    // The unbonded bond deltas reduced by the slashes may add up to slightly
+    // more than the validator's stake, because the slashes are rounded for
+    // each of the bond deltas separately
+    let stake: TokenChange = validator_total_deltas
+        .get_at_offset(current_epoch, update_offset, params)
+        .unwrap_or_default();
+    let mut token_change = -TokenChange::from(slashed_amount);
+    if Into::<i128>::into(stake + token_change) < 0 {
+        token_change = -stake;
+    }
+
+    // Update validator set. This has to be done before we update the
+    // `validator_total_deltas`, because we need to look-up the validator with
+    // its voting power before the change.
+    update_validator_set(
+        params,
+        &bond_id.validator,
+        token_change,
+        update_offset,
+        validator_set,
+        Some(validator_total_deltas),
+        current_epoch,
+    )?;
+
+    // Update validator's total deltas
+    validator_total_deltas.add(token_change, current_epoch, params);
+
+    // Update the validator's and the total voting power.
+    update_voting_powers(
+        params,
+        update_offset,
+        validator_total_deltas,
+        validator_voting_power,
+        total_voting_power,
+        current_epoch,
+    )?;
+
+    Ok(UnbondData { unbond })
+}
+
+/// Decrement the bond deltas starting from the rightmost value (a bond in a
+/// future-most epoch) until the whole amount is decremented. Returns the
+/// decremented deltas with the start epochs of their bonds and their total
+/// amount reduced by the given slashes.
+fn decrement_bond<TokenAmount>(
+    params: &PosParams,
+    bond: &mut Bonds<TokenAmount>,
+    amount: TokenAmount,
+    slashes: &[Slash],
+    current_epoch: Epoch,
+) -> Result<(Vec<(Epoch, TokenAmount)>, TokenAmount), ArithmeticError>
+where
+    TokenAmount: Display
+        + Debug
+        + Default
+        + Clone
+        + Copy
+        + PartialOrd
+        + Add<Output = TokenAmount>
+        + AddAssign
+        + Into<u64>
+        + From<u64>
+        + SubAssign
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    let mut to_decrement = amount;
+    let to_decrement = &mut to_decrement;
+    let mut decremented = vec![];
     let mut slashed_amount = TokenAmount::default();
-    // Decrement the bond deltas starting from the rightmost value (a bond in a
-    // future-most epoch) until whole amount is decremented
+    let mut error = None;
     bond.rev_update_while(
         |bonds, _epoch| {
             bonds.deltas.retain(|epoch_start, bond_delta| {
-                if *to_unbond == 0.into() {
+                if *to_decrement == 0.into() {
                     return true;
                 }
-                let mut unbonded = HashMap::default();
-                let unbond_end =
-                    current_epoch + update_offset.value(params) - 1;
                 // We need to accumulate the slashed delta for multiple slashes
                 // applicable to a bond, where each slash should be
                 // calculated from the delta reduced by the previous slash.
-                let applied_delta = if to_unbond > bond_delta {
-                    unbonded.insert((*epoch_start, unbond_end), *bond_delta);
-                    *to_unbond -= *bond_delta;
+                let applied_delta = if to_decrement > bond_delta {
+                    *to_decrement -= *bond_delta;
                     let applied_delta = *bond_delta;
                     *bond_delta = 0.into();
                     applied_delta
                 } else {
-                    unbonded.insert((*epoch_start, unbond_end), *to_unbond);
-                    *bond_delta -= *to_unbond;
-                    let applied_delta = *to_unbond;
-                    *to_unbond = 0.into();
+                    *bond_delta -= *to_decrement;
+                    let applied_delta = *to_decrement;
+                    *to_decrement = 0.into();
                     applied_delta
                 };
+                decremented.push((*epoch_start, applied_delta));
                 // Calculate how much the bond delta would be after slashing
                 let mut slashed_bond_delta = applied_delta;
-                for slash in &slashes {
+                for slash in slashes {
                     if slash.epoch >= *epoch_start {
                         let raw_delta: u64 = slashed_bond_delta.into();
-                        let raw_slashed_delta = slash.rate * raw_delta;
-                        let slashed_delta =
-                            TokenAmount::from(raw_slashed_delta);
-                        slashed_bond_delta -= slashed_delta;
+                        match slash.rate.portion_of(raw_delta) {
+                            Ok(raw_slashed_delta) => {
+                                slashed_bond_delta -=
+                                    TokenAmount::from(raw_slashed_delta)
+                            }
+                            Err(err) => error = Some(err),
+                        }
                     }
                 }
                 slashed_amount += slashed_bond_delta;
-
-                // For each decremented bond value write a new unbond
-                unbond.add(Unbond { deltas: unbonded }, current_epoch, params);
                 // Remove bonds with no tokens left
                 *bond_delta != 0.into()
             });
-            // Stop the update once all the tokens are unbonded
-            *to_unbond != 0.into()
+            // Stop the update once all the tokens are decremented or on error
+            *to_decrement != 0.into() && error.is_none()
         },
         current_epoch,
         params,
     );
+    match error {
+        Some(err) => Err(err),
+        None => Ok((decremented, slashed_amount)),
+    }
+}
 
-    // Update validator set. This has to be done before we update the
-    // `validator_total_deltas`, because we need to look-up the validator with
-    // its voting power before the change.
-    let token_change = -TokenChange::from(slashed_amount);
-    update_validator_set(
-        params,
-        &bond_id.validator,
-        token_change,
-        update_offset,
-        validator_set,
-        Some(validator_total_deltas),
-        current_epoch,
-    );
-
-    // Update validator's total deltas
-    validator_total_deltas.add(token_change, current_epoch, params);
-
-    // Update the validator's and the total voting power.
-    update_voting_powers(
-        params,
-        update_offset,
-        validator_total_deltas,
-        validator_voting_power,
-        total_voting_power,
-        current_epoch,
-    )
-    .map_err(UnbondError::VotingPowerOverflow)?;
-
-    Ok(UnbondData { unbond })
+struct RedelegationData<TokenAmount> {
+    /// The redelegated bond deltas with the start epochs of their bonds at
+    /// the source validator
+    pub deltas: Vec<(Epoch, TokenAmount)>,
 }
 
-/// Update validator set when a validator's receives a new bond and when its
-/// bond is unbonded (self-bond or delegation).
-fn update_validator_set<Address, TokenChange>(
+/// Redelegate tokens from a delegation to a source validator. The tokens are
+/// removed from the source validator's stake at the pipeline offset. They
+/// have to be bonded to the destination validator with [`bond_tokens`].
+#[allow(clippy::too_many_arguments)]
+fn redelegate_tokens<Address, TokenAmount, TokenChange>(
     params: &PosParams,
-    validator: &Address,
-    token_change: TokenChange,
-    change_offset: DynEpochOffset,
+    bond_id: &BondId<Address>,
+    dest_validator: &Address,
+    bond: &mut Bonds<TokenAmount>,
+    redelegations: Option<&mut Redelegations<Address, TokenAmount>>,
+    redelegations_slashes: &HashMap<Address, Slashes>,
+    amount: TokenAmount,
+    slashes: Slashes,
+    validator_total_deltas: &mut ValidatorTotalDeltas<TokenChange>,
+    validator_voting_power: &mut ValidatorVotingPowers,
+    total_voting_power: &mut TotalVotingPowers,
     validator_set: &mut ValidatorSets<Address>,
-    validator_total_deltas: Option<&ValidatorTotalDeltas<TokenChange>>,
     current_epoch: Epoch,
-) where
+) -> Result<
+    RedelegationData<TokenAmount>,
+    RedelegationError<Address, TokenAmount>,
+>
+where
     Address: Display
         + Debug
         + Clone
@@ -1701,43 +3130,237 @@ fn update_validator_set<Address, TokenChange>(
         + Hash
         + BorshDeserialize
         + BorshSerialize,
-    TokenChange: Display
+    TokenAmount: Display
+        + Debug
         + Default
+        + Clone
+        + Copy
+        + PartialOrd
+        + Add<Output = TokenAmount>
+        + AddAssign
+        + Into<u64>
+        + From<u64>
+        + SubAssign
+        + BorshDeserialize
+        + BorshSerialize,
+    TokenChange: Display
         + Debug
+        + Default
         + Clone
         + Copy
         + Add<Output = TokenChange>
         + Sub
+        + From<TokenAmount>
+        + Neg<Output = TokenChange>
         + Into<i128>
         + BorshDeserialize
         + BorshSerialize,
 {
-    validator_set.update_from_offset(
-        |validator_set, epoch| {
-            // Find the validator's voting power at the epoch that's being
-            // updated from its total deltas
-            let tokens_pre = validator_total_deltas
-                .and_then(|d| d.get(epoch))
-                .unwrap_or_default();
-            let tokens_post = tokens_pre + token_change;
-            let tokens_pre: i128 = tokens_pre.into();
-            let tokens_post: i128 = tokens_post.into();
-            let tokens_pre: u64 = TryFrom::try_from(tokens_pre).unwrap();
-            let tokens_post: u64 = TryFrom::try_from(tokens_post).unwrap();
-            let voting_power_pre = VotingPower::from_tokens(tokens_pre, params);
-            let voting_power_post =
-                VotingPower::from_tokens(tokens_post, params);
-            if voting_power_pre != voting_power_post {
-                let validator_pre = WeightedValidator {
-                    voting_power: voting_power_pre,
-                    address: validator.clone(),
-                };
-                let validator_post = WeightedValidator {
-                    voting_power: voting_power_post,
-                    address: validator.clone(),
-                };
-
-                if validator_set.inactive.contains(&validator_pre) {
+    if amount == TokenAmount::default() {
+        return Err(RedelegationError::ZeroAmount);
+    }
+    if &bond_id.validator == dest_validator {
+        return Err(RedelegationError::SameValidator(dest_validator.clone()));
+    }
+    // Tokens redelegated to the source validator may only be redelegated
+    // again once they can no longer be slashed for the faults of their
+    // previous validator
+    if let Some(redelegations) = redelegations {
+        forget_redelegations(
+            params,
+            redelegations,
+            redelegations_slashes,
+            current_epoch,
+        );
+        if !redelegations.deltas.is_empty() {
+            return Err(RedelegationError::IncomingRedelegations(
+                bond_id.clone(),
+            ));
+        }
+    }
+    // Like in unbonding, we can redelegate tokens that are bonded for a
+    // future epoch (not yet active)
+    let redelegatable_amount = bond
+        .get_at_offset(current_epoch, DynEpochOffset::PipelineLen, params)
+        .unwrap_or_default()
+        .sum();
+    if amount > redelegatable_amount {
+        return Err(RedelegationError::RedelegationAmountGreaterThanBond(
+            amount,
+            redelegatable_amount,
+        ));
+    }
+
+    let update_offset = DynEpochOffset::PipelineLen;
+    let (deltas, slashed_amount) =
+        decrement_bond(params, bond, amount, &slashes, current_epoch)?;
+
+    // Update validator set. This has to be done before we update the
+    // `validator_total_deltas`, because we need to look-up the validator with
+    // its voting power before the change.
+    let token_change = -TokenChange::from(slashed_amount);
+    update_validator_set(
+        params,
+        &bond_id.validator,
+        token_change,
+        update_offset,
+        validator_set,
+        Some(validator_total_deltas),
+        current_epoch,
+    )?;
+
+    // Update validator's total deltas
+    validator_total_deltas.add_at_offset(
+        token_change,
+        current_epoch,
+        update_offset,
+        params,
+    );
+
+    // Update the validator's and the total voting power.
+    update_voting_powers(
+        params,
+        update_offset,
+        validator_total_deltas,
+        validator_voting_power,
+        total_voting_power,
+        current_epoch,
+    )?;
+
+    Ok(RedelegationData { deltas })
+}
+
+/// Forget the redelegations that can no longer be slashed for the faults of
+/// their source validators, i.e. the ones whose source validator's faults
+/// can no longer be evidenced and to which no slash applies.
+fn forget_redelegations<Address, TokenAmount>(
+    params: &PosParams,
+    redelegations: &mut Redelegations<Address, TokenAmount>,
+    redelegations_slashes: &HashMap<Address, Slashes>,
+    current_epoch: Epoch,
+) where
+    Address: Display
+        + Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize,
+    TokenAmount: Debug
+        + Copy
+        + PartialEq
+        + Into<u64>
+        + From<u64>
+        + SubAssign
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    redelegations.deltas.retain(
+        |(redelegation_epoch, src_validator, bond_start), delta| {
+            let slashable =
+                current_epoch < *redelegation_epoch + params.unbonding_len;
+            let slashes: &[Slash] = redelegations_slashes
+                .get(src_validator)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let slashed_delta = Redelegations::<Address, _>::slash_delta(
+                *redelegation_epoch,
+                *bond_start,
+                *delta,
+                slashes,
+            );
+            // A redelegation whose slashed delta cannot be calculated is kept
+            slashable
+                || slashed_delta.map_or(true, |slashed| slashed != *delta)
+        },
+    );
+}
+
+/// Update validator set when a validator's receives a new bond and when its
+/// bond is unbonded (self-bond or delegation).
+fn update_validator_set<Address, TokenChange>(
+    params: &PosParams,
+    validator: &Address,
+    token_change: TokenChange,
+    change_offset: DynEpochOffset,
+    validator_set: &mut ValidatorSets<Address>,
+    validator_total_deltas: Option<&ValidatorTotalDeltas<TokenChange>>,
+    current_epoch: Epoch,
+) -> Result<(), ArithmeticError>
+where
+    Address: Display
+        + Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize,
+    TokenChange: Display
+        + Default
+        + Debug
+        + Clone
+        + Copy
+        + Add<Output = TokenChange>
+        + Sub
+        + Into<i128>
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    // Find the validator's voting powers at the epochs that are being updated
+    // from its total deltas before the validator set is modified, so that an
+    // arithmetic error cannot leave it partially updated
+    let change_offset_value = change_offset.value(params);
+    let start_epoch = current_epoch + change_offset_value;
+    let mut voting_powers = HashMap::new();
+    for epoch in start_epoch.iter_range(
+        DynEpochOffset::UnbondingLen.value(params) - change_offset_value + 1,
+    ) {
+        let tokens_pre: i128 = validator_total_deltas
+            .and_then(|d| d.get(epoch))
+            .unwrap_or_default()
+            .into();
+        let tokens_post = tokens_pre
+            .checked_add(token_change.into())
+            .ok_or(ArithmeticError::TokenAmountOverflow)?;
+        let voting_power_pre =
+            VotingPower::try_from_tokens(stake_to_tokens(tokens_pre)?, params)?;
+        let voting_power_post = VotingPower::try_from_tokens(
+            stake_to_tokens(tokens_post)?,
+            params,
+        )?;
+        voting_powers.insert(epoch, (voting_power_pre, voting_power_post));
+    }
+    validator_set.update_from_offset(
+        |validator_set, epoch| {
+            let (voting_power_pre, voting_power_post) =
+                match voting_powers.get(&epoch) {
+                    Some(voting_powers) => *voting_powers,
+                    None => return,
+                };
+            if voting_power_pre != voting_power_post {
+                let validator_pre = WeightedValidator {
+                    voting_power: voting_power_pre,
+                    address: validator.clone(),
+                };
+                let validator_post = WeightedValidator {
+                    voting_power: voting_power_post,
+                    address: validator.clone(),
+                };
+
+                // A jailed validator is not in the validator set
+                if !validator_set.active.contains(&validator_pre)
+                    && !validator_set.inactive.contains(&validator_pre)
+                {
+                    return;
+                }
+
+                if validator_set.inactive.contains(&validator_pre) {
                     let min_active_validator =
                         validator_set.active.first_shim();
                     let min_voting_power = min_active_validator
@@ -1786,7 +3409,17 @@ fn update_validator_set<Address, TokenChange>(
         current_epoch,
         change_offset,
         params,
-    )
+    );
+    Ok(())
+}
+
+/// Convert a validator's stake into a token amount. Fails when the stake is
+/// negative or when it doesn't fit into the token amount.
+fn stake_to_tokens(stake: i128) -> Result<u64, ArithmeticError> {
+    if stake < 0 {
+        return Err(ArithmeticError::NegativeStake(stake));
+    }
+    u64::try_from(stake).map_err(|_| ArithmeticError::TokenAmountOverflow)
 }
 
 /// Update the validator's voting power and the total voting power.
@@ -1797,7 +3430,7 @@ fn update_voting_powers<TokenChange>(
     validator_voting_power: &mut ValidatorVotingPowers,
     total_voting_power: &mut TotalVotingPowers,
     current_epoch: Epoch,
-) -> Result<(), TryFromIntError>
+) -> Result<(), ArithmeticError>
 where
     TokenChange: Display
         + Debug
@@ -1819,17 +3452,24 @@ where
     );
     for epoch in epochs {
         // Recalculate validator's voting power from validator's total deltas
+        let total_deltas_at_pipeline: i128 =
+            validator_total_deltas.get(epoch).unwrap_or_default().into();
         let total_deltas_at_pipeline =
-            validator_total_deltas.get(epoch).unwrap_or_default();
-        let total_deltas_at_pipeline: i128 = total_deltas_at_pipeline.into();
-        let total_deltas_at_pipeline: u64 =
-            TryFrom::try_from(total_deltas_at_pipeline).unwrap();
+            stake_to_tokens(total_deltas_at_pipeline)?;
         let voting_power_at_pipeline =
             validator_voting_power.get(epoch).unwrap_or_default();
         let voting_power_delta = VotingPowerDelta::try_from_tokens(
             total_deltas_at_pipeline,
             params,
-        )? - voting_power_at_pipeline;
+        )?
+        .checked_sub(voting_power_at_pipeline)
+        .ok_or(ArithmeticError::VotingPowerOverflow)?;
+        // The total voting power must not overflow
+        total_voting_power
+            .get(epoch)
+            .unwrap_or_default()
+            .checked_add(voting_power_delta)
+            .ok_or(ArithmeticError::VotingPowerOverflow)?;
 
         validator_voting_power.add_at_epoch(
             voting_power_delta,
@@ -1861,16 +3501,23 @@ where
         + BorshSerialize,
 {
     pub unbond: Unbonds<TokenAmount>,
+    /// The withdrawn unbond deltas before slashing
+    pub withdrawn_deltas: HashMap<(Epoch, Epoch), TokenAmount>,
     pub withdrawn: TokenAmount,
     pub slashed: TokenAmount,
 }
 
-/// Withdraw tokens from unbonds of self-bonds or delegations.
+/// Withdraw tokens from unbonds of self-bonds or delegations. The tokens
+/// redelegated to the validator are withdrawn first from the unbonds of the
+/// bonds that started at the epoch of their redelegation and the slashes of
+/// their source validator are applied to them.
 fn withdraw_unbonds<Address, TokenAmount>(
     params: &PosParams,
     bond_id: &BondId<Address>,
     unbond: Option<Unbonds<TokenAmount>>,
     slashes: Vec<Slash>,
+    redelegations: Option<&mut Redelegations<Address, TokenAmount>>,
+    redelegations_slashes: &HashMap<Address, Slashes>,
     current_epoch: Epoch,
 ) -> Result<WithdrawData<TokenAmount>, WithdrawError<Address>>
 where
@@ -1904,27 +3551,899 @@ where
         .get(current_epoch)
         .ok_or_else(|| WithdrawError::NoWithdrawableUnbond(bond_id.clone()))?;
     let mut slashed = TokenAmount::default();
-    let withdrawn_amount = withdrawable_unbond.deltas.iter().fold(
-        TokenAmount::default(),
-        |sum, ((epoch_start, epoch_end), delta)| {
-            let mut delta = *delta;
-            // Check and apply slashes, if any
-            for slash in &slashes {
-                if slash.epoch >= *epoch_start && slash.epoch <= *epoch_end {
-                    let raw_delta: u64 = delta.into();
-                    let current_slashed =
-                        TokenAmount::from(slash.rate * raw_delta);
-                    slashed += current_slashed;
-                    delta -= current_slashed;
-                }
+    let mut withdrawn_amount = TokenAmount::default();
+    for ((epoch_start, epoch_end), delta) in &withdrawable_unbond.deltas {
+        let mut delta = *delta;
+        // Check and apply slashes, if any
+        for slash in &slashes {
+            if slash.epoch >= *epoch_start && slash.epoch <= *epoch_end {
+                let raw_delta: u64 = delta.into();
+                let current_slashed =
+                    TokenAmount::from(slash.rate.portion_of(raw_delta)?);
+                slashed += current_slashed;
+                delta -= current_slashed;
             }
-            sum + delta
-        },
-    );
+        }
+        withdrawn_amount += delta;
+    }
+    if let Some(redelegations) = redelegations {
+        let mut withdrawn_by_start: BTreeMap<Epoch, TokenAmount> =
+            BTreeMap::default();
+        for ((epoch_start, _epoch_end), delta) in &withdrawable_unbond.deltas {
+            *withdrawn_by_start.entry(*epoch_start).or_default() += *delta;
+        }
+        for ((redelegation_epoch, src_validator, bond_start), redelegated) in
+            redelegations.deltas.iter_mut()
+        {
+            let withdrawn = match withdrawn_by_start.get_mut(redelegation_epoch)
+            {
+                Some(withdrawn) => withdrawn,
+                None => continue,
+            };
+            let delta = if *withdrawn < *redelegated {
+                *withdrawn
+            } else {
+                *redelegated
+            };
+            *withdrawn -= delta;
+            *redelegated -= delta;
+            // Check and apply the source validator's slashes, if any
+            let src_slashes: &[Slash] = redelegations_slashes
+                .get(src_validator)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let mut current_slashed = delta;
+            current_slashed -= Redelegations::<Address, _>::slash_delta(
+                *redelegation_epoch,
+                *bond_start,
+                delta,
+                src_slashes,
+            )?;
+            slashed += current_slashed;
+            withdrawn_amount -= current_slashed;
+        }
+        redelegations
+            .deltas
+            .retain(|_key, delta| *delta != TokenAmount::default());
+    }
+    let withdrawn_deltas = withdrawable_unbond.deltas.clone();
     unbond.delete_current(current_epoch, params);
     Ok(WithdrawData {
         unbond,
+        withdrawn_deltas,
         withdrawn: withdrawn_amount,
         slashed,
     })
 }
+
+/// Record the unbond deltas withdrawn from a bond in its `withdrawals`, so
+/// that they can still be slashed for the faults committed while they were
+/// bonded, and forget the withdrawn deltas that can no longer be slashed.
+/// Returns the part of the `withdrawn` tokens that repays the clawback owed
+/// to the slash pool.
+fn record_withdrawal<TokenAmount>(
+    params: &PosParams,
+    withdrawals: &mut Withdrawals<TokenAmount>,
+    withdrawn_deltas: HashMap<(Epoch, Epoch), TokenAmount>,
+    withdrawn: TokenAmount,
+    current_epoch: Epoch,
+) -> TokenAmount
+where
+    TokenAmount: Debug
+        + Default
+        + Copy
+        + PartialOrd
+        + AddAssign
+        + SubAssign
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    for (key, delta) in withdrawn_deltas {
+        *withdrawals.deltas.entry(key).or_default() += delta;
+    }
+    withdrawals.deltas.retain(|(_epoch_start, epoch_end), _delta| {
+        Withdrawals::<TokenAmount>::is_slashable(
+            params,
+            *epoch_end,
+            current_epoch,
+        )
+    });
+    let repaid = if withdrawals.clawback < withdrawn {
+        withdrawals.clawback
+    } else {
+        withdrawn
+    };
+    withdrawals.clawback -= repaid;
+    repaid
+}
+
+/// Find the stake of a bond in the given epoch, reduced by the slashes
+/// applicable to it.
+fn slashed_bond_stake<TokenAmount>(
+    bonds: &Bonds<TokenAmount>,
+    slashes: &[Slash],
+    epoch: Epoch,
+) -> Result<TokenAmount, ArithmeticError>
+where
+    TokenAmount: Debug
+        + Default
+        + Clone
+        + Copy
+        + Add<Output = TokenAmount>
+        + AddAssign
+        + Into<u64>
+        + From<u64>
+        + SubAssign
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    let bond = match bonds.get(epoch) {
+        Some(bond) => bond,
+        None => return Ok(TokenAmount::default()),
+    };
+    let mut stake = TokenAmount::default();
+    for (epoch_start, delta) in &bond.deltas {
+        if *epoch_start > epoch {
+            continue;
+        }
+        // Each slash is calculated from the delta reduced by the previous
+        // slash
+        let mut delta = *delta;
+        for slash in slashes {
+            if slash.epoch >= *epoch_start {
+                let raw_delta: u64 = delta.into();
+                delta -= TokenAmount::from(slash.rate.portion_of(raw_delta)?);
+            }
+        }
+        stake += delta;
+    }
+    Ok(stake)
+}
+
+/// Split a validator's reward, less its commission, between its bonds pro rata
+/// by their stake. The commission and the remainder left over from rounding, or
+/// the whole reward when the validator has no stake, belong to the validator's
+/// self-bond.
+fn bonds_rewards<Address, TokenAmount>(
+    validator: &Address,
+    stakes: &[(BondId<Address>, TokenAmount)],
+    reward: TokenAmount,
+    commission_rate: BasisPoints,
+) -> Vec<(BondId<Address>, TokenAmount)>
+where
+    Address: Display + Debug + Clone + PartialEq + Eq + PartialOrd + Ord + Hash,
+    TokenAmount: Copy + Into<u64> + From<u64>,
+{
+    let raw_reward: u64 = reward.into();
+    // The commission rate cannot be set above 100%, but if it was, the
+    // commission would be the whole reward
+    let commission = commission_rate
+        .portion_of(raw_reward)
+        .unwrap_or(raw_reward);
+    let shared_reward = raw_reward - commission;
+    let total_stake: u128 = stakes
+        .iter()
+        .map(|(_bond_id, stake)| {
+            let raw_stake: u64 = (*stake).into();
+            raw_stake as u128
+        })
+        .sum();
+    let mut remainder = raw_reward;
+    let mut rewards: Vec<(BondId<Address>, u64)> = Vec::new();
+    if total_stake != 0 {
+        for (bond_id, stake) in stakes {
+            let raw_stake: u64 = (*stake).into();
+            // The share is at most `shared_reward`, so it fits into `u64`
+            let share = (shared_reward as u128 * raw_stake as u128
+                / total_stake) as u64;
+            remainder -= share;
+            rewards.push((bond_id.clone(), share));
+        }
+    }
+    if remainder != 0 {
+        let self_bond = rewards
+            .iter_mut()
+            .find(|(bond_id, _)| &bond_id.source == validator);
+        match self_bond {
+            Some((_, share)) => *share += remainder,
+            None => rewards.push((
+                BondId {
+                    source: validator.clone(),
+                    validator: validator.clone(),
+                },
+                remainder,
+            )),
+        }
+    }
+    rewards
+        .into_iter()
+        .map(|(bond_id, share)| (bond_id, TokenAmount::from(share)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::BasisPoints;
+
+    #[test]
+    fn test_bonds_rewards_pro_rata() {
+        let validator = 1_u64;
+        let delegator = 2_u64;
+        let self_bond = BondId {
+            source: validator,
+            validator,
+        };
+        let delegation = BondId {
+            source: delegator,
+            validator,
+        };
+        let stakes =
+            vec![(self_bond.clone(), 300_u64), (delegation.clone(), 100)];
+        let rewards =
+            bonds_rewards(&validator, &stakes, 1000_u64, BasisPoints::new(0));
+        assert_eq!(
+            rewards,
+            vec![(self_bond.clone(), 750), (delegation.clone(), 250)]
+        );
+
+        // The commission is taken before the reward is split
+        let rewards = bonds_rewards(
+            &validator,
+            &stakes,
+            1000_u64,
+            BasisPoints::new(2000),
+        );
+        assert_eq!(rewards, vec![(self_bond, 800), (delegation, 200)]);
+    }
+
+    #[test]
+    fn test_bonds_rewards_remainder() {
+        let validator = 1_u64;
+        let delegation = BondId {
+            source: 2_u64,
+            validator,
+        };
+        let self_bond = BondId {
+            source: validator,
+            validator,
+        };
+        let stakes = vec![(delegation.clone(), 1_u64), (self_bond.clone(), 2)];
+        // The rounding remainder is added to the self-bond
+        let rewards =
+            bonds_rewards(&validator, &stakes, 10_u64, BasisPoints::new(0));
+        assert_eq!(rewards, vec![(delegation, 3), (self_bond.clone(), 7)]);
+
+        // Without any stake, the whole reward belongs to the self-bond
+        let rewards =
+            bonds_rewards(&validator, &[], 10_u64, BasisPoints::new(0));
+        assert_eq!(rewards, vec![(self_bond, 10)]);
+    }
+
+    #[test]
+    fn test_change_commission_rate() {
+        let params = PosParams::default();
+        let mut rates: ValidatorCommissionRates =
+            Epoched::init_at_genesis(BasisPoints::new(500), Epoch::from(0));
+
+        // The change is limited by the maximum change per epoch
+        let max_rate = BasisPoints::new(600);
+        let res: Result<(), CommissionRateChangeError<u64>> =
+            change_commission_rate(
+                &params,
+                &mut rates,
+                BasisPoints::new(601),
+                Epoch::from(0),
+            );
+        assert!(res.is_err());
+        let res: Result<(), CommissionRateChangeError<u64>> =
+            change_commission_rate(
+                &params,
+                &mut rates,
+                max_rate,
+                Epoch::from(0),
+            );
+        assert!(res.is_ok());
+
+        // The new rate takes effect at the pipeline offset
+        let pipeline_epoch = Epoch::from(params.pipeline_len);
+        assert_eq!(
+            rates.get(pipeline_epoch - 1_u64),
+            Some(&BasisPoints::new(500))
+        );
+        assert_eq!(rates.get(pipeline_epoch), Some(&max_rate));
+
+        // The rate cannot be greater than 100%
+        let res: Result<(), CommissionRateChangeError<u64>> =
+            change_commission_rate(
+                &params,
+                &mut rates,
+                BasisPoints::new(10_001),
+                Epoch::from(0),
+            );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_change_consensus_key() {
+        let params = PosParams::default();
+        let validator = 1_u64;
+        let mut keys: ValidatorConsensusKeys<u64> =
+            Epoched::init_at_genesis(10_u64, Epoch::from(0));
+
+        // The key must be different from the current key
+        let res = change_consensus_key(
+            &params,
+            &mut keys,
+            &validator,
+            &10,
+            Epoch::from(0),
+        );
+        assert!(res.is_err());
+        let res = change_consensus_key(
+            &params,
+            &mut keys,
+            &validator,
+            &11,
+            Epoch::from(0),
+        );
+        assert!(res.is_ok());
+
+        // The new key takes effect at the pipeline offset
+        let pipeline_epoch = Epoch::from(params.pipeline_len);
+        assert_eq!(keys.get(pipeline_epoch - 1_u64), Some(&10));
+        assert_eq!(keys.get(pipeline_epoch), Some(&11));
+
+        // The key can be changed again before the pending change takes
+        // effect, but not to the pending key
+        let res = change_consensus_key(
+            &params,
+            &mut keys,
+            &validator,
+            &11,
+            Epoch::from(1),
+        );
+        assert!(res.is_err());
+        let res = change_consensus_key(
+            &params,
+            &mut keys,
+            &validator,
+            &10,
+            Epoch::from(1),
+        );
+        assert!(res.is_ok());
+        assert_eq!(keys.get(pipeline_epoch), Some(&11));
+        assert_eq!(keys.get(pipeline_epoch + 1_u64), Some(&10));
+    }
+
+    #[test]
+    fn test_validate_consensus_key_change() {
+        use crate::validation::{
+            validate, Data, DataUpdate, Error, ValidatorUpdate,
+        };
+
+        let params = PosParams::default();
+        let current_epoch = Epoch::from(0);
+        let pipeline_epoch = current_epoch + params.pipeline_len;
+        let validator = 1_u64;
+        let keys_pre: ValidatorConsensusKeys<u64> =
+            Epoched::init_at_genesis(10_u64, current_epoch);
+        let mut keys_post = keys_pre.clone();
+        change_consensus_key(
+            &params,
+            &mut keys_post,
+            &validator,
+            &11,
+            current_epoch,
+        )
+        .unwrap();
+        let key_change = || DataUpdate::<u64, u64, i128, u64>::Validator {
+            address: validator,
+            update: ValidatorUpdate::ConsensusKey(Data {
+                pre: Some(keys_pre.clone()),
+                post: Some(keys_post.clone()),
+            }),
+        };
+        let replaced_owner = || DataUpdate::ValidatorConsensusKeyOwner {
+            raw_hash: "10".to_string(),
+            data: Data {
+                pre: Some(ConsensusKeyOwner {
+                    validator,
+                    start: current_epoch,
+                    end: None,
+                }),
+                post: Some(ConsensusKeyOwner {
+                    validator,
+                    start: current_epoch,
+                    end: Some(pipeline_epoch),
+                }),
+            },
+            pipeline_key_raw_hash: Some("11".to_string()),
+        };
+        let new_owner = |validator| DataUpdate::ValidatorConsensusKeyOwner {
+            raw_hash: "11".to_string(),
+            data: Data {
+                pre: None,
+                post: Some(ConsensusKeyOwner {
+                    validator,
+                    start: pipeline_epoch,
+                    end: None,
+                }),
+            },
+            pipeline_key_raw_hash: Some("11".to_string()),
+        };
+
+        // The changed key must be updated in the index of consensus keys
+        let errors: Vec<Error<u64, i128>> =
+            validate(&params, vec![key_change()], current_epoch);
+        assert!(matches!(
+            errors[..],
+            [Error::MissingConsensusKeyOwnerUpdate(1)]
+        ));
+        let errors: Vec<Error<u64, i128>> = validate(
+            &params,
+            vec![key_change(), new_owner(validator)],
+            current_epoch,
+        );
+        assert!(matches!(
+            errors[..],
+            [Error::MissingConsensusKeyOwnerUpdate(1)]
+        ));
+        let errors: Vec<Error<u64, i128>> = validate(
+            &params,
+            vec![key_change(), replaced_owner(), new_owner(validator)],
+            current_epoch,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        // The new key must be owned by the validator who changed it
+        let errors: Vec<Error<u64, i128>> = validate(
+            &params,
+            vec![key_change(), replaced_owner(), new_owner(2)],
+            current_epoch,
+        );
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn test_jail_and_unjail_validator() {
+        let params = PosParams {
+            max_validator_slots: 1,
+            ..PosParams::default()
+        };
+        let jailed = WeightedValidator {
+            voting_power: VotingPower::from(10),
+            address: 1_u64,
+        };
+        let other = WeightedValidator {
+            voting_power: VotingPower::from(5),
+            address: 2_u64,
+        };
+        let mut validator_set: ValidatorSets<u64> = Epoched::init_at_genesis(
+            ValidatorSet {
+                active: [jailed.clone()].iter().cloned().collect(),
+                inactive: [other.clone()].iter().cloned().collect(),
+            },
+            Epoch::from(0),
+        );
+        let mut state: ValidatorStates =
+            Epoched::init_at_genesis(ValidatorState::Candidate, Epoch::from(0));
+        let voting_power: ValidatorVotingPowers = EpochedDelta::init_at_genesis(
+            VotingPowerDelta::from(10),
+            Epoch::from(0),
+        );
+
+        // The jailed validator is removed from the validator set at the
+        // pipeline offset and the inactive validator takes its slot
+        jail_validator(
+            &params,
+            &jailed.address,
+            &mut state,
+            &mut validator_set,
+            Epoch::from(0),
+        );
+        let pipeline_epoch = Epoch::from(params.pipeline_len);
+        let set = validator_set.get(pipeline_epoch - 1_u64).unwrap();
+        assert!(set.active.contains(&jailed));
+        let set = validator_set.get(pipeline_epoch).unwrap();
+        assert!(set.active.contains(&other));
+        assert!(!set.active.contains(&jailed) && set.inactive.is_empty());
+        assert_eq!(state.get(pipeline_epoch), Some(&ValidatorState::Jailed));
+
+        // The validator cannot be unjailed before the minimum jail duration
+        let unjail_epoch = Epoch::from(params.min_jail_duration);
+        let res: Result<(), UnjailError<u64>> = unjail_validator(
+            &params,
+            &jailed.address,
+            &mut state,
+            Epoch::from(0),
+            &voting_power,
+            &mut validator_set,
+            unjail_epoch - 1_u64,
+        );
+        assert!(res.is_err());
+
+        // Once unjailed, the validator is added back to the validator set
+        let res: Result<(), UnjailError<u64>> = unjail_validator(
+            &params,
+            &jailed.address,
+            &mut state,
+            Epoch::from(0),
+            &voting_power,
+            &mut validator_set,
+            unjail_epoch,
+        );
+        assert!(res.is_ok());
+        let pipeline_epoch = unjail_epoch + params.pipeline_len;
+        let set = validator_set.get(pipeline_epoch).unwrap();
+        assert!(set.active.contains(&jailed));
+        assert!(set.inactive.contains(&other));
+        assert_eq!(state.get(pipeline_epoch), Some(&ValidatorState::Candidate));
+    }
+
+    #[test]
+    fn test_slashed_bond_stake() {
+        let params = PosParams::default();
+        let mut deltas = HashMap::default();
+        deltas.insert(Epoch::from(0), 1000_u64);
+        let mut bonds: Bonds<u64> =
+            EpochedDelta::init_at_genesis(Bond { deltas }, Epoch::from(0));
+        let mut deltas = HashMap::default();
+        deltas.insert(Epoch::from(2), 1000_u64);
+        bonds.add_at_offset(
+            Bond { deltas },
+            Epoch::from(0),
+            DynEpochOffset::PipelineLen,
+            &params,
+        );
+        assert_eq!(slashed_bond_stake(&bonds, &[], Epoch::from(1)), Ok(1000));
+        assert_eq!(slashed_bond_stake(&bonds, &[], Epoch::from(2)), Ok(2000));
+
+        // A slash only applies to the bonds that were active at its epoch
+        let slash = Slash {
+            epoch: Epoch::from(1),
+            block_height: 10,
+            r#type: SlashType::DuplicateVote,
+            rate: BasisPoints::new(1000),
+        };
+        assert_eq!(
+            slashed_bond_stake(&bonds, &[slash], Epoch::from(2)),
+            Ok(1900)
+        );
+    }
+
+    #[test]
+    fn test_withdraw_redelegated_tokens() {
+        let params = PosParams::default();
+        let src_validator = 1_u64;
+        let dest_validator = 2_u64;
+        let bond_id = BondId {
+            source: 3_u64,
+            validator: dest_validator,
+        };
+        let redelegation_epoch = Epoch::from(5);
+        let mut redelegations = Redelegations {
+            deltas: BTreeMap::default(),
+        };
+        redelegations.deltas.insert(
+            (redelegation_epoch, src_validator, Epoch::from(0)),
+            1000_u64,
+        );
+        let slash = Slash {
+            epoch: Epoch::from(3),
+            block_height: 10,
+            r#type: SlashType::DuplicateVote,
+            rate: BasisPoints::new(1000),
+        };
+        let mut redelegations_slashes = HashMap::default();
+        redelegations_slashes.insert(src_validator, vec![slash]);
+
+        // The redelegations slashed for their source validator's faults are
+        // not forgotten, even once they can no longer be evidenced
+        let end_epoch = redelegation_epoch + params.unbonding_len;
+        forget_redelegations(
+            &params,
+            &mut redelegations,
+            &redelegations_slashes,
+            end_epoch,
+        );
+        assert_eq!(redelegations.deltas.len(), 1);
+        let mut unslashed = redelegations.clone();
+        forget_redelegations(
+            &params,
+            &mut unslashed,
+            &HashMap::default(),
+            end_epoch - 1_u64,
+        );
+        assert_eq!(unslashed.deltas.len(), 1);
+        forget_redelegations(
+            &params,
+            &mut unslashed,
+            &HashMap::default(),
+            end_epoch,
+        );
+        assert!(unslashed.deltas.is_empty());
+
+        // The source validator's slash is applied to the redelegated tokens on
+        // withdrawal
+        let mut deltas = HashMap::default();
+        deltas.insert((redelegation_epoch, end_epoch - 1_u64), 1500_u64);
+        let unbond: Unbonds<u64> =
+            EpochedDelta::init_at_genesis(Unbond { deltas }, Epoch::from(0));
+        let res: Result<WithdrawData<u64>, WithdrawError<u64>> =
+            withdraw_unbonds(
+                &params,
+                &bond_id,
+                Some(unbond),
+                vec![],
+                Some(&mut redelegations),
+                &redelegations_slashes,
+                end_epoch,
+            );
+        let WithdrawData {
+            withdrawn, slashed, ..
+        } = res.unwrap();
+        assert_eq!(withdrawn, 1400);
+        assert_eq!(slashed, 100);
+        assert!(redelegations.deltas.is_empty());
+    }
+
+    #[test]
+    fn test_validate_withdrawal() {
+        use crate::validation::{validate, Data, DataUpdate, Error};
+
+        let params = PosParams::default();
+        let current_epoch = Epoch::from(0);
+        let bond_id = BondId {
+            source: 2_u64,
+            validator: 1_u64,
+        };
+        let key = (Epoch::from(0), Epoch::from(1));
+        let mut deltas = HashMap::default();
+        deltas.insert(key, 1000_u64);
+        let unbond: Unbonds<u64> =
+            EpochedDelta::init_at_genesis(Unbond { deltas }, current_epoch);
+        let withdrawn_unbond = || DataUpdate::<u64, u64, i128, u64>::Unbond {
+            id: bond_id.clone(),
+            data: Data {
+                pre: Some(unbond.clone()),
+                post: None,
+            },
+            slashes: vec![],
+        };
+        let balance = || DataUpdate::Balance(Data {
+            pre: Some(1000),
+            post: Some(0),
+        });
+        let withdrawal = |post_clawback| {
+            let mut deltas = HashMap::default();
+            deltas.insert(key, 1000_u64);
+            DataUpdate::Withdrawal {
+                id: bond_id.clone(),
+                data: Data {
+                    pre: Some(Withdrawals {
+                        deltas: HashMap::default(),
+                        clawback: 200,
+                    }),
+                    post: Some(Withdrawals {
+                        deltas,
+                        clawback: post_clawback,
+                    }),
+                },
+            }
+        };
+        let slash_pool_balance = |post| {
+            DataUpdate::SlashPoolBalance(Data {
+                pre: Some(0),
+                post: Some(post),
+            })
+        };
+
+        // The withdrawn tokens are recorded and the clawback is repaid to the
+        // slash pool
+        let errors: Vec<Error<u64, i128>> = validate(
+            &params,
+            vec![
+                withdrawn_unbond(),
+                balance(),
+                withdrawal(0),
+                slash_pool_balance(200),
+            ],
+            current_epoch,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+
+        // The withdrawn tokens that may still be slashed must be recorded
+        let errors: Vec<Error<u64, i128>> = validate(
+            &params,
+            vec![withdrawn_unbond(), balance()],
+            current_epoch,
+        );
+        assert!(matches!(errors[..], [Error::UnrecordedWithdrawal(_)]));
+
+        // The slash pool may only receive the repaid clawback
+        let errors: Vec<Error<u64, i128>> = validate(
+            &params,
+            vec![
+                withdrawn_unbond(),
+                balance(),
+                withdrawal(0),
+                slash_pool_balance(300),
+            ],
+            current_epoch,
+        );
+        assert!(matches!(
+            errors[..],
+            [Error::InvalidSlashPoolBalance { .. }]
+        ));
+
+        // A transaction cannot add to the clawback
+        let errors: Vec<Error<u64, i128>> = validate(
+            &params,
+            vec![withdrawn_unbond(), balance(), withdrawal(300)],
+            current_epoch,
+        );
+        assert!(matches!(errors[..], [Error::InvalidWithdrawalsUpdate(_)]));
+    }
+
+    #[test]
+    fn test_validator_liveness() {
+        let window_len = 4;
+        let max_missed_blocks_rate = BasisPoints::new(5000);
+        let mut liveness = ValidatorLiveness::default();
+
+        // A validator is not down before the whole window is recorded
+        for _ in 0..3 {
+            liveness.record(false, window_len);
+            assert!(!liveness.is_down(max_missed_blocks_rate));
+        }
+        liveness.record(true, window_len);
+        assert_eq!(liveness.missed_count, 3);
+        assert!(liveness.is_down(max_missed_blocks_rate));
+
+        // The oldest blocks fall out of the window
+        liveness.record(true, window_len);
+        liveness.record(true, window_len);
+        assert_eq!(liveness.missed_count, 1);
+        assert!(!liveness.is_down(max_missed_blocks_rate));
+
+        // Missing exactly the maximum rate is still fine
+        liveness.record(false, window_len);
+        liveness.record(false, window_len);
+        assert_eq!(liveness.missed_count, 2);
+        assert!(!liveness.is_down(max_missed_blocks_rate));
+
+        // The tracking restarts when the window length changes
+        liveness.record(false, 2);
+        assert_eq!(liveness.blocks, 1);
+        assert_eq!(liveness.missed_count, 1);
+        assert!(!liveness.is_down(max_missed_blocks_rate));
+    }
+
+    #[test]
+    fn test_slashed_amounts() {
+        let params = PosParams::default();
+        let validator = 1_u64;
+        let bond_id = BondId {
+            source: 2_u64,
+            validator,
+        };
+        let previous_slash = Slash {
+            epoch: Epoch::from(1),
+            block_height: 10,
+            r#type: SlashType::DuplicateVote,
+            rate: BasisPoints::new(1000),
+        };
+        let slash = Slash {
+            epoch: Epoch::from(4),
+            block_height: 20,
+            r#type: SlashType::DuplicateVote,
+            rate: BasisPoints::new(5000),
+        };
+        let current_epoch = Epoch::from(6);
+
+        // A bond started before the previous slash, a bond started between
+        // the slashes and a bond started after the slash
+        let mut deltas = HashMap::default();
+        deltas.insert(Epoch::from(0), 1000_u64);
+        deltas.insert(Epoch::from(3), 300);
+        deltas.insert(Epoch::from(5), 500);
+        let bonds: Bonds<u64> =
+            EpochedDelta::init_at_genesis(Bond { deltas }, Epoch::from(0));
+        // An unbond that ended before the slash, an unbond that covers both
+        // slashes and an unbond that started after the slash
+        let mut deltas = HashMap::default();
+        deltas.insert((Epoch::from(0), Epoch::from(3)), 200_u64);
+        deltas.insert((Epoch::from(0), Epoch::from(7)), 400);
+        deltas.insert((Epoch::from(5), Epoch::from(9)), 600);
+        let unbonds: Unbonds<u64> =
+            EpochedDelta::init_at_genesis(Unbond { deltas }, Epoch::from(0));
+        // A withdrawal that ended before the slash and a withdrawal that
+        // covers both slashes
+        let mut deltas = HashMap::default();
+        deltas.insert((Epoch::from(0), Epoch::from(2)), 100_u64);
+        deltas.insert((Epoch::from(0), Epoch::from(5)), 800);
+        let withdrawals = Withdrawals {
+            deltas,
+            clawback: 0,
+        };
+
+        let SlashedAmounts {
+            bonded,
+            unbonded,
+            withdrawn,
+        } = slashed_amounts(
+            &params,
+            &slash,
+            &[previous_slash],
+            &[(bond_id.clone(), bonds)],
+            &[(bond_id.clone(), unbonds)],
+            &[(bond_id.clone(), withdrawals)],
+            current_epoch,
+        )
+        .unwrap();
+        // (1000 - 100) / 2 + 300 / 2
+        assert_eq!(bonded, 600);
+        // (400 - 40) / 2
+        assert_eq!(unbonded, 180);
+        // (800 - 80) / 2
+        assert_eq!(withdrawn, vec![(bond_id, 360)]);
+    }
+
+    #[test]
+    fn test_record_withdrawal() {
+        let params = PosParams {
+            pipeline_len: 2,
+            unbonding_len: 6,
+            ..PosParams::default()
+        };
+        let mut withdrawals = Withdrawals::<u64>::default();
+
+        // The withdrawn deltas are recorded while they're slashable
+        let mut deltas = HashMap::default();
+        deltas.insert((Epoch::from(0), Epoch::from(3)), 500_u64);
+        let repaid = record_withdrawal(
+            &params,
+            &mut withdrawals,
+            deltas,
+            500,
+            Epoch::from(8),
+        );
+        assert_eq!(repaid, 0);
+        assert_eq!(withdrawals.deltas.len(), 1);
+
+        // The clawback is repaid from the next withdrawal, up to the
+        // withdrawn amount
+        withdrawals.clawback = 300;
+        let mut deltas = HashMap::default();
+        deltas.insert((Epoch::from(2), Epoch::from(5)), 200_u64);
+        let repaid = record_withdrawal(
+            &params,
+            &mut withdrawals,
+            deltas,
+            200,
+            Epoch::from(9),
+        );
+        assert_eq!(repaid, 200);
+        assert_eq!(withdrawals.clawback, 100);
+        assert_eq!(withdrawals.deltas.len(), 2);
+
+        // The deltas that can no longer be slashed are forgotten, but not the
+        // clawback
+        let repaid = record_withdrawal(
+            &params,
+            &mut withdrawals,
+            HashMap::default(),
+            0,
+            Epoch::from(10),
+        );
+        assert_eq!(repaid, 0);
+        assert_eq!(withdrawals.clawback, 100);
+        assert_eq!(withdrawals.deltas.len(), 1);
+        assert!(
+            withdrawals
+                .deltas
+                .contains_key(&(Epoch::from(2), Epoch::from(5)))
+        );
+    }
+}
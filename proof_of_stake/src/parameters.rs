@@ -20,10 +20,13 @@ pub struct PosParams {
     /// Used in validators' voting power calculation. Given in basis points
     /// (voting power per ten thousand tokens).
     pub votes_per_token: BasisPoints,
-    /// Amount of tokens rewarded to a validator for proposing a block
+    /// Weight of the reward of a validator for proposing a block. The rewards
+    /// accumulated in an epoch are scaled so that they sum up to the PoS share
+    /// of the inflation minted in the next epoch, so only their ratio to
+    /// `block_vote_reward` matters.
     pub block_proposer_reward: u64,
-    /// Amount of tokens rewarded to each validator that voted on a block
-    /// proposal
+    /// Weight of the reward of each validator that voted on a block proposal,
+    /// scaled in the same way as `block_proposer_reward`
     pub block_vote_reward: u64,
     /// Portion of validator's stake that should be slashed on a duplicate
     /// vote. Given in basis points (slashed amount per ten thousand tokens).
@@ -31,6 +34,22 @@ pub struct PosParams {
     /// Portion of validator's stake that should be slashed on a light client
     /// attack. Given in basis points (slashed amount per ten thousand tokens).
     pub light_client_attack_slash_rate: BasisPoints,
+    /// The maximum change of a validator's commission rate from one epoch to
+    /// the next. Given in basis points.
+    pub max_commission_rate_change: BasisPoints,
+    /// Portion of validator's stake that should be slashed for downtime.
+    /// Given in basis points (slashed amount per ten thousand tokens).
+    pub downtime_slash_rate: BasisPoints,
+    /// The number of the most recent blocks in which validators' liveness is
+    /// tracked.
+    pub signed_blocks_window: u64,
+    /// The maximum portion of the blocks in the signing window that a
+    /// validator may miss signing before it gets slashed for downtime and
+    /// jailed. Given in basis points.
+    pub max_missed_blocks_rate: BasisPoints,
+    /// The minimum number of epochs that a validator must stay jailed for
+    /// before it can be unjailed.
+    pub min_jail_duration: u64,
 }
 
 impl Default for PosParams {
@@ -47,6 +66,14 @@ impl Default for PosParams {
             duplicate_vote_slash_rate: BasisPoints::new(500),
             // slash 5%
             light_client_attack_slash_rate: BasisPoints::new(500),
+            // 1% per epoch
+            max_commission_rate_change: BasisPoints::new(100),
+            // slash 0.01%
+            downtime_slash_rate: BasisPoints::new(1),
+            signed_blocks_window: 1000,
+            // 50% of the signing window
+            max_missed_blocks_rate: BasisPoints::new(5000),
+            min_jail_duration: 2,
         }
     }
 }
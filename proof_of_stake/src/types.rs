@@ -1,14 +1,15 @@
 //! Proof of Stake data types
 
 use core::fmt::Debug;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::convert::TryFrom;
 use std::fmt::Display;
 use std::hash::Hash;
 use std::num::TryFromIntError;
-use std::ops::{Add, AddAssign, Mul, Sub};
+use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 use borsh::{BorshDeserialize, BorshSerialize};
+use thiserror::Error;
 
 use crate::epoched::{
     Epoched, EpochedDelta, OffsetPipelineLen, OffsetUnboundingLen,
@@ -23,6 +24,8 @@ pub type ValidatorStates = Epoched<ValidatorState, OffsetPipelineLen>;
 /// Epoched validator's total deltas.
 pub type ValidatorTotalDeltas<TokenChange> =
     EpochedDelta<TokenChange, OffsetUnboundingLen>;
+/// Epoched validator's commission rate.
+pub type ValidatorCommissionRates = Epoched<BasisPoints, OffsetPipelineLen>;
 /// Epoched validator's voting power.
 pub type ValidatorVotingPowers =
     EpochedDelta<VotingPowerDelta, OffsetUnboundingLen>;
@@ -114,6 +117,9 @@ pub struct GenesisValidator<Address, Token, PK> {
     pub consensus_key: PK,
     /// An public key associated with the staking reward address
     pub staking_reward_key: PK,
+    /// A portion of the block rewards that the validator keeps before the
+    /// rest is split between its bonds
+    pub commission_rate: BasisPoints,
 }
 
 /// An update of the active and inactive validator set.
@@ -252,7 +258,10 @@ pub enum ValidatorState {
     /// A `Candidate` validator may participate in the consensus. It is either
     /// in the active or inactive validator set.
     Candidate,
-    // TODO consider adding `Jailed`
+    /// A `Jailed` validator has been removed from the validator set for a
+    /// fault or for being offline and it may not participate in the consensus
+    /// until it's unjailed.
+    Jailed,
 }
 
 /// A bond is validator's self-bond or a delegation from a regular account to a
@@ -279,6 +288,45 @@ pub struct Unbond<Token: Default> {
     pub deltas: HashMap<(Epoch, Epoch), Token>,
 }
 
+/// The unbonded tokens withdrawn from a self-bond or a delegation that can
+/// still be slashed for the faults committed while they were bonded, and the
+/// amount owed to the slash pool for the slashes applied to them after they
+/// have been withdrawn.
+#[derive(Debug, Clone, Default, BorshDeserialize, BorshSerialize)]
+pub struct Withdrawals<Token: Default> {
+    /// A key is a pair of the start and end epochs of the withdrawn unbond,
+    /// the same as in the unbond deltas. This is needed for slash epoch range
+    /// check.
+    pub deltas: HashMap<(Epoch, Epoch), Token>,
+    /// The slashed tokens that have been withdrawn and have yet to be repaid
+    /// to the slash pool from the bond's next withdrawal.
+    pub clawback: Token,
+}
+
+/// Redelegations of a delegation to a validator from other validators. The
+/// redelegated tokens remain slashable for the faults of their source
+/// validators committed before the tokens were moved.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct Redelegations<Address, Token>
+where
+    Address: Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize,
+    Token: Debug + BorshDeserialize + BorshSerialize,
+{
+    /// A key is a triple of the epoch from which the tokens are bonded to the
+    /// destination validator, the source validator and the epoch of the bond
+    /// at the source validator from which the tokens were redelegated. This
+    /// is needed for slash epoch range check.
+    pub deltas: BTreeMap<(Epoch, Address, Epoch), Token>,
+}
+
 /// A slash applied to validator, to punish byzantine behavior by removing
 /// their staked tokens at and before the epoch of the slash.
 #[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
@@ -304,17 +352,194 @@ pub enum SlashType {
     DuplicateVote,
     /// Light client attack.
     LightClientAttack,
+    /// Missing to sign too many blocks in the signing window.
+    Downtime,
+}
+
+/// Liveness of a validator, tracking which of the blocks in the most recent
+/// signing window the validator has missed signing.
+#[derive(
+    Debug, Clone, Default, BorshDeserialize, BorshSerialize, PartialEq, Eq,
+)]
+pub struct ValidatorLiveness {
+    /// The number of blocks recorded since the tracking has started.
+    pub blocks: u64,
+    /// A ring buffer of the signing window, with `true` for the blocks that
+    /// the validator has missed signing. The block `n` is at the index
+    /// `n % window_len`.
+    pub missed: Vec<bool>,
+    /// The number of the blocks in the signing window that the validator has
+    /// missed signing.
+    pub missed_count: u64,
+}
+
+/// The validator that uses a consensus key, recorded in an index from the key
+/// to look-up validators by the keys with which they sign blocks and commit
+/// faults. A key that has been replaced stays in the index until the evidence
+/// of a fault committed with it may no longer be submitted.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize, PartialEq, Eq)]
+pub struct ConsensusKeyOwner<Address> {
+    /// The address of the validator using the key.
+    pub validator: Address,
+    /// The first epoch in which the key is used.
+    pub start: Epoch,
+    /// The first epoch in which the key is no longer used, if it has been
+    /// replaced.
+    pub end: Option<Epoch>,
+}
+
+/// A validator's profile. Unlike most of the validator's data, the metadata
+/// are not epoched and an update takes effect immediately.
+#[derive(
+    Debug, Clone, Default, BorshDeserialize, BorshSerialize, PartialEq, Eq,
+)]
+pub struct ValidatorMetadata {
+    /// A human-readable name of the validator.
+    pub moniker: Option<String>,
+    /// A description of the validator.
+    pub description: Option<String>,
+    /// The validator's website.
+    pub website: Option<String>,
+    /// The validator's contact, e.g. an e-mail address.
+    pub contact: Option<String>,
+}
+
+/// Block rewards accumulated by validators in an epoch. The rewards are
+/// distributed to the validators and their delegators at the beginning of the
+/// following epoch.
+#[derive(Debug, Clone, BorshDeserialize, BorshSerialize)]
+pub struct RewardsAccumulator<Address, TokenAmount>
+where
+    Address: Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize,
+    TokenAmount: Debug + Default + BorshDeserialize + BorshSerialize,
+{
+    /// The epoch in which the rewards have been accumulated.
+    pub epoch: Epoch,
+    /// The rewards accumulated by each validator in the epoch.
+    pub rewards: BTreeMap<Address, TokenAmount>,
 }
 
-/// ‱ (Parts per ten thousand). This can be multiplied by any type that
-/// implements [`Into<u64>`] or [`Into<i128>`].
-#[derive(Debug, Clone, Copy, BorshDeserialize, BorshSerialize)]
+/// Block rewards of a bond (validator self-bond or a delegation). The rewards
+/// of a self-bond are paid out directly, while delegators' rewards accrue in
+/// the PoS account until they are claimed.
+#[derive(Debug, Clone, Default, BorshDeserialize, BorshSerialize)]
+pub struct BondRewards<TokenAmount: Default> {
+    /// The last epoch for which the rewards have been distributed.
+    pub last_epoch: Epoch,
+    /// The amount distributed for the last epoch.
+    pub last_amount: TokenAmount,
+    /// The total amount distributed for all the epochs.
+    pub total: TokenAmount,
+    /// The amount that has accrued, but has not yet been claimed.
+    pub unclaimed: TokenAmount,
+}
+
+/// ‱ (Parts per ten thousand). This can be multiplied with checked arithmetic
+/// by a token amount in [`u64`] or a token change in [`i128`].
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    BorshDeserialize,
+    BorshSerialize,
+)]
 pub struct BasisPoints(u64);
 
+/// An error in PoS arithmetic.
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArithmeticError {
+    #[error("Voting power overflow")]
+    VotingPowerOverflow,
+    #[error("Token amount overflow")]
+    TokenAmountOverflow,
+    #[error("Unexpected negative stake {0}")]
+    NegativeStake(i128),
+    #[error("Invalid rate {0}, it must not be greater than 100%")]
+    RateTooLarge(BasisPoints),
+}
+
+impl<Address, TokenAmount> RewardsAccumulator<Address, TokenAmount>
+where
+    Address: Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize,
+    TokenAmount:
+        Debug + Default + Copy + AddAssign + BorshDeserialize + BorshSerialize,
+{
+    /// Initialize an empty accumulator for the given epoch.
+    pub fn new(epoch: Epoch) -> Self {
+        Self {
+            epoch,
+            rewards: BTreeMap::default(),
+        }
+    }
+
+    /// Add a reward to the given validator.
+    pub fn add(&mut self, validator: &Address, reward: TokenAmount) {
+        *self.rewards.entry(validator.clone()).or_default() += reward;
+    }
+}
+
+impl<TokenAmount> BondRewards<TokenAmount>
+where
+    TokenAmount: Debug + Default + Copy + AddAssign,
+{
+    /// Record rewards paid out for the given epoch.
+    pub fn record(&mut self, epoch: Epoch, amount: TokenAmount) {
+        self.last_epoch = epoch;
+        self.last_amount = amount;
+        self.total += amount;
+    }
+
+    /// Record rewards for the given epoch that are kept unclaimed until
+    /// [`BondRewards::claim`] is called.
+    pub fn accrue(&mut self, epoch: Epoch, amount: TokenAmount) {
+        self.record(epoch, amount);
+        self.unclaimed += amount;
+    }
+
+    /// Take all the unclaimed rewards.
+    pub fn claim(&mut self) -> TokenAmount {
+        std::mem::take(&mut self.unclaimed)
+    }
+}
+
 impl VotingPower {
-    /// Convert token amount into a voting power.
-    pub fn from_tokens(tokens: impl Into<u64>, params: &PosParams) -> Self {
-        Self(params.votes_per_token * tokens.into() / 1_000_000)
+    /// Try to convert token amount into a voting power.
+    pub fn try_from_tokens(
+        tokens: impl Into<u64>,
+        params: &PosParams,
+    ) -> Result<Self, ArithmeticError> {
+        let votes = params
+            .votes_per_token
+            .checked_mul(tokens.into())
+            .ok_or(ArithmeticError::VotingPowerOverflow)?;
+        Ok(Self(votes / 1_000_000))
+    }
+
+    /// Checked voting power addition. Returns `None` on overflow.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
     }
 }
 
@@ -323,9 +548,13 @@ impl VotingPowerDelta {
     pub fn try_from_token_change(
         change: impl Into<i128>,
         params: &PosParams,
-    ) -> Result<Self, TryFromIntError> {
-        let delta: i128 = params.votes_per_token * change.into() / 1_000_000;
-        let delta: i64 = TryFrom::try_from(delta)?;
+    ) -> Result<Self, ArithmeticError> {
+        let delta = params
+            .votes_per_token
+            .checked_mul_change(change.into())
+            .ok_or(ArithmeticError::VotingPowerOverflow)?;
+        let delta: i64 = TryFrom::try_from(delta / 1_000_000)
+            .map_err(|_| ArithmeticError::VotingPowerOverflow)?;
         Ok(Self(delta))
     }
 
@@ -333,12 +562,22 @@ impl VotingPowerDelta {
     pub fn try_from_tokens(
         tokens: impl Into<u64>,
         params: &PosParams,
-    ) -> Result<Self, TryFromIntError> {
-        let delta: i64 = TryFrom::try_from(
-            params.votes_per_token * tokens.into() / 1_000_000,
-        )?;
+    ) -> Result<Self, ArithmeticError> {
+        let voting_power = VotingPower::try_from_tokens(tokens, params)?;
+        let delta: i64 = TryFrom::try_from(voting_power.0)
+            .map_err(|_| ArithmeticError::VotingPowerOverflow)?;
         Ok(Self(delta))
     }
+
+    /// Checked voting power change addition. Returns `None` on overflow.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
+
+    /// Checked voting power change subtraction. Returns `None` on overflow.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.0.checked_sub(rhs.0).map(Self)
+    }
 }
 
 impl TryFrom<VotingPower> for VotingPowerDelta {
@@ -625,8 +864,11 @@ where
     Token: Copy + Into<u64>,
 {
     /// Calculate validator's voting power
-    pub fn voting_power(&self, params: &PosParams) -> VotingPower {
-        VotingPower::from_tokens(self.tokens, params)
+    pub fn voting_power(
+        &self,
+        params: &PosParams,
+    ) -> Result<VotingPower, ArithmeticError> {
+        VotingPower::try_from_tokens(self.tokens, params)
     }
 }
 
@@ -639,6 +881,7 @@ impl SlashType {
             SlashType::LightClientAttack => {
                 params.light_client_attack_slash_rate
             }
+            SlashType::Downtime => params.downtime_slash_rate,
         }
     }
 }
@@ -648,38 +891,209 @@ impl Display for SlashType {
         match self {
             SlashType::DuplicateVote => write!(f, "Duplicate vote"),
             SlashType::LightClientAttack => write!(f, "Light client attack"),
+            SlashType::Downtime => write!(f, "Downtime"),
+        }
+    }
+}
+
+impl<Token: Default> Withdrawals<Token> {
+    /// Can the withdrawn tokens of an unbond that ended at the `epoch_end`
+    /// still be slashed in the current epoch? The evidence of a fault is
+    /// accepted for at most the unbonding length.
+    pub fn is_slashable(
+        params: &PosParams,
+        epoch_end: Epoch,
+        current_epoch: Epoch,
+    ) -> bool {
+        current_epoch <= epoch_end + params.unbonding_len
+    }
+}
+
+impl<Address, Token> Redelegations<Address, Token>
+where
+    Address: Debug
+        + Clone
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + BorshDeserialize
+        + BorshSerialize,
+    Token: Debug
+        + Copy
+        + Into<u64>
+        + From<u64>
+        + SubAssign
+        + BorshDeserialize
+        + BorshSerialize,
+{
+    /// Find the amount left from a redelegated token amount after applying
+    /// the slashes of its source validator. Only the slashes for the faults
+    /// committed from the start of the bond at the source validator until
+    /// the epoch of the redelegation apply. Each slash is calculated from the
+    /// amount reduced by the previous slash.
+    pub fn slash_delta(
+        redelegation_epoch: Epoch,
+        bond_start: Epoch,
+        amount: Token,
+        slashes: &[Slash],
+    ) -> Result<Token, ArithmeticError> {
+        let mut amount = amount;
+        for slash in slashes {
+            if slash.epoch >= bond_start && slash.epoch < redelegation_epoch {
+                let raw_amount: u64 = amount.into();
+                amount -= Token::from(slash.rate.portion_of(raw_amount)?);
+            }
+        }
+        Ok(amount)
+    }
+}
+
+impl ValidatorMetadata {
+    /// The maximum length of any of the metadata fields in bytes.
+    pub const MAX_FIELD_LEN: usize = 256;
+
+    /// Find the name of the first field that is longer than
+    /// [`ValidatorMetadata::MAX_FIELD_LEN`], if any.
+    pub fn find_too_long_field(&self) -> Option<&'static str> {
+        let fields = [
+            ("moniker", &self.moniker),
+            ("description", &self.description),
+            ("website", &self.website),
+            ("contact", &self.contact),
+        ];
+        fields.iter().find_map(|(name, field)| match field {
+            Some(field) if field.len() > Self::MAX_FIELD_LEN => Some(*name),
+            _ => None,
+        })
+    }
+}
+
+impl<Address> ConsensusKeyOwner<Address> {
+    /// Check if the evidence of faults committed with the key has expired in
+    /// the given epoch, after which the key may be used again.
+    pub fn is_expired(&self, epoch: Epoch, params: &PosParams) -> bool {
+        match self.end {
+            Some(end) => epoch >= end + params.unbonding_len,
+            None => false,
         }
     }
 }
 
+impl ValidatorLiveness {
+    /// Record whether the validator has signed the next block in a signing
+    /// window of the given length. If the window length has changed, the
+    /// tracking is restarted.
+    pub fn record(&mut self, signed: bool, window_len: u64) {
+        if self.missed.len() as u64 != window_len {
+            *self = Self {
+                blocks: 0,
+                missed: vec![false; window_len as usize],
+                missed_count: 0,
+            };
+        }
+        if window_len == 0 {
+            return;
+        }
+        let index = (self.blocks % window_len) as usize;
+        let missed = !signed;
+        match (self.missed[index], missed) {
+            (false, true) => self.missed_count += 1,
+            (true, false) => self.missed_count -= 1,
+            _ => {}
+        }
+        self.missed[index] = missed;
+        self.blocks += 1;
+    }
+
+    /// Check if the validator has missed signing more than the given portion
+    /// of the blocks in the signing window. The validator is only considered
+    /// to be down once the whole window has been recorded.
+    pub fn is_down(&self, max_missed_blocks_rate: BasisPoints) -> bool {
+        let window_len = self.missed.len() as u64;
+        // When the maximum overflows, it's greater than any missed count
+        let max_missed_count = max_missed_blocks_rate.checked_mul(window_len);
+        window_len != 0
+            && self.blocks >= window_len
+            && max_missed_count.map_or(false, |max_missed_count| {
+                self.missed_count > max_missed_count
+            })
+    }
+}
+
 impl BasisPoints {
+    /// The basis points of a whole, i.e. 100%.
+    pub const MAX: Self = Self(10_000);
+
     /// Initialize basis points from an integer.
     pub fn new(value: u64) -> Self {
         Self(value)
     }
-}
 
-impl Display for BasisPoints {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}‱", self.0)
+    /// The absolute difference between two values.
+    pub fn abs_diff(self, other: Self) -> Self {
+        if self.0 > other.0 {
+            Self(self.0 - other.0)
+        } else {
+            Self(other.0 - self.0)
+        }
     }
-}
 
-impl Mul<u64> for BasisPoints {
-    type Output = u64;
+    /// Checked multiplication of a token amount, rounded down. Returns `None`
+    /// on overflow.
+    pub fn checked_mul(self, rhs: u64) -> Option<u64> {
+        // Split the amount to avoid an overflow of the intermediate product
+        let whole = (rhs / 10_000).checked_mul(self.0)?;
+        let rest = (rhs % 10_000).checked_mul(self.0)? / 10_000;
+        whole.checked_add(rest)
+    }
 
-    fn mul(self, rhs: u64) -> Self::Output {
-        // TODO checked arithmetics
-        rhs * self.0 / 10_000
+    /// Checked multiplication of a token change, rounded towards zero.
+    /// Returns `None` on overflow.
+    pub fn checked_mul_change(self, rhs: i128) -> Option<i128> {
+        let bp = self.0 as i128;
+        let whole = (rhs / 10_000).checked_mul(bp)?;
+        let rest = (rhs % 10_000).checked_mul(bp)? / 10_000;
+        whole.checked_add(rest)
+    }
+
+    /// Find the portion of a token amount given by the basis points, rounded
+    /// down. Unlike [`BasisPoints::checked_mul`], this fails when the basis
+    /// points are greater than [`BasisPoints::MAX`], so the portion is never
+    /// greater than the amount.
+    pub fn portion_of(self, amount: u64) -> Result<u64, ArithmeticError> {
+        if self > Self::MAX {
+            return Err(ArithmeticError::RateTooLarge(self));
+        }
+        self.checked_mul(amount)
+            .ok_or(ArithmeticError::TokenAmountOverflow)
+    }
+
+    /// Find the portion of a token change given by the basis points, rounded
+    /// towards zero. Like [`BasisPoints::portion_of`], this fails when the
+    /// basis points are greater than [`BasisPoints::MAX`].
+    pub fn portion_of_change(
+        self,
+        change: i128,
+    ) -> Result<i128, ArithmeticError> {
+        if self > Self::MAX {
+            return Err(ArithmeticError::RateTooLarge(self));
+        }
+        self.checked_mul_change(change)
+            .ok_or(ArithmeticError::TokenAmountOverflow)
     }
 }
 
-impl Mul<i128> for BasisPoints {
-    type Output = i128;
+impl From<BasisPoints> for u64 {
+    fn from(value: BasisPoints) -> Self {
+        value.0
+    }
+}
 
-    fn mul(self, rhs: i128) -> Self::Output {
-        // TODO checked arithmetics
-        rhs * self.0 as i128 / 10_000
+impl Display for BasisPoints {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}‱", self.0)
     }
 }
 
@@ -696,4 +1110,70 @@ pub mod tests {
     pub fn arb_epoch(range: Range<u64>) -> impl Strategy<Value = Epoch> {
         range.prop_map(Epoch)
     }
+
+    proptest! {
+        /// The checked multiplication must agree with the multiplication in a
+        /// wider integer type whenever the result fits.
+        #[test]
+        fn test_basis_points_checked_mul(
+            bp in 0..=100_000_u64,
+            amount: u64,
+            change: i64,
+        ) {
+            let bp = BasisPoints::new(bp);
+            let expected = amount as u128 * bp.0 as u128 / 10_000;
+            assert_eq!(
+                bp.checked_mul(amount),
+                u64::try_from(expected).ok()
+            );
+            let expected = change as i128 * bp.0 as i128 / 10_000;
+            assert_eq!(bp.checked_mul_change(change as i128), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_basis_points_portion_of() {
+        assert_eq!(BasisPoints::MAX.portion_of(u64::MAX), Ok(u64::MAX));
+        assert_eq!(BasisPoints::new(2_500).portion_of(1_001), Ok(250));
+        assert_eq!(BasisPoints::new(2_500).portion_of_change(-1_001), Ok(-250));
+        let too_large = BasisPoints::new(10_001);
+        assert_eq!(
+            too_large.portion_of(1),
+            Err(ArithmeticError::RateTooLarge(too_large))
+        );
+        assert_eq!(
+            too_large.portion_of_change(-1),
+            Err(ArithmeticError::RateTooLarge(too_large))
+        );
+    }
+
+    #[test]
+    fn test_voting_power_overflow() {
+        let params = PosParams {
+            votes_per_token: BasisPoints::new(10_000_000),
+            ..PosParams::default()
+        };
+        assert_eq!(
+            VotingPower::try_from_tokens(u64::MAX, &params),
+            Err(ArithmeticError::VotingPowerOverflow)
+        );
+        assert_eq!(
+            VotingPowerDelta::try_from_token_change(i128::MIN, &params),
+            Err(ArithmeticError::VotingPowerOverflow)
+        );
+        assert_eq!(
+            VotingPower::try_from_tokens(1_000_000_u64, &params),
+            Ok(VotingPower(1_000))
+        );
+
+        assert_eq!(VotingPower(u64::MAX).checked_add(VotingPower(1)), None);
+        assert_eq!(
+            VotingPowerDelta(i64::MIN).checked_sub(VotingPowerDelta(1)),
+            None
+        );
+        assert_eq!(
+            VotingPowerDelta(1).checked_add(VotingPowerDelta(1)),
+            Some(VotingPowerDelta(2))
+        );
+    }
 }
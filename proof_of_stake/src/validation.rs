@@ -1,7 +1,7 @@
 //! Validation of updated PoS data
 
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt::{Debug, Display};
 use std::hash::Hash;
@@ -14,10 +14,12 @@ use crate::btree_set::BTreeSetShims;
 use crate::epoched::DynEpochOffset;
 use crate::parameters::PosParams;
 use crate::types::{
-    BondId, Bonds, Epoch, Slashes, TotalVotingPowers, Unbonds,
-    ValidatorConsensusKeys, ValidatorSets, ValidatorState, ValidatorStates,
+    ArithmeticError, BasisPoints, BondId, BondRewards, Bonds,
+    ConsensusKeyOwner, Epoch, Redelegations, Slash, Slashes, TotalVotingPowers,
+    Unbonds, ValidatorCommissionRates, ValidatorConsensusKeys,
+    ValidatorMetadata, ValidatorSets, ValidatorState, ValidatorStates,
     ValidatorTotalDeltas, ValidatorVotingPowers, VotingPower, VotingPowerDelta,
-    WeightedValidator,
+    WeightedValidator, Withdrawals,
 };
 
 #[allow(missing_docs)]
@@ -63,12 +65,15 @@ where
     InvalidLastUpdate,
     #[error(
         "Invalid staking token balances. Balance Δ {balance_delta}, bonds Δ \
-         {bond_delta}, unbonds Δ {unbond_delta}"
+         {bond_delta}, unbonds Δ {unbond_delta}, rewards Δ {rewards_delta}, \
+         slashed redelegations Δ {redelegation_delta}"
     )]
     InvalidBalances {
         balance_delta: TokenChange,
         bond_delta: TokenChange,
         unbond_delta: TokenChange,
+        rewards_delta: TokenChange,
+        redelegation_delta: TokenChange,
     },
     #[error(
         "Data must be set or updated in the correct epoch. Got epoch {got}, \
@@ -77,6 +82,17 @@ where
     EpochedDataWrongEpoch { got: u64, expected: Vec<u64> },
     #[error("Empty bond {0} must be deleted")]
     EmptyBond(BondId<Address>),
+    #[error("Empty redelegation in {0} must be deleted")]
+    EmptyRedelegation(BondId<Address>),
+    #[error(
+        "Tokens redelegated to {id} must be added at the correct epoch. Got \
+         epoch {got}, expected {expected}"
+    )]
+    InvalidNewRedelegationEpoch {
+        id: BondId<Address>,
+        got: u64,
+        expected: u64,
+    },
     #[error(
         "Bond ID {id} must start at the correct epoch. Got epoch {got}, \
          expected {expected}"
@@ -130,6 +146,10 @@ where
     MissingValidatorVotingPower(Address),
     #[error("Validator {0} has an invalid voting power value {1}")]
     InvalidValidatorVotingPower(Address, i64),
+    #[error("Voting power overflow for validator {0}")]
+    VotingPowerOverflow(Address),
+    #[error("Total voting power overflow in epoch {0}")]
+    TotalVotingPowerOverflow(u64),
     #[error("Validator set should be updated when voting powers change")]
     ValidatorSetNotUpdated,
     #[error("Invalid voting power changes")]
@@ -156,12 +176,72 @@ where
     InvalidAddressRawHash(String, String),
     #[error("Invalid address raw hash update")]
     InvalidRawHashUpdate,
+    #[error("Invalid owner of the consensus key with raw hash {0}")]
+    InvalidConsensusKeyOwner(String),
+    #[error(
+        "Invalid update of the owner of the consensus key with raw hash {0}"
+    )]
+    InvalidConsensusKeyOwnerUpdate(String),
+    #[error(
+        "The consensus key owner of validator {0} doesn't belong to a new \
+         validator or a changed consensus key"
+    )]
+    UnexpectedConsensusKeyOwner(Address),
+    #[error(
+        "The changed consensus key of validator {0} is not updated in the \
+         index of consensus keys"
+    )]
+    MissingConsensusKeyOwnerUpdate(Address),
     #[error("Invalid new validator {0}, some fields are missing: {1:?}.")]
     InvalidNewValidator(Address, NewValidator),
     #[error("New validator {0} has not been added to the validator set.")]
     NewValidatorMissingInValidatorSet(Address),
     #[error("Validator set has not been updated for new validators.")]
     MissingValidatorSetUpdate,
+    #[error("Unexpectedly missing commission rate for validator {0}")]
+    MissingValidatorCommissionRate(Address),
+    #[error("Invalid commission rate {0}, it must not be greater than 100%")]
+    InvalidCommissionRate(BasisPoints),
+    #[error("Invalid validator commission rate update in epoch {0}")]
+    InvalidCommissionRateUpdate(u64),
+    #[error(
+        "The commission rate change {0} is greater than the maximum change \
+         {1} per epoch"
+    )]
+    CommissionRateChangeTooLarge(BasisPoints, BasisPoints),
+    #[error("Unexpectedly missing metadata of validator {0}")]
+    MissingValidatorMetadata(Address),
+    #[error(
+        "The metadata field {1} of validator {0} is longer than the maximum \
+         length of {} bytes",
+        ValidatorMetadata::MAX_FIELD_LEN
+    )]
+    ValidatorMetadataFieldTooLong(Address, &'static str),
+    #[error("Invalid rewards update of bond {0}")]
+    InvalidBondRewardsUpdate(BondId<Address>),
+    #[error("Invalid withdrawals update of bond {0}")]
+    InvalidWithdrawalsUpdate(BondId<Address>),
+    #[error(
+        "The unbonded tokens withdrawn from bond {0} must be recorded in its \
+         withdrawals"
+    )]
+    UnrecordedWithdrawal(BondId<Address>),
+    #[error(
+        "Invalid slash pool balance. Balance Δ {balance_delta}, repaid \
+         clawbacks Δ {clawback_delta}"
+    )]
+    InvalidSlashPoolBalance {
+        balance_delta: TokenChange,
+        clawback_delta: TokenChange,
+    },
+    #[error("Invalid jailed epoch update of validator {0}")]
+    InvalidJailedEpochUpdate(Address),
+    #[error("The validator {0} cannot be unjailed before epoch {1}")]
+    JailDurationNotElapsed(Address, u64),
+    #[error("Invalid unjailing of validator {0}")]
+    InvalidUnjail(Address),
+    #[error("Arithmetic error: {0}")]
+    Arithmetic(ArithmeticError),
 }
 
 /// An update of PoS data.
@@ -222,6 +302,31 @@ where
         /// List of slashes applied to the bond's validator
         slashes: Slashes,
     },
+    /// Redelegations update
+    Redelegation {
+        /// Bond ID of the delegation to which the tokens are redelegated
+        id: BondId<Address>,
+        /// Redelegations prior and posterior state
+        data: Data<Redelegations<Address, TokenAmount>>,
+        /// Slashes applied to the source validators of the redelegations
+        slashes: HashMap<Address, Slashes>,
+    },
+    /// Withdrawals update
+    Withdrawal {
+        /// Bond ID
+        id: BondId<Address>,
+        /// Withdrawals prior and posterior state
+        data: Data<Withdrawals<TokenAmount>>,
+    },
+    /// PoS slash pool's balance update
+    SlashPoolBalance(Data<TokenAmount>),
+    /// Bond rewards update
+    BondRewards {
+        /// Bond ID
+        id: BondId<Address>,
+        /// Bond rewards prior and posterior state
+        data: Data<BondRewards<TokenAmount>>,
+    },
     /// A validator update
     Validator {
         /// Validator's address
@@ -240,6 +345,16 @@ where
         /// The address and raw hash derived from it
         data: Data<(Address, String)>,
     },
+    /// The owner of a consensus key
+    ValidatorConsensusKeyOwner {
+        /// Raw hash of the consensus key
+        raw_hash: String,
+        /// The owner of the key
+        data: Data<ConsensusKeyOwner<Address>>,
+        /// Raw hash of the owner's posterior consensus key in the pipeline
+        /// epoch, if any
+        pipeline_key_raw_hash: Option<String>,
+    },
 }
 
 /// An update of a validator's data.
@@ -270,6 +385,18 @@ where
     TotalDeltas(Data<ValidatorTotalDeltas<TokenChange>>),
     /// Voting power update
     VotingPowerUpdate(Data<ValidatorVotingPowers>),
+    /// Commission rate update
+    CommissionRate(Data<ValidatorCommissionRates>),
+    /// Metadata update
+    Metadata(Data<ValidatorMetadata>),
+    /// Jailed epoch update
+    JailedEpoch {
+        /// The epoch in which the validator has been jailed
+        data: Data<Epoch>,
+        /// The validator's voting power, used to check that an unjailed
+        /// validator is added back to the validator set
+        voting_power: Option<ValidatorVotingPowers>,
+    },
 }
 
 /// Data update with prior and posterior state.
@@ -294,6 +421,8 @@ pub struct NewValidator {
     has_voting_power: bool,
     has_staking_reward_address: bool,
     has_address_raw_hash: bool,
+    has_consensus_key_owner: bool,
+    has_commission_rate: bool,
     voting_power: VotingPower,
 }
 
@@ -360,15 +489,37 @@ where
     let unbonding_epoch = current_epoch + unbonding_offset;
 
     let mut errors = vec![];
+    // The first error in the arithmetic of token changes, if any
+    let mut arithmetic_error: Option<ArithmeticError> = None;
 
     let mut balance_delta = TokenChange::default();
     // Changes of validators' bonds
     let mut bond_delta: HashMap<Address, TokenChange> = HashMap::default();
     // Changes of validators' unbonds
     let mut unbond_delta: HashMap<Address, TokenChange> = HashMap::default();
+    // Changes of delegators' unclaimed rewards
+    let mut rewards_delta = TokenChange::default();
+    // Changes of the redelegated tokens slashed for the faults of their source
+    // validators
+    let mut redelegation_delta = TokenChange::default();
+    // The unbond deltas withdrawn from bonds
+    let mut withdrawn_unbonds: HashMap<
+        BondId<Address>,
+        HashMap<(Epoch, Epoch), TokenAmount>,
+    > = HashMap::default();
+    // The withdrawn deltas newly recorded in bonds' withdrawals
+    let mut recorded_withdrawals: HashMap<
+        BondId<Address>,
+        HashMap<(Epoch, Epoch), TokenAmount>,
+    > = HashMap::default();
+    // The clawbacks repaid from bonds' withdrawals
+    let mut clawback_delta = TokenChange::default();
+    let mut slash_pool_delta = TokenChange::default();
 
     // Changes of all validator total deltas (up to `unbonding_epoch`)
     let mut total_deltas: HashMap<Address, TokenChange> = HashMap::default();
+    // Validators whose stake has been emptied by the total deltas changes
+    let mut emptied_stakes: HashSet<Address> = HashSet::default();
     // Accumulative stake calculated from validator total deltas for each epoch
     // in which it has changed
     let mut total_stake_by_epoch: HashMap<
@@ -400,6 +551,20 @@ where
     > = HashMap::default();
 
     let mut new_validators: HashMap<Address, NewValidator> = HashMap::default();
+    // Validators with a new entry in the index of consensus keys
+    let mut new_consensus_key_owners: HashSet<Address> = HashSet::default();
+    // Validators whose replaced consensus key's entry in the index of
+    // consensus keys has been ended or deleted
+    let mut replaced_consensus_key_owners: HashSet<Address> =
+        HashSet::default();
+    // Validators whose consensus key has been changed at pipeline epoch
+    let mut changed_consensus_keys: HashSet<Address> = HashSet::default();
+
+    // Validators whose state changed from `Jailed` to `Candidate`
+    let mut unjailed_states: HashSet<Address> = HashSet::default();
+    // Validators whose jailed epoch has been removed, with their voting power
+    let mut unjailed: HashMap<Address, Option<ValidatorVotingPowers>> =
+        HashMap::default();
 
     for change in changes {
         match change {
@@ -468,6 +633,11 @@ where
                                 Some(Inactive),
                                 Some(Candidate) | Some(Pending),
                             ) => {}
+                            // A validator may only be jailed by the protocol,
+                            // but it may be unjailed in a transaction
+                            (Some(Jailed), Some(Candidate)) => {
+                                unjailed_states.insert(address.clone());
+                            }
                             _ => errors.push(Error::InvalidNewValidatorState(
                                 pipeline_epoch.into(),
                             )),
@@ -516,6 +686,9 @@ where
                                 ),
                             }
                         }
+                        if pre.get(pipeline_epoch) != post.get(pipeline_epoch) {
+                            changed_consensus_keys.insert(address.clone());
+                        }
                     }
                     (Some(_), None) => errors
                         .push(Error::ValidatorStateIsRequired(address.clone())),
@@ -582,14 +755,30 @@ where
                                     pre.get_delta_at_epoch(epoch).copied()
                                 }
                             } {
-                                delta -= change;
-                                pre_deltas_sum += change;
+                                sub_change(
+                                    &mut delta,
+                                    change,
+                                    &mut arithmetic_error,
+                                );
+                                add_change(
+                                    &mut pre_deltas_sum,
+                                    change,
+                                    &mut arithmetic_error,
+                                );
                             }
                             // Find the delta in `post`
                             if let Some(change) = post.get_delta_at_epoch(epoch)
                             {
-                                delta += *change;
-                                post_deltas_sum += *change;
+                                add_change(
+                                    &mut delta,
+                                    *change,
+                                    &mut arithmetic_error,
+                                );
+                                add_change(
+                                    &mut post_deltas_sum,
+                                    *change,
+                                    &mut arithmetic_error,
+                                );
                                 let stake_pre: i128 =
                                     Into::into(pre_deltas_sum);
                                 let stake_post: i128 =
@@ -609,23 +798,37 @@ where
                                                 stake_post,
                                             );
                                         // Check if voting power should change
-                                        let delta_pre =
+                                        let voting_powers = (
                                             VotingPowerDelta::try_from_tokens(
                                                 stake_pre, params,
-                                            )
-                                            .unwrap_or_default();
-                                        let delta_post =
+                                            ),
                                             VotingPowerDelta::try_from_tokens(
                                                 stake_post, params,
-                                            )
-                                            .unwrap_or_default();
+                                            ),
+                                            VotingPower::try_from_tokens(
+                                                stake_post, params,
+                                            ),
+                                        );
+                                        let (
+                                            delta_pre,
+                                            delta_post,
+                                            voting_power_post,
+                                        ) = match voting_powers {
+                                            (Ok(pre), Ok(post), Ok(vp)) => {
+                                                (pre, post, vp)
+                                            }
+                                            _ => {
+                                                errors.push(
+                                                    Error::VotingPowerOverflow(
+                                                        address.clone(),
+                                                    ),
+                                                );
+                                                continue;
+                                            }
+                                        };
                                         if delta_pre != delta_post {
                                             // Accumulate expected voting power
                                             // change
-                                            let voting_power_post =
-                                                VotingPower::from_tokens(
-                                                    stake_post, params,
-                                                );
                                             expected_voting_power_by_epoch
                                                 .entry(epoch)
                                                 .or_insert_with(
@@ -635,18 +838,29 @@ where
                                                     address.clone(),
                                                     voting_power_post,
                                                 );
-                                            let delta = delta_post
-                                                - delta_pre
-                                                - last_voting_power_delta;
+                                            // Both voting powers are
+                                            // non-negative, so this cannot
+                                            // overflow
+                                            let epoch_delta =
+                                                delta_post - delta_pre;
+                                            let delta = epoch_delta
+                                                .checked_sub(
+                                                    last_voting_power_delta,
+                                                );
                                             if delta
-                                                != VotingPowerDelta::default()
+                                                != Some(
+                                                    VotingPowerDelta::default(),
+                                                )
                                             {
                                                 let current_delta = expected_total_voting_power_delta_by_epoch.entry(epoch)
                                                 .or_insert_with(Default::default);
-                                                *current_delta += delta;
+                                                match delta.and_then(|delta| current_delta.checked_add(delta)) {
+                                                    Some(sum) => *current_delta = sum,
+                                                    None => errors.push(Error::TotalVotingPowerOverflow(epoch.into())),
+                                                }
                                             }
                                             last_voting_power_delta =
-                                                delta_post - delta_pre;
+                                                epoch_delta;
                                         }
                                     }
                                     _ => errors.push(
@@ -657,7 +871,11 @@ where
                                     ),
                                 }
                             }
-                            deltas += delta;
+                            add_change(
+                                &mut deltas,
+                                delta,
+                                &mut arithmetic_error,
+                            );
                             // A total delta can only be increased at
                             // `pipeline_offset` from bonds and decreased at
                             // `unbonding_offset` from unbonding
@@ -683,6 +901,9 @@ where
                                 address.clone(),
                             ))
                         }
+                        if post_deltas_sum == TokenChange::default() {
+                            emptied_stakes.insert(address.clone());
+                        }
                         if deltas != TokenChange::default() {
                             total_deltas.insert(address.clone(), deltas);
                         }
@@ -710,7 +931,11 @@ where
                                         expected: vec![pipeline_epoch.into()],
                                     })
                                 }
-                                deltas += *change;
+                                add_change(
+                                    &mut deltas,
+                                    *change,
+                                    &mut arithmetic_error,
+                                );
                                 let stake: i128 = Into::into(deltas);
                                 match u64::try_from(stake) {
                                     Ok(stake) => {
@@ -721,10 +946,28 @@ where
                                             .insert(address.clone(), stake);
                                         // Accumulate expected voting power
                                         // change
-                                        let voting_power =
-                                            VotingPower::from_tokens(
+                                        let voting_powers = (
+                                            VotingPower::try_from_tokens(
                                                 stake, params,
-                                            );
+                                            ),
+                                            VotingPowerDelta::try_from_token_change(
+                                                *change, params,
+                                            ),
+                                        );
+                                        let (voting_power, voting_power_delta) =
+                                            match voting_powers {
+                                                (Ok(vp), Ok(delta)) => {
+                                                    (vp, delta)
+                                                }
+                                                _ => {
+                                                    errors.push(
+                                                        Error::VotingPowerOverflow(
+                                                            address.clone(),
+                                                        ),
+                                                    );
+                                                    continue;
+                                                }
+                                            };
                                         expected_voting_power_by_epoch
                                             .entry(epoch)
                                             .or_insert_with(HashMap::default)
@@ -732,11 +975,18 @@ where
                                                 address.clone(),
                                                 voting_power,
                                             );
-                                        let voting_power_delta = VotingPowerDelta::try_from_token_change(
-                                            *change, params).unwrap_or_default();
                                         let current_delta = expected_total_voting_power_delta_by_epoch.entry(epoch)
                                                 .or_insert_with(Default::default);
-                                        *current_delta += voting_power_delta;
+                                        match current_delta
+                                            .checked_add(voting_power_delta)
+                                        {
+                                            Some(sum) => *current_delta = sum,
+                                            None => errors.push(
+                                                Error::TotalVotingPowerOverflow(
+                                                    epoch.into(),
+                                                ),
+                                            ),
+                                        }
                                     }
                                     Err(_) => errors.push(
                                         Error::InvalidValidatorTotalDeltas(
@@ -778,7 +1028,18 @@ where
                         ) {
                             if let Some(delta) = post.get_delta_at_epoch(epoch)
                             {
-                                voting_power += *delta;
+                                voting_power =
+                                    match voting_power.checked_add(*delta) {
+                                        Some(voting_power) => voting_power,
+                                        None => {
+                                            errors.push(
+                                                Error::VotingPowerOverflow(
+                                                    address.clone(),
+                                                ),
+                                            );
+                                            break;
+                                        }
+                                    };
                                 let vp: i64 = Into::into(voting_power);
                                 match u64::try_from(vp) {
                                     Ok(vp) => {
@@ -809,12 +1070,127 @@ where
                     ),
                     (None, None) => continue,
                 },
+                CommissionRate(data) => match (data.pre, data.post) {
+                    (None, Some(post)) => {
+                        if post.last_update() != current_epoch {
+                            errors.push(Error::InvalidLastUpdate)
+                        }
+                        // The value must be known at pipeline epoch
+                        match post.get(pipeline_epoch) {
+                            Some(rate) if *rate <= BasisPoints::MAX => {}
+                            Some(rate) => {
+                                errors.push(Error::InvalidCommissionRate(*rate))
+                            }
+                            None => errors.push(
+                                Error::MissingValidatorCommissionRate(
+                                    address.clone(),
+                                ),
+                            ),
+                        }
+                        let validator =
+                            new_validators.entry(address.clone()).or_default();
+                        validator.has_commission_rate = true;
+                    }
+                    (Some(pre), Some(post)) => {
+                        if post.last_update() != current_epoch {
+                            errors.push(Error::InvalidLastUpdate)
+                        }
+                        // Before pipeline epoch, the rate must not change
+                        for epoch in
+                            Epoch::iter_range(current_epoch, pipeline_offset)
+                        {
+                            if pre.get(epoch) != post.get(epoch) {
+                                errors.push(Error::InvalidCommissionRateUpdate(
+                                    epoch.into(),
+                                ))
+                            }
+                        }
+                        // At pipeline epoch, the rate may only change within
+                        // the limit from the rate in the epoch before it
+                        let prev_rate = post
+                            .get(pipeline_epoch - 1_u64)
+                            .copied()
+                            .unwrap_or_default();
+                        match post.get(pipeline_epoch) {
+                            Some(rate) if *rate > BasisPoints::MAX => {
+                                errors.push(Error::InvalidCommissionRate(*rate))
+                            }
+                            Some(rate) => {
+                                let change = rate.abs_diff(prev_rate);
+                                if change > params.max_commission_rate_change {
+                                    errors.push(
+                                        Error::CommissionRateChangeTooLarge(
+                                            change,
+                                            params.max_commission_rate_change,
+                                        ),
+                                    )
+                                }
+                            }
+                            None => errors.push(
+                                Error::MissingValidatorCommissionRate(
+                                    address.clone(),
+                                ),
+                            ),
+                        }
+                    }
+                    (Some(_), None) => errors.push(
+                        Error::MissingValidatorCommissionRate(address.clone()),
+                    ),
+                    (None, None) => continue,
+                },
+                Metadata(data) => match (data.pre, data.post) {
+                    (_, Some(post)) => {
+                        if let Some(field) = post.find_too_long_field() {
+                            errors.push(Error::ValidatorMetadataFieldTooLong(
+                                address.clone(),
+                                field,
+                            ))
+                        }
+                    }
+                    (Some(_), None) => errors
+                        .push(Error::MissingValidatorMetadata(address.clone())),
+                    (None, None) => continue,
+                },
+                JailedEpoch { data, voting_power } => {
+                    match (data.pre, data.post) {
+                        // A validator may only be jailed by the protocol, but
+                        // it may be unjailed in a transaction once it has
+                        // been jailed for the minimum duration
+                        (Some(jailed_epoch), None) => {
+                            let unjail_epoch =
+                                jailed_epoch + params.min_jail_duration;
+                            if current_epoch < unjail_epoch {
+                                errors.push(Error::JailDurationNotElapsed(
+                                    address.clone(),
+                                    unjail_epoch.into(),
+                                ))
+                            }
+                            unjailed.insert(address.clone(), voting_power);
+                        }
+                        (None, None) => continue,
+                        _ => errors.push(Error::InvalidJailedEpochUpdate(
+                            address.clone(),
+                        )),
+                    }
+                }
             },
             Balance(data) => match (data.pre, data.post) {
-                (None, Some(post)) => balance_delta += TokenChange::from(post),
+                (None, Some(post)) => add_change(
+                    &mut balance_delta,
+                    TokenChange::from(post),
+                    &mut arithmetic_error,
+                ),
                 (Some(pre), Some(post)) => {
-                    balance_delta -= TokenChange::from(pre);
-                    balance_delta += TokenChange::from(post);
+                    sub_change(
+                        &mut balance_delta,
+                        TokenChange::from(pre),
+                        &mut arithmetic_error,
+                    );
+                    add_change(
+                        &mut balance_delta,
+                        TokenChange::from(post),
+                        &mut arithmetic_error,
+                    );
                 }
                 (Some(_), None) => errors.push(Error::MissingBalance),
                 (None, None) => continue,
@@ -872,10 +1248,13 @@ where
                                 let delta = TokenChange::from(*delta);
                                 match slashed_deltas.get_mut(start_epoch) {
                                     Some(pre_delta) => {
-                                        if *pre_delta + delta == 0_i128.into() {
+                                        add_change(
+                                            pre_delta,
+                                            delta,
+                                            &mut arithmetic_error,
+                                        );
+                                        if *pre_delta == 0_i128.into() {
                                             slashed_deltas.remove(start_epoch);
-                                        } else {
-                                            *pre_delta += delta;
                                         }
                                     }
                                     None => {
@@ -926,16 +1305,18 @@ where
                             if slash.epoch >= *start_epoch {
                                 let raw_delta: i128 = (*delta).into();
                                 let current_slashed =
-                                    TokenChange::from(slash.rate * raw_delta);
+                                    TokenChange::from(or_arithmetic_error(
+                                        slash.rate.portion_of_change(raw_delta),
+                                        &mut arithmetic_error,
+                                    ));
                                 *delta -= current_slashed;
                             }
                         }
                     }
-                    let total = slashed_deltas
-                        .values()
-                        .fold(TokenChange::default(), |acc, delta| {
-                            acc + *delta
-                        });
+                    let mut total = TokenChange::default();
+                    for delta in slashed_deltas.values() {
+                        add_change(&mut total, *delta, &mut arithmetic_error);
+                    }
                     if total != TokenChange::default() {
                         bond_delta.insert(id.validator, total);
                     }
@@ -972,13 +1353,21 @@ where
                                     if slash.epoch >= *start_epoch {
                                         let raw_delta: u64 = delta.into();
                                         let current_slashed = TokenAmount::from(
-                                            slash.rate * raw_delta,
+                                            or_arithmetic_error(
+                                                slash
+                                                    .rate
+                                                    .portion_of(raw_delta),
+                                                &mut arithmetic_error,
+                                            ),
                                         );
                                         delta -= current_slashed;
                                     }
                                 }
-                                let delta = TokenChange::from(delta);
-                                total_delta += delta
+                                add_change(
+                                    &mut total_delta,
+                                    TokenChange::from(delta),
+                                    &mut arithmetic_error,
+                                );
                             }
                         }
                     }
@@ -1002,13 +1391,21 @@ where
                                     if slash.epoch >= *start_epoch {
                                         let raw_delta: u64 = delta.into();
                                         let current_slashed = TokenAmount::from(
-                                            slash.rate * raw_delta,
+                                            or_arithmetic_error(
+                                                slash
+                                                    .rate
+                                                    .portion_of(raw_delta),
+                                                &mut arithmetic_error,
+                                            ),
                                         );
                                         delta -= current_slashed;
                                     }
                                 }
-                                let delta = TokenChange::from(delta);
-                                total_delta -= delta
+                                sub_change(
+                                    &mut total_delta,
+                                    TokenChange::from(delta),
+                                    &mut arithmetic_error,
+                                );
                             }
                         }
                     }
@@ -1054,10 +1451,13 @@ where
                                 let key = (*start_epoch, *end_epoch);
                                 match slashed_deltas.get_mut(&key) {
                                     Some(pre_delta) => {
-                                        if *pre_delta + delta == 0_i128.into() {
+                                        add_change(
+                                            pre_delta,
+                                            delta,
+                                            &mut arithmetic_error,
+                                        );
+                                        if *pre_delta == 0_i128.into() {
                                             slashed_deltas.remove(&key);
-                                        } else {
-                                            *pre_delta += delta;
                                         }
                                     }
                                     None => {
@@ -1067,6 +1467,24 @@ where
                             }
                         }
                     }
+                    // The removed deltas have been withdrawn
+                    let withdrawn: HashMap<(Epoch, Epoch), TokenAmount> =
+                        slashed_deltas
+                            .iter()
+                            .filter(|(_key, delta)| {
+                                **delta < TokenChange::default()
+                            })
+                            .map(|(key, delta)| {
+                                let raw_delta: i128 = (*delta).into();
+                                let amount = TokenAmount::from(
+                                    raw_delta.unsigned_abs() as u64,
+                                );
+                                (*key, amount)
+                            })
+                            .collect();
+                    if !withdrawn.is_empty() {
+                        withdrawn_unbonds.insert(id.clone(), withdrawn);
+                    }
                     // Check slashes
                     for ((start_epoch, end_epoch), delta) in
                         slashed_deltas.iter_mut()
@@ -1077,16 +1495,18 @@ where
                             {
                                 let raw_delta: i128 = (*delta).into();
                                 let current_slashed =
-                                    TokenChange::from(slash.rate * raw_delta);
+                                    TokenChange::from(or_arithmetic_error(
+                                        slash.rate.portion_of_change(raw_delta),
+                                        &mut arithmetic_error,
+                                    ));
                                 *delta -= current_slashed;
                             }
                         }
                     }
-                    let total = slashed_deltas
-                        .values()
-                        .fold(TokenChange::default(), |acc, delta| {
-                            acc + *delta
-                        });
+                    let mut total = TokenChange::default();
+                    for delta in slashed_deltas.values() {
+                        add_change(&mut total, *delta, &mut arithmetic_error);
+                    }
                     if total != TokenChange::default() {
                         unbond_delta.insert(id.validator, total);
                     }
@@ -1113,13 +1533,21 @@ where
                                     {
                                         let raw_delta: u64 = delta.into();
                                         let current_slashed = TokenAmount::from(
-                                            slash.rate * raw_delta,
+                                            or_arithmetic_error(
+                                                slash
+                                                    .rate
+                                                    .portion_of(raw_delta),
+                                                &mut arithmetic_error,
+                                            ),
                                         );
                                         delta -= current_slashed;
                                     }
                                 }
-                                let delta = TokenChange::from(delta);
-                                total_delta += delta;
+                                add_change(
+                                    &mut total_delta,
+                                    TokenChange::from(delta),
+                                    &mut arithmetic_error,
+                                );
                             }
                         }
                     }
@@ -1128,6 +1556,8 @@ where
                 // Unbond may be deleted when all the tokens are withdrawn
                 (Some(pre), None) => {
                     let mut total_delta = TokenChange::default();
+                    let mut withdrawn: HashMap<(Epoch, Epoch), TokenAmount> =
+                        HashMap::default();
                     for epoch in Epoch::iter_range(
                         pre.last_update(),
                         unbonding_offset + 1,
@@ -1136,6 +1566,9 @@ where
                             for ((start_epoch, end_epoch), delta) in
                                 unbond.deltas.iter()
                             {
+                                *withdrawn
+                                    .entry((*start_epoch, *end_epoch))
+                                    .or_default() += *delta;
                                 let mut delta = *delta;
                                 // Check and apply slashes, if any
                                 for slash in &slashes {
@@ -1144,20 +1577,212 @@ where
                                     {
                                         let raw_delta: u64 = delta.into();
                                         let current_slashed = TokenAmount::from(
-                                            slash.rate * raw_delta,
+                                            or_arithmetic_error(
+                                                slash
+                                                    .rate
+                                                    .portion_of(raw_delta),
+                                                &mut arithmetic_error,
+                                            ),
                                         );
                                         delta -= current_slashed;
                                     }
                                 }
-                                let delta = TokenChange::from(delta);
-                                total_delta -= delta;
+                                sub_change(
+                                    &mut total_delta,
+                                    TokenChange::from(delta),
+                                    &mut arithmetic_error,
+                                );
                             }
                         }
                     }
+                    withdrawn_unbonds.insert(id.clone(), withdrawn);
                     unbond_delta.insert(id.validator, total_delta);
                 }
                 (None, None) => continue,
             },
+            Redelegation { id, data, slashes } => {
+                // We have to slash only the difference between post and pre,
+                // not both pre and post to avoid rounding errors
+                let mut deltas: BTreeMap<(Epoch, Address, Epoch), TokenChange> =
+                    BTreeMap::default();
+                if let Some(pre) = data.pre {
+                    for (key, delta) in pre.deltas {
+                        sub_change(
+                            deltas.entry(key).or_default(),
+                            TokenChange::from(delta),
+                            &mut arithmetic_error,
+                        );
+                    }
+                }
+                if let Some(post) = data.post {
+                    for (key, delta) in post.deltas {
+                        // An empty redelegation must be deleted
+                        if delta == TokenAmount::default() {
+                            errors.push(Error::EmptyRedelegation(id.clone()))
+                        }
+                        add_change(
+                            deltas.entry(key).or_default(),
+                            TokenChange::from(delta),
+                            &mut arithmetic_error,
+                        );
+                    }
+                }
+                for ((redelegation_epoch, src_validator, bond_start), delta) in
+                    deltas
+                {
+                    if delta == TokenChange::default() {
+                        continue;
+                    }
+                    // Tokens may only be newly redelegated at the pipeline
+                    // offset
+                    if delta > TokenChange::default()
+                        && redelegation_epoch != pipeline_epoch
+                    {
+                        errors.push(Error::InvalidNewRedelegationEpoch {
+                            id: id.clone(),
+                            got: redelegation_epoch.into(),
+                            expected: pipeline_epoch.into(),
+                        })
+                    }
+                    // Check the source validator's slashes
+                    let raw_delta: i128 = delta.into();
+                    let amount =
+                        TokenAmount::from(raw_delta.unsigned_abs() as u64);
+                    let src_slashes: &[Slash] = slashes
+                        .get(&src_validator)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default();
+                    let mut slashed = amount;
+                    slashed -= or_arithmetic_error(
+                        Redelegations::<Address, _>::slash_delta(
+                            redelegation_epoch,
+                            bond_start,
+                            amount,
+                            src_slashes,
+                        ),
+                        &mut arithmetic_error,
+                    );
+                    if delta > TokenChange::default() {
+                        add_change(
+                            &mut redelegation_delta,
+                            TokenChange::from(slashed),
+                            &mut arithmetic_error,
+                        );
+                    } else {
+                        sub_change(
+                            &mut redelegation_delta,
+                            TokenChange::from(slashed),
+                            &mut arithmetic_error,
+                        );
+                    }
+                }
+            }
+            Withdrawal { id, data } => {
+                let (pre_deltas, pre_clawback) = match data.pre {
+                    Some(pre) => (pre.deltas, pre.clawback.into()),
+                    None => (HashMap::default(), 0_u64),
+                };
+                let (post_deltas, post_clawback) = match data.post {
+                    Some(post) => (post.deltas, post.clawback.into()),
+                    None => (HashMap::default(), 0_u64),
+                };
+                let mut is_valid = true;
+                // The clawback is only added by the protocol, a transaction
+                // may only repay it
+                if post_clawback > pre_clawback {
+                    is_valid = false;
+                } else {
+                    add_change(
+                        &mut clawback_delta,
+                        TokenChange::from(TokenAmount::from(
+                            pre_clawback - post_clawback,
+                        )),
+                        &mut arithmetic_error,
+                    );
+                }
+                // The withdrawn deltas may only be added to
+                let mut recorded: HashMap<(Epoch, Epoch), TokenAmount> =
+                    HashMap::default();
+                for (key, post_delta) in &post_deltas {
+                    let pre_delta: u64 = pre_deltas
+                        .get(key)
+                        .map(|delta| (*delta).into())
+                        .unwrap_or_default();
+                    let post_delta: u64 = (*post_delta).into();
+                    if post_delta < pre_delta {
+                        is_valid = false;
+                    } else if post_delta > pre_delta {
+                        recorded.insert(
+                            *key,
+                            TokenAmount::from(post_delta - pre_delta),
+                        );
+                    }
+                }
+                // Only the withdrawn deltas that can no longer be slashed may
+                // be forgotten
+                for (_epoch_start, epoch_end) in pre_deltas
+                    .keys()
+                    .filter(|key| !post_deltas.contains_key(key))
+                {
+                    if Withdrawals::<TokenAmount>::is_slashable(
+                        params,
+                        *epoch_end,
+                        current_epoch,
+                    ) {
+                        is_valid = false;
+                    }
+                }
+                if !is_valid {
+                    errors.push(Error::InvalidWithdrawalsUpdate(id.clone()))
+                }
+                if !recorded.is_empty() {
+                    recorded_withdrawals.insert(id, recorded);
+                }
+            }
+            SlashPoolBalance(data) => {
+                if let Some(pre) = data.pre {
+                    sub_change(
+                        &mut slash_pool_delta,
+                        TokenChange::from(pre),
+                        &mut arithmetic_error,
+                    );
+                }
+                if let Some(post) = data.post {
+                    add_change(
+                        &mut slash_pool_delta,
+                        TokenChange::from(post),
+                        &mut arithmetic_error,
+                    );
+                }
+            }
+            BondRewards { id, data } => match (data.pre, data.post) {
+                // Rewards are only distributed by the protocol, a transaction
+                // may only claim the unclaimed rewards
+                (Some(pre), Some(post)) => {
+                    let pre_unclaimed: u64 = pre.unclaimed.into();
+                    let post_unclaimed: u64 = post.unclaimed.into();
+                    if post.last_epoch != pre.last_epoch
+                        || post.last_amount != pre.last_amount
+                        || post.total != pre.total
+                        || post_unclaimed > pre_unclaimed
+                    {
+                        errors.push(Error::InvalidBondRewardsUpdate(id))
+                    } else {
+                        sub_change(
+                            &mut rewards_delta,
+                            TokenChange::from(pre.unclaimed),
+                            &mut arithmetic_error,
+                        );
+                        add_change(
+                            &mut rewards_delta,
+                            TokenChange::from(post.unclaimed),
+                            &mut arithmetic_error,
+                        );
+                    }
+                }
+                (None, None) => continue,
+                _ => errors.push(Error::InvalidBondRewardsUpdate(id)),
+            },
             ValidatorSet(data) => match (data.pre, data.post) {
                 (Some(pre), Some(post)) => {
                     if post.last_update() != current_epoch {
@@ -1195,8 +1820,17 @@ where
                             .copied()
                             .unwrap_or_default();
                         if delta_pre != delta_post {
-                            total_voting_power_delta_by_epoch
-                                .insert(epoch, delta_post - delta_pre);
+                            match delta_post.checked_sub(delta_pre) {
+                                Some(delta) => {
+                                    total_voting_power_delta_by_epoch
+                                        .insert(epoch, delta);
+                                }
+                                None => errors.push(
+                                    Error::TotalVotingPowerOverflow(
+                                        epoch.into(),
+                                    ),
+                                ),
+                            }
                         }
                     }
                 }
@@ -1221,14 +1855,109 @@ where
                     _ => continue,
                 }
             }
+            ValidatorConsensusKeyOwner {
+                raw_hash,
+                data,
+                pipeline_key_raw_hash,
+            } => match (data.pre, data.post) {
+                // A new entry may be written for a key that is not used, or
+                // whose evidence has expired
+                (pre, Some(post))
+                    if pre.as_ref().map_or(true, |pre| {
+                        pre.is_expired(current_epoch, params)
+                    }) =>
+                {
+                    // The key must be the owner's consensus key from the
+                    // pipeline epoch
+                    if post.start != pipeline_epoch
+                        || post.end.is_some()
+                        || pipeline_key_raw_hash.as_ref() != Some(&raw_hash)
+                    {
+                        errors.push(Error::InvalidConsensusKeyOwner(raw_hash))
+                    }
+                    new_consensus_key_owners.insert(post.validator);
+                }
+                (Some(pre), Some(post)) if pre != post => {
+                    let is_pipeline_key =
+                        pipeline_key_raw_hash.as_ref() == Some(&raw_hash);
+                    if pre.validator != post.validator
+                        || pre.start != post.start
+                    {
+                        errors.push(Error::InvalidConsensusKeyOwnerUpdate(
+                            raw_hash,
+                        ))
+                    } else if pre.end.is_none()
+                        && post.end == Some(pipeline_epoch)
+                        && !is_pipeline_key
+                    {
+                        // The key has been replaced at the pipeline epoch
+                        replaced_consensus_key_owners.insert(post.validator);
+                    } else if pre.end.is_some()
+                        && post.end.is_none()
+                        && is_pipeline_key
+                    {
+                        // The key is used again by the same validator from the
+                        // pipeline epoch
+                        new_consensus_key_owners.insert(post.validator);
+                    } else {
+                        errors.push(Error::InvalidConsensusKeyOwnerUpdate(
+                            raw_hash,
+                        ))
+                    }
+                }
+                (Some(pre), None) => {
+                    // The key may only be removed when it's been replaced
+                    // before it took effect
+                    if pre.start == pipeline_epoch
+                        && pre.end.is_none()
+                        && pipeline_key_raw_hash.as_ref() != Some(&raw_hash)
+                    {
+                        replaced_consensus_key_owners.insert(pre.validator);
+                    } else {
+                        errors.push(Error::InvalidConsensusKeyOwnerUpdate(
+                            raw_hash,
+                        ))
+                    }
+                }
+                _ => continue,
+            },
         }
     }
 
+    // Check that the unjailed validators' state and jailed epoch are updated
+    // together
+    let unjailed_addresses: HashSet<Address> =
+        unjailed.keys().cloned().collect();
+    for validator in unjailed_states.symmetric_difference(&unjailed_addresses) {
+        errors.push(Error::InvalidUnjail(validator.clone()))
+    }
+    // Check if the validator has been added back to the validator set with
+    // its voting power after being unjailed
+    let is_unjailed = |validator: &WeightedValidator<Address>, epoch: Epoch| {
+        epoch >= pipeline_epoch
+            && unjailed
+                .get(&validator.address)
+                .map(|voting_power| {
+                    let voting_power = voting_power
+                        .as_ref()
+                        .and_then(|voting_power| voting_power.get(epoch))
+                        .and_then(|delta| VotingPower::try_from(delta).ok())
+                        .unwrap_or_default();
+                    voting_power == validator.voting_power
+                })
+                .unwrap_or_default()
+    };
+
     // Check total deltas against bonds
     for (validator, total_delta) in total_deltas.iter() {
         let bond_delta = bond_delta.get(validator).copied().unwrap_or_default();
         let total_delta = *total_delta;
-        if total_delta != bond_delta {
+        // The unbonded bonds reduced by the slashes may add up to slightly
+        // more than the validator's stake, because the slashes are rounded
+        // for each bond delta separately. The stake is then emptied instead.
+        let is_stake_emptied =
+            emptied_stakes.contains(validator) && total_delta > bond_delta;
+        if total_delta != bond_delta && !is_stake_emptied {
             errors.push(Error::InvalidValidatorTotalDeltasSum {
                 address: validator.clone(),
                 total_delta,
@@ -1289,13 +2018,16 @@ where
                         for validator in &post.active {
                             match total_stakes.get(&validator.address) {
                                 Some(stake) => {
-                                    let voting_power = VotingPower::from_tokens(
-                                        *stake, params,
-                                    );
+                                    let voting_power =
+                                        VotingPower::try_from_tokens(
+                                            *stake, params,
+                                        );
                                     // Any validator who's total deltas changed,
                                     // should
                                     // be up-to-date
-                                    if validator.voting_power != voting_power {
+                                    if Ok(validator.voting_power)
+                                        != voting_power
+                                    {
                                         errors.push(
                                             Error::InvalidActiveValidator(
                                                 validator.clone(),
@@ -1304,8 +2036,11 @@ where
                                     }
                                 }
                                 None => {
-                                    // Others must be the same as in pre
-                                    if !pre.active.contains(validator) {
+                                    // Others must be the same as in pre,
+                                    // unless they've been unjailed
+                                    if !pre.active.contains(validator)
+                                        && !is_unjailed(validator, epoch)
+                                    {
                                         errors.push(
                                             Error::InvalidActiveValidator(
                                                 validator.clone(),
@@ -1321,10 +2056,13 @@ where
                             // be up-to-date
                             match total_stakes.get(&validator.address) {
                                 Some(stake) => {
-                                    let voting_power = VotingPower::from_tokens(
-                                        *stake, params,
-                                    );
-                                    if validator.voting_power != voting_power {
+                                    let voting_power =
+                                        VotingPower::try_from_tokens(
+                                            *stake, params,
+                                        );
+                                    if Ok(validator.voting_power)
+                                        != voting_power
+                                    {
                                         errors.push(
                                             Error::InvalidInactiveValidator(
                                                 validator.clone(),
@@ -1333,8 +2071,11 @@ where
                                     }
                                 }
                                 None => {
-                                    // Others must be the same as in pre
-                                    if !pre.active.contains(validator) {
+                                    // Others must be the same as in pre,
+                                    // unless they've been unjailed
+                                    if !pre.active.contains(validator)
+                                        && !is_unjailed(validator, epoch)
+                                    {
                                         errors.push(
                                             Error::InvalidInactiveValidator(
                                                 validator.clone(),
@@ -1354,10 +2095,21 @@ where
                 match post.get(epoch) {
                     Some(post) => {
                         for (validator, tokens_at_epoch) in total_stake {
-                            let voting_power = VotingPower::from_tokens(
-                                *tokens_at_epoch,
-                                params,
-                            );
+                            let voting_power =
+                                match VotingPower::try_from_tokens(
+                                    *tokens_at_epoch,
+                                    params,
+                                ) {
+                                    Ok(voting_power) => voting_power,
+                                    Err(_) => {
+                                        errors.push(
+                                            Error::VotingPowerOverflow(
+                                                validator.clone(),
+                                            ),
+                                        );
+                                        continue;
+                                    }
+                                };
                             let weighted_validator = WeightedValidator {
                                 voting_power,
                                 address: validator.clone(),
@@ -1411,8 +2163,8 @@ where
             for (validator, voting_power) in voting_powers {
                 if let Some(stake) = total_stakes.get(validator) {
                     let voting_power_from_stake =
-                        VotingPower::from_tokens(*stake, params);
-                    if *voting_power != voting_power_from_stake {
+                        VotingPower::try_from_tokens(*stake, params);
+                    if Ok(*voting_power) != voting_power_from_stake {
                         errors.push(Error::InvalidVotingPowerChanges)
                     }
                 } else {
@@ -1479,6 +2231,29 @@ where
         }
     }
 
+    // Check that the changed consensus keys are updated in the index of
+    // consensus keys
+    for address in &changed_consensus_keys {
+        if !new_consensus_key_owners.contains(address)
+            || !replaced_consensus_key_owners.contains(address)
+        {
+            errors.push(Error::MissingConsensusKeyOwnerUpdate(address.clone()))
+        }
+    }
+    for address in replaced_consensus_key_owners {
+        if !changed_consensus_keys.contains(&address) {
+            errors.push(Error::UnexpectedConsensusKeyOwner(address))
+        }
+    }
+    // Check that the consensus keys of new validators are indexed
+    for address in new_consensus_key_owners {
+        match new_validators.get_mut(&address) {
+            Some(validator) => validator.has_consensus_key_owner = true,
+            None if changed_consensus_keys.contains(&address) => {}
+            None => errors.push(Error::UnexpectedConsensusKeyOwner(address)),
+        }
+    }
+
     // Check new validators are initialized with all the required fields
     if !new_validators.is_empty() {
         match &validator_set_post {
@@ -1493,6 +2268,8 @@ where
                         has_voting_power,
                         has_staking_reward_address,
                         has_address_raw_hash,
+                        has_consensus_key_owner,
+                        has_commission_rate,
                         voting_power,
                     } = &new_validator;
                     // The new validator must have set all the required fields
@@ -1501,7 +2278,9 @@ where
                         && *has_total_deltas
                         && *has_voting_power
                         && *has_staking_reward_address
-                        && *has_address_raw_hash)
+                        && *has_address_raw_hash
+                        && *has_consensus_key_owner
+                        && *has_commission_rate)
                     {
                         errors.push(Error::InvalidNewValidator(
                             address.clone(),
@@ -1530,24 +2309,127 @@ where
         }
     }
 
+    // The withdrawn unbond deltas that can still be slashed must be recorded
+    // in their bond's withdrawals
+    for (id, withdrawn) in withdrawn_unbonds {
+        let mut recorded = recorded_withdrawals.remove(&id).unwrap_or_default();
+        let is_recorded =
+            withdrawn
+                .into_iter()
+                .all(|((epoch_start, epoch_end), delta)| {
+                    !Withdrawals::<TokenAmount>::is_slashable(
+                        params,
+                        epoch_end,
+                        current_epoch,
+                    ) || recorded.remove(&(epoch_start, epoch_end))
+                        == Some(delta)
+                });
+        if !is_recorded || !recorded.is_empty() {
+            errors.push(Error::UnrecordedWithdrawal(id))
+        }
+    }
+    for (id, _recorded) in recorded_withdrawals {
+        errors.push(Error::InvalidWithdrawalsUpdate(id))
+    }
+    // The slash pool may only receive the repaid clawbacks
+    if arithmetic_error.is_none() && slash_pool_delta != clawback_delta {
+        errors.push(Error::InvalidSlashPoolBalance {
+            balance_delta: slash_pool_delta,
+            clawback_delta,
+        })
+    }
+
     // Sum the bond totals
-    let bond_delta = bond_delta
-        .values()
-        .into_iter()
-        .fold(TokenChange::default(), |acc, delta| acc + (*delta));
+    let mut bond_total = TokenChange::default();
+    for delta in bond_delta.values() {
+        add_change(&mut bond_total, *delta, &mut arithmetic_error);
+    }
+    let bond_delta = bond_total;
     // Sum the unbond totals
-    let unbond_delta = unbond_delta
-        .values()
-        .into_iter()
-        .fold(TokenChange::default(), |acc, delta| acc + (*delta));
+    let mut unbond_total = TokenChange::default();
+    for delta in unbond_delta.values() {
+        add_change(&mut unbond_total, *delta, &mut arithmetic_error);
+    }
+    let unbond_delta = unbond_total;
 
-    if balance_delta != bond_delta + unbond_delta {
+    // The redelegated tokens slashed for the faults of their source validators
+    // have already been removed from the balance
+    let mut expected_balance_delta = bond_delta;
+    add_change(
+        &mut expected_balance_delta,
+        unbond_delta,
+        &mut arithmetic_error,
+    );
+    add_change(
+        &mut expected_balance_delta,
+        rewards_delta,
+        &mut arithmetic_error,
+    );
+    sub_change(
+        &mut expected_balance_delta,
+        redelegation_delta,
+        &mut arithmetic_error,
+    );
+    if let Some(error) = arithmetic_error {
+        errors.push(Error::Arithmetic(error))
+    } else if balance_delta != expected_balance_delta {
         errors.push(Error::InvalidBalances {
             balance_delta,
             bond_delta,
             unbond_delta,
+            rewards_delta,
+            redelegation_delta,
         })
     }
 
     errors
 }
+
+/// Add `rhs` to the token change `lhs`. On overflow, the sum is saturated and
+/// the overflow is recorded in the `error`, unless it already holds one.
+fn add_change<TokenChange>(
+    lhs: &mut TokenChange,
+    rhs: TokenChange,
+    error: &mut Option<ArithmeticError>,
+) where
+    TokenChange: Copy + Into<i128> + From<i128>,
+{
+    let lhs_raw: i128 = (*lhs).into();
+    let rhs_raw: i128 = rhs.into();
+    let sum = lhs_raw.checked_add(rhs_raw).unwrap_or_else(|| {
+        error.get_or_insert(ArithmeticError::TokenAmountOverflow);
+        lhs_raw.saturating_add(rhs_raw)
+    });
+    *lhs = TokenChange::from(sum);
+}
+
+/// Subtract `rhs` from the token change `lhs`. On overflow, the difference is
+/// saturated and the overflow is recorded in the `error`, unless it already
+/// holds one.
+fn sub_change<TokenChange>(
+    lhs: &mut TokenChange,
+    rhs: TokenChange,
+    error: &mut Option<ArithmeticError>,
+) where
+    TokenChange: Copy + Into<i128> + From<i128>,
+{
+    let lhs_raw: i128 = (*lhs).into();
+    let rhs_raw: i128 = rhs.into();
+    let diff = lhs_raw.checked_sub(rhs_raw).unwrap_or_else(|| {
+        error.get_or_insert(ArithmeticError::TokenAmountOverflow);
+        lhs_raw.saturating_sub(rhs_raw)
+    });
+    *lhs = TokenChange::from(diff);
+}
+
+/// Get the value of the `result` or, on error, record it in the `error`, unless
+/// it already holds one, and fall back to the default value.
+fn or_arithmetic_error<T: Default>(
+    result: Result<T, ArithmeticError>,
+    error: &mut Option<ArithmeticError>,
+) -> T {
+    result.unwrap_or_else(|err| {
+        error.get_or_insert(err);
+        T::default()
+    })
+}
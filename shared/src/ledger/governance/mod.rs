@@ -0,0 +1,730 @@
+//! On-chain governance integration as a native validity predicate
+//!
+//! Anyone can submit a proposal by depositing at least the minimum proposal
+//! deposit of the staking token. A proposal may carry WASM transaction code.
+//! Validators and delegators vote on a proposal within its voting period with
+//! their voting power from the epoch in which the voting starts, the same way
+//! as on [parameters proposals](crate::ledger::parameters).
+//!
+//! In the first block of the proposal's grace epoch, the ledger tallies the
+//! votes. A proposal passes when the voting power that voted reaches the
+//! quorum and the portion of it that voted for the proposal is above the
+//! threshold. The code of a passed proposal is then executed with governance
+//! authority, i.e. native VPs that accept changes from governance check
+//! [`is_proposal_accepted`] for the transaction. The deposit of a passed
+//! proposal is returned to its author, while the deposit of a rejected
+//! proposal is transferred to the [treasury](crate::ledger::treasury).
+//!
+//! The proposals that haven't been finalized yet are indexed by their grace
+//! epoch, so the ledger doesn't have to read all the proposals in every epoch.
+
+mod storage;
+pub mod vp;
+
+use std::collections::HashMap;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+pub use storage::*;
+pub use vp::GovernanceVp;
+
+use crate::ledger::native_vp::{self, Ctx};
+use crate::ledger::parameters::{
+    tally_votes, voting_power_from_delta, ProposalTally, ProposalVote,
+};
+use crate::ledger::pos::types::{BasisPoints, VotingPower};
+use crate::ledger::pos::{
+    self, bonds_for_source_prefix, is_bond_key, total_voting_power_key,
+    validator_voting_power_key, Bonds, PosParams, TotalVotingPowers,
+    ValidatorVotingPowers,
+};
+use crate::ledger::storage::types::encode;
+use crate::ledger::storage::{self as ledger_storage, Storage, StorageHasher};
+use crate::ledger::treasury;
+use crate::types::address::{self, Address, InternalAddress};
+use crate::types::storage::{Epoch, Key};
+use crate::types::token;
+
+/// Address of the governance account implemented as a native VP
+pub const ADDRESS: Address = Address::Internal(InternalAddress::Governance);
+
+/// Address of the token used for proposal deposits (XAN)
+pub fn deposit_token_address() -> Address {
+    address::xan()
+}
+
+/// Governance parameters
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct GovParams {
+    /// Minimum amount of the deposit token to be deposited with a proposal
+    pub min_proposal_deposit: token::Amount,
+    /// Minimum number of epochs of a voting period
+    pub min_voting_period: u64,
+    /// Maximum number of epochs of a voting period
+    pub max_voting_period: u64,
+    /// Maximum size of a proposal's WASM code in bytes
+    pub max_proposal_code_size: u64,
+    /// Maximum size of a proposal's content in bytes
+    pub max_proposal_content_size: u64,
+    /// Minimum portion of the total voting power that has to vote on a
+    /// proposal for it to pass
+    pub quorum: BasisPoints,
+    /// Portion of the voting power that voted on a proposal that has to be
+    /// exceeded by the voting power that voted for it for it to pass
+    pub threshold: BasisPoints,
+}
+
+impl Default for GovParams {
+    fn default() -> Self {
+        Self {
+            min_proposal_deposit: token::Amount::whole(500),
+            min_voting_period: 3,
+            max_voting_period: 30,
+            max_proposal_code_size: 300_000,
+            max_proposal_content_size: 10_000,
+            // 33.4%
+            quorum: BasisPoints::new(3340),
+            // 50%
+            threshold: BasisPoints::new(5000),
+        }
+    }
+}
+
+impl GovParams {
+    /// Does the tally of a proposal pass with these parameters?
+    pub fn is_passed(&self, tally: &ProposalTally) -> bool {
+        let voted = tally.yay.saturating_add(tally.nay);
+        // When a product overflows, it's greater than any number of votes
+        voted > 0
+            && self
+                .quorum
+                .checked_mul(tally.total)
+                .map_or(false, |quorum| voted >= quorum)
+            && self
+                .threshold
+                .checked_mul(voted)
+                .map_or(false, |threshold| tally.yay > threshold)
+    }
+}
+
+/// A governance proposal
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Proposal {
+    /// The author of the proposal, who deposits the funds and has to sign it
+    pub author: Address,
+    /// The content of the proposal, e.g. its title and description
+    pub content: String,
+    /// The first epoch of the voting period. The voting power of the voters
+    /// is taken from this epoch.
+    pub voting_start_epoch: Epoch,
+    /// The epoch at which the voting period ends, exclusive
+    pub voting_end_epoch: Epoch,
+    /// The epoch in which the proposal is tallied and, if it passed, its code
+    /// executed
+    pub grace_epoch: Epoch,
+}
+
+/// The result of a proposal, written by the ledger in the proposal's grace
+/// epoch
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProposalResult {
+    /// The tally of the votes
+    pub tally: ProposalTally,
+    /// Did the proposal pass?
+    pub passed: bool,
+    /// Has the code of a passed proposal been accepted? `None` if the
+    /// proposal has no code or didn't pass.
+    pub code_accepted: Option<bool>,
+}
+
+/// Initialize governance parameters in storage in the genesis block.
+pub fn init_genesis_storage<DB, H>(
+    storage: &mut Storage<DB, H>,
+    params: &GovParams,
+) where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    storage.write(&params_key(), encode(params)).expect(
+        "Governance parameters must be initialized in the genesis block",
+    );
+    storage.write(&counter_key(), encode(&0_u64)).expect(
+        "Governance proposal counter must be initialized in the genesis block",
+    );
+}
+
+/// Check if the transaction is the code of a passed proposal that is being
+/// executed by the ledger. The data of such transaction is the proposal ID.
+/// Native VPs use this to accept changes made with governance authority.
+pub fn is_proposal_accepted<DB, H>(
+    ctx: &Ctx<DB, H>,
+    tx_data: &[u8],
+) -> native_vp::Result<bool>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    match u64::try_from_slice(tx_data) {
+        Ok(id) => ctx.has_key_pre(&proposal_pending_key(id)),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Read the governance parameters.
+pub fn read_params<DB, H>(
+    storage: &Storage<DB, H>,
+) -> ledger_storage::Result<Option<GovParams>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    read(storage, &params_key())
+}
+
+/// Find the proposals that haven't been finalized yet and whose grace epoch
+/// has been reached in the given epoch.
+pub fn proposals_to_finalize<DB, H>(
+    storage: &Storage<DB, H>,
+    epoch: Epoch,
+) -> ledger_storage::Result<Vec<(u64, Proposal)>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let mut proposals = vec![];
+    let (iter, _gas) = storage.iter_prefix(&grace_epoch_index_prefix());
+    for (key, _value, _gas) in iter {
+        let index = Key::parse(&key)
+            .ok()
+            .and_then(|key| is_grace_epoch_index_key(&key));
+        let (grace_epoch, id) = match index {
+            Some(index) => index,
+            None => continue,
+        };
+        if grace_epoch > epoch {
+            continue;
+        }
+        if let Some(proposal) =
+            read::<_, _, Proposal>(storage, &proposal_key(id))?
+        {
+            proposals.push((id, proposal));
+        }
+    }
+    proposals.sort_by_key(|(id, _)| *id);
+    Ok(proposals)
+}
+
+/// Read the WASM code of a proposal, if any.
+pub fn read_proposal_code<DB, H>(
+    storage: &Storage<DB, H>,
+    id: u64,
+) -> ledger_storage::Result<Option<Vec<u8>>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    read(storage, &proposal_code_key(id))
+}
+
+/// Tally the votes on a proposal with the voting powers from the epoch in
+/// which its voting started.
+pub fn tally<DB, H>(
+    storage: &Storage<DB, H>,
+    id: u64,
+    proposal: &Proposal,
+) -> ledger_storage::Result<ProposalTally>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let epoch = proposal.voting_start_epoch;
+    let total =
+        read::<_, _, TotalVotingPowers>(storage, &total_voting_power_key())?
+            .and_then(|powers| powers.get(epoch))
+            .map(voting_power_from_delta)
+            .unwrap_or_default();
+
+    let mut votes = vec![];
+    let (iter, _gas) = storage.iter_prefix(&proposal_votes_prefix(id));
+    for (key, value, _gas) in iter {
+        let key = match Key::parse(&key) {
+            Ok(key) => key,
+            Err(_) => continue,
+        };
+        if let (Some((_, voter)), Ok(vote)) = (
+            is_proposal_vote_key(&key),
+            ProposalVote::try_from_slice(&value[..]),
+        ) {
+            votes.push((voter.clone(), vote));
+        }
+    }
+    let pos_params: Option<PosParams> = read(storage, &pos::params_key())?;
+    tally_votes(
+        votes,
+        total,
+        |voter| validator_voting_power(storage, voter, epoch),
+        |voter| match &pos_params {
+            Some(params) => delegations(storage, params, voter, epoch),
+            None => Ok(HashMap::new()),
+        },
+    )
+}
+
+/// Return the deposit of a passed proposal to its author.
+pub fn refund_deposit<DB, H>(
+    storage: &mut Storage<DB, H>,
+    id: u64,
+    proposal: &Proposal,
+) -> ledger_storage::Result<()>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    transfer_deposit(storage, id, &proposal.author)
+}
+
+/// Transfer the deposit of a rejected proposal to the treasury.
+pub fn transfer_deposit_to_treasury<DB, H>(
+    storage: &mut Storage<DB, H>,
+    id: u64,
+) -> ledger_storage::Result<()>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    transfer_deposit(storage, id, &treasury::ADDRESS)
+}
+
+/// Transfer the deposit of a proposal from the governance account.
+fn transfer_deposit<DB, H>(
+    storage: &mut Storage<DB, H>,
+    id: u64,
+    dest: &Address,
+) -> ledger_storage::Result<()>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let funds: token::Amount =
+        read(storage, &proposal_funds_key(id))?.unwrap_or_default();
+    let token = deposit_token_address();
+    let src_key = token::balance_key(&token, &ADDRESS);
+    let dest_key = token::balance_key(&token, dest);
+    let mut src_balance: token::Amount =
+        read(storage, &src_key)?.unwrap_or_default();
+    let mut dest_balance: token::Amount =
+        read(storage, &dest_key)?.unwrap_or_default();
+    src_balance
+        .spend(&funds)
+        .and_then(|()| dest_balance.receive(&funds))
+        .map_err(|err| ledger_storage::Error::Temporary {
+            error: format!("Cannot transfer the deposit {}: {}", funds, err),
+        })?;
+    storage.write(&src_key, encode(&src_balance))?;
+    storage.write(&dest_key, encode(&dest_balance))?;
+    Ok(())
+}
+
+/// Write the result of a proposal and remove it from the grace epoch index,
+/// which marks it as finalized.
+pub fn write_result<DB, H>(
+    storage: &mut Storage<DB, H>,
+    id: u64,
+    proposal: &Proposal,
+    result: &ProposalResult,
+) -> ledger_storage::Result<()>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    storage.write(&proposal_result_key(id), encode(result))?;
+    storage.delete(&grace_epoch_index_key(proposal.grace_epoch, id))?;
+    Ok(())
+}
+
+/// Read the voting power of a validator in the given epoch. Returns `0` if
+/// the address is not a validator.
+fn validator_voting_power<DB, H>(
+    storage: &Storage<DB, H>,
+    address: &Address,
+    epoch: Epoch,
+) -> ledger_storage::Result<u64>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    Ok(read::<_, _, ValidatorVotingPowers>(
+        storage,
+        &validator_voting_power_key(address),
+    )?
+    .and_then(|powers| powers.get(epoch))
+    .map(voting_power_from_delta)
+    .unwrap_or_default())
+}
+
+/// Read the voting powers of the delegations from the given source to
+/// validators in the given epoch. Self-bonds are excluded.
+fn delegations<DB, H>(
+    storage: &Storage<DB, H>,
+    params: &PosParams,
+    source: &Address,
+    epoch: Epoch,
+) -> ledger_storage::Result<HashMap<Address, u64>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let mut delegations = HashMap::new();
+    let (iter, _gas) = storage.iter_prefix(&bonds_for_source_prefix(source));
+    for (key, value, _gas) in iter {
+        let bond_id =
+            match Key::parse(&key).ok().and_then(|key| is_bond_key(&key)) {
+                Some(bond_id) if bond_id.source != bond_id.validator => bond_id,
+                _ => continue,
+            };
+        let tokens = match Bonds::try_from_slice(&value[..]) {
+            Ok(bonds) => bonds
+                .get(epoch)
+                .map(|bond| u64::from(bond.sum()))
+                .unwrap_or_default(),
+            Err(_) => continue,
+        };
+        let power = VotingPower::try_from_tokens(tokens, params)
+            .map(u64::from)
+            .unwrap_or_default();
+        delegations.insert(bond_id.validator, power);
+    }
+    Ok(delegations)
+}
+
+fn read<DB, H, T>(
+    storage: &Storage<DB, H>,
+    key: &Key,
+) -> ledger_storage::Result<Option<T>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+    T: BorshDeserialize,
+{
+    let (value, _gas) = storage.read(key)?;
+    Ok(value.and_then(|value| T::try_from_slice(&value[..]).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use anoma_proof_of_stake::types::{BasisPoints, Bond};
+
+    use super::*;
+    use crate::ledger::gas::VpGasMeter;
+    use crate::ledger::native_vp::NativeVp;
+    use crate::ledger::pos::{bond_key, BondId, GenesisValidator};
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::ledger::storage::write_log::WriteLog;
+    use crate::proto::Tx;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+        established_address_4, gen_established_address,
+    };
+    use crate::types::key::ed25519::testing::keypair_1;
+
+    fn validator_a() -> Address {
+        established_address_1()
+    }
+
+    fn validator_b() -> Address {
+        established_address_2()
+    }
+
+    fn delegator() -> Address {
+        established_address_3()
+    }
+
+    fn author() -> Address {
+        established_address_4()
+    }
+
+    fn proposal() -> Proposal {
+        Proposal {
+            author: author(),
+            content: "Test proposal".to_owned(),
+            voting_start_epoch: Epoch(1),
+            voting_end_epoch: Epoch(4),
+            grace_epoch: Epoch(4),
+        }
+    }
+
+    /// Initialize the storage with the validator A with voting power 2000, the
+    /// validator B with voting power 1000, a delegation from the delegator to
+    /// the validator A with voting power 500 and the author's balance of 1000
+    /// XAN.
+    fn init_storage() -> TestStorage {
+        let mut storage = TestStorage::default();
+        init_genesis_storage(&mut storage, &GovParams::default());
+
+        let params = PosParams::default();
+        let keypair = keypair_1();
+        let validators: Vec<GenesisValidator> =
+            vec![(validator_a(), 2_000_000), (validator_b(), 1_000_000)]
+                .into_iter()
+                .map(|(address, tokens)| GenesisValidator {
+                    address,
+                    staking_reward_address: gen_established_address(),
+                    tokens: token::Amount::whole(tokens),
+                    consensus_key: keypair.public.clone(),
+                    staking_reward_key: keypair.public.clone(),
+                    commission_rate: BasisPoints::new(0),
+                })
+                .collect();
+        pos::init_genesis_storage(
+            &mut storage,
+            &params,
+            validators.iter(),
+            Epoch(0),
+        );
+
+        let bond = Bond {
+            deltas: vec![(Epoch(0).into(), token::Amount::whole(500_000))]
+                .into_iter()
+                .collect(),
+        };
+        let bonds = Bonds::init_at_genesis(bond, Epoch(0));
+        let bond_id = BondId {
+            source: delegator(),
+            validator: validator_a(),
+        };
+        storage
+            .write(&bond_key(&bond_id), encode(&bonds))
+            .expect("write failed");
+        storage
+            .write(
+                &token::balance_key(&deposit_token_address(), &author()),
+                encode(&token::Amount::whole(1_000)),
+            )
+            .expect("write failed");
+        // Commit the writes to be able to iterate them
+        storage.commit().expect("commit failed");
+
+        storage
+    }
+
+    fn validate(
+        storage: &TestStorage,
+        write_log: &WriteLog,
+        tx_data: &[u8],
+        keys_changed: &[Key],
+        verifiers: &[Address],
+    ) -> bool {
+        let tx = Tx::new(vec![], Some(tx_data.to_vec()));
+        let ctx = Ctx::new(storage, write_log, &tx, VpGasMeter::new(0));
+        let vp = GovernanceVp { ctx };
+        let keys_changed = keys_changed.iter().cloned().collect();
+        let verifiers = verifiers.iter().cloned().collect();
+        vp.validate_tx(tx_data, &keys_changed, &verifiers)
+            .expect("validation failed")
+    }
+
+    /// Write a new proposal with the given deposit into the write log.
+    fn init_proposal(deposit: token::Amount) -> (WriteLog, Vec<Key>) {
+        let mut write_log = WriteLog::default();
+        let balance_key =
+            token::balance_key(&deposit_token_address(), &ADDRESS);
+        let index_key = grace_epoch_index_key(proposal().grace_epoch, 0);
+        write_log
+            .write(&proposal_key(0), encode(&proposal()))
+            .expect("write failed");
+        write_log.write(&index_key, vec![]).expect("write failed");
+        write_log
+            .write(&proposal_funds_key(0), encode(&deposit))
+            .expect("write failed");
+        write_log
+            .write(&balance_key, encode(&deposit))
+            .expect("write failed");
+        write_log
+            .write(&counter_key(), encode(&1_u64))
+            .expect("write failed");
+        let keys_changed = vec![
+            proposal_key(0),
+            index_key,
+            proposal_funds_key(0),
+            balance_key,
+            counter_key(),
+        ];
+        (write_log, keys_changed)
+    }
+
+    #[test]
+    fn test_submit_proposal() {
+        let storage = init_storage();
+        let deposit = GovParams::default().min_proposal_deposit;
+        let (write_log, keys_changed) = init_proposal(deposit);
+        assert!(validate(
+            &storage,
+            &write_log,
+            &[],
+            &keys_changed,
+            &[author()]
+        ));
+        // The author must sign the proposal
+        assert!(!validate(&storage, &write_log, &[], &keys_changed, &[]));
+
+        // The deposit must be at least the minimum deposit
+        let (write_log, keys_changed) =
+            init_proposal(token::Amount::whole(100));
+        assert!(!validate(
+            &storage,
+            &write_log,
+            &[],
+            &keys_changed,
+            &[author()]
+        ));
+
+        // The proposal must be added to the index with its grace epoch
+        let (mut write_log, mut keys_changed) = init_proposal(deposit);
+        let index_key = grace_epoch_index_key(proposal().grace_epoch, 0);
+        write_log.delete(&index_key).expect("delete failed");
+        keys_changed.retain(|key| key != &index_key);
+        assert!(!validate(
+            &storage,
+            &write_log,
+            &[],
+            &keys_changed,
+            &[author()]
+        ));
+        let wrong_index_key = grace_epoch_index_key(Epoch(5), 0);
+        write_log
+            .write(&wrong_index_key, vec![])
+            .expect("write failed");
+        keys_changed.push(wrong_index_key);
+        assert!(!validate(
+            &storage,
+            &write_log,
+            &[],
+            &keys_changed,
+            &[author()]
+        ));
+    }
+
+    #[test]
+    fn test_finalize_rejected_proposal() {
+        let mut storage = init_storage();
+        let deposit = GovParams::default().min_proposal_deposit;
+        let token = deposit_token_address();
+        let gov_balance_key = token::balance_key(&token, &ADDRESS);
+        let treasury_balance_key =
+            token::balance_key(&token, &treasury::ADDRESS);
+        let index_key = grace_epoch_index_key(proposal().grace_epoch, 0);
+        storage
+            .write(&proposal_key(0), encode(&proposal()))
+            .expect("write failed");
+        storage.write(&index_key, vec![]).expect("write failed");
+        storage
+            .write(&proposal_funds_key(0), encode(&deposit))
+            .expect("write failed");
+        storage
+            .write(&gov_balance_key, encode(&deposit))
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+
+        // The proposal is only due in its grace epoch
+        let due = proposals_to_finalize(&storage, Epoch(3))
+            .expect("reading proposals failed");
+        assert!(due.is_empty());
+        let due = proposals_to_finalize(&storage, Epoch(4))
+            .expect("reading proposals failed");
+        assert_eq!(due, vec![(0, proposal())]);
+
+        // The deposit of a rejected proposal goes to the treasury
+        transfer_deposit_to_treasury(&mut storage, 0)
+            .expect("transferring the deposit failed");
+        let gov_balance: Option<token::Amount> =
+            read(&storage, &gov_balance_key).expect("read failed");
+        let treasury_balance: Option<token::Amount> =
+            read(&storage, &treasury_balance_key).expect("read failed");
+        assert_eq!(gov_balance, Some(token::Amount::default()));
+        assert_eq!(treasury_balance, Some(deposit));
+
+        // Writing the result removes the proposal from the index
+        let result = ProposalResult {
+            tally: tally(&storage, 0, &proposal()).expect("tally failed"),
+            passed: false,
+            code_accepted: None,
+        };
+        write_result(&mut storage, 0, &proposal(), &result)
+            .expect("writing the result failed");
+        assert!(!storage.has_key(&index_key).expect("read failed").0);
+    }
+
+    #[test]
+    fn test_governance_authority() {
+        let mut storage = init_storage();
+        let mut params = GovParams::default();
+        params.min_voting_period = 1;
+        let mut write_log = WriteLog::default();
+        write_log
+            .write(&params_key(), encode(&params))
+            .expect("write failed");
+        let keys_changed = [params_key()];
+        let tx_data = 0_u64.try_to_vec().unwrap();
+
+        // The parameters cannot be changed without a passed proposal
+        assert!(!validate(
+            &storage,
+            &write_log,
+            &tx_data,
+            &keys_changed,
+            &[]
+        ));
+
+        storage
+            .write(&proposal_pending_key(0), vec![])
+            .expect("write failed");
+        assert!(validate(&storage, &write_log, &tx_data, &keys_changed, &[]));
+        // The pending key is for a different proposal
+        let tx_data = 1_u64.try_to_vec().unwrap();
+        assert!(!validate(
+            &storage,
+            &write_log,
+            &tx_data,
+            &keys_changed,
+            &[]
+        ));
+    }
+
+    #[test]
+    fn test_tally() {
+        let mut storage = init_storage();
+        let params = GovParams::default();
+        storage
+            .write(&proposal_key(0), encode(&proposal()))
+            .expect("write failed");
+        storage
+            .write(
+                &proposal_vote_key(0, &validator_b()),
+                encode(&ProposalVote::Yay),
+            )
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+        let result = tally(&storage, 0, &proposal()).expect("tally failed");
+        // 1000 of 3000 voting power voted, which is below the quorum
+        assert_eq!(result.yay, 1_000);
+        assert!(!params.is_passed(&result));
+
+        // The delegator's vote overrides the validator's vote for its
+        // delegation
+        storage
+            .write(
+                &proposal_vote_key(0, &validator_a()),
+                encode(&ProposalVote::Yay),
+            )
+            .expect("write failed");
+        storage
+            .write(
+                &proposal_vote_key(0, &delegator()),
+                encode(&ProposalVote::Nay),
+            )
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+        let result = tally(&storage, 0, &proposal()).expect("tally failed");
+        assert_eq!((result.yay, result.nay, result.total), (2_500, 500, 3_000));
+        assert!(params.is_passed(&result));
+    }
+}
@@ -0,0 +1,191 @@
+//! Governance storage keys
+
+use super::ADDRESS;
+use crate::types::address::Address;
+use crate::types::storage::{DbKeySeg, Epoch, Key, KeySeg};
+
+const PARAMS_STORAGE_KEY: &str = "params";
+const COUNTER_STORAGE_KEY: &str = "counter";
+const PROPOSAL_STORAGE_KEY: &str = "proposal";
+const PROPOSAL_DATA_STORAGE_KEY: &str = "data";
+const PROPOSAL_CODE_STORAGE_KEY: &str = "code";
+const PROPOSAL_FUNDS_STORAGE_KEY: &str = "funds";
+const PROPOSAL_VOTE_STORAGE_KEY: &str = "vote";
+const PROPOSAL_RESULT_STORAGE_KEY: &str = "result";
+const PROPOSAL_PENDING_STORAGE_KEY: &str = "pending";
+const GRACE_EPOCH_STORAGE_KEY: &str = "grace_epoch";
+
+/// Is the given key a governance storage key?
+pub fn is_governance_key(key: &Key) -> bool {
+    match &key.segments.get(0) {
+        Some(DbKeySeg::AddressSeg(addr)) => addr == &ADDRESS,
+        _ => false,
+    }
+}
+
+/// Storage key for governance parameters.
+pub fn params_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&PARAMS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for governance parameters?
+pub fn is_params_key(key: &Key) -> bool {
+    *key == params_key()
+}
+
+/// Storage key for the counter of proposals, which is the ID of the next
+/// proposal.
+pub fn counter_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&COUNTER_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the counter of proposals?
+pub fn is_counter_key(key: &Key) -> bool {
+    *key == counter_key()
+}
+
+/// Storage key prefix for a proposal.
+fn proposal_prefix(id: u64) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&PROPOSAL_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for a proposal's field.
+fn proposal_field_key(id: u64, field: &str) -> Key {
+    proposal_prefix(id)
+        .push(&field.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the given field of a proposal? Returns the proposal ID.
+fn is_proposal_field_key(key: &Key, field: &str) -> Option<u64> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(id), DbKeySeg::StringSeg(key)]
+            if addr == &ADDRESS
+                && prefix == PROPOSAL_STORAGE_KEY
+                && key == field =>
+        {
+            id.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Storage key for a proposal's data.
+pub fn proposal_key(id: u64) -> Key {
+    proposal_field_key(id, PROPOSAL_DATA_STORAGE_KEY)
+}
+
+/// Is storage key for a proposal's data? Returns the proposal ID.
+pub fn is_proposal_key(key: &Key) -> Option<u64> {
+    is_proposal_field_key(key, PROPOSAL_DATA_STORAGE_KEY)
+}
+
+/// Storage key for the WASM code of a proposal.
+pub fn proposal_code_key(id: u64) -> Key {
+    proposal_field_key(id, PROPOSAL_CODE_STORAGE_KEY)
+}
+
+/// Is storage key for the WASM code of a proposal? Returns the proposal ID.
+pub fn is_proposal_code_key(key: &Key) -> Option<u64> {
+    is_proposal_field_key(key, PROPOSAL_CODE_STORAGE_KEY)
+}
+
+/// Storage key for the funds deposited with a proposal.
+pub fn proposal_funds_key(id: u64) -> Key {
+    proposal_field_key(id, PROPOSAL_FUNDS_STORAGE_KEY)
+}
+
+/// Is storage key for the funds deposited with a proposal? Returns the
+/// proposal ID.
+pub fn is_proposal_funds_key(key: &Key) -> Option<u64> {
+    is_proposal_field_key(key, PROPOSAL_FUNDS_STORAGE_KEY)
+}
+
+/// Storage key for the result of a proposal, written by the ledger when the
+/// proposal is finalized.
+pub fn proposal_result_key(id: u64) -> Key {
+    proposal_field_key(id, PROPOSAL_RESULT_STORAGE_KEY)
+}
+
+/// Is storage key for the result of a proposal? Returns the proposal ID.
+pub fn is_proposal_result_key(key: &Key) -> Option<u64> {
+    is_proposal_field_key(key, PROPOSAL_RESULT_STORAGE_KEY)
+}
+
+/// Storage key that is present only while the ledger executes the code of a
+/// passed proposal.
+pub fn proposal_pending_key(id: u64) -> Key {
+    proposal_field_key(id, PROPOSAL_PENDING_STORAGE_KEY)
+}
+
+/// Is storage key that is present only while the ledger executes the code of
+/// a passed proposal? Returns the proposal ID.
+pub fn is_proposal_pending_key(key: &Key) -> Option<u64> {
+    is_proposal_field_key(key, PROPOSAL_PENDING_STORAGE_KEY)
+}
+
+/// Storage key prefix for the index of the proposals that haven't been
+/// finalized yet by their grace epoch.
+pub fn grace_epoch_index_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&GRACE_EPOCH_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for a proposal in the grace epoch index. It's written with a
+/// new proposal and deleted by the ledger when the proposal is finalized.
+pub fn grace_epoch_index_key(grace_epoch: Epoch, id: u64) -> Key {
+    grace_epoch_index_prefix()
+        .push(&grace_epoch.0.to_string())
+        .expect("Cannot obtain a storage key")
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for a proposal in the grace epoch index? Returns the grace
+/// epoch and the proposal ID.
+pub fn is_grace_epoch_index_key(key: &Key) -> Option<(Epoch, u64)> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(epoch), DbKeySeg::StringSeg(id)]
+            if addr == &ADDRESS && prefix == GRACE_EPOCH_STORAGE_KEY =>
+        {
+            Some((Epoch(epoch.parse().ok()?), id.parse().ok()?))
+        }
+        _ => None,
+    }
+}
+
+/// Storage key prefix for the votes on a proposal.
+pub fn proposal_votes_prefix(id: u64) -> Key {
+    proposal_field_key(id, PROPOSAL_VOTE_STORAGE_KEY)
+}
+
+/// Storage key for a vote on a proposal.
+pub fn proposal_vote_key(id: u64, voter: &Address) -> Key {
+    proposal_votes_prefix(id)
+        .push(&voter.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for a vote on a proposal? Returns the proposal ID and the
+/// voter.
+pub fn is_proposal_vote_key(key: &Key) -> Option<(u64, &Address)> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(id), DbKeySeg::StringSeg(vote), DbKeySeg::AddressSeg(voter)]
+            if addr == &ADDRESS
+                && prefix == PROPOSAL_STORAGE_KEY
+                && vote == PROPOSAL_VOTE_STORAGE_KEY =>
+        {
+            id.parse().ok().map(|id| (id, voter))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,327 @@
+//! Governance native validity predicate.
+
+use std::collections::HashSet;
+
+use borsh::BorshDeserialize;
+use thiserror::Error;
+
+use super::{
+    counter_key, deposit_token_address, grace_epoch_index_key, is_counter_key,
+    is_grace_epoch_index_key, is_params_key, is_proposal_accepted,
+    is_proposal_code_key, is_proposal_funds_key, is_proposal_key,
+    is_proposal_pending_key, is_proposal_result_key, is_proposal_vote_key,
+    params_key, proposal_code_key, proposal_funds_key, proposal_key, GovParams,
+    Proposal, ADDRESS,
+};
+use crate::ledger::native_vp::{self, Ctx, NativeVp};
+use crate::ledger::parameters::{voting_power_from_delta, ProposalVote};
+use crate::ledger::pos::{
+    bonds_for_source_prefix, is_bond_key, validator_voting_power_key, Bonds,
+    ValidatorVotingPowers,
+};
+use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::types::address::{Address, InternalAddress};
+use crate::types::storage::{Epoch, Key};
+use crate::types::token;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(native_vp::Error),
+}
+
+/// Governance functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Governance validity predicate
+pub struct GovernanceVp<'a, DB, H>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H>,
+}
+
+impl<'a, DB, H> NativeVp for GovernanceVp<'a, DB, H>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    type Error = Error;
+
+    const ADDR: InternalAddress = InternalAddress::Governance;
+
+    fn validate_tx(
+        &self,
+        tx_data: &[u8],
+        keys_changed: &HashSet<Key>,
+        verifiers: &HashSet<Address>,
+    ) -> Result<bool> {
+        // The code of a passed proposal has governance authority
+        if is_proposal_accepted(&self.ctx, tx_data)? {
+            return Ok(true);
+        }
+        let params = match self.read_pre::<GovParams>(&params_key())? {
+            Some(params) => params,
+            None => return Ok(false),
+        };
+        let current_epoch = self.ctx.get_block_epoch()?;
+        for key in keys_changed {
+            let is_valid = if is_params_key(key) {
+                tracing::info!(
+                    "Governance parameters can only be changed by a proposal"
+                );
+                false
+            } else if is_counter_key(key) {
+                self.is_valid_counter_change(keys_changed)?
+            } else if let Some(id) = is_proposal_key(key) {
+                self.is_valid_proposal(
+                    id,
+                    &params,
+                    current_epoch,
+                    keys_changed,
+                    verifiers,
+                )?
+            } else if let Some(id) = is_proposal_code_key(key) {
+                self.is_valid_proposal_code(id, &params, keys_changed)?
+            } else if let Some(id) = is_proposal_funds_key(key) {
+                // The funds are checked with the new proposal
+                keys_changed.contains(&proposal_key(id))
+            } else if let Some((grace_epoch, id)) =
+                is_grace_epoch_index_key(key)
+            {
+                self.is_valid_grace_epoch_index(
+                    key,
+                    grace_epoch,
+                    id,
+                    keys_changed,
+                )?
+            } else if let Some((id, voter)) = is_proposal_vote_key(key) {
+                self.is_valid_vote(key, id, voter, current_epoch)?
+            } else if is_proposal_result_key(key).is_some()
+                || is_proposal_pending_key(key).is_some()
+            {
+                // Only the ledger may write these
+                false
+            } else if let Some(owner) = token::is_any_token_balance_key(key) {
+                owner == &ADDRESS && self.is_valid_balance_change(key)?
+            } else {
+                tracing::info!(
+                    "Governance unrecognized key change {} rejected",
+                    key
+                );
+                false
+            };
+            if !is_valid {
+                tracing::info!("Governance key change {} rejected", key);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<'a, DB, H> GovernanceVp<'a, DB, H>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    /// The proposal counter must be incremented by one for a new proposal.
+    fn is_valid_counter_change(
+        &self,
+        keys_changed: &HashSet<Key>,
+    ) -> Result<bool> {
+        let key = counter_key();
+        let pre = self.read_pre::<u64>(&key)?.unwrap_or_default();
+        let post = self.read_post::<u64>(&key)?;
+        Ok(post == pre.checked_add(1)
+            && keys_changed.contains(&proposal_key(pre)))
+    }
+
+    /// A new proposal must take the next ID, be signed by its author, have
+    /// valid epochs and content, be added to the grace epoch index and the
+    /// author must deposit at least the minimum deposit into the governance
+    /// account.
+    fn is_valid_proposal(
+        &self,
+        id: u64,
+        params: &GovParams,
+        current_epoch: Epoch,
+        keys_changed: &HashSet<Key>,
+        verifiers: &HashSet<Address>,
+    ) -> Result<bool> {
+        let key = proposal_key(id);
+        if self.ctx.has_key_pre(&key)? {
+            tracing::info!("Governance proposal {} cannot be modified", id);
+            return Ok(false);
+        }
+        let counter = self.read_pre::<u64>(&counter_key())?.unwrap_or_default();
+        let proposal = match self.read_post::<Proposal>(&key)? {
+            Some(proposal) => proposal,
+            None => return Ok(false),
+        };
+        let voting_period = proposal
+            .voting_end_epoch
+            .0
+            .checked_sub(proposal.voting_start_epoch.0);
+        let is_valid_period = matches!(
+            voting_period,
+            Some(period) if period >= params.min_voting_period
+                && period <= params.max_voting_period
+        );
+        if id != counter
+            || !verifiers.contains(&proposal.author)
+            || current_epoch >= proposal.voting_start_epoch
+            || !is_valid_period
+            || proposal.grace_epoch < proposal.voting_end_epoch
+            || proposal.content.len() as u64 > params.max_proposal_content_size
+            || !keys_changed
+                .contains(&grace_epoch_index_key(proposal.grace_epoch, id))
+        {
+            return Ok(false);
+        }
+
+        // Check the deposit
+        let funds_key = proposal_funds_key(id);
+        let funds = match self.read_post::<token::Amount>(&funds_key)? {
+            Some(funds) if keys_changed.contains(&funds_key) => funds,
+            _ => return Ok(false),
+        };
+        let balance_key =
+            token::balance_key(&deposit_token_address(), &ADDRESS);
+        let pre_balance = self
+            .read_pre::<token::Amount>(&balance_key)?
+            .unwrap_or_default();
+        let post_balance = self
+            .read_post::<token::Amount>(&balance_key)?
+            .unwrap_or_default();
+        let deposited = post_balance.checked_sub(&pre_balance);
+        Ok(funds >= params.min_proposal_deposit
+            && matches!(deposited, Some(deposited) if deposited >= funds))
+    }
+
+    /// The governance account may receive tokens, but it cannot spend them.
+    /// The deposits are transferred by the ledger when the proposals are
+    /// finalized.
+    fn is_valid_balance_change(&self, key: &Key) -> Result<bool> {
+        let pre = self.read_pre::<token::Amount>(key)?.unwrap_or_default();
+        let post = self.read_post::<token::Amount>(key)?.unwrap_or_default();
+        Ok(post >= pre)
+    }
+
+    /// A proposal can only be added to the grace epoch index together with a
+    /// new proposal and with its grace epoch. Only the ledger may remove it.
+    fn is_valid_grace_epoch_index(
+        &self,
+        key: &Key,
+        grace_epoch: Epoch,
+        id: u64,
+        keys_changed: &HashSet<Key>,
+    ) -> Result<bool> {
+        let proposal = self.read_post::<Proposal>(&proposal_key(id))?;
+        Ok(keys_changed.contains(&proposal_key(id))
+            && self.ctx.has_key_post(key)?
+            && matches!(proposal, Some(proposal)
+                if proposal.grace_epoch == grace_epoch))
+    }
+
+    /// A proposal's code can only be set together with a new proposal and it
+    /// must not exceed the maximum code size.
+    fn is_valid_proposal_code(
+        &self,
+        id: u64,
+        params: &GovParams,
+        keys_changed: &HashSet<Key>,
+    ) -> Result<bool> {
+        let code = self.read_post::<Vec<u8>>(&proposal_code_key(id))?;
+        Ok(keys_changed.contains(&proposal_key(id))
+            && matches!(code, Some(code)
+                if code.len() as u64 <= params.max_proposal_code_size))
+    }
+
+    /// A vote can be cast or changed within the voting period by a voter with
+    /// some voting power. The voter's VP is triggered by its address in the
+    /// vote key.
+    fn is_valid_vote(
+        &self,
+        key: &Key,
+        id: u64,
+        voter: &Address,
+        current_epoch: Epoch,
+    ) -> Result<bool> {
+        let proposal = match self.read_pre::<Proposal>(&proposal_key(id))? {
+            Some(proposal) => proposal,
+            None => return Ok(false),
+        };
+        if current_epoch < proposal.voting_start_epoch
+            || current_epoch >= proposal.voting_end_epoch
+        {
+            tracing::info!(
+                "Governance proposal {} is not in its voting period",
+                id
+            );
+            return Ok(false);
+        }
+        if self.read_post::<ProposalVote>(key)?.is_none() {
+            return Ok(false);
+        }
+        self.has_voting_power(voter, proposal.voting_start_epoch)
+    }
+
+    /// A voter has some voting power if it's a validator with some voting
+    /// power or if it has delegations in the given epoch.
+    fn has_voting_power(&self, voter: &Address, epoch: Epoch) -> Result<bool> {
+        let validator_power = self
+            .read_pre::<ValidatorVotingPowers>(&validator_voting_power_key(
+                voter,
+            ))?
+            .and_then(|powers| powers.get(epoch))
+            .map(voting_power_from_delta)
+            .unwrap_or_default();
+        if validator_power > 0 {
+            return Ok(true);
+        }
+        let mut iter = self.ctx.iter_prefix(&bonds_for_source_prefix(voter))?;
+        while let Some((key, value)) = self.ctx.iter_pre_next(&mut iter)? {
+            let is_delegation = Key::parse(&key)
+                .ok()
+                .and_then(|key| is_bond_key(&key))
+                .map(|bond_id| bond_id.source != bond_id.validator)
+                .unwrap_or_default();
+            if !is_delegation {
+                continue;
+            }
+            let has_tokens = Bonds::try_from_slice(&value[..])
+                .ok()
+                .and_then(|bonds| bonds.get(epoch))
+                .map(|bond| u64::from(bond.sum()) > 0)
+                .unwrap_or_default();
+            if has_tokens {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn read_pre<T: BorshDeserialize>(&self, key: &Key) -> Result<Option<T>> {
+        Ok(self
+            .ctx
+            .read_pre(key)?
+            .and_then(|bytes| T::try_from_slice(&bytes[..]).ok()))
+    }
+
+    fn read_post<T: BorshDeserialize>(&self, key: &Key) -> Result<Option<T>> {
+        Ok(self
+            .ctx
+            .read_post(key)?
+            .and_then(|bytes| T::try_from_slice(&bytes[..]).ok()))
+    }
+}
+
+impl From<native_vp::Error> for Error {
+    fn from(err: native_vp::Error) -> Self {
+        Self::NativeVpError(err)
+    }
+}
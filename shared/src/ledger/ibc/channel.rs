@@ -1,5 +1,7 @@
 //! IBC validity predicate for channel module
 
+use std::convert::TryFrom;
+
 use borsh::BorshDeserialize;
 use ibc::ics02_client::client_consensus::AnyConsensusState;
 use ibc::ics02_client::client_state::AnyClientState;
@@ -32,9 +34,8 @@ use super::{Ibc, StateChange};
 use crate::ledger::native_vp::Error as NativeVpError;
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
 use crate::types::ibc::{
-    ChannelCloseConfirmData, ChannelCloseInitData, ChannelOpenAckData,
-    ChannelOpenConfirmData, ChannelOpenTryData, Error as IbcDataError,
-    TimeoutData,
+    ChannelCloseConfirmData, ChannelOpenAckData, ChannelOpenConfirmData,
+    ChannelOpenTryData, Error as IbcDataError, IbcMessage, TimeoutData,
 };
 use crate::types::storage::Key;
 
@@ -211,18 +212,22 @@ where
                         port_channel_id.port_id, port_channel_id.channel_id,
                     )));
                 }
-                match TimeoutData::try_from_slice(tx_data) {
-                    Ok(data) => self.validate_commitment_absence(data),
-                    Err(_) => {
-                        match ChannelCloseInitData::try_from_slice(tx_data) {
-                            Ok(_) => Ok(()),
-                            Err(_) => self.verify_channel_close_proof(
-                                port_channel_id,
-                                channel,
-                                tx_data,
-                            ),
-                        }
+                match IbcMessage::decode(tx_data)? {
+                    IbcMessage::Timeout(data) => {
+                        self.validate_commitment_absence(data)
                     }
+                    IbcMessage::ChannelCloseInit(_) => Ok(()),
+                    IbcMessage::ChannelCloseConfirm(data) => self
+                        .verify_channel_close_proof(
+                            port_channel_id,
+                            channel,
+                            data,
+                        ),
+                    _ => Err(Error::InvalidStateChange(format!(
+                        "The state change of the channel is invalid: Port {}, \
+                         Channel {}",
+                        port_channel_id.port_id, port_channel_id.channel_id,
+                    ))),
                 }
             }
             _ => Err(Error::InvalidStateChange(format!(
@@ -261,7 +266,7 @@ where
         channel: &ChannelEnd,
         tx_data: &[u8],
     ) -> Result<()> {
-        let data = ChannelOpenTryData::try_from_slice(tx_data)?;
+        let data = ChannelOpenTryData::try_from(IbcMessage::decode(tx_data)?)?;
         let expected_my_side = Counterparty::new(port_channel_id.port_id, None);
 
         self.verify_proofs(
@@ -278,7 +283,7 @@ where
         channel: &ChannelEnd,
         tx_data: &[u8],
     ) -> Result<()> {
-        let data = ChannelOpenAckData::try_from_slice(tx_data)?;
+        let data = ChannelOpenAckData::try_from(IbcMessage::decode(tx_data)?)?;
         let expected_my_side = Counterparty::new(
             port_channel_id.port_id.clone(),
             Some(port_channel_id.channel_id.clone()),
@@ -298,7 +303,8 @@ where
         channel: &ChannelEnd,
         tx_data: &[u8],
     ) -> Result<()> {
-        let data = ChannelOpenConfirmData::try_from_slice(tx_data)?;
+        let data =
+            ChannelOpenConfirmData::try_from(IbcMessage::decode(tx_data)?)?;
         let expected_my_side = Counterparty::new(
             port_channel_id.port_id.clone(),
             Some(port_channel_id.channel_id.clone()),
@@ -316,9 +322,8 @@ where
         &self,
         port_channel_id: &PortChannelId,
         channel: &ChannelEnd,
-        tx_data: &[u8],
+        data: ChannelCloseConfirmData,
     ) -> Result<()> {
-        let data = ChannelCloseConfirmData::try_from_slice(tx_data)?;
         let expected_my_side = Counterparty::new(
             port_channel_id.port_id.clone(),
             Some(port_channel_id.channel_id.clone()),
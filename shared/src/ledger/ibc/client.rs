@@ -1,22 +1,35 @@
 //! IBC validity predicate for client module
 
+use std::convert::TryFrom;
+
 use borsh::BorshDeserialize;
 use ibc::ics02_client::client_consensus::AnyConsensusState;
 use ibc::ics02_client::client_def::{AnyClient, ClientDef};
-use ibc::ics02_client::client_state::AnyClientState;
+use ibc::ics02_client::client_state::{AnyClientState, ClientState};
 use ibc::ics02_client::client_type::ClientType;
 use ibc::ics02_client::context::ClientReader;
+use ibc::ics02_client::header::{AnyHeader, Header};
 use ibc::ics02_client::height::Height;
+use ibc::ics07_tendermint::client_state::ClientState as TmClientState;
+use ibc::ics07_tendermint::consensus_state::ConsensusState as TmConsensusState;
+use ibc::ics07_tendermint::header::Header as TmHeader;
 use ibc::ics24_host::identifier::ClientId;
+use tendermint::account::Id as TmAccountId;
+use tendermint::block::CommitSig;
+use tendermint::time::Time as TmTime;
+use tendermint::validator::Set as TmValidatorSet;
+use tendermint::vote::{SignedVote, Type as VoteType, ValidatorIndex, Vote};
 use thiserror::Error;
 
 use super::storage::{
-    client_counter_key, client_state_key, client_type_key, consensus_state_key,
+    client_counter_key, client_frozen_key, client_state_key, client_type_key,
+    consensus_state_key,
 };
 use super::{Ibc, StateChange};
 use crate::ledger::storage::{self, StorageHasher};
 use crate::types::ibc::{
-    ClientUpdateData, ClientUpgradeData, Error as IbcDataError,
+    ClientMisbehaviourData, ClientUpdateData, ClientUpgradeData,
+    Error as IbcDataError, IbcMessage,
 };
 
 #[allow(missing_docs)]
@@ -28,6 +41,10 @@ pub enum Error {
     InvalidClient(String),
     #[error("Header error: {0}")]
     InvalidHeader(String),
+    #[error("Misbehaviour error: {0}")]
+    InvalidMisbehaviour(String),
+    #[error("Frozen client error: {0}")]
+    FrozenClient(String),
     #[error("Proof verification error: {0}")]
     ProofVerificationFailure(String),
     #[error("Decoding TX data error: {0}")]
@@ -109,16 +126,23 @@ where
         tx_data: &[u8],
     ) -> Result<()> {
         // check the type of data in tx_data
-        match ClientUpdateData::try_from_slice(tx_data) {
-            Ok(data) => {
+        match IbcMessage::decode(tx_data)? {
+            IbcMessage::ClientUpdate(data) => {
                 // "UpdateClient"
                 self.verify_update_client(client_id, data)
             }
-            Err(_) => {
+            IbcMessage::ClientUpgrade(data) => {
                 // "UpgradeClient"
-                let data = ClientUpgradeData::try_from_slice(tx_data)?;
                 self.verify_upgrade_client(client_id, data)
             }
+            IbcMessage::ClientMisbehaviour(data) => {
+                // "SubmitMisbehaviour"
+                self.verify_client_misbehaviour(client_id, data)
+            }
+            _ => Err(Error::InvalidStateChange(format!(
+                "The state change of the client is invalid: ID {}",
+                client_id
+            ))),
         }
     }
 
@@ -133,6 +157,7 @@ where
                 data.client_id, client_id,
             )));
         }
+        self.validate_client_not_frozen(client_id)?;
 
         // check the posterior states
         let client_state = ClientReader::client_state(self, client_id)
@@ -199,6 +224,7 @@ where
                 data.client_id, client_id,
             )));
         }
+        self.validate_client_not_frozen(client_id)?;
 
         // check the posterior states
         let client_state_post = ClientReader::client_state(self, client_id)
@@ -252,6 +278,167 @@ where
         }
     }
 
+    fn verify_client_misbehaviour(
+        &self,
+        client_id: &ClientId,
+        data: ClientMisbehaviourData,
+    ) -> Result<()> {
+        if data.client_id != *client_id {
+            return Err(Error::InvalidClient(format!(
+                "The client ID is mismatched: {} in the tx data, {} in the key",
+                data.client_id, client_id,
+            )));
+        }
+
+        // check the prior client state
+        if let Some(height) = self.client_frozen_height_pre(client_id)? {
+            return Err(Error::FrozenClient(format!(
+                "The client has already been frozen: ID {}, Height {}",
+                client_id, height
+            )));
+        }
+        let client_state = self.client_state_pre(client_id)?;
+        if client_state.is_frozen() {
+            return Err(Error::FrozenClient(format!(
+                "The client state has already been frozen: ID {}",
+                client_id
+            )));
+        }
+        // the client state itself is kept as it is
+        if ClientReader::client_state(self, client_id).as_ref()
+            != Some(&client_state)
+        {
+            return Err(Error::InvalidClient(format!(
+                "The client state shouldn't be changed: ID {}",
+                client_id
+            )));
+        }
+
+        // verify the evidence
+        let height = data
+            .height()
+            .map_err(|e| Error::InvalidMisbehaviour(e.to_string()))?;
+        let (header1, header2, client_state) =
+            match (&data.header1, &data.header2, &client_state) {
+                (
+                    AnyHeader::Tendermint(header1),
+                    AnyHeader::Tendermint(header2),
+                    AnyClientState::Tendermint(client_state),
+                ) => (header1, header2, client_state),
+                _ => {
+                    return Err(Error::InvalidMisbehaviour(format!(
+                        "The misbehaviour can't be verified for the client \
+                         type: ID {}, Type {}",
+                        client_id,
+                        client_state.client_type()
+                    )));
+                }
+            };
+        // the headers are verified at the time of the current block
+        let now = match self.ctx.storage.get_block_header().0 {
+            Some(block_header) => block_header.time,
+            None => {
+                return Err(Error::InvalidMisbehaviour(
+                    "The header of the current block doesn't exist".to_owned(),
+                ));
+            }
+        };
+        // both headers have to be valid for the client
+        for header in [header1, header2].iter() {
+            let trusted =
+                self.consensus_state_pre(client_id, header.trusted_height)?;
+            let trusted = match trusted {
+                AnyConsensusState::Tendermint(trusted) => trusted,
+                _ => {
+                    return Err(Error::InvalidClient(format!(
+                        "The trusted consensus state isn't for Tendermint: \
+                         ID {}, Height {}",
+                        client_id, header.trusted_height
+                    )));
+                }
+            };
+            verify_tendermint_header(client_state, &trusted, header, now)
+                .map_err(|e| {
+                    Error::InvalidMisbehaviour(format!(
+                        "The header is invalid: ID {}, Height {}, {}",
+                        client_id, height, e
+                    ))
+                })?;
+        }
+        // the headers have to commit to different blocks
+        let (consensus_state1, consensus_state2) = data.consensus_states();
+        if consensus_state1 == consensus_state2 {
+            return Err(Error::InvalidMisbehaviour(format!(
+                "The headers don't conflict with each other: ID {}, Height {}",
+                client_id, height
+            )));
+        }
+
+        // check the client is frozen at the height
+        match self.client_frozen_height(client_id)? {
+            Some(frozen_height) if frozen_height == height => Ok(()),
+            _ => Err(Error::FrozenClient(format!(
+                "The client should be frozen: ID {}, Height {}",
+                client_id, height
+            ))),
+        }
+    }
+
+    /// Check that the client hasn't been frozen by a misbehaviour. The
+    /// operations relying on a frozen client are refused.
+    pub(super) fn validate_client_not_frozen(
+        &self,
+        client_id: &ClientId,
+    ) -> Result<()> {
+        let frozen_height = match self.client_frozen_height_pre(client_id)? {
+            Some(height) => Some(height),
+            None => self.client_frozen_height(client_id)?,
+        };
+        if let Some(height) = frozen_height {
+            return Err(Error::FrozenClient(format!(
+                "The client has been frozen: ID {}, Height {}",
+                client_id, height
+            )));
+        }
+        match ClientReader::client_state(self, client_id) {
+            Some(client_state) if client_state.is_frozen() => {
+                Err(Error::FrozenClient(format!(
+                    "The client state has been frozen: ID {}",
+                    client_id
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn client_frozen_height(
+        &self,
+        client_id: &ClientId,
+    ) -> Result<Option<Height>> {
+        let key = client_frozen_key(client_id);
+        match self.ctx.read_post(&key) {
+            Ok(value) => decode_frozen_height(client_id, value),
+            Err(e) => Err(Error::InvalidClient(format!(
+                "Reading the frozen height failed: ID {}, {}",
+                client_id, e
+            ))),
+        }
+    }
+
+    fn client_frozen_height_pre(
+        &self,
+        client_id: &ClientId,
+    ) -> Result<Option<Height>> {
+        let key = client_frozen_key(client_id);
+        match self.ctx.read_pre(&key) {
+            Ok(value) => decode_frozen_height(client_id, value),
+            Err(e) => Err(Error::InvalidClient(format!(
+                "Reading the prior frozen height failed: ID {}, {}",
+                client_id, e
+            ))),
+        }
+    }
+
     fn client_state_pre(&self, client_id: &ClientId) -> Result<AnyClientState> {
         let key = client_state_key(client_id);
         match self.ctx.read_pre(&key) {
@@ -343,6 +530,194 @@ where
     }
 }
 
+/// Verify a Tendermint header of a misbehaviour against the consensus state
+/// which the client has trusted at the header's trusted height. The header
+/// has to be committed by more than 2/3 of the voting power of its validator
+/// set and by at least 1/3 of the voting power of the trusted validator set,
+/// which is the default trust level of the Tendermint client. The trusted
+/// consensus state has to be within the client's trusting period at the given
+/// current time, and the header time has to be after the trusted time and
+/// within the client's maximum clock drift from the current time.
+fn verify_tendermint_header(
+    client_state: &TmClientState,
+    trusted: &TmConsensusState,
+    header: &TmHeader,
+    now: TmTime,
+) -> std::result::Result<(), String> {
+    let signed_header = &header.signed_header;
+    if signed_header.header.chain_id.as_str() != client_state.chain_id.as_str()
+    {
+        return Err(format!(
+            "The chain ID is mismatched: {} in the header, {} in the client",
+            signed_header.header.chain_id, client_state.chain_id
+        ));
+    }
+    if let Ok(elapsed) = now.duration_since(trusted.timestamp) {
+        if elapsed >= client_state.trusting_period {
+            return Err(format!(
+                "The trusted consensus state has expired: trusted at {}, now \
+                 {}",
+                trusted.timestamp, now
+            ));
+        }
+    }
+    let header_time = signed_header.header.time;
+    if header_time <= trusted.timestamp {
+        return Err(format!(
+            "The header time {} isn't after the trusted time {}",
+            header_time, trusted.timestamp
+        ));
+    }
+    if let Ok(drift) = header_time.duration_since(now) {
+        if drift > client_state.max_clock_drift {
+            return Err(format!(
+                "The header time {} exceeds the maximum clock drift from the \
+                 current time {}",
+                header_time, now
+            ));
+        }
+    }
+    if header.trusted_height >= header.height() {
+        return Err(format!(
+            "The header isn't newer than the trusted height {}",
+            header.trusted_height
+        ));
+    }
+    if header.trusted_validator_set.hash() != trusted.next_validators_hash {
+        return Err(
+            "The trusted validator set doesn't match the trusted consensus \
+             state"
+                .to_owned(),
+        );
+    }
+    if header.validator_set.hash() != signed_header.header.validators_hash {
+        return Err(
+            "The validator set doesn't match the header's validators hash"
+                .to_owned(),
+        );
+    }
+    let commit = &signed_header.commit;
+    if commit.height != signed_header.header.height
+        || commit.block_id.hash != signed_header.header.hash()
+    {
+        return Err("The commit isn't for the header".to_owned());
+    }
+
+    let mut signers: Vec<TmAccountId> = vec![];
+    for (index, commit_sig) in commit.signatures.iter().enumerate() {
+        // only the votes for the block count
+        let (validator_address, timestamp, signature) = match commit_sig {
+            CommitSig::BlockIdFlagCommit {
+                validator_address,
+                timestamp,
+                signature,
+            } => (*validator_address, *timestamp, signature.clone()),
+            _ => continue,
+        };
+        if signers.contains(&validator_address) {
+            return Err(format!(
+                "The validator signed the commit twice: {}",
+                validator_address
+            ));
+        }
+        let validator = header
+            .validator_set
+            .validators()
+            .iter()
+            .find(|validator| validator.address == validator_address)
+            .ok_or_else(|| {
+                format!(
+                    "The signer isn't in the validator set: {}",
+                    validator_address
+                )
+            })?;
+        let validator_index = ValidatorIndex::try_from(index)
+            .map_err(|e| format!("The validator index is invalid: {}", e))?;
+        let vote = Vote {
+            vote_type: VoteType::Precommit,
+            height: commit.height,
+            round: commit.round,
+            block_id: Some(commit.block_id),
+            timestamp: Some(timestamp),
+            validator_address,
+            validator_index,
+            signature,
+        };
+        let signed_vote = SignedVote::new(
+            vote.clone(),
+            signed_header.header.chain_id.clone(),
+            vote.validator_address,
+            vote.signature,
+        );
+        validator
+            .verify_signature(
+                &signed_vote.sign_bytes(),
+                signed_vote.signature(),
+            )
+            .map_err(|e| {
+                format!(
+                    "The signature of the validator is invalid: {}, {}",
+                    validator_address, e
+                )
+            })?;
+        signers.push(validator_address);
+    }
+
+    let (total, signed) = voting_power(&header.validator_set, &signers);
+    if signed * 3 <= total * 2 {
+        return Err(format!(
+            "The header isn't committed by more than 2/3 of the voting power: \
+             {} of {}",
+            signed, total
+        ));
+    }
+    let (total, signed) = voting_power(&header.trusted_validator_set, &signers);
+    if signed * 3 < total {
+        return Err(format!(
+            "The header isn't committed by at least 1/3 of the trusted voting \
+             power: {} of {}",
+            signed, total
+        ));
+    }
+    Ok(())
+}
+
+/// Returns the total voting power of the validator set and the voting power
+/// of the given signers in it
+fn voting_power(
+    validator_set: &TmValidatorSet,
+    signers: &[TmAccountId],
+) -> (u128, u128) {
+    validator_set.validators().iter().fold(
+        (0, 0),
+        |(total, signed), validator| {
+            let power = u128::from(validator.power());
+            if signers.contains(&validator.address) {
+                (total + power, signed + power)
+            } else {
+                (total + power, signed)
+            }
+        },
+    )
+}
+
+fn decode_frozen_height(
+    client_id: &ClientId,
+    value: Option<Vec<u8>>,
+) -> Result<Option<Height>> {
+    match value {
+        Some(value) => {
+            Height::try_from_slice(&value[..]).map(Some).map_err(|e| {
+                Error::InvalidClient(format!(
+                    "Decoding the frozen height failed: ID {}, {}",
+                    client_id, e
+                ))
+            })
+        }
+        None => Ok(None),
+    }
+}
+
 impl From<IbcDataError> for Error {
     fn from(err: IbcDataError) -> Self {
         Self::InvalidIbcData(err)
@@ -1,5 +1,7 @@
 //! IBC validity predicate for connection module
 
+use std::convert::TryFrom;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 use ibc::ics02_client::client_consensus::{AnyConsensusState, ConsensusState};
 use ibc::ics02_client::client_state::AnyClientState;
@@ -23,7 +25,7 @@ use crate::ledger::storage::{self, StorageHasher};
 use crate::types::address::{Address, InternalAddress};
 use crate::types::ibc::{
     ConnectionOpenAckData, ConnectionOpenConfirmData, ConnectionOpenTryData,
-    Error as IbcDataError,
+    Error as IbcDataError, IbcMessage,
 };
 use crate::types::storage::{BlockHeight, Epoch, Key, KeySeg};
 
@@ -79,6 +81,8 @@ where
                 conn_id
             ))
         })?;
+        self.validate_client_not_frozen(conn.client_id())
+            .map_err(|e| Error::InvalidClient(e.to_string()))?;
 
         match self.get_connection_state_change(&conn_id)? {
             StateChange::Created => {
@@ -163,7 +167,8 @@ where
         conn: ConnectionEnd,
         tx_data: &[u8],
     ) -> Result<()> {
-        let data = ConnectionOpenTryData::try_from_slice(tx_data)?;
+        let data =
+            ConnectionOpenTryData::try_from(IbcMessage::decode(tx_data)?)?;
 
         let client_id = conn.client_id().clone();
         let counterpart_client_id = conn.counterparty().client_id().clone();
@@ -195,7 +200,8 @@ where
         conn: ConnectionEnd,
         tx_data: &[u8],
     ) -> Result<()> {
-        let data = ConnectionOpenAckData::try_from_slice(tx_data)?;
+        let data =
+            ConnectionOpenAckData::try_from(IbcMessage::decode(tx_data)?)?;
 
         // version check
         if !conn.versions().contains(&data.version) {
@@ -246,7 +252,8 @@ where
         conn: ConnectionEnd,
         tx_data: &[u8],
     ) -> Result<()> {
-        let data = ConnectionOpenConfirmData::try_from_slice(tx_data)?;
+        let data =
+            ConnectionOpenConfirmData::try_from(IbcMessage::decode(tx_data)?)?;
 
         // expected counterpart connection
         let expected_conn = ConnectionEnd::new(
@@ -314,8 +321,8 @@ where
     }
 
     fn host_current_height(&self) -> Height {
-        let epoch = self.ctx.storage.get_current_epoch().0.0;
-        let height = self.ctx.storage.get_block_height().0.0;
+        let epoch = self.ctx.storage.get_current_epoch().0 .0;
+        let height = self.ctx.storage.get_block_height().0 .0;
         Height::new(epoch, height)
     }
 
@@ -7,6 +7,7 @@ mod packet;
 mod port;
 mod sequence;
 pub mod storage;
+pub mod token;
 
 use std::collections::HashSet;
 
@@ -44,6 +45,8 @@ pub enum Error {
     PacketError(packet::Error),
     #[error("Sequence validation error: {0}")]
     SequenceError(sequence::Error),
+    #[error("Token transfer validation error: {0}")]
+    TokenError(token::Error),
 }
 
 /// IBC functions result
@@ -110,6 +113,7 @@ where
         _verifiers: &HashSet<Address>,
     ) -> Result<bool> {
         let mut clients = HashSet::new();
+        let mut has_packet_change = false;
 
         for key in keys_changed {
             match ibc_prefix(key) {
@@ -146,9 +150,13 @@ where
                     self.validate_sequence_ack(key, tx_data)?
                 }
                 IbcPrefix::Commitment => {
+                    has_packet_change = true;
                     self.validate_commitment(key, tx_data)?
                 }
-                IbcPrefix::Receipt => self.validate_receipt(key, tx_data)?,
+                IbcPrefix::Receipt => {
+                    has_packet_change = true;
+                    self.validate_receipt(key, tx_data)?
+                }
                 IbcPrefix::Ack => self.validate_ack(key)?,
                 IbcPrefix::Unknown => {
                     return Err(Error::KeyError(format!(
@@ -159,6 +167,12 @@ where
             };
         }
 
+        if has_packet_change {
+            // a packet on the transfer port should move the tokens
+            token::validate_transfer(&self.ctx, tx_data)
+                .map_err(Error::TokenError)?;
+        }
+
         Ok(true)
     }
 }
@@ -269,11 +283,11 @@ mod tests {
     use std::time::Duration;
 
     use borsh::ser::BorshSerialize;
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
     use ibc::ics02_client::client_consensus::ConsensusState;
     use ibc::ics02_client::client_state::ClientState;
     use ibc::ics02_client::client_type::ClientType;
-    use ibc::ics02_client::header::AnyHeader;
+    use ibc::ics02_client::header::{AnyHeader, Header};
     use ibc::ics03_connection::connection::{
         ConnectionEnd, Counterparty as ConnCounterparty, State as ConnState,
     };
@@ -282,11 +296,15 @@ mod tests {
         ChannelEnd, Counterparty as ChanCounterparty, Order, State as ChanState,
     };
     use ibc::ics04_channel::packet::{Packet, Sequence};
+    use ibc::ics07_tendermint::client_state::ClientState as TmClientState;
+    use ibc::ics07_tendermint::consensus_state::ConsensusState as IbcTmConsensusState;
+    use ibc::ics07_tendermint::header::Header as IbcTmHeader;
     use ibc::ics23_commitment::commitment::{
         CommitmentPrefix, CommitmentProofBytes,
     };
     use ibc::ics24_host::identifier::{
-        ChannelId, ClientId, ConnectionId, PortChannelId, PortId,
+        ChainId as IbcChainId, ChannelId, ClientId, ConnectionId,
+        PortChannelId, PortId,
     };
     use ibc::mock::client_state::{MockClientState, MockConsensusState};
     use ibc::mock::header::MockHeader;
@@ -294,10 +312,10 @@ mod tests {
     use ibc::Height;
     use sha2::Digest;
     use storage::{
-        ack_key, capability_key, channel_key, client_state_key,
-        client_type_key, commitment_key, connection_key, consensus_state_key,
-        next_sequence_ack_key, next_sequence_recv_key, next_sequence_send_key,
-        port_key, receipt_key,
+        ack_key, capability_key, channel_key, client_frozen_key,
+        client_state_key, client_type_key, commitment_key, connection_key,
+        consensus_state_key, next_sequence_ack_key, next_sequence_recv_key,
+        next_sequence_send_key, port_key, receipt_key,
     };
     use tendermint::account::Id as TmAccountId;
     use tendermint::block::header::{Header as TmHeader, Version as TmVersion};
@@ -305,6 +323,11 @@ mod tests {
     use tendermint::chain::Id as TmChainId;
     use tendermint::hash::{AppHash, Hash as TmHash};
     use tendermint::time::Time as TmTime;
+    use tendermint_testgen::light_block::TmLightBlock;
+    use tendermint_testgen::{
+        Commit as TestgenCommit, Generator, Header as TestgenHeader,
+        LightBlock as TestgenLightBlock, Validator as TestgenValidator,
+    };
 
     use super::*;
     use crate::ledger::gas::VpGasMeter;
@@ -313,10 +336,10 @@ mod tests {
     use crate::proto::Tx;
     use crate::types::ibc::{
         ChannelOpenAckData, ChannelOpenConfirmData, ChannelOpenInitData,
-        ChannelOpenTryData, ClientUpdateData, ConnectionOpenAckData,
-        ConnectionOpenConfirmData, ConnectionOpenInitData,
-        ConnectionOpenTryData, PacketAckData, PacketReceiptData,
-        PacketSendData,
+        ChannelOpenTryData, ClientMisbehaviourData, ClientUpdateData,
+        ConnectionOpenAckData, ConnectionOpenConfirmData,
+        ConnectionOpenInitData, ConnectionOpenTryData, IbcMessage,
+        PacketAckData, PacketReceiptData, PacketSendData,
     };
     use crate::types::storage::KeySeg;
 
@@ -580,10 +603,11 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data =
-            ClientUpdateData::new(client_id, vec![AnyHeader::from(header)])
-                .try_to_vec()
-                .expect("encoding failed");
+        let data =
+            ClientUpdateData::new(client_id, vec![AnyHeader::from(header)]);
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -601,6 +625,372 @@ mod tests {
         );
     }
 
+    const TM_CHAIN_ID: &str = "test_chain";
+    const TM_REVISION: u64 = 1;
+
+    fn get_tm_validators(ids: &[&str]) -> Vec<TestgenValidator> {
+        ids.iter()
+            .map(|id| TestgenValidator::new(id).voting_power(50))
+            .collect()
+    }
+
+    /// Generate a Tendermint block at the height signed by the validators
+    fn generate_tm_block(
+        validators: &[TestgenValidator],
+        height: u64,
+        time: u64,
+    ) -> TmLightBlock {
+        let chain_id =
+            IbcChainId::new(TM_CHAIN_ID.to_owned(), TM_REVISION).to_string();
+        let header = TestgenHeader::new(validators)
+            .chain_id(&chain_id)
+            .height(height)
+            .time(time);
+        let commit = TestgenCommit::new(header.clone(), 1);
+        TestgenLightBlock::new(header, commit)
+            .generate()
+            .expect("Generating a Tendermint block shouldn't fail")
+    }
+
+    /// Generate a header of a Tendermint client trusting the block
+    fn generate_tm_client_header(
+        block: TmLightBlock,
+        trusted_block: &TmLightBlock,
+    ) -> AnyHeader {
+        let trusted_height = Height::new(
+            TM_REVISION,
+            trusted_block.signed_header.header.height.value(),
+        );
+        AnyHeader::from(IbcTmHeader {
+            signed_header: block.signed_header,
+            validator_set: block.validators,
+            trusted_height,
+            trusted_validator_set: trusted_block.next_validators.clone(),
+        })
+    }
+
+    /// Initialize the storage with a Tendermint client which has trusted the
+    /// block. The current block of the storage is at the given time in seconds
+    /// like the time of the generated Tendermint blocks.
+    fn insert_tm_client_states(
+        trusted_block: &TmLightBlock,
+        time: i64,
+    ) -> (TestStorage, WriteLog) {
+        let mut storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        init_genesis_storage(&mut storage);
+        let mut header = get_dummy_header();
+        header.time = TmTime::from(Utc.timestamp(time, 0));
+        storage
+            .set_header(header)
+            .expect("Setting a dummy header shouldn't fail");
+
+        let client_id = get_client_id();
+        let client_type_key = client_type_key(&client_id);
+        let client_type = ClientType::Tendermint
+            .try_to_vec()
+            .expect("encoding failed");
+        write_log
+            .write(&client_type_key, client_type)
+            .expect("write failed");
+        let height = Height::new(
+            TM_REVISION,
+            trusted_block.signed_header.header.height.value(),
+        );
+        let client_state = TmClientState::new(
+            IbcChainId::new(TM_CHAIN_ID.to_owned(), TM_REVISION),
+            Default::default(),
+            Duration::from_secs(3600),
+            Duration::from_secs(7200),
+            Duration::from_secs(10),
+            height,
+            Height::zero(),
+            vec![],
+            false,
+            false,
+        )
+        .expect("Creating a Tendermint client state shouldn't fail")
+        .wrap_any();
+        let bytes = client_state.try_to_vec().expect("encoding failed");
+        write_log
+            .write(&client_state_key(&client_id), bytes)
+            .expect("write failed");
+        let consensus_state = IbcTmConsensusState::from(
+            trusted_block.signed_header.header.clone(),
+        )
+        .wrap_any();
+        let bytes = consensus_state.try_to_vec().expect("encoding failed");
+        write_log
+            .write(&consensus_state_key(&client_id, height), bytes)
+            .expect("write failed");
+        write_log.commit_tx();
+        write_log.commit_block(&mut storage).expect("commit failed");
+
+        (storage, write_log)
+    }
+
+    /// Validate the misbehaviour submitted with the headers after the client
+    /// has been frozen at their height
+    fn validate_misbehaviour(
+        storage: &TestStorage,
+        write_log: &mut WriteLog,
+        header1: AnyHeader,
+        header2: AnyHeader,
+    ) -> Result<bool> {
+        let client_id = get_client_id();
+        let frozen_key = client_frozen_key(&client_id);
+        let bytes = header1.height().try_to_vec().expect("encoding failed");
+        write_log.write(&frozen_key, bytes).expect("write failed");
+        write_log.commit_tx();
+
+        let tx_code = vec![];
+        let data = ClientMisbehaviourData::new(client_id, header1, header2);
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
+        let tx = Tx::new(tx_code, Some(tx_data.clone()));
+        let gas_meter = VpGasMeter::new(0);
+        let ctx = Ctx::new(storage, write_log, &tx, gas_meter);
+
+        let mut keys_changed = HashSet::new();
+        keys_changed.insert(frozen_key);
+
+        let verifiers = HashSet::new();
+
+        let ibc = Ibc { ctx };
+        ibc.validate_tx(&tx_data, &keys_changed, &verifiers)
+    }
+
+    #[test]
+    fn test_client_misbehaviour() {
+        let validators = get_tm_validators(&["a", "b", "c"]);
+        let trusted_block = generate_tm_block(&validators, 10, 1);
+        let (storage, mut write_log) =
+            insert_tm_client_states(&trusted_block, 4);
+
+        // two different blocks at the same height signed by the validators
+        let header1 = generate_tm_client_header(
+            generate_tm_block(&validators, 11, 2),
+            &trusted_block,
+        );
+        let header2 = generate_tm_client_header(
+            generate_tm_block(&validators, 11, 3),
+            &trusted_block,
+        );
+        // this should return true because the client has been frozen
+        assert!(
+            validate_misbehaviour(&storage, &mut write_log, header1, header2)
+                .expect("validation failed")
+        );
+    }
+
+    #[test]
+    fn test_client_misbehaviour_forged_header_fail() {
+        let validators = get_tm_validators(&["a", "b", "c"]);
+        let trusted_block = generate_tm_block(&validators, 10, 1);
+        let (storage, mut write_log) =
+            insert_tm_client_states(&trusted_block, 4);
+
+        let header1 = generate_tm_client_header(
+            generate_tm_block(&validators, 11, 2),
+            &trusted_block,
+        );
+        // a header signed by validators that the client doesn't trust
+        let forgers = get_tm_validators(&["x", "y", "z"]);
+        let header2 = generate_tm_client_header(
+            generate_tm_block(&forgers, 11, 3),
+            &trusted_block,
+        );
+        // this should fail because the second header is forged
+        let result =
+            validate_misbehaviour(&storage, &mut write_log, header1, header2)
+                .unwrap_err();
+        assert_matches!(
+            result,
+            Error::ClientError(client::Error::InvalidMisbehaviour(_))
+        );
+    }
+
+    #[test]
+    fn test_client_misbehaviour_same_headers_fail() {
+        let validators = get_tm_validators(&["a", "b", "c", "d"]);
+        let trusted_block = generate_tm_block(&validators, 10, 1);
+        let (storage, mut write_log) =
+            insert_tm_client_states(&trusted_block, 4);
+
+        // the same block with a commit signed by a subset of the validators
+        let block = generate_tm_block(&validators, 11, 2);
+        let header1 = generate_tm_client_header(block.clone(), &trusted_block);
+        let mut block = block;
+        block.signed_header.commit.signatures.pop();
+        let header2 = generate_tm_client_header(block, &trusted_block);
+        // this should fail because the headers are for the same block
+        let result =
+            validate_misbehaviour(&storage, &mut write_log, header1, header2)
+                .unwrap_err();
+        assert_matches!(
+            result,
+            Error::ClientError(client::Error::InvalidMisbehaviour(_))
+        );
+    }
+
+    #[test]
+    fn test_client_misbehaviour_expired_trusted_state_fail() {
+        let validators = get_tm_validators(&["a", "b", "c"]);
+        let trusted_block = generate_tm_block(&validators, 10, 1);
+        // the trusting period of the client has passed since the trusted block
+        let (storage, mut write_log) =
+            insert_tm_client_states(&trusted_block, 3602);
+
+        let header1 = generate_tm_client_header(
+            generate_tm_block(&validators, 11, 2),
+            &trusted_block,
+        );
+        let header2 = generate_tm_client_header(
+            generate_tm_block(&validators, 11, 3),
+            &trusted_block,
+        );
+        // this should fail because the trusted consensus state has expired
+        let result =
+            validate_misbehaviour(&storage, &mut write_log, header1, header2)
+                .unwrap_err();
+        assert_matches!(
+            result,
+            Error::ClientError(client::Error::InvalidMisbehaviour(_))
+        );
+    }
+
+    #[test]
+    fn test_client_misbehaviour_header_time_fail() {
+        let validators = get_tm_validators(&["a", "b", "c"]);
+        let trusted_block = generate_tm_block(&validators, 10, 2);
+        let (storage, mut write_log) =
+            insert_tm_client_states(&trusted_block, 4);
+
+        let header1 = generate_tm_client_header(
+            generate_tm_block(&validators, 11, 3),
+            &trusted_block,
+        );
+        // a header whose time is before the trusted time
+        let header2 = generate_tm_client_header(
+            generate_tm_block(&validators, 11, 1),
+            &trusted_block,
+        );
+        // this should fail because the second header isn't after the trusted
+        // block
+        let result = validate_misbehaviour(
+            &storage,
+            &mut write_log,
+            header1.clone(),
+            header2,
+        )
+        .unwrap_err();
+        assert_matches!(
+            result,
+            Error::ClientError(client::Error::InvalidMisbehaviour(_))
+        );
+
+        // a header whose time is too far in the future
+        let header2 = generate_tm_client_header(
+            generate_tm_block(&validators, 11, 20),
+            &trusted_block,
+        );
+        // this should fail because the second header exceeds the maximum
+        // clock drift of the client
+        let result =
+            validate_misbehaviour(&storage, &mut write_log, header1, header2)
+                .unwrap_err();
+        assert_matches!(
+            result,
+            Error::ClientError(client::Error::InvalidMisbehaviour(_))
+        );
+    }
+
+    #[test]
+    fn test_client_misbehaviour_mock_fail() {
+        let (mut storage, mut write_log) = insert_init_states();
+        write_log.commit_block(&mut storage).expect("commit failed");
+
+        let height = Height::new(1, 10);
+        let header1 = MockHeader {
+            height,
+            timestamp: Timestamp::now(),
+        };
+        let header2 = MockHeader {
+            height,
+            timestamp: Timestamp::none(),
+        };
+        // this should fail because the mock headers can't be verified
+        let result = validate_misbehaviour(
+            &storage,
+            &mut write_log,
+            AnyHeader::from(header1),
+            AnyHeader::from(header2),
+        )
+        .unwrap_err();
+        assert_matches!(
+            result,
+            Error::ClientError(client::Error::InvalidMisbehaviour(_))
+        );
+    }
+
+    #[test]
+    fn test_update_frozen_client_fail() {
+        let (mut storage, mut write_log) = insert_init_states();
+        // the client has been frozen
+        let client_id = get_client_id();
+        let frozen_key = client_frozen_key(&client_id);
+        let bytes = Height::new(1, 10).try_to_vec().expect("encoding failed");
+        write_log.write(&frozen_key, bytes).expect("write failed");
+        write_log.commit_tx();
+        write_log.commit_block(&mut storage).expect("commit failed");
+
+        // update the client
+        let client_state_key = client_state_key(&get_client_id());
+        let height = Height::new(1, 11);
+        let header = MockHeader {
+            height,
+            timestamp: Timestamp::now(),
+        };
+        let client_state = MockClientState(header).wrap_any();
+        let bytes = client_state.try_to_vec().expect("encoding failed");
+        write_log
+            .write(&client_state_key, bytes)
+            .expect("write failed");
+        let consensus_key = consensus_state_key(&client_id, height);
+        let consensus_state = MockConsensusState::new(header).wrap_any();
+        let bytes = consensus_state.try_to_vec().expect("encoding failed");
+        write_log
+            .write(&consensus_key, bytes)
+            .expect("write failed");
+        write_log.commit_tx();
+
+        let tx_code = vec![];
+        let data =
+            ClientUpdateData::new(client_id, vec![AnyHeader::from(header)]);
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
+        let tx = Tx::new(tx_code, Some(tx_data.clone()));
+        let gas_meter = VpGasMeter::new(0);
+        let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
+
+        let mut keys_changed = HashSet::new();
+        keys_changed.insert(client_state_key);
+
+        let verifiers = HashSet::new();
+
+        let ibc = Ibc { ctx };
+        // this should fail because the client has been frozen
+        let result = ibc
+            .validate_tx(&tx_data, &keys_changed, &verifiers)
+            .unwrap_err();
+        assert_matches!(
+            result,
+            Error::ClientError(client::Error::FrozenClient(_))
+        );
+    }
+
     #[test]
     fn test_init_connection() {
         let (mut storage, mut write_log) = insert_init_states();
@@ -622,7 +1012,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -661,7 +1053,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -717,7 +1111,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -774,7 +1170,9 @@ mod tests {
             Version::default(),
         );
 
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -821,7 +1219,9 @@ mod tests {
             proof_consensus,
         );
 
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -866,7 +1266,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -919,7 +1321,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -975,7 +1379,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -1029,7 +1435,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -1144,7 +1552,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -1212,7 +1622,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -1286,7 +1698,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -1354,7 +1768,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
@@ -1426,7 +1842,9 @@ mod tests {
         write_log.commit_tx();
 
         let tx_code = vec![];
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = IbcMessage::from(data)
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx::new(tx_code, Some(tx_data.clone()));
         let gas_meter = VpGasMeter::new(0);
         let ctx = Ctx::new(&storage, &write_log, &tx, gas_meter);
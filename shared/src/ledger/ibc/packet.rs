@@ -1,6 +1,7 @@
 //! IBC validity predicate for packets
 
-use borsh::BorshDeserialize;
+use std::convert::TryFrom;
+
 use ibc::ics02_client::height::Height;
 use ibc::ics04_channel::channel::{ChannelEnd, Counterparty, Order, State};
 use ibc::ics04_channel::context::ChannelReader;
@@ -20,8 +21,8 @@ use super::storage::{port_channel_sequence_id, Error as IbcStorageError};
 use super::{Ibc, StateChange};
 use crate::ledger::storage::{self, StorageHasher};
 use crate::types::ibc::{
-    Error as IbcDataError, PacketAckData, PacketReceiptData, PacketSendData,
-    TimeoutData,
+    Error as IbcDataError, IbcMessage, PacketAckData, PacketReceiptData,
+    PacketSendData, TimeoutData,
 };
 use crate::types::storage::Key;
 
@@ -76,7 +77,8 @@ where
         {
             StateChange::Created => {
                 // sending a packet
-                let data = PacketSendData::try_from_slice(tx_data)?;
+                let data =
+                    PacketSendData::try_from(IbcMessage::decode(tx_data)?)?;
                 let packet = data.packet(commitment_key.2);
                 let commitment = self
                     .get_packet_commitment(&commitment_key)
@@ -110,7 +112,9 @@ where
                 match channel.state() {
                     State::Open => {
                         // "PacketAcknowledgement"
-                        let data = PacketAckData::try_from_slice(tx_data)?;
+                        let data = PacketAckData::try_from(
+                            IbcMessage::decode(tx_data)?,
+                        )?;
                         let commitment_pre = self
                             .get_packet_commitment_pre(&commitment_key)
                             .map_err(|e| Error::InvalidPacket(e.to_string()))?;
@@ -161,7 +165,8 @@ where
         {
             StateChange::Created => {
                 let receipt_key = port_channel_sequence_id(key)?;
-                let data = PacketReceiptData::try_from_slice(tx_data)?;
+                let data =
+                    PacketReceiptData::try_from(IbcMessage::decode(tx_data)?)?;
                 let packet = &data.packet;
                 self.validate_recv_packet(&receipt_key, packet)?;
                 let port_channel_id = PortChannelId {
@@ -357,6 +362,8 @@ where
                 "The connection isn't open".to_owned(),
             ));
         }
+        self.validate_client_not_frozen(connection.client_id())
+            .map_err(|e| Error::InvalidClient(e.to_string()))?;
 
         // counterparty consistency
         let counterparty = match phase {
@@ -495,7 +502,7 @@ where
         commitment_key: &(PortId, ChannelId, Sequence),
         tx_data: &[u8],
     ) -> Result<()> {
-        let data = TimeoutData::try_from_slice(tx_data)?;
+        let data = TimeoutData::try_from(IbcMessage::decode(tx_data)?)?;
         let packet = data.packet.clone();
         // deleted commitment should be for the packet sent from this channel
         let commitment = self
@@ -1,6 +1,7 @@
 //! IBC validity predicate for sequences
 
-use borsh::BorshDeserialize;
+use std::convert::TryFrom;
+
 use ibc::ics04_channel::channel::Order;
 use ibc::ics04_channel::context::ChannelReader;
 use ibc::ics24_host::identifier::PortChannelId;
@@ -9,7 +10,10 @@ use thiserror::Error;
 use super::storage::{port_channel_id, Error as IbcStorageError};
 use super::Ibc;
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
-use crate::types::ibc::{PacketAckData, PacketReceiptData, PacketSendData};
+use crate::types::ibc::{
+    Error as IbcDataError, IbcMessage, PacketAckData, PacketReceiptData,
+    PacketSendData,
+};
 use crate::types::storage::Key;
 
 #[allow(missing_docs)]
@@ -23,8 +27,8 @@ pub enum Error {
     InvalidSequence(String),
     #[error("Packet error: {0}")]
     InvalidPacket(String),
-    #[error("Decoding TX data error: {0}")]
-    DecodingTxData(std::io::Error),
+    #[error("IBC data error: {0}")]
+    InvalidIbcData(IbcDataError),
     #[error("IBC storage error: {0}")]
     IbcStorage(IbcStorageError),
 }
@@ -43,7 +47,7 @@ where
         tx_data: &[u8],
     ) -> Result<()> {
         let port_channel_id = port_channel_id(key)?;
-        let data = PacketSendData::try_from_slice(tx_data)?;
+        let data = PacketSendData::try_from(IbcMessage::decode(tx_data)?)?;
         let next_seq_pre = self
             .get_next_sequence_send_pre(&port_channel_id)
             .map_err(|e| Error::InvalidSequence(e.to_string()))?;
@@ -95,7 +99,7 @@ where
         tx_data: &[u8],
     ) -> Result<()> {
         let port_channel_id = port_channel_id(key)?;
-        let data = PacketReceiptData::try_from_slice(tx_data)?;
+        let data = PacketReceiptData::try_from(IbcMessage::decode(tx_data)?)?;
         let packet = &data.packet;
         let next_seq_pre = self
             .get_next_sequence_recv_pre(&port_channel_id)
@@ -153,7 +157,7 @@ where
         tx_data: &[u8],
     ) -> Result<()> {
         let port_channel_id = port_channel_id(key)?;
-        let data = PacketAckData::try_from_slice(tx_data)?;
+        let data = PacketAckData::try_from(IbcMessage::decode(tx_data)?)?;
         let packet = &data.packet;
         let next_seq_pre = self
             .get_next_sequence_ack_pre(&port_channel_id)
@@ -225,8 +229,8 @@ impl From<IbcStorageError> for Error {
     }
 }
 
-impl From<std::io::Error> for Error {
-    fn from(err: std::io::Error) -> Self {
-        Self::DecodingTxData(err)
+impl From<IbcDataError> for Error {
+    fn from(err: IbcDataError) -> Self {
+        Self::InvalidIbcData(err)
     }
 }
@@ -19,6 +19,7 @@ const CONNECTIONS_COUNTER: &str = "connections/counter";
 const CHANNELS_COUNTER: &str = "channelEnds/counter";
 const CAPABILITIES_INDEX: &str = "capabilities/index";
 const CAPABILITIES: &str = "capabilities";
+const DENOM: &str = "denom";
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -145,6 +146,13 @@ pub fn client_state_key(client_id: &ClientId) -> Key {
         .expect("Creating a key for the client state shouldn't fail")
 }
 
+/// Returns a key for the height at which the client has been frozen by a
+/// misbehaviour
+pub fn client_frozen_key(client_id: &ClientId) -> Key {
+    let path = format!("clients/{}/frozenHeight", client_id);
+    ibc_key(path).expect("Creating a key for the frozen height shouldn't fail")
+}
+
 /// Returns a key for the consensus state
 pub fn consensus_state_key(client_id: &ClientId, height: Height) -> Key {
     let path = Path::ClientConsensusState {
@@ -261,6 +269,29 @@ pub fn ack_key(
         .expect("Creating a key for the ack shouldn't fail")
 }
 
+/// Returns a key of the denomination trace of the IBC token
+/// `#ibc::<hash>/denom`
+pub fn ibc_denom_key(token: &Address) -> Key {
+    Key::from(token.to_db_key())
+        .push(&DENOM.to_owned())
+        .expect("Creating a key for the denomination shouldn't fail")
+}
+
+/// Returns the IBC token address if the given key is a key of the denomination
+/// trace
+pub fn is_ibc_denom_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(
+            token @ Address::Internal(InternalAddress::IbcToken(_)),
+        ), DbKeySeg::StringSeg(prefix)]
+            if prefix == DENOM =>
+        {
+            Some(token)
+        }
+        _ => None,
+    }
+}
+
 /// Returns a client ID from the given client key `#IBC/clients/<client_id>`
 pub fn client_id(key: &Key) -> Result<ClientId> {
     match &key.segments[..] {
@@ -0,0 +1,410 @@
+//! IBC validity predicate for the ICS-20 fungible token transfer. It validates
+//! the balance changes of the IBC escrow account and the IBC tokens.
+
+use std::collections::{HashMap, HashSet};
+
+use borsh::BorshDeserialize;
+use ibc::ics04_channel::packet::Packet;
+use thiserror::Error;
+
+use super::storage::{ack_key, ibc_denom_key, is_ibc_denom_key};
+use crate::ledger::native_vp::{self, Ctx, NativeVp};
+use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::types::address::{Address, InternalAddress};
+use crate::types::ibc::{
+    Error as IbcDataError, FungibleTokenAck, FungibleTokenPacketData,
+    IbcMessage, TransferAction, TRANSFER_PORT_ID,
+};
+use crate::types::storage::{DbKeySeg, Key};
+use crate::types::token::{self, Amount, Change};
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(native_vp::Error),
+    #[error("IBC data error: {0}")]
+    InvalidIbcData(IbcDataError),
+    #[error("Key error: {0}")]
+    InvalidKey(String),
+    #[error("Denomination error: {0}")]
+    InvalidDenom(String),
+    #[error("Transfer error: {0}")]
+    InvalidTransfer(String),
+    #[error("Acknowledgement error: {0}")]
+    InvalidAck(String),
+}
+
+/// IBC token functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// IBC token VP for the escrow account and the IBC tokens
+pub struct IbcToken<'a, DB, H>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H>,
+}
+
+impl<'a, DB, H> NativeVp for IbcToken<'a, DB, H>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    type Error = Error;
+
+    const ADDR: InternalAddress = InternalAddress::IbcEscrow;
+
+    fn validate_tx(
+        &self,
+        tx_data: &[u8],
+        keys_changed: &HashSet<Key>,
+        _verifiers: &HashSet<Address>,
+    ) -> Result<bool> {
+        if !requires_ibc_message(&self.ctx, keys_changed)? {
+            // the IBC tokens are only transferred between the owners on this
+            // chain, which is validated by the VPs of the owners
+            return Ok(true);
+        }
+        let msg = IbcMessage::decode(tx_data)?;
+        let transfer = expected_transfer(&self.ctx, &msg)?;
+
+        for key in keys_changed {
+            if is_ibc_denom_key(key).is_some() {
+                // the value is checked with the expected changes below
+                match &transfer.denom {
+                    Some((denom_key, _)) if denom_key == key => {}
+                    _ => {
+                        return Err(Error::InvalidDenom(format!(
+                            "The denomination is unexpectedly changed: Key {}",
+                            key
+                        )));
+                    }
+                }
+            } else if let Some((token, owner)) = token::is_any_balance_key(key)
+            {
+                if !is_ibc_account(token) && !is_ibc_account(owner) {
+                    // a balance of the other token or owner
+                    continue;
+                }
+                let expected =
+                    transfer.changes.get(key).copied().unwrap_or_default();
+                let change = balance_change(&self.ctx, key)?;
+                if change != expected {
+                    return Err(Error::InvalidTransfer(format!(
+                        "The balance change is unexpected: Key {}, \
+                         expected {}, actual {}",
+                        key, expected, change
+                    )));
+                }
+            } else if let Some(DbKeySeg::AddressSeg(addr)) =
+                key.segments.first()
+            {
+                if is_ibc_account(addr) {
+                    return Err(Error::InvalidKey(format!(
+                        "Invalid IBC token key: {}",
+                        key
+                    )));
+                }
+            }
+        }
+
+        validate_changes(&self.ctx, &transfer)?;
+        Ok(true)
+    }
+}
+
+/// Returns true if the changes can only be made by an IBC message, i.e. the
+/// denomination traces, the balances of the IBC accounts or the supplies of
+/// the IBC tokens are changed. Otherwise, the IBC tokens are only transferred
+/// between the owners on this chain.
+fn requires_ibc_message<DB, H>(
+    ctx: &Ctx<DB, H>,
+    keys_changed: &HashSet<Key>,
+) -> Result<bool>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    let mut supply_changes: HashMap<&Address, Change> = HashMap::new();
+    for key in keys_changed {
+        if is_ibc_denom_key(key).is_some() {
+            return Ok(true);
+        }
+        match token::is_any_balance_key(key) {
+            Some((_, owner)) if is_ibc_account(owner) => return Ok(true),
+            Some((token, _))
+                if matches!(
+                    token,
+                    Address::Internal(InternalAddress::IbcToken(_))
+                ) =>
+            {
+                *supply_changes.entry(token).or_default() +=
+                    balance_change(ctx, key)?;
+            }
+            Some(_) => {}
+            None => {
+                // the other keys of the IBC accounts are rejected with the
+                // IBC message
+                if let Some(DbKeySeg::AddressSeg(addr)) = key.segments.first() {
+                    if is_ibc_account(addr) {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+    }
+    // minting or burning an IBC token changes its supply
+    Ok(supply_changes.values().any(|change| *change != 0))
+}
+
+/// The state changes expected by a fungible token transfer
+#[derive(Debug, Default)]
+struct Transfer {
+    /// The expected balance changes
+    changes: HashMap<Key, Change>,
+    /// The denomination trace key and value of the minted IBC token
+    denom: Option<(Key, String)>,
+}
+
+impl From<TransferAction> for Transfer {
+    fn from(action: TransferAction) -> Self {
+        let mut changes = HashMap::new();
+        for (key, change) in action.balance_changes() {
+            *changes.entry(key).or_default() += change;
+        }
+        let denom = match action {
+            TransferAction::Mint { denom, .. } => {
+                let token =
+                    Address::Internal(InternalAddress::ibc_token(&denom));
+                Some((ibc_denom_key(&token), denom))
+            }
+            _ => None,
+        };
+        Self { changes, denom }
+    }
+}
+
+/// Validate that the balances have been changed by the fungible token
+/// transfer requested by the IBC message. This is called by the IBC VP
+/// because the IBC token VP isn't triggered when no token has been moved.
+pub(super) fn validate_transfer<DB, H>(
+    ctx: &Ctx<DB, H>,
+    tx_data: &[u8],
+) -> Result<()>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    let msg = IbcMessage::decode(tx_data)?;
+    let transfer = expected_transfer(ctx, &msg)?;
+    validate_changes(ctx, &transfer)
+}
+
+/// Returns the state changes expected by the IBC message. Messages other than
+/// packets on the transfer port don't move any token.
+fn expected_transfer<DB, H>(
+    ctx: &Ctx<DB, H>,
+    msg: &IbcMessage,
+) -> Result<Transfer>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    match msg {
+        IbcMessage::PacketSend(data)
+            if data.source_port.as_str() == TRANSFER_PORT_ID =>
+        {
+            let packet_data =
+                FungibleTokenPacketData::decode(&data.packet_data)?;
+            let action = TransferAction::on_send(
+                &packet_data,
+                &data.source_port,
+                &data.source_channel,
+            )?;
+            Ok(Transfer::from(action))
+        }
+        IbcMessage::PacketReceipt(data)
+            if data.packet.destination_port.as_str() == TRANSFER_PORT_ID =>
+        {
+            let packet = &data.packet;
+            let key = ack_key(
+                &packet.destination_port,
+                &packet.destination_channel,
+                packet.sequence,
+            );
+            let ack = match ctx.read_post(&key)? {
+                Some(value) => {
+                    let ack = Vec::<u8>::try_from_slice(&value[..])
+                        .map_err(|e| Error::InvalidAck(e.to_string()))?;
+                    FungibleTokenAck::decode(&ack)?
+                }
+                None => {
+                    return Err(Error::InvalidAck(
+                        "The acknowledgement doesn't exist".to_owned(),
+                    ));
+                }
+            };
+            match (receive_action(ctx, packet), ack.is_success()) {
+                (Ok(action), true) => Ok(Transfer::from(action)),
+                (Err(_), false) => Ok(Transfer::default()),
+                (Ok(_), false) => Err(Error::InvalidAck(
+                    "The acknowledgement should be successful".to_owned(),
+                )),
+                (Err(e), true) => Err(Error::InvalidAck(format!(
+                    "The acknowledgement should be an error: {}",
+                    e
+                ))),
+            }
+        }
+        IbcMessage::PacketAck(data)
+            if data.packet.source_port.as_str() == TRANSFER_PORT_ID =>
+        {
+            let is_success = FungibleTokenAck::decode(&data.ack)
+                .map(|ack| ack.is_success())
+                .unwrap_or(false);
+            if is_success {
+                Ok(Transfer::default())
+            } else {
+                let action = TransferAction::on_refund(&data.packet)?;
+                Ok(Transfer::from(action))
+            }
+        }
+        IbcMessage::Timeout(data)
+            if data.packet.source_port.as_str() == TRANSFER_PORT_ID =>
+        {
+            let action = TransferAction::on_refund(&data.packet)?;
+            Ok(Transfer::from(action))
+        }
+        _ => Ok(Transfer::default()),
+    }
+}
+
+/// Returns the action to receive the packet if it can be applied to the prior
+/// state, otherwise the packet should be acknowledged with an error
+fn receive_action<DB, H>(
+    ctx: &Ctx<DB, H>,
+    packet: &Packet,
+) -> Result<TransferAction>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    let action = TransferAction::on_receive(packet)?;
+    match &action {
+        TransferAction::Unescrow { token, amount, .. } => {
+            let escrow = Address::Internal(InternalAddress::IbcEscrow);
+            let key = token::balance_key(token, &escrow);
+            if read_balance_pre(ctx, &key)?.checked_sub(amount).is_none() {
+                return Err(Error::InvalidTransfer(format!(
+                    "The escrowed amount is insufficient: Token {}",
+                    token
+                )));
+            }
+        }
+        TransferAction::Mint {
+            denom,
+            owner,
+            amount,
+        } => {
+            let token = Address::Internal(InternalAddress::ibc_token(denom));
+            let key = token::balance_key(&token, owner);
+            if read_balance_pre(ctx, &key)?.checked_add(amount).is_none() {
+                return Err(Error::InvalidTransfer(format!(
+                    "The balance overflows: Owner {}",
+                    owner
+                )));
+            }
+        }
+        _ => {
+            return Err(Error::InvalidTransfer(
+                "Receiving a packet only unescrows or mints a token".to_owned(),
+            ));
+        }
+    }
+    Ok(action)
+}
+
+/// Validate that all the expected changes have been applied
+fn validate_changes<DB, H>(ctx: &Ctx<DB, H>, transfer: &Transfer) -> Result<()>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    for (key, expected) in &transfer.changes {
+        let change = balance_change(ctx, key)?;
+        if change != *expected {
+            return Err(Error::InvalidTransfer(format!(
+                "The balance change is unexpected: Key {}, expected {}, \
+                 actual {}",
+                key, expected, change
+            )));
+        }
+    }
+    if let Some((key, denom)) = &transfer.denom {
+        let value = ctx.read_post(key)?.ok_or_else(|| {
+            Error::InvalidDenom(format!(
+                "The denomination doesn't exist: Key {}",
+                key
+            ))
+        })?;
+        let stored = String::try_from_slice(&value[..])
+            .map_err(|e| Error::InvalidDenom(e.to_string()))?;
+        if stored != *denom {
+            return Err(Error::InvalidDenom(format!(
+                "The denomination is invalid: expected {}, actual {}",
+                denom, stored
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn balance_change<DB, H>(ctx: &Ctx<DB, H>, key: &Key) -> Result<Change>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    let pre = read_balance_pre(ctx, key)?;
+    let post = match ctx.read_post(key)? {
+        Some(value) => Amount::try_from_slice(&value[..])
+            .map_err(|e| Error::InvalidTransfer(e.to_string()))?,
+        None => Amount::default(),
+    };
+    Ok(post.change() - pre.change())
+}
+
+fn read_balance_pre<DB, H>(ctx: &Ctx<DB, H>, key: &Key) -> Result<Amount>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    match ctx.read_pre(key)? {
+        Some(value) => Amount::try_from_slice(&value[..])
+            .map_err(|e| Error::InvalidTransfer(e.to_string())),
+        None => Ok(Amount::default()),
+    }
+}
+
+fn is_ibc_account(addr: &Address) -> bool {
+    matches!(
+        addr,
+        Address::Internal(
+            InternalAddress::IbcEscrow | InternalAddress::IbcToken(_)
+        )
+    )
+}
+
+impl From<native_vp::Error> for Error {
+    fn from(err: native_vp::Error) -> Self {
+        Self::NativeVpError(err)
+    }
+}
+
+impl From<IbcDataError> for Error {
+    fn from(err: IbcDataError) -> Self {
+        Self::InvalidIbcData(err)
+    }
+}
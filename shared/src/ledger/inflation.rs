@@ -0,0 +1,525 @@
+//! Inflation of the native token
+//!
+//! At the beginning of every epoch, the ledger mints new native tokens. The
+//! annual inflation rate is adjusted in every epoch by a
+//! proportional-derivative controller towards the target staking ratio, i.e.
+//! the portion of the native token supply that is bonded in PoS. When less
+//! tokens are staked than targeted, the rate goes up to make staking more
+//! attractive and vice versa, always within the minimum and maximum rate.
+//!
+//! The minted tokens are split between the [treasury](crate::ledger::treasury)
+//! and the PoS rewards. The PoS share is paid out to the validators and their
+//! delegators in proportion to the block rewards they have accumulated in the
+//! last epoch. The block rewards are only weights for the PoS share, so when
+//! no inflation is minted for them in an epoch, they are discarded rather than
+//! paid out.
+//!
+//! The inflation parameters and state are kept in the parameters account's
+//! sub-space, so they can only be changed by a passed
+//! [governance proposal](crate::ledger::governance).
+
+use std::convert::TryFrom;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use thiserror::Error;
+
+use crate::ledger::pos::anoma_proof_of_stake::PosBase;
+use crate::ledger::pos::types::BasisPoints;
+use crate::ledger::pos::RewardsAccumulator;
+use crate::ledger::storage::types::encode;
+use crate::ledger::storage::{self as ledger_storage, Storage, StorageHasher};
+use crate::ledger::treasury;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::storage::{Epoch, Key, KeySeg};
+use crate::types::token;
+
+const ADDRESS: Address = Address::Internal(InternalAddress::Parameters);
+
+const INFLATION_STORAGE_KEY: &str = "inflation";
+const PARAMS_STORAGE_KEY: &str = "params";
+const STATE_STORAGE_KEY: &str = "state";
+const SUPPLY_STORAGE_KEY: &str = "supply";
+
+/// The denominator of basis points
+const BASIS_POINTS_DENOM: i128 = 10_000;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Storage error: {0}")]
+    StorageError(ledger_storage::Error),
+    #[error("Treasury error: {0}")]
+    TreasuryError(treasury::Error),
+    #[error("The token supply would overflow")]
+    SupplyOverflow,
+    #[error("The inflation of the epoch would overflow")]
+    InflationOverflow,
+    #[error("The paid PoS rewards would overflow")]
+    RewardsOverflow,
+}
+
+/// Inflation functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Inflation parameters
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct InflationParams {
+    /// Targeted portion of the native token supply that is staked
+    pub target_staking_ratio: BasisPoints,
+    /// Minimum annual inflation rate
+    pub min_rate: BasisPoints,
+    /// Maximum annual inflation rate
+    pub max_rate: BasisPoints,
+    /// Proportional gain of the controller, applied to the difference
+    /// between the target and the actual staking ratio
+    pub p_gain: BasisPoints,
+    /// Derivative gain of the controller, applied to the change of the
+    /// difference between the target and the actual staking ratio since the
+    /// last epoch
+    pub d_gain: BasisPoints,
+    /// Expected number of epochs in a year, used to derive the amount minted
+    /// in an epoch from the annual rate
+    pub epochs_per_year: u64,
+    /// Portion of the minted tokens credited to the treasury. The rest is
+    /// paid out as PoS rewards.
+    pub treasury_share: BasisPoints,
+}
+
+impl Default for InflationParams {
+    fn default() -> Self {
+        Self {
+            // 66.67%
+            target_staking_ratio: BasisPoints::new(6667),
+            // 2%
+            min_rate: BasisPoints::new(200),
+            // 10%
+            max_rate: BasisPoints::new(1000),
+            // 0.1
+            p_gain: BasisPoints::new(1000),
+            // 0.1
+            d_gain: BasisPoints::new(1000),
+            epochs_per_year: 365,
+            // 10%
+            treasury_share: BasisPoints::new(1000),
+        }
+    }
+}
+
+/// The state of the inflation as of its last update
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct InflationState {
+    /// The epoch of the last update
+    pub epoch: Epoch,
+    /// The annual inflation rate
+    pub rate: BasisPoints,
+    /// The portion of the native token supply that was staked
+    pub staking_ratio: BasisPoints,
+    /// The difference between the target and the actual staking ratio in
+    /// basis points
+    pub error: i64,
+    /// The amount minted into the treasury
+    pub treasury_minted: token::Amount,
+    /// The amount paid out as PoS rewards
+    pub pos_minted: token::Amount,
+}
+
+fn inflation_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&INFLATION_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for inflation parameters.
+pub fn params_key() -> Key {
+    inflation_prefix()
+        .push(&PARAMS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for the inflation state.
+pub fn state_key() -> Key {
+    inflation_prefix()
+        .push(&STATE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for the total supply of the native token.
+pub fn supply_key() -> Key {
+    inflation_prefix()
+        .push(&SUPPLY_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Initialize the inflation parameters, its state and the total supply of the
+/// native token in storage in the genesis block. The PoS genesis storage must
+/// be initialized first. The inflation starts at the minimum rate.
+pub fn init_genesis_storage<DB, H>(
+    storage: &mut Storage<DB, H>,
+    params: &InflationParams,
+    supply: token::Amount,
+    current_epoch: Epoch,
+) where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let staking_ratio = staking_ratio(storage, supply, current_epoch);
+    let state = InflationState {
+        epoch: current_epoch,
+        rate: params.min_rate,
+        staking_ratio,
+        error: staking_error(params, staking_ratio),
+        treasury_minted: token::Amount::default(),
+        pos_minted: token::Amount::default(),
+    };
+    storage.write(&params_key(), encode(params)).expect(
+        "Inflation parameters must be initialized in the genesis block",
+    );
+    storage
+        .write(&state_key(), encode(&state))
+        .expect("Inflation state must be initialized in the genesis block");
+    storage
+        .write(&supply_key(), encode(&supply))
+        .expect("Token supply must be initialized in the genesis block");
+}
+
+/// Update the inflation rate for the current epoch and mint the inflation for
+/// it. The treasury's share is credited to the treasury and the PoS share
+/// replaces the block rewards accumulated in the last epoch, pro rata, before
+/// they are distributed. Returns the new state.
+pub fn update<DB, H>(
+    storage: &mut Storage<DB, H>,
+    current_epoch: Epoch,
+) -> Result<Option<InflationState>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (params, state, supply) = match (
+        read::<_, _, InflationParams>(storage, &params_key())?,
+        read::<_, _, InflationState>(storage, &state_key())?,
+        read::<_, _, token::Amount>(storage, &supply_key())?,
+    ) {
+        (Some(params), Some(state), Some(supply)) => (params, state, supply),
+        _ => return Ok(None),
+    };
+    if state.epoch >= current_epoch {
+        return Ok(None);
+    }
+
+    let staking_ratio = staking_ratio(storage, supply, current_epoch);
+    let error = staking_error(&params, staking_ratio);
+    let rate = next_rate(&params, &state, error);
+
+    let epoch_inflation = u128::from(u64::from(supply))
+        * u128::from(u64::from(rate))
+        / BASIS_POINTS_DENOM as u128
+        / u128::from(params.epochs_per_year.max(1));
+    let epoch_inflation =
+        u64::try_from(epoch_inflation).map_err(|_| Error::InflationOverflow)?;
+    let epoch_inflation = token::Amount::from(epoch_inflation);
+    // The treasury share cannot be set above 100%, but if it was, the whole
+    // inflation would go to the treasury
+    let treasury_minted = params
+        .treasury_share
+        .portion_of(u64::from(epoch_inflation))
+        .unwrap_or_else(|_| u64::from(epoch_inflation));
+    let treasury_minted = token::Amount::from(treasury_minted);
+    let scaled_rewards = scale_pos_rewards(
+        storage,
+        epoch_inflation - treasury_minted,
+        current_epoch,
+    )?;
+    let pos_minted = scaled_rewards
+        .as_ref()
+        .map(|(_, paid)| *paid)
+        .unwrap_or_default();
+    let supply = supply
+        .checked_add(&treasury_minted)
+        .and_then(|supply| supply.checked_add(&pos_minted))
+        .ok_or(Error::SupplyOverflow)?;
+    treasury::mint(storage, treasury_minted)?;
+    match scaled_rewards {
+        Some((accumulator, _paid)) => {
+            storage.write_rewards_accumulator(&accumulator)
+        }
+        None => discard_pos_rewards(storage, current_epoch),
+    }
+    let state = InflationState {
+        epoch: current_epoch,
+        rate,
+        staking_ratio,
+        error,
+        treasury_minted,
+        pos_minted,
+    };
+    storage.write(&state_key(), encode(&state))?;
+    storage.write(&supply_key(), encode(&supply))?;
+    Ok(Some(state))
+}
+
+/// Discard the PoS block rewards accumulated in a previous epoch, if any. This
+/// must be called when the rewards haven't been scaled to the inflation by
+/// [`update`] in the current epoch, because the accumulated rewards are only
+/// weights and they must not be paid out as they are.
+pub fn discard_pos_rewards<DB, H>(
+    storage: &mut Storage<DB, H>,
+    current_epoch: Epoch,
+) where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    match storage.read_rewards_accumulator() {
+        Some(accumulator) if accumulator.epoch < current_epoch.into() => {
+            storage.write_rewards_accumulator(&RewardsAccumulator::new(
+                current_epoch.into(),
+            ));
+        }
+        _ => {}
+    }
+}
+
+/// The portion of the supply that is bonded to the active and inactive
+/// validators in the given epoch.
+fn staking_ratio<DB, H>(
+    storage: &Storage<DB, H>,
+    supply: token::Amount,
+    epoch: Epoch,
+) -> BasisPoints
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let supply = u64::from(supply) as i128;
+    if supply == 0 {
+        return BasisPoints::new(0);
+    }
+    let staked: token::Change = storage
+        .read_validator_set()
+        .get(epoch)
+        .map(|set| {
+            set.active
+                .iter()
+                .chain(set.inactive.iter())
+                .filter_map(|validator| {
+                    storage
+                        .read_validator_total_deltas(&validator.address)
+                        .and_then(|deltas| deltas.get(epoch))
+                })
+                .sum()
+        })
+        .unwrap_or_default();
+    let ratio =
+        (staked.max(0) * BASIS_POINTS_DENOM / supply).min(BASIS_POINTS_DENOM);
+    BasisPoints::new(ratio as u64)
+}
+
+/// The difference between the target and the actual staking ratio.
+fn staking_error(params: &InflationParams, staking_ratio: BasisPoints) -> i64 {
+    u64::from(params.target_staking_ratio) as i64
+        - u64::from(staking_ratio) as i64
+}
+
+/// The PD controller that adjusts the rate by the proportional and derivative
+/// terms of the error and keeps it within the minimum and maximum rate.
+fn next_rate(
+    params: &InflationParams,
+    state: &InflationState,
+    error: i64,
+) -> BasisPoints {
+    let error = error as i128;
+    let derivative = error - state.error as i128;
+    let adjustment = (u64::from(params.p_gain) as i128 * error
+        + u64::from(params.d_gain) as i128 * derivative)
+        / BASIS_POINTS_DENOM;
+    let min = u64::from(params.min_rate) as i128;
+    let max = u64::from(params.max_rate) as i128;
+    let rate = (u64::from(state.rate) as i128 + adjustment)
+        .max(min)
+        .min(max);
+    BasisPoints::new(rate as u64)
+}
+
+/// Scale the PoS block rewards accumulated in a previous epoch so that they
+/// sum up to the given amount. The rewards that will not be paid out, because
+/// the validator has been slashed for a fault committed in the epoch or it has
+/// no staking reward address, are excluded. Returns the scaled rewards
+/// together with the amount that will be paid out, if there are any rewards
+/// to be paid out.
+fn scale_pos_rewards<DB, H>(
+    storage: &Storage<DB, H>,
+    amount: token::Amount,
+    current_epoch: Epoch,
+) -> Result<Option<(RewardsAccumulator, token::Amount)>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let mut accumulator = match storage.read_rewards_accumulator() {
+        Some(accumulator) if accumulator.epoch < current_epoch.into() => {
+            accumulator
+        }
+        _ => return Ok(None),
+    };
+    let epoch = accumulator.epoch;
+    accumulator.rewards.retain(|validator, _| {
+        let is_slashed = storage
+            .read_validator_slashes(validator)
+            .iter()
+            .any(|slash| slash.epoch == epoch);
+        !is_slashed
+            && storage
+                .read_validator_staking_reward_address(validator)
+                .is_some()
+    });
+    let total: u128 = accumulator
+        .rewards
+        .values()
+        .map(|reward| u128::from(u64::from(*reward)))
+        .sum();
+    if total == 0 {
+        return Ok(None);
+    }
+    let mut paid = token::Amount::default();
+    for reward in accumulator.rewards.values_mut() {
+        let scaled = u128::from(u64::from(*reward))
+            * u128::from(u64::from(amount))
+            / total;
+        *reward = token::Amount::from(scaled as u64);
+        paid = paid.checked_add(reward).ok_or(Error::RewardsOverflow)?;
+    }
+    Ok(Some((accumulator, paid)))
+}
+
+impl From<ledger_storage::Error> for Error {
+    fn from(err: ledger_storage::Error) -> Self {
+        Self::StorageError(err)
+    }
+}
+
+impl From<treasury::Error> for Error {
+    fn from(err: treasury::Error) -> Self {
+        Self::TreasuryError(err)
+    }
+}
+
+fn read<DB, H, T>(
+    storage: &Storage<DB, H>,
+    key: &Key,
+) -> ledger_storage::Result<Option<T>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+    T: BorshDeserialize,
+{
+    let (value, _gas) = storage.read(key)?;
+    Ok(value.and_then(|value| T::try_from_slice(&value[..]).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::pos::PosParams;
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+
+    fn state(rate: u64, error: i64) -> InflationState {
+        InflationState {
+            epoch: Epoch(0),
+            rate: BasisPoints::new(rate),
+            staking_ratio: BasisPoints::new(0),
+            error,
+            treasury_minted: token::Amount::default(),
+            pos_minted: token::Amount::default(),
+        }
+    }
+
+    #[test]
+    fn test_next_rate() {
+        let params = InflationParams::default();
+        // Below the target staking ratio, the rate goes up
+        let rate = next_rate(&params, &state(500, 1000), 1000);
+        assert_eq!(rate, BasisPoints::new(600));
+        // The derivative term reacts to the change of the error
+        let rate = next_rate(&params, &state(500, 0), 1000);
+        assert_eq!(rate, BasisPoints::new(700));
+        // Above the target staking ratio, the rate goes down
+        let rate = next_rate(&params, &state(500, -1000), -1000);
+        assert_eq!(rate, BasisPoints::new(400));
+        // The rate stays within its bounds
+        let rate = next_rate(&params, &state(900, 6000), 6000);
+        assert_eq!(rate, params.max_rate);
+        let rate = next_rate(&params, &state(300, -3000), -3000);
+        assert_eq!(rate, params.min_rate);
+    }
+
+    /// Test that the PoS block rewards only serve as weights for splitting the
+    /// PoS share of the inflation.
+    #[test]
+    fn test_scale_pos_rewards() {
+        let mut storage = TestStorage::default();
+        storage.write_pos_params(&PosParams::default());
+        let proposer = established_address_1();
+        let signer = established_address_2();
+        for validator in [&proposer, &signer].iter() {
+            storage.write_validator_staking_reward_address(
+                validator,
+                &established_address_3(),
+            );
+        }
+        // The proposer's weight is the sum of the default block proposer and
+        // vote rewards, 101, and the other signer's weight is 1
+        storage.accumulate_block_rewards(
+            Epoch(0),
+            Some(&proposer),
+            &[proposer.clone(), signer.clone()],
+        );
+
+        let (accumulator, paid) =
+            scale_pos_rewards(&storage, token::Amount::from(1020), Epoch(1))
+                .expect("scaling failed")
+                .expect("rewards must be paid out");
+        assert_eq!(
+            accumulator.rewards.get(&proposer),
+            Some(&token::Amount::from(1010))
+        );
+        assert_eq!(
+            accumulator.rewards.get(&signer),
+            Some(&token::Amount::from(10))
+        );
+        assert_eq!(paid, token::Amount::from(1020));
+
+        // The rewards are only paid out after the epoch in which they have
+        // been accumulated
+        let scaled =
+            scale_pos_rewards(&storage, token::Amount::from(1020), Epoch(0))
+                .expect("scaling failed");
+        assert!(scaled.is_none());
+    }
+
+    /// Test that the PoS block rewards that haven't been scaled to the
+    /// inflation are discarded instead of being paid out.
+    #[test]
+    fn test_discard_pos_rewards() {
+        let mut storage = TestStorage::default();
+        storage.write_pos_params(&PosParams::default());
+        let proposer = established_address_1();
+        storage.write_validator_staking_reward_address(
+            &proposer,
+            &established_address_2(),
+        );
+        storage.accumulate_block_rewards(Epoch(0), Some(&proposer), &[]);
+
+        // The rewards of the current epoch are kept
+        discard_pos_rewards(&mut storage, Epoch(0));
+        let accumulator = storage.read_rewards_accumulator().unwrap();
+        assert!(accumulator.rewards.contains_key(&proposer));
+
+        // The rewards of a previous epoch are discarded
+        discard_pos_rewards(&mut storage, Epoch(1));
+        let accumulator = storage.read_rewards_accumulator().unwrap();
+        assert_eq!(accumulator.epoch, Epoch(1).into());
+        assert!(accumulator.rewards.is_empty());
+    }
+}
@@ -1,10 +1,14 @@
 //! The ledger modules
 
 pub mod gas;
+pub mod governance;
 #[cfg(feature = "ibc-vp")]
 pub mod ibc;
+pub mod inflation;
 pub mod native_vp;
 pub mod parameters;
 pub mod pos;
 pub mod storage;
+pub mod treasury;
+pub mod upgrade;
 pub mod vp_env;
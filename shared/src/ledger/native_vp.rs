@@ -221,12 +221,25 @@ where
         {
             use std::marker::PhantomData;
 
+            use crate::ledger::parameters;
             use crate::vm::host_env::VpCtx;
             use crate::vm::wasm::run::VpEvalWasm;
 
+            let wasm_limits = match parameters::read(self.storage) {
+                Ok((parameters, _gas)) => parameters.wasm_limits,
+                Err(err) => {
+                    tracing::warn!(
+                        "VP eval from a native VP failed to read the WASM \
+                         limits with: {}",
+                        err
+                    );
+                    return false;
+                }
+            };
             let eval_runner = VpEvalWasm {
                 db: PhantomData,
                 hasher: PhantomData,
+                wasm_limits,
             };
             let mut iterators: PrefixIterators<'_, DB> =
                 PrefixIterators::default();
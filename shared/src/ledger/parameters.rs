@@ -1,20 +1,49 @@
 //! Protocol parameters
+//!
+//! The parameters can be changed by a proposal voted on by validators and
+//! delegators. A proposal is submitted with the proposed parameters and the
+//! epochs of its voting period and of its activation. The votes are weighted
+//! by the voting power of the voters in the epoch in which the voting starts.
+//! A delegator's vote overrides the vote of its validator for the voting power
+//! of its delegation. A proposal passes when more than 2/3 of the total voting
+//! power votes for it. A passed proposal can be applied by anyone from its
+//! activation epoch onward, and the parameters VP checks the tally.
+//!
+//! The parameters can also be changed by the code of a passed
+//! [governance proposal](crate::ledger::governance).
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt::Display;
+use std::str::FromStr;
 
+use anoma_proof_of_stake::types::{VotingPower, VotingPowerDelta};
 use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::storage::types::decode;
+use crate::ledger::governance;
 use crate::ledger::native_vp::{self, Ctx, NativeVp};
+use crate::ledger::pos::{
+    self, bonds_for_source_prefix, is_bond_key, total_voting_power_key,
+    validator_voting_power_key, Bonds, PosParams, TotalVotingPowers,
+    ValidatorVotingPowers,
+};
 use crate::ledger::storage::types::{self, encode};
 use crate::ledger::storage::{self, Storage, StorageHasher};
 use crate::types::address::{Address, InternalAddress};
-use crate::types::storage::{DbKeySeg, Key};
+use crate::types::storage::{DbKeySeg, Epoch, Key, KeySeg};
 use crate::types::time::DurationSecs;
 
 const ADDR: InternalAddress = InternalAddress::Parameters;
 
+const PROPOSAL_STORAGE_KEY: &str = "proposal";
+const PROPOSAL_COUNTER_STORAGE_KEY: &str = "counter";
+const PROPOSAL_DATA_STORAGE_KEY: &str = "data";
+const PROPOSAL_VOTE_STORAGE_KEY: &str = "vote";
+const PROPOSAL_APPLIED_STORAGE_KEY: &str = "applied";
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum Error {
@@ -50,6 +79,8 @@ where
 pub struct Parameters {
     /// Epoch duration
     pub epoch_duration: EpochDuration,
+    /// Limits of the WASM VM for transactions and validity predicates
+    pub wasm_limits: WasmLimits,
 }
 
 /// Epoch duration. A new epoch begins as soon as both the `min_num_of_blocks`
@@ -72,6 +103,98 @@ pub struct EpochDuration {
     pub min_duration: DurationSecs,
 }
 
+/// Limits of the WASM VM used to run transactions and validity predicates. The
+/// memory limits are set in number of pages, the actual size is multiplied by
+/// the WASM page size of 64 KiB.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+)]
+pub struct WasmLimits {
+    /// Maximum number of memory pages of a transaction
+    pub tx_memory_max_pages: u32,
+    /// Maximum number of memory pages of a validity predicate
+    pub vp_memory_max_pages: u32,
+    /// Maximum stack height of a transaction or a validity predicate
+    pub stack_limit: u32,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            // 12.8 MiB
+            tx_memory_max_pages: 200,
+            // 12.8 MiB
+            vp_memory_max_pages: 200,
+            stack_limit: u16::MAX as u32,
+        }
+    }
+}
+
+/// A proposal to change the protocol parameters
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ParametersProposal {
+    /// The author of the proposal, who has to sign it
+    pub author: Address,
+    /// The proposed parameters
+    pub parameters: Parameters,
+    /// The first epoch of the voting period. The voting power of the voters
+    /// is taken from this epoch.
+    pub voting_start_epoch: Epoch,
+    /// The epoch at which the voting period ends, exclusive
+    pub voting_end_epoch: Epoch,
+    /// The epoch from which the proposal can be applied, if it passed
+    pub activation_epoch: Epoch,
+}
+
+/// A vote on a proposal
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum ProposalVote {
+    /// For the proposal
+    Yay,
+    /// Against the proposal
+    Nay,
+}
+
+/// The tally of the votes on a proposal
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize,
+)]
+pub struct ProposalTally {
+    /// The voting power that voted for the proposal
+    pub yay: u64,
+    /// The voting power that voted against the proposal
+    pub nay: u64,
+    /// The total voting power in the epoch in which the voting started
+    pub total: u64,
+}
+
+impl ProposalTally {
+    /// A proposal passes when more than 2/3 of the total voting power voted
+    /// for it
+    pub fn is_passed(&self) -> bool {
+        self.yay as u128 * 3 > self.total as u128 * 2
+    }
+}
+
 /// Initialize parameters in storage in the genesis block.
 pub fn init_genesis_storage<DB, H>(
     storage: &mut Storage<DB, H>,
@@ -114,6 +237,23 @@ where
     Ok((parameters, gas))
 }
 
+/// Read a parameters proposal from storage, if any.
+pub fn read_proposal<DB, H>(
+    storage: &Storage<DB, H>,
+    id: u64,
+) -> std::result::Result<Option<ParametersProposal>, ReadError>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: storage::StorageHasher,
+{
+    let (value, _gas) = storage
+        .read(&proposal_key(id))
+        .map_err(ReadError::StorageError)?;
+    value
+        .map(|value| decode(value).map_err(ReadError::StorageTypeError))
+        .transpose()
+}
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum WriteError {
@@ -151,25 +291,712 @@ where
 
     fn validate_tx(
         &self,
-        _tx_data: &[u8],
-        _keys_changed: &HashSet<Key>,
-        _verifiers: &HashSet<Address>,
+        tx_data: &[u8],
+        keys_changed: &HashSet<Key>,
+        verifiers: &HashSet<Address>,
+    ) -> Result<bool> {
+        // The code of a passed governance proposal may change the parameters
+        if governance::is_proposal_accepted(&self.ctx, tx_data)? {
+            return Ok(true);
+        }
+        let current_epoch = self.ctx.get_block_epoch()?;
+        for key in keys_changed {
+            let is_valid = if *key == storage_key() {
+                self.is_valid_parameters_change(keys_changed, current_epoch)?
+            } else if is_proposal_counter_key(key) {
+                self.is_valid_counter_change(keys_changed)?
+            } else if let Some(id) = is_proposal_key(key) {
+                self.is_valid_proposal(id, current_epoch, verifiers)?
+            } else if let Some((id, voter)) = is_proposal_vote_key(key) {
+                self.is_valid_vote(key, id, voter, current_epoch)?
+            } else if is_proposal_applied_key(key).is_some() {
+                // The proposal is checked with the parameters change
+                keys_changed.contains(&storage_key())
+            } else {
+                tracing::info!(
+                    "Parameters unrecognized key change {} rejected",
+                    key
+                );
+                false
+            };
+            if !is_valid {
+                tracing::info!("Parameters key change {} rejected", key);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<'a, DB, H> ParametersVp<'a, DB, H>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    /// The parameters can only be changed by applying a passed proposal in or
+    /// after its activation epoch.
+    fn is_valid_parameters_change(
+        &self,
+        keys_changed: &HashSet<Key>,
+        current_epoch: Epoch,
+    ) -> Result<bool> {
+        let applied: Vec<u64> = keys_changed
+            .iter()
+            .filter_map(is_proposal_applied_key)
+            .collect();
+        let id = match applied[..] {
+            [id] => id,
+            _ => {
+                tracing::info!(
+                    "Parameters must be changed by applying a single proposal"
+                );
+                return Ok(false);
+            }
+        };
+        let applied_key = proposal_applied_key(id);
+        if self.ctx.has_key_pre(&applied_key)? {
+            tracing::info!("Parameters proposal {} is already applied", id);
+            return Ok(false);
+        }
+        let applied_epoch = self.read_post::<Epoch>(&applied_key)?;
+        let proposal =
+            self.read_pre::<ParametersProposal>(&proposal_key(id))?;
+        let parameters = self.read_post::<Parameters>(&storage_key())?;
+        match (proposal, parameters) {
+            (Some(proposal), Some(parameters))
+                if applied_epoch == Some(current_epoch)
+                    && current_epoch >= proposal.activation_epoch
+                    && parameters == proposal.parameters =>
+            {
+                let tally = self.tally(id, &proposal)?;
+                tracing::info!("Parameters proposal {} tally: {:?}", id, tally);
+                Ok(tally.is_passed())
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// The proposal counter must be incremented by one for a new proposal.
+    fn is_valid_counter_change(
+        &self,
+        keys_changed: &HashSet<Key>,
+    ) -> Result<bool> {
+        let key = proposal_counter_key();
+        let pre = self.read_pre::<u64>(&key)?.unwrap_or_default();
+        let post = self.read_post::<u64>(&key)?;
+        Ok(post == pre.checked_add(1)
+            && keys_changed.contains(&proposal_key(pre)))
+    }
+
+    /// A new proposal must take the next ID, be signed by its author and its
+    /// epochs must be in order, starting after the current epoch.
+    fn is_valid_proposal(
+        &self,
+        id: u64,
+        current_epoch: Epoch,
+        verifiers: &HashSet<Address>,
     ) -> Result<bool> {
-        // TODO allow parameters change by over 2/3 validator voting power
-        // No changes are currently permitted
-        Ok(false)
+        let key = proposal_key(id);
+        if self.ctx.has_key_pre(&key)? {
+            tracing::info!("Parameters proposal {} cannot be modified", id);
+            return Ok(false);
+        }
+        let counter = self
+            .read_pre::<u64>(&proposal_counter_key())?
+            .unwrap_or_default();
+        let proposal = match self.read_post::<ParametersProposal>(&key)? {
+            Some(proposal) => proposal,
+            None => return Ok(false),
+        };
+        Ok(id == counter
+            && verifiers.contains(&proposal.author)
+            && current_epoch < proposal.voting_start_epoch
+            && proposal.voting_start_epoch < proposal.voting_end_epoch
+            && proposal.voting_end_epoch <= proposal.activation_epoch
+            && proposal.parameters.epoch_duration.min_num_of_blocks > 0)
+    }
+
+    /// A vote can be cast or changed within the voting period by a voter with
+    /// some voting power. The voter's VP is triggered by its address in the
+    /// vote key.
+    fn is_valid_vote(
+        &self,
+        key: &Key,
+        id: u64,
+        voter: &Address,
+        current_epoch: Epoch,
+    ) -> Result<bool> {
+        let proposal =
+            match self.read_pre::<ParametersProposal>(&proposal_key(id))? {
+                Some(proposal) => proposal,
+                None => return Ok(false),
+            };
+        if current_epoch < proposal.voting_start_epoch
+            || current_epoch >= proposal.voting_end_epoch
+        {
+            tracing::info!(
+                "Parameters proposal {} is not in its voting period",
+                id
+            );
+            return Ok(false);
+        }
+        if self.read_post::<ProposalVote>(key)?.is_none() {
+            return Ok(false);
+        }
+        let epoch = proposal.voting_start_epoch;
+        let has_voting_power = self.validator_voting_power(voter, epoch)? > 0
+            || self
+                .delegations(voter, epoch)?
+                .values()
+                .any(|power| *power > 0);
+        Ok(has_voting_power)
+    }
+
+    /// Tally the votes on a proposal with the voting powers from the epoch in
+    /// which its voting started.
+    fn tally(
+        &self,
+        id: u64,
+        proposal: &ParametersProposal,
+    ) -> Result<ProposalTally> {
+        let epoch = proposal.voting_start_epoch;
+        let total = self
+            .read_pre::<TotalVotingPowers>(&total_voting_power_key())?
+            .and_then(|powers| powers.get(epoch))
+            .map(voting_power_from_delta)
+            .unwrap_or_default();
+
+        let mut votes = vec![];
+        let mut iter = self.ctx.iter_prefix(&proposal_votes_prefix(id))?;
+        while let Some((key, value)) = self.ctx.iter_pre_next(&mut iter)? {
+            let key = match Key::parse(&key) {
+                Ok(key) => key,
+                Err(_) => continue,
+            };
+            if let (Some((_, voter)), Ok(vote)) = (
+                is_proposal_vote_key(&key),
+                ProposalVote::try_from_slice(&value[..]),
+            ) {
+                votes.push((voter.clone(), vote));
+            }
+        }
+        tally_votes(
+            votes,
+            total,
+            |voter| self.validator_voting_power(voter, epoch),
+            |voter| self.delegations(voter, epoch),
+        )
+    }
+
+    /// Read the voting power of a validator in the given epoch. Returns `0` if
+    /// the address is not a validator.
+    fn validator_voting_power(
+        &self,
+        address: &Address,
+        epoch: Epoch,
+    ) -> Result<u64> {
+        Ok(self
+            .read_pre::<ValidatorVotingPowers>(&validator_voting_power_key(
+                address,
+            ))?
+            .and_then(|powers| powers.get(epoch))
+            .map(voting_power_from_delta)
+            .unwrap_or_default())
+    }
+
+    /// Read the voting powers of the delegations from the given source to
+    /// validators in the given epoch. Self-bonds are excluded.
+    fn delegations(
+        &self,
+        source: &Address,
+        epoch: Epoch,
+    ) -> Result<HashMap<Address, u64>> {
+        let params = match self.read_pre::<PosParams>(&pos::params_key())? {
+            Some(params) => params,
+            None => return Ok(HashMap::new()),
+        };
+        let mut delegations = HashMap::new();
+        let mut iter =
+            self.ctx.iter_prefix(&bonds_for_source_prefix(source))?;
+        while let Some((key, value)) = self.ctx.iter_pre_next(&mut iter)? {
+            let bond_id = match Key::parse(&key)
+                .ok()
+                .and_then(|key| is_bond_key(&key))
+            {
+                Some(bond_id) if bond_id.source != bond_id.validator => bond_id,
+                _ => continue,
+            };
+            let tokens = match Bonds::try_from_slice(&value[..]) {
+                Ok(bonds) => bonds
+                    .get(epoch)
+                    .map(|bond| u64::from(bond.sum()))
+                    .unwrap_or_default(),
+                Err(_) => continue,
+            };
+            let power = VotingPower::try_from_tokens(tokens, &params)
+                .map(u64::from)
+                .unwrap_or_default();
+            delegations.insert(bond_id.validator, power);
+        }
+        Ok(delegations)
+    }
+
+    fn read_pre<T: BorshDeserialize>(&self, key: &Key) -> Result<Option<T>> {
+        Ok(self
+            .ctx
+            .read_pre(key)?
+            .and_then(|bytes| T::try_from_slice(&bytes[..]).ok()))
+    }
+
+    fn read_post<T: BorshDeserialize>(&self, key: &Key) -> Result<Option<T>> {
+        Ok(self
+            .ctx
+            .read_post(key)?
+            .and_then(|bytes| T::try_from_slice(&bytes[..]).ok()))
+    }
+}
+
+/// Tally the given votes. A voter with some voting power votes as a validator,
+/// otherwise its delegations to validators are counted. A delegator's vote
+/// overrides the vote of its validator for the voting power of its
+/// delegation.
+pub fn tally_votes<E, P, D>(
+    votes: impl IntoIterator<Item = (Address, ProposalVote)>,
+    total: u64,
+    validator_voting_power: P,
+    delegations: D,
+) -> std::result::Result<ProposalTally, E>
+where
+    P: Fn(&Address) -> std::result::Result<u64, E>,
+    D: Fn(&Address) -> std::result::Result<HashMap<Address, u64>, E>,
+{
+    // The votes of validators with their voting power
+    let mut validator_votes: HashMap<Address, (ProposalVote, u64)> =
+        HashMap::new();
+    // The votes of delegators with their delegations
+    let mut delegator_votes: Vec<(ProposalVote, HashMap<Address, u64>)> =
+        vec![];
+    for (voter, vote) in votes {
+        let power = validator_voting_power(&voter)?;
+        if power > 0 {
+            validator_votes.insert(voter, (vote, power));
+        } else {
+            delegator_votes.push((vote, delegations(&voter)?));
+        }
+    }
+
+    let mut tally = ProposalTally {
+        total,
+        ..ProposalTally::default()
+    };
+    let mut add_vote = |vote: ProposalVote, power: u64| match vote {
+        ProposalVote::Yay => tally.yay = tally.yay.saturating_add(power),
+        ProposalVote::Nay => tally.nay = tally.nay.saturating_add(power),
+    };
+    for (vote, delegations) in delegator_votes {
+        for (validator, power) in delegations {
+            // The delegator's vote overrides its validator's vote
+            if let Some((_, validator_power)) =
+                validator_votes.get_mut(&validator)
+            {
+                *validator_power = validator_power.saturating_sub(power);
+            }
+            add_vote(vote, power);
+        }
+    }
+    for (_validator, (vote, power)) in validator_votes {
+        add_vote(vote, power);
     }
+    Ok(tally)
+}
+
+/// Convert a voting power delta into a voting power. Negative values are
+/// treated as zero.
+pub fn voting_power_from_delta(delta: VotingPowerDelta) -> u64 {
+    let delta: i64 = delta.into();
+    u64::try_from(delta).unwrap_or_default()
 }
 
 /// Storage key used for parameters.
-fn storage_key() -> Key {
+pub fn storage_key() -> Key {
     Key {
         segments: vec![DbKeySeg::AddressSeg(Address::Internal(ADDR))],
     }
 }
 
+/// Storage key prefix for parameters proposals.
+fn proposals_prefix() -> Key {
+    storage_key()
+        .push(&PROPOSAL_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key prefix for a parameters proposal.
+fn proposal_prefix(id: u64) -> Key {
+    proposals_prefix()
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for the counter of parameters proposals, which is the ID of
+/// the next proposal.
+pub fn proposal_counter_key() -> Key {
+    proposals_prefix()
+        .push(&PROPOSAL_COUNTER_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the counter of parameters proposals?
+pub fn is_proposal_counter_key(key: &Key) -> bool {
+    *key == proposal_counter_key()
+}
+
+/// Storage key for a parameters proposal.
+pub fn proposal_key(id: u64) -> Key {
+    proposal_prefix(id)
+        .push(&PROPOSAL_DATA_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for a parameters proposal? Returns the proposal ID.
+pub fn is_proposal_key(key: &Key) -> Option<u64> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(id), DbKeySeg::StringSeg(data)]
+            if addr == &Address::Internal(ADDR)
+                && prefix == PROPOSAL_STORAGE_KEY
+                && data == PROPOSAL_DATA_STORAGE_KEY =>
+        {
+            id.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Storage key prefix for the votes on a parameters proposal.
+pub fn proposal_votes_prefix(id: u64) -> Key {
+    proposal_prefix(id)
+        .push(&PROPOSAL_VOTE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for a vote on a parameters proposal.
+pub fn proposal_vote_key(id: u64, voter: &Address) -> Key {
+    proposal_votes_prefix(id)
+        .push(&voter.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for a vote on a parameters proposal? Returns the proposal ID
+/// and the voter.
+pub fn is_proposal_vote_key(key: &Key) -> Option<(u64, &Address)> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(id), DbKeySeg::StringSeg(vote), DbKeySeg::AddressSeg(voter)]
+            if addr == &Address::Internal(ADDR)
+                && prefix == PROPOSAL_STORAGE_KEY
+                && vote == PROPOSAL_VOTE_STORAGE_KEY =>
+        {
+            id.parse().ok().map(|id| (id, voter))
+        }
+        _ => None,
+    }
+}
+
+/// Storage key for the epoch in which a parameters proposal has been applied.
+pub fn proposal_applied_key(id: u64) -> Key {
+    proposal_prefix(id)
+        .push(&PROPOSAL_APPLIED_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the epoch in which a parameters proposal has been
+/// applied? Returns the proposal ID.
+pub fn is_proposal_applied_key(key: &Key) -> Option<u64> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(id), DbKeySeg::StringSeg(applied)]
+            if addr == &Address::Internal(ADDR)
+                && prefix == PROPOSAL_STORAGE_KEY
+                && applied == PROPOSAL_APPLIED_STORAGE_KEY =>
+        {
+            id.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+impl Display for ProposalVote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProposalVote::Yay => write!(f, "yay"),
+            ProposalVote::Nay => write!(f, "nay"),
+        }
+    }
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum ParseVoteError {
+    #[error("Unexpected vote {0}, expected \"yay\" or \"nay\"")]
+    UnexpectedVote(String),
+}
+
+impl FromStr for ProposalVote {
+    type Err = ParseVoteError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "yay" => Ok(ProposalVote::Yay),
+            "nay" => Ok(ProposalVote::Nay),
+            _ => Err(ParseVoteError::UnexpectedVote(s.to_owned())),
+        }
+    }
+}
+
 impl From<native_vp::Error> for Error {
     fn from(err: native_vp::Error) -> Self {
         Self::NativeVpError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anoma_proof_of_stake::types::{BasisPoints, Bond};
+
+    use super::*;
+    use crate::ledger::gas::VpGasMeter;
+    use crate::ledger::pos::{bond_key, BondId, GenesisValidator};
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::ledger::storage::write_log::WriteLog;
+    use crate::proto::Tx;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+        established_address_4, gen_established_address,
+    };
+    use crate::types::key::ed25519::testing::keypair_1;
+    use crate::types::token;
+
+    fn validator_a() -> Address {
+        established_address_1()
+    }
+
+    fn validator_b() -> Address {
+        established_address_2()
+    }
+
+    fn delegator() -> Address {
+        established_address_3()
+    }
+
+    fn author() -> Address {
+        established_address_4()
+    }
+
+    fn parameters(min_num_of_blocks: u64) -> Parameters {
+        Parameters {
+            epoch_duration: EpochDuration {
+                min_num_of_blocks,
+                min_duration: DurationSecs(60),
+            },
+            wasm_limits: WasmLimits::default(),
+        }
+    }
+
+    fn proposal() -> ParametersProposal {
+        ParametersProposal {
+            author: author(),
+            parameters: parameters(20),
+            voting_start_epoch: Epoch(1),
+            voting_end_epoch: Epoch(3),
+            activation_epoch: Epoch(4),
+        }
+    }
+
+    /// Initialize the storage with the validator A with voting power 2000, the
+    /// validator B with voting power 1000 and a delegation from the delegator
+    /// to the validator A with voting power 500.
+    fn init_storage() -> TestStorage {
+        let mut storage = TestStorage::default();
+        init_genesis_storage(&mut storage, &parameters(10));
+
+        let params = PosParams::default();
+        let keypair = keypair_1();
+        let validators: Vec<GenesisValidator> =
+            vec![(validator_a(), 2_000_000), (validator_b(), 1_000_000)]
+                .into_iter()
+                .map(|(address, tokens)| GenesisValidator {
+                    address,
+                    staking_reward_address: gen_established_address(),
+                    tokens: token::Amount::whole(tokens),
+                    consensus_key: keypair.public.clone(),
+                    staking_reward_key: keypair.public.clone(),
+                    commission_rate: BasisPoints::new(0),
+                })
+                .collect();
+        pos::init_genesis_storage(
+            &mut storage,
+            &params,
+            validators.iter(),
+            Epoch(0),
+        );
+
+        let bond = Bond {
+            deltas: vec![(Epoch(0).into(), token::Amount::whole(500_000))]
+                .into_iter()
+                .collect(),
+        };
+        let bonds = Bonds::init_at_genesis(bond, Epoch(0));
+        let bond_id = BondId {
+            source: delegator(),
+            validator: validator_a(),
+        };
+        storage
+            .write(&bond_key(&bond_id), encode(&bonds))
+            .expect("write failed");
+        // Commit the writes to be able to iterate them
+        storage.commit().expect("commit failed");
+
+        storage
+    }
+
+    fn validate(
+        storage: &TestStorage,
+        write_log: &WriteLog,
+        keys_changed: &[Key],
+        verifiers: &[Address],
+    ) -> bool {
+        let tx = Tx::new(vec![], None);
+        let ctx = Ctx::new(storage, write_log, &tx, VpGasMeter::new(0));
+        let vp = ParametersVp { ctx };
+        let keys_changed = keys_changed.iter().cloned().collect();
+        let verifiers = verifiers.iter().cloned().collect();
+        vp.validate_tx(&[], &keys_changed, &verifiers)
+            .expect("validation failed")
+    }
+
+    #[test]
+    fn test_submit_proposal() {
+        let mut storage = init_storage();
+        let mut write_log = WriteLog::default();
+        write_log
+            .write(&proposal_key(0), encode(&proposal()))
+            .expect("write failed");
+        write_log
+            .write(&proposal_counter_key(), encode(&1_u64))
+            .expect("write failed");
+        let keys_changed = [proposal_key(0), proposal_counter_key()];
+
+        assert!(validate(&storage, &write_log, &keys_changed, &[author()]));
+        // The author must sign the proposal
+        assert!(!validate(&storage, &write_log, &keys_changed, &[]));
+        // The counter must be incremented
+        assert!(!validate(
+            &storage,
+            &write_log,
+            &[proposal_key(0)],
+            &[author()]
+        ));
+        // The voting must start after the current epoch
+        storage.block.epoch = Epoch(1);
+        assert!(!validate(&storage, &write_log, &keys_changed, &[author()]));
+    }
+
+    #[test]
+    fn test_vote_on_proposal() {
+        let mut storage = init_storage();
+        storage
+            .write(&proposal_key(0), encode(&proposal()))
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+
+        let vote = |voter: &Address, epoch: u64, storage: &mut TestStorage| {
+            storage.block.epoch = Epoch(epoch);
+            let key = proposal_vote_key(0, voter);
+            let mut write_log = WriteLog::default();
+            write_log
+                .write(&key, encode(&ProposalVote::Yay))
+                .expect("write failed");
+            validate(storage, &write_log, &[key], &[voter.clone()])
+        };
+
+        // The votes must be in the voting period
+        assert!(!vote(&validator_a(), 0, &mut storage));
+        assert!(vote(&validator_a(), 1, &mut storage));
+        assert!(vote(&validator_a(), 2, &mut storage));
+        assert!(!vote(&validator_a(), 3, &mut storage));
+        // Delegators can vote too
+        assert!(vote(&delegator(), 1, &mut storage));
+        // The voter must have some voting power
+        assert!(!vote(&author(), 1, &mut storage));
+    }
+
+    #[test]
+    fn test_apply_proposal() {
+        let mut storage = init_storage();
+        storage
+            .write(&proposal_key(0), encode(&proposal()))
+            .expect("write failed");
+        storage
+            .write(
+                &proposal_vote_key(0, &validator_a()),
+                encode(&ProposalVote::Yay),
+            )
+            .expect("write failed");
+        // The delegator's vote overrides the validator's vote for its
+        // delegation
+        storage
+            .write(
+                &proposal_vote_key(0, &delegator()),
+                encode(&ProposalVote::Nay),
+            )
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+
+        let apply =
+            |parameters: &Parameters, epoch: u64, storage: &mut TestStorage| {
+                storage.block.epoch = Epoch(epoch);
+                let mut write_log = WriteLog::default();
+                write_log
+                    .write(&storage_key(), encode(parameters))
+                    .expect("write failed");
+                write_log
+                    .write(&proposal_applied_key(0), encode(&Epoch(epoch)))
+                    .expect("write failed");
+                let keys_changed = [storage_key(), proposal_applied_key(0)];
+                validate(storage, &write_log, &keys_changed, &[])
+            };
+
+        // 1500 of 3000 voting power voted for the proposal
+        assert!(!apply(&parameters(20), 4, &mut storage));
+
+        storage
+            .write(
+                &proposal_vote_key(0, &validator_b()),
+                encode(&ProposalVote::Yay),
+            )
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+        // 2500 of 3000 voting power voted for the proposal
+        assert!(apply(&parameters(20), 4, &mut storage));
+        assert!(apply(&parameters(20), 5, &mut storage));
+        // The proposal cannot be applied before its activation epoch
+        assert!(!apply(&parameters(20), 3, &mut storage));
+        // Only the proposed parameters can be applied
+        assert!(!apply(&parameters(30), 4, &mut storage));
+
+        // A proposal cannot be applied twice
+        storage
+            .write(&proposal_applied_key(0), encode(&Epoch(4)))
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+        assert!(!apply(&parameters(20), 5, &mut storage));
+    }
+
+    #[test]
+    fn test_parameters_change_without_proposal() {
+        let storage = init_storage();
+        let mut write_log = WriteLog::default();
+        write_log
+            .write(&storage_key(), encode(&parameters(20)))
+            .expect("write failed");
+        assert!(!validate(&storage, &write_log, &[storage_key()], &[]));
+    }
+}
@@ -6,7 +6,8 @@ pub mod vp;
 pub use anoma_proof_of_stake;
 pub use anoma_proof_of_stake::parameters::PosParams;
 pub use anoma_proof_of_stake::types::{
-    self, Slash, Slashes, TotalVotingPowers, ValidatorStates,
+    self, BasisPoints, Slash, Slashes, TotalVotingPowers,
+    ValidatorCommissionRates, ValidatorMetadata, ValidatorStates,
     ValidatorVotingPowers,
 };
 use anoma_proof_of_stake::PosBase;
@@ -51,6 +52,10 @@ pub type ValidatorConsensusKeys =
         key::ed25519::PublicKey,
     >;
 
+/// Alias for a PoS type with the same name with concrete type parameters
+pub type ConsensusKeyOwner =
+    anoma_proof_of_stake::types::ConsensusKeyOwner<Address>;
+
 /// Alias for a PoS type with the same name with concrete type parameters
 pub type ValidatorTotalDeltas =
     anoma_proof_of_stake::types::ValidatorTotalDeltas<token::Change>;
@@ -61,12 +66,26 @@ pub type Bonds = anoma_proof_of_stake::types::Bonds<token::Amount>;
 /// Alias for a PoS type with the same name with concrete type parameters
 pub type Unbonds = anoma_proof_of_stake::types::Unbonds<token::Amount>;
 
+/// Alias for a PoS type with the same name with concrete type parameters
+pub type Withdrawals = anoma_proof_of_stake::types::Withdrawals<token::Amount>;
+
+/// Alias for a PoS type with the same name with concrete type parameters
+pub type Redelegations =
+    anoma_proof_of_stake::types::Redelegations<Address, token::Amount>;
+
 /// Alias for a PoS type with the same name with concrete type parameters
 pub type ValidatorSets = anoma_proof_of_stake::types::ValidatorSets<Address>;
 
 /// Alias for a PoS type with the same name with concrete type parameters
 pub type BondId = anoma_proof_of_stake::types::BondId<Address>;
 
+/// Alias for a PoS type with the same name with concrete type parameters
+pub type RewardsAccumulator =
+    anoma_proof_of_stake::types::RewardsAccumulator<Address, token::Amount>;
+
+/// Alias for a PoS type with the same name with concrete type parameters
+pub type BondRewards = anoma_proof_of_stake::types::BondRewards<token::Amount>;
+
 /// Alias for a PoS type with the same name with concrete type parameters
 pub type GenesisValidator = anoma_proof_of_stake::types::GenesisValidator<
     Address,
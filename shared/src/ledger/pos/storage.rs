@@ -2,12 +2,14 @@
 
 use anoma_proof_of_stake::parameters::PosParams;
 use anoma_proof_of_stake::types::{
-    TotalVotingPowers, ValidatorStates, ValidatorVotingPowers,
+    TotalVotingPowers, ValidatorCommissionRates, ValidatorStates,
+    ValidatorVotingPowers,
 };
 use anoma_proof_of_stake::{types, PosBase};
 
 use super::{
-    BondId, Bonds, ValidatorConsensusKeys, ValidatorSets, ValidatorTotalDeltas,
+    BondId, BondRewards, Bonds, ConsensusKeyOwner, RewardsAccumulator, Unbonds,
+    ValidatorConsensusKeys, ValidatorSets, ValidatorTotalDeltas, Withdrawals,
     ADDRESS,
 };
 use crate::ledger::storage::types::{decode, encode};
@@ -19,17 +21,26 @@ use crate::types::{key, token};
 const PARAMS_STORAGE_KEY: &str = "params";
 const VALIDATOR_STORAGE_PREFIX: &str = "validator";
 const VALIDATOR_ADDRESS_RAW_HASH: &str = "address_raw_hash";
+const CONSENSUS_KEY_OWNER_STORAGE_KEY: &str = "consensus_key_owner";
 const VALIDATOR_STAKING_REWARD_ADDRESS_STORAGE_KEY: &str =
     "staking_reward_address";
 const VALIDATOR_CONSENSUS_KEY_STORAGE_KEY: &str = "consensus_key";
 const VALIDATOR_STATE_STORAGE_KEY: &str = "state";
 const VALIDATOR_TOTAL_DELTAS_STORAGE_KEY: &str = "total_deltas";
 const VALIDATOR_VOTING_POWER_STORAGE_KEY: &str = "voting_power";
+const VALIDATOR_COMMISSION_RATE_STORAGE_KEY: &str = "commission_rate";
+const VALIDATOR_JAILED_EPOCH_STORAGE_KEY: &str = "jailed_epoch";
+const VALIDATOR_LIVENESS_STORAGE_KEY: &str = "liveness";
+const VALIDATOR_METADATA_STORAGE_KEY: &str = "metadata";
 const SLASHES_PREFIX: &str = "slash";
 const BOND_STORAGE_KEY: &str = "bond";
 const UNBOND_STORAGE_KEY: &str = "unbond";
+const REDELEGATION_STORAGE_KEY: &str = "redelegation";
+const WITHDRAWAL_STORAGE_KEY: &str = "withdrawal";
 const VALIDATOR_SET_STORAGE_KEY: &str = "validator_set";
 const TOTAL_VOTING_POWER_STORAGE_KEY: &str = "total_voting_power";
+const REWARDS_ACCUMULATOR_STORAGE_KEY: &str = "rewards_accumulator";
+const BOND_REWARDS_STORAGE_KEY: &str = "bond_rewards";
 
 /// Is the given key a PoS storage key?
 pub fn is_pos_key(key: &Key) -> bool {
@@ -90,6 +101,30 @@ pub fn is_validator_address_raw_hash_key(key: &Key) -> Option<&str> {
     }
 }
 
+/// Storage key for the owner of a consensus key for look-up from the raw hash
+/// of the key, which is the validator's address in Tendermint.
+pub fn consensus_key_owner_key(raw_hash: impl AsRef<str>) -> Key {
+    let raw_hash = raw_hash.as_ref().to_owned();
+    Key::from(ADDRESS.to_db_key())
+        .push(&CONSENSUS_KEY_OWNER_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&raw_hash)
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the owner of a consensus key?
+pub fn is_consensus_key_owner_key(key: &Key) -> Option<&str> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(raw_hash)]
+            if addr == &ADDRESS
+                && prefix == CONSENSUS_KEY_OWNER_STORAGE_KEY =>
+        {
+            Some(raw_hash)
+        }
+        _ => None,
+    }
+}
+
 /// Storage key for validator's staking reward address.
 pub fn validator_staking_reward_address_key(validator: &Address) -> Key {
     validator_prefix(validator)
@@ -195,6 +230,90 @@ pub fn is_validator_voting_power_key(key: &Key) -> Option<&Address> {
     }
 }
 
+/// Storage key for validator's commission rate.
+pub fn validator_commission_rate_key(validator: &Address) -> Key {
+    validator_prefix(validator)
+        .push(&VALIDATOR_COMMISSION_RATE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for validator's commission rate?
+pub fn is_validator_commission_rate_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::AddressSeg(validator), DbKeySeg::StringSeg(key)]
+            if addr == &ADDRESS
+                && prefix == VALIDATOR_STORAGE_PREFIX
+                && key == VALIDATOR_COMMISSION_RATE_STORAGE_KEY =>
+        {
+            Some(validator)
+        }
+        _ => None,
+    }
+}
+
+/// Storage key for validator's metadata.
+pub fn validator_metadata_key(validator: &Address) -> Key {
+    validator_prefix(validator)
+        .push(&VALIDATOR_METADATA_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for validator's metadata?
+pub fn is_validator_metadata_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::AddressSeg(validator), DbKeySeg::StringSeg(key)]
+            if addr == &ADDRESS
+                && prefix == VALIDATOR_STORAGE_PREFIX
+                && key == VALIDATOR_METADATA_STORAGE_KEY =>
+        {
+            Some(validator)
+        }
+        _ => None,
+    }
+}
+
+/// Storage key for the epoch in which a validator has been jailed.
+pub fn validator_jailed_epoch_key(validator: &Address) -> Key {
+    validator_prefix(validator)
+        .push(&VALIDATOR_JAILED_EPOCH_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for the epoch in which a validator has been jailed?
+pub fn is_validator_jailed_epoch_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::AddressSeg(validator), DbKeySeg::StringSeg(key)]
+            if addr == &ADDRESS
+                && prefix == VALIDATOR_STORAGE_PREFIX
+                && key == VALIDATOR_JAILED_EPOCH_STORAGE_KEY =>
+        {
+            Some(validator)
+        }
+        _ => None,
+    }
+}
+
+/// Storage key for validator's liveness in the signing window.
+pub fn validator_liveness_key(validator: &Address) -> Key {
+    validator_prefix(validator)
+        .push(&VALIDATOR_LIVENESS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for validator's liveness in the signing window?
+pub fn is_validator_liveness_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::AddressSeg(validator), DbKeySeg::StringSeg(key)]
+            if addr == &ADDRESS
+                && prefix == VALIDATOR_STORAGE_PREFIX
+                && key == VALIDATOR_LIVENESS_STORAGE_KEY =>
+        {
+            Some(validator)
+        }
+        _ => None,
+    }
+}
+
 /// Storage prefix for slashes.
 pub fn slashes_prefix() -> Key {
     Key::from(ADDRESS.to_db_key())
@@ -293,6 +412,79 @@ pub fn is_unbond_key(key: &Key) -> Option<BondId> {
     }
 }
 
+/// Storage key prefix for all withdrawals.
+pub fn withdrawals_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&WITHDRAWAL_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key prefix for all withdrawals of the given source address.
+pub fn withdrawals_for_source_prefix(source: &Address) -> Key {
+    withdrawals_prefix()
+        .push(&source.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for the withdrawals with the given ID (source and validator).
+pub fn withdrawal_key(bond_id: &BondId) -> Key {
+    withdrawals_for_source_prefix(&bond_id.source)
+        .push(&bond_id.validator.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for a withdrawal?
+pub fn is_withdrawal_key(key: &Key) -> Option<BondId> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::AddressSeg(source), DbKeySeg::AddressSeg(validator)]
+            if addr == &ADDRESS && prefix == WITHDRAWAL_STORAGE_KEY =>
+        {
+            Some(BondId {
+                source: source.clone(),
+                validator: validator.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Storage key prefix for all redelegations.
+pub fn redelegations_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&REDELEGATION_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key prefix for all redelegations of the given source address.
+pub fn redelegations_for_source_prefix(source: &Address) -> Key {
+    redelegations_prefix()
+        .push(&source.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for the redelegations received by a bond with the given ID
+/// (source and destination validator).
+pub fn redelegation_key(bond_id: &BondId) -> Key {
+    redelegations_for_source_prefix(&bond_id.source)
+        .push(&bond_id.validator.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for a redelegation?
+pub fn is_redelegation_key(key: &Key) -> Option<BondId> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::AddressSeg(source), DbKeySeg::AddressSeg(validator)]
+            if addr == &ADDRESS && prefix == REDELEGATION_STORAGE_KEY =>
+        {
+            Some(BondId {
+                source: source.clone(),
+                validator: validator.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
 /// Storage key for validator set (active and inactive).
 pub fn validator_set_key() -> Key {
     Key::from(ADDRESS.to_db_key())
@@ -331,6 +523,62 @@ pub fn is_total_voting_power_key(key: &Key) -> bool {
     }
 }
 
+/// Storage key for block rewards accumulated by validators in an epoch.
+pub fn rewards_accumulator_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&REWARDS_ACCUMULATOR_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for block rewards accumulated by validators?
+pub fn is_rewards_accumulator_key(key: &Key) -> bool {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)]
+            if addr == &ADDRESS && key == REWARDS_ACCUMULATOR_STORAGE_KEY =>
+        {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Storage key prefix for all bonds' rewards.
+pub fn bond_rewards_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&BOND_REWARDS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key prefix for rewards of all bonds of the given source address.
+pub fn bond_rewards_for_source_prefix(source: &Address) -> Key {
+    bond_rewards_prefix()
+        .push(&source.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for rewards of a bond with the given ID (source and
+/// validator).
+pub fn bond_rewards_key(bond_id: &BondId) -> Key {
+    bond_rewards_for_source_prefix(&bond_id.source)
+        .push(&bond_id.validator.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for rewards of a bond?
+pub fn is_bond_rewards_key(key: &Key) -> Option<BondId> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(prefix), DbKeySeg::AddressSeg(source), DbKeySeg::AddressSeg(validator)]
+            if addr == &ADDRESS && prefix == BOND_REWARDS_STORAGE_KEY =>
+        {
+            Some(BondId {
+                source: source.clone(),
+                validator: validator.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
 impl<D, H> PosBase for Storage<D, H>
 where
     D: storage::DB + for<'iter> storage::DBIter<'iter>,
@@ -363,6 +611,15 @@ where
         value.map(|value| decode(value).unwrap())
     }
 
+    fn read_consensus_key_owner(
+        &self,
+        raw_hash: impl AsRef<str>,
+    ) -> Option<ConsensusKeyOwner> {
+        let (value, _gas) =
+            self.read(&consensus_key_owner_key(raw_hash)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
     fn read_validator_consensus_key(
         &self,
         key: &Self::Address,
@@ -415,6 +672,105 @@ where
         decode(value.unwrap()).unwrap()
     }
 
+    fn read_validator_staking_reward_address(
+        &self,
+        key: &Self::Address,
+    ) -> Option<Self::Address> {
+        let (value, _gas) = self
+            .read(&validator_staking_reward_address_key(key))
+            .unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
+    fn read_validator_commission_rate(
+        &self,
+        key: &Self::Address,
+    ) -> Option<ValidatorCommissionRates> {
+        let (value, _gas) =
+            self.read(&validator_commission_rate_key(key)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
+    fn read_validator_jailed_epoch(
+        &self,
+        key: &Self::Address,
+    ) -> Option<types::Epoch> {
+        let (value, _gas) =
+            self.read(&validator_jailed_epoch_key(key)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
+    fn read_validator_liveness(
+        &self,
+        key: &Self::Address,
+    ) -> types::ValidatorLiveness {
+        let (value, _gas) = self.read(&validator_liveness_key(key)).unwrap();
+        value
+            .map(|value| decode(value).unwrap())
+            .unwrap_or_default()
+    }
+
+    fn read_validator_bonds(
+        &self,
+        validator: &Self::Address,
+    ) -> Vec<(BondId, Bonds)> {
+        let (iter, _gas) = self.iter_prefix(&bonds_prefix());
+        iter.filter_map(|(key, value, _gas)| {
+            let key = Key::parse(key).ok()?;
+            let bond_id = is_bond_key(&key)?;
+            if &bond_id.validator == validator {
+                Some((bond_id, decode(value).unwrap()))
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    fn read_validator_unbonds(
+        &self,
+        validator: &Self::Address,
+    ) -> Vec<(BondId, Unbonds)> {
+        let (iter, _gas) = self.iter_prefix(&unbonds_prefix());
+        iter.filter_map(|(key, value, _gas)| {
+            let key = Key::parse(key).ok()?;
+            let bond_id = is_unbond_key(&key)?;
+            if &bond_id.validator == validator {
+                Some((bond_id, decode(value).unwrap()))
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    fn read_validator_withdrawals(
+        &self,
+        validator: &Self::Address,
+    ) -> Vec<(BondId, Withdrawals)> {
+        let (iter, _gas) = self.iter_prefix(&withdrawals_prefix());
+        iter.filter_map(|(key, value, _gas)| {
+            let key = Key::parse(key).ok()?;
+            let bond_id = is_withdrawal_key(&key)?;
+            if &bond_id.validator == validator {
+                Some((bond_id, decode(value).unwrap()))
+            } else {
+                None
+            }
+        })
+        .collect()
+    }
+
+    fn read_rewards_accumulator(&self) -> Option<RewardsAccumulator> {
+        let (value, _gas) = self.read(&rewards_accumulator_key()).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
+    fn read_bond_rewards(&self, key: &BondId) -> Option<BondRewards> {
+        let (value, _gas) = self.read(&bond_rewards_key(key)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
     fn write_pos_params(&mut self, params: &PosParams) {
         self.write(&params_key(), encode(params)).unwrap();
     }
@@ -443,6 +799,15 @@ where
             .unwrap();
     }
 
+    fn write_consensus_key_owner(
+        &mut self,
+        key: &Self::PublicKey,
+        value: &ConsensusKeyOwner,
+    ) {
+        self.write(&consensus_key_owner_key(key.tm_raw_hash()), encode(value))
+            .unwrap();
+    }
+
     fn write_validator_state(
         &mut self,
         key: &Self::Address,
@@ -470,6 +835,33 @@ where
             .unwrap();
     }
 
+    fn write_validator_commission_rate(
+        &mut self,
+        key: &Self::Address,
+        value: &ValidatorCommissionRates,
+    ) {
+        self.write(&validator_commission_rate_key(key), encode(value))
+            .unwrap();
+    }
+
+    fn write_validator_jailed_epoch(
+        &mut self,
+        key: &Self::Address,
+        value: types::Epoch,
+    ) {
+        self.write(&validator_jailed_epoch_key(key), encode(&value))
+            .unwrap();
+    }
+
+    fn write_validator_liveness(
+        &mut self,
+        key: &Self::Address,
+        value: &types::ValidatorLiveness,
+    ) {
+        self.write(&validator_liveness_key(key), encode(value))
+            .unwrap();
+    }
+
     fn write_validator_slash(
         &mut self,
         validator: &Self::Address,
@@ -485,6 +877,10 @@ where
         self.write(&bond_key(key), encode(value)).unwrap();
     }
 
+    fn write_withdrawals(&mut self, key: &BondId, value: &Withdrawals) {
+        self.write(&withdrawal_key(key), encode(value)).unwrap();
+    }
+
     fn write_validator_set(&mut self, value: &ValidatorSets) {
         self.write(&validator_set_key(), encode(value)).unwrap();
     }
@@ -494,6 +890,15 @@ where
             .unwrap();
     }
 
+    fn write_rewards_accumulator(&mut self, value: &RewardsAccumulator) {
+        self.write(&rewards_accumulator_key(), encode(value))
+            .unwrap();
+    }
+
+    fn write_bond_rewards(&mut self, key: &BondId, value: &BondRewards) {
+        self.write(&bond_rewards_key(key), encode(value)).unwrap();
+    }
+
     fn init_staking_reward_account(
         &mut self,
         address: &Self::Address,
@@ -524,7 +929,18 @@ where
             (Some(balance), _gas) => {
                 let balance: Self::TokenAmount =
                     decode(balance).unwrap_or_default();
-                balance + amount
+                match balance.checked_add(&amount) {
+                    Some(new_balance) => new_balance,
+                    None => {
+                        tracing::error!(
+                            "PoS system credit error, the target's balance {} \
+                             would overflow when credited {}",
+                            balance,
+                            amount
+                        );
+                        return;
+                    }
+                }
             }
             _ => amount,
         };
@@ -547,7 +963,7 @@ where
         {
             let mut src_balance: Self::TokenAmount =
                 decode(src_balance).unwrap_or_default();
-            if src_balance < amount {
+            if src_balance.spend(&amount).is_err() {
                 tracing::error!(
                     "PoS system transfer error, the source doesn't have \
                      sufficient balance. It has {}, but {} is required",
@@ -556,12 +972,22 @@ where
                 );
                 return;
             }
-            src_balance.spend(&amount);
             let (dest_balance, _gas) = self.read(&dest_key).unwrap_or_default();
-            let mut dest_balance: Self::TokenAmount = dest_balance
+            let dest_balance: Self::TokenAmount = dest_balance
                 .and_then(|b| decode(b).ok())
                 .unwrap_or_default();
-            dest_balance.receive(&amount);
+            let dest_balance = match dest_balance.checked_add(&amount) {
+                Some(dest_balance) => dest_balance,
+                None => {
+                    tracing::error!(
+                        "PoS system transfer error, the destination's balance \
+                         {} would overflow when receiving {}",
+                        dest_balance,
+                        amount
+                    );
+                    return;
+                }
+            };
             self.write(&src_key, encode(&src_balance))
                 .expect("Unable to write token balance for PoS system");
             self.write(&dest_key, encode(&dest_balance))
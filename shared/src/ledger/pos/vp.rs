@@ -1,13 +1,13 @@
 //! Proof-of-Stake native validity predicate.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::panic::{RefUnwindSafe, UnwindSafe};
 
 pub use anoma_proof_of_stake;
 pub use anoma_proof_of_stake::parameters::PosParams;
 pub use anoma_proof_of_stake::types::{
-    self, Slash, Slashes, TotalVotingPowers, ValidatorStates,
-    ValidatorVotingPowers,
+    self, Slash, Slashes, TotalVotingPowers, ValidatorCommissionRates,
+    ValidatorStates, ValidatorVotingPowers,
 };
 use anoma_proof_of_stake::validation::validate;
 use anoma_proof_of_stake::{validation, PosReadOnly};
@@ -16,20 +16,25 @@ use itertools::Itertools;
 use thiserror::Error;
 
 use super::{
-    bond_key, is_bond_key, is_params_key, is_total_voting_power_key,
-    is_unbond_key, is_validator_set_key,
-    is_validator_staking_reward_address_key, is_validator_total_deltas_key,
-    is_validator_voting_power_key, params_key, staking_token_address,
-    total_voting_power_key, unbond_key, validator_consensus_key_key,
-    validator_set_key, validator_slashes_key,
+    bond_key, bond_rewards_key, consensus_key_owner_key, is_bond_key,
+    is_bond_rewards_key, is_params_key, is_redelegation_key,
+    is_total_voting_power_key, is_unbond_key, is_validator_commission_rate_key,
+    is_validator_jailed_epoch_key, is_validator_metadata_key,
+    is_validator_set_key, is_validator_staking_reward_address_key,
+    is_validator_total_deltas_key, is_validator_voting_power_key,
+    is_withdrawal_key, params_key, redelegation_key, staking_token_address,
+    total_voting_power_key, unbond_key, validator_commission_rate_key,
+    validator_consensus_key_key, validator_jailed_epoch_key,
+    validator_metadata_key, validator_set_key, validator_slashes_key,
     validator_staking_reward_address_key, validator_state_key,
-    validator_total_deltas_key, validator_voting_power_key, BondId, Bonds,
-    Unbonds, ValidatorConsensusKeys, ValidatorSets, ValidatorTotalDeltas,
+    validator_total_deltas_key, validator_voting_power_key, withdrawal_key,
+    BondId, Bonds, Redelegations, Unbonds, ValidatorConsensusKeys,
+    ValidatorSets, ValidatorTotalDeltas, Withdrawals,
 };
 use crate::ledger::native_vp::{self, Ctx, NativeVp};
 use crate::ledger::pos::{
-    is_validator_address_raw_hash_key, is_validator_consensus_key_key,
-    is_validator_state_key,
+    is_consensus_key_owner_key, is_validator_address_raw_hash_key,
+    is_validator_consensus_key_key, is_validator_state_key, ConsensusKeyOwner,
 };
 use crate::ledger::storage::types::decode;
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
@@ -57,10 +62,11 @@ where
     pub ctx: Ctx<'a, DB, H>,
 }
 
-// TODO this is temporarily to run PoS native VP in a new thread to avoid
-// crashing the ledger (in apps/src/lib/node/ledger/protocol/mod.rs). The
-// RefCells contained within PosVP are not thread-safe, but each thread has its
-// own instances.
+// TODO this is temporarily to catch panics of the PoS native VP to avoid
+// crashing the ledger (in apps/src/lib/node/ledger/protocol/mod.rs), because
+// the storage reads of the `PosReadOnly` implementation below are infallible
+// and panic on a storage or decoding error. The RefCells contained within
+// PosVP are not shared with any other thread.
 impl<DB, H> UnwindSafe for PosVP<'_, DB, H>
 where
     DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
@@ -68,10 +74,8 @@ where
 {
 }
 
-// TODO this is temporarily to run PoS native VP in a new thread to avoid
-// crashing the ledger (in apps/src/lib/node/ledger/protocol/mod.rs). The
-// RefCells contained within PosVP are not thread-safe, but each thread has its
-// own instances.
+// TODO this is temporarily to catch panics of the PoS native VP, see the
+// comment on `UnwindSafe` above.
 impl<DB, H> RefUnwindSafe for PosVP<'_, DB, H>
 where
     DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
@@ -100,6 +104,18 @@ where
 
         let mut changes: Vec<DataUpdate<_, _, _, _>> = vec![];
         let current_epoch = self.ctx.get_block_epoch()?;
+        let params = match self
+            .ctx
+            .read_pre(&params_key())?
+            .and_then(|bytes| PosParams::try_from_slice(&bytes[..]).ok())
+        {
+            Some(params) => params,
+            None => {
+                tracing::info!("PoS parameters could not be read");
+                return Ok(false);
+            }
+        };
+        let pipeline_epoch = current_epoch + params.pipeline_len;
         for key in keys_changed {
             if is_params_key(key) {
                 // TODO parameters changes are not yet implemented
@@ -182,6 +198,50 @@ where
                     address: validator.clone(),
                     update: VotingPowerUpdate(Data { pre, post }),
                 });
+            } else if let Some(validator) =
+                is_validator_commission_rate_key(key)
+            {
+                let pre = self.ctx.read_pre(key)?.and_then(|bytes| {
+                    ValidatorCommissionRates::try_from_slice(&bytes[..]).ok()
+                });
+                let post = self.ctx.read_post(key)?.and_then(|bytes| {
+                    ValidatorCommissionRates::try_from_slice(&bytes[..]).ok()
+                });
+                changes.push(Validator {
+                    address: validator.clone(),
+                    update: CommissionRate(Data { pre, post }),
+                });
+            } else if let Some(validator) = is_validator_metadata_key(key) {
+                let pre = self.ctx.read_pre(key)?.and_then(|bytes| {
+                    types::ValidatorMetadata::try_from_slice(&bytes[..]).ok()
+                });
+                let post = self.ctx.read_post(key)?.and_then(|bytes| {
+                    types::ValidatorMetadata::try_from_slice(&bytes[..]).ok()
+                });
+                changes.push(Validator {
+                    address: validator.clone(),
+                    update: Metadata(Data { pre, post }),
+                });
+            } else if let Some(validator) = is_validator_jailed_epoch_key(key) {
+                let pre = self.ctx.read_pre(key)?.and_then(|bytes| {
+                    types::Epoch::try_from_slice(&bytes[..]).ok()
+                });
+                let post = self.ctx.read_post(key)?.and_then(|bytes| {
+                    types::Epoch::try_from_slice(&bytes[..]).ok()
+                });
+                let voting_power = self
+                    .ctx
+                    .read_post(&validator_voting_power_key(validator))?
+                    .and_then(|bytes| {
+                        ValidatorVotingPowers::try_from_slice(&bytes[..]).ok()
+                    });
+                changes.push(Validator {
+                    address: validator.clone(),
+                    update: JailedEpoch {
+                        data: Data { pre, post },
+                        voting_power,
+                    },
+                });
             } else if let Some(raw_hash) =
                 is_validator_address_raw_hash_key(key)
             {
@@ -208,6 +268,37 @@ where
                     raw_hash: raw_hash.to_string(),
                     data: Data { pre, post },
                 });
+            } else if let Some(raw_hash) = is_consensus_key_owner_key(key) {
+                let pre = self.ctx.read_pre(key)?.and_then(|bytes| {
+                    ConsensusKeyOwner::try_from_slice(&bytes[..]).ok()
+                });
+                let post = self.ctx.read_post(key)?.and_then(|bytes| {
+                    ConsensusKeyOwner::try_from_slice(&bytes[..]).ok()
+                });
+                // Find the raw hash of the owner's consensus key in the
+                // pipeline epoch
+                let pipeline_key_raw_hash = match post.as_ref().or(pre.as_ref())
+                {
+                    Some(owner) => self
+                        .ctx
+                        .read_post(&validator_consensus_key_key(
+                            &owner.validator,
+                        ))?
+                        .and_then(|bytes| {
+                            ValidatorConsensusKeys::try_from_slice(&bytes[..])
+                                .ok()
+                        })
+                        .and_then(|keys| {
+                            keys.get(pipeline_epoch)
+                                .map(|key| key.tm_raw_hash())
+                        }),
+                    None => None,
+                };
+                changes.push(ValidatorConsensusKeyOwner {
+                    raw_hash: raw_hash.to_string(),
+                    data: Data { pre, post },
+                    pipeline_key_raw_hash,
+                });
             } else if let Some(owner) =
                 token::is_balance_key(&staking_token_address(), key)
             {
@@ -261,6 +352,61 @@ where
                     data: Data { pre, post },
                     slashes,
                 });
+            } else if let Some(bond_id) = is_redelegation_key(key) {
+                let pre = self.ctx.read_pre(key)?.and_then(|bytes| {
+                    Redelegations::try_from_slice(&bytes[..]).ok()
+                });
+                let post = self.ctx.read_post(key)?.and_then(|bytes| {
+                    Redelegations::try_from_slice(&bytes[..]).ok()
+                });
+                // For redelegations, we need to look-up slashes of all the
+                // source validators
+                let mut slashes = HashMap::new();
+                for src_validator in pre
+                    .iter()
+                    .chain(post.iter())
+                    .flat_map(|redelegations| redelegations.deltas.keys())
+                    .map(|(_epoch, src_validator, _bond_start)| src_validator)
+                {
+                    if slashes.contains_key(src_validator) {
+                        continue;
+                    }
+                    let validator_slashes = self
+                        .ctx
+                        .read_pre(&validator_slashes_key(src_validator))?
+                        .and_then(|bytes| {
+                            Slashes::try_from_slice(&bytes[..]).ok()
+                        })
+                        .unwrap_or_default();
+                    slashes.insert(src_validator.clone(), validator_slashes);
+                }
+                changes.push(Redelegation {
+                    id: bond_id,
+                    data: Data { pre, post },
+                    slashes,
+                });
+            } else if let Some(bond_id) = is_withdrawal_key(key) {
+                let pre = self.ctx.read_pre(key)?.and_then(|bytes| {
+                    Withdrawals::try_from_slice(&bytes[..]).ok()
+                });
+                let post = self.ctx.read_post(key)?.and_then(|bytes| {
+                    Withdrawals::try_from_slice(&bytes[..]).ok()
+                });
+                changes.push(Withdrawal {
+                    id: bond_id,
+                    data: Data { pre, post },
+                });
+            } else if let Some(bond_id) = is_bond_rewards_key(key) {
+                let pre = self.ctx.read_pre(key)?.and_then(|bytes| {
+                    super::BondRewards::try_from_slice(&bytes[..]).ok()
+                });
+                let post = self.ctx.read_post(key)?.and_then(|bytes| {
+                    super::BondRewards::try_from_slice(&bytes[..]).ok()
+                });
+                changes.push(BondRewards {
+                    id: bond_id,
+                    data: Data { pre, post },
+                });
             } else if is_total_voting_power_key(key) {
                 let pre = self.ctx.read_pre(key)?.and_then(|bytes| {
                     TotalVotingPowers::try_from_slice(&bytes[..]).ok()
@@ -274,8 +420,23 @@ where
                 return Ok(false);
             }
         }
+        // The slash pool's balance may only change by the repaid clawbacks
+        let slash_pool_balance_key = token::balance_key(
+            &staking_token_address(),
+            &super::SLASH_POOL_ADDRESS,
+        );
+        let pre = self
+            .ctx
+            .read_pre(&slash_pool_balance_key)?
+            .and_then(|bytes| token::Amount::try_from_slice(&bytes[..]).ok());
+        let post = self
+            .ctx
+            .read_post(&slash_pool_balance_key)?
+            .and_then(|bytes| token::Amount::try_from_slice(&bytes[..]).ok());
+        if pre != post {
+            changes.push(SlashPoolBalance(Data { pre, post }));
+        }
 
-        let params = self.read_pos_params();
         let errors = validate(&params, changes, current_epoch);
         Ok(if errors.is_empty() {
             true
@@ -300,6 +461,7 @@ where
     type TokenChange = token::Change;
 
     const POS_ADDRESS: Self::Address = super::ADDRESS;
+    const POS_SLASH_POOL_ADDRESS: Self::Address = super::SLASH_POOL_ADDRESS;
 
     fn staking_token_address() -> Self::Address {
         super::staking_token_address()
@@ -332,6 +494,17 @@ where
         value.map(|value| decode(value).unwrap())
     }
 
+    fn read_consensus_key_owner(
+        &self,
+        key: &Self::PublicKey,
+    ) -> Option<ConsensusKeyOwner> {
+        let value = self
+            .ctx
+            .read_pre(&consensus_key_owner_key(key.tm_raw_hash()))
+            .unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
     fn read_validator_state(
         &self,
         key: &Self::Address,
@@ -358,6 +531,34 @@ where
         value.map(|value| decode(value).unwrap())
     }
 
+    fn read_validator_commission_rate(
+        &self,
+        key: &Self::Address,
+    ) -> Option<ValidatorCommissionRates> {
+        let value = self
+            .ctx
+            .read_pre(&validator_commission_rate_key(key))
+            .unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
+    fn read_validator_jailed_epoch(
+        &self,
+        key: &Self::Address,
+    ) -> Option<types::Epoch> {
+        let value =
+            self.ctx.read_pre(&validator_jailed_epoch_key(key)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
+    fn read_validator_metadata(
+        &self,
+        key: &Self::Address,
+    ) -> Option<types::ValidatorMetadata> {
+        let value = self.ctx.read_pre(&validator_metadata_key(key)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
     fn read_validator_slashes(&self, key: &Self::Address) -> Vec<types::Slash> {
         let value = self.ctx.read_pre(&validator_slashes_key(key)).unwrap();
         value
@@ -375,6 +576,21 @@ where
         value.map(|value| decode(value).unwrap())
     }
 
+    fn read_redelegations(&self, key: &BondId) -> Option<Redelegations> {
+        let value = self.ctx.read_pre(&redelegation_key(key)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
+    fn read_withdrawals(&self, key: &BondId) -> Option<Withdrawals> {
+        let value = self.ctx.read_pre(&withdrawal_key(key)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
+    fn read_bond_rewards(&self, key: &BondId) -> Option<super::BondRewards> {
+        let value = self.ctx.read_pre(&bond_rewards_key(key)).unwrap();
+        value.map(|value| decode(value).unwrap())
+    }
+
     fn read_validator_set(&self) -> ValidatorSets {
         let value = self.ctx.read_pre(&validator_set_key()).unwrap().unwrap();
         decode(value).unwrap()
@@ -395,3 +611,69 @@ impl From<native_vp::Error> for Error {
         Self::NativeVpError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anoma_proof_of_stake::epoched::DynEpochOffset;
+
+    use super::*;
+    use crate::ledger::gas::VpGasMeter;
+    use crate::ledger::pos::{init_genesis_storage, GenesisValidator};
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::ledger::storage::types::encode;
+    use crate::ledger::storage::write_log::WriteLog;
+    use crate::proto::Tx;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use crate::types::key::ed25519::testing::{keypair_1, keypair_2};
+    use crate::types::storage::Epoch;
+
+    /// Test that the validation of token deltas that overflow is rejected
+    /// rather than panicking.
+    #[test]
+    fn test_overflowing_total_deltas() {
+        let mut storage = TestStorage::default();
+        let params = PosParams::default();
+        let validator = established_address_1();
+        let genesis_validator = GenesisValidator {
+            address: validator.clone(),
+            staking_reward_address: established_address_2(),
+            tokens: token::Amount::whole(100_000),
+            consensus_key: keypair_1().public,
+            staking_reward_key: keypair_2().public,
+            commission_rate: types::BasisPoints::new(500),
+        };
+        let current_epoch = Epoch::default();
+        init_genesis_storage(
+            &mut storage,
+            &params,
+            [genesis_validator].iter(),
+            current_epoch,
+        );
+
+        // Add the maximum token change on top of the validator's stake
+        let key = validator_total_deltas_key(&validator);
+        let mut total_deltas: ValidatorTotalDeltas =
+            decode(storage.read(&key).unwrap().0.unwrap()).unwrap();
+        total_deltas.add_at_offset(
+            token::Change::MAX,
+            current_epoch,
+            DynEpochOffset::PipelineLen,
+            &params,
+        );
+        let mut write_log = WriteLog::default();
+        write_log
+            .write(&key, encode(&total_deltas))
+            .expect("write failed");
+
+        let tx = Tx::new(vec![], None);
+        let ctx = Ctx::new(&storage, &write_log, &tx, VpGasMeter::new(0));
+        let vp = PosVP { ctx };
+        let keys_changed = [key].iter().cloned().collect();
+        let accepted = vp
+            .validate_tx(&[], &keys_changed, &HashSet::new())
+            .expect("validation failed");
+        assert!(!accepted);
+    }
+}
@@ -9,6 +9,7 @@ use core::fmt::Debug;
 use std::collections::HashMap;
 use std::fmt::Display;
 
+use ics23::{HashOp, InnerSpec, LeafOp, LengthOp, ProofSpec};
 use prost::Message;
 use sparse_merkle_tree::default_store::DefaultStore;
 use sparse_merkle_tree::{SparseMerkleTree, H256};
@@ -90,6 +91,8 @@ pub enum Error {
     MerkleTreeError(sparse_merkle_tree::error::Error),
     #[error("Merkle tree error: {0}")]
     DBError(String),
+    #[error("The storage hasher has no ICS-23 hash operation")]
+    NoProofSpecs,
 }
 
 /// The block's state as stored in the database.
@@ -404,6 +407,37 @@ where
         })
     }
 
+    /// Get the ICS-23 proof specs to verify the proofs from
+    /// [`Storage::get_proof`]. The key of a proof is already hashed with
+    /// [`StorageHasher::hash_key`] and the value is hashed by the leaf op.
+    /// Returns an error if the storage hasher cannot be expressed as an ICS-23
+    /// hash operation, because the proofs couldn't be verified with the
+    /// specs.
+    pub fn get_proof_specs(&self) -> Result<Vec<ProofSpec>> {
+        let hash_op = H::hash_op().ok_or(Error::NoProofSpecs)?;
+        let leaf_spec = LeafOp {
+            hash: hash_op.into(),
+            prehash_key: HashOp::NoHash.into(),
+            prehash_value: hash_op.into(),
+            length: LengthOp::NoPrefix.into(),
+            prefix: vec![],
+        };
+        let inner_spec = InnerSpec {
+            child_order: vec![0, 1],
+            child_size: H256::zero().as_slice().len() as _,
+            min_prefix_length: 0,
+            max_prefix_length: 0,
+            empty_child: H256::zero().as_slice().to_vec(),
+            hash: hash_op.into(),
+        };
+        Ok(vec![ProofSpec {
+            leaf_spec: Some(leaf_spec),
+            inner_spec: Some(inner_spec),
+            max_depth: 256,
+            min_depth: 0,
+        }])
+    }
+
     /// Get the current (yet to be committed) block epoch
     pub fn get_current_epoch(&self) -> (Epoch, u64) {
         (self.block.epoch, MIN_STORAGE_GAS)
@@ -504,6 +538,9 @@ pub trait StorageHasher: sparse_merkle_tree::traits::Hasher + Default {
     fn hash_key(key: &Key) -> H256;
     /// Hash a storage value
     fn hash_value(value: impl AsRef<[u8]>) -> H256;
+    /// The ICS-23 hash operation of this hasher for the proof specs, if the
+    /// hasher has one
+    fn hash_op() -> Option<HashOp>;
 }
 
 /// Helpers for testing components that depend on storage
@@ -560,6 +597,10 @@ pub mod testing {
             );
             bytes.into()
         }
+
+        fn hash_op() -> Option<HashOp> {
+            Some(HashOp::Sha256)
+        }
     }
 
     /// Storage with a mock DB for testing
@@ -663,6 +704,7 @@ mod tests {
             };
             let mut parameters = Parameters {
                 epoch_duration: epoch_duration.clone(),
+                wasm_limits: Default::default(),
             };
             parameters::init_genesis_storage(&mut storage, &parameters);
 
@@ -0,0 +1,443 @@
+//! Protocol treasury integration as a native validity predicate
+//!
+//! The treasury receives a share of the transaction fees paid by wrapper
+//! transactions, a share of the [inflation](crate::ledger::inflation)
+//! minted in every epoch and the deposits of rejected governance proposals.
+//! Anyone can deposit tokens into the treasury, but the tokens can only be
+//! spent by the code of a passed
+//! [governance proposal](crate::ledger::governance).
+
+use std::collections::{HashMap, HashSet};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use thiserror::Error;
+
+use crate::ledger::governance;
+use crate::ledger::native_vp::{self, Ctx, NativeVp};
+use crate::ledger::pos::types::BasisPoints;
+use crate::ledger::storage::types::encode;
+use crate::ledger::storage::{self as ledger_storage, Storage, StorageHasher};
+use crate::types::address::{self, Address, InternalAddress};
+use crate::types::storage::{DbKeySeg, Key};
+use crate::types::token;
+
+/// Address of the treasury account implemented as a native VP
+pub const ADDRESS: Address = Address::Internal(InternalAddress::Treasury);
+
+const PARAMS_STORAGE_KEY: &str = "params";
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Native VP error: {0}")]
+    NativeVpError(native_vp::Error),
+    #[error("Storage error: {0}")]
+    StorageError(ledger_storage::Error),
+    #[error("The balance of {owner} in token {token} would overflow")]
+    BalanceOverflow { token: Address, owner: Address },
+}
+
+/// Treasury functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Treasury parameters
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct TreasuryParams {
+    /// Portion of every transaction fee that is credited to the treasury. The
+    /// rest is credited to the block proposer.
+    pub fee_share: BasisPoints,
+}
+
+impl Default for TreasuryParams {
+    fn default() -> Self {
+        Self {
+            // 20%
+            fee_share: BasisPoints::new(2000),
+        }
+    }
+}
+
+/// Address of the token minted into the treasury (XAN)
+pub fn inflation_token_address() -> Address {
+    address::xan()
+}
+
+/// Is the given key a treasury storage key?
+pub fn is_treasury_key(key: &Key) -> bool {
+    match &key.segments.get(0) {
+        Some(DbKeySeg::AddressSeg(addr)) => addr == &ADDRESS,
+        _ => false,
+    }
+}
+
+/// Storage key for treasury parameters.
+pub fn params_key() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&PARAMS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Is storage key for treasury parameters?
+pub fn is_params_key(key: &Key) -> bool {
+    *key == params_key()
+}
+
+/// Initialize treasury parameters and its balance of the native token in
+/// storage in the genesis block.
+pub fn init_genesis_storage<DB, H>(
+    storage: &mut Storage<DB, H>,
+    params: &TreasuryParams,
+    balance: token::Amount,
+) where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    storage
+        .write(&params_key(), encode(params))
+        .expect("Treasury parameters must be initialized in the genesis block");
+    storage
+        .write(
+            &token::balance_key(&inflation_token_address(), &ADDRESS),
+            encode(&balance),
+        )
+        .expect("Treasury balance must be initialized in the genesis block");
+}
+
+/// Read the treasury parameters.
+pub fn read_params<DB, H>(
+    storage: &Storage<DB, H>,
+) -> ledger_storage::Result<Option<TreasuryParams>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    read(storage, &params_key())
+}
+
+/// Mint the given amount of the native token into the treasury.
+pub fn mint<DB, H>(
+    storage: &mut Storage<DB, H>,
+    amount: token::Amount,
+) -> Result<()>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let token = inflation_token_address();
+    let mut balances = HashMap::new();
+    credit(storage, &mut balances, &token, &ADDRESS, amount)?;
+    write_balances(storage, &token, balances)
+}
+
+/// Pay a transaction fee from the fee payer's balance. The treasury's share
+/// of the fee is credited to the treasury and the rest to the block proposer,
+/// or to the treasury too if the proposer is unknown. Returns `false` without
+/// any change if the payer's balance is insufficient. The balances are only
+/// written once all of them have been updated without an error.
+pub fn pay_fee<DB, H>(
+    storage: &mut Storage<DB, H>,
+    params: &TreasuryParams,
+    token: &Address,
+    amount: token::Amount,
+    payer: &Address,
+    proposer: Option<&Address>,
+) -> Result<bool>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let payer_balance: token::Amount =
+        read(storage, &token::balance_key(token, payer))?.unwrap_or_default();
+    let payer_balance = match payer_balance.checked_sub(&amount) {
+        Some(balance) => balance,
+        None => return Ok(false),
+    };
+    let mut balances = HashMap::new();
+    balances.insert(payer.clone(), payer_balance);
+
+    // The fee share cannot be set above 100%, but if it was, the whole fee
+    // would go to the treasury
+    let treasury_share = params
+        .fee_share
+        .portion_of(u64::from(amount))
+        .unwrap_or_else(|_| u64::from(amount));
+    let treasury_share = token::Amount::from(treasury_share);
+    let (treasury_share, proposer_share) = match proposer {
+        Some(proposer) => {
+            (treasury_share, Some((proposer, amount - treasury_share)))
+        }
+        None => (amount, None),
+    };
+    credit(storage, &mut balances, token, &ADDRESS, treasury_share)?;
+    if let Some((proposer, share)) = proposer_share {
+        credit(storage, &mut balances, token, proposer, share)?;
+    }
+    write_balances(storage, token, balances)?;
+    Ok(true)
+}
+
+/// Treasury validity predicate
+pub struct TreasuryVp<'a, DB, H>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// Context to interact with the host structures.
+    pub ctx: Ctx<'a, DB, H>,
+}
+
+impl<'a, DB, H> NativeVp for TreasuryVp<'a, DB, H>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    type Error = Error;
+
+    const ADDR: InternalAddress = InternalAddress::Treasury;
+
+    fn validate_tx(
+        &self,
+        tx_data: &[u8],
+        keys_changed: &HashSet<Key>,
+        _verifiers: &HashSet<Address>,
+    ) -> Result<bool> {
+        // The treasury can only be spent from and its parameters changed by
+        // the code of a passed proposal
+        if governance::is_proposal_accepted(&self.ctx, tx_data)? {
+            return Ok(true);
+        }
+        for key in keys_changed {
+            let is_valid = match token::is_any_token_balance_key(key) {
+                Some(owner) if owner == &ADDRESS => {
+                    let pre = self.read_amount_pre(key)?;
+                    let post = self.read_amount_post(key)?;
+                    post >= pre
+                }
+                _ => false,
+            };
+            if !is_valid {
+                tracing::info!("Treasury key change {} rejected", key);
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<'a, DB, H> TreasuryVp<'a, DB, H>
+where
+    DB: 'static + ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: 'static + StorageHasher,
+{
+    fn read_amount_pre(&self, key: &Key) -> Result<token::Amount> {
+        Ok(self
+            .ctx
+            .read_pre(key)?
+            .and_then(|bytes| token::Amount::try_from_slice(&bytes[..]).ok())
+            .unwrap_or_default())
+    }
+
+    fn read_amount_post(&self, key: &Key) -> Result<token::Amount> {
+        Ok(self
+            .ctx
+            .read_post(key)?
+            .and_then(|bytes| token::Amount::try_from_slice(&bytes[..]).ok())
+            .unwrap_or_default())
+    }
+}
+
+impl From<native_vp::Error> for Error {
+    fn from(err: native_vp::Error) -> Self {
+        Self::NativeVpError(err)
+    }
+}
+
+impl From<ledger_storage::Error> for Error {
+    fn from(err: ledger_storage::Error) -> Self {
+        Self::StorageError(err)
+    }
+}
+
+/// Credit the given amount to the owner's balance of the token. The balance
+/// is read from the pending `balances`, or from storage if it hasn't been
+/// updated yet, and the new balance is only written to the pending
+/// `balances`.
+fn credit<DB, H>(
+    storage: &Storage<DB, H>,
+    balances: &mut HashMap<Address, token::Amount>,
+    token: &Address,
+    owner: &Address,
+    amount: token::Amount,
+) -> Result<()>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let balance = match balances.get(owner) {
+        Some(balance) => *balance,
+        None => read(storage, &token::balance_key(token, owner))?
+            .unwrap_or_default(),
+    };
+    let balance =
+        balance
+            .checked_add(&amount)
+            .ok_or_else(|| Error::BalanceOverflow {
+                token: token.clone(),
+                owner: owner.clone(),
+            })?;
+    balances.insert(owner.clone(), balance);
+    Ok(())
+}
+
+/// Write the pending `balances` of the token to storage.
+fn write_balances<DB, H>(
+    storage: &mut Storage<DB, H>,
+    token: &Address,
+    balances: HashMap<Address, token::Amount>,
+) -> Result<()>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    for (owner, balance) in balances {
+        storage.write(&token::balance_key(token, &owner), encode(&balance))?;
+    }
+    Ok(())
+}
+
+fn read<DB, H, T>(
+    storage: &Storage<DB, H>,
+    key: &Key,
+) -> ledger_storage::Result<Option<T>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+    T: BorshDeserialize,
+{
+    let (value, _gas) = storage.read(key)?;
+    Ok(value.and_then(|value| T::try_from_slice(&value[..]).ok()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::gas::VpGasMeter;
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::ledger::storage::write_log::WriteLog;
+    use crate::proto::Tx;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+
+    fn validate(
+        storage: &TestStorage,
+        write_log: &WriteLog,
+        tx_data: &[u8],
+        keys_changed: &[Key],
+    ) -> bool {
+        let tx = Tx::new(vec![], Some(tx_data.to_vec()));
+        let ctx = Ctx::new(storage, write_log, &tx, VpGasMeter::new(0));
+        let vp = TreasuryVp { ctx };
+        let keys_changed = keys_changed.iter().cloned().collect();
+        vp.validate_tx(tx_data, &keys_changed, &HashSet::new())
+            .expect("validation failed")
+    }
+
+    #[test]
+    fn test_spend_only_with_governance() {
+        let mut storage = TestStorage::default();
+        init_genesis_storage(
+            &mut storage,
+            &TreasuryParams::default(),
+            token::Amount::whole(1_000),
+        );
+        let key = token::balance_key(&inflation_token_address(), &ADDRESS);
+        let tx_data = 0_u64.try_to_vec().unwrap();
+
+        // Anyone can deposit into the treasury
+        let mut write_log = WriteLog::default();
+        write_log
+            .write(&key, encode(&token::Amount::whole(1_100)))
+            .expect("write failed");
+        assert!(validate(&storage, &write_log, &[], &[key.clone()]));
+
+        // Spending requires a passed proposal
+        let mut write_log = WriteLog::default();
+        write_log
+            .write(&key, encode(&token::Amount::whole(900)))
+            .expect("write failed");
+        assert!(!validate(&storage, &write_log, &tx_data, &[key.clone()]));
+        storage
+            .write(&governance::proposal_pending_key(0), vec![])
+            .expect("write failed");
+        assert!(validate(&storage, &write_log, &tx_data, &[key]));
+    }
+
+    #[test]
+    fn test_pay_fee() {
+        let mut storage = TestStorage::default();
+        let params = TreasuryParams::default();
+        let token = address::xan();
+        let payer = established_address_1();
+        let proposer = established_address_2();
+        let balance = |storage: &TestStorage, owner: &Address| {
+            read::<_, _, token::Amount>(
+                storage,
+                &token::balance_key(&token, owner),
+            )
+            .unwrap()
+            .unwrap_or_default()
+        };
+        storage
+            .write(
+                &token::balance_key(&token, &payer),
+                encode(&token::Amount::whole(100)),
+            )
+            .expect("write failed");
+
+        let paid = pay_fee(
+            &mut storage,
+            &params,
+            &token,
+            token::Amount::whole(10),
+            &payer,
+            Some(&proposer),
+        )
+        .unwrap();
+        assert!(paid);
+        assert_eq!(balance(&storage, &payer), token::Amount::whole(90));
+        assert_eq!(balance(&storage, &ADDRESS), token::Amount::whole(2));
+        assert_eq!(balance(&storage, &proposer), token::Amount::whole(8));
+
+        // The fee cannot exceed the payer's balance
+        let paid = pay_fee(
+            &mut storage,
+            &params,
+            &token,
+            token::Amount::whole(100),
+            &payer,
+            None,
+        )
+        .unwrap();
+        assert!(!paid);
+        assert_eq!(balance(&storage, &payer), token::Amount::whole(90));
+
+        // A credit that would overflow the proposer's balance is an error
+        storage
+            .write(
+                &token::balance_key(&token, &proposer),
+                encode(&token::Amount::from(u64::MAX)),
+            )
+            .expect("write failed");
+        let result = pay_fee(
+            &mut storage,
+            &params,
+            &token,
+            token::Amount::whole(10),
+            &payer,
+            Some(&proposer),
+        );
+        assert!(matches!(result, Err(Error::BalanceOverflow { .. })));
+        assert_eq!(balance(&storage, &payer), token::Amount::whole(90));
+    }
+}
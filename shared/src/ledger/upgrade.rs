@@ -0,0 +1,204 @@
+//! Protocol upgrades scheduled by governance
+//!
+//! The code of a passed [governance proposal](crate::ledger::governance) can
+//! schedule a protocol upgrade by writing an [`UpgradePlan`] with the name of
+//! the upgrade and the block height at which it takes effect. A version of
+//! the ledger that doesn't implement the upgrade halts before the block at
+//! that height, so that the node operators can switch to a version that
+//! does. The new version verifies that the upgrade it implements is scheduled
+//! and applies it, which clears the plan.
+//!
+//! The plan is kept in the parameters account's sub-space, so it can only be
+//! written by a passed governance proposal. A plan that cannot be read or
+//! decoded is an error, on which the ledger must halt too.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use thiserror::Error;
+
+use crate::ledger::storage::types::encode;
+use crate::ledger::storage::{self as ledger_storage, Storage, StorageHasher};
+use crate::types::address::{Address, InternalAddress};
+use crate::types::storage::{BlockHeight, Key, KeySeg};
+
+const ADDRESS: Address = Address::Internal(InternalAddress::Parameters);
+
+const UPGRADE_STORAGE_KEY: &str = "upgrade";
+const PLAN_STORAGE_KEY: &str = "plan";
+const APPLIED_STORAGE_KEY: &str = "applied";
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Storage error: {0}")]
+    StorageError(ledger_storage::Error),
+    #[error("The upgrade plan cannot be decoded: {0}")]
+    InvalidPlan(std::io::Error),
+}
+
+/// Upgrade functions result
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A scheduled protocol upgrade
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct UpgradePlan {
+    /// The name of the upgrade, which identifies it in the versions of the
+    /// ledger that implement it
+    pub name: String,
+    /// The height of the first block that must be applied by a version of the
+    /// ledger that implements the upgrade
+    pub height: BlockHeight,
+}
+
+/// What the ledger has to do about a scheduled upgrade before it applies a
+/// block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpgradeCheck {
+    /// No upgrade is due, the block can be applied
+    Continue,
+    /// The upgrade is due and this version implements it, so it has to be
+    /// applied before the block
+    Apply(UpgradePlan),
+    /// The upgrade is due, but this version doesn't implement it, so the
+    /// ledger must halt
+    Halt(UpgradePlan),
+    /// This version implements an upgrade that is not due yet, so it must not
+    /// apply the block
+    TooEarly(UpgradePlan),
+}
+
+fn upgrade_prefix() -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&UPGRADE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for the scheduled upgrade plan.
+pub fn plan_key() -> Key {
+    upgrade_prefix()
+        .push(&PLAN_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key for the last applied upgrade plan.
+pub fn applied_key() -> Key {
+    upgrade_prefix()
+        .push(&APPLIED_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Read the scheduled upgrade plan, if any. Returns an error if the plan
+/// cannot be decoded.
+pub fn read_plan<DB, H>(storage: &Storage<DB, H>) -> Result<Option<UpgradePlan>>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (value, _gas) = storage.read(&plan_key())?;
+    value
+        .map(|value| UpgradePlan::try_from_slice(&value[..]))
+        .transpose()
+        .map_err(Error::InvalidPlan)
+}
+
+/// Check the scheduled upgrade plan before applying the block at the given
+/// height with a version of the ledger that implements the given upgrades.
+pub fn check(
+    plan: Option<UpgradePlan>,
+    height: BlockHeight,
+    implemented: &[&str],
+) -> UpgradeCheck {
+    match plan {
+        None => UpgradeCheck::Continue,
+        Some(plan) => {
+            let is_implemented = implemented.contains(&plan.name.as_str());
+            let is_due = height >= plan.height;
+            match (is_implemented, is_due) {
+                (true, true) => UpgradeCheck::Apply(plan),
+                (false, true) => UpgradeCheck::Halt(plan),
+                (true, false) => UpgradeCheck::TooEarly(plan),
+                (false, false) => UpgradeCheck::Continue,
+            }
+        }
+    }
+}
+
+/// Apply the upgrade plan by clearing it and recording it as the last applied
+/// upgrade. The changes of the upgrade itself are up to the version of the
+/// ledger that implements it.
+pub fn apply<DB, H>(
+    storage: &mut Storage<DB, H>,
+    plan: &UpgradePlan,
+) -> Result<()>
+where
+    DB: ledger_storage::DB + for<'iter> ledger_storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    storage.delete(&plan_key())?;
+    storage.write(&applied_key(), encode(plan))?;
+    Ok(())
+}
+
+impl From<ledger_storage::Error> for Error {
+    fn from(err: ledger_storage::Error) -> Self {
+        Self::StorageError(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestStorage;
+
+    #[test]
+    fn test_check_upgrade() {
+        let plan = UpgradePlan {
+            name: "upgrade-1".to_owned(),
+            height: BlockHeight(10),
+        };
+        let check_at = |height, implemented: &[&str]| {
+            check(Some(plan.clone()), BlockHeight(height), implemented)
+        };
+
+        assert_eq!(check(None, BlockHeight(10), &[]), UpgradeCheck::Continue);
+        // The old version runs until the upgrade height
+        assert_eq!(check_at(9, &[]), UpgradeCheck::Continue);
+        assert_eq!(check_at(10, &[]), UpgradeCheck::Halt(plan.clone()));
+        assert_eq!(
+            check_at(10, &["upgrade-0"]),
+            UpgradeCheck::Halt(plan.clone())
+        );
+        // The new version takes over at the upgrade height
+        assert_eq!(
+            check_at(9, &["upgrade-0", "upgrade-1"]),
+            UpgradeCheck::TooEarly(plan.clone())
+        );
+        assert_eq!(
+            check_at(10, &["upgrade-0", "upgrade-1"]),
+            UpgradeCheck::Apply(plan.clone())
+        );
+    }
+
+    #[test]
+    fn test_apply_upgrade() {
+        let mut storage = TestStorage::default();
+        let plan = UpgradePlan {
+            name: "upgrade-1".to_owned(),
+            height: BlockHeight(10),
+        };
+        storage.write(&plan_key(), encode(&plan)).unwrap();
+        assert_eq!(read_plan(&storage).unwrap(), Some(plan.clone()));
+
+        apply(&mut storage, &plan).unwrap();
+        assert_eq!(read_plan(&storage).unwrap(), None);
+        let (applied, _gas) = storage.read(&applied_key()).unwrap();
+        assert_eq!(applied, Some(encode(&plan)));
+    }
+
+    #[test]
+    fn test_read_invalid_plan() {
+        let mut storage = TestStorage::default();
+        storage.write(&plan_key(), vec![1, 2, 3]).unwrap();
+        // A plan that cannot be decoded must not be read as no plan
+        assert!(matches!(read_plan(&storage), Err(Error::InvalidPlan(_))));
+    }
+}
@@ -43,6 +43,12 @@ mod internal {
         "ano::Inter-Blockchain Communication          ";
     pub const PARAMETERS: &str =
         "ano::Protocol Parameters                     ";
+    pub const IBC_ESCROW: &str =
+        "ano::IBC Escrow Address                      ";
+    pub const GOVERNANCE: &str =
+        "ano::Governance                              ";
+    pub const TREASURY: &str =
+        "ano::Treasury                                ";
 }
 
 /// Fixed-length address strings prefix for established addresses.
@@ -51,6 +57,8 @@ const PREFIX_ESTABLISHED: &str = "est";
 const PREFIX_IMPLICIT: &str = "imp";
 /// Fixed-length address strings prefix for internal addresses.
 const PREFIX_INTERNAL: &str = "ano";
+/// Fixed-length address strings prefix for IBC token addresses.
+const PREFIX_IBC: &str = "ibc";
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -149,12 +157,21 @@ impl Address {
             Address::Implicit(ImplicitAddress::Ed25519(pkh)) => {
                 format!("{}::{}", PREFIX_IMPLICIT, pkh)
             }
+            Address::Internal(InternalAddress::IbcToken(hash)) => {
+                format!("{}::{}", PREFIX_IBC, hash)
+            }
             Address::Internal(internal) => {
                 let string = match internal {
                     InternalAddress::PoS => internal::POS,
                     InternalAddress::PosSlashPool => internal::POS_SLASH_POOL,
                     InternalAddress::Ibc => internal::IBC,
                     InternalAddress::Parameters => internal::PARAMETERS,
+                    InternalAddress::IbcEscrow => internal::IBC_ESCROW,
+                    InternalAddress::Governance => internal::GOVERNANCE,
+                    InternalAddress::Treasury => internal::TREASURY,
+                    InternalAddress::IbcToken(_) => {
+                        unreachable!("IBC token addresses are handled above")
+                    }
                 }
                 .to_string();
                 debug_assert_eq!(string.len(), FIXED_LEN_STRING_BYTES);
@@ -201,11 +218,32 @@ impl Address {
                 internal::PARAMETERS => {
                     Ok(Address::Internal(InternalAddress::Parameters))
                 }
+                internal::IBC_ESCROW => {
+                    Ok(Address::Internal(InternalAddress::IbcEscrow))
+                }
+                internal::GOVERNANCE => {
+                    Ok(Address::Internal(InternalAddress::Governance))
+                }
+                internal::TREASURY => {
+                    Ok(Address::Internal(InternalAddress::Treasury))
+                }
                 _ => Err(Error::new(
                     ErrorKind::InvalidData,
                     "Invalid internal address",
                 )),
             },
+            Some((PREFIX_IBC, hash)) => {
+                if hash.len() == HASH_LEN {
+                    Ok(Address::Internal(InternalAddress::IbcToken(
+                        hash.to_string(),
+                    )))
+                } else {
+                    Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "IBC token address hash must be 40 characters long",
+                    ))
+                }
+            }
             _ => Err(Error::new(
                 ErrorKind::InvalidData,
                 "Invalid address prefix",
@@ -383,20 +421,39 @@ pub enum InternalAddress {
     Ibc,
     /// Protocol parameters
     Parameters,
+    /// Escrow for the tokens sent to other chains over IBC
+    IbcEscrow,
+    /// IBC token derived from the hash of its denomination trace
+    IbcToken(String),
+    /// Governance
+    Governance,
+    /// Protocol treasury
+    Treasury,
+}
+
+impl InternalAddress {
+    /// Get an IBC token address from the given denomination trace, e.g.
+    /// `transfer/channel-0/<token address>`
+    pub fn ibc_token(denom_trace: impl AsRef<str>) -> Self {
+        let digest = Sha256::digest(denom_trace.as_ref().as_bytes());
+        // hex of the first 40 chars of the hash
+        let hash = format!("{:.width$X}", digest, width = HASH_LEN);
+        Self::IbcToken(hash)
+    }
 }
 
 impl Display for InternalAddress {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Self::PoS => "PoS",
-                Self::PosSlashPool => "PosSlashPool",
-                Self::Ibc => "IBC",
-                Self::Parameters => "Parameters",
-            }
-        )
+        match self {
+            Self::PoS => write!(f, "PoS"),
+            Self::PosSlashPool => write!(f, "PosSlashPool"),
+            Self::Ibc => write!(f, "IBC"),
+            Self::Parameters => write!(f, "Parameters"),
+            Self::IbcEscrow => write!(f, "IbcEscrow"),
+            Self::IbcToken(hash) => write!(f, "IbcToken {}", hash),
+            Self::Governance => write!(f, "Governance"),
+            Self::Treasury => write!(f, "Treasury"),
+        }
     }
 }
 
@@ -490,6 +547,22 @@ pub mod tests {
         assert_eq!(encoded_address, expect);
     }
 
+    #[test]
+    fn test_ibc_token_address() {
+        let address = Address::Internal(InternalAddress::ibc_token(
+            "transfer/channel-0/xan",
+        ));
+        let encoded = address.encode();
+        assert_eq!(encoded.len(), ADDRESS_LEN);
+        assert_eq!(Address::decode(&encoded).unwrap(), address);
+
+        // A different denomination trace gives a different token
+        let other = Address::Internal(InternalAddress::ibc_token(
+            "transfer/channel-1/xan",
+        ));
+        assert_ne!(address, other);
+    }
+
     proptest! {
         #[test]
         /// Check that all the address types are of the same length
@@ -622,14 +695,22 @@ pub mod testing {
             InternalAddress::PoS => {}
             InternalAddress::PosSlashPool => {}
             InternalAddress::Ibc => {}
-            InternalAddress::Parameters => {} /* Add new addresses in the
-                                               * `prop_oneof` below. */
+            InternalAddress::Parameters => {}
+            InternalAddress::IbcEscrow => {}
+            InternalAddress::IbcToken(_) => {}
+            InternalAddress::Governance => {}
+            InternalAddress::Treasury => {} /* Add new addresses in the
+                                             * `prop_oneof` below. */
         };
         prop_oneof![
             Just(InternalAddress::PoS),
             Just(InternalAddress::PosSlashPool),
             Just(InternalAddress::Ibc),
             Just(InternalAddress::Parameters),
+            Just(InternalAddress::IbcEscrow),
+            "[a-zA-Z0-9]{40}".prop_map(InternalAddress::IbcToken),
+            Just(InternalAddress::Governance),
+            Just(InternalAddress::Treasury),
         ]
     }
 }
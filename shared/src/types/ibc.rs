@@ -1,5 +1,6 @@
 //! IBC-related data definitions and transaction and validity-predicate helpers.
 
+use std::convert::TryFrom;
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -7,7 +8,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use ibc::ics02_client::client_consensus::AnyConsensusState;
 use ibc::ics02_client::client_def::{AnyClient, ClientDef};
 use ibc::ics02_client::client_state::AnyClientState;
-use ibc::ics02_client::header::AnyHeader;
+use ibc::ics02_client::header::{AnyHeader, Header};
 use ibc::ics02_client::height::Height;
 use ibc::ics03_connection::connection::{
     ConnectionEnd, Counterparty as ConnCounterparty, State as ConnState,
@@ -17,34 +18,53 @@ use ibc::ics04_channel::channel::{
     ChannelEnd, Counterparty as ChanCounterparty, Order, State as ChanState,
 };
 use ibc::ics04_channel::packet::{Packet, Sequence};
+use ibc::ics07_tendermint::consensus_state::ConsensusState as TmConsensusState;
 use ibc::ics23_commitment::commitment::{
     CommitmentPrefix, CommitmentProofBytes,
 };
 use ibc::ics24_host::identifier::{
     ChannelId, ClientId, ConnectionId, PortChannelId, PortId,
 };
+use ibc::mock::client_state::MockConsensusState;
 use ibc::proofs::{ConsensusProof, Proofs};
 use ibc::timestamp::Timestamp;
 use ibc_proto::ibc::core::commitment::v1::MerkleProof;
 use prost::Message;
+use serde::{Deserialize, Serialize};
 use sha2::Digest;
 use thiserror::Error;
 
 use crate::types::address::{Address, InternalAddress};
-use crate::types::storage::KeySeg;
+use crate::types::key::ed25519::SignedTxData;
+use crate::types::storage::{self, KeySeg};
 use crate::types::time::{DateTimeUtc, DurationNanos};
+use crate::types::token;
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Invalid client error: {0}")]
     InvalidClient(String),
+    #[error("Invalid connection error: {0}")]
+    InvalidConnection(String),
+    #[error("Invalid channel error: {0}")]
+    InvalidChannel(String),
     #[error("Invalid port error: {0}")]
     InvalidPort(String),
     #[error("Invalid proof error: {0}")]
     InvalidProof(String),
     #[error("Updating a client error: {0}")]
     ClientUpdate(String),
+    #[error("Invalid misbehaviour error: {0}")]
+    InvalidMisbehaviour(String),
+    #[error("Decoding an IBC message error: {0}")]
+    DecodingMessage(String),
+    #[error("Unexpected IBC message error: {0}")]
+    UnexpectedMessage(String),
+    #[error("Invalid packet data error: {0}")]
+    InvalidPacketData(String),
+    #[error("Invalid denomination error: {0}")]
+    InvalidDenom(String),
 }
 
 /// Decode result for IBC data
@@ -153,6 +173,73 @@ impl ClientUpgradeData {
     }
 }
 
+/// Data to submit a misbehaviour of a client's counterparty chain. The
+/// evidence is the two conflicting headers at the same height.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ClientMisbehaviourData {
+    /// The client ID to be frozen
+    pub client_id: ClientId,
+    /// The first header
+    pub header1: AnyHeader,
+    /// The second header conflicting with the first header
+    pub header2: AnyHeader,
+}
+
+impl ClientMisbehaviourData {
+    /// Returns the data to submit a misbehaviour
+    pub fn new(
+        client_id: ClientId,
+        header1: AnyHeader,
+        header2: AnyHeader,
+    ) -> Self {
+        Self {
+            client_id,
+            header1,
+            header2,
+        }
+    }
+
+    /// Returns the height of the conflicting headers at which the client is
+    /// frozen
+    pub fn height(&self) -> Result<Height> {
+        if self.header1.client_type() != self.header2.client_type() {
+            return Err(Error::InvalidMisbehaviour(
+                "The client types of the headers are mismatched".to_owned(),
+            ));
+        }
+        let height = self.header1.height();
+        if height != self.header2.height() {
+            return Err(Error::InvalidMisbehaviour(format!(
+                "The heights of the headers are mismatched: {}, {}",
+                height,
+                self.header2.height()
+            )));
+        }
+        let header1 = self
+            .header1
+            .try_to_vec()
+            .expect("Encoding a header shouldn't fail");
+        let header2 = self
+            .header2
+            .try_to_vec()
+            .expect("Encoding a header shouldn't fail");
+        if header1 == header2 {
+            return Err(Error::InvalidMisbehaviour(
+                "The headers don't conflict".to_owned(),
+            ));
+        }
+        Ok(height)
+    }
+
+    /// Returns the consensus states derived from the headers
+    pub fn consensus_states(&self) -> (AnyConsensusState, AnyConsensusState) {
+        (
+            header_consensus_state(self.header1.clone()),
+            header_consensus_state(self.header2.clone()),
+        )
+    }
+}
+
 /// Data to initialize a connection
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct ConnectionOpenInitData {
@@ -893,6 +980,417 @@ impl TimeoutData {
     }
 }
 
+/// IBC messages handled by the IBC transaction. The transaction data is an
+/// encoded message so that the IBC validity predicate can determine which
+/// state transition has been requested.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub enum IbcMessage {
+    /// Create a client
+    ClientCreation(ClientCreationData),
+    /// Update a client
+    ClientUpdate(ClientUpdateData),
+    /// Upgrade a client
+    ClientUpgrade(ClientUpgradeData),
+    /// Submit a misbehaviour to freeze a client
+    ClientMisbehaviour(ClientMisbehaviourData),
+    /// Initialize a connection
+    ConnectionOpenInit(ConnectionOpenInitData),
+    /// Try to open a connection
+    ConnectionOpenTry(ConnectionOpenTryData),
+    /// Acknowledge the opening of a connection
+    ConnectionOpenAck(ConnectionOpenAckData),
+    /// Confirm the opening of a connection
+    ConnectionOpenConfirm(ConnectionOpenConfirmData),
+    /// Initialize a channel
+    ChannelOpenInit(ChannelOpenInitData),
+    /// Try to open a channel
+    ChannelOpenTry(ChannelOpenTryData),
+    /// Acknowledge the opening of a channel
+    ChannelOpenAck(ChannelOpenAckData),
+    /// Confirm the opening of a channel
+    ChannelOpenConfirm(ChannelOpenConfirmData),
+    /// Initialize closing a channel
+    ChannelCloseInit(ChannelCloseInitData),
+    /// Confirm closing a channel
+    ChannelCloseConfirm(ChannelCloseConfirmData),
+    /// Send a packet
+    PacketSend(PacketSendData),
+    /// Receive a packet
+    PacketReceipt(PacketReceiptData),
+    /// Acknowledge a packet
+    PacketAck(PacketAckData),
+    /// Time out a packet
+    Timeout(TimeoutData),
+}
+
+impl IbcMessage {
+    /// Decode an IBC message from the transaction data. The message can also
+    /// be wrapped in a signed transaction data, e.g. when a token owner sends
+    /// a fungible token transfer packet.
+    pub fn decode(tx_data: &[u8]) -> Result<Self> {
+        match Self::try_from_slice(tx_data) {
+            Ok(msg) => Ok(msg),
+            Err(e) => match SignedTxData::try_from_slice(tx_data) {
+                Ok(SignedTxData {
+                    data: Some(data), ..
+                }) => Self::try_from_slice(&data[..])
+                    .map_err(|e| Error::DecodingMessage(e.to_string())),
+                _ => Err(Error::DecodingMessage(e.to_string())),
+            },
+        }
+    }
+}
+
+/// Implement the conversions between an [`IbcMessage`] variant and its data
+macro_rules! impl_ibc_message_data {
+    ($($variant:ident($data:ty)),* $(,)?) => {
+        $(
+            impl From<$data> for IbcMessage {
+                fn from(data: $data) -> Self {
+                    Self::$variant(data)
+                }
+            }
+
+            impl TryFrom<IbcMessage> for $data {
+                type Error = Error;
+
+                fn try_from(msg: IbcMessage) -> Result<Self> {
+                    match msg {
+                        IbcMessage::$variant(data) => Ok(data),
+                        _ => Err(Error::UnexpectedMessage(format!(
+                            "The message isn't {}",
+                            stringify!($variant)
+                        ))),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_ibc_message_data! {
+    ClientCreation(ClientCreationData),
+    ClientUpdate(ClientUpdateData),
+    ClientUpgrade(ClientUpgradeData),
+    ClientMisbehaviour(ClientMisbehaviourData),
+    ConnectionOpenInit(ConnectionOpenInitData),
+    ConnectionOpenTry(ConnectionOpenTryData),
+    ConnectionOpenAck(ConnectionOpenAckData),
+    ConnectionOpenConfirm(ConnectionOpenConfirmData),
+    ChannelOpenInit(ChannelOpenInitData),
+    ChannelOpenTry(ChannelOpenTryData),
+    ChannelOpenAck(ChannelOpenAckData),
+    ChannelOpenConfirm(ChannelOpenConfirmData),
+    ChannelCloseInit(ChannelCloseInitData),
+    ChannelCloseConfirm(ChannelCloseConfirmData),
+    PacketSend(PacketSendData),
+    PacketReceipt(PacketReceiptData),
+    PacketAck(PacketAckData),
+    Timeout(TimeoutData),
+}
+
+/// The port ID of the ICS-20 fungible token transfer application
+pub const TRANSFER_PORT_ID: &str = "transfer";
+
+/// The data of an ICS-20 fungible token transfer packet. It's encoded in JSON
+/// to be compatible with the other chains. The amount is in micro units.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FungibleTokenPacketData {
+    /// The denomination with the trace of the ports and the channels
+    pub denom: String,
+    /// The amount of the token
+    pub amount: String,
+    /// The sender on the source chain
+    pub sender: String,
+    /// The receiver on the destination chain
+    pub receiver: String,
+}
+
+impl FungibleTokenPacketData {
+    /// Create data for a fungible token transfer packet
+    pub fn new(
+        denom: impl Into<String>,
+        amount: token::Amount,
+        sender: impl Into<String>,
+        receiver: impl Into<String>,
+    ) -> Self {
+        Self {
+            denom: denom.into(),
+            amount: u64::from(amount).to_string(),
+            sender: sender.into(),
+            receiver: receiver.into(),
+        }
+    }
+
+    /// Decode the packet data
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| Error::InvalidPacketData(e.to_string()))
+    }
+
+    /// Encode the packet data
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self)
+            .expect("Encoding the packet data shouldn't fail")
+    }
+
+    /// Returns the amount to be transferred
+    pub fn amount(&self) -> Result<token::Amount> {
+        self.amount
+            .parse::<u64>()
+            .map(token::Amount::from)
+            .map_err(|e| Error::InvalidPacketData(e.to_string()))
+    }
+
+    /// Returns the amount to be transferred, which has to be non-zero
+    pub fn nonzero_amount(&self) -> Result<token::Amount> {
+        let amount = self.amount()?;
+        if amount == token::Amount::default() {
+            return Err(Error::InvalidPacketData(
+                "The amount is zero".to_owned(),
+            ));
+        }
+        Ok(amount)
+    }
+
+    /// Returns the address of the sender
+    pub fn sender(&self) -> Result<Address> {
+        Address::decode(&self.sender)
+            .map_err(|e| Error::InvalidPacketData(e.to_string()))
+    }
+
+    /// Returns the address of the receiver
+    pub fn receiver(&self) -> Result<Address> {
+        Address::decode(&self.receiver)
+            .map_err(|e| Error::InvalidPacketData(e.to_string()))
+    }
+}
+
+/// The acknowledgement of an ICS-20 fungible token transfer packet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FungibleTokenAck {
+    /// The transfer succeeded with the result `[0x01]` encoded in base64
+    Result(String),
+    /// The transfer failed
+    Error(String),
+}
+
+impl FungibleTokenAck {
+    /// Returns a successful acknowledgement
+    pub fn success() -> Self {
+        Self::Result("AQ==".to_owned())
+    }
+
+    /// Returns a failed acknowledgement with the reason
+    pub fn error(reason: impl ToString) -> Self {
+        Self::Error(reason.to_string())
+    }
+
+    /// Returns true if the transfer succeeded
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Result(_))
+    }
+
+    /// Decode the acknowledgement
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| Error::InvalidPacketData(e.to_string()))
+    }
+
+    /// Encode the acknowledgement
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self)
+            .expect("Encoding the acknowledgement shouldn't fail")
+    }
+}
+
+/// Returns the prefix of a denomination trace for the given port and channel
+pub fn denom_prefix(port_id: &PortId, channel_id: &ChannelId) -> String {
+    format!("{}/{}/", port_id, channel_id)
+}
+
+/// Returns the token address of the denomination. A denomination without a
+/// trace is a native token address, otherwise it's an IBC token.
+pub fn token_address(denom: &str) -> Result<Address> {
+    if denom.contains('/') {
+        Ok(Address::Internal(InternalAddress::ibc_token(denom)))
+    } else {
+        Address::decode(denom).map_err(|e| Error::InvalidDenom(e.to_string()))
+    }
+}
+
+/// A movement of tokens on this chain by an ICS-20 fungible token transfer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferAction {
+    /// Escrow the native or the IBC token of the owner
+    Escrow {
+        /// The token address
+        token: Address,
+        /// The owner sending the token
+        owner: Address,
+        /// The amount of the token
+        amount: token::Amount,
+    },
+    /// Release the escrowed token to the owner
+    Unescrow {
+        /// The token address
+        token: Address,
+        /// The owner receiving the token
+        owner: Address,
+        /// The amount of the token
+        amount: token::Amount,
+    },
+    /// Mint the IBC token with the denomination trace to the owner
+    Mint {
+        /// The denomination trace of the IBC token
+        denom: String,
+        /// The owner receiving the token
+        owner: Address,
+        /// The amount of the token
+        amount: token::Amount,
+    },
+    /// Burn the IBC token of the owner
+    Burn {
+        /// The IBC token address
+        token: Address,
+        /// The owner sending the token
+        owner: Address,
+        /// The amount of the token
+        amount: token::Amount,
+    },
+}
+
+impl TransferAction {
+    /// Returns the action to send a packet from the given source port and
+    /// channel. The token is burned if it has come from the destination
+    /// chain via the channel, otherwise it's escrowed.
+    pub fn on_send(
+        data: &FungibleTokenPacketData,
+        source_port: &PortId,
+        source_channel: &ChannelId,
+    ) -> Result<Self> {
+        let amount = data.nonzero_amount()?;
+        let owner = data.sender()?;
+        if data
+            .denom
+            .starts_with(&denom_prefix(source_port, source_channel))
+        {
+            let token =
+                Address::Internal(InternalAddress::ibc_token(&data.denom));
+            Ok(Self::Burn {
+                token,
+                owner,
+                amount,
+            })
+        } else {
+            let token = token_address(&data.denom)?;
+            Ok(Self::Escrow {
+                token,
+                owner,
+                amount,
+            })
+        }
+    }
+
+    /// Returns the action to receive the packet. The escrowed token is
+    /// released if it has come from this chain, otherwise the IBC token with
+    /// the new denomination trace is minted.
+    pub fn on_receive(packet: &Packet) -> Result<Self> {
+        let data = FungibleTokenPacketData::decode(&packet.data)?;
+        let amount = data.nonzero_amount()?;
+        let owner = data.receiver()?;
+        let source_prefix =
+            denom_prefix(&packet.source_port, &packet.source_channel);
+        match data.denom.strip_prefix(&source_prefix) {
+            Some(base_denom) => {
+                let token = token_address(base_denom)?;
+                Ok(Self::Unescrow {
+                    token,
+                    owner,
+                    amount,
+                })
+            }
+            None => {
+                let denom = format!(
+                    "{}{}",
+                    denom_prefix(
+                        &packet.destination_port,
+                        &packet.destination_channel
+                    ),
+                    data.denom
+                );
+                Ok(Self::Mint {
+                    denom,
+                    owner,
+                    amount,
+                })
+            }
+        }
+    }
+
+    /// Returns the action to refund the sent packet when it has been failed
+    /// on the destination chain or timed out
+    pub fn on_refund(packet: &Packet) -> Result<Self> {
+        let data = FungibleTokenPacketData::decode(&packet.data)?;
+        match Self::on_send(&data, &packet.source_port, &packet.source_channel)?
+        {
+            Self::Burn { owner, amount, .. } => Ok(Self::Mint {
+                denom: data.denom,
+                owner,
+                amount,
+            }),
+            Self::Escrow {
+                token,
+                owner,
+                amount,
+            } => Ok(Self::Unescrow {
+                token,
+                owner,
+                amount,
+            }),
+            _ => unreachable!("Sending a packet only escrows or burns"),
+        }
+    }
+
+    /// Returns the changes of the token balances by this action
+    pub fn balance_changes(&self) -> Vec<(storage::Key, token::Change)> {
+        let escrow = Address::Internal(InternalAddress::IbcEscrow);
+        match self {
+            Self::Escrow {
+                token,
+                owner,
+                amount,
+            } => vec![
+                (token::balance_key(token, owner), -amount.change()),
+                (token::balance_key(token, &escrow), amount.change()),
+            ],
+            Self::Unescrow {
+                token,
+                owner,
+                amount,
+            } => vec![
+                (token::balance_key(token, &escrow), -amount.change()),
+                (token::balance_key(token, owner), amount.change()),
+            ],
+            Self::Mint {
+                denom,
+                owner,
+                amount,
+            } => {
+                let token =
+                    Address::Internal(InternalAddress::ibc_token(denom));
+                vec![(token::balance_key(&token, owner), amount.change())]
+            }
+            Self::Burn {
+                token,
+                owner,
+                amount,
+            } => vec![(token::balance_key(token, owner), -amount.change())],
+        }
+    }
+}
+
 /// Update a client with the given state and headers
 pub fn update_client(
     client_state: AnyClientState,
@@ -920,6 +1418,18 @@ pub fn update_client(
     }
 }
 
+/// Returns the consensus state derived from the header
+pub fn header_consensus_state(header: AnyHeader) -> AnyConsensusState {
+    match header {
+        AnyHeader::Tendermint(header) => {
+            AnyConsensusState::Tendermint(TmConsensusState::from(header))
+        }
+        AnyHeader::Mock(header) => {
+            AnyConsensusState::Mock(MockConsensusState::new(header))
+        }
+    }
+}
+
 /// Returns a new connection ID
 pub fn connection_id(counter: u64) -> ConnectionId {
     ConnectionId::new(counter)
@@ -992,7 +1502,8 @@ pub fn channel_counterparty(
     ChanCounterparty::new(port_id, Some(channel_id))
 }
 
-fn commitment_prefix() -> CommitmentPrefix {
+/// Returns the commitment prefix of the IBC storage
+pub fn commitment_prefix() -> CommitmentPrefix {
     let addr = Address::Internal(InternalAddress::Ibc);
     let bytes = addr
         .raw()
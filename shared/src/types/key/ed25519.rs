@@ -1,5 +1,6 @@
 //! Ed25519 keys and related functionality
 
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::fmt::{Debug, Display};
 use std::hash::{Hash, Hasher};
@@ -125,22 +126,108 @@ pub fn verify_signature_raw(
 /// Because the signature is not checked by the ledger, we don't inline it into
 /// the `Tx` type directly. Instead, the signature is attached to the `tx.data`,
 /// which is can then be checked by a validity predicate wasm.
-#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+///
+/// A transaction may carry more than one signature, e.g. for accounts that
+/// require a threshold of signatures from a set of keys (see
+/// [`super::multisig`]). Each signature is indexed by the position of its
+/// signing key in the signer's key set. A single-key signer always uses the
+/// index `0`.
+///
+/// The Borsh encoding of a single signature with the index `0` is the same as
+/// the encoding of the data followed by the signature, which is the format
+/// used before multiple signatures were supported. Otherwise, the first
+/// signature is followed by its index and a map of the other signatures. The
+/// encoded signed tx data must not be followed by any other bytes, because
+/// the presence of the other signatures is detected from the remaining bytes.
+#[derive(Clone, Debug)]
 pub struct SignedTxData {
     /// The original tx data bytes, if any
     pub data: Option<Vec<u8>>,
-    /// The signature is produced on the tx data concatenated with the tx code
-    /// and the timestamp.
-    pub sig: Signature,
+    /// The signatures are produced on the tx data concatenated with the tx
+    /// code and the timestamp.
+    pub sigs: BTreeMap<u8, Signature>,
+}
+
+impl SignedTxData {
+    /// Get the signature made with the key at the index `0`. This is the
+    /// signature used by single-key signers.
+    pub fn sig(&self) -> Option<&Signature> {
+        self.sigs.get(&0)
+    }
+}
+
+impl BorshSerialize for SignedTxData {
+    fn serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let mut sigs = self.sigs.iter();
+        let (first_index, first_sig) = sigs.next().ok_or_else(|| {
+            std::io::Error::new(
+                ErrorKind::InvalidInput,
+                "Signed tx data must have at least one signature",
+            )
+        })?;
+        BorshSerialize::serialize(&self.data, writer)?;
+        BorshSerialize::serialize(first_sig, writer)?;
+        // A single signature with the index `0` is encoded without the
+        // trailing signatures
+        if *first_index != 0 || self.sigs.len() > 1 {
+            let other_sigs: BTreeMap<u8, Signature> =
+                sigs.map(|(index, sig)| (*index, sig.clone())).collect();
+            BorshSerialize::serialize(first_index, writer)?;
+            BorshSerialize::serialize(&other_sigs, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for SignedTxData {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let data: Option<Vec<u8>> = BorshDeserialize::deserialize(buf)?;
+        let first_sig: Signature = BorshDeserialize::deserialize(buf)?;
+        if buf.is_empty() {
+            let sigs = std::iter::once((0, first_sig)).collect();
+            return Ok(Self { data, sigs });
+        }
+        let first_index: u8 = BorshDeserialize::deserialize(buf)?;
+        let mut sigs: BTreeMap<u8, Signature> =
+            BorshDeserialize::deserialize(buf)?;
+        // Only accept the canonical encoding, in which the first signature
+        // has the lowest index and a single signature with the index `0` has
+        // no trailing signatures
+        let is_canonical = sigs.keys().all(|index| *index > first_index)
+            && (first_index != 0 || !sigs.is_empty());
+        if !is_canonical {
+            return Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                "Non-canonical encoding of signed tx data signatures",
+            ));
+        }
+        sigs.insert(first_index, first_sig);
+        Ok(Self { data, sigs })
+    }
 }
 
 /// Sign a transaction using [`SignedTxData`].
 pub fn sign_tx(keypair: &Keypair, tx: Tx) -> Tx {
-    let to_sign = tx.to_bytes();
-    let sig = sign(keypair, &to_sign);
-    let signed = SignedTxData { data: tx.data, sig }
-        .try_to_vec()
-        .expect("Encoding transaction data shouldn't fail");
+    let sig = sign_tx_partial(keypair, &tx);
+    attach_tx_sigs(tx, std::iter::once((0, sig)).collect())
+}
+
+/// Produce a signature of an unsigned transaction that can be combined with
+/// signatures from other keys using [`attach_tx_sigs`]. This allows to
+/// collect signatures for multi-signature accounts offline.
+pub fn sign_tx_partial(keypair: &Keypair, tx: &Tx) -> Signature {
+    sign(keypair, &tx.to_bytes())
+}
+
+/// Attach the given signatures to an unsigned transaction using
+/// [`SignedTxData`]. Panics if there are no signatures.
+pub fn attach_tx_sigs(tx: Tx, sigs: BTreeMap<u8, Signature>) -> Tx {
+    let signed = SignedTxData {
+        data: tx.data,
+        sigs,
+    }
+    .try_to_vec()
+    .expect("Encoding transaction data shouldn't fail");
     Tx {
         code: tx.code,
         data: Some(signed),
@@ -217,6 +304,15 @@ impl PublicKey {
         let pk = ed25519_dalek::PublicKey::from_bytes(bytes)?;
         Ok(pk.into())
     }
+
+    /// The raw hash of the key used by Tendermint as the address of a
+    /// validator with this consensus key. It's the hex of the first 20 bytes
+    /// of the SHA-256 hash of the key's bytes.
+    pub fn tm_raw_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.0.as_bytes());
+        format!("{:.width$X}", hasher.finalize(), width = PKH_HASH_LEN)
+    }
 }
 
 impl<T> PartialEq for Signed<T>
@@ -747,4 +843,60 @@ pub mod tests {
         println!("Public key: {}", public_key);
         println!("Secret key: {}", secret_key);
     }
+    /// The encoding of signed tx data with a single signature must stay
+    /// compatible with the encoding used before multiple signatures were
+    /// supported, which is still used by the pre-built wasm.
+    #[test]
+    fn test_signed_tx_data_single_sig_encoding() {
+        #[derive(BorshSerialize, BorshDeserialize)]
+        struct SingleSignedTxData {
+            data: Option<Vec<u8>>,
+            sig: Signature,
+        }
+
+        let keypair = testing::keypair_1();
+        let tx = Tx::new(vec![], Some(b"data".to_vec()));
+        let sig = sign_tx_partial(&keypair, &tx);
+        let signed = sign_tx(&keypair, tx.clone());
+        let encoded = signed.data.expect("signed tx must have data");
+        let expected = SingleSignedTxData {
+            data: tx.data,
+            sig: sig.clone(),
+        }
+        .try_to_vec()
+        .unwrap();
+        assert_eq!(encoded, expected);
+
+        let decoded = SignedTxData::try_from_slice(&expected[..]).unwrap();
+        assert_eq!(decoded.sigs.len(), 1);
+        assert_eq!(decoded.sig(), Some(&sig));
+    }
+
+    /// Multiple signatures must round-trip through the encoding, even when
+    /// the index `0` is missing.
+    #[test]
+    fn test_signed_tx_data_multi_sig_encoding() {
+        let tx = Tx::new(vec![], Some(b"data".to_vec()));
+        let sig_1 = sign_tx_partial(&testing::keypair_1(), &tx);
+        let sig_2 = sign_tx_partial(&testing::keypair_2(), &tx);
+        for sigs in [
+            vec![(1, sig_1.clone())],
+            vec![(0, sig_1.clone()), (2, sig_2.clone())],
+            vec![(1, sig_1), (3, sig_2)],
+        ] {
+            let sigs: BTreeMap<u8, Signature> = sigs.into_iter().collect();
+            let signed = attach_tx_sigs(tx.clone(), sigs.clone());
+            let decoded =
+                SignedTxData::try_from_slice(&signed.data.unwrap()[..])
+                    .unwrap();
+            assert_eq!(decoded.data, tx.data);
+            assert_eq!(decoded.sigs, sigs);
+        }
+        // Signed tx data without any signatures cannot be encoded
+        let unsigned = SignedTxData {
+            data: tx.data,
+            sigs: BTreeMap::new(),
+        };
+        assert!(unsigned.try_to_vec().is_err());
+    }
 }
@@ -1,3 +1,4 @@
 //! Cryptographic keys
 
 pub mod ed25519;
+pub mod multisig;
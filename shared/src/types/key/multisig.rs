@@ -0,0 +1,277 @@
+//! Multi-signature accounts, controlled by a threshold of signatures from a set
+//! of ed25519 keys ("m-of-n").
+
+use std::collections::{BTreeMap, HashSet};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::ed25519::{PublicKey, Signature};
+use crate::types::address::Address;
+use crate::types::storage::{DbKeySeg, Key, KeySeg};
+
+const KEYS_STORAGE_KEY: &str = "multisig_keys";
+
+/// The maximum number of keys in a key set. The signatures in
+/// [`super::ed25519::SignedTxData`] are indexed by `u8`.
+pub const MAX_KEYS: usize = u8::MAX as usize + 1;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum Error {
+    #[error("The threshold must be at least 1")]
+    ZeroThreshold,
+    #[error(
+        "The threshold {threshold} is greater than the number of keys {keys}"
+    )]
+    ThresholdTooHigh { threshold: u8, keys: usize },
+    #[error("The key set has {0} keys, but the maximum is {}", MAX_KEYS)]
+    TooManyKeys(usize),
+    #[error("The public key {0} is duplicated in the key set")]
+    DuplicateKey(PublicKey),
+}
+
+/// A set of public keys of which at least `threshold` must sign a transaction
+/// to authorize it.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ThresholdKeys {
+    /// The minimum number of valid signatures
+    pub threshold: u8,
+    /// The set of public keys. A signature is associated with a key by its
+    /// index in this set.
+    pub public_keys: Vec<PublicKey>,
+}
+
+impl ThresholdKeys {
+    /// Create a new validated key set.
+    pub fn new(
+        threshold: u8,
+        public_keys: Vec<PublicKey>,
+    ) -> Result<Self, Error> {
+        let keys = Self {
+            threshold,
+            public_keys,
+        };
+        keys.validate()?;
+        Ok(keys)
+    }
+
+    /// Check that the threshold can be satisfied and that the keys are
+    /// unique.
+    pub fn validate(&self) -> Result<(), Error> {
+        let keys = self.public_keys.len();
+        if keys > MAX_KEYS {
+            return Err(Error::TooManyKeys(keys));
+        }
+        if self.threshold == 0 {
+            return Err(Error::ZeroThreshold);
+        }
+        if self.threshold as usize > keys {
+            return Err(Error::ThresholdTooHigh {
+                threshold: self.threshold,
+                keys,
+            });
+        }
+        let mut seen = HashSet::with_capacity(keys);
+        for pk in &self.public_keys {
+            if !seen.insert(pk) {
+                return Err(Error::DuplicateKey(pk.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the public key at the given index, if any.
+    pub fn get(&self, index: u8) -> Option<&PublicKey> {
+        self.public_keys.get(index as usize)
+    }
+
+    /// Find the index of the given public key in the key set, if any.
+    pub fn index_of(&self, pk: &PublicKey) -> Option<u8> {
+        self.public_keys
+            .iter()
+            .position(|key| key == pk)
+            .map(|index| index as u8)
+    }
+
+    /// Count the signatures that are valid for the keys at their indices.
+    /// The `verify` function checks a single signature against a public key
+    /// (e.g. the host function in a VP or [`super::ed25519::verify_tx_sig`]
+    /// natively). Signatures with an index outside of the key set are ignored.
+    pub fn count_valid_sigs(
+        &self,
+        sigs: &BTreeMap<u8, Signature>,
+        mut verify: impl FnMut(&PublicKey, &Signature) -> bool,
+    ) -> usize {
+        sigs.iter()
+            .filter(|(index, sig)| match self.get(**index) {
+                Some(pk) => verify(pk, sig),
+                None => false,
+            })
+            .count()
+    }
+
+    /// Check that there are at least `threshold` valid signatures. Stops
+    /// verifying signatures once the threshold is reached.
+    pub fn is_satisfied_by(
+        &self,
+        sigs: &BTreeMap<u8, Signature>,
+        mut verify: impl FnMut(&PublicKey, &Signature) -> bool,
+    ) -> bool {
+        let mut valid: usize = 0;
+        for (index, sig) in sigs {
+            if let Some(pk) = self.get(*index) {
+                if verify(pk, sig) {
+                    valid += 1;
+                    if valid >= self.threshold as usize {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Obtain a storage key for user's multi-signature key set.
+pub fn keys_key(owner: &Address) -> Key {
+    Key::from(owner.to_db_key())
+        .push(&KEYS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a multi-signature key set. If it is,
+/// returns the owner.
+pub fn is_keys_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key)]
+            if key == KEYS_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::Tx;
+    use crate::types::address;
+    use crate::types::key::ed25519::{
+        attach_tx_sigs, sign_tx_partial, testing, verify_tx_sig, Keypair,
+    };
+
+    fn keypairs(n: usize) -> Vec<Keypair> {
+        (0..n).map(|_| testing::gen_keypair()).collect()
+    }
+
+    fn key_set(threshold: u8, keypairs: &[Keypair]) -> ThresholdKeys {
+        ThresholdKeys::new(
+            threshold,
+            keypairs.iter().map(|kp| kp.public.clone()).collect(),
+        )
+        .expect("valid key set")
+    }
+
+    #[test]
+    fn test_validate_key_set() {
+        let keypairs = keypairs(3);
+        let pks: Vec<PublicKey> =
+            keypairs.iter().map(|kp| kp.public.clone()).collect();
+
+        assert!(ThresholdKeys::new(1, pks.clone()).is_ok());
+        assert!(ThresholdKeys::new(3, pks.clone()).is_ok());
+        assert_eq!(
+            ThresholdKeys::new(0, pks.clone()),
+            Err(Error::ZeroThreshold)
+        );
+        assert_eq!(
+            ThresholdKeys::new(4, pks.clone()),
+            Err(Error::ThresholdTooHigh {
+                threshold: 4,
+                keys: 3
+            })
+        );
+        assert_eq!(
+            ThresholdKeys::new(1, vec![]),
+            Err(Error::ThresholdTooHigh {
+                threshold: 1,
+                keys: 0
+            })
+        );
+
+        let mut duplicated = pks.clone();
+        duplicated.push(pks[1].clone());
+        assert_eq!(
+            ThresholdKeys::new(2, duplicated),
+            Err(Error::DuplicateKey(pks[1].clone()))
+        );
+    }
+
+    #[test]
+    fn test_threshold_sigs() {
+        let keypairs = keypairs(3);
+        let keys = key_set(2, &keypairs);
+        let tx = Tx::new(b"wasm code".to_vec(), Some(b"data".to_vec()));
+        let verify = |pk: &PublicKey, sig: &Signature, tx: &Tx| {
+            verify_tx_sig(pk, tx, sig).is_ok()
+        };
+
+        // A single signature is not enough
+        let mut sigs = BTreeMap::new();
+        sigs.insert(0, sign_tx_partial(&keypairs[0], &tx));
+        let signed = attach_tx_sigs(tx.clone(), sigs.clone());
+        assert_eq!(
+            keys.count_valid_sigs(&sigs, |pk, sig| verify(pk, sig, &signed)),
+            1
+        );
+        assert!(
+            !keys.is_satisfied_by(&sigs, |pk, sig| verify(pk, sig, &signed))
+        );
+
+        // A signature at the wrong index is not valid
+        sigs.insert(1, sign_tx_partial(&keypairs[2], &tx));
+        let signed = attach_tx_sigs(tx.clone(), sigs.clone());
+        assert!(
+            !keys.is_satisfied_by(&sigs, |pk, sig| verify(pk, sig, &signed))
+        );
+
+        // A signature at an index outside of the key set is ignored
+        let mut out_of_range = sigs.clone();
+        out_of_range.insert(3, sign_tx_partial(&keypairs[1], &tx));
+        let signed = attach_tx_sigs(tx.clone(), out_of_range.clone());
+        assert!(!keys.is_satisfied_by(&out_of_range, |pk, sig| verify(
+            pk, sig, &signed
+        )));
+
+        // Two valid signatures satisfy the threshold
+        sigs.insert(2, sign_tx_partial(&keypairs[2], &tx));
+        sigs.remove(&1);
+        let signed = attach_tx_sigs(tx, sigs.clone());
+        assert_eq!(
+            keys.count_valid_sigs(&sigs, |pk, sig| verify(pk, sig, &signed)),
+            2
+        );
+        assert!(keys.is_satisfied_by(&sigs, |pk, sig| verify(pk, sig, &signed)));
+    }
+
+    #[test]
+    fn test_keys_key() {
+        let owner = address::testing::established_address_1();
+        let key = keys_key(&owner);
+        assert_eq!(is_keys_key(&key), Some(&owner));
+        let pk_key = crate::types::key::ed25519::pk_key(&owner);
+        assert_eq!(is_keys_key(&pk_key), None);
+    }
+}
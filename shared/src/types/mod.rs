@@ -7,6 +7,7 @@ pub mod ibc;
 pub mod intent;
 pub mod internal;
 pub mod key;
+pub mod spending_policy;
 pub mod storage;
 pub mod time;
 pub mod token;
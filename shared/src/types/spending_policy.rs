@@ -0,0 +1,209 @@
+//! An optional spending policy of an account. The policy can limit the outflow
+//! of tokens per epoch, restrict the recipients of tokens and designate a
+//! guardian key whose co-signature bypasses the limits.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::types::address::Address;
+use crate::types::key::ed25519::PublicKey;
+use crate::types::storage::{DbKeySeg, Epoch, Key, KeySeg};
+use crate::types::token;
+
+const POLICY_STORAGE_KEY: &str = "spending_policy";
+const SPENT_STORAGE_KEY: &str = "spending_policy_spent";
+
+/// The index of the guardian's co-signature in
+/// [`crate::types::key::ed25519::SignedTxData`]. The account's own signature is
+/// at index `0`.
+pub const GUARDIAN_SIG_INDEX: u8 = 1;
+
+/// A spending policy of an account.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct SpendingPolicy {
+    /// The maximum outflow of a token per epoch. Tokens without a limit are
+    /// not limited.
+    pub limits: BTreeMap<Address, token::Amount>,
+    /// When not empty, tokens can only be sent to these addresses.
+    pub allowed_targets: BTreeSet<Address>,
+    /// A key whose co-signature bypasses the limits and the allowed targets.
+    /// When set, its co-signature is also required to change the policy, the
+    /// account's public key or its validity predicate.
+    pub guardian: Option<PublicKey>,
+}
+
+impl SpendingPolicy {
+    /// Get the limit of outflow per epoch of the given token, if any.
+    pub fn limit(&self, token: &Address) -> Option<token::Amount> {
+        self.limits.get(token).copied()
+    }
+
+    /// Check if tokens may be sent to the given address.
+    pub fn is_allowed_target(&self, target: &Address) -> bool {
+        self.allowed_targets.is_empty() || self.allowed_targets.contains(target)
+    }
+
+    /// Check if the policy doesn't restrict anything.
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+            && self.allowed_targets.is_empty()
+            && self.guardian.is_none()
+    }
+}
+
+/// The outflow of a token in an epoch, tracked against the limit of a
+/// [`SpendingPolicy`].
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize,
+)]
+pub struct Spent {
+    /// The epoch in which the tokens were spent
+    pub epoch: Epoch,
+    /// The amount spent in the epoch
+    pub amount: token::Amount,
+}
+
+impl Spent {
+    /// Record an outflow of the given amount in the given epoch. An amount
+    /// spent in an earlier epoch is not carried over. Fails when the amount
+    /// spent in the epoch overflows.
+    pub fn record(
+        prev: Option<Spent>,
+        epoch: Epoch,
+        amount: token::Amount,
+    ) -> Result<Self, token::AmountError> {
+        let mut spent = match prev {
+            Some(prev) if prev.epoch == epoch => prev.amount,
+            _ => token::Amount::default(),
+        };
+        spent.receive(&amount)?;
+        Ok(Self {
+            epoch,
+            amount: spent,
+        })
+    }
+
+    /// Get the amount spent in the given epoch.
+    pub fn in_epoch(&self, epoch: Epoch) -> token::Amount {
+        if self.epoch == epoch {
+            self.amount
+        } else {
+            token::Amount::default()
+        }
+    }
+}
+
+/// Obtain a storage key for user's spending policy.
+pub fn policy_key(owner: &Address) -> Key {
+    Key::from(owner.to_db_key())
+        .push(&POLICY_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a spending policy key. If it is, returns
+/// the owner.
+pub fn is_policy_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key)]
+            if key == POLICY_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+/// Obtain a storage key for user's outflow of the given token.
+pub fn spent_key(owner: &Address, token: &Address) -> Key {
+    Key::from(owner.to_db_key())
+        .push(&SPENT_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&token.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a key of user's outflow of a token. If it
+/// is, returns the owner and the token.
+pub fn is_spent_key(key: &Key) -> Option<(&Address, &Address)> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key), DbKeySeg::AddressSeg(token)]
+            if key == SPENT_STORAGE_KEY =>
+        {
+            Some((owner, token))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address;
+
+    #[test]
+    fn test_record_spent() {
+        let amount = token::Amount::from(100);
+        let spent = Spent::record(None, Epoch(1), amount).unwrap();
+        assert_eq!(spent.amount, amount);
+        assert_eq!(spent.in_epoch(Epoch(1)), amount);
+        assert_eq!(spent.in_epoch(Epoch(2)), token::Amount::default());
+
+        // The outflow accumulates within an epoch
+        let spent = Spent::record(Some(spent), Epoch(1), amount).unwrap();
+        assert_eq!(spent.amount, token::Amount::from(200));
+
+        // And it's reset in a new epoch
+        let spent = Spent::record(Some(spent), Epoch(2), amount).unwrap();
+        assert_eq!(spent.epoch, Epoch(2));
+        assert_eq!(spent.amount, amount);
+
+        // An overflowing outflow is rejected
+        let max = token::Amount::from(u64::MAX);
+        assert_eq!(
+            Spent::record(Some(spent), Epoch(2), max),
+            Err(token::AmountError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_allowed_targets() {
+        let target = address::testing::established_address_1();
+        let other = address::testing::established_address_2();
+
+        let mut policy = SpendingPolicy::default();
+        assert!(policy.is_empty());
+        assert!(policy.is_allowed_target(&target));
+        assert!(policy.is_allowed_target(&other));
+
+        policy.allowed_targets.insert(target.clone());
+        assert!(!policy.is_empty());
+        assert!(policy.is_allowed_target(&target));
+        assert!(!policy.is_allowed_target(&other));
+    }
+
+    #[test]
+    fn test_policy_keys() {
+        let owner = address::testing::established_address_1();
+        let token = address::xan();
+
+        let key = policy_key(&owner);
+        assert_eq!(is_policy_key(&key), Some(&owner));
+        assert_eq!(is_spent_key(&key), None);
+
+        let key = spent_key(&owner, &token);
+        assert_eq!(is_spent_key(&key), Some((&owner, &token)));
+        assert_eq!(is_policy_key(&key), None);
+    }
+}
@@ -46,15 +46,38 @@ impl Amount {
     }
 
     /// Spend a given amount.
-    /// Panics when given `amount` > `self.micro` amount.
-    pub fn spend(&mut self, amount: &Amount) {
-        self.micro = self.micro.checked_sub(amount.micro).unwrap();
+    /// Fails when given `amount` > `self.micro` amount, in which case `self` is
+    /// left unchanged.
+    pub fn spend(&mut self, amount: &Amount) -> Result<(), AmountError> {
+        self.micro = self
+            .micro
+            .checked_sub(amount.micro)
+            .ok_or(AmountError::Insufficient)?;
+        Ok(())
     }
 
     /// Receive a given amount.
-    /// Panics on overflow.
-    pub fn receive(&mut self, amount: &Amount) {
-        self.micro = self.micro.checked_add(amount.micro).unwrap();
+    /// Fails on overflow, in which case `self` is left unchanged.
+    pub fn receive(&mut self, amount: &Amount) -> Result<(), AmountError> {
+        self.micro = self
+            .micro
+            .checked_add(amount.micro)
+            .ok_or(AmountError::Overflow)?;
+        Ok(())
+    }
+
+    /// Checked addition. Returns `None` on overflow.
+    pub fn checked_add(&self, amount: &Amount) -> Option<Self> {
+        self.micro
+            .checked_add(amount.micro)
+            .map(|micro| Self { micro })
+    }
+
+    /// Checked subtraction. Returns `None` on underflow.
+    pub fn checked_sub(&self, amount: &Amount) -> Option<Self> {
+        self.micro
+            .checked_sub(amount.micro)
+            .map(|micro| Self { micro })
     }
 
     /// Create a new amount from whole number of tokens
@@ -150,6 +173,15 @@ impl SubAssign for Amount {
     }
 }
 
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountError {
+    #[error("Insufficient token amount")]
+    Insufficient,
+    #[error("Token amount overflow")]
+    Overflow,
+}
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum AmountParseError {
@@ -248,6 +280,19 @@ pub fn is_any_token_balance_key(key: &Key) -> Option<&Address> {
     }
 }
 
+/// Check if the given storage key is balance key for unspecified token. If it
+/// is, returns the token and the owner.
+pub fn is_any_balance_key(key: &Key) -> Option<(&Address, &Address)> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(token), DbKeySeg::StringSeg(key), DbKeySeg::AddressSeg(owner)]
+            if key == BALANCE_STORAGE_KEY =>
+        {
+            Some((token, owner))
+        }
+        _ => None,
+    }
+}
+
 /// A simple bilateral token transfer
 #[derive(
     Debug,
@@ -290,4 +335,16 @@ mod tests {
                 assert_eq!(amount, identity);
         }
     }
+
+    #[test]
+    fn test_token_amount_checked_arithmetic() {
+        let max = Amount::from(u64::MAX);
+        let one = Amount::from(1);
+        let zero = Amount::default();
+
+        assert_eq!(max.checked_add(&zero), Some(max));
+        assert_eq!(max.checked_add(&one), None);
+        assert_eq!(one.checked_sub(&one), Some(zero));
+        assert_eq!(zero.checked_sub(&one), None);
+    }
 }
@@ -1,5 +1,7 @@
 //! Types that are used in transactions.
 
+pub mod governance;
+pub mod parameters;
 pub mod pos;
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -7,6 +9,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::types::address::Address;
 use crate::types::key::ed25519::PublicKey;
+use crate::types::key::multisig::ThresholdKeys;
+use crate::types::spending_policy::SpendingPolicy;
 
 /// A tx data type to update an account's validity predicate
 #[derive(
@@ -25,6 +29,40 @@ pub struct UpdateVp {
     pub vp_code: Vec<u8>,
 }
 
+/// A tx data type to update an account's public key
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpdateKey {
+    /// An address of the account
+    pub addr: Address,
+    /// The new public key
+    pub public_key: PublicKey,
+}
+
+/// A tx data type to set or remove an account's spending policy
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpdateSpendingPolicy {
+    /// An address of the account
+    pub addr: Address,
+    /// The new spending policy. If `None`, the policy is removed.
+    pub policy: Option<SpendingPolicy>,
+}
+
 /// A tx data type to initialize a new established account
 #[derive(
     Debug,
@@ -44,6 +82,26 @@ pub struct InitAccount {
     pub vp_code: Vec<u8>,
 }
 
+/// A tx data type to initialize a new established account controlled by a
+/// threshold of signatures from a set of keys
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct InitMultisigAccount {
+    /// The key set to be written into the account's storage. This can be used
+    /// for signature verification of transactions for the newly created
+    /// account.
+    pub keys: ThresholdKeys,
+    /// The VP code
+    pub vp_code: Vec<u8>,
+}
+
 /// A tx data type to initialize a new validator account and its staking reward
 /// account.
 #[derive(
@@ -70,6 +128,18 @@ pub struct InitValidator {
     pub validator_vp_code: Vec<u8>,
     /// The VP code for validator's staking reward account
     pub rewards_vp_code: Vec<u8>,
+    /// A portion of the block rewards that the validator keeps before the rest
+    /// is split between its bonds. Given in basis points (per ten thousand
+    /// tokens of rewards).
+    pub commission_rate: u64,
+    /// The validator's name
+    pub moniker: Option<String>,
+    /// The validator's description
+    pub description: Option<String>,
+    /// The validator's website
+    pub website: Option<String>,
+    /// The validator's contact, e.g. an e-mail address
+    pub contact: Option<String>,
 }
 
 /// Integration of Ferveo cryptographic primitives
@@ -477,7 +547,7 @@ pub mod wrapper_tx {
     pub fn process_tx(mut tx: Tx) -> Result<TxType, WrapperTxErr> {
         if let Some(Ok(SignedTxData {
             data: Some(data),
-            ref sig,
+            ref sigs,
         })) = tx
             .data
             .as_ref()
@@ -486,6 +556,7 @@ pub mod wrapper_tx {
             if let Ok(wrapper) =
                 <WrapperTx as BorshDeserialize>::deserialize(&mut data.as_ref())
             {
+                let sig = sigs.get(&0).ok_or(WrapperTxErr::Unsigned)?;
                 verify_tx_sig(&wrapper.pk, &tx, sig)
                     .map_err(|err| WrapperTxErr::SigError(err.to_string()))?;
                 tx.data = Some(
@@ -778,8 +849,12 @@ pub mod wrapper_tx {
             tx.data = Some(signed_tx_data.try_to_vec().expect("Test failed"));
 
             // check that the signature is not valid
-            verify_tx_sig(&keypair.public, &tx, &signed_tx_data.sig)
-                .expect_err("Test failed");
+            verify_tx_sig(
+                &keypair.public,
+                &tx,
+                signed_tx_data.sig().expect("Test failed"),
+            )
+            .expect_err("Test failed");
             // check that the try from method also fails
             let err = process_tx(tx).expect_err("Test failed");
             assert_eq!(
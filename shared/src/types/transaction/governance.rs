@@ -0,0 +1,58 @@
+//! Types used for governance transactions
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::ledger::parameters::ProposalVote;
+use crate::types::address::Address;
+use crate::types::storage::Epoch;
+
+/// A governance proposal submission.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct InitProposal {
+    /// The author of the proposal, who deposits the funds
+    pub author: Address,
+    /// The content of the proposal, e.g. its title and description
+    pub content: String,
+    /// The first epoch of the voting period
+    pub voting_start_epoch: Epoch,
+    /// The epoch at which the voting period ends, exclusive
+    pub voting_end_epoch: Epoch,
+    /// The epoch in which the proposal is tallied and, if it passed, its code
+    /// executed
+    pub grace_epoch: Epoch,
+    /// The WASM transaction code to be executed with governance authority if
+    /// the proposal passes, if any
+    pub proposal_code: Option<Vec<u8>>,
+}
+
+/// A vote on a governance proposal.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct VoteProposal {
+    /// The ID of the proposal
+    pub id: u64,
+    /// The voter's address
+    pub voter: Address,
+    /// The vote
+    pub vote: ProposalVote,
+}
@@ -0,0 +1,45 @@
+//! Types used for protocol parameters proposals transactions
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::ledger::parameters::ProposalVote;
+use crate::types::address::Address;
+
+/// A vote on a protocol parameters proposal.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct VoteProposal {
+    /// The ID of the proposal
+    pub id: u64,
+    /// The voter's address
+    pub voter: Address,
+    /// The vote
+    pub vote: ProposalVote,
+}
+
+/// A request to apply a passed protocol parameters proposal.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct ApplyProposal {
+    /// The ID of the proposal
+    pub id: u64,
+}
@@ -4,6 +4,7 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use crate::types::address::Address;
+use crate::types::key::ed25519::PublicKey;
 use crate::types::token;
 
 /// A bond is a validator's self-bond or a delegation from non-validator to a
@@ -32,6 +33,29 @@ pub struct Bond {
 /// An unbond of a bond.
 pub type Unbond = Bond;
 
+/// A redelegation of a delegation from one validator to another.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct Redelegation {
+    /// Source validator address
+    pub src_validator: Address,
+    /// Destination validator address
+    pub dest_validator: Address,
+    /// The amount of tokens
+    pub amount: token::Amount,
+    /// Source address of the delegation
+    pub source: Address,
+}
+
 /// A withdrawal of an unbond.
 #[derive(
     Debug,
@@ -51,3 +75,104 @@ pub struct Withdraw {
     /// from self-bonds, the validator is also the source
     pub source: Option<Address>,
 }
+
+/// A change of a validator's commission rate.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct CommissionChange {
+    /// Validator address
+    pub validator: Address,
+    /// The new commission rate. Given in basis points (per ten thousand tokens
+    /// of rewards).
+    pub rate: u64,
+}
+
+/// A change of a validator's consensus key.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct ConsensusKeyChange {
+    /// Validator address
+    pub validator: Address,
+    /// The new consensus key
+    pub consensus_key: PublicKey,
+}
+
+/// A claim of the rewards that have accrued to a delegation.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct ClaimRewards {
+    /// Validator address
+    pub validator: Address,
+    /// The delegator's address
+    pub source: Address,
+}
+
+/// A request to unjail a jailed validator.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct Unjail {
+    /// Validator address
+    pub validator: Address,
+}
+
+/// A change of a validator's metadata. The fields that are `None` are left
+/// unchanged and the fields set to an empty string are cleared.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Hash,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub struct MetadataChange {
+    /// Validator address
+    pub validator: Address,
+    /// The validator's name
+    pub moniker: Option<String>,
+    /// The validator's description
+    pub description: Option<String>,
+    /// The validator's website
+    pub website: Option<String>,
+    /// The validator's contact, e.g. an e-mail address
+    pub contact: Option<String>,
+}
@@ -21,7 +21,8 @@ use crate::vm::memory::VmMemory;
 use crate::vm::prefix_iter::{PrefixIteratorId, PrefixIterators};
 use crate::vm::types::KeyVal;
 use crate::vm::{
-    validate_untrusted_wasm, HostRef, MutHostRef, WasmValidationError,
+    validate_untrusted_code, HostRef, MutHostRef, WasmCodeKind,
+    WasmValidationError,
 };
 
 const VERIFY_TX_SIG_GAS_COST: u64 = 1000;
@@ -1173,7 +1174,8 @@ where
     tx_add_gas(env, gas)?;
 
     tx_add_gas(env, code.len() as u64 * WASM_VALIDATION_GAS_PER_BYTE)?;
-    validate_untrusted_wasm(&code).map_err(TxRuntimeError::UpdateVpInvalid)?;
+    validate_untrusted_code(&code, WasmCodeKind::Vp)
+        .map_err(TxRuntimeError::UpdateVpInvalid)?;
 
     let write_log = unsafe { env.ctx.write_log.get() };
     let (gas, _size_diff) = write_log
@@ -1202,7 +1204,7 @@ where
     tx_add_gas(env, gas)?;
 
     tx_add_gas(env, code.len() as u64 * WASM_VALIDATION_GAS_PER_BYTE)?;
-    validate_untrusted_wasm(&code)
+    validate_untrusted_code(&code, WasmCodeKind::Vp)
         .map_err(TxRuntimeError::InitAccountInvalidVpWasm)?;
 
     tracing::debug!("tx_init_account");
@@ -5,7 +5,9 @@ use std::ffi::c_void;
 use std::marker::PhantomData;
 use std::slice;
 
-use wasmparser::{Validator, WasmFeatures};
+use wasmparser::{
+    ImportSectionEntryType, Operator, Parser, Payload, Validator, WasmFeatures,
+};
 
 pub mod host_env;
 pub mod memory;
@@ -29,6 +31,56 @@ const UNTRUSTED_WASM_FEATURES: WasmFeatures = WasmFeatures {
     memory64: false,
 };
 
+/// The maximum number of elements in a table of an untrusted wasm code
+pub const UNTRUSTED_WASM_MAX_TABLE_SIZE: u32 = 10_000;
+
+/// The module from which the host environment is imported
+const HOST_MODULE: &str = "env";
+/// The name of the memory imported from the host environment
+const HOST_MEMORY: &str = "memory";
+
+/// The host functions that may be imported by a transaction code. These must
+/// match the imports in `crate::vm::wasm::host_env::tx_imports`, except for the
+/// gas meter, which is injected by the host.
+pub const TX_HOST_FUNCTIONS: &[&str] = &[
+    "anoma_tx_read",
+    "anoma_tx_result_buffer",
+    "anoma_tx_has_key",
+    "anoma_tx_write",
+    "anoma_tx_delete",
+    "anoma_tx_iter_prefix",
+    "anoma_tx_iter_next",
+    "anoma_tx_insert_verifier",
+    "anoma_tx_update_validity_predicate",
+    "anoma_tx_init_account",
+    "anoma_tx_get_chain_id",
+    "anoma_tx_get_block_height",
+    "anoma_tx_get_block_hash",
+    "anoma_tx_get_block_epoch",
+    "anoma_tx_log_string",
+];
+
+/// The host functions that may be imported by a validity predicate code. These
+/// must match the imports in `crate::vm::wasm::host_env::vp_imports`, except
+/// for the gas meter, which is injected by the host.
+pub const VP_HOST_FUNCTIONS: &[&str] = &[
+    "anoma_vp_read_pre",
+    "anoma_vp_read_post",
+    "anoma_vp_result_buffer",
+    "anoma_vp_has_key_pre",
+    "anoma_vp_has_key_post",
+    "anoma_vp_iter_prefix",
+    "anoma_vp_iter_pre_next",
+    "anoma_vp_iter_post_next",
+    "anoma_vp_get_chain_id",
+    "anoma_vp_get_block_height",
+    "anoma_vp_get_block_hash",
+    "anoma_vp_get_block_epoch",
+    "anoma_vp_verify_tx_signature",
+    "anoma_vp_eval",
+    "anoma_vp_log_string",
+];
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum WasmValidationError {
@@ -37,6 +89,62 @@ pub enum WasmValidationError {
          {UNTRUSTED_WASM_FEATURES:?}"
     )]
     ForbiddenWasmFeatures(wasmparser::BinaryReaderError),
+    #[error("Failed to parse WASM: {0}")]
+    ParsingError(wasmparser::BinaryReaderError),
+    #[error(
+        "Invalid WASM using a floating-point instruction {instruction} in \
+         function {func_index} at offset {offset}"
+    )]
+    FloatingPointInstruction {
+        instruction: String,
+        func_index: u32,
+        offset: usize,
+    },
+    #[error(
+        "Invalid WASM importing an unknown \"{module}.{field}\". A {kind} may \
+         only import its host functions and memory from the \"env\" module"
+    )]
+    UnknownImport {
+        module: String,
+        field: String,
+        kind: WasmCodeKind,
+    },
+    #[error("Invalid WASM with a start function {0}")]
+    StartFunction(u32),
+    #[error(
+        "Invalid WASM with a table of size {0}, the maximum size is \
+         {UNTRUSTED_WASM_MAX_TABLE_SIZE}"
+    )]
+    OversizedTable(u32),
+}
+
+/// The kind of an untrusted wasm code, which determines the host functions
+/// that it may import
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasmCodeKind {
+    /// A transaction code
+    Tx,
+    /// A validity predicate code
+    Vp,
+}
+
+impl WasmCodeKind {
+    /// The host functions that may be imported by this kind of code
+    pub fn host_functions(&self) -> &'static [&'static str] {
+        match self {
+            WasmCodeKind::Tx => TX_HOST_FUNCTIONS,
+            WasmCodeKind::Vp => VP_HOST_FUNCTIONS,
+        }
+    }
+}
+
+impl std::fmt::Display for WasmCodeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WasmCodeKind::Tx => write!(f, "transaction"),
+            WasmCodeKind::Vp => write!(f, "validity predicate"),
+        }
+    }
 }
 
 /// This is used to attach the Ledger's host structures to wasm environment,
@@ -208,3 +316,357 @@ pub fn validate_untrusted_wasm(
         .validate_all(wasm_code.as_ref())
         .map_err(WasmValidationError::ForbiddenWasmFeatures)
 }
+
+/// Validate an untrusted wasm code of the given kind on its submission. On top
+/// of the restrictions checked by [`validate_untrusted_wasm`], this rejects
+/// code that:
+/// - uses any floating-point instructions
+/// - imports anything other than the host functions available to its kind and
+///   the host memory
+/// - has a start function
+/// - has a table bigger than [`UNTRUSTED_WASM_MAX_TABLE_SIZE`]
+pub fn validate_untrusted_code(
+    wasm_code: impl AsRef<[u8]>,
+    kind: WasmCodeKind,
+) -> Result<(), WasmValidationError> {
+    let wasm_code = wasm_code.as_ref();
+    validate_untrusted_wasm(wasm_code)?;
+
+    let host_functions = kind.host_functions();
+    // The index of the next function, imported functions come first
+    let mut func_index: u32 = 0;
+    for payload in Parser::new(0).parse_all(wasm_code) {
+        match payload.map_err(WasmValidationError::ParsingError)? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import =
+                        import.map_err(WasmValidationError::ParsingError)?;
+                    let field = import.field.unwrap_or_default();
+                    let is_known = match import.ty {
+                        ImportSectionEntryType::Function(_) => {
+                            func_index += 1;
+                            host_functions.contains(&field)
+                        }
+                        ImportSectionEntryType::Memory(_) => {
+                            field == HOST_MEMORY
+                        }
+                        _ => false,
+                    };
+                    if !is_known || import.module != HOST_MODULE {
+                        return Err(WasmValidationError::UnknownImport {
+                            module: import.module.to_string(),
+                            field: field.to_string(),
+                            kind,
+                        });
+                    }
+                }
+            }
+            Payload::StartSection { func, .. } => {
+                return Err(WasmValidationError::StartFunction(func));
+            }
+            Payload::TableSection(reader) => {
+                for table in reader {
+                    let table =
+                        table.map_err(WasmValidationError::ParsingError)?;
+                    let size = table
+                        .limits
+                        .maximum
+                        .unwrap_or(table.limits.initial)
+                        .max(table.limits.initial);
+                    if size > UNTRUSTED_WASM_MAX_TABLE_SIZE {
+                        return Err(WasmValidationError::OversizedTable(size));
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let mut reader = body
+                    .get_operators_reader()
+                    .map_err(WasmValidationError::ParsingError)?;
+                while !reader.eof() {
+                    let (op, offset) = reader
+                        .read_with_offset()
+                        .map_err(WasmValidationError::ParsingError)?;
+                    if is_floating_point_instruction(&op) {
+                        return Err(
+                            WasmValidationError::FloatingPointInstruction {
+                                instruction: format!("{:?}", op),
+                                func_index,
+                                offset,
+                            },
+                        );
+                    }
+                }
+                func_index += 1;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Check if the given instruction operates on floating-point numbers. The SIMD
+/// instructions are not included, because the SIMD feature is disabled for
+/// untrusted code.
+fn is_floating_point_instruction(op: &Operator) -> bool {
+    use Operator::*;
+    matches!(
+        op,
+        F32Load { .. }
+            | F64Load { .. }
+            | F32Store { .. }
+            | F64Store { .. }
+            | F32Const { .. }
+            | F64Const { .. }
+            | F32Eq
+            | F32Ne
+            | F32Lt
+            | F32Gt
+            | F32Le
+            | F32Ge
+            | F64Eq
+            | F64Ne
+            | F64Lt
+            | F64Gt
+            | F64Le
+            | F64Ge
+            | F32Abs
+            | F32Neg
+            | F32Ceil
+            | F32Floor
+            | F32Trunc
+            | F32Nearest
+            | F32Sqrt
+            | F32Add
+            | F32Sub
+            | F32Mul
+            | F32Div
+            | F32Min
+            | F32Max
+            | F32Copysign
+            | F64Abs
+            | F64Neg
+            | F64Ceil
+            | F64Floor
+            | F64Trunc
+            | F64Nearest
+            | F64Sqrt
+            | F64Add
+            | F64Sub
+            | F64Mul
+            | F64Div
+            | F64Min
+            | F64Max
+            | F64Copysign
+            | I32TruncF32S
+            | I32TruncF32U
+            | I32TruncF64S
+            | I32TruncF64U
+            | I64TruncF32S
+            | I64TruncF32U
+            | I64TruncF64S
+            | I64TruncF64U
+            | F32ConvertI32S
+            | F32ConvertI32U
+            | F32ConvertI64S
+            | F32ConvertI64U
+            | F32DemoteF64
+            | F64ConvertI32S
+            | F64ConvertI32U
+            | F64ConvertI64S
+            | F64ConvertI64U
+            | F64PromoteF32
+            | I32ReinterpretF32
+            | I64ReinterpretF64
+            | F32ReinterpretI32
+            | F64ReinterpretI64
+            | I32TruncSatF32S
+            | I32TruncSatF32U
+            | I32TruncSatF64S
+            | I32TruncSatF64U
+            | I64TruncSatF32S
+            | I64TruncSatF32U
+            | I64TruncSatF64S
+            | I64TruncSatF64U
+    )
+}
+
+#[cfg(all(test, feature = "wasm-runtime"))]
+mod tests {
+    use wasmer::wat2wasm;
+
+    use super::*;
+
+    const TX_NO_OP_WASM: &str = "../wasm_for_tests/tx_no_op.wasm";
+    const VP_READ_STORAGE_KEY_WASM: &str =
+        "../wasm_for_tests/vp_read_storage_key.wasm";
+    const WASM_DIR: &str = "../wasm";
+
+    /// Test that transactions and validity predicates built with the VM
+    /// environment pass the validation
+    #[test]
+    fn test_validate_untrusted_code() {
+        let tx_code = std::fs::read(TX_NO_OP_WASM).expect("cannot load wasm");
+        validate_untrusted_code(&tx_code, WasmCodeKind::Tx)
+            .expect("Transaction code should be valid");
+        let vp_code =
+            std::fs::read(VP_READ_STORAGE_KEY_WASM).expect("cannot load wasm");
+        validate_untrusted_code(&vp_code, WasmCodeKind::Vp)
+            .expect("Validity predicate code should be valid");
+
+        // The host functions available to a VP cannot be imported by a tx
+        let error = validate_untrusted_code(&vp_code, WasmCodeKind::Tx)
+            .expect_err("Expected an unknown import");
+        assert!(
+            matches!(error, WasmValidationError::UnknownImport { .. }),
+            "Expected an unknown import, got {}",
+            error
+        );
+    }
+
+    /// Test that all the transactions and validity predicates built from
+    /// `wasm/wasm_source` pass the validation. The wasm must be built first,
+    /// e.g. with `make build-wasm-scripts`.
+    #[test]
+    fn test_validate_untrusted_code_wasm_source() {
+        let mut validated = 0;
+        for entry in std::fs::read_dir(WASM_DIR).expect("cannot read wasm dir")
+        {
+            let path = entry.expect("cannot read wasm dir").path();
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            if !file_name.ends_with(".wasm") {
+                continue;
+            }
+            // The matchmaker and its filter are not submitted in txs
+            let kind = if file_name.starts_with("tx_") {
+                WasmCodeKind::Tx
+            } else if file_name.starts_with("vp_") {
+                WasmCodeKind::Vp
+            } else {
+                continue;
+            };
+            let code = std::fs::read(&path).expect("cannot load wasm");
+            if let Err(error) = validate_untrusted_code(&code, kind) {
+                panic!("{} should be valid, got {}", file_name, error);
+            }
+            validated += 1;
+        }
+        assert!(
+            validated > 0,
+            "No wasm found in {}, build it with `make build-wasm-scripts`",
+            WASM_DIR
+        );
+    }
+
+    /// Test that floating-point instructions are rejected
+    #[test]
+    fn test_validate_untrusted_code_float() {
+        let code = wat2wasm(
+            r#"
+            (module
+                (func $_apply_tx (param i64 i64)
+                (drop (f64.add (f64.const 1.0) (f64.const 2.0))))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .unwrap();
+        let error = validate_untrusted_code(&code, WasmCodeKind::Tx)
+            .expect_err("Expected a floating-point instruction error");
+        assert!(
+            matches!(
+                error,
+                WasmValidationError::FloatingPointInstruction {
+                    func_index: 0,
+                    ..
+                }
+            ),
+            "Expected a floating-point instruction error, got {}",
+            error
+        );
+    }
+
+    /// Test that unknown imports are rejected
+    #[test]
+    fn test_validate_untrusted_code_unknown_import() {
+        let code = wat2wasm(
+            r#"
+            (module
+                (import "env" "anoma_tx_read" (func (param i64 i64) (result i64)))
+                (import "env" "gas" (func (param i32)))
+                (func $_apply_tx (param i64 i64))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .unwrap();
+        let error = validate_untrusted_code(&code, WasmCodeKind::Tx)
+            .expect_err("Expected an unknown import error");
+        match error {
+            WasmValidationError::UnknownImport {
+                module,
+                field,
+                kind,
+            } => {
+                assert_eq!(module, "env");
+                assert_eq!(field, "gas");
+                assert_eq!(kind, WasmCodeKind::Tx);
+            }
+            _ => panic!("Expected an unknown import error, got {}", error),
+        }
+    }
+
+    /// Test that a start function is rejected
+    #[test]
+    fn test_validate_untrusted_code_start_function() {
+        let code = wat2wasm(
+            r#"
+            (module
+                (func $start)
+                (func $_apply_tx (param i64 i64))
+                (start $start)
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .unwrap();
+        let error = validate_untrusted_code(&code, WasmCodeKind::Tx)
+            .expect_err("Expected a start function error");
+        assert!(
+            matches!(error, WasmValidationError::StartFunction(0)),
+            "Expected a start function error, got {}",
+            error
+        );
+    }
+
+    /// Test that a table over the maximum size is rejected
+    #[test]
+    fn test_validate_untrusted_code_oversized_table() {
+        let code = wat2wasm(
+            format!(
+                r#"
+                (module
+                    (func $_apply_tx (param i64 i64))
+                    (table {} funcref)
+                    (export "_apply_tx" (func $_apply_tx)))
+                "#,
+                UNTRUSTED_WASM_MAX_TABLE_SIZE + 1
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        let error = validate_untrusted_code(&code, WasmCodeKind::Tx)
+            .expect_err("Expected an oversized table error");
+        assert!(
+            matches!(
+                error,
+                WasmValidationError::OversizedTable(size)
+                if size == UNTRUSTED_WASM_MAX_TABLE_SIZE + 1
+            ),
+            "Expected an oversized table error, got {}",
+            error
+        );
+    }
+}
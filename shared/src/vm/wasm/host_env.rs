@@ -65,7 +65,8 @@ impl WasmerEnv for FilterEnv<WasmMemory> {
 }
 
 /// Prepare imports (memory and host functions) exposed to the vm guest running
-/// transaction code
+/// transaction code. The host functions must also be added to
+/// [`crate::vm::TX_HOST_FUNCTIONS`].
 #[allow(clippy::too_many_arguments)]
 pub fn tx_imports<DB, H>(
     wasm_store: &Store,
@@ -101,7 +102,8 @@ where
 }
 
 /// Prepare imports (memory and host functions) exposed to the vm guest running
-/// validity predicate code
+/// validity predicate code. The host functions must also be added to
+/// [`crate::vm::VP_HOST_FUNCTIONS`].
 pub fn vp_imports<DB, H, EVAL>(
     wasm_store: &Store,
     initial_memory: Memory,
@@ -3,10 +3,12 @@
 
 use std::ptr::NonNull;
 use std::str::Utf8Error;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use borsh::BorshSerialize;
 use loupe::MemoryUsage;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use wasmer::{
     vm, BaseTunables, HostEnvInitError, LazyInit, Memory, MemoryError,
@@ -16,6 +18,7 @@ use wasmer_vm::{
     MemoryStyle, TableStyle, VMMemoryDefinition, VMTableDefinition,
 };
 
+use crate::ledger::parameters::WasmLimits;
 use crate::vm::memory::VmMemory;
 use crate::vm::types::VpInput;
 
@@ -38,36 +41,81 @@ pub enum Error {
 pub type Result<T> = std::result::Result<T, Error>;
 
 // The bounds are set in number of pages, the actual size is multiplied by
-// `wasmer::WASM_PAGE_SIZE = 64kiB`.
+// `wasmer::WASM_PAGE_SIZE = 64kiB`. The maximum number of pages for
+// transactions and validity predicates are set in the protocol parameters
+// (`crate::ledger::parameters::WasmLimits`).
 // TODO set bounds to accommodate for wasm env size
 /// Initial pages in tx memory
 pub const TX_MEMORY_INIT_PAGES: u32 = 100; // 6.4 MiB
-/// Mamixmum pages in tx memory
-pub const TX_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
 /// Initial pages in VP memory
 pub const VP_MEMORY_INIT_PAGES: u32 = 100; // 6.4 MiB
-/// Mamixmum pages in VP memory
-pub const VP_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
-/// Initial pages in matchmaker memory
+/// Default initial pages in matchmaker memory
 pub const MATCHMAKER_MEMORY_INIT_PAGES: u32 = 400; // 25.6 MiB
-/// Initial pages in matchmaker filter memory
+/// Default initial pages in matchmaker filter memory
 pub const FILTER_MEMORY_INIT_PAGES: u32 = 100; // 6.4 MiB
+// Like the default maximum pages of transactions and validity predicates, the
+// default maximum pages are twice the initial pages.
+/// Default maximum pages in matchmaker memory
+pub const MATCHMAKER_MEMORY_MAX_PAGES: u32 = 800; // 51.2 MiB
+/// Default maximum pages in matchmaker filter memory
+pub const FILTER_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
+
+/// Memory limits of the matchmaker and the matchmaker filter, which are set in
+/// the node's configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryLimits {
+    /// Initial number of pages
+    pub init_pages: u32,
+    /// Maximum number of pages. When not set, the memory may grow up to the
+    /// maximum size of a WASM memory (4 GiB).
+    pub max_pages: Option<u32>,
+}
+
+impl MemoryLimits {
+    /// Default memory limits of the matchmaker
+    pub fn matchmaker() -> Self {
+        Self {
+            init_pages: MATCHMAKER_MEMORY_INIT_PAGES,
+            max_pages: Some(MATCHMAKER_MEMORY_MAX_PAGES),
+        }
+    }
+
+    /// Default memory limits of the matchmaker filter
+    pub fn filter() -> Self {
+        Self {
+            init_pages: FILTER_MEMORY_INIT_PAGES,
+            max_pages: Some(FILTER_MEMORY_MAX_PAGES),
+        }
+    }
+
+    /// The maximum number of pages, defaulting to the maximum size of a WASM
+    /// memory when not set
+    pub fn max_pages(&self) -> u32 {
+        self.max_pages.unwrap_or(wasmer::WASM_MAX_PAGES)
+    }
+}
 
 /// Prepare memory for instantiating a transaction module
-pub fn prepare_tx_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
+pub fn prepare_tx_memory(
+    store: &wasmer::Store,
+    max_pages: u32,
+) -> Result<wasmer::Memory> {
     let mem_type = wasmer::MemoryType::new(
-        TX_MEMORY_INIT_PAGES,
-        Some(TX_MEMORY_MAX_PAGES),
+        TX_MEMORY_INIT_PAGES.min(max_pages),
+        Some(max_pages),
         false,
     );
     Memory::new(store, mem_type).map_err(Error::InitMemoryError)
 }
 
 /// Prepare memory for instantiating a validity predicate module
-pub fn prepare_vp_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
+pub fn prepare_vp_memory(
+    store: &wasmer::Store,
+    max_pages: u32,
+) -> Result<wasmer::Memory> {
     let mem_type = wasmer::MemoryType::new(
-        VP_MEMORY_INIT_PAGES,
-        Some(VP_MEMORY_MAX_PAGES),
+        VP_MEMORY_INIT_PAGES.min(max_pages),
+        Some(max_pages),
         false,
     );
     let memory =
@@ -78,16 +126,20 @@ pub fn prepare_vp_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
 /// Prepare memory for instantiating a matchmaker module
 pub fn prepare_matchmaker_memory(
     store: &wasmer::Store,
+    limits: &MemoryLimits,
 ) -> Result<wasmer::Memory> {
     let mem_type =
-        wasmer::MemoryType::new(MATCHMAKER_MEMORY_INIT_PAGES, None, false);
+        wasmer::MemoryType::new(limits.init_pages, limits.max_pages, false);
     Memory::new(store, mem_type).map_err(Error::InitMemoryError)
 }
 
 /// Prepare memory for instantiating a filter module
-pub fn prepare_filter_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
+pub fn prepare_filter_memory(
+    store: &wasmer::Store,
+    limits: &MemoryLimits,
+) -> Result<wasmer::Memory> {
     let mem_type =
-        wasmer::MemoryType::new(FILTER_MEMORY_INIT_PAGES, None, false);
+        wasmer::MemoryType::new(limits.init_pages, limits.max_pages, false);
     Memory::new(store, mem_type).map_err(Error::InitMemoryError)
 }
 
@@ -376,25 +428,59 @@ pub struct Limit<T: Tunables> {
     /// each). Since Wasmer ensures there is only none or one memory, this
     /// is practically an upper limit for the guest memory.
     limit: Pages,
+    /// Set when a memory created with these tunables attempted to grow over
+    /// the limit
+    exceeded: LimitExceeded,
     /// The base implementation we delegate all the logic to
     base: T,
 }
 
+/// A flag that is set when a WASM memory attempted to grow over its limit.
+/// It's shared between the [`Limit`] tunables and the memories created with
+/// them, so that it can be checked after a WASM execution fails.
+#[derive(Clone, Debug, Default, MemoryUsage)]
+pub struct LimitExceeded(Arc<AtomicBool>);
+
+impl LimitExceeded {
+    /// Check if the memory limit has been exceeded
+    pub fn is_set(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    fn set(&self) {
+        self.0.store(true, Ordering::SeqCst)
+    }
+}
+
+/// A [`Limit`] with the given maximum number of pages.
+pub fn limit(max_pages: u32) -> Limit<BaseTunables> {
+    let base = BaseTunables::for_target(&Target::default());
+    let limit = Pages(max_pages);
+    Limit {
+        limit,
+        exceeded: LimitExceeded::default(),
+        base,
+    }
+}
+
 /// A [`Limit`] with memory limit setup for validity predicate WASM
 /// execution.
-pub fn vp_limit() -> Limit<BaseTunables> {
-    let base = BaseTunables::for_target(&Target::default());
-    let limit = Pages(VP_MEMORY_MAX_PAGES);
-    Limit { limit, base }
+pub fn vp_limit(wasm_limits: &WasmLimits) -> Limit<BaseTunables> {
+    limit(wasm_limits.vp_memory_max_pages)
 }
+
 /// A [`Limit`] with memory limit setup for transaction WASM execution.
-pub fn tx_limit() -> Limit<BaseTunables> {
-    let base = BaseTunables::for_target(&Target::default());
-    let limit = Pages(TX_MEMORY_MAX_PAGES);
-    Limit { limit, base }
+pub fn tx_limit(wasm_limits: &WasmLimits) -> Limit<BaseTunables> {
+    limit(wasm_limits.tx_memory_max_pages)
 }
 
 impl<T: Tunables> Limit<T> {
+    /// Get the flag that is set when a memory created with these tunables
+    /// attempts to grow over the limit.
+    pub fn exceeded(&self) -> LimitExceeded {
+        self.exceeded.clone()
+    }
+
     /// Takes an input memory type as requested by the guest and sets
     /// a maximum if missing. The resulting memory type is final if
     /// valid. However, this can produce invalid types, such that
@@ -431,6 +517,59 @@ impl<T: Tunables> Limit<T> {
 
         Ok(())
     }
+
+    /// Wrap a memory created by the base tunables to record an attempt to
+    /// grow it over the limit.
+    fn limited(&self, memory: Arc<dyn vm::Memory>) -> Arc<dyn vm::Memory> {
+        Arc::new(LimitedMemory {
+            inner: memory,
+            limit: self.limit,
+            exceeded: self.exceeded.clone(),
+        })
+    }
+}
+
+/// A memory created by the [`Limit`] tunables. The implementation is delegated
+/// to the inner memory.
+#[derive(Debug, MemoryUsage)]
+struct LimitedMemory {
+    inner: Arc<dyn vm::Memory>,
+    limit: Pages,
+    exceeded: LimitExceeded,
+}
+
+impl vm::Memory for LimitedMemory {
+    fn ty(&self) -> MemoryType {
+        self.inner.ty()
+    }
+
+    fn style(&self) -> &MemoryStyle {
+        self.inner.style()
+    }
+
+    fn size(&self) -> Pages {
+        self.inner.size()
+    }
+
+    /// Grow the inner memory. When the growth fails because it would go over
+    /// the limit, the [`LimitExceeded`] flag is set.
+    fn grow(&self, delta: Pages) -> std::result::Result<Pages, MemoryError> {
+        let result = self.inner.grow(delta);
+        if result.is_err() {
+            let over_limit = match self.inner.size().0.checked_add(delta.0) {
+                Some(requested) => requested > self.limit.0,
+                None => true,
+            };
+            if over_limit {
+                self.exceeded.set();
+            }
+        }
+        result
+    }
+
+    fn vmmemory(&self) -> NonNull<VMMemoryDefinition> {
+        self.inner.vmmemory()
+    }
 }
 
 impl<T: Tunables> Tunables for Limit<T> {
@@ -461,7 +600,8 @@ impl<T: Tunables> Tunables for Limit<T> {
     ) -> std::result::Result<Arc<dyn vm::Memory>, MemoryError> {
         let adjusted = self.adjust_memory(ty);
         self.validate_memory(&adjusted)?;
-        self.base.create_host_memory(&adjusted, style)
+        let memory = self.base.create_host_memory(&adjusted, style)?;
+        Ok(self.limited(memory))
     }
 
     /// Create a memory owned by the VM given a [`MemoryType`] and a
@@ -476,8 +616,12 @@ impl<T: Tunables> Tunables for Limit<T> {
     ) -> std::result::Result<Arc<dyn vm::Memory>, MemoryError> {
         let adjusted = self.adjust_memory(ty);
         self.validate_memory(&adjusted)?;
-        self.base
-            .create_vm_memory(&adjusted, style, vm_definition_location)
+        let memory = self.base.create_vm_memory(
+            &adjusted,
+            style,
+            vm_definition_location,
+        )?;
+        Ok(self.limited(memory))
     }
 
     /// Create a table owned by the host given a [`TableType`] and a
@@ -508,9 +652,7 @@ impl<T: Tunables> Tunables for Limit<T> {
 
 #[cfg(test)]
 pub mod tests {
-    use wasmer::{
-        wat2wasm, BaseTunables, Cranelift, Instance, Module, Store, Target,
-    };
+    use wasmer::{wat2wasm, Cranelift, Instance, Module, Store};
 
     use super::*;
 
@@ -529,9 +671,8 @@ pub mod tests {
         let compiler = Cranelift::default();
         let engine = wasmer_engine_universal::Universal::new(compiler).engine();
 
-        let base = BaseTunables::for_target(&Target::default());
+        let tunables = super::limit(24);
         let limit = Pages(24);
-        let tunables = Limit { limit, base };
 
         // Create a store, that holds the engine and our custom tunables
         let store = Store::new_with_tunables(&engine, tunables);
@@ -558,4 +699,30 @@ pub mod tests {
         println!("Memory of this instance: {:?}", first_memory);
         assert_eq!(first_memory.ty().maximum.unwrap(), limit);
     }
+
+    #[test]
+    fn test_wasm_tunables_limit_exceeded() {
+        // A Wasm module with one exported memory (min: 7 pages, max: unset)
+        let wat = br#"(module (memory 7) (export "memory" (memory 0)))"#;
+        let wasm_bytes = wat2wasm(wat).unwrap();
+
+        let compiler = Cranelift::default();
+        let engine = wasmer_engine_universal::Universal::new(compiler).engine();
+
+        let tunables = super::limit(24);
+        let exceeded = tunables.exceeded();
+        let store = Store::new_with_tunables(&engine, tunables);
+        let module = Module::new(&store, wasm_bytes).unwrap();
+        let import_object = wasmer::imports! {};
+        let instance = Instance::new(&module, &import_object).unwrap();
+        let memory = instance.exports.get_memory("memory").unwrap();
+
+        // Growing within the limit must succeed
+        memory.grow(Pages(17)).unwrap();
+        assert!(!exceeded.is_set());
+
+        // Growing over the limit must fail and set the flag
+        memory.grow(Pages(1)).expect_err("Expected to fail to grow");
+        assert!(exceeded.is_set());
+    }
 }
@@ -8,9 +8,10 @@ use pwasm_utils::{self, rules};
 use thiserror::Error;
 use wasmer::BaseTunables;
 
-use super::memory::{Limit, WasmMemory};
+use super::memory::{Limit, LimitExceeded, MemoryLimits, WasmMemory};
 use crate::gossip::mm::MmHost;
 use crate::ledger::gas::{BlockGasMeter, VpGasMeter};
+use crate::ledger::parameters::WasmLimits;
 use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{self, Storage, StorageHasher};
 use crate::proto::Tx;
@@ -30,7 +31,8 @@ const TX_ENTRYPOINT: &str = "_apply_tx";
 const VP_ENTRYPOINT: &str = "_validate_tx";
 const MATCHMAKER_ENTRYPOINT: &str = "_match_intent";
 const FILTER_ENTRYPOINT: &str = "_validate_intent";
-const WASM_STACK_LIMIT: u32 = u16::MAX as u32;
+/// The name of the export of the global injected by the stack-height limiter
+const STACK_HEIGHT_EXPORT: &str = "__stack_height";
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -65,6 +67,10 @@ pub enum Error {
     },
     #[error("Wasm validation error: {0}")]
     ValidationError(WasmValidationError),
+    #[error("Wasm memory limit of {0} pages exceeded")]
+    MemoryLimitExceeded(u32),
+    #[error("Wasm stack-height limit of {0} exceeded")]
+    StackLimitExceeded(u32),
 }
 
 /// Result for functions that may fail
@@ -78,12 +84,16 @@ pub fn tx<DB, H>(
     gas_meter: &mut BlockGasMeter,
     tx_code: impl AsRef<[u8]>,
     tx_data: impl AsRef<[u8]>,
+    wasm_limits: &WasmLimits,
 ) -> Result<HashSet<Address>>
 where
     DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
     H: 'static + StorageHasher,
 {
-    let wasm_store = untrusted_wasm_store(memory::tx_limit());
+    let limit = memory::tx_limit(wasm_limits);
+    let memory_limit_exceeded = limit.exceeded();
+    let memory_max_pages = wasm_limits.tx_memory_max_pages;
+    let wasm_store = untrusted_wasm_store(limit);
 
     validate_untrusted_wasm(&tx_code).map_err(Error::ValidationError)?;
 
@@ -101,10 +111,11 @@ where
         &mut result_buffer,
     );
 
-    let tx_code = prepare_wasm_code(tx_code)?;
+    let tx_code = prepare_wasm_code(tx_code, wasm_limits.stack_limit)?;
 
     let initial_memory =
-        memory::prepare_tx_memory(&wasm_store).map_err(Error::MemoryError)?;
+        memory::prepare_tx_memory(&wasm_store, memory_max_pages)
+            .map_err(Error::MemoryError)?;
     let imports = tx_imports(&wasm_store, initial_memory, env);
 
     // Compile the wasm module
@@ -124,7 +135,9 @@ where
     let memory::TxCallInput {
         tx_data_ptr,
         tx_data_len,
-    } = memory::write_tx_inputs(memory, tx_data).map_err(Error::MemoryError)?;
+    } = memory::write_tx_inputs(memory, tx_data).map_err(|err| {
+        memory_error(err, &memory_limit_exceeded, memory_max_pages)
+    })?;
 
     // Get the module's entrypoint to be called
     let apply_tx = instance
@@ -136,9 +149,15 @@ where
             entrypoint: TX_ENTRYPOINT,
             error,
         })?;
-    apply_tx
-        .call(tx_data_ptr, tx_data_len)
-        .map_err(Error::RuntimeError)?;
+    apply_tx.call(tx_data_ptr, tx_data_len).map_err(|err| {
+        runtime_error(
+            err,
+            &instance,
+            &memory_limit_exceeded,
+            memory_max_pages,
+            Some(wasm_limits.stack_limit),
+        )
+    })?;
 
     Ok(verifiers)
 }
@@ -156,6 +175,7 @@ pub fn vp<DB, H>(
     gas_meter: &mut VpGasMeter,
     keys_changed: &HashSet<Key>,
     verifiers: &HashSet<Address>,
+    wasm_limits: &WasmLimits,
 ) -> Result<bool>
 where
     DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
@@ -167,7 +187,9 @@ where
         None => &[],
     };
 
-    let wasm_store = untrusted_wasm_store(memory::tx_limit());
+    let limit = memory::vp_limit(wasm_limits);
+    let memory_limit_exceeded = limit.exceeded();
+    let wasm_store = untrusted_wasm_store(limit);
 
     validate_untrusted_wasm(vp_code).map_err(Error::ValidationError)?;
 
@@ -176,6 +198,7 @@ where
     let eval_runner = VpEvalWasm {
         db: PhantomData,
         hasher: PhantomData,
+        wasm_limits: wasm_limits.clone(),
     };
 
     let env = VpEnv::new(
@@ -193,7 +216,8 @@ where
     );
 
     let initial_memory =
-        memory::prepare_vp_memory(&wasm_store).map_err(Error::MemoryError)?;
+        memory::prepare_vp_memory(&wasm_store, wasm_limits.vp_memory_max_pages)
+            .map_err(Error::MemoryError)?;
     let imports = vp_imports(&wasm_store, initial_memory, env);
 
     run_vp(
@@ -204,9 +228,12 @@ where
         address,
         keys_changed,
         verifiers,
+        wasm_limits,
+        &memory_limit_exceeded,
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_vp(
     wasm_store: wasmer::Store,
     vp_imports: wasmer::ImportObject,
@@ -215,8 +242,11 @@ fn run_vp(
     address: &Address,
     keys_changed: &HashSet<Key>,
     verifiers: &HashSet<Address>,
+    wasm_limits: &WasmLimits,
+    memory_limit_exceeded: &LimitExceeded,
 ) -> Result<bool> {
-    let vp_code = prepare_wasm_code(vp_code)?;
+    let memory_max_pages = wasm_limits.vp_memory_max_pages;
+    let vp_code = prepare_wasm_code(vp_code, wasm_limits.stack_limit)?;
 
     // Compile the wasm module
     let module = wasmer::Module::new(&wasm_store, &vp_code)
@@ -247,7 +277,9 @@ fn run_vp(
         keys_changed_len,
         verifiers_ptr,
         verifiers_len,
-    } = memory::write_vp_inputs(memory, input).map_err(Error::MemoryError)?;
+    } = memory::write_vp_inputs(memory, input).map_err(|err| {
+        memory_error(err, memory_limit_exceeded, memory_max_pages)
+    })?;
 
     // Get the module's entrypoint to be called
     let validate_tx = instance
@@ -270,7 +302,15 @@ fn run_vp(
             verifiers_ptr,
             verifiers_len,
         )
-        .map_err(Error::RuntimeError)?;
+        .map_err(|err| {
+            runtime_error(
+                err,
+                &instance,
+                memory_limit_exceeded,
+                memory_max_pages,
+                Some(wasm_limits.stack_limit),
+            )
+        })?;
     tracing::debug!("is_valid {}", is_valid);
     Ok(is_valid == 1)
 }
@@ -286,6 +326,8 @@ where
     pub db: PhantomData<*const DB>,
     /// Phantom type for DB Hasher
     pub hasher: PhantomData<*const H>,
+    /// The limits of the WASM VM to run the evaluated VPs with
+    pub wasm_limits: WasmLimits,
 }
 
 impl<DB, H> VpEvaluator for VpEvalWasm<DB, H>
@@ -325,12 +367,17 @@ where
         vp_code: Vec<u8>,
         input_data: Vec<u8>,
     ) -> Result<bool> {
-        let wasm_store = untrusted_wasm_store(memory::tx_limit());
+        let limit = memory::vp_limit(&self.wasm_limits);
+        let memory_limit_exceeded = limit.exceeded();
+        let wasm_store = untrusted_wasm_store(limit);
 
         validate_untrusted_wasm(&vp_code).map_err(Error::ValidationError)?;
 
-        let initial_memory = memory::prepare_vp_memory(&wasm_store)
-            .map_err(Error::MemoryError)?;
+        let initial_memory = memory::prepare_vp_memory(
+            &wasm_store,
+            self.wasm_limits.vp_memory_max_pages,
+        )
+        .map_err(Error::MemoryError)?;
 
         let address = unsafe { ctx.address.get() };
         let keys_changed = unsafe { ctx.keys_changed.get() };
@@ -350,6 +397,8 @@ where
             address,
             keys_changed,
             verifiers,
+            &self.wasm_limits,
+            &memory_limit_exceeded,
         )
     }
 }
@@ -361,19 +410,24 @@ pub fn matchmaker<MM>(
     intent_id: impl AsRef<[u8]>,
     intent_data: impl AsRef<[u8]>,
     mm: MM,
+    memory_limits: &MemoryLimits,
 ) -> Result<bool>
 where
     MM: 'static + MmHost,
 {
-    let wasm_store = trusted_wasm_store();
+    let limit = memory::limit(memory_limits.max_pages());
+    let memory_limit_exceeded = limit.exceeded();
+    let memory_max_pages = memory_limits.max_pages();
+    let wasm_store = trusted_wasm_store(limit);
 
     // Compile the wasm module
     let module: wasmer::Module =
         wasmer::Module::new(&wasm_store, &matchmaker_code)
             .map_err(Error::CompileError)?;
 
-    let initial_memory = memory::prepare_matchmaker_memory(&wasm_store)
-        .map_err(Error::MemoryError)?;
+    let initial_memory =
+        memory::prepare_matchmaker_memory(&wasm_store, memory_limits)
+            .map_err(Error::MemoryError)?;
 
     let matchmaker_imports = mm_imports(&wasm_store, initial_memory, mm);
 
@@ -394,7 +448,9 @@ where
         intent_data_len,
     }: memory::MatchmakerCallInput =
         memory::write_matchmaker_inputs(memory, data, intent_id, intent_data)
-            .map_err(Error::MemoryError)?;
+            .map_err(|err| {
+            memory_error(err, &memory_limit_exceeded, memory_max_pages)
+        })?;
     let apply_matchmaker = instance
         .exports
         .get_function(MATCHMAKER_ENTRYPOINT)
@@ -413,7 +469,15 @@ where
             intent_data_ptr,
             intent_data_len,
         )
-        .map_err(Error::RuntimeError)?;
+        .map_err(|err| {
+            runtime_error(
+                err,
+                &instance,
+                &memory_limit_exceeded,
+                memory_max_pages,
+                None,
+            )
+        })?;
     Ok(found_match == 0)
 }
 
@@ -422,16 +486,21 @@ where
 pub fn matchmaker_filter(
     code: impl AsRef<[u8]>,
     intent_data: impl AsRef<[u8]>,
+    memory_limits: &MemoryLimits,
 ) -> Result<bool> {
-    let wasm_store = trusted_wasm_store();
+    let limit = memory::limit(memory_limits.max_pages());
+    let memory_limit_exceeded = limit.exceeded();
+    let memory_max_pages = memory_limits.max_pages();
+    let wasm_store = trusted_wasm_store(limit);
 
     validate_untrusted_wasm(code.as_ref()).map_err(Error::ValidationError)?;
 
     // Compile the wasm module
     let module: wasmer::Module =
         wasmer::Module::new(&wasm_store, &code).map_err(Error::CompileError)?;
-    let initial_memory = memory::prepare_filter_memory(&wasm_store)
-        .map_err(Error::MemoryError)?;
+    let initial_memory =
+        memory::prepare_filter_memory(&wasm_store, memory_limits)
+            .map_err(Error::MemoryError)?;
 
     let filter_imports = mm_filter_imports(&wasm_store, initial_memory);
 
@@ -447,8 +516,9 @@ pub fn matchmaker_filter(
         intent_data_ptr,
         intent_data_len,
     }: memory::FilterCallInput =
-        memory::write_filter_inputs(memory, intent_data)
-            .map_err(Error::MemoryError)?;
+        memory::write_filter_inputs(memory, intent_data).map_err(|err| {
+            memory_error(err, &memory_limit_exceeded, memory_max_pages)
+        })?;
     let apply_filter = instance
         .exports
         .get_function(FILTER_ENTRYPOINT)
@@ -460,7 +530,15 @@ pub fn matchmaker_filter(
         })?;
     let found_match = apply_filter
         .call(intent_data_ptr, intent_data_len)
-        .map_err(Error::RuntimeError)?;
+        .map_err(|err| {
+            runtime_error(
+                err,
+                &instance,
+                &memory_limit_exceeded,
+                memory_max_pages,
+                None,
+            )
+        })?;
     Ok(found_match == 0)
 }
 
@@ -475,27 +553,105 @@ fn untrusted_wasm_store(limit: Limit<BaseTunables>) -> wasmer::Store {
 }
 
 /// Prepare a wasm store for trusted code.
-fn trusted_wasm_store() -> wasmer::Store {
+fn trusted_wasm_store(limit: Limit<BaseTunables>) -> wasmer::Store {
     // TODO use LLVM compiler with native engine
     let compiler = wasmer_compiler_cranelift::Cranelift::default();
-    wasmer::Store::new(
+    wasmer::Store::new_with_tunables(
         &wasmer_engine_universal::Universal::new(compiler).engine(),
+        limit,
     )
 }
 
 /// Inject gas counter and stack-height limiter into the given wasm code
-fn prepare_wasm_code<T: AsRef<[u8]>>(code: T) -> Result<Vec<u8>> {
+fn prepare_wasm_code<T: AsRef<[u8]>>(
+    code: T,
+    stack_limit: u32,
+) -> Result<Vec<u8>> {
     let module: elements::Module = elements::deserialize_buffer(code.as_ref())
         .map_err(Error::DeserializationError)?;
     let module =
         pwasm_utils::inject_gas_counter(module, &get_gas_rules(), "env")
             .map_err(|_original_module| Error::GasMeterInjection)?;
-    let module =
-        pwasm_utils::stack_height::inject_limiter(module, WASM_STACK_LIMIT)
-            .map_err(|_original_module| Error::StackLimiterInjection)?;
+    let module = pwasm_utils::stack_height::inject_limiter(module, stack_limit)
+        .map_err(|_original_module| Error::StackLimiterInjection)?;
+    let module = export_stack_height(module);
     elements::serialize(module).map_err(Error::SerializationError)
 }
 
+/// Export the global used by the stack-height limiter, so that we can check if
+/// the limit has been exceeded when the execution fails. The limiter appends
+/// the global to the end of the module's global section. The export section
+/// is added if the module doesn't export anything.
+fn export_stack_height(mut module: elements::Module) -> elements::Module {
+    let globals_len = module
+        .global_section()
+        .map(|section| section.entries().len())
+        .unwrap_or_default();
+    let global_index = match globals_len.checked_sub(1) {
+        Some(global_index) => global_index as u32,
+        None => return module,
+    };
+    let export = elements::ExportEntry::new(
+        STACK_HEIGHT_EXPORT.to_string(),
+        elements::Internal::Global(global_index),
+    );
+    match module.export_section_mut() {
+        Some(exports) => exports.entries_mut().push(export),
+        None => {
+            let exports = elements::ExportSection::with_entries(vec![export]);
+            // This cannot fail, because the module has no export section
+            let _ = module.insert_section(elements::Section::Export(exports));
+        }
+    }
+    module
+}
+
+/// Check the stack-height global exported by [`export_stack_height`] to find
+/// out if the execution of the given instance went over the limit.
+fn stack_limit_exceeded(instance: &wasmer::Instance, stack_limit: u32) -> bool {
+    match instance.exports.get_global(STACK_HEIGHT_EXPORT) {
+        Ok(global) => match global.get() {
+            wasmer::Val::I32(height) => height as u32 > stack_limit,
+            _ => false,
+        },
+        Err(_) => false,
+    }
+}
+
+/// Turn an error from writing the inputs into wasm memory into
+/// [`Error::MemoryLimitExceeded`] if it was caused by the memory limit.
+fn memory_error(
+    error: memory::Error,
+    memory_limit_exceeded: &LimitExceeded,
+    memory_max_pages: u32,
+) -> Error {
+    if memory_limit_exceeded.is_set() {
+        Error::MemoryLimitExceeded(memory_max_pages)
+    } else {
+        Error::MemoryError(error)
+    }
+}
+
+/// Turn a wasm runtime error into [`Error::MemoryLimitExceeded`] or
+/// [`Error::StackLimitExceeded`] if it was caused by one of the limits.
+fn runtime_error(
+    error: wasmer::RuntimeError,
+    instance: &wasmer::Instance,
+    memory_limit_exceeded: &LimitExceeded,
+    memory_max_pages: u32,
+    stack_limit: Option<u32>,
+) -> Error {
+    if memory_limit_exceeded.is_set() {
+        return Error::MemoryLimitExceeded(memory_max_pages);
+    }
+    match stack_limit {
+        Some(stack_limit) if stack_limit_exceeded(instance, stack_limit) => {
+            Error::StackLimitExceeded(stack_limit)
+        }
+        _ => Error::RuntimeError(error),
+    }
+}
+
 /// Get the gas rules used to meter wasm operations
 fn get_gas_rules() -> rules::Set {
     rules::Set::default().with_grow_cost(1)
@@ -503,14 +659,16 @@ fn get_gas_rules() -> rules::Set {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use borsh::BorshSerialize;
-    use itertools::Either;
     use test_env_log::test;
-    use wasmer_vm::TrapCode;
 
     use super::*;
     use crate::ledger::storage::testing::TestStorage;
+    use crate::types::address::InternalAddress;
     use crate::types::validity_predicate::EvalVp;
+    use crate::vm::{TX_HOST_FUNCTIONS, VP_HOST_FUNCTIONS};
 
     const TX_MEMORY_LIMIT_WASM: &str = "../wasm_for_tests/tx_memory_limit.wasm";
     const TX_NO_OP_WASM: &str = "../wasm_for_tests/tx_no_op.wasm";
@@ -529,16 +687,16 @@ mod tests {
         // Because each call into `$loop` inside the wasm consumes 3 stack
         // heights, this should hit the stack limit. If we were to subtract
         // one from this value, we should be just under the limit.
-        let loops = WASM_STACK_LIMIT / 3 - 1;
+        let loops = WasmLimits::default().stack_limit / 3 - 1;
 
         let error = loop_in_tx_wasm(loops).expect_err(&format!(
-            "Expecting runtime error \"unreachable\" caused by stack-height \
-             overflow, loops {}. Got",
+            "Expecting stack-height overflow error, loops {}. Got",
             loops,
         ));
-        assert_eq!(
-            get_trap_code(&error),
-            Either::Left(wasmer_vm::TrapCode::UnreachableCodeReached),
+        assert!(
+            matches!(error, Error::StackLimitExceeded(_)),
+            "Expected stack-height limit error, got {:?}",
+            error
         );
 
         // one less loop shouldn't go over the limit
@@ -553,15 +711,14 @@ mod tests {
         // Because each call into `$loop` inside the wasm consumes 3 stack
         // heights, this should hit the stack limit. If we were to subtract
         // one from this value, we should be just under the limit.
-        let loops = WASM_STACK_LIMIT / 3 - 1;
-
-        let error = loop_in_vp_wasm(loops).expect_err(
-            "Expecting runtime error \"unreachable\" caused by stack-height \
-             overflow. Got",
-        );
-        assert_eq!(
-            get_trap_code(&error),
-            Either::Left(wasmer_vm::TrapCode::UnreachableCodeReached),
+        let loops = WasmLimits::default().stack_limit / 3 - 1;
+
+        let error = loop_in_vp_wasm(loops)
+            .expect_err("Expecting stack-height overflow error. Got");
+        assert!(
+            matches!(error, Error::StackLimitExceeded(_)),
+            "Expected stack-height limit error, got {:?}",
+            error
         );
 
         // one less loop shouldn't go over the limit
@@ -569,6 +726,52 @@ mod tests {
         assert!(result.is_ok(), "Expected success. Got {:?}", result);
     }
 
+    /// Test that the stack-height global is exported from a module that
+    /// doesn't export anything else.
+    #[test]
+    fn test_export_stack_height_without_exports() {
+        let code = wasmer::wat2wasm(
+            r#"
+            (module
+                (func $loop (param i32)
+                    local.get 0
+                    i32.eqz
+                    br_if 0
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    call $loop))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+        let code = prepare_wasm_code(code, WasmLimits::default().stack_limit)
+            .expect("preparing the wasm code failed");
+
+        let module: elements::Module = elements::deserialize_buffer(&code)
+            .expect("deserializing the wasm code failed");
+        let globals_len = module
+            .global_section()
+            .expect("the stack-height global must be injected")
+            .entries()
+            .len();
+        let exports = module
+            .export_section()
+            .expect("the export section must be added")
+            .entries();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].field(), STACK_HEIGHT_EXPORT);
+        assert_eq!(
+            *exports[0].internal(),
+            elements::Internal::Global(globals_len as u32 - 1)
+        );
+        // The module must still be valid
+        let store =
+            untrusted_wasm_store(memory::tx_limit(&WasmLimits::default()));
+        wasmer::Module::new(&store, &code).expect("invalid wasm module");
+    }
+
     /// Test that when a transaction wasm goes over the memory limit inside the
     /// wasm execution, the execution is aborted.
     #[test]
@@ -582,7 +785,8 @@ mod tests {
             std::fs::read(TX_MEMORY_LIMIT_WASM).expect("cannot load wasm");
 
         // Assuming 200 pages, 12.8 MiB limit
-        assert_eq!(memory::TX_MEMORY_MAX_PAGES, 200);
+        let wasm_limits = WasmLimits::default();
+        assert_eq!(wasm_limits.tx_memory_max_pages, 200);
 
         // Allocating `2^23` (8 MiB) should be below the memory limit and
         // shouldn't fail
@@ -593,18 +797,26 @@ mod tests {
             &mut gas_meter,
             tx_code.clone(),
             tx_data,
+            &wasm_limits,
         );
         assert!(result.is_ok(), "Expected success, got {:?}", result);
 
         // Allocating `2^24` (16 MiB) should be above the memory limit and
         // should fail
         let tx_data = 2_usize.pow(24).try_to_vec().unwrap();
-        let error =
-            tx(&storage, &mut write_log, &mut gas_meter, tx_code, tx_data)
-                .expect_err("Expected to run out of memory");
-        assert_eq!(
-            get_trap_code(&error),
-            Either::Left(wasmer_vm::TrapCode::UnreachableCodeReached),
+        let error = tx(
+            &storage,
+            &mut write_log,
+            &mut gas_meter,
+            tx_code,
+            tx_data,
+            &wasm_limits,
+        )
+        .expect_err("Expected to run out of memory");
+        assert!(
+            matches!(error, Error::MemoryLimitExceeded(200)),
+            "Expected memory limit error, got {:?}",
+            error
         );
     }
 
@@ -627,7 +839,8 @@ mod tests {
             std::fs::read(VP_MEMORY_LIMIT_WASM).expect("cannot load wasm");
 
         // Assuming 200 pages, 12.8 MiB limit
-        assert_eq!(memory::VP_MEMORY_MAX_PAGES, 200);
+        let wasm_limits = WasmLimits::default();
+        assert_eq!(wasm_limits.vp_memory_max_pages, 200);
 
         // Allocating `2^23` (8 MiB) should be below the memory limit and
         // shouldn't fail
@@ -649,6 +862,7 @@ mod tests {
             &mut gas_meter,
             &keys_changed,
             &verifiers,
+            &wasm_limits,
         )
         .unwrap();
         assert!(passed);
@@ -674,6 +888,7 @@ mod tests {
             &mut gas_meter,
             &keys_changed,
             &verifiers,
+            &wasm_limits,
         )
         .unwrap();
 
@@ -696,7 +911,8 @@ mod tests {
             std::fs::read(VP_MEMORY_LIMIT_WASM).expect("cannot load wasm");
 
         // Assuming 200 pages, 12.8 MiB limit
-        assert_eq!(memory::VP_MEMORY_MAX_PAGES, 200);
+        let wasm_limits = WasmLimits::default();
+        assert_eq!(wasm_limits.vp_memory_max_pages, 200);
 
         // Allocating `2^23` (8 MiB) should be below the memory limit and
         // shouldn't fail
@@ -711,6 +927,7 @@ mod tests {
             &mut gas_meter,
             &keys_changed,
             &verifiers,
+            &wasm_limits,
         );
         assert!(result.is_ok(), "Expected success, got {:?}", result);
 
@@ -727,12 +944,14 @@ mod tests {
             &mut gas_meter,
             &keys_changed,
             &verifiers,
+            &wasm_limits,
         )
         .expect_err("Expected to run out of memory");
 
-        assert_eq!(
-            get_trap_code(&error),
-            Either::Left(wasmer_vm::TrapCode::UnreachableCodeReached),
+        assert!(
+            matches!(error, Error::MemoryLimitExceeded(200)),
+            "Expected memory limit error, got {:?}",
+            error
         );
     }
 
@@ -747,18 +966,23 @@ mod tests {
         let tx_no_op = std::fs::read(TX_NO_OP_WASM).expect("cannot load wasm");
 
         // Assuming 200 pages, 12.8 MiB limit
-        assert_eq!(memory::TX_MEMORY_MAX_PAGES, 200);
+        let wasm_limits = WasmLimits::default();
+        assert_eq!(wasm_limits.tx_memory_max_pages, 200);
 
         // Allocating `2^24` (16 MiB) for the input should be above the memory
         // limit and should fail
         let len = 2_usize.pow(24);
         let tx_data: Vec<u8> = vec![6_u8; len];
-        let result =
-            tx(&storage, &mut write_log, &mut gas_meter, tx_no_op, tx_data);
+        let result = tx(
+            &storage,
+            &mut write_log,
+            &mut gas_meter,
+            tx_no_op,
+            tx_data,
+            &wasm_limits,
+        );
         match result {
-            Err(Error::MemoryError(memory::Error::MemoryOutOfBounds(
-                wasmer::MemoryError::CouldNotGrow { .. },
-            ))) => {
+            Err(Error::MemoryLimitExceeded(200)) => {
                 // as expected
             }
             _ => panic!("Expected to run out of memory, got {:?}", result),
@@ -780,7 +1004,8 @@ mod tests {
             std::fs::read(VP_ALWAYS_TRUE_WASM).expect("cannot load wasm");
 
         // Assuming 200 pages, 12.8 MiB limit
-        assert_eq!(memory::VP_MEMORY_MAX_PAGES, 200);
+        let wasm_limits = WasmLimits::default();
+        assert_eq!(wasm_limits.vp_memory_max_pages, 200);
 
         // Allocating `2^24` (16 MiB) for the input should be above the memory
         // limit and should fail
@@ -796,11 +1021,10 @@ mod tests {
             &mut gas_meter,
             &keys_changed,
             &verifiers,
+            &wasm_limits,
         );
         match result {
-            Err(Error::MemoryError(memory::Error::MemoryOutOfBounds(
-                wasmer::MemoryError::CouldNotGrow { .. },
-            ))) => {
+            Err(Error::MemoryLimitExceeded(200)) => {
                 // as expected
             }
             _ => panic!("Expected to run out of memory, got {:?}", result),
@@ -815,6 +1039,7 @@ mod tests {
         let mut storage = TestStorage::default();
         let mut write_log = WriteLog::default();
         let mut gas_meter = BlockGasMeter::default();
+        let wasm_limits = WasmLimits::default();
 
         let tx_read_key =
             std::fs::read(TX_READ_STORAGE_KEY_WASM).expect("cannot load wasm");
@@ -837,11 +1062,13 @@ mod tests {
             &mut gas_meter,
             tx_read_key,
             tx_data,
+            &wasm_limits,
         )
         .expect_err("Expected to run out of memory");
-        assert_eq!(
-            get_trap_code(&error),
-            Either::Left(wasmer_vm::TrapCode::UnreachableCodeReached),
+        assert!(
+            matches!(error, Error::MemoryLimitExceeded(200)),
+            "Expected memory limit error, got {:?}",
+            error
         );
     }
 
@@ -856,6 +1083,7 @@ mod tests {
         let mut gas_meter = VpGasMeter::new(0);
         let keys_changed = HashSet::new();
         let verifiers = HashSet::new();
+        let wasm_limits = WasmLimits::default();
 
         let vp_read_key =
             std::fs::read(VP_READ_STORAGE_KEY_WASM).expect("cannot load wasm");
@@ -882,11 +1110,13 @@ mod tests {
             &mut gas_meter,
             &keys_changed,
             &verifiers,
+            &wasm_limits,
         )
         .expect_err("Expected to run out of memory");
-        assert_eq!(
-            get_trap_code(&error),
-            Either::Left(wasmer_vm::TrapCode::UnreachableCodeReached),
+        assert!(
+            matches!(error, Error::MemoryLimitExceeded(200)),
+            "Expected memory limit error, got {:?}",
+            error
         );
     }
 
@@ -902,6 +1132,7 @@ mod tests {
         let mut gas_meter = VpGasMeter::new(0);
         let keys_changed = HashSet::new();
         let verifiers = HashSet::new();
+        let wasm_limits = WasmLimits::default();
 
         // This code will call `eval` with the other VP below
         let vp_eval = std::fs::read(VP_EVAL_WASM).expect("cannot load wasm");
@@ -936,11 +1167,93 @@ mod tests {
             &mut gas_meter,
             &keys_changed,
             &verifiers,
+            &wasm_limits,
         )
         .unwrap();
         assert!(!passed);
     }
 
+    /// Test that the host functions that untrusted code may import, as
+    /// checked by [`crate::vm::validate_untrusted_code`], match the imports
+    /// prepared for the wasm guest.
+    #[test]
+    fn test_host_functions_match_imports() {
+        let storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let mut gas_meter = BlockGasMeter::default();
+        let mut vp_gas_meter = VpGasMeter::new(0);
+        let wasm_limits = WasmLimits::default();
+        let tx = Tx::new(vec![], None);
+        let address = Address::Internal(InternalAddress::PoS);
+        let keys_changed = HashSet::new();
+        let mut verifiers = HashSet::new();
+        let mut tx_iterators = PrefixIterators::default();
+        let mut vp_iterators = PrefixIterators::default();
+        let mut tx_result_buffer = None;
+        let mut vp_result_buffer = None;
+        let eval_runner = VpEvalWasm {
+            db: PhantomData,
+            hasher: PhantomData,
+            wasm_limits: wasm_limits.clone(),
+        };
+        // The memory and the gas meter are provided by the host
+        let host_functions = |imports: &wasmer::ImportObject| {
+            imports
+                .get_namespace_exports("env")
+                .expect("the imports must have the \"env\" namespace")
+                .iter()
+                .map(|(name, _)| name.clone())
+                .filter(|name| name != "memory" && name != "gas")
+                .collect::<BTreeSet<_>>()
+        };
+        let expected = |functions: &[&str]| {
+            functions
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<BTreeSet<_>>()
+        };
+
+        let wasm_store = untrusted_wasm_store(memory::tx_limit(&wasm_limits));
+        let initial_memory = memory::prepare_tx_memory(
+            &wasm_store,
+            wasm_limits.tx_memory_max_pages,
+        )
+        .unwrap();
+        let env = TxEnv::new(
+            WasmMemory::default(),
+            &storage,
+            &mut write_log,
+            &mut tx_iterators,
+            &mut gas_meter,
+            &mut verifiers,
+            &mut tx_result_buffer,
+        );
+        let imports = tx_imports(&wasm_store, initial_memory, env);
+        assert_eq!(host_functions(&imports), expected(TX_HOST_FUNCTIONS));
+
+        let wasm_store = untrusted_wasm_store(memory::vp_limit(&wasm_limits));
+        let initial_memory = memory::prepare_vp_memory(
+            &wasm_store,
+            wasm_limits.vp_memory_max_pages,
+        )
+        .unwrap();
+        let env = VpEnv::new(
+            WasmMemory::default(),
+            &address,
+            &storage,
+            &write_log,
+            &mut vp_gas_meter,
+            &tx,
+            &mut vp_iterators,
+            &verifiers,
+            &mut vp_result_buffer,
+            &keys_changed,
+            &eval_runner,
+        );
+        let imports = vp_imports(&wasm_store, initial_memory, env);
+        assert_eq!(host_functions(&imports), expected(VP_HOST_FUNCTIONS));
+    }
+
     fn loop_in_tx_wasm(loops: u32) -> Result<HashSet<Address>> {
         // A transaction with a recursive loop.
         // The boilerplate code is generated from tx_template.wasm using
@@ -980,7 +1293,15 @@ mod tests {
         let storage = TestStorage::default();
         let mut write_log = WriteLog::default();
         let mut gas_meter = BlockGasMeter::default();
-        tx(&storage, &mut write_log, &mut gas_meter, tx_code, tx_data)
+        let wasm_limits = WasmLimits::default();
+        tx(
+            &storage,
+            &mut write_log,
+            &mut gas_meter,
+            tx_code,
+            tx_data,
+            &wasm_limits,
+        )
     }
 
     fn loop_in_vp_wasm(loops: u32) -> Result<bool> {
@@ -1019,6 +1340,7 @@ mod tests {
         let mut gas_meter = VpGasMeter::new(0);
         let keys_changed = HashSet::new();
         let verifiers = HashSet::new();
+        let wasm_limits = WasmLimits::default();
         vp(
             vp_code,
             &tx,
@@ -1028,18 +1350,7 @@ mod tests {
             &mut gas_meter,
             &keys_changed,
             &verifiers,
+            &wasm_limits,
         )
     }
-
-    fn get_trap_code(error: &Error) -> Either<TrapCode, String> {
-        if let Error::RuntimeError(err) = error {
-            if let Some(trap_code) = err.clone().to_trap() {
-                Either::Left(trap_code)
-            } else {
-                Either::Right(format!("Missing trap code {}", err))
-            }
-        } else {
-            Either::Right(format!("Unexpected error {}", error))
-        }
-    }
 }
@@ -12,6 +12,7 @@
 //! `ANOMA_E2E_KEEP_TEMP=true`.
 
 pub mod gossip_tests;
+pub mod ibc_tests;
 pub mod ledger_tests;
 pub mod setup;
 pub mod wallet_tests;
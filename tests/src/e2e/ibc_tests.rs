@@ -0,0 +1,592 @@
+//! IBC E2E tests between two local chains. A minimal relayer built into these
+//! tests opens a connection and a channel between the chains and relays the
+//! packets, acknowledgements and timeouts. The relayer uses mock light
+//! clients, so the relayed proofs are queried from the chains, but they are
+//! not verified.
+//!
+//! By default, these tests will run in release mode. This can be disabled
+//! by setting environment variable `ANOMA_E2E_DEBUG=true`. For debugging,
+//! you'll typically also want to set `RUST_BACKTRACE=1`, e.g.:
+//!
+//! ```ignore,shell
+//! ANOMA_E2E_DEBUG=true RUST_BACKTRACE=1 cargo test e2e::ibc_tests -- --test-threads=1 --nocapture
+//! ```
+//!
+//! To keep the temporary files created by a test, use env var
+//! `ANOMA_E2E_KEEP_TEMP=true`.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use anoma::types::ibc::{
+    channel_counterparty, channel_id, commitment_prefix,
+    connection_counterparty, connection_id, port_channel_id, port_id, sequence,
+    ChannelOpenAckData, ChannelOpenConfirmData, ChannelOpenInitData,
+    ChannelOpenTryData, ClientCreationData, ClientUpdateData,
+    ConnectionOpenAckData, ConnectionOpenConfirmData, ConnectionOpenInitData,
+    ConnectionOpenTryData, IbcMessage, PacketAckData, PacketReceiptData,
+    PacketSendData, TimeoutData,
+};
+use anoma::types::storage::Epoch;
+use anoma_apps::node::ledger::rpc::Path;
+use borsh::{BorshDeserialize, BorshSerialize};
+use color_eyre::eyre::Result;
+use eyre::eyre;
+use ibc::ics02_client::client_consensus::ConsensusState;
+use ibc::ics02_client::client_state::{AnyClientState, ClientState};
+use ibc::ics02_client::header::Header;
+use ibc::ics03_connection::connection::{
+    ConnectionEnd, Counterparty as ConnCounterparty, State as ConnState,
+};
+use ibc::ics03_connection::version::Version;
+use ibc::ics04_channel::channel::{
+    ChannelEnd, Counterparty as ChanCounterparty, Order, State as ChanState,
+};
+use ibc::ics04_channel::packet::Packet;
+use ibc::ics23_commitment::commitment::CommitmentProofBytes;
+use ibc::ics24_host::identifier::{
+    ChannelId, ClientId, ConnectionId, PortChannelId, PortId,
+};
+use ibc::mock::client_state::{MockClientState, MockConsensusState};
+use ibc::mock::header::MockHeader;
+use ibc::timestamp::Timestamp;
+use ibc::Height;
+use setup::constants::*;
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+use tendermint_rpc::{Client, HttpClient};
+use tokio::runtime::Runtime;
+
+use crate::e2e::setup::{self, sleep, Bin, Test, Who};
+use crate::{run, run_as};
+
+/// In this test we:
+/// 1. Run the ledger nodes of two chains
+/// 2. Create a client of each chain on the other chain
+/// 3. Open a connection between the chains
+/// 4. Open a channel between the chains
+/// 5. Send a packet from the first chain and receive it on the second chain
+/// 6. Relay the acknowledgement of the packet to the first chain
+/// 7. Send a packet which isn't received before its timeout height
+/// 8. Relay the timeout of the packet to the first chain
+#[test]
+fn run_ledger_ibc() -> Result<()> {
+    let (test_a, test_b) = setup::two_single_node_nets()?;
+
+    // 1. Run the ledger nodes
+    let mut ledger_a =
+        run_as!(test_a, Who::Validator(0), Bin::Node, &["ledger"], Some(40))?;
+    ledger_a.exp_string("Anoma ledger node started")?;
+    let mut ledger_b =
+        run_as!(test_b, Who::Validator(0), Bin::Node, &["ledger"], Some(40))?;
+    ledger_b.exp_string("Anoma ledger node started")?;
+    // Wait to commit a block on both chains
+    ledger_a.exp_regex(r"Committed block hash.*, height: [0-9]+")?;
+    ledger_b.exp_regex(r"Committed block hash.*, height: [0-9]+")?;
+
+    // 2. Create the clients
+    let runtime = Runtime::new()?;
+    let (mut chain_a, mut chain_b) =
+        create_clients(&test_a, &test_b, &runtime)?;
+
+    // 3. Open a connection
+    let (conn_a, conn_b) = open_connection(&mut chain_a, &mut chain_b)?;
+
+    // 4. Open a channel
+    let port_id = port_id("test_port")?;
+    let (channel_a, channel_b) =
+        open_channel(&mut chain_a, &mut chain_b, &port_id, &conn_a, &conn_b)?;
+    let port_channel_a = port_channel_id(port_id.clone(), channel_a.clone());
+    let port_channel_b = port_channel_id(port_id.clone(), channel_b.clone());
+
+    // 5. Send a packet and receive it on the counterparty chain
+    let timestamp = chrono::Utc::now() + chrono::Duration::seconds(100);
+    let data = PacketSendData::new(
+        port_id.clone(),
+        channel_a.clone(),
+        port_id.clone(),
+        channel_b.clone(),
+        b"ping".to_vec(),
+        Height::zero(),
+        Timestamp::from_datetime(timestamp),
+    );
+    let packet = data.packet(sequence(1));
+    submit_ibc_tx(chain_a.test, data.into())?;
+    assert_eq!(
+        unreceived_packets(&chain_b, &port_channel_b, &[1])?,
+        vec![1]
+    );
+    receive_packet(&mut chain_b, &chain_a, &packet)?;
+    assert!(unreceived_packets(&chain_b, &port_channel_b, &[1])?.is_empty());
+
+    // 6. Relay the acknowledgement
+    assert_eq!(unreceived_acks(&chain_a, &port_channel_a, &[1])?, vec![1]);
+    acknowledge_packet(&mut chain_a, &chain_b, &packet)?;
+    assert!(unreceived_acks(&chain_a, &port_channel_a, &[1])?.is_empty());
+
+    // 7. Send a packet which times out on the counterparty chain
+    let height_b = chain_b.height()?;
+    let timeout_height =
+        Height::new(height_b.revision_number, height_b.revision_height + 2);
+    let data = PacketSendData::new(
+        port_id.clone(),
+        channel_a,
+        port_id,
+        channel_b,
+        b"timeout".to_vec(),
+        timeout_height,
+        Timestamp::none(),
+    );
+    let packet = data.packet(sequence(2));
+    submit_ibc_tx(chain_a.test, data.into())?;
+    // Wait until the counterparty chain passes the timeout height
+    while chain_b.height()? <= timeout_height {
+        sleep(1);
+    }
+    assert_eq!(
+        unreceived_packets(&chain_b, &port_channel_b, &[2])?,
+        vec![2]
+    );
+
+    // 8. Relay the timeout
+    timeout_packet(&mut chain_a, &chain_b, &packet)?;
+    assert!(unreceived_acks(&chain_a, &port_channel_a, &[2])?.is_empty());
+
+    Ok(())
+}
+
+/// A chain connected by the relayer with its client of the counterparty chain
+struct Chain<'a> {
+    test: &'a Test,
+    runtime: &'a Runtime,
+    rpc: HttpClient,
+    /// The ID of the client of the counterparty chain on this chain
+    client_id: ClientId,
+    /// The latest height of the counterparty chain known by the client
+    client_height: Height,
+}
+
+/// The proofs for a connection or channel handshake
+struct HandshakeProofs {
+    /// The client state of this chain on the counterparty chain
+    client_state: AnyClientState,
+    /// The proof of the connection or channel
+    proof_object: CommitmentProofBytes,
+    /// The proof of the client state
+    proof_client: CommitmentProofBytes,
+    /// The proof of the consensus state
+    proof_consensus: CommitmentProofBytes,
+}
+
+impl<'a> Chain<'a> {
+    /// Query the current height of the chain as an IBC height. The revision
+    /// number is the epoch.
+    fn height(&self) -> Result<Height> {
+        let info = self.runtime.block_on(self.rpc.abci_info())?;
+        let response = self.query(Path::Epoch, vec![])?;
+        let epoch = Epoch::try_from_slice(&response.value[..])?;
+        Ok(Height::new(epoch.0, info.last_block_height.value()))
+    }
+
+    /// Query the chain with a proof
+    fn query(&self, path: Path, data: Vec<u8>) -> Result<AbciQuery> {
+        let response = self.runtime.block_on(self.rpc.abci_query(
+            Some(path.into()),
+            data,
+            None,
+            true,
+        ))?;
+        Ok(response)
+    }
+
+    /// Query an IBC state and its proof
+    fn query_value<T: BorshDeserialize>(
+        &self,
+        path: Path,
+    ) -> Result<(T, CommitmentProofBytes)> {
+        let response = self.query(path, vec![])?;
+        if response.code.is_err() {
+            return Err(eyre!("The IBC query failed: {}", response.info));
+        }
+        let value = T::try_from_slice(&response.value[..])?;
+        Ok((value, proof_bytes(response)?))
+    }
+
+    /// Query the membership or non-membership proof of an IBC state
+    fn query_proof(&self, path: Path) -> Result<CommitmentProofBytes> {
+        let response = self.query(path, vec![])?;
+        proof_bytes(response)
+    }
+
+    /// Query the proofs to open a connection or channel on the counterparty
+    /// chain
+    fn handshake_proofs(&self, object_path: Path) -> Result<HandshakeProofs> {
+        let proof_object = self.query_proof(object_path)?;
+        let (client_state, proof_client) = self.query_value::<AnyClientState>(
+            Path::IbcClientState(self.client_id.clone()),
+        )?;
+        let proof_consensus = self.query_proof(Path::IbcConsensusState(
+            self.client_id.clone(),
+            client_state.latest_height(),
+        ))?;
+        Ok(HandshakeProofs {
+            client_state,
+            proof_object,
+            proof_client,
+            proof_consensus,
+        })
+    }
+}
+
+/// Returns the ICS-23 proof in the query response
+fn proof_bytes(response: AbciQuery) -> Result<CommitmentProofBytes> {
+    let op = response
+        .proof
+        .and_then(|proof| proof.ops.into_iter().next())
+        .ok_or_else(|| eyre!("No proof in the IBC query response"))?;
+    Ok(CommitmentProofBytes::from(op.data))
+}
+
+/// Create a client of each chain on the other chain
+fn create_clients<'a>(
+    test_a: &'a Test,
+    test_b: &'a Test,
+    runtime: &'a Runtime,
+) -> Result<(Chain<'a>, Chain<'a>)> {
+    let mut chains = vec![];
+    for test in [test_a, test_b] {
+        let address =
+            tendermint::net::Address::from_str(&test.net.ledger_address)?;
+        chains.push(Chain {
+            test,
+            runtime,
+            rpc: HttpClient::new(address)?,
+            client_id: ClientId::default(),
+            client_height: Height::zero(),
+        });
+    }
+    let mut chain_b = chains.pop().unwrap();
+    let mut chain_a = chains.pop().unwrap();
+
+    create_client(&mut chain_a, &chain_b)?;
+    create_client(&mut chain_b, &chain_a)?;
+    Ok((chain_a, chain_b))
+}
+
+/// Create a client of the counterparty chain on the chain
+fn create_client(chain: &mut Chain, counterparty: &Chain) -> Result<()> {
+    let height = counterparty.height()?;
+    let header = MockHeader {
+        height,
+        timestamp: Timestamp::now(),
+    };
+    let client_state = MockClientState(header).wrap_any();
+    let consensus_state = MockConsensusState::new(header).wrap_any();
+    let data = ClientCreationData::new(client_state, consensus_state);
+    // This is the first client on the chain
+    chain.client_id = data.client_id(0)?;
+    chain.client_height = height;
+    submit_ibc_tx(chain.test, data.into())
+}
+
+/// Update the client of the counterparty chain on the chain to the current
+/// height of the counterparty chain. Returns the latest height of the client.
+fn update_client(chain: &mut Chain, counterparty: &Chain) -> Result<Height> {
+    let height = counterparty.height()?;
+    if height > chain.client_height {
+        let header = MockHeader {
+            height,
+            timestamp: Timestamp::now(),
+        };
+        let data = ClientUpdateData::new(
+            chain.client_id.clone(),
+            vec![header.wrap_any()],
+        );
+        submit_ibc_tx(chain.test, data.into())?;
+        chain.client_height = height;
+    }
+    Ok(chain.client_height)
+}
+
+/// Open a connection between the chains. Returns the connection IDs on both
+/// chains.
+fn open_connection(
+    chain_a: &mut Chain,
+    chain_b: &mut Chain,
+) -> Result<(ConnectionId, ConnectionId)> {
+    let delay_period = Duration::new(0, 0);
+
+    // Initialize a connection on the chain A
+    let counterparty = ConnCounterparty::new(
+        chain_b.client_id.clone(),
+        None,
+        commitment_prefix(),
+    );
+    let data = ConnectionOpenInitData::new(
+        chain_a.client_id.clone(),
+        counterparty,
+        Version::default(),
+        delay_period,
+    );
+    submit_ibc_tx(chain_a.test, data.into())?;
+    // This is the first connection on the chain
+    let conn_a = connection_id(0);
+
+    // Try to open the connection on the chain B
+    let proofs =
+        chain_a.handshake_proofs(Path::IbcConnection(conn_a.clone()))?;
+    let height = update_client(chain_b, chain_a)?;
+    let data = ConnectionOpenTryData::new(
+        chain_b.client_id.clone(),
+        proofs.client_state,
+        connection_counterparty(chain_a.client_id.clone(), conn_a.clone()),
+        vec![Version::default()],
+        height,
+        proofs.proof_object,
+        proofs.proof_client,
+        proofs.proof_consensus,
+        delay_period,
+    );
+    submit_ibc_tx(chain_b.test, data.into())?;
+    let conn_b = connection_id(0);
+
+    // Acknowledge the connection on the chain A
+    let proofs =
+        chain_b.handshake_proofs(Path::IbcConnection(conn_b.clone()))?;
+    let height = update_client(chain_a, chain_b)?;
+    let data = ConnectionOpenAckData::new(
+        conn_a.clone(),
+        conn_b.clone(),
+        proofs.client_state,
+        height,
+        proofs.proof_object,
+        proofs.proof_client,
+        proofs.proof_consensus,
+        Version::default(),
+    );
+    submit_ibc_tx(chain_a.test, data.into())?;
+
+    // Confirm the connection on the chain B
+    let proofs =
+        chain_a.handshake_proofs(Path::IbcConnection(conn_a.clone()))?;
+    let height = update_client(chain_b, chain_a)?;
+    let data = ConnectionOpenConfirmData::new(
+        conn_b.clone(),
+        height,
+        proofs.proof_object,
+        proofs.proof_client,
+        proofs.proof_consensus,
+    );
+    submit_ibc_tx(chain_b.test, data.into())?;
+
+    // Check the connection ends
+    for (chain, conn_id) in [(&*chain_a, &conn_a), (&*chain_b, &conn_b)] {
+        let (conn, _) = chain.query_value::<ConnectionEnd>(
+            Path::IbcConnection(conn_id.clone()),
+        )?;
+        assert_eq!(conn.state(), &ConnState::Open);
+    }
+
+    Ok((conn_a, conn_b))
+}
+
+/// Open an unordered channel between the chains. Returns the channel IDs on
+/// both chains.
+fn open_channel(
+    chain_a: &mut Chain,
+    chain_b: &mut Chain,
+    port_id: &PortId,
+    conn_a: &ConnectionId,
+    conn_b: &ConnectionId,
+) -> Result<(ChannelId, ChannelId)> {
+    let order = Order::Unordered;
+    let version = order.to_string();
+
+    // Initialize a channel on the chain A
+    let data = ChannelOpenInitData::new(
+        port_id.clone(),
+        order,
+        ChanCounterparty::new(port_id.clone(), None),
+        vec![conn_a.clone()],
+        version.clone(),
+    );
+    submit_ibc_tx(chain_a.test, data.into())?;
+    // This is the first channel on the chain
+    let channel_a = channel_id(0);
+    let port_channel_a = port_channel_id(port_id.clone(), channel_a.clone());
+
+    // Try to open the channel on the chain B
+    let proofs =
+        chain_a.handshake_proofs(Path::IbcChannel(port_channel_a.clone()))?;
+    let height = update_client(chain_b, chain_a)?;
+    let data = ChannelOpenTryData::new(
+        port_id.clone(),
+        order,
+        channel_counterparty(port_id.clone(), channel_a.clone()),
+        vec![conn_b.clone()],
+        version.clone(),
+        version.clone(),
+        height,
+        proofs.proof_object,
+        proofs.proof_client,
+        proofs.proof_consensus,
+    );
+    submit_ibc_tx(chain_b.test, data.into())?;
+    let channel_b = channel_id(0);
+    let port_channel_b = port_channel_id(port_id.clone(), channel_b.clone());
+
+    // Acknowledge the channel on the chain A
+    let proofs =
+        chain_b.handshake_proofs(Path::IbcChannel(port_channel_b.clone()))?;
+    let height = update_client(chain_a, chain_b)?;
+    let data = ChannelOpenAckData::new(
+        port_id.clone(),
+        channel_a.clone(),
+        channel_b.clone(),
+        version,
+        height,
+        proofs.proof_object,
+        proofs.proof_client,
+        proofs.proof_consensus,
+    );
+    submit_ibc_tx(chain_a.test, data.into())?;
+
+    // Confirm the channel on the chain B
+    let proofs =
+        chain_a.handshake_proofs(Path::IbcChannel(port_channel_a.clone()))?;
+    let height = update_client(chain_b, chain_a)?;
+    let data = ChannelOpenConfirmData::new(
+        port_id.clone(),
+        channel_b.clone(),
+        height,
+        proofs.proof_object,
+        proofs.proof_client,
+        proofs.proof_consensus,
+    );
+    submit_ibc_tx(chain_b.test, data.into())?;
+
+    // Check the channel ends
+    for (chain, port_channel_id) in
+        [(&*chain_a, port_channel_a), (&*chain_b, port_channel_b)]
+    {
+        let (channel, _) = chain
+            .query_value::<ChannelEnd>(Path::IbcChannel(port_channel_id))?;
+        assert_eq!(channel.state(), &ChanState::Open);
+    }
+
+    Ok((channel_a, channel_b))
+}
+
+/// Receive the packet sent from the counterparty chain
+fn receive_packet(
+    chain: &mut Chain,
+    counterparty: &Chain,
+    packet: &Packet,
+) -> Result<()> {
+    let port_channel_id = port_channel_id(
+        packet.source_port.clone(),
+        packet.source_channel.clone(),
+    );
+    let proof = counterparty.query_proof(Path::IbcPacketCommitment(
+        port_channel_id,
+        packet.sequence,
+    ))?;
+    let height = update_client(chain, counterparty)?;
+    let data = PacketReceiptData::new(packet.clone(), height, proof);
+    submit_ibc_tx(chain.test, data.into())
+}
+
+/// Acknowledge the packet sent from the chain with the acknowledgement written
+/// on the counterparty chain
+fn acknowledge_packet(
+    chain: &mut Chain,
+    counterparty: &Chain,
+    packet: &Packet,
+) -> Result<()> {
+    let port_channel_id = port_channel_id(
+        packet.destination_port.clone(),
+        packet.destination_channel.clone(),
+    );
+    let (ack, proof) = counterparty.query_value::<Vec<u8>>(
+        Path::IbcPacketAck(port_channel_id, packet.sequence),
+    )?;
+    let height = update_client(chain, counterparty)?;
+    let data = PacketAckData::new(packet.clone(), ack, height, proof);
+    submit_ibc_tx(chain.test, data.into())
+}
+
+/// Time out the packet sent from the chain which hasn't been received on the
+/// counterparty chain
+fn timeout_packet(
+    chain: &mut Chain,
+    counterparty: &Chain,
+    packet: &Packet,
+) -> Result<()> {
+    let port_channel_id = port_channel_id(
+        packet.destination_port.clone(),
+        packet.destination_channel.clone(),
+    );
+    // The proof of the receipt absence
+    let proof = counterparty.query_proof(Path::IbcPacketReceipt(
+        port_channel_id,
+        packet.sequence,
+    ))?;
+    let height = update_client(chain, counterparty)?;
+    let data = TimeoutData::new(packet.clone(), packet.sequence, height, proof);
+    submit_ibc_tx(chain.test, data.into())
+}
+
+/// Returns the given sequences of the packets which haven't been received on
+/// the chain
+fn unreceived_packets(
+    chain: &Chain,
+    port_channel_id: &PortChannelId,
+    sequences: &[u64],
+) -> Result<Vec<u64>> {
+    let path = Path::IbcUnreceivedPackets(port_channel_id.clone());
+    query_sequences(chain, path, sequences)
+}
+
+/// Returns the given sequences of the packets sent from the chain whose
+/// acknowledgements haven't been received
+fn unreceived_acks(
+    chain: &Chain,
+    port_channel_id: &PortChannelId,
+    sequences: &[u64],
+) -> Result<Vec<u64>> {
+    let path = Path::IbcUnreceivedAcks(port_channel_id.clone());
+    query_sequences(chain, path, sequences)
+}
+
+fn query_sequences(
+    chain: &Chain,
+    path: Path,
+    sequences: &[u64],
+) -> Result<Vec<u64>> {
+    let response = chain.query(path, sequences.to_vec().try_to_vec()?)?;
+    if response.code.is_err() {
+        return Err(eyre!("The IBC query failed: {}", response.info));
+    }
+    Ok(Vec::<u64>::try_from_slice(&response.value[..])?)
+}
+
+/// Submit the IBC message with the IBC transaction to the chain of the test
+fn submit_ibc_tx(test: &Test, message: IbcMessage) -> Result<()> {
+    let data_path = test.base_dir.path().join("tx_ibc.data");
+    std::fs::write(&data_path, message.try_to_vec()?)?;
+    let code_path = wasm_abs_path(TX_IBC_WASM);
+    let code_path = code_path.to_string_lossy();
+    let data_path = data_path.to_string_lossy();
+    let tx_args = vec![
+        "tx",
+        "--code-path",
+        &code_path,
+        "--data-path",
+        &data_path,
+        "--ledger-address",
+        &test.net.ledger_address,
+    ];
+    let mut client = run!(test, Bin::Client, tx_args, Some(40))?;
+    client.exp_string("Transaction is valid.")?;
+    client.assert_success();
+    Ok(())
+}
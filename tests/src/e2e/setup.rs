@@ -39,10 +39,20 @@ const ENV_VAR_KEEP_TEMP: &str = "ANOMA_E2E_KEEP_TEMP";
 /// setup the [`network`].
 const SINGLE_NODE_NET_GENESIS: &str = "genesis/e2e-tests-single-node.toml";
 
+/// The chain ID prefix of the E2E test networks
+const CHAIN_PREFIX: &str = "e2e-test";
+
+/// The offset of the ports of the second network in
+/// [`two_single_node_nets`], so that both networks can run on localhost at
+/// the same time
+const SECOND_NET_PORT_OFFSET: u16 = 1000;
+
 /// An E2E test network.
 #[derive(Debug)]
 pub struct Network {
-    chain_id: ChainId,
+    pub chain_id: ChainId,
+    /// The ledger RPC address of the first genesis validator node
+    pub ledger_address: String,
 }
 
 /// Add `num` validators to the genesis config.
@@ -65,14 +75,50 @@ pub fn add_validators(num: u8, mut genesis: GenesisConfig) -> GenesisConfig {
     genesis
 }
 
+/// Shift the ports of all the validators in the genesis config by `offset`.
+pub fn shift_validator_ports(
+    offset: u16,
+    mut genesis: GenesisConfig,
+) -> GenesisConfig {
+    for validator in genesis.validator.values_mut() {
+        if let Some(net_address) = validator.net_address.as_mut() {
+            let mut address = SocketAddr::from_str(net_address).unwrap();
+            address.set_port(address.port() + offset);
+            *net_address = address.to_string();
+        }
+    }
+    genesis
+}
+
 /// Setup a network with a single genesis validator node.
 pub fn single_node_net() -> Result<Test> {
     network(|genesis| genesis)
 }
 
+/// Setup two different networks, each with a single genesis validator node.
+/// The networks use different ports and chain IDs, so their nodes can run on
+/// localhost at the same time, e.g. to test IBC between them.
+pub fn two_single_node_nets() -> Result<(Test, Test)> {
+    let first = network_with_chain_prefix(|genesis| genesis, CHAIN_PREFIX)?;
+    let second_prefix = format!("{}-b", CHAIN_PREFIX);
+    let second = network_with_chain_prefix(
+        |genesis| shift_validator_ports(SECOND_NET_PORT_OFFSET, genesis),
+        &second_prefix,
+    )?;
+    Ok((first, second))
+}
+
 /// Setup a configurable network.
 pub fn network(
     update_genesis: impl Fn(GenesisConfig) -> GenesisConfig,
+) -> Result<Test> {
+    network_with_chain_prefix(update_genesis, CHAIN_PREFIX)
+}
+
+/// Setup a configurable network with the given chain ID prefix.
+pub fn network_with_chain_prefix(
+    update_genesis: impl Fn(GenesisConfig) -> GenesisConfig,
+    chain_prefix: &str,
 ) -> Result<Test> {
     INIT.call_once(|| {
         if let Err(err) = color_eyre::install() {
@@ -90,6 +136,15 @@ pub fn network(
     // Run the provided function on it
     let mut genesis = update_genesis(genesis);
 
+    // The ledger RPC port is right after the P2P port of the validator's net
+    // address, see `init-network`
+    let validator_0 = genesis.validator.get("validator-0").unwrap();
+    let mut ledger_address =
+        SocketAddr::from_str(validator_0.net_address.as_ref().unwrap())
+            .unwrap();
+    ledger_address.set_port(ledger_address.port() + 1);
+    let ledger_address = ledger_address.to_string();
+
     // Update the WASM sha256 fields
     let checksums =
         wasm_loader::Checksums::read_checksums(working_dir.join("wasm"));
@@ -123,7 +178,7 @@ pub fn network(
             "--genesis-path",
             genesis_path.as_ref(),
             "--chain-prefix",
-            "e2e-test",
+            chain_prefix,
             "--localhost",
         ],
         Some(5),
@@ -139,7 +194,10 @@ pub fn network(
         matched.trim().split_once("Derived chain ID: ").unwrap().1;
     let chain_id = ChainId::from_str(chain_id_raw.trim())?;
     println!("'init-network' output: {}", unread);
-    let net = Network { chain_id };
+    let net = Network {
+        chain_id,
+        ledger_address,
+    };
 
     // Move the "others" accounts wallet in the main base dir, so that we can
     // use them with `Who::NonValidator`
@@ -578,6 +636,7 @@ pub mod constants {
 
     // Paths to the WASMs used for tests
     pub const TX_TRANSFER_WASM: &str = "wasm/tx_transfer.wasm";
+    pub const TX_IBC_WASM: &str = "wasm/tx_ibc.wasm";
     pub const VP_USER_WASM: &str = "wasm/vp_user.wasm";
     pub const TX_NO_OP_WASM: &str = "wasm_for_tests/tx_no_op.wasm";
     pub const VP_ALWAYS_TRUE_WASM: &str = "wasm_for_tests/vp_always_true.wasm";
@@ -8,9 +8,10 @@ pub use anoma::ledger::ibc::storage::{
     ack_key, capability_index_key, capability_key, channel_counter_key,
     channel_key, client_counter_key, client_state_key, client_type_key,
     commitment_key, connection_counter_key, connection_key,
-    consensus_state_key, next_sequence_ack_key, next_sequence_recv_key,
-    next_sequence_send_key, port_key, receipt_key,
+    consensus_state_key, ibc_denom_key, next_sequence_ack_key,
+    next_sequence_recv_key, next_sequence_send_key, port_key, receipt_key,
 };
+use anoma::ledger::ibc::token::IbcToken;
 use anoma::ledger::ibc::Ibc;
 use anoma::ledger::native_vp::{Ctx, NativeVp};
 use anoma::ledger::storage::mockdb::MockDB;
@@ -19,6 +20,7 @@ use anoma::proto::Tx;
 use anoma::types::address::{Address, InternalAddress};
 pub use anoma::types::ibc::*;
 use anoma::types::storage::Key;
+use anoma::types::token::Amount;
 use anoma_vm_env::tx_prelude::BorshSerialize;
 use ibc::ics02_client::client_consensus::ConsensusState;
 use ibc::ics02_client::client_state::{AnyClientState, ClientState};
@@ -79,6 +81,44 @@ pub fn init_ibc_vp_from_tx<'a>(
     TestIbcVp { ibc, keys_changed }
 }
 
+pub struct TestIbcTokenVp<'a> {
+    pub token: IbcToken<'a, MockDB, Sha256Hasher>,
+    pub keys_changed: HashSet<Key>,
+}
+
+impl<'a> TestIbcTokenVp<'a> {
+    pub fn validate(
+        &self,
+        tx_data: &[u8],
+    ) -> std::result::Result<bool, anoma::ledger::ibc::token::Error> {
+        self.token
+            .validate_tx(tx_data, &self.keys_changed, &HashSet::new())
+    }
+}
+
+/// Initialize IBC token VP for the given address by running a transaction.
+pub fn init_token_vp_from_tx<'a>(
+    tx_env: &'a TestTxEnv,
+    tx: &'a Tx,
+    addr: &Address,
+) -> TestIbcTokenVp<'a> {
+    let keys_changed = tx_env
+        .write_log
+        .verifiers_changed_keys(&HashSet::new())
+        .get(addr)
+        .cloned()
+        .expect("no IBC token address");
+
+    let ctx =
+        Ctx::new(&tx_env.storage, &tx_env.write_log, tx, VpGasMeter::new(0));
+    let token = IbcToken { ctx };
+
+    TestIbcTokenVp {
+        token,
+        keys_changed,
+    }
+}
+
 pub fn tm_dummy_header() -> TmHeader {
     TmHeader {
         version: TmVersion { block: 10, app: 0 },
@@ -152,11 +192,18 @@ pub fn prepare_opened_connection(
 
 pub fn prepare_opened_channel(
     conn_id: &ConnectionId,
+) -> (PortId, ChannelId, HashMap<Key, Vec<u8>>) {
+    let port_id = port_id("test_port").expect("invalid port ID");
+    prepare_opened_channel_on_port(conn_id, port_id)
+}
+
+pub fn prepare_opened_channel_on_port(
+    conn_id: &ConnectionId,
+    port_id: PortId,
 ) -> (PortId, ChannelId, HashMap<Key, Vec<u8>>) {
     let mut writes = HashMap::new();
 
     // port
-    let port_id = port_id("test_port").expect("invalid port ID");
     let key = port_key(&port_id);
     writes.insert(key, 0_u64.try_to_vec().unwrap());
     // capability
@@ -397,6 +444,24 @@ pub fn packet_send_data(
     )
 }
 
+pub fn transfer_packet_send_data(
+    port_id: PortId,
+    channel_id: ChannelId,
+    token: &Address,
+    sender: &Address,
+    amount: Amount,
+) -> PacketSendData {
+    let mut data = packet_send_data(port_id, channel_id);
+    data.packet_data = FungibleTokenPacketData::new(
+        token.encode(),
+        amount,
+        sender.encode(),
+        "counterpart_receiver",
+    )
+    .encode();
+    data
+}
+
 pub fn set_timeout_height(data: &mut PacketSendData) {
     data.timeout_height = Height::new(1, 1);
 }
@@ -429,6 +494,25 @@ pub fn received_packet(
     }
 }
 
+pub fn received_transfer_packet(
+    port_id: PortId,
+    channel_id: ChannelId,
+    sequence: Sequence,
+    denom: impl Into<String>,
+    receiver: &Address,
+    amount: Amount,
+) -> Packet {
+    let mut packet = received_packet(port_id, channel_id, sequence);
+    packet.data = FungibleTokenPacketData::new(
+        denom,
+        amount,
+        "counterpart_sender",
+        receiver.encode(),
+    )
+    .encode();
+    packet
+}
+
 pub fn timeout_data(packet: Packet, next_seq_recv: Sequence) -> TimeoutData {
     TimeoutData::new(packet, next_seq_recv, Height::new(1, 10), vec![0].into())
 }
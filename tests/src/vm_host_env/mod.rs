@@ -24,7 +24,8 @@ mod tests {
     use anoma::types::key::ed25519::SignedTxData;
     use anoma::types::storage::{self, Key, KeySeg};
     use anoma::types::time::DateTimeUtc;
-    use anoma::types::{address, key};
+    use anoma::types::{address, key, token};
+    use anoma_vm_env::ibc as ibc_tx;
     use anoma_vm_env::tx_prelude::{
         BorshDeserialize, BorshSerialize, KeyValIterator,
     };
@@ -414,12 +415,13 @@ mod tests {
                     _ => panic!("decoding failed"),
                 };
             assert_eq!(&signed_tx_data.data, data);
-            assert!(vp_host_env::verify_tx_signature(&pk, &signed_tx_data.sig));
+            let sig = signed_tx_data.sig().expect("signature should exist");
+            assert!(vp_host_env::verify_tx_signature(&pk, sig));
 
             let other_keypair = key::ed25519::testing::keypair_2();
             assert!(!vp_host_env::verify_tx_signature(
                 &other_keypair.public,
-                &signed_tx_data.sig
+                sig
             ));
         }
     }
@@ -484,7 +486,9 @@ mod tests {
 
         // Start an invalid transaction
         let data = ibc::client_creation_data();
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -513,7 +517,9 @@ mod tests {
 
         // Start a transaction to create a new client
         let data = ibc::client_creation_data();
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -552,7 +558,9 @@ mod tests {
 
         // Start an invalid transaction
         let data = ibc::client_update_data(client_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -584,7 +592,9 @@ mod tests {
 
         // Start a transaction to update the client
         let data = ibc::client_update_data(client_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -618,7 +628,9 @@ mod tests {
 
         // Start a transaction to upgrade the client
         let data = ibc::client_upgrade_data(client_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -661,7 +673,9 @@ mod tests {
 
         // Start an invalid transaction
         let data = ibc::connection_open_init_data(client_id.clone());
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -692,7 +706,9 @@ mod tests {
         // Start a transaction for ConnectionOpenInit
         // tx (Not need to decode tx_data)
         let data = ibc::connection_open_init_data(client_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -725,7 +741,9 @@ mod tests {
         // Start the next transaction for ConnectionOpenAck
         // tx (Not need to decode tx_data)
         let data = ibc::connection_open_ack_data(conn_id, client_state);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -764,7 +782,9 @@ mod tests {
         // Start a transaction for ConnectionOpenTry
         // tx (Not need to decode tx_data)
         let data = ibc::connection_open_try_data(client_id, client_state);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -797,7 +817,9 @@ mod tests {
         // Start the next transaction for ConnectionOpenConfirm
         // tx (Not need to decode tx_data)
         let data = ibc::connection_open_confirm_data(conn_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -837,7 +859,9 @@ mod tests {
         let port_id = ibc::port_id("test_port").expect("invalid port ID");
         let data =
             ibc::channel_open_init_data(port_id.clone(), conn_id.clone());
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -870,7 +894,9 @@ mod tests {
         let port_id = ibc::port_id("test_port").expect("invalid port ID");
         let data =
             ibc::channel_open_init_data(port_id.clone(), conn_id.clone());
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -911,7 +937,9 @@ mod tests {
         // tx (Not need to decode tx_data)
         let port_id = ibc::port_id("test_port").expect("invalid port ID");
         let data = ibc::channel_open_init_data(port_id.clone(), conn_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -952,7 +980,9 @@ mod tests {
         // Start the next transaction for ChannelOpenAck
         // tx (Not need to decode tx_data)
         let data = ibc::channel_open_ack_data(port_id, channel_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -994,7 +1024,9 @@ mod tests {
         // tx (Not need to decode tx_data)
         let port_id = ibc::port_id("test_port").expect("invalid port ID");
         let data = ibc::channel_open_try_data(port_id.clone(), conn_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1035,7 +1067,9 @@ mod tests {
         // Start the next transaction for ChannelOpenConfirm
         // tx (Not need to decode tx_data)
         let data = ibc::channel_open_confirm_data(port_id, channel_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1079,7 +1113,9 @@ mod tests {
         // Start a transaction to close the channel
         // tx (Not need to decode tx_data)
         let data = ibc::channel_close_init_data(port_id, channel_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1124,7 +1160,9 @@ mod tests {
         // Start a transaction to close the channel
         // tx (Not need to decode tx_data)
         let data = ibc::channel_close_confirm_data(port_id, channel_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1169,7 +1207,9 @@ mod tests {
         // Start a transaction to send a packet
         // tx (Not need to decode tx_data)
         let data = ibc::packet_send_data(port_id, channel_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1210,7 +1250,9 @@ mod tests {
         // Start the next transaction for receiving an ack
         // tx (Not need to decode tx_data)
         let data = ibc::packet_ack_data(packet);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1269,7 +1311,9 @@ mod tests {
         // Start a transaction to receive a packet
         // tx (Not need to decode tx_data)
         let data = ibc::packet_receipt_data(packet);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1326,7 +1370,9 @@ mod tests {
         // Start a transaction to send a packet
         // tx (Not need to decode tx_data)
         let data = ibc::packet_send_data(port_id, channel_id);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1367,7 +1413,9 @@ mod tests {
         // Start the next transaction for receiving an ack
         // tx (Not need to decode tx_data)
         let data = ibc::packet_ack_data(packet);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1426,7 +1474,9 @@ mod tests {
         // Start a transaction to receive a packet
         // tx (Not need to decode tx_data)
         let data = ibc::packet_receipt_data(packet);
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1502,7 +1552,9 @@ mod tests {
 
         // Start a transaction to notify the timeout
         let data = ibc::timeout_data(packet, ibc::sequence(1));
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1579,7 +1631,9 @@ mod tests {
 
         // Start a transaction to notify the timing-out on closed
         let data = ibc::timeout_data(packet, ibc::sequence(1));
-        let tx_data = data.try_to_vec().expect("encoding failed");
+        let tx_data = ibc::IbcMessage::from(data.clone())
+            .try_to_vec()
+            .expect("encoding failed");
         let tx = Tx {
             code: vec![],
             data: Some(tx_data.clone()),
@@ -1610,4 +1664,286 @@ mod tests {
                 .expect("validation failed unexpectedly")
         );
     }
+
+    #[test]
+    fn test_ibc_tx_channel_handshake_and_packet() {
+        // The environment must be initialized first
+        let mut env = TestTxEnv::default();
+        init_tx_env(&mut env);
+
+        // Set the initial state before starting transactions
+        init_genesis_storage(&mut env.storage);
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        writes.into_iter().for_each(|(key, val)| {
+            env.storage.write(&key, val).expect("write error");
+        });
+
+        // Start a transaction for ChannelOpenInit with the IBC tx library
+        let port_id = ibc::port_id("test_port").expect("invalid port ID");
+        let data = ibc::channel_open_init_data(port_id.clone(), conn_id);
+        let msg = ibc::IbcMessage::from(data);
+        let tx_data = msg.try_to_vec().expect("encoding failed");
+        let tx = Tx {
+            code: vec![],
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        };
+        ibc_tx::dispatch(msg).expect("the IBC message failed");
+
+        // Check
+        let ibc_vp = ibc::init_ibc_vp_from_tx(&env, &tx);
+        assert!(
+            ibc_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+
+        // Commit
+        env.write_log.commit_tx();
+        env.write_log.commit_block(&mut env.storage).unwrap();
+
+        // Start the next transaction for ChannelOpenAck
+        let channel_id = ibc::channel_id(0);
+        let data = ibc::channel_open_ack_data(port_id, channel_id.clone());
+        let port_id = data.port_id.clone();
+        let msg = ibc::IbcMessage::from(data);
+        let tx_data = msg.try_to_vec().expect("encoding failed");
+        let tx = Tx {
+            code: vec![],
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        };
+        ibc_tx::dispatch(msg).expect("the IBC message failed");
+
+        // Check
+        let ibc_vp = ibc::init_ibc_vp_from_tx(&env, &tx);
+        assert!(
+            ibc_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+
+        // Commit
+        env.write_log.commit_tx();
+        env.write_log.commit_block(&mut env.storage).unwrap();
+
+        // Start a transaction to send a packet on the opened channel
+        let data = ibc::packet_send_data(port_id, channel_id);
+        let msg = ibc::IbcMessage::from(data);
+        let tx_data = msg.try_to_vec().expect("encoding failed");
+        let tx = Tx {
+            code: vec![],
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        };
+        ibc_tx::dispatch(msg).expect("the IBC message failed");
+
+        // Check
+        let ibc_vp = ibc::init_ibc_vp_from_tx(&env, &tx);
+        assert!(
+            ibc_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+    }
+
+    #[test]
+    fn test_ibc_transfer() {
+        // The environment must be initialized first
+        let mut env = TestTxEnv::default();
+        init_tx_env(&mut env);
+
+        // Set the initial state before starting transactions
+        init_genesis_storage(&mut env.storage);
+        let (client_id, _client_state, mut writes) = ibc::prepare_client();
+        let (conn_id, conn_writes) = ibc::prepare_opened_connection(&client_id);
+        writes.extend(conn_writes);
+        let port_id =
+            ibc::port_id(ibc::TRANSFER_PORT_ID).expect("invalid port ID");
+        let (port_id, channel_id, channel_writes) =
+            ibc::prepare_opened_channel_on_port(&conn_id, port_id);
+        writes.extend(channel_writes);
+        writes.into_iter().for_each(|(key, val)| {
+            env.storage.write(&key, val).expect("write error");
+        });
+        let token = address::xan();
+        let owner = address::testing::established_address_1();
+        let escrow =
+            address::Address::Internal(address::InternalAddress::IbcEscrow);
+        let balance_key = token::balance_key(&token, &owner);
+        let init_balance = token::Amount::from(1_000_000);
+        env.storage
+            .write(&balance_key, init_balance.try_to_vec().unwrap())
+            .expect("write error");
+
+        // Start a transaction to send the token
+        let amount = token::Amount::from(100);
+        let data = ibc::transfer_packet_send_data(
+            port_id.clone(),
+            channel_id.clone(),
+            &token,
+            &owner,
+            amount,
+        );
+        let msg = ibc::IbcMessage::from(data);
+        let tx_data = msg.try_to_vec().expect("encoding failed");
+        let tx = Tx {
+            code: vec![],
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        };
+        ibc_tx::dispatch(msg).expect("the IBC message failed");
+
+        // Check the escrow and the VPs
+        let escrow_key = token::balance_key(&token, &escrow);
+        let escrowed: token::Amount =
+            tx_host_env::read(&escrow_key.to_string()).expect("no escrow");
+        assert_eq!(escrowed, amount);
+        let ibc_vp = ibc::init_ibc_vp_from_tx(&env, &tx);
+        assert!(
+            ibc_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+        let token_vp = ibc::init_token_vp_from_tx(&env, &tx, &escrow);
+        assert!(
+            token_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+
+        // Commit
+        env.write_log.commit_tx();
+        env.write_log.commit_block(&mut env.storage).unwrap();
+
+        // Start a transaction to receive the token back
+        let denom = format!(
+            "counterpart_test_port/counterpart_test_channel/{}",
+            token.encode()
+        );
+        let packet = ibc::received_transfer_packet(
+            port_id.clone(),
+            channel_id.clone(),
+            ibc::sequence(1),
+            denom,
+            &owner,
+            amount,
+        );
+        let msg = ibc::IbcMessage::from(ibc::packet_receipt_data(packet));
+        let tx_data = msg.try_to_vec().expect("encoding failed");
+        let tx = Tx {
+            code: vec![],
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        };
+        ibc_tx::dispatch(msg).expect("the IBC message failed");
+
+        // Check the token has been released from the escrow
+        let balance: token::Amount =
+            tx_host_env::read(&balance_key.to_string()).expect("no balance");
+        assert_eq!(balance, init_balance);
+        let ibc_vp = ibc::init_ibc_vp_from_tx(&env, &tx);
+        assert!(
+            ibc_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+        let token_vp = ibc::init_token_vp_from_tx(&env, &tx, &escrow);
+        assert!(
+            token_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+
+        // Commit
+        env.write_log.commit_tx();
+        env.write_log.commit_block(&mut env.storage).unwrap();
+
+        // Start a transaction to receive a token of the counterparty chain
+        let packet = ibc::received_transfer_packet(
+            port_id.clone(),
+            channel_id.clone(),
+            ibc::sequence(2),
+            "uatom",
+            &owner,
+            amount,
+        );
+        let msg = ibc::IbcMessage::from(ibc::packet_receipt_data(packet));
+        let tx_data = msg.try_to_vec().expect("encoding failed");
+        let tx = Tx {
+            code: vec![],
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        };
+        ibc_tx::dispatch(msg).expect("the IBC message failed");
+
+        // Check the IBC token has been minted with the denomination trace
+        let trace =
+            format!("{}uatom", ibc::denom_prefix(&port_id, &channel_id));
+        let ibc_token = address::Address::Internal(
+            address::InternalAddress::ibc_token(&trace),
+        );
+        let key = token::balance_key(&ibc_token, &owner);
+        let minted: token::Amount =
+            tx_host_env::read(&key.to_string()).expect("no balance");
+        assert_eq!(minted, amount);
+        let key = ibc::ibc_denom_key(&ibc_token);
+        let stored: String =
+            tx_host_env::read(&key.to_string()).expect("no denom");
+        assert_eq!(stored, trace);
+        let ibc_vp = ibc::init_ibc_vp_from_tx(&env, &tx);
+        assert!(
+            ibc_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+        let token_vp = ibc::init_token_vp_from_tx(&env, &tx, &ibc_token);
+        assert!(
+            token_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+
+        // Commit
+        env.write_log.commit_tx();
+        env.write_log.commit_block(&mut env.storage).unwrap();
+
+        // Start a transaction to transfer the IBC token on this chain without
+        // any IBC message
+        let receiver = address::testing::established_address_2();
+        let transfer = token::Transfer {
+            source: owner.clone(),
+            target: receiver.clone(),
+            token: ibc_token.clone(),
+            amount,
+        };
+        let tx_data = transfer.try_to_vec().expect("encoding failed");
+        let tx = Tx {
+            code: vec![],
+            data: Some(tx_data.clone()),
+            timestamp: DateTimeUtc::now(),
+        };
+        let source_key = token::balance_key(&ibc_token, &owner);
+        tx_host_env::write(&source_key.to_string(), token::Amount::default());
+        let target_key = token::balance_key(&ibc_token, &receiver);
+        tx_host_env::write(&target_key.to_string(), amount);
+
+        // The IBC token VP should accept the transfer which doesn't change
+        // the supply of the IBC token
+        let token_vp = ibc::init_token_vp_from_tx(&env, &tx, &ibc_token);
+        assert!(
+            token_vp
+                .validate(&tx_data)
+                .expect("validation failed unexpectedly")
+        );
+
+        // Start a transaction to mint the IBC token without any IBC message
+        env.write_log.drop_tx();
+        tx_host_env::write(&target_key.to_string(), amount);
+        let token_vp = ibc::init_token_vp_from_tx(&env, &tx, &ibc_token);
+        // This should fail because the supply of the IBC token is changed
+        assert!(token_vp.validate(&tx_data).is_err());
+    }
 }
@@ -7,7 +7,7 @@ use anoma::ledger::storage::testing::TestStorage;
 use anoma::ledger::storage::write_log::WriteLog;
 use anoma::types::address::Address;
 use anoma::types::storage::Key;
-use anoma::types::{key, token};
+use anoma::types::{key, spending_policy, token};
 use anoma::vm;
 use anoma::vm::prefix_iter::PrefixIterators;
 use anoma_vm_env::tx_prelude::BorshSerialize;
@@ -90,6 +90,30 @@ impl TestTxEnv {
             .write(&storage_key, public_key.try_to_vec().unwrap())
             .unwrap();
     }
+
+    /// Set multi-signature key set for the address.
+    pub fn write_multisig_keys(
+        &mut self,
+        address: &Address,
+        keys: &key::multisig::ThresholdKeys,
+    ) {
+        let storage_key = key::multisig::keys_key(address);
+        self.storage
+            .write(&storage_key, keys.try_to_vec().unwrap())
+            .unwrap();
+    }
+
+    /// Set spending policy for the address.
+    pub fn write_spending_policy(
+        &mut self,
+        address: &Address,
+        policy: &spending_policy::SpendingPolicy,
+    ) {
+        let storage_key = spending_policy::policy_key(address);
+        self.storage
+            .write(&storage_key, policy.try_to_vec().unwrap())
+            .unwrap();
+    }
 }
 
 /// Initialize the host environment inside the [`tx_host_env`] module.
@@ -0,0 +1,46 @@
+//! Governance proposals with functions for transactions
+
+pub use anoma::ledger::governance::*;
+use anoma::ledger::parameters::ProposalVote;
+use anoma::types::address::Address;
+use anoma::types::transaction::governance::InitProposal;
+
+use crate::imports::tx;
+use crate::token::tx::transfer;
+
+/// Submit a new governance proposal and deposit the minimum proposal deposit
+/// from its author. Returns the ID of the proposal.
+pub fn init_proposal(data: InitProposal) -> u64 {
+    let counter_key = counter_key().to_string();
+    let id: u64 = tx::read(&counter_key).unwrap_or_default();
+    let params: GovParams = tx::read(&params_key().to_string())
+        .expect("Governance parameters should always exist in storage");
+
+    let proposal = Proposal {
+        author: data.author.clone(),
+        content: data.content,
+        voting_start_epoch: data.voting_start_epoch,
+        voting_end_epoch: data.voting_end_epoch,
+        grace_epoch: data.grace_epoch,
+    };
+    tx::write(
+        &grace_epoch_index_key(proposal.grace_epoch, id).to_string(),
+        (),
+    );
+    tx::write(&proposal_key(id).to_string(), proposal);
+    if let Some(code) = data.proposal_code {
+        tx::write(&proposal_code_key(id).to_string(), code);
+    }
+    let funds = params.min_proposal_deposit;
+    tx::write(&proposal_funds_key(id).to_string(), funds);
+    transfer(&data.author, &ADDRESS, &deposit_token_address(), funds);
+    tx::write(&counter_key, id + 1);
+    // The author's VP has to authorize the proposal
+    tx::insert_verifier(&data.author);
+    id
+}
+
+/// Vote on a proposal. A vote may be changed within the voting period.
+pub fn vote_proposal(id: u64, voter: &Address, vote: ProposalVote) {
+    tx::write(&proposal_vote_key(id, voter).to_string(), vote);
+}
@@ -0,0 +1,481 @@
+//! IBC functions for transactions. The functions apply the state transitions
+//! of the IBC handlers, which are then checked by the IBC validity predicate.
+
+use anoma::ledger::ibc::storage::{
+    ack_key, capability_index_key, capability_key, channel_counter_key,
+    channel_key, client_counter_key, client_frozen_key, client_state_key,
+    client_type_key, commitment_key, connection_counter_key, connection_key,
+    consensus_state_key, ibc_denom_key, next_sequence_ack_key,
+    next_sequence_recv_key, next_sequence_send_key, port_key, receipt_key,
+};
+use anoma::types::address::{Address, InternalAddress};
+pub use anoma::types::ibc::*;
+use ibc::ics02_client::client_state::AnyClientState;
+use ibc::ics03_connection::connection::{
+    ConnectionEnd, Counterparty as ConnCounterparty,
+};
+use ibc::ics04_channel::channel::{ChannelEnd, Order};
+use ibc::ics04_channel::packet::Packet;
+use ibc::ics24_host::identifier::{
+    ClientId, ConnectionId, PortChannelId, PortId,
+};
+
+use crate::imports::tx;
+use crate::token::tx as token;
+
+/// The acknowledgement written for a received packet on the ports other than
+/// the transfer port
+const PACKET_ACK: &[u8] = b"ack";
+
+/// Apply the state transition requested by the given IBC message
+pub fn dispatch(msg: IbcMessage) -> Result<()> {
+    match msg {
+        IbcMessage::ClientCreation(data) => create_client(data),
+        IbcMessage::ClientUpdate(data) => update_client(data),
+        IbcMessage::ClientUpgrade(data) => upgrade_client(data),
+        IbcMessage::ClientMisbehaviour(data) => freeze_client(data),
+        IbcMessage::ConnectionOpenInit(data) => init_connection(data),
+        IbcMessage::ConnectionOpenTry(data) => try_connection(data),
+        IbcMessage::ConnectionOpenAck(data) => ack_connection(data),
+        IbcMessage::ConnectionOpenConfirm(data) => confirm_connection(data),
+        IbcMessage::ChannelOpenInit(data) => init_channel(data),
+        IbcMessage::ChannelOpenTry(data) => try_channel(data),
+        IbcMessage::ChannelOpenAck(data) => ack_channel(data),
+        IbcMessage::ChannelOpenConfirm(data) => confirm_channel(data),
+        IbcMessage::ChannelCloseInit(data) => close_init_channel(data),
+        IbcMessage::ChannelCloseConfirm(data) => close_confirm_channel(data),
+        IbcMessage::PacketSend(data) => send_packet(data),
+        IbcMessage::PacketReceipt(data) => receive_packet(data),
+        IbcMessage::PacketAck(data) => acknowledge_packet(data),
+        IbcMessage::Timeout(data) => timeout_packet(data),
+    }
+}
+
+/// Create a new client
+pub fn create_client(data: ClientCreationData) -> Result<()> {
+    let counter = increment_counter(&client_counter_key().to_string());
+    let client_id = data.client_id(counter)?;
+
+    let client_type_key = client_type_key(&client_id).to_string();
+    tx::write(&client_type_key, data.client_state.client_type());
+    let height = data.client_state.latest_height();
+    let client_state_key = client_state_key(&client_id).to_string();
+    tx::write(&client_state_key, data.client_state);
+    let consensus_state_key =
+        consensus_state_key(&client_id, height).to_string();
+    tx::write(&consensus_state_key, data.consensus_state);
+    Ok(())
+}
+
+/// Update a client with the given headers
+pub fn update_client(data: ClientUpdateData) -> Result<()> {
+    check_client_not_frozen(&data.client_id)?;
+    let client_state_key = client_state_key(&data.client_id).to_string();
+    let client_state: AnyClientState =
+        tx::read(&client_state_key).ok_or_else(|| {
+            Error::InvalidClient(format!(
+                "The client doesn't exist: ID {}",
+                data.client_id
+            ))
+        })?;
+    let (new_client_state, new_consensus_state) =
+        anoma::types::ibc::update_client(client_state, data.headers)?;
+
+    let height = new_client_state.latest_height();
+    tx::write(&client_state_key, new_client_state);
+    let consensus_state_key =
+        consensus_state_key(&data.client_id, height).to_string();
+    tx::write(&consensus_state_key, new_consensus_state);
+    Ok(())
+}
+
+/// Upgrade a client with the given client and consensus states
+pub fn upgrade_client(data: ClientUpgradeData) -> Result<()> {
+    check_client_not_frozen(&data.client_id)?;
+    let height = data.client_state.latest_height();
+    let client_state_key = client_state_key(&data.client_id).to_string();
+    tx::write(&client_state_key, data.client_state);
+    let consensus_state_key =
+        consensus_state_key(&data.client_id, height).to_string();
+    tx::write(&consensus_state_key, data.consensus_state);
+    Ok(())
+}
+
+/// Freeze a client at the height of the conflicting headers
+pub fn freeze_client(data: ClientMisbehaviourData) -> Result<()> {
+    check_client_not_frozen(&data.client_id)?;
+    let height = data.height()?;
+    let frozen_key = client_frozen_key(&data.client_id).to_string();
+    tx::write(&frozen_key, height);
+    Ok(())
+}
+
+/// Initialize a new connection
+pub fn init_connection(data: ConnectionOpenInitData) -> Result<()> {
+    let counter = increment_counter(&connection_counter_key().to_string());
+    let conn_key = connection_key(&connection_id(counter)).to_string();
+    tx::write(&conn_key, data.connection());
+    Ok(())
+}
+
+/// Try to open a new connection with the counterparty's connection
+pub fn try_connection(data: ConnectionOpenTryData) -> Result<()> {
+    let counter = increment_counter(&connection_counter_key().to_string());
+    let conn_key = connection_key(&connection_id(counter)).to_string();
+    tx::write(&conn_key, data.connection());
+    Ok(())
+}
+
+/// Open the initialized connection with the counterparty's connection
+pub fn ack_connection(data: ConnectionOpenAckData) -> Result<()> {
+    let conn_key = connection_key(&data.conn_id).to_string();
+    let mut conn = read_connection(&conn_key, &data.conn_id)?;
+    let counterparty = ConnCounterparty::new(
+        conn.counterparty().client_id().clone(),
+        Some(data.counterpart_conn_id),
+        conn.counterparty().prefix().clone(),
+    );
+    conn.set_counterparty(counterparty);
+    conn.set_version(data.version);
+    open_connection(&mut conn);
+    tx::write(&conn_key, conn);
+    Ok(())
+}
+
+/// Open the connection which has tried to open
+pub fn confirm_connection(data: ConnectionOpenConfirmData) -> Result<()> {
+    let conn_key = connection_key(&data.conn_id).to_string();
+    let mut conn = read_connection(&conn_key, &data.conn_id)?;
+    open_connection(&mut conn);
+    tx::write(&conn_key, conn);
+    Ok(())
+}
+
+/// Initialize a new channel, binding the port if it hasn't been bound yet
+pub fn init_channel(data: ChannelOpenInitData) -> Result<()> {
+    bind_port(&data.port_id);
+    let counter = increment_counter(&channel_counter_key().to_string());
+    let port_channel_id =
+        port_channel_id(data.port_id.clone(), channel_id(counter));
+    let channel_key = channel_key(&port_channel_id).to_string();
+    tx::write(&channel_key, data.channel());
+    Ok(())
+}
+
+/// Try to open a new channel with the counterparty's channel, binding the
+/// port if it hasn't been bound yet
+pub fn try_channel(data: ChannelOpenTryData) -> Result<()> {
+    bind_port(&data.port_id);
+    let counter = increment_counter(&channel_counter_key().to_string());
+    let port_channel_id =
+        port_channel_id(data.port_id.clone(), channel_id(counter));
+    let channel_key = channel_key(&port_channel_id).to_string();
+    tx::write(&channel_key, data.channel());
+    Ok(())
+}
+
+/// Open the initialized channel with the counterparty's channel
+pub fn ack_channel(data: ChannelOpenAckData) -> Result<()> {
+    let port_channel_id = port_channel_id(data.port_id, data.channel_id);
+    let channel_key = channel_key(&port_channel_id).to_string();
+    let mut channel = read_channel(&channel_key, &port_channel_id)?;
+    channel.set_counterparty_channel_id(data.counterpart_channel_id);
+    channel.set_version(data.counterparty_version);
+    open_channel(&mut channel);
+    tx::write(&channel_key, channel);
+    Ok(())
+}
+
+/// Open the channel which has tried to open
+pub fn confirm_channel(data: ChannelOpenConfirmData) -> Result<()> {
+    let port_channel_id = port_channel_id(data.port_id, data.channel_id);
+    let channel_key = channel_key(&port_channel_id).to_string();
+    let mut channel = read_channel(&channel_key, &port_channel_id)?;
+    open_channel(&mut channel);
+    tx::write(&channel_key, channel);
+    Ok(())
+}
+
+/// Close the opened channel on this chain
+pub fn close_init_channel(data: ChannelCloseInitData) -> Result<()> {
+    close(port_channel_id(data.port_id, data.channel_id))
+}
+
+/// Close the opened channel after the counterparty's channel has been closed
+pub fn close_confirm_channel(data: ChannelCloseConfirmData) -> Result<()> {
+    close(port_channel_id(data.port_id, data.channel_id))
+}
+
+/// Send a packet with the next send sequence and store its commitment
+pub fn send_packet(data: PacketSendData) -> Result<()> {
+    let port_channel_id =
+        port_channel_id(data.source_port.clone(), data.source_channel.clone());
+    let seq_key = next_sequence_send_key(&port_channel_id).to_string();
+    let seq_index = increment_sequence(&seq_key);
+    let packet = data.packet(sequence(seq_index));
+
+    let commitment_key = commitment_key(
+        &packet.source_port,
+        &packet.source_channel,
+        packet.sequence,
+    )
+    .to_string();
+    tx::write(&commitment_key, commitment(&packet));
+
+    if is_transfer_port(&packet.source_port) {
+        let packet_data = FungibleTokenPacketData::decode(&packet.data)?;
+        let action = TransferAction::on_send(
+            &packet_data,
+            &packet.source_port,
+            &packet.source_channel,
+        )?;
+        apply_transfer(action);
+    }
+    Ok(())
+}
+
+/// Receive a packet, storing its receipt and acknowledgement
+pub fn receive_packet(data: PacketReceiptData) -> Result<()> {
+    let packet = data.packet;
+    let port_channel_id = port_channel_id(
+        packet.destination_port.clone(),
+        packet.destination_channel.clone(),
+    );
+    let channel_key = channel_key(&port_channel_id).to_string();
+    let channel = read_channel(&channel_key, &port_channel_id)?;
+    if channel.order_matches(&Order::Ordered) {
+        let seq_key = next_sequence_recv_key(&port_channel_id).to_string();
+        increment_sequence(&seq_key);
+    }
+
+    let receipt_key = receipt_key(
+        &packet.destination_port,
+        &packet.destination_channel,
+        packet.sequence,
+    )
+    .to_string();
+    tx::write(&receipt_key, 0_u64);
+    let ack_key = ack_key(
+        &packet.destination_port,
+        &packet.destination_channel,
+        packet.sequence,
+    )
+    .to_string();
+    let ack = if is_transfer_port(&packet.destination_port) {
+        receive_transfer(&packet).encode()
+    } else {
+        PACKET_ACK.to_vec()
+    };
+    tx::write(&ack_key, ack);
+    Ok(())
+}
+
+/// Acknowledge a sent packet, deleting its commitment
+pub fn acknowledge_packet(data: PacketAckData) -> Result<()> {
+    let packet = data.packet;
+    let commitment_key = commitment_key(
+        &packet.source_port,
+        &packet.source_channel,
+        packet.sequence,
+    )
+    .to_string();
+    tx::delete(&commitment_key);
+
+    if is_transfer_port(&packet.source_port) {
+        let is_success = FungibleTokenAck::decode(&data.ack)
+            .map(|ack| ack.is_success())
+            .unwrap_or(false);
+        if !is_success {
+            apply_transfer(TransferAction::on_refund(&packet)?);
+        }
+    }
+
+    let port_channel_id =
+        port_channel_id(packet.source_port, packet.source_channel);
+    let seq_key = next_sequence_ack_key(&port_channel_id).to_string();
+    increment_sequence(&seq_key);
+    Ok(())
+}
+
+/// Time out a sent packet, closing its channel and deleting its commitment
+pub fn timeout_packet(data: TimeoutData) -> Result<()> {
+    let packet = data.packet;
+    close(port_channel_id(
+        packet.source_port.clone(),
+        packet.source_channel.clone(),
+    ))?;
+
+    let commitment_key = commitment_key(
+        &packet.source_port,
+        &packet.source_channel,
+        packet.sequence,
+    )
+    .to_string();
+    tx::delete(&commitment_key);
+
+    if is_transfer_port(&packet.source_port) {
+        apply_transfer(TransferAction::on_refund(&packet)?);
+    }
+    Ok(())
+}
+
+/// Bind the port to a new capability if it hasn't been bound yet
+fn bind_port(port_id: &PortId) {
+    let port_key = port_key(port_id).to_string();
+    if tx::has_key(&port_key) {
+        return;
+    }
+    let cap_index = increment_counter(&capability_index_key().to_string());
+    tx::write(&port_key, cap_index);
+    let cap_key = capability_key(cap_index).to_string();
+    tx::write(&cap_key, port_id.clone());
+}
+
+fn check_client_not_frozen(client_id: &ClientId) -> Result<()> {
+    let frozen_key = client_frozen_key(client_id).to_string();
+    if tx::has_key(&frozen_key) {
+        return Err(Error::InvalidClient(format!(
+            "The client has been frozen: ID {}",
+            client_id
+        )));
+    }
+    Ok(())
+}
+
+fn is_transfer_port(port_id: &PortId) -> bool {
+    port_id.as_str() == TRANSFER_PORT_ID
+}
+
+/// Receive the tokens of the fungible token transfer packet and return the
+/// acknowledgement. The packet is acknowledged with an error without moving
+/// any token when the transfer can't be applied.
+fn receive_transfer(packet: &Packet) -> FungibleTokenAck {
+    let action = match TransferAction::on_receive(packet) {
+        Ok(action) => action,
+        Err(err) => return FungibleTokenAck::error(err),
+    };
+    let escrow = Address::Internal(InternalAddress::IbcEscrow);
+    match &action {
+        TransferAction::Unescrow { token, amount, .. } => {
+            let key = token::balance_key(token, &escrow).to_string();
+            let balance: token::Amount = tx::read(&key).unwrap_or_default();
+            if balance.checked_sub(amount).is_none() {
+                return FungibleTokenAck::error(format!(
+                    "The escrowed amount is insufficient: Token {}",
+                    token
+                ));
+            }
+        }
+        TransferAction::Mint {
+            denom,
+            owner,
+            amount,
+        } => {
+            let token = Address::Internal(InternalAddress::ibc_token(denom));
+            let key = token::balance_key(&token, owner).to_string();
+            let balance: token::Amount = tx::read(&key).unwrap_or_default();
+            if balance.checked_add(amount).is_none() {
+                return FungibleTokenAck::error(format!(
+                    "The balance overflows: Owner {}",
+                    owner
+                ));
+            }
+        }
+        _ => unreachable!("Receiving a packet only unescrows or mints"),
+    }
+    apply_transfer(action);
+    FungibleTokenAck::success()
+}
+
+/// Move the tokens of the fungible token transfer on this chain
+fn apply_transfer(action: TransferAction) {
+    let escrow = Address::Internal(InternalAddress::IbcEscrow);
+    match action {
+        TransferAction::Escrow {
+            token,
+            owner,
+            amount,
+        } => token::transfer(&owner, &escrow, &token, amount),
+        TransferAction::Unescrow {
+            token,
+            owner,
+            amount,
+        } => token::transfer(&escrow, &owner, &token, amount),
+        TransferAction::Mint {
+            denom,
+            owner,
+            amount,
+        } => {
+            let token = Address::Internal(InternalAddress::ibc_token(&denom));
+            let key = token::balance_key(&token, &owner).to_string();
+            let mut balance: token::Amount = tx::read(&key).unwrap_or_default();
+            if let Err(err) = balance.receive(&amount) {
+                tx::log_string(format!("owner {} balance: {}", owner, err));
+                unreachable!()
+            }
+            tx::write(&key, balance);
+            tx::write(&ibc_denom_key(&token).to_string(), denom);
+        }
+        TransferAction::Burn {
+            token,
+            owner,
+            amount,
+        } => {
+            let key = token::balance_key(&token, &owner).to_string();
+            let mut balance: token::Amount = tx::read(&key).unwrap_or_default();
+            if let Err(err) = balance.spend(&amount) {
+                tx::log_string(format!("owner {} balance: {}", owner, err));
+                unreachable!()
+            }
+            tx::write(&key, balance);
+        }
+    }
+}
+
+fn close(port_channel_id: PortChannelId) -> Result<()> {
+    let channel_key = channel_key(&port_channel_id).to_string();
+    let mut channel = read_channel(&channel_key, &port_channel_id)?;
+    close_channel(&mut channel);
+    tx::write(&channel_key, channel);
+    Ok(())
+}
+
+fn read_connection(
+    conn_key: &str,
+    conn_id: &ConnectionId,
+) -> Result<ConnectionEnd> {
+    tx::read(conn_key).ok_or_else(|| {
+        Error::InvalidConnection(format!(
+            "The connection doesn't exist: ID {}",
+            conn_id
+        ))
+    })
+}
+
+fn read_channel(
+    channel_key: &str,
+    port_channel_id: &PortChannelId,
+) -> Result<ChannelEnd> {
+    tx::read(channel_key).ok_or_else(|| {
+        Error::InvalidChannel(format!(
+            "The channel doesn't exist: Port/Channel {}",
+            port_channel_id
+        ))
+    })
+}
+
+/// Increment the counter and return the value before the increment. The
+/// counters are initialized in the genesis.
+fn increment_counter(key: &str) -> u64 {
+    let counter: u64 = tx::read(key).unwrap_or_default();
+    tx::write(key, counter + 1);
+    counter
+}
+
+/// Increment the sequence and return the value before the increment. The
+/// sequences start from 1.
+fn increment_sequence(key: &str) -> u64 {
+    let seq_index: u64 = tx::read(key).unwrap_or(1);
+    tx::write(key, seq_index + 1);
+    seq_index
+}
@@ -0,0 +1,36 @@
+use anoma::types::address::Address;
+use anoma::types::key::multisig;
+
+/// Vp imports and functions.
+pub mod vp {
+    use std::collections::BTreeMap;
+
+    use anoma::types::key::ed25519::Signature;
+    pub use anoma::types::key::multisig::*;
+
+    use super::*;
+    use crate::imports::vp;
+
+    /// Get the multi-signature key set associated with the given address in
+    /// the prior state, if any.
+    pub fn get(owner: &Address) -> Option<ThresholdKeys> {
+        let key = multisig::keys_key(owner).to_string();
+        vp::read_pre(&key)
+    }
+
+    /// Get the multi-signature key set associated with the given address in
+    /// the posterior state, if any.
+    pub fn get_post(owner: &Address) -> Option<ThresholdKeys> {
+        let key = multisig::keys_key(owner).to_string();
+        vp::read_post(&key)
+    }
+
+    /// Check that the transaction carries at least `threshold` valid
+    /// signatures from the given key set.
+    pub fn verify_tx_signatures(
+        keys: &ThresholdKeys,
+        sigs: &BTreeMap<u8, Signature>,
+    ) -> bool {
+        keys.is_satisfied_by(sigs, vp::verify_tx_signature)
+    }
+}
@@ -6,21 +6,33 @@
 #![deny(rustdoc::broken_intra_doc_links)]
 #![deny(rustdoc::private_intra_doc_links)]
 
+pub mod governance;
+#[cfg(feature = "ibc-tx")]
+pub mod ibc;
 pub mod imports;
 pub mod intent;
 pub mod key;
+pub mod parameters;
 pub mod proof_of_stake;
+pub mod spending_policy;
 pub mod token;
+pub mod treasury;
+pub mod upgrade;
 
 pub mod tx_prelude {
     pub use anoma::types::address::Address;
     pub use anoma::types::*;
     pub use anoma_vm_macro::transaction;
 
+    pub use crate::governance;
     pub use crate::imports::tx::*;
     pub use crate::intent::tx as intent;
+    pub use crate::parameters;
     pub use crate::proof_of_stake::{self, PoS, PosRead, PosWrite};
+    pub use crate::spending_policy::tx as spending_policy;
     pub use crate::token::tx as token;
+    pub use crate::treasury;
+    pub use crate::upgrade;
 }
 
 pub mod vp_prelude {
@@ -35,9 +47,11 @@ pub mod vp_prelude {
     pub use crate::intent::vp as intent;
     pub mod key {
         pub use crate::key::ed25519::vp as ed25519;
+        pub use crate::key::multisig::vp as multisig;
     }
     pub use anoma::ledger::pos as proof_of_stake;
 
+    pub use crate::spending_policy::vp as spending_policy;
     pub use crate::token::vp as token;
 }
 
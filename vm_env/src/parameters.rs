@@ -0,0 +1,41 @@
+//! Protocol parameters proposals with functions for transactions
+
+pub use anoma::ledger::parameters::*;
+use anoma::types::address::Address;
+
+use crate::imports::tx;
+
+/// Submit a new proposal to change the protocol parameters. Returns the ID of
+/// the proposal.
+pub fn submit_proposal(proposal: &ParametersProposal) -> u64 {
+    let counter_key = proposal_counter_key().to_string();
+    let id: u64 = tx::read(&counter_key).unwrap_or_default();
+    tx::write(&proposal_key(id).to_string(), proposal);
+    tx::write(&counter_key, id + 1);
+    // The author's VP has to authorize the proposal
+    tx::insert_verifier(&proposal.author);
+    id
+}
+
+/// Vote on a proposal. A vote may be changed within the voting period.
+pub fn vote_proposal(id: u64, voter: &Address, vote: ProposalVote) {
+    tx::write(&proposal_vote_key(id, voter).to_string(), vote);
+}
+
+/// Apply a passed proposal by writing its parameters. Returns `false` if the
+/// proposal doesn't exist.
+pub fn apply_proposal(id: u64) -> bool {
+    let proposal: Option<ParametersProposal> =
+        tx::read(&proposal_key(id).to_string());
+    match proposal {
+        Some(proposal) => {
+            tx::write(&storage_key().to_string(), &proposal.parameters);
+            tx::write(
+                &proposal_applied_key(id).to_string(),
+                tx::get_block_epoch(),
+            );
+            true
+        }
+        None => false,
+    }
+}
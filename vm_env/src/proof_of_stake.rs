@@ -1,18 +1,23 @@
 //! Proof of Stake system integration with functions for transactions
 
 use anoma::ledger::pos::anoma_proof_of_stake::{
-    BecomeValidatorError, BondError, UnbondError, WithdrawError,
+    BecomeValidatorError, BondError, ClaimRewardsError,
+    CommissionRateChangeError, ConsensusKeyChangeError, MetadataUpdateError,
+    RedelegationError, UnbondError, UnjailError, WithdrawError,
 };
-use anoma::ledger::pos::types::Slash;
+use anoma::ledger::pos::types::{BasisPoints, Slash, ValidatorMetadata};
 pub use anoma::ledger::pos::*;
 use anoma::ledger::pos::{
-    anoma_proof_of_stake, bond_key, params_key, total_voting_power_key,
-    unbond_key, validator_address_raw_hash_key, validator_consensus_key_key,
-    validator_set_key, validator_slashes_key,
+    anoma_proof_of_stake, bond_key, bond_rewards_key, consensus_key_owner_key,
+    params_key, redelegation_key, total_voting_power_key, unbond_key,
+    validator_address_raw_hash_key, validator_commission_rate_key,
+    validator_consensus_key_key, validator_jailed_epoch_key,
+    validator_metadata_key, validator_set_key, validator_slashes_key,
     validator_staking_reward_address_key, validator_state_key,
-    validator_total_deltas_key, validator_voting_power_key,
+    validator_total_deltas_key, validator_voting_power_key, withdrawal_key,
 };
 use anoma::types::address::{self, Address, InternalAddress};
+use anoma::types::transaction::pos::MetadataChange;
 use anoma::types::transaction::InitValidator;
 use anoma::types::{key, token};
 pub use anoma_proof_of_stake::{
@@ -56,6 +61,92 @@ pub fn withdraw_tokens(
     PoS.withdraw_tokens(source, validator, current_epoch)
 }
 
+/// Redelegate tokens delegated from the `source` to the `src_validator` to
+/// the `dest_validator`, without having to unbond and withdraw them first.
+pub fn redelegate_tokens(
+    source: &Address,
+    src_validator: &Address,
+    dest_validator: &Address,
+    amount: token::Amount,
+) -> Result<(), RedelegationError<Address, token::Amount>> {
+    let current_epoch = tx::get_block_epoch();
+    PoS.redelegate_tokens(
+        source,
+        src_validator,
+        dest_validator,
+        amount,
+        current_epoch,
+    )
+}
+
+/// Change a validator's commission rate. The new rate takes effect at the
+/// pipeline offset from the current epoch.
+pub fn change_commission_rate(
+    validator: &Address,
+    rate: BasisPoints,
+) -> Result<(), CommissionRateChangeError<Address>> {
+    let current_epoch = tx::get_block_epoch();
+    PoS.change_commission_rate(validator, rate, current_epoch)
+}
+
+/// Change a validator's consensus key. The new key takes effect at the
+/// pipeline offset from the current epoch.
+pub fn change_consensus_key(
+    validator: &Address,
+    consensus_key: &key::ed25519::PublicKey,
+) -> Result<(), ConsensusKeyChangeError<Address>> {
+    let current_epoch = tx::get_block_epoch();
+    PoS.change_consensus_key(validator, consensus_key, current_epoch)
+}
+
+/// Claim the rewards that have accrued to a delegation from the `source` to
+/// the `validator`.
+pub fn claim_rewards(
+    source: &Address,
+    validator: &Address,
+) -> Result<token::Amount, ClaimRewardsError<Address>> {
+    PoS.claim_rewards(source, validator)
+}
+
+/// Unjail a validator that has been jailed for at least the minimum jail
+/// duration. The validator is added back to the validator set at the pipeline
+/// offset from the current epoch.
+pub fn unjail_validator(
+    validator: &Address,
+) -> Result<(), UnjailError<Address>> {
+    let current_epoch = tx::get_block_epoch();
+    PoS.unjail_validator(validator, current_epoch)
+}
+
+/// Update a validator's metadata. The fields of the change that are `None`
+/// are left unchanged and the fields set to an empty string are cleared.
+pub fn update_validator_metadata(
+    MetadataChange {
+        validator,
+        moniker,
+        description,
+        website,
+        contact,
+    }: MetadataChange,
+) -> Result<(), MetadataUpdateError<Address>> {
+    let mut metadata =
+        PoS.read_validator_metadata(&validator).unwrap_or_default();
+    let apply = |field: &mut Option<String>, change: Option<String>| {
+        if let Some(change) = change {
+            *field = if change.is_empty() {
+                None
+            } else {
+                Some(change)
+            };
+        }
+    };
+    apply(&mut metadata.moniker, moniker);
+    apply(&mut metadata.description, description);
+    apply(&mut metadata.website, website);
+    apply(&mut metadata.contact, contact);
+    PoS.update_validator_metadata(&validator, metadata)
+}
+
 /// Attempt to initialize a validator account. On success, returns the
 /// initialized validator account's address and its staking reward address.
 pub fn init_validator(
@@ -65,6 +156,11 @@ pub fn init_validator(
         rewards_account_key,
         validator_vp_code,
         rewards_vp_code,
+        commission_rate,
+        moniker,
+        description,
+        website,
+        contact,
     }: InitValidator,
 ) -> Result<(Address, Address), BecomeValidatorError<Address>> {
     let current_epoch = tx::get_block_epoch();
@@ -82,6 +178,13 @@ pub fn init_validator(
         &validator_address,
         &rewards_address,
         &consensus_key,
+        BasisPoints::new(commission_rate),
+        ValidatorMetadata {
+            moniker,
+            description,
+            website,
+            contact,
+        },
         current_epoch,
     )?;
     Ok((validator_address, rewards_address))
@@ -98,6 +201,8 @@ impl anoma_proof_of_stake::PosReadOnly for PoS {
     type TokenChange = token::Change;
 
     const POS_ADDRESS: Self::Address = Address::Internal(InternalAddress::PoS);
+    const POS_SLASH_POOL_ADDRESS: Self::Address =
+        Address::Internal(InternalAddress::PosSlashPool);
 
     fn staking_token_address() -> Self::Address {
         address::xan()
@@ -121,6 +226,13 @@ impl anoma_proof_of_stake::PosReadOnly for PoS {
         tx::read(validator_consensus_key_key(key).to_string())
     }
 
+    fn read_consensus_key_owner(
+        &self,
+        key: &Self::PublicKey,
+    ) -> Option<ConsensusKeyOwner> {
+        tx::read(consensus_key_owner_key(key.tm_raw_hash()).to_string())
+    }
+
     fn read_validator_state(
         &self,
         key: &Self::Address,
@@ -142,6 +254,27 @@ impl anoma_proof_of_stake::PosReadOnly for PoS {
         tx::read(validator_voting_power_key(key).to_string())
     }
 
+    fn read_validator_commission_rate(
+        &self,
+        key: &Self::Address,
+    ) -> Option<ValidatorCommissionRates> {
+        tx::read(validator_commission_rate_key(key).to_string())
+    }
+
+    fn read_validator_jailed_epoch(
+        &self,
+        key: &Self::Address,
+    ) -> Option<types::Epoch> {
+        tx::read(validator_jailed_epoch_key(key).to_string())
+    }
+
+    fn read_validator_metadata(
+        &self,
+        key: &Self::Address,
+    ) -> Option<ValidatorMetadata> {
+        tx::read(validator_metadata_key(key).to_string())
+    }
+
     fn read_validator_slashes(&self, key: &Self::Address) -> Vec<Slash> {
         tx::read(validator_slashes_key(key).to_string()).unwrap_or_default()
     }
@@ -154,6 +287,18 @@ impl anoma_proof_of_stake::PosReadOnly for PoS {
         tx::read(unbond_key(key).to_string())
     }
 
+    fn read_redelegations(&self, key: &BondId) -> Option<Redelegations> {
+        tx::read(redelegation_key(key).to_string())
+    }
+
+    fn read_withdrawals(&self, key: &BondId) -> Option<Withdrawals> {
+        tx::read(withdrawal_key(key).to_string())
+    }
+
+    fn read_bond_rewards(&self, key: &BondId) -> Option<BondRewards> {
+        tx::read(bond_rewards_key(key).to_string())
+    }
+
     fn read_validator_set(&self) -> ValidatorSets {
         tx::read(validator_set_key().to_string()).unwrap()
     }
@@ -195,6 +340,17 @@ impl anoma_proof_of_stake::PosActions for PoS {
         tx::write(validator_consensus_key_key(key).to_string(), &value)
     }
 
+    fn write_consensus_key_owner(
+        &mut self,
+        key: &Self::PublicKey,
+        value: ConsensusKeyOwner,
+    ) {
+        tx::write(
+            consensus_key_owner_key(key.tm_raw_hash()).to_string(),
+            &value,
+        )
+    }
+
     fn write_validator_state(
         &mut self,
         key: &Self::Address,
@@ -219,6 +375,22 @@ impl anoma_proof_of_stake::PosActions for PoS {
         tx::write(validator_voting_power_key(key).to_string(), &value)
     }
 
+    fn write_validator_commission_rate(
+        &mut self,
+        key: &Self::Address,
+        value: ValidatorCommissionRates,
+    ) {
+        tx::write(validator_commission_rate_key(key).to_string(), &value)
+    }
+
+    fn write_validator_metadata(
+        &mut self,
+        key: &Self::Address,
+        value: ValidatorMetadata,
+    ) {
+        tx::write(validator_metadata_key(key).to_string(), &value)
+    }
+
     fn write_bond(&mut self, key: &BondId, value: Bonds) {
         tx::write(bond_key(key).to_string(), &value)
     }
@@ -227,6 +399,18 @@ impl anoma_proof_of_stake::PosActions for PoS {
         tx::write(unbond_key(key).to_string(), &value)
     }
 
+    fn write_redelegations(&mut self, key: &BondId, value: Redelegations) {
+        tx::write(redelegation_key(key).to_string(), &value)
+    }
+
+    fn write_withdrawals(&mut self, key: &BondId, value: Withdrawals) {
+        tx::write(withdrawal_key(key).to_string(), &value)
+    }
+
+    fn write_bond_rewards(&mut self, key: &BondId, value: BondRewards) {
+        tx::write(bond_rewards_key(key).to_string(), &value)
+    }
+
     fn write_validator_set(&mut self, value: ValidatorSets) {
         tx::write(validator_set_key().to_string(), &value)
     }
@@ -243,6 +427,22 @@ impl anoma_proof_of_stake::PosActions for PoS {
         tx::delete(unbond_key(key).to_string())
     }
 
+    fn delete_redelegations(&mut self, key: &BondId) {
+        tx::delete(redelegation_key(key).to_string())
+    }
+
+    fn delete_withdrawals(&mut self, key: &BondId) {
+        tx::delete(withdrawal_key(key).to_string())
+    }
+
+    fn delete_validator_jailed_epoch(&mut self, key: &Self::Address) {
+        tx::delete(validator_jailed_epoch_key(key).to_string())
+    }
+
+    fn delete_consensus_key_owner(&mut self, key: &Self::PublicKey) {
+        tx::delete(consensus_key_owner_key(key.tm_raw_hash()).to_string())
+    }
+
     fn transfer(
         &mut self,
         token: &Self::Address,
@@ -0,0 +1,75 @@
+use anoma::types::address::Address;
+use anoma::types::spending_policy;
+use anoma::types::token;
+
+/// Vp imports and functions.
+pub mod vp {
+    pub use anoma::types::spending_policy::*;
+
+    use super::*;
+    use crate::imports::vp;
+
+    /// Get the spending policy of the given address in the prior state, if
+    /// any.
+    pub fn get(owner: &Address) -> Option<SpendingPolicy> {
+        let key = spending_policy::policy_key(owner).to_string();
+        vp::read_pre(&key)
+    }
+
+    /// Get the outflow of the given token from the given address in the
+    /// prior state, if any.
+    pub fn get_spent_pre(owner: &Address, token: &Address) -> Option<Spent> {
+        let key = spending_policy::spent_key(owner, token).to_string();
+        vp::read_pre(&key)
+    }
+
+    /// Get the outflow of the given token from the given address in the
+    /// posterior state, if any.
+    pub fn get_spent_post(owner: &Address, token: &Address) -> Option<Spent> {
+        let key = spending_policy::spent_key(owner, token).to_string();
+        vp::read_post(&key)
+    }
+}
+
+/// Tx imports and functions.
+pub mod tx {
+    pub use anoma::types::spending_policy::*;
+
+    use super::*;
+    use crate::imports::tx;
+
+    /// Record an outflow of tokens from an account that has a spending policy,
+    /// so that its VP can check it against the policy.
+    pub fn record_outflow(
+        owner: &Address,
+        token: &Address,
+        amount: token::Amount,
+    ) {
+        let policy_key = spending_policy::policy_key(owner).to_string();
+        let policy: Option<SpendingPolicy> = tx::read(&policy_key);
+        if let Some(policy) = policy {
+            // The owner's VP has to be able to see the recipients
+            tx::insert_verifier(owner);
+            if policy.limit(token).is_some() {
+                let key = spending_policy::spent_key(owner, token).to_string();
+                match Spent::record(
+                    tx::read(&key),
+                    tx::get_block_epoch(),
+                    amount,
+                ) {
+                    Ok(spent) => tx::write(&key, spent),
+                    Err(err) => {
+                        let msg = format!(
+                            "outflow of {} cannot be recorded: {}",
+                            owner, err
+                        );
+                        tx::log_string(&msg);
+                        // Abort the transaction, the outflow must not
+                        // bypass the policy
+                        panic!("{}", msg)
+                    }
+                }
+            }
+        }
+    }
+}
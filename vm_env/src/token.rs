@@ -49,6 +49,7 @@ pub mod tx {
 
     use super::*;
     use crate::imports::tx;
+    use crate::spending_policy::tx as spending_policy;
 
     /// A token transfer that can be used in a transaction.
     pub fn transfer(
@@ -66,12 +67,19 @@ pub mod tx {
                 unreachable!()
             }
             Some(mut src_bal) => {
-                src_bal.spend(&amount);
+                if let Err(err) = src_bal.spend(&amount) {
+                    tx::log_string(format!("src {} balance: {}", src, err));
+                    unreachable!()
+                }
                 let mut dest_bal: Amount =
                     tx::read(&dest_key.to_string()).unwrap_or_default();
-                dest_bal.receive(&amount);
+                if let Err(err) = dest_bal.receive(&amount) {
+                    tx::log_string(format!("dest {} balance: {}", dest, err));
+                    unreachable!()
+                }
                 tx::write(&src_key.to_string(), src_bal);
                 tx::write(&dest_key.to_string(), dest_bal);
+                spending_policy::record_outflow(src, token, amount);
             }
         }
     }
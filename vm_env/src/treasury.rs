@@ -0,0 +1,13 @@
+//! Treasury with functions for transactions
+
+pub use anoma::ledger::treasury::*;
+use anoma::types::address::Address;
+use anoma::types::token::Amount;
+
+use crate::token::tx::transfer;
+
+/// Spend tokens from the treasury. This is only accepted by the treasury VP
+/// in the code of a passed governance proposal.
+pub fn spend(target: &Address, token: &Address, amount: Amount) {
+    transfer(&ADDRESS, target, token, amount);
+}
@@ -0,0 +1,17 @@
+//! Protocol upgrades with functions for transactions
+
+pub use anoma::ledger::upgrade::*;
+use anoma::types::storage::BlockHeight;
+
+use crate::imports::tx;
+
+/// Schedule a protocol upgrade with the given name at the given block height.
+/// This is only accepted by the parameters VP in the code of a passed
+/// governance proposal.
+pub fn schedule(name: impl AsRef<str>, height: BlockHeight) {
+    let plan = UpgradePlan {
+        name: name.as_ref().to_owned(),
+        height,
+    };
+    tx::write(&plan_key().to_string(), plan);
+}
@@ -7,6 +7,9 @@ pub mod vp_user;
 #[cfg(feature = "vp_testnet_faucet")]
 pub mod vp_testnet_faucet;
 
+#[cfg(feature = "vp_multisig")]
+pub mod vp_multisig;
+
 /// A tx to initialize a new established address with a given public key and
 /// a validity predicate.
 #[cfg(feature = "tx_init_account")]
@@ -30,6 +33,36 @@ pub mod tx_init_account {
     }
 }
 
+/// A tx to initialize a new established address with a given multi-signature
+/// key set and a validity predicate.
+#[cfg(feature = "tx_init_multisig_account")]
+pub mod tx_init_multisig_account {
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let tx_data = transaction::InitMultisigAccount::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+        log_string(
+            "apply_tx called to init a new established multi-signature \
+             account"
+                .to_string(),
+        );
+
+        if let Err(err) = tx_data.keys.validate() {
+            log_string(format!("Invalid multi-signature key set: {}", err));
+            panic!()
+        }
+        let address = init_account(&tx_data.vp_code);
+        let keys_key = key::multisig::keys_key(&address);
+        write(&keys_key.to_string(), &tx_data.keys);
+    }
+}
+
 /// A tx to initialize a new validator account and staking reward account with a
 /// given public keys and a validity predicates.
 #[cfg(feature = "tx_init_validator")]
@@ -232,6 +265,264 @@ pub mod tx_withdraw {
     }
 }
 
+/// A tx for a PoS redelegation that moves delegated tokens from one validator
+/// to another at the pipeline offset, without unbonding them.
+#[cfg(feature = "tx_redelegate")]
+pub mod tx_redelegate {
+    use anoma_vm_env::tx_prelude::proof_of_stake::redelegate_tokens;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let redelegation = transaction::pos::Redelegation::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+
+        if let Err(err) = redelegate_tokens(
+            &redelegation.source,
+            &redelegation.src_validator,
+            &redelegation.dest_validator,
+            redelegation.amount,
+        ) {
+            log_string(format!("Redelegation failed with: {}", err));
+            panic!()
+        }
+    }
+}
+
+/// A tx for a validator to change its commission rate. The new rate takes
+/// effect at the pipeline offset.
+#[cfg(feature = "tx_change_commission")]
+pub mod tx_change_commission {
+    use anoma_vm_env::tx_prelude::proof_of_stake::{
+        change_commission_rate, BasisPoints,
+    };
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let change = transaction::pos::CommissionChange::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+
+        if let Err(err) = change_commission_rate(
+            &change.validator,
+            BasisPoints::new(change.rate),
+        ) {
+            log_string(format!("Commission rate change failed with: {}", err));
+            panic!()
+        }
+    }
+}
+
+/// A tx for a delegator to claim the rewards that have accrued to their
+/// delegation.
+#[cfg(feature = "tx_claim_rewards")]
+pub mod tx_claim_rewards {
+    use anoma_vm_env::tx_prelude::proof_of_stake::claim_rewards;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let claim = transaction::pos::ClaimRewards::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+
+        match claim_rewards(&claim.source, &claim.validator) {
+            Ok(claimed) => {
+                log_string(format!("Claimed rewards {}", claimed));
+            }
+            Err(err) => {
+                log_string(format!("Claiming rewards failed with: {}", err));
+                panic!()
+            }
+        }
+    }
+}
+
+/// A tx for a jailed validator to request to be unjailed. The validator is
+/// added back to the validator set at the pipeline offset.
+#[cfg(feature = "tx_unjail")]
+pub mod tx_unjail {
+    use anoma_vm_env::tx_prelude::proof_of_stake::unjail_validator;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let unjail =
+            transaction::pos::Unjail::try_from_slice(&signed.data.unwrap()[..])
+                .unwrap();
+
+        if let Err(err) = unjail_validator(&unjail.validator) {
+            log_string(format!("Unjailing failed with: {}", err));
+            panic!()
+        }
+    }
+}
+
+/// A tx for a validator to change its consensus key. The new key takes effect
+/// at the pipeline offset.
+#[cfg(feature = "tx_change_consensus_key")]
+pub mod tx_change_consensus_key {
+    use anoma_vm_env::tx_prelude::proof_of_stake::change_consensus_key;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let change = transaction::pos::ConsensusKeyChange::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+
+        if let Err(err) =
+            change_consensus_key(&change.validator, &change.consensus_key)
+        {
+            log_string(format!("Consensus key change failed with: {}", err));
+            panic!()
+        }
+    }
+}
+
+/// A tx for a validator to update its metadata.
+#[cfg(feature = "tx_update_validator_metadata")]
+pub mod tx_update_validator_metadata {
+    use anoma_vm_env::tx_prelude::proof_of_stake::update_validator_metadata;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let change = transaction::pos::MetadataChange::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+
+        if let Err(err) = update_validator_metadata(change) {
+            log_string(format!("Metadata update failed with: {}", err));
+            panic!()
+        }
+    }
+}
+
+/// A tx to submit a proposal to change the protocol parameters.
+#[cfg(feature = "tx_propose_parameters")]
+pub mod tx_propose_parameters {
+    use anoma_vm_env::tx_prelude::parameters::{
+        submit_proposal, ParametersProposal,
+    };
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let proposal =
+            ParametersProposal::try_from_slice(&signed.data.unwrap()[..])
+                .unwrap();
+
+        let id = submit_proposal(&proposal);
+        log_string(format!("Submitted parameters proposal {}", id));
+    }
+}
+
+/// A tx to vote on a protocol parameters proposal.
+#[cfg(feature = "tx_vote_parameters")]
+pub mod tx_vote_parameters {
+    use anoma_vm_env::tx_prelude::parameters::vote_proposal;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let vote = transaction::parameters::VoteProposal::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+
+        vote_proposal(vote.id, &vote.voter, vote.vote);
+    }
+}
+
+/// A tx to apply a passed protocol parameters proposal. The tx doesn't have to
+/// be signed, because anyone can apply a passed proposal.
+#[cfg(feature = "tx_apply_parameters")]
+pub mod tx_apply_parameters {
+    use anoma_vm_env::tx_prelude::parameters::apply_proposal;
+    use anoma_vm_env::tx_prelude::transaction::parameters::ApplyProposal;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let data =
+            match key::ed25519::SignedTxData::try_from_slice(&tx_data[..]) {
+                Ok(signed) => signed.data.unwrap(),
+                Err(_) => tx_data,
+            };
+        let apply = ApplyProposal::try_from_slice(&data[..]).unwrap();
+
+        if !apply_proposal(apply.id) {
+            log_string(format!(
+                "Parameters proposal {} doesn't exist",
+                apply.id
+            ));
+            panic!()
+        }
+    }
+}
+
+/// A tx to submit a governance proposal.
+#[cfg(feature = "tx_init_proposal")]
+pub mod tx_init_proposal {
+    use anoma_vm_env::tx_prelude::governance::init_proposal;
+    use anoma_vm_env::tx_prelude::transaction::governance::InitProposal;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let data =
+            InitProposal::try_from_slice(&signed.data.unwrap()[..]).unwrap();
+
+        let id = init_proposal(data);
+        log_string(format!("Submitted governance proposal {}", id));
+    }
+}
+
+/// A tx to vote on a governance proposal.
+#[cfg(feature = "tx_vote_proposal")]
+pub mod tx_vote_proposal {
+    use anoma_vm_env::tx_prelude::governance::vote_proposal;
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let vote = transaction::governance::VoteProposal::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+
+        vote_proposal(vote.id, &vote.voter, vote.vote);
+    }
+}
+
 /// A tx for a token transfer crafted by matchmaker from intents.
 /// This tx uses `intent::IntentTransfers` wrapped inside
 /// `key::ed25519::SignedTxData` as its input as declared in `shared` crate.
@@ -314,6 +605,69 @@ pub mod tx_update_vp {
     }
 }
 
+/// A tx for updating an account's public key. The new key is written into the
+/// account's storage, where it replaces the old key.
+#[cfg(feature = "tx_update_key")]
+pub mod tx_update_key {
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let update_key =
+            transaction::UpdateKey::try_from_slice(&signed.data.unwrap()[..])
+                .unwrap();
+        log_string(format!("update public key for: {:#?}", update_key.addr));
+        let pk_key = key::ed25519::pk_key(&update_key.addr);
+        write(&pk_key.to_string(), &update_key.public_key);
+    }
+}
+
+/// A tx for setting or removing an account's spending policy.
+#[cfg(feature = "tx_update_spending_policy")]
+pub mod tx_update_spending_policy {
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let signed =
+            key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
+        let update = transaction::UpdateSpendingPolicy::try_from_slice(
+            &signed.data.unwrap()[..],
+        )
+        .unwrap();
+        log_string(format!("update spending policy for: {:#?}", update.addr));
+        let policy_key = spending_policy::policy_key(&update.addr);
+        match update.policy {
+            Some(policy) => write(&policy_key.to_string(), &policy),
+            None => delete(&policy_key.to_string()),
+        }
+    }
+}
+
+/// A tx for IBC handshakes and packets. The tx data is an encoded IBC message
+/// submitted by a relayer and it's not signed, because the IBC validity
+/// predicate verifies the proofs of the counterparty chain instead. A token
+/// owner sending a fungible token transfer packet has to sign the message so
+/// that the owner's VP accepts the debit.
+#[cfg(feature = "tx_ibc")]
+pub mod tx_ibc {
+    use anoma_vm_env::ibc::{dispatch, IbcMessage};
+    use anoma_vm_env::tx_prelude::*;
+
+    #[transaction]
+    fn apply_tx(tx_data: Vec<u8>) {
+        let msg = IbcMessage::decode(&tx_data[..]).unwrap();
+        log_string(format!("apply_tx called with IBC message: {:#?}", msg));
+
+        if let Err(err) = dispatch(msg) {
+            log_string(format!("IBC message failed with: {}", err));
+            panic!()
+        }
+    }
+}
+
 /// A VP for a token.
 #[cfg(feature = "vp_token")]
 pub mod vp_token {
@@ -0,0 +1,316 @@
+//! A multi-signature account VP.
+//!
+//! This VP requires a threshold of valid signatures from the account's key set
+//! (see [`key::multisig::ThresholdKeys`]) for sending tokens (receiving tokens
+//! is permissive).
+//!
+//! It allows to bond, unbond and withdraw tokens to and from PoS system with a
+//! threshold of valid signatures.
+//!
+//! The key set can be replaced with a threshold of valid signatures from the
+//! current key set, as long as the new key set is valid. It cannot be deleted.
+//!
+//! Any other storage key changes are allowed only with a threshold of valid
+//! signatures.
+
+use anoma_vm_env::vp_prelude::key::ed25519::SignedTxData;
+use anoma_vm_env::vp_prelude::*;
+use once_cell::unsync::Lazy;
+
+#[validity_predicate]
+fn validate_tx(
+    tx_data: Vec<u8>,
+    addr: Address,
+    keys_changed: HashSet<storage::Key>,
+    verifiers: HashSet<Address>,
+) -> bool {
+    log_string(format!(
+        "vp_multisig called with user addr: {}, key_changed: {:?}, \
+         verifiers: {:?}",
+        addr, keys_changed, verifiers
+    ));
+
+    let signed_tx_data =
+        Lazy::new(|| SignedTxData::try_from_slice(&tx_data[..]));
+
+    let valid_sig = Lazy::new(|| match &*signed_tx_data {
+        Ok(signed_tx_data) => match key::multisig::get(&addr) {
+            Some(keys) => {
+                key::multisig::verify_tx_signatures(&keys, &signed_tx_data.sigs)
+            }
+            None => false,
+        },
+        _ => false,
+    });
+
+    for key in keys_changed.iter() {
+        let is_valid = if let Some(owner) = token::is_any_token_balance_key(key)
+        {
+            if owner == &addr {
+                let key = key.to_string();
+                let pre: token::Amount = read_pre(&key).unwrap_or_default();
+                let post: token::Amount = read_post(&key).unwrap_or_default();
+                let change = post.change() - pre.change();
+                // debit has to signed, credit doesn't
+                let valid = change >= 0 || *valid_sig;
+                log_string(format!(
+                    "token key: {}, change: {}, valid_sig: {}, valid \
+                     modification: {}",
+                    key, change, *valid_sig, valid
+                ));
+                valid
+            } else {
+                log_string(format!(
+                    "This address ({}) is not of owner ({}) of token key: {}",
+                    addr, owner, key
+                ));
+                // If this is not the owner, allow any change
+                true
+            }
+        } else if proof_of_stake::is_pos_key(key) {
+            // Allow the account to be used in PoS
+            let bond_id = proof_of_stake::is_bond_key(key)
+                .or_else(|| proof_of_stake::is_unbond_key(key))
+                .or_else(|| proof_of_stake::is_redelegation_key(key))
+                .or_else(|| proof_of_stake::is_withdrawal_key(key))
+                .or_else(|| proof_of_stake::is_bond_rewards_key(key));
+            let valid = match bond_id {
+                Some(bond_id) => {
+                    // Bonds, unbonds, redelegations, withdrawals and rewards
+                    // changes for this address must be signed
+                    bond_id.source != addr || *valid_sig
+                }
+                None => {
+                    match proof_of_stake::is_validator_commission_rate_key(key)
+                        .or_else(|| {
+                            proof_of_stake::is_validator_jailed_epoch_key(key)
+                        })
+                        .or_else(|| {
+                            proof_of_stake::is_validator_metadata_key(key)
+                        })
+                        .or_else(|| {
+                            proof_of_stake::is_validator_consensus_key_key(key)
+                        }) {
+                        // Commission rate changes, unjailing, metadata
+                        // updates and consensus key changes of this validator
+                        // must be signed
+                        Some(validator) => validator != &addr || *valid_sig,
+                        // Any other PoS changes are allowed without signature
+                        None => true,
+                    }
+                }
+            };
+            log_string(format!(
+                "PoS key {} {}",
+                key,
+                if valid { "accepted" } else { "rejected" }
+            ));
+            valid
+        } else if let Some(owner) = key::multisig::is_keys_key(key) {
+            if owner == &addr {
+                // The new key set must be valid and signed by the old one
+                let valid_keys = match key::multisig::get_post(&addr) {
+                    Some(keys) => match keys.validate() {
+                        Ok(()) => true,
+                        Err(err) => {
+                            log_string(format!(
+                                "Invalid multi-signature key set: {}",
+                                err
+                            ));
+                            false
+                        }
+                    },
+                    None => {
+                        log_string(
+                            "The multi-signature key set cannot be deleted"
+                                .to_string(),
+                        );
+                        false
+                    }
+                };
+                valid_keys && *valid_sig
+            } else {
+                // This is not the owner, the owner's VP must check it
+                true
+            }
+        } else {
+            log_string(format!(
+                "Unknown key modified, valid sig {}",
+                *valid_sig
+            ));
+            // Allow any other key change if authorized by a signature
+            *valid_sig
+        };
+        if !is_valid {
+            log_string(format!("key {} modification failed vp", key));
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    // Use this as `#[test]` annotation to enable logging
+    use anoma_tests::log::test;
+    use anoma_tests::tx::{tx_host_env, TestTxEnv};
+    use anoma_tests::vp::*;
+    use key::ed25519::{attach_tx_sigs, sign_tx_partial, Keypair};
+    use key::multisig::ThresholdKeys;
+
+    use super::*;
+
+    /// Create a 2-of-3 key set.
+    fn two_of_three() -> (Vec<Keypair>, ThresholdKeys) {
+        let keypairs: Vec<Keypair> = (0..3)
+            .map(|_| key::ed25519::testing::gen_keypair())
+            .collect();
+        let keys = ThresholdKeys::new(
+            2,
+            keypairs.iter().map(|kp| kp.public.clone()).collect(),
+        )
+        .unwrap();
+        (keypairs, keys)
+    }
+
+    /// Initialize a VP environment with a debit transfer from a multi-signature
+    /// account.
+    fn init_debit_transfer(
+        vp_owner: &Address,
+        keys: &ThresholdKeys,
+    ) -> TestVpEnv {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let target = address::testing::established_address_2();
+        let token = address::xan();
+        let amount = token::Amount::from(10_098_123);
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([vp_owner, &target, &token]);
+
+        // Credit the tokens to the VP owner before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(vp_owner, &token, amount);
+
+        tx_env.write_multisig_keys(vp_owner, keys);
+
+        // Initialize VP environment from a transaction
+        init_vp_env_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(address, &target, &token, amount);
+        })
+    }
+
+    /// Sign the VP environment's tx with the keypairs at the given indices
+    /// and return the signed tx data.
+    fn sign(
+        vp_env: &mut TestVpEnv,
+        keypairs: &[Keypair],
+        indices: &[u8],
+    ) -> Vec<u8> {
+        let tx = vp_env.tx.clone();
+        let sigs: BTreeMap<u8, key::ed25519::Signature> = indices
+            .iter()
+            .map(|ix| (*ix, sign_tx_partial(&keypairs[*ix as usize], &tx)))
+            .collect();
+        let signed_tx = attach_tx_sigs(tx, sigs);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        tx_data
+    }
+
+    /// Test that no-op transaction (i.e. no storage modifications) accepted.
+    #[test]
+    fn test_no_op_transaction() {
+        let mut env = TestVpEnv::default();
+        init_vp_env(&mut env);
+
+        let tx_data: Vec<u8> = vec![];
+        let addr: Address = env.addr;
+        let keys_changed: HashSet<storage::Key> = HashSet::default();
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        assert!(validate_tx(tx_data, addr, keys_changed, verifiers));
+    }
+
+    /// Test that a debit transfer without any signatures is rejected.
+    #[test]
+    fn test_unsigned_debit_transfer_rejected() {
+        let vp_owner = address::testing::established_address_1();
+        let (_keypairs, keys) = two_of_three();
+        let vp_env = init_debit_transfer(&vp_owner, &keys);
+
+        let tx_data: Vec<u8> = vec![];
+        let keys_changed: HashSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: HashSet<Address> = HashSet::default();
+        assert!(!validate_tx(tx_data, vp_owner, keys_changed, verifiers));
+    }
+
+    /// Test that a debit transfer with signatures below the threshold is
+    /// rejected.
+    #[test]
+    fn test_below_threshold_debit_transfer_rejected() {
+        let vp_owner = address::testing::established_address_1();
+        let (keypairs, keys) = two_of_three();
+        let mut vp_env = init_debit_transfer(&vp_owner, &keys);
+
+        let tx_data = sign(&mut vp_env, &keypairs, &[1]);
+        let keys_changed: HashSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: HashSet<Address> = HashSet::default();
+        assert!(!validate_tx(tx_data, vp_owner, keys_changed, verifiers));
+    }
+
+    /// Test that a debit transfer with a threshold of signatures is accepted.
+    #[test]
+    fn test_threshold_debit_transfer_accepted() {
+        let vp_owner = address::testing::established_address_1();
+        let (keypairs, keys) = two_of_three();
+        let mut vp_env = init_debit_transfer(&vp_owner, &keys);
+
+        let tx_data = sign(&mut vp_env, &keypairs, &[0, 2]);
+        let keys_changed: HashSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: HashSet<Address> = HashSet::default();
+        assert!(validate_tx(tx_data, vp_owner, keys_changed, verifiers));
+    }
+
+    /// Test that the key set can be replaced with a valid key set with a
+    /// threshold of signatures, but not with an invalid one.
+    #[test]
+    fn test_key_set_update() {
+        let vp_owner = address::testing::established_address_1();
+        let (keypairs, keys) = two_of_three();
+
+        for (threshold, expected) in [(3, true), (4, false)] {
+            // Initialize a tx environment
+            let mut tx_env = TestTxEnv::default();
+            tx_env.spawn_accounts([&vp_owner]);
+            tx_env.write_multisig_keys(&vp_owner, &keys);
+
+            // Raise the threshold
+            let new_keys = ThresholdKeys {
+                threshold,
+                public_keys: keys.public_keys.clone(),
+            };
+            let mut vp_env =
+                init_vp_env_from_tx(vp_owner.clone(), tx_env, |address| {
+                    let keys_key = key::multisig::keys_key(address);
+                    tx_host_env::write(keys_key.to_string(), &new_keys);
+                });
+
+            let tx_data = sign(&mut vp_env, &keypairs, &[0, 1]);
+            let keys_changed: HashSet<storage::Key> =
+                vp_env.all_touched_storage_keys();
+            let verifiers: HashSet<Address> = HashSet::default();
+            assert_eq!(
+                validate_tx(tx_data, vp_owner.clone(), keys_changed, verifiers),
+                expected
+            );
+        }
+    }
+}
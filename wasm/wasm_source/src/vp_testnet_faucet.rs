@@ -31,9 +31,9 @@ fn validate_tx(
     let valid_sig = Lazy::new(|| match &*signed_tx_data {
         Ok(signed_tx_data) => {
             let pk = key::ed25519::get(&addr);
-            match pk {
-                Some(pk) => verify_tx_signature(&pk, &signed_tx_data.sig),
-                None => false,
+            match (pk, signed_tx_data.sig()) {
+                (Some(pk), Some(sig)) => verify_tx_signature(&pk, sig),
+                _ => false,
             }
         }
         _ => false,
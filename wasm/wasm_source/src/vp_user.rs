@@ -10,12 +10,25 @@
 //! haven't already been fulfilled (fulfilled intents are added to the owner's
 //! invalid intent set).
 //!
+//! The account's public key can be updated with a valid signature of the old
+//! key. The public key cannot be deleted.
+//!
+//! The account may have a spending policy (see
+//! [`spending_policy::SpendingPolicy`]). When it does, the outflow of tokens is
+//! limited per epoch and tokens can only be sent to the allowed targets (the
+//! PoS system is always allowed), unless the tx is co-signed by the policy's
+//! guardian key. If the policy has a guardian key, the guardian's co-signature
+//! is also required to change the policy, the public key or the VP.
+//!
 //! Any other storage key changes are allowed only with a valid signature.
 
 use anoma_vm_env::vp_prelude::intent::{
     Exchange, FungibleTokenIntent, IntentTransfers,
 };
 use anoma_vm_env::vp_prelude::key::ed25519::{Signed, SignedTxData};
+use anoma_vm_env::vp_prelude::spending_policy::{
+    SpendingPolicy, GUARDIAN_SIG_INDEX,
+};
 use anoma_vm_env::vp_prelude::*;
 use once_cell::unsync::Lazy;
 use rust_decimal::prelude::*;
@@ -38,9 +51,9 @@ fn validate_tx(
     let valid_sig = Lazy::new(|| match &*signed_tx_data {
         Ok(signed_tx_data) => {
             let pk = key::ed25519::get(&addr);
-            match pk {
-                Some(pk) => verify_tx_signature(&pk, &signed_tx_data.sig),
-                None => false,
+            match (pk, signed_tx_data.sig()) {
+                (Some(pk), Some(sig)) => verify_tx_signature(&pk, sig),
+                _ => false,
             }
         }
         _ => false,
@@ -51,16 +64,52 @@ fn validate_tx(
         _ => false,
     });
 
+    let policy = Lazy::new(|| spending_policy::get(&addr));
+
+    let valid_guardian_sig = Lazy::new(|| match (&*signed_tx_data, &*policy) {
+        (
+            Ok(signed_tx_data),
+            Some(SpendingPolicy {
+                guardian: Some(guardian),
+                ..
+            }),
+        ) => match signed_tx_data.sigs.get(&GUARDIAN_SIG_INDEX) {
+            Some(sig) => verify_tx_signature(guardian, sig),
+            None => false,
+        },
+        _ => false,
+    });
+
+    // If the policy has a guardian, its co-signature is required to change the
+    // account's keys, VP and the policy itself
+    let guardian_approved = Lazy::new(|| match &*policy {
+        Some(SpendingPolicy {
+            guardian: Some(_), ..
+        }) => *valid_guardian_sig,
+        _ => true,
+    });
+
     for key in keys_changed.iter() {
-        let is_valid = if let Some(owner) = token::is_any_token_balance_key(key)
+        let is_valid = if let Some((token, owner)) =
+            token::is_any_balance_key(key)
         {
             if owner == &addr {
                 let key = key.to_string();
                 let pre: token::Amount = read_pre(&key).unwrap_or_default();
                 let post: token::Amount = read_post(&key).unwrap_or_default();
                 let change = post.change() - pre.change();
-                // debit has to signed, credit doesn't
-                let valid = change >= 0 || *valid_sig || *valid_intent;
+                // debit has to signed and satisfy the spending policy, if any,
+                // credit doesn't
+                let valid = change >= 0
+                    || ((*valid_sig || *valid_intent)
+                        && (*valid_guardian_sig
+                            || check_spending_policy(
+                                &addr,
+                                &*policy,
+                                token,
+                                -change,
+                                &keys_changed,
+                            )));
                 log_string(format!(
                     "token key: {}, change: {}, valid_sig: {}, valid_intent: \
                      {}, valid modification: {}",
@@ -78,16 +127,34 @@ fn validate_tx(
         } else if proof_of_stake::is_pos_key(key) {
             // Allow the account to be used in PoS
             let bond_id = proof_of_stake::is_bond_key(key)
-                .or_else(|| proof_of_stake::is_unbond_key(key));
+                .or_else(|| proof_of_stake::is_unbond_key(key))
+                .or_else(|| proof_of_stake::is_redelegation_key(key))
+                .or_else(|| proof_of_stake::is_withdrawal_key(key))
+                .or_else(|| proof_of_stake::is_bond_rewards_key(key));
             let valid = match bond_id {
                 Some(bond_id) => {
-                    // Bonds and unbonds changes for this address
-                    // must be signed
+                    // Bonds, unbonds, redelegations, withdrawals and rewards
+                    // changes for this address must be signed
                     bond_id.source != addr || *valid_sig
                 }
                 None => {
-                    // Any other PoS changes are allowed without signature
-                    true
+                    match proof_of_stake::is_validator_commission_rate_key(key)
+                        .or_else(|| {
+                            proof_of_stake::is_validator_jailed_epoch_key(key)
+                        })
+                        .or_else(|| {
+                            proof_of_stake::is_validator_metadata_key(key)
+                        })
+                        .or_else(|| {
+                            proof_of_stake::is_validator_consensus_key_key(key)
+                        }) {
+                        // Commission rate changes, unjailing, metadata
+                        // updates and consensus key changes of this validator
+                        // must be signed
+                        Some(validator) => validator != &addr || *valid_sig,
+                        // Any other PoS changes are allowed without signature
+                        None => true,
+                    }
                 }
             };
             log_string(format!(
@@ -112,6 +179,56 @@ fn validate_tx(
                 // If this is not the owner, allow any change
                 true
             }
+        } else if let Some(owner) = key::ed25519::is_pk_key(key) {
+            if owner == &addr {
+                // The new key must be valid and the change signed by the old
+                // key
+                let key = key.to_string();
+                let post: Option<key::ed25519::PublicKey> = read_post(&key);
+                let valid = post.is_some() && *valid_sig && *guardian_approved;
+                log_string(format!(
+                    "public key update, new key: {:?}, valid sig: {}, valid \
+                     modification: {}",
+                    post, *valid_sig, valid
+                ));
+                valid
+            } else {
+                // This is not the owner, the owner's VP must check it
+                true
+            }
+        } else if let Some(owner) = spending_policy::is_policy_key(key) {
+            if owner == &addr {
+                // The new policy must be valid, if any
+                let key = key.to_string();
+                let post: Option<SpendingPolicy> = read_post(&key);
+                let valid_policy = !has_key_post(&key) || post.is_some();
+                let valid = valid_policy && *valid_sig && *guardian_approved;
+                log_string(format!(
+                    "spending policy update, valid policy: {}, valid sig: {}, \
+                     guardian approved: {}, valid modification: {}",
+                    valid_policy, *valid_sig, *guardian_approved, valid
+                ));
+                valid
+            } else {
+                // This is not the owner, the owner's VP must check it
+                true
+            }
+        } else if let Some((owner, token)) = spending_policy::is_spent_key(key)
+        {
+            if owner == &addr {
+                // The outflow must be recorded accurately, unless the tx is
+                // co-signed by the guardian
+                *valid_guardian_sig || check_spent_record(&addr, token)
+            } else {
+                // This is not the owner, the owner's VP must check it
+                true
+            }
+        } else if key.is_validity_predicate() == Some(&addr) {
+            log_string(format!(
+                "VP update, valid sig: {}, guardian approved: {}",
+                *valid_sig, *guardian_approved
+            ));
+            *valid_sig && *guardian_approved
         } else {
             log_string(format!(
                 "Unknown key modified, valid sig {}",
@@ -128,6 +245,96 @@ fn validate_tx(
     true
 }
 
+/// Check a debit of the given token against the spending policy, if any.
+fn check_spending_policy(
+    addr: &Address,
+    policy: &Option<SpendingPolicy>,
+    token: &Address,
+    debit: token::Change,
+    keys_changed: &HashSet<storage::Key>,
+) -> bool {
+    let policy = match policy {
+        Some(policy) => policy,
+        None => return true,
+    };
+    if let Some(limit) = policy.limit(token) {
+        // The outflow must be recorded in this tx
+        let spent_key = spending_policy::spent_key(addr, token);
+        if !keys_changed.contains(&spent_key) {
+            log_string(format!(
+                "The outflow of token {} is not recorded",
+                token
+            ));
+            return false;
+        }
+        match spending_policy::get_spent_post(addr, token) {
+            Some(spent) if spent.amount <= limit => {}
+            spent => {
+                log_string(format!(
+                    "The outflow of token {} {:?} exceeds the limit {}",
+                    token, spent, limit
+                ));
+                return false;
+            }
+        }
+    }
+    if !policy.allowed_targets.is_empty() {
+        // The debited amount must be credited to the allowed targets
+        let credited: token::Change = keys_changed
+            .iter()
+            .filter_map(|key| token::is_balance_key(token, key))
+            .filter(|owner| {
+                *owner != addr
+                    && (policy.is_allowed_target(owner)
+                        || **owner == proof_of_stake::ADDRESS)
+            })
+            .map(|owner| {
+                let key = token::balance_key(token, owner).to_string();
+                let pre: token::Amount = read_pre(&key).unwrap_or_default();
+                let post: token::Amount = read_post(&key).unwrap_or_default();
+                post.change() - pre.change()
+            })
+            .filter(|change| *change > 0)
+            .sum();
+        if credited < debit {
+            log_string(format!(
+                "Only {} of the debited {} of token {} is sent to the allowed \
+                 targets",
+                credited, debit, token
+            ));
+            return false;
+        }
+    }
+    true
+}
+
+/// Check that the recorded outflow of the given token matches the change of
+/// the balance.
+fn check_spent_record(addr: &Address, token: &Address) -> bool {
+    let key = token::balance_key(token, addr).to_string();
+    let pre: token::Amount = read_pre(&key).unwrap_or_default();
+    let post: token::Amount = read_post(&key).unwrap_or_default();
+    let debit = pre.checked_sub(&post).unwrap_or_default();
+    let expected = match spending_policy::Spent::record(
+        spending_policy::get_spent_pre(addr, token),
+        get_block_epoch(),
+        debit,
+    ) {
+        Ok(expected) => expected,
+        Err(err) => {
+            log_string(format!("token {} outflow: {}", token, err));
+            return false;
+        }
+    };
+    let post = spending_policy::get_spent_post(addr, token);
+    let valid = post == Some(expected);
+    log_string(format!(
+        "token {} outflow record: {:?}, expected: {:?}, valid: {}",
+        token, post, expected, valid
+    ));
+    valid
+}
+
 fn check_intent_transfers(
     addr: &Address,
     signed_tx_data: &SignedTxData,
@@ -221,14 +428,20 @@ fn check_intent(
     let sell_post: token::Amount =
         read_post(token_sell_key).unwrap_or_default();
 
-    sell_difference.spend(&sell_post);
+    if sell_difference.spend(&sell_post).is_err() {
+        log_string(format!("token {} was not sold", token_sell));
+        return false;
+    }
 
     let token_buy_key = token::balance_key(token_buy, addr).to_string();
     let buy_pre: token::Amount = read_pre(&token_buy_key).unwrap_or_default();
     let mut buy_difference: token::Amount =
         read_post(token_buy_key).unwrap_or_default();
 
-    buy_difference.spend(&buy_pre);
+    if buy_difference.spend(&buy_pre).is_err() {
+        log_string(format!("token {} was not bought", token_buy));
+        return false;
+    }
 
     let sell_diff: Decimal = sell_difference.change().into(); // -> how many token I sold
     let buy_diff: Decimal = buy_difference.change().into(); // -> how many token I got
@@ -586,4 +799,300 @@ mod tests {
         let verifiers: HashSet<Address> = HashSet::default();
         assert!(validate_tx(tx_data, vp_owner, keys_changed, verifiers));
     }
+
+    /// Test that a public key update is accepted only when it's signed by the
+    /// old key.
+    #[test]
+    fn test_key_update() {
+        let vp_owner = address::testing::established_address_1();
+        let old_keypair = key::ed25519::testing::keypair_1();
+        let new_keypair = key::ed25519::testing::keypair_2();
+
+        for (signer, expected) in [
+            (None, false),
+            (Some(&new_keypair), false),
+            (Some(&old_keypair), true),
+        ] {
+            // Initialize a tx environment
+            let mut tx_env = TestTxEnv::default();
+
+            // Spawn the accounts to be able to modify their storage
+            tx_env.spawn_accounts([&vp_owner]);
+
+            tx_env.write_public_key(&vp_owner, &old_keypair.public);
+
+            // Initialize VP environment from a transaction
+            let mut vp_env =
+                init_vp_env_from_tx(vp_owner.clone(), tx_env, |address| {
+                    // Update the public key in a transaction
+                    let pk_key = key::ed25519::pk_key(address);
+                    tx_host_env::write(pk_key.to_string(), &new_keypair.public);
+                });
+
+            let tx_data: Vec<u8> = match signer {
+                Some(keypair) => {
+                    let tx = vp_env.tx.clone();
+                    let signed_tx = key::ed25519::sign_tx(keypair, tx);
+                    let tx_data = signed_tx.data.as_ref().cloned().unwrap();
+                    vp_env.tx = signed_tx;
+                    tx_data
+                }
+                None => vec![],
+            };
+            let keys_changed: HashSet<storage::Key> =
+                vp_env.all_touched_storage_keys();
+            let verifiers: HashSet<Address> = HashSet::default();
+            assert_eq!(
+                validate_tx(tx_data, vp_owner.clone(), keys_changed, verifiers),
+                expected
+            );
+        }
+    }
+
+    /// Sign the VP environment's tx with the given keypairs at their signature
+    /// indices and return the signed tx data.
+    fn sign_with(
+        vp_env: &mut TestVpEnv,
+        signers: &[(u8, &key::ed25519::Keypair)],
+    ) -> Vec<u8> {
+        let tx = vp_env.tx.clone();
+        let sigs = signers
+            .iter()
+            .map(|(ix, keypair)| {
+                (*ix, key::ed25519::sign_tx_partial(keypair, &tx))
+            })
+            .collect();
+        let signed_tx = key::ed25519::attach_tx_sigs(tx, sigs);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        tx_data
+    }
+
+    /// Initialize a VP environment with a debit transfer from an account with
+    /// the given spending policy.
+    fn init_policy_transfer(
+        vp_owner: &Address,
+        public_key: &key::ed25519::PublicKey,
+        policy: &SpendingPolicy,
+        target: &Address,
+        amount: token::Amount,
+    ) -> TestVpEnv {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let token = address::xan();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([vp_owner, target, &token]);
+
+        // Credit the tokens to the VP owner before running the transaction to
+        // be able to transfer from it
+        tx_env.credit_tokens(vp_owner, &token, token::Amount::whole(1_000));
+
+        tx_env.write_public_key(vp_owner, public_key);
+        tx_env.write_spending_policy(vp_owner, policy);
+
+        // Initialize VP environment from a transaction
+        init_vp_env_from_tx(vp_owner.clone(), tx_env, |address| {
+            // Apply transfer in a transaction
+            tx_host_env::token::transfer(address, target, &token, amount);
+        })
+    }
+
+    /// Test that a debit transfer is accepted only within the spending limit,
+    /// unless it's co-signed by the guardian.
+    #[test]
+    fn test_spending_limit() {
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::ed25519::testing::keypair_1();
+        let guardian = key::ed25519::testing::keypair_2();
+        let target = address::testing::established_address_2();
+
+        let mut policy = SpendingPolicy {
+            guardian: Some(guardian.public.clone()),
+            ..Default::default()
+        };
+        policy
+            .limits
+            .insert(address::xan(), token::Amount::whole(100));
+
+        for (amount, with_guardian, expected) in [
+            (token::Amount::whole(60), false, true),
+            (token::Amount::whole(100), false, true),
+            (token::Amount::whole(150), false, false),
+            (token::Amount::whole(150), true, true),
+        ] {
+            let mut vp_env = init_policy_transfer(
+                &vp_owner,
+                &keypair.public,
+                &policy,
+                &target,
+                amount,
+            );
+            let tx_data = if with_guardian {
+                sign_with(
+                    &mut vp_env,
+                    &[(0, &keypair), (GUARDIAN_SIG_INDEX, &guardian)],
+                )
+            } else {
+                sign_with(&mut vp_env, &[(0, &keypair)])
+            };
+            let keys_changed: HashSet<storage::Key> =
+                vp_env.all_touched_storage_keys();
+            let verifiers: HashSet<Address> = HashSet::default();
+            assert_eq!(
+                validate_tx(tx_data, vp_owner.clone(), keys_changed, verifiers),
+                expected
+            );
+        }
+    }
+
+    /// Test that a debit of a limited token that is not recorded as an
+    /// outflow is rejected.
+    #[test]
+    fn test_unrecorded_outflow_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::ed25519::testing::keypair_1();
+        let token = address::xan();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner, &token]);
+        tx_env.credit_tokens(&vp_owner, &token, token::Amount::whole(1_000));
+        tx_env.write_public_key(&vp_owner, &keypair.public);
+        let mut policy = SpendingPolicy::default();
+        policy
+            .limits
+            .insert(token.clone(), token::Amount::whole(100));
+        tx_env.write_spending_policy(&vp_owner, &policy);
+
+        // Initialize VP environment from a transaction
+        let mut vp_env =
+            init_vp_env_from_tx(vp_owner.clone(), tx_env, |address| {
+                // Debit the balance directly, without the transfer function
+                let balance_key = token::balance_key(&token, address);
+                tx_host_env::write(
+                    balance_key.to_string(),
+                    token::Amount::whole(500),
+                );
+            });
+
+        let tx_data = sign_with(&mut vp_env, &[(0, &keypair)]);
+        let keys_changed: HashSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: HashSet<Address> = HashSet::default();
+        assert!(!validate_tx(tx_data, vp_owner, keys_changed, verifiers));
+    }
+
+    /// Test that tokens can only be sent to the allowed targets.
+    #[test]
+    fn test_allowed_targets() {
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::ed25519::testing::keypair_1();
+        let allowed = address::testing::established_address_2();
+        let other = address::testing::established_address_3();
+
+        let mut policy = SpendingPolicy::default();
+        policy.allowed_targets.insert(allowed.clone());
+
+        for (target, expected) in [(&allowed, true), (&other, false)] {
+            let mut vp_env = init_policy_transfer(
+                &vp_owner,
+                &keypair.public,
+                &policy,
+                target,
+                token::Amount::whole(10),
+            );
+            let tx_data = sign_with(&mut vp_env, &[(0, &keypair)]);
+            let keys_changed: HashSet<storage::Key> =
+                vp_env.all_touched_storage_keys();
+            let verifiers: HashSet<Address> = HashSet::default();
+            assert_eq!(
+                validate_tx(tx_data, vp_owner.clone(), keys_changed, verifiers),
+                expected
+            );
+        }
+    }
+
+    /// Test that the spending policy with a guardian can only be changed with
+    /// the guardian's co-signature.
+    #[test]
+    fn test_policy_update_requires_guardian() {
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::ed25519::testing::keypair_1();
+        let guardian = key::ed25519::testing::keypair_2();
+        let policy = SpendingPolicy {
+            guardian: Some(guardian.public.clone()),
+            ..Default::default()
+        };
+
+        for (with_guardian, expected) in [(false, false), (true, true)] {
+            // Initialize a tx environment
+            let mut tx_env = TestTxEnv::default();
+
+            // Spawn the accounts to be able to modify their storage
+            tx_env.spawn_accounts([&vp_owner]);
+            tx_env.write_public_key(&vp_owner, &keypair.public);
+            tx_env.write_spending_policy(&vp_owner, &policy);
+
+            // Initialize VP environment from a transaction
+            let mut vp_env =
+                init_vp_env_from_tx(vp_owner.clone(), tx_env, |address| {
+                    // Remove the policy in a transaction
+                    let policy_key = spending_policy::policy_key(address);
+                    tx_host_env::delete(policy_key.to_string());
+                });
+
+            let tx_data = if with_guardian {
+                sign_with(
+                    &mut vp_env,
+                    &[(0, &keypair), (GUARDIAN_SIG_INDEX, &guardian)],
+                )
+            } else {
+                sign_with(&mut vp_env, &[(0, &keypair)])
+            };
+            let keys_changed: HashSet<storage::Key> =
+                vp_env.all_touched_storage_keys();
+            let verifiers: HashSet<Address> = HashSet::default();
+            assert_eq!(
+                validate_tx(tx_data, vp_owner.clone(), keys_changed, verifiers),
+                expected
+            );
+        }
+    }
+
+    /// Test that the public key cannot be deleted, even with a valid
+    /// signature.
+    #[test]
+    fn test_signed_key_delete_rejected() {
+        // Initialize a tx environment
+        let mut tx_env = TestTxEnv::default();
+
+        let vp_owner = address::testing::established_address_1();
+        let keypair = key::ed25519::testing::keypair_1();
+
+        // Spawn the accounts to be able to modify their storage
+        tx_env.spawn_accounts([&vp_owner]);
+
+        tx_env.write_public_key(&vp_owner, &keypair.public);
+
+        // Initialize VP environment from a transaction
+        let mut vp_env =
+            init_vp_env_from_tx(vp_owner.clone(), tx_env, |address| {
+                // Delete the public key in a transaction
+                let pk_key = key::ed25519::pk_key(address);
+                tx_host_env::delete(pk_key.to_string());
+            });
+
+        let tx = vp_env.tx.clone();
+        let signed_tx = key::ed25519::sign_tx(&keypair, tx);
+        let tx_data: Vec<u8> = signed_tx.data.as_ref().cloned().unwrap();
+        vp_env.tx = signed_tx;
+        let keys_changed: HashSet<storage::Key> =
+            vp_env.all_touched_storage_keys();
+        let verifiers: HashSet<Address> = HashSet::default();
+        assert!(!validate_tx(tx_data, vp_owner, keys_changed, verifiers));
+    }
 }
@@ -59,7 +59,7 @@ pub mod main {
         let target_key = token::balance_key(&token, &target);
         let mut target_bal: token::Amount =
             read(&target_key.to_string()).unwrap_or_default();
-        target_bal.receive(&amount);
+        target_bal.receive(&amount).unwrap();
         write(&target_key.to_string(), target_bal);
     }
 }